@@ -0,0 +1,20 @@
+//! Build script
+//!
+//! With the `header` feature, generates `include/agi_core.h` from the FFI
+//! declarations with cbindgen, configured by `cbindgen.toml`.
+
+fn main() {
+    for path in ["build.rs", "cbindgen.toml", "src/lib.rs", "src/ffi.rs", "src/tensor_ffi.rs"] {
+        println!("cargo:rerun-if-changed={}", path);
+    }
+
+    #[cfg(feature = "header")]
+    {
+        let crate_dir = std::path::PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").expect("set by cargo"));
+        let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml")).expect("cbindgen.toml is readable");
+        std::fs::create_dir_all(crate_dir.join("include")).expect("include directory can be created");
+        cbindgen::generate_with_config(&crate_dir, config)
+            .expect("FFI declarations can be exported to C")
+            .write_to_file(crate_dir.join("include/agi_core.h"));
+    }
+}
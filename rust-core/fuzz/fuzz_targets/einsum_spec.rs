@@ -0,0 +1,59 @@
+//! Arbitrary subscript strings and label extents through `EinsumSpec::parse` and
+//! `einsum`: parsing may reject a spec, but nothing may panic.
+
+#![no_main]
+
+use agi_rust_core::tensor_ops::{einsum, EinsumSpec, Tensor};
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+
+/// Bound on the product of all label extents, which bounds contraction work
+const MAX_WORK: usize = 1 << 16;
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    spec: String,
+    /// Extent of each label, indexed by its position in the alphabet
+    extents: [u8; 52],
+    /// Drop or add an operand to exercise rank and arity checks
+    operand_delta: i8,
+}
+
+fuzz_target!(|input: Input| {
+    let Ok(spec) = EinsumSpec::parse(&input.spec) else {
+        return;
+    };
+
+    let extent = |label: char| {
+        let slot = if label.is_ascii_lowercase() { label as usize - 'a' as usize } else { 26 + label as usize - 'A' as usize };
+        usize::from(input.extents[slot] % 4)
+    };
+    let mut labels: Vec<char> = spec.inputs.iter().flatten().copied().collect();
+    labels.sort_unstable();
+    labels.dedup();
+    let work = labels.iter().try_fold(1usize, |work, &label| work.checked_mul(extent(label).max(1)));
+    if work.map_or(true, |work| work > MAX_WORK) {
+        return;
+    }
+
+    let mut tensors: Vec<Tensor> = spec.inputs.iter()
+        .map(|input| {
+            let shape: Vec<usize> = input.iter().map(|&label| extent(label)).collect();
+            let size = shape.iter().product();
+            Tensor::new(shape, (0..size).map(|x| x as f64).collect())
+        })
+        .collect();
+    match input.operand_delta.signum() {
+        -1 => {
+            tensors.pop();
+        }
+        1 => tensors.push(Tensor::new(vec![1], vec![1.0])),
+        _ => {}
+    }
+
+    let operands: Vec<&Tensor> = tensors.iter().collect();
+    if let Ok(result) = einsum(&input.spec, &operands) {
+        assert_eq!(result.shape.len(), spec.output.len());
+        assert_eq!(result.data.len(), result.shape.iter().product::<usize>());
+    }
+});
@@ -0,0 +1,54 @@
+//! Arbitrary shapes, strides, offsets and index ranges through `TensorView` and
+//! the owned indexing ops: invalid layouts must be rejected, never read out of
+//! bounds or panic.
+
+#![no_main]
+
+use std::ops::Range;
+
+use agi_rust_core::tensor_ops::{Tensor, TensorView};
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    len: u8,
+    offset: u16,
+    shape: Vec<u8>,
+    strides: Vec<u8>,
+    ranges: Vec<(u8, u8)>,
+    axes: Vec<u8>,
+    indices: Vec<u8>,
+}
+
+fuzz_target!(|input: Input| {
+    let data: Vec<f64> = (0..input.len).map(f64::from).collect();
+    let shape: Vec<usize> = input.shape.iter().take(6).map(|&d| usize::from(d % 5)).collect();
+    let strides: Vec<usize> = input.strides.iter().take(6).map(|&s| usize::from(s)).collect();
+    let ranges: Vec<Range<usize>> = input.ranges.iter().map(|&(start, end)| usize::from(start)..usize::from(end)).collect();
+    let axes: Vec<usize> = input.axes.iter().map(|&a| usize::from(a)).collect();
+    let indices: Vec<usize> = input.indices.iter().map(|&i| usize::from(i)).collect();
+
+    if let Ok(view) = TensorView::with_strides(&data, usize::from(input.offset), &shape, &strides) {
+        let tensor = view.to_tensor();
+        assert_eq!(tensor.shape, view.shape());
+        let _ = view.slice(&ranges).map(|v| v.to_tensor());
+        let _ = view.permute(&axes).map(|v| v.to_tensor());
+        let _ = view.broadcast_to(&[2, 3]).map(|v| v.to_tensor());
+        let coordinates: Vec<usize> = indices.iter().take(shape.len()).copied().collect();
+        let _ = view.get(&coordinates);
+    }
+
+    let size = shape.iter().product::<usize>();
+    if size <= data.len() {
+        let tensor = Tensor::new(shape.clone(), data[..size].to_vec());
+        let _ = tensor.slice(&ranges);
+        let _ = tensor.permute(&axes);
+        let _ = tensor.reshape(&indices);
+        if let Some(&axis) = axes.first() {
+            let _ = tensor.index_select(axis, &indices);
+            let _ = tensor.squeeze(Some(axis));
+            let _ = tensor.unsqueeze(axis);
+        }
+    }
+});
@@ -2,23 +2,123 @@
 //! 
 //! This module provides consciousness simulation capabilities for the AGI system.
 
+pub mod agents;
+pub mod appraisal;
+pub mod attention;
+pub mod complexity;
+pub mod config;
+pub mod consolidation;
+pub mod emotion;
+pub mod global_workspace;
+pub mod hooks;
+pub mod introspection;
+pub mod metacognition;
+pub mod phi;
+pub mod snapshot;
+pub mod timeline;
+pub mod working_memory;
+
+use std::collections::{BTreeMap, VecDeque};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use ndarray::Array1;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rand_distr::{Distribution, Normal};
 use serde::{Deserialize, Serialize};
 use tracing::info;
 
+use crate::error::AgiError;
+use crate::memory_manager::{blackboard, Blackboard};
+use crate::util::now_ms;
+
+pub use agents::{AgentMessage, AgentRegistry, InteractionParams};
+pub use appraisal::{EmbeddingEmotionModel, Embedder, EmotionModel, LexicalEmotionModel};
+pub use attention::AttentionGate;
+pub use complexity::{ComplexityAnalyzer, CompressionComplexity, DispersionComplexity, EntropyComplexity};
+pub use config::{ConsciousnessConfig, DimensionDynamics, Saturation};
+pub use consolidation::{ConsolidationOptions, ConsolidationReport};
+pub use emotion::{EmotionVector, InputFeatures};
+pub use global_workspace::{Broadcast, Candidate, ContentSource, Goal, GlobalWorkspace};
+pub use hooks::{ConsciousnessEvent, EventCallback, HookId, Hooks};
+pub use introspection::{Cause, ChangeDriver, DimensionChange, SelfReport, TrajectorySummary};
+pub use metacognition::{CalibrationBin, FeedbackOutcome, MetaCognition};
+pub use snapshot::{DimensionDiff, Snapshot, SnapshotId, StateDiff};
+pub use timeline::{Timeline, TimelineEntry, TimelineEvent, TimelineStart};
+pub use working_memory::{Context, WorkingMemory, WorkingMemoryItem};
+
+/// Version of the binary state format written by `ConsciousnessEngine::save`
+pub const CONSCIOUSNESS_FORMAT_VERSION: u32 = 7;
+
+/// Default number of states kept in the evolution history before it is compacted
+pub const DEFAULT_HISTORY_CAPACITY: usize = 1024;
+
+/// Number of most recent steps the awareness trend is measured over
+const TREND_WINDOW: usize = 16;
+
+/// Number of most recent steps Φ is estimated over
+const PHI_WINDOW: usize = 64;
+
+/// Number of most recent causes of change kept for introspection
+const DRIVER_WINDOW: usize = 16;
+
+/// Number of most recent inputs kept for consolidation replay
+const REPLAY_BUFFER: usize = 64;
+
+/// Fraction of the way self-awareness moves toward calibration per feedback
+const FEEDBACK_RATE: f64 = 0.05;
+
+/// Largest per-dimension difference tolerated between a replayed and a recorded state
+const REPLAY_TOLERANCE: f64 = 1e-9;
+
+/// Names of the entries of `ConsciousnessState::dimensions`, in order
+pub const DIMENSION_NAMES: [&str; 8] = [
+    "awareness_level",
+    "self_awareness",
+    "memory_coherence",
+    "attention_focus",
+    "creativity_level",
+    "valence",
+    "arousal",
+    "dominance",
+];
+
 /// Consciousness state representation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConsciousnessState {
     pub awareness_level: f64,
     pub self_awareness: f64,
+    /// Nearest discrete label of `emotion`
     pub emotional_state: EmotionalState,
+    pub emotion: EmotionVector,
     pub memory_coherence: f64,
     pub attention_focus: f64,
     pub creativity_level: f64,
 }
 
-/// Emotional state representation
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl ConsciousnessState {
+    /// The continuous dimensions, named by `DIMENSION_NAMES`
+    pub fn dimensions(&self) -> Vec<f64> {
+        vec![
+            self.awareness_level,
+            self.self_awareness,
+            self.memory_coherence,
+            self.attention_focus,
+            self.creativity_level,
+            self.emotion.valence,
+            self.emotion.arousal,
+            self.emotion.dominance,
+        ]
+    }
+}
+
+/// Discrete emotional state, derived from the continuous `EmotionVector`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum EmotionalState {
+    #[default]
     Neutral,
     Curious,
     Excited,
@@ -27,112 +127,830 @@ pub enum EmotionalState {
     Analytical,
 }
 
-impl Default for EmotionalState {
-    fn default() -> Self {
-        Self::Neutral
-    }
-}
-
 /// Consciousness engine
+///
+/// Every call to `evolve` advances `current_state` and appends it to the evolution
+/// history. Once the history exceeds its capacity, its older half is thinned to
+/// every other state, so long sessions keep a coarse record of their whole
+/// trajectory and a full record of recent steps in bounded memory.
+///
+/// Between inputs, `tick` lets time pass: dimensions with an idle rate relax
+/// toward their baselines, and after `idle_after` seconds without input the
+/// engine reports itself idle.
+///
+/// Inputs are appraised into emotion by an `EmotionModel`, the lexical heuristic
+/// unless `with_emotion_model` installs another. The model is not saved; a loaded
+/// engine starts with the lexical one. Likewise input complexity, which drives
+/// awareness, is scored by a `ComplexityAnalyzer`, by default `EntropyComplexity`.
+///
+/// The engine owns the `GlobalWorkspace`; `broadcast` runs a workspace cycle and
+/// moves attention focus toward the strength of the winning coalition. Attention
+/// focus in turn gates neural input through the engine's `AttentionGate`.
+///
+/// Inputs and broadcast winners are held in a `WorkingMemory`, which `evolve`
+/// consults so that familiar inputs stir awareness less than novel ones and the
+/// recent mood colours each new appraisal.
+///
+/// Confidences issued through `predict_confidence` are tracked by `MetaCognition`;
+/// `record_feedback` resolves them and moves self-awareness toward how well
+/// calibrated they have proven.
+///
+/// `snapshot` and `restore` branch the engine's state for comparing alternative
+/// input sequences; snapshots are kept in memory and not saved.
+///
+/// While a `Timeline` is being recorded, every state-changing operation is logged
+/// so the trajectory can be exported and replayed.
 pub struct ConsciousnessEngine {
     current_state: ConsciousnessState,
     evolution_history: Vec<ConsciousnessState>,
+    config: ConsciousnessConfig,
+    /// Evolution steps taken, including those compacted out of the history
+    total_evolutions: u64,
+    /// Time passed through `tick` since the last input
+    idle_time: Duration,
+    emotion_model: Box<dyn EmotionModel>,
+    complexity_analyzer: Box<dyn ComplexityAnalyzer>,
+    workspace: GlobalWorkspace,
+    working_memory: WorkingMemory,
+    /// Recent causes of change, oldest first
+    drivers: VecDeque<ChangeDriver>,
+    attention_gate: AttentionGate,
+    hooks: Hooks,
+    /// Most recent inputs, oldest first, for consolidation replay
+    recent_inputs: VecDeque<String>,
+    metacognition: MetaCognition,
+    timeline: Option<Timeline>,
+    snapshots: BTreeMap<SnapshotId, Snapshot>,
+    next_snapshot: SnapshotId,
+    rng: StdRng,
+    /// Shared working memory each new state is published to
+    blackboard: Option<Arc<Blackboard>>,
 }
 
 impl ConsciousnessEngine {
-    /// Create a new consciousness engine
-    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
+    /// Create a new consciousness engine with the default dynamics
+    pub fn new() -> Result<Self, AgiError> {
+        Self::with_config(ConsciousnessConfig::default())
+    }
+
+    /// Create an engine starting from and evolving under `config`
+    pub fn with_config(config: ConsciousnessConfig) -> Result<Self, AgiError> {
+        config.validate().map_err(AgiError::Config)?;
         let initial_state = ConsciousnessState {
-            awareness_level: 0.1,
-            self_awareness: 0.05,
+            awareness_level: config.awareness.initial,
+            self_awareness: config.self_awareness.initial,
             emotional_state: EmotionalState::Neutral,
-            memory_coherence: 0.8,
-            attention_focus: 0.6,
-            creativity_level: 0.3,
+            emotion: EmotionVector::NEUTRAL,
+            memory_coherence: config.memory_coherence.initial,
+            attention_focus: config.attention_focus.initial,
+            creativity_level: config.creativity.initial,
         };
+        Ok(Self::from_parts(initial_state.clone(), vec![initial_state], config, 0))
+    }
 
-        Ok(Self {
-            current_state: initial_state.clone(),
-            evolution_history: vec![initial_state.clone()],
-        })
+    fn from_parts(
+        current_state: ConsciousnessState,
+        evolution_history: Vec<ConsciousnessState>,
+        config: ConsciousnessConfig,
+        total_evolutions: u64,
+    ) -> Self {
+        let rng = match config.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        let mut engine = Self {
+            current_state,
+            evolution_history,
+            config,
+            total_evolutions,
+            idle_time: Duration::ZERO,
+            emotion_model: Box::new(LexicalEmotionModel),
+            complexity_analyzer: Box::new(EntropyComplexity),
+            workspace: GlobalWorkspace::default(),
+            working_memory: WorkingMemory::default(),
+            drivers: VecDeque::with_capacity(DRIVER_WINDOW),
+            attention_gate: AttentionGate::default(),
+            hooks: Hooks::default(),
+            recent_inputs: VecDeque::with_capacity(REPLAY_BUFFER),
+            metacognition: MetaCognition::default(),
+            timeline: None,
+            snapshots: BTreeMap::new(),
+            next_snapshot: 0,
+            rng,
+            blackboard: None,
+        };
+        engine.compact_history();
+        engine
+    }
+
+    /// Keep at most `capacity` states (at least 2) in the evolution history
+    pub fn with_history_capacity(mut self, capacity: usize) -> Self {
+        self.config.history_capacity = capacity;
+        self.compact_history();
+        self
+    }
+
+    /// Publish each new state to `blackboard`
+    pub fn with_blackboard(mut self, blackboard: Arc<Blackboard>) -> Self {
+        self.set_blackboard(Some(blackboard));
+        self
+    }
+
+    /// Publish each new state to `blackboard`, or stop publishing with `None`
+    pub fn set_blackboard(&mut self, blackboard: Option<Arc<Blackboard>>) {
+        self.blackboard = blackboard;
+        self.publish();
+    }
+
+    pub fn blackboard(&self) -> Option<&Arc<Blackboard>> {
+        self.blackboard.as_ref()
+    }
+
+    /// Appraise inputs with `model` instead of the lexical heuristic
+    pub fn with_emotion_model(mut self, model: Box<dyn EmotionModel>) -> Self {
+        self.emotion_model = model;
+        self
+    }
+
+    /// Emotion model in use
+    pub fn emotion_model(&self) -> &dyn EmotionModel {
+        self.emotion_model.as_ref()
+    }
+
+    /// Score input complexity with `analyzer` instead of the entropy measure
+    pub fn with_complexity_analyzer(mut self, analyzer: Box<dyn ComplexityAnalyzer>) -> Self {
+        self.complexity_analyzer = analyzer;
+        self
+    }
+
+    /// Complexity analyzer in use, e.g. to score tensor inputs
+    pub fn complexity_analyzer(&self) -> &dyn ComplexityAnalyzer {
+        self.complexity_analyzer.as_ref()
+    }
+
+    /// Global workspace whose broadcasts drive attention
+    pub fn workspace(&self) -> &GlobalWorkspace {
+        &self.workspace
+    }
+
+    /// Global workspace, e.g. to set goals
+    pub fn workspace_mut(&mut self) -> &mut GlobalWorkspace {
+        &mut self.workspace
+    }
+
+    /// Short-term context consulted by `evolve`
+    pub fn working_memory(&self) -> &WorkingMemory {
+        &self.working_memory
+    }
+
+    /// Short-term context, e.g. to hold external percepts
+    pub fn working_memory_mut(&mut self) -> &mut WorkingMemory {
+        &mut self.working_memory
+    }
+
+    /// Run a workspace cycle over `candidates` and the standing goals
+    ///
+    /// Attention focus moves halfway toward the broadcast strength, in the current
+    /// state and in its history entry, and the leading winner enters working memory.
+    pub fn broadcast(&mut self, input: &str, candidates: Vec<Candidate>) -> Broadcast {
+        let logged = self.timeline.as_ref().map(|_| candidates.clone());
+        let broadcast = self.workspace.compete(input, candidates).clone();
+        let before = self.current_state.clone();
+        let focus = (self.current_state.attention_focus + broadcast.strength) / 2.0;
+        self.current_state.attention_focus = focus;
+        if let Some(latest) = self.evolution_history.last_mut() {
+            latest.attention_focus = focus;
+        }
+        if let Some(leader) = broadcast.winners.first() {
+            self.working_memory.push(&leader.label, leader.salience, EmotionVector::NEUTRAL);
+        }
+        let winner = broadcast.winners.first().map(|candidate| candidate.label.clone());
+        self.transition(Cause::Broadcast { winner, strength: broadcast.strength }, &before);
+        if let Some(candidates) = logged {
+            self.log(|| TimelineEvent::Broadcast { input: input.to_string(), candidates });
+        }
+        broadcast
+    }
+
+    /// Gate neural input through `gate` instead of the default
+    pub fn with_attention_gate(mut self, gate: AttentionGate) -> Self {
+        self.attention_gate = gate;
+        self
+    }
+
+    pub fn attention_gate(&self) -> &AttentionGate {
+        &self.attention_gate
+    }
+
+    /// Mask over a neural input vector at the current attention focus
+    pub fn attention_mask(&self, input: &Array1<f64>) -> Array1<f64> {
+        self.attention_gate.mask(input, self.current_state.attention_focus)
+    }
+
+    /// Neural input vector gated at the current attention focus
+    pub fn attend(&self, input: &Array1<f64>) -> Array1<f64> {
+        self.attention_gate.apply(input, self.current_state.attention_focus)
+    }
+
+    /// Callbacks on state transitions; not saved with the engine
+    pub fn hooks_mut(&mut self) -> &mut Hooks {
+        &mut self.hooks
+    }
+
+    /// Calibration of issued confidences against reported outcomes
+    pub fn metacognition(&self) -> &MetaCognition {
+        &self.metacognition
+    }
+
+    /// Calibrated confidence from activation strength, pattern confidence and
+    /// coherence, awaiting an outcome through `record_feedback`
+    pub fn predict_confidence(&mut self, signals: [f64; 3]) -> f64 {
+        let confidence = self.metacognition.predict(signals);
+        self.log(|| TimelineEvent::Prediction { signals });
+        confidence
+    }
+
+    /// Resolve the oldest pending confidence with its outcome
+    ///
+    /// Self-awareness moves part of the way toward one minus the expected
+    /// calibration error, rising as confidence proves to match accuracy.
+    pub fn record_feedback(&mut self, correct: bool) -> Result<FeedbackOutcome, AgiError> {
+        let outcome = self.metacognition.record_feedback(correct).map_err(AgiError::Consciousness)?;
+        let before = self.current_state.clone();
+        let target = 1.0 - outcome.calibration_error;
+        let self_awareness = &mut self.current_state.self_awareness;
+        *self_awareness = (*self_awareness + FEEDBACK_RATE * (target - *self_awareness)).clamp(0.0, 1.0);
+        if let Some(latest) = self.evolution_history.last_mut() {
+            *latest = self.current_state.clone();
+        }
+        self.transition(Cause::Feedback { correct, calibration_error: outcome.calibration_error }, &before);
+        self.log(|| TimelineEvent::Feedback { correct });
+        Ok(outcome)
+    }
+
+    /// Evolution dynamics in use
+    pub fn config(&self) -> &ConsciousnessConfig {
+        &self.config
+    }
+
+    /// Current consciousness state
+    pub fn current_state(&self) -> &ConsciousnessState {
+        &self.current_state
+    }
+
+    /// Recorded states, oldest first, starting with the initial state
+    pub fn history(&self) -> &[ConsciousnessState] {
+        &self.evolution_history
+    }
+
+    /// Save the current state and evolution history to a file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), AgiError> {
+        let mut writer = BufWriter::new(std::fs::File::create(path.as_ref())?);
+        let saved = SavedConsciousnessRef {
+            format_version: CONSCIOUSNESS_FORMAT_VERSION,
+            current_state: &self.current_state,
+            evolution_history: &self.evolution_history,
+            config: &self.config,
+            total_evolutions: self.total_evolutions,
+            workspace: &self.workspace,
+            working_memory: &self.working_memory,
+            metacognition: &self.metacognition,
+        };
+        bincode::serialize_into(&mut writer, &saved)?;
+        writer.flush()?;
+        info!("Consciousness state saved to {}", path.as_ref().display());
+        Ok(())
+    }
+
+    /// Restore an engine previously written by `save`
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, AgiError> {
+        let reader = BufReader::new(std::fs::File::open(path.as_ref())?);
+        let saved: SavedConsciousness = bincode::deserialize_from(reader)?;
+        if saved.format_version != CONSCIOUSNESS_FORMAT_VERSION {
+            return Err(AgiError::Serialization(format!(
+                "Unsupported consciousness format version {} (expected {})",
+                saved.format_version, CONSCIOUSNESS_FORMAT_VERSION
+            )));
+        }
+        if saved.evolution_history.is_empty() {
+            return Err(AgiError::Serialization("Saved consciousness history is empty".to_string()));
+        }
+        saved.config.validate().map_err(AgiError::Config)?;
+        
+        info!("Consciousness state loaded from {} after {} evolutions", path.as_ref().display(), saved.total_evolutions);
+        let mut engine = Self::from_parts(saved.current_state, saved.evolution_history, saved.config, saved.total_evolutions);
+        engine.workspace = saved.workspace;
+        engine.working_memory = saved.working_memory;
+        engine.metacognition = saved.metacognition;
+        Ok(engine)
+    }
+
+    /// Start recording a timeline from the current state, replacing any being recorded
+    ///
+    /// The noise generator is reseeded from a fresh seed kept in the timeline, so
+    /// the recording can be replayed exactly even when noise is on.
+    pub fn start_timeline(&mut self) {
+        let seed = self.rng.gen::<u64>();
+        self.rng = StdRng::seed_from_u64(seed);
+        self.timeline = Some(Timeline {
+            start: TimelineStart {
+                timestamp_ms: now_ms(),
+                seed,
+                total_evolutions: self.total_evolutions,
+                idle_time: self.idle_time,
+                state: self.current_state.clone(),
+                history: self.evolution_history.clone(),
+                config: self.config.clone(),
+                workspace: self.workspace.clone(),
+                working_memory: self.working_memory.clone(),
+                metacognition: self.metacognition.clone(),
+                recent_inputs: self.recent_inputs.iter().cloned().collect(),
+            },
+            entries: Vec::new(),
+        });
+    }
+
+    /// Timeline being recorded, if any
+    pub fn timeline(&self) -> Option<&Timeline> {
+        self.timeline.as_ref()
     }
 
-    /// Evolve consciousness based on input
-    pub async fn evolve(&self, input: &str) -> Result<ConsciousnessState, Box<dyn std::error::Error>> {
+    /// Stop recording and return the timeline
+    pub fn stop_timeline(&mut self) -> Option<Timeline> {
+        self.timeline.take()
+    }
+
+    /// Append the event built by `event` to the timeline being recorded, if any
+    fn log(&mut self, event: impl FnOnce() -> TimelineEvent) {
+        if let Some(timeline) = &mut self.timeline {
+            timeline.entries.push(TimelineEntry {
+                step: self.total_evolutions,
+                timestamp_ms: now_ms(),
+                event: event(),
+                state: self.current_state.clone(),
+            });
+        }
+    }
+
+    /// Store the engine's current state and return its id
+    pub fn snapshot(&mut self) -> SnapshotId {
+        self.next_snapshot += 1;
+        self.snapshots.insert(self.next_snapshot, Snapshot {
+            taken_at_ms: now_ms(),
+            state: self.current_state.clone(),
+            history: self.evolution_history.clone(),
+            config: self.config.clone(),
+            total_evolutions: self.total_evolutions,
+            idle_time: self.idle_time,
+            workspace: self.workspace.clone(),
+            working_memory: self.working_memory.clone(),
+            metacognition: self.metacognition.clone(),
+            drivers: self.drivers.clone(),
+            recent_inputs: self.recent_inputs.clone(),
+            rng: self.rng.clone(),
+        });
+        self.next_snapshot
+    }
+
+    /// Return to a stored snapshot, which remains stored
+    ///
+    /// Any timeline being recorded is discarded, since the trajectory jumps.
+    pub fn restore(&mut self, id: SnapshotId) -> Result<(), AgiError> {
+        let snapshot = self.snapshots.get(&id).ok_or_else(|| AgiError::Consciousness(format!("No snapshot with id {}", id)))?.clone();
+        self.current_state = snapshot.state;
+        self.evolution_history = snapshot.history;
+        self.config = snapshot.config;
+        self.total_evolutions = snapshot.total_evolutions;
+        self.idle_time = snapshot.idle_time;
+        self.workspace = snapshot.workspace;
+        self.working_memory = snapshot.working_memory;
+        self.metacognition = snapshot.metacognition;
+        self.drivers = snapshot.drivers;
+        self.recent_inputs = snapshot.recent_inputs;
+        self.rng = snapshot.rng;
+        self.timeline = None;
+        self.publish();
+        info!("Restored consciousness snapshot {} at evolution {}", id, self.total_evolutions);
+        Ok(())
+    }
+
+    /// Compare two stored snapshots dimension by dimension
+    pub fn diff(&self, from: SnapshotId, to: SnapshotId) -> Result<StateDiff, AgiError> {
+        let get = |id: SnapshotId| self.snapshots.get(&id).map(|snapshot| (id, snapshot)).ok_or_else(|| AgiError::Consciousness(format!("No snapshot with id {}", id)));
+        Ok(StateDiff::new(get(from)?, get(to)?))
+    }
+
+    /// A stored snapshot
+    pub fn get_snapshot(&self, id: SnapshotId) -> Option<&Snapshot> {
+        self.snapshots.get(&id)
+    }
+
+    /// Ids of the stored snapshots, oldest first
+    pub fn snapshot_ids(&self) -> Vec<SnapshotId> {
+        self.snapshots.keys().copied().collect()
+    }
+
+    /// Drop a stored snapshot; false if no snapshot has this id
+    pub fn remove_snapshot(&mut self, id: SnapshotId) -> bool {
+        self.snapshots.remove(&id).is_some()
+    }
+
+    /// Rebuild an engine by re-executing a recorded timeline from its start
+    ///
+    /// Inputs are appraised by the lexical model and scored by the entropy measure;
+    /// see `replay_with`.
+    pub async fn replay(timeline: &Timeline) -> Result<Self, AgiError> {
+        Self::replay_with(timeline, Box::new(LexicalEmotionModel), Box::new(EntropyComplexity)).await
+    }
+
+    /// Rebuild an engine by re-executing a recorded timeline, appraising inputs
+    /// with `model` and scoring them with `analyzer`
+    ///
+    /// Fails at the first entry whose replayed state differs from the recorded one,
+    /// as happens when the recording used another emotion model or complexity
+    /// analyzer, or the workspace goals were changed while recording.
+    pub async fn replay_with(
+        timeline: &Timeline,
+        model: Box<dyn EmotionModel>,
+        analyzer: Box<dyn ComplexityAnalyzer>,
+    ) -> Result<Self, AgiError> {
+        let start = &timeline.start;
+        start.config.validate().map_err(AgiError::Config)?;
+        if start.history.is_empty() {
+            return Err(AgiError::Serialization("Timeline start history is empty".to_string()));
+        }
+        let mut engine = Self::from_parts(start.state.clone(), start.history.clone(), start.config.clone(), start.total_evolutions)
+            .with_emotion_model(model)
+            .with_complexity_analyzer(analyzer);
+        engine.rng = StdRng::seed_from_u64(start.seed);
+        engine.idle_time = start.idle_time;
+        engine.workspace = start.workspace.clone();
+        engine.working_memory = start.working_memory.clone();
+        engine.metacognition = start.metacognition.clone();
+        engine.recent_inputs = start.recent_inputs.iter().cloned().collect();
+
+        for (index, entry) in timeline.entries.iter().enumerate() {
+            match &entry.event {
+                TimelineEvent::Input { text } => {
+                    engine.evolve(text).await?;
+                }
+                TimelineEvent::Idle { duration } => {
+                    engine.tick(*duration);
+                }
+                TimelineEvent::Broadcast { input, candidates } => {
+                    engine.broadcast(input, candidates.clone());
+                }
+                TimelineEvent::Interaction { from, other, params } => engine.absorb(from, other, params),
+                TimelineEvent::Prediction { signals } => {
+                    engine.predict_confidence(*signals);
+                }
+                TimelineEvent::Feedback { correct } => {
+                    engine.record_feedback(*correct)?;
+                }
+                TimelineEvent::Consolidation { options } => {
+                    engine.consolidate(options)?;
+                }
+            }
+            let divergence = engine.current_state.dimensions().iter().zip(entry.state.dimensions())
+                .map(|(replayed, recorded)| (replayed - recorded).abs())
+                .fold(0.0, f64::max);
+            if divergence > REPLAY_TOLERANCE || engine.total_evolutions != entry.step {
+                return Err(AgiError::Consciousness(format!(
+                    "Replay diverged at entry {} ({}): state differs by {:.3e}",
+                    index, entry.event.kind(), divergence
+                )));
+            }
+        }
+        info!("Replayed {} timeline entries", timeline.entries.len());
+        Ok(engine)
+    }
+
+    /// Evolve consciousness based on input, recording the new state
+    pub async fn evolve(&mut self, input: &str) -> Result<ConsciousnessState, AgiError> {
         info!("Evolving consciousness based on input: {} characters", input.len());
         
-        let mut new_state = self.current_state.clone();
+        let before = self.current_state.clone();
+        let mut new_state = before.clone();
         
-        // Evolve awareness based on input complexity
-        let input_complexity = self.analyze_input_complexity(input);
-        new_state.awareness_level = (new_state.awareness_level + input_complexity * 0.1).min(1.0);
+        // Evolve awareness based on input complexity, damped for inputs resembling the context
+        let input_complexity = self.complexity_analyzer.text_complexity(input)?.clamp(0.0, 1.0);
+        let context = self.working_memory.consult(input);
+        let drive = input_complexity * (0.5 + 0.5 * context.novelty);
+        new_state.awareness_level = self.config.awareness.evolve(new_state.awareness_level, drive);
         
         // Evolve self-awareness
-        new_state.self_awareness = (new_state.self_awareness + 0.01).min(1.0);
+        new_state.self_awareness = self.config.self_awareness.evolve(new_state.self_awareness, 1.0);
         
-        // Update emotional state based on input
-        new_state.emotional_state = self.determine_emotional_state(input);
+        // Move emotion toward the input's appraisal, coloured by the recent mood
+        let appraisal = self.emotion_model.appraise(input)?;
+        let target = match context.mood {
+            Some(mood) => appraisal.blend(&mood, self.working_memory.context_weight()),
+            None => appraisal,
+        };
+        new_state.emotion = new_state.emotion.blend(&target, self.config.emotion_responsiveness);
+        new_state.emotional_state = new_state.emotion.label();
         
         // Update memory coherence
-        new_state.memory_coherence = (new_state.memory_coherence + 0.02).min(1.0);
+        new_state.memory_coherence = self.config.memory_coherence.evolve(new_state.memory_coherence, 1.0);
         
         // Update attention focus
-        new_state.attention_focus = (new_state.attention_focus + 0.05).min(1.0);
+        new_state.attention_focus = self.config.attention_focus.evolve(new_state.attention_focus, 1.0);
         
         // Update creativity level
-        new_state.creativity_level = (new_state.creativity_level + 0.03).min(1.0);
+        new_state.creativity_level = self.config.creativity.evolve(new_state.creativity_level, 1.0);
+        
+        self.perturb(&mut new_state);
         
         info!("Consciousness evolved - Awareness: {:.2}, Self-awareness: {:.2}", 
               new_state.awareness_level, new_state.self_awareness);
         
+        self.record(new_state.clone());
+        if self.recent_inputs.len() == REPLAY_BUFFER {
+            self.recent_inputs.pop_front();
+        }
+        self.recent_inputs.push_back(input.to_string());
+        let salience = input_complexity.max(appraisal.distance(&EmotionVector::NEUTRAL));
+        self.working_memory.push(input, salience, appraisal);
+        self.transition(Cause::input(input, input_complexity, appraisal), &before);
+        self.log(|| TimelineEvent::Input { text: input.to_string() });
         Ok(new_state)
     }
 
-    /// Analyze input complexity
-    fn analyze_input_complexity(&self, input: &str) -> f64 {
-        let word_count = input.split_whitespace().count();
-        let char_count = input.chars().count();
-        let unique_chars = input.chars().collect::<std::collections::HashSet<_>>().len();
-        
-        let complexity = (word_count as f64 * 0.3 + 
-                         char_count as f64 * 0.1 + 
-                         unique_chars as f64 * 0.2) / 100.0;
-        
-        complexity.min(1.0)
+    /// Add the configured Gaussian noise to every evolved dimension
+    fn perturb(&mut self, state: &mut ConsciousnessState) {
+        if self.config.noise == 0.0 {
+            return;
+        }
+        let normal = Normal::new(0.0, self.config.noise).expect("noise is validated as finite and non-negative");
+        for value in [
+            &mut state.awareness_level,
+            &mut state.self_awareness,
+            &mut state.memory_coherence,
+            &mut state.attention_focus,
+            &mut state.creativity_level,
+        ] {
+            *value = (*value + normal.sample(&mut self.rng)).clamp(0.0, 1.0);
+        }
     }
 
-    /// Determine emotional state based on input
-    fn determine_emotional_state(&self, input: &str) -> EmotionalState {
-        let input_lower = input.to_lowercase();
-        
-        if input_lower.contains("creative") || input_lower.contains("imagine") {
-            EmotionalState::Creative
-        } else if input_lower.contains("analyze") || input_lower.contains("explain") {
-            EmotionalState::Analytical
-        } else if input_lower.contains("wonder") || input_lower.contains("curious") {
-            EmotionalState::Curious
-        } else if input_lower.contains("exciting") || input_lower.contains("amazing") {
-            EmotionalState::Excited
-        } else if input_lower.contains("think") || input_lower.contains("consider") {
-            EmotionalState::Contemplative
-        } else {
-            EmotionalState::Neutral
+    /// Let `dt` pass without input, relaxing each dimension toward its baseline
+    ///
+    /// The relaxed state replaces the current one but is not added to the history.
+    pub fn tick(&mut self, dt: Duration) -> &ConsciousnessState {
+        let before = self.current_state.clone();
+        let seconds = dt.as_secs_f64();
+        let config = &self.config;
+        let state = &mut self.current_state;
+        state.awareness_level = config.awareness.relax(state.awareness_level, seconds);
+        state.self_awareness = config.self_awareness.relax(state.self_awareness, seconds);
+        state.memory_coherence = config.memory_coherence.relax(state.memory_coherence, seconds);
+        state.attention_focus = config.attention_focus.relax(state.attention_focus, seconds);
+        state.creativity_level = config.creativity.relax(state.creativity_level, seconds);
+        state.emotion = state.emotion.decay(config.emotion_idle_rate, seconds);
+        state.emotional_state = state.emotion.label();
+        self.idle_time = self.idle_time.saturating_add(dt);
+        self.transition(Cause::Idle { seconds }, &before);
+        self.log(|| TimelineEvent::Idle { duration: dt });
+        &self.current_state
+    }
+
+    /// Move part of the way toward another agent's state, as set by `params`
+    ///
+    /// Emotion moves by `contagion`; awareness, attention and creativity by
+    /// `empathy`. The change applies to the current state and its history entry.
+    pub fn absorb(&mut self, from: &str, other: &ConsciousnessState, params: &InteractionParams) {
+        let before = self.current_state.clone();
+        let state = &mut self.current_state;
+        let toward = |own: f64, theirs: f64| own + params.empathy * (theirs - own);
+        state.awareness_level = toward(state.awareness_level, other.awareness_level);
+        state.attention_focus = toward(state.attention_focus, other.attention_focus);
+        state.creativity_level = toward(state.creativity_level, other.creativity_level);
+        state.emotion = state.emotion.blend(&other.emotion, params.contagion);
+        state.emotional_state = state.emotion.label();
+        if let Some(latest) = self.evolution_history.last_mut() {
+            *latest = self.current_state.clone();
+        }
+        self.transition(Cause::Interaction { from: from.to_string() }, &before);
+        self.log(|| TimelineEvent::Interaction { from: from.to_string(), other: other.clone(), params: *params });
+    }
+
+    /// Log `cause` as having moved the state from `before` to the current state,
+    /// notify the hooks and publish it to the blackboard
+    ///
+    /// Consecutive idle stretches are merged into one log entry.
+    fn transition(&mut self, cause: Cause, before: &ConsciousnessState) {
+        self.publish();
+        self.hooks.fire(before, &self.current_state, matches!(cause, Cause::Input { .. }));
+        let driver = ChangeDriver::new(self.total_evolutions, cause, before, &self.current_state);
+        if let Some(last) = self.drivers.back_mut() {
+            if last.absorb_idle(&driver) {
+                return;
+            }
+        }
+        if self.drivers.len() == DRIVER_WINDOW {
+            self.drivers.pop_front();
+        }
+        self.drivers.push_back(driver);
+    }
+
+    /// Report the current state, what recently changed it, and where it is heading
+    pub fn introspect(&self) -> SelfReport {
+        SelfReport {
+            state: self.current_state.clone(),
+            drivers: self.drivers.iter().cloned().collect(),
+            attention_target: self.workspace.last_broadcast().winners.first().cloned(),
+            dominant_emotion: self.current_state.emotion.label(),
+            emotion_intensity: self.current_state.emotion.distance(&EmotionVector::NEUTRAL),
+            trajectory: TrajectorySummary {
+                evolutions: self.total_evolutions,
+                awareness_trend: self.awareness_trend(),
+                peak_awareness: self.peak_awareness(),
+                phi: self.phi_estimate(),
+                idle: self.is_idle(),
+                idle_seconds: self.idle_time.as_secs_f64(),
+            },
+        }
+    }
+
+    /// Time passed through `tick` since the last input
+    pub fn idle_time(&self) -> Duration {
+        self.idle_time
+    }
+
+    /// Whether no input has arrived for at least the configured `idle_after`
+    pub fn is_idle(&self) -> bool {
+        self.idle_time.as_secs_f64() >= self.config.idle_after
+    }
+
+    /// Make `state` current and append it to the history
+    fn record(&mut self, state: ConsciousnessState) {
+        self.idle_time = Duration::ZERO;
+        self.current_state = state.clone();
+        self.evolution_history.push(state);
+        self.total_evolutions += 1;
+        self.compact_history();
+    }
+
+    /// Post the current state to the blackboard, if the engine has one
+    fn publish(&self) {
+        if let Some(board) = &self.blackboard {
+            board.put(blackboard::CONSCIOUSNESS_STATE, self.current_state.dimensions(), "consciousness_engine");
+            board.put(blackboard::CONSCIOUSNESS_EMOTION, format!("{:?}", self.current_state.emotional_state), "consciousness_engine");
         }
     }
 
+    /// Thin the older half of the history to every other state while it is over capacity
+    ///
+    /// The initial state is always kept.
+    fn compact_history(&mut self) {
+        self.compact_to(self.config.history_capacity);
+    }
+
+    /// Thin the history as `compact_history` does until it holds at most `capacity` (at least 2) states
+    fn compact_to(&mut self, capacity: usize) {
+        let capacity = capacity.max(2);
+        while self.evolution_history.len() > capacity {
+            // At least the second state falls in the thinned range, so each pass shrinks;
+            // thinning stops once the history is down to `capacity`
+            let mut excess = self.evolution_history.len() - capacity;
+            let older = (self.evolution_history.len() / 2).max(3);
+            let mut position = 0;
+            self.evolution_history.retain(|_| {
+                let keep = excess == 0 || position == 0 || position >= older || position % 2 == 0;
+                if !keep {
+                    excess -= 1;
+                }
+                position += 1;
+                keep
+            });
+        }
+    }
+
+    /// Bytes held by the evolution history
+    pub fn history_bytes(&self) -> usize {
+        self.evolution_history.len() * std::mem::size_of::<ConsciousnessState>()
+    }
+
+    /// Thin the history as compaction does to release about `bytes`, returning the bytes released
+    pub fn shed_history(&mut self, bytes: usize) -> usize {
+        let state_size = std::mem::size_of::<ConsciousnessState>();
+        let before = self.evolution_history.len();
+        self.compact_to(before.saturating_sub(bytes.div_ceil(state_size)));
+        (before - self.evolution_history.len()) * state_size
+    }
+
+    /// Integrated information Φ of the state's dimensions over recent history
+    ///
+    /// Estimated from the step-to-step changes of each dimension rather than their
+    /// levels, so shared upward drift does not count as integration; see `phi`.
+    pub fn phi_estimate(&self) -> f64 {
+        let recent = &self.evolution_history[self.evolution_history.len().saturating_sub(PHI_WINDOW + 1)..];
+        let changes: Vec<Vec<f64>> = recent.windows(2).map(|pair| {
+            pair[1].dimensions().iter().zip(pair[0].dimensions()).map(|(next, previous)| next - previous).collect()
+        }).collect();
+        phi::phi_estimate(&changes).unwrap_or(0.0)
+    }
+
     /// Get consciousness statistics
-    pub async fn get_stats(&self) -> Result<ConsciousnessStats, Box<dyn std::error::Error>> {
+    pub async fn get_stats(&self) -> Result<ConsciousnessStats, AgiError> {
+        let history = &self.evolution_history;
         Ok(ConsciousnessStats {
             current_awareness: self.current_state.awareness_level,
-            evolution_stages: self.evolution_history.len(),
-            average_awareness: self.evolution_history.iter()
-                .map(|state| state.awareness_level)
-                .sum::<f64>() / self.evolution_history.len() as f64,
+            evolution_stages: self.total_evolutions as usize + 1,
+            average_awareness: history.iter().map(|state| state.awareness_level).sum::<f64>() / history.len() as f64,
+            peak_awareness: self.peak_awareness(),
+            awareness_trend: self.awareness_trend(),
+            recorded_states: history.len(),
+            idle: self.is_idle(),
+            phi: self.phi_estimate(),
+            calibration_error: self.metacognition.calibration_error(),
+        })
+    }
+
+    /// Highest recorded awareness
+    fn peak_awareness(&self) -> f64 {
+        self.evolution_history.iter().map(|state| state.awareness_level).fold(f64::NEG_INFINITY, f64::max)
+    }
+
+    /// Mean change in awareness per step over the most recent steps
+    fn awareness_trend(&self) -> f64 {
+        let recent = &self.evolution_history[self.evolution_history.len().saturating_sub(TREND_WINDOW + 1)..];
+        match recent {
+            [first, .., last] => (last.awareness_level - first.awareness_level) / (recent.len() - 1) as f64,
+            _ => 0.0,
+        }
+    }
+
+    /// Run an offline consolidation pass; see `consolidation`
+    ///
+    /// Fails when `require_idle` is set and the engine is not idle. Fine-tuning is
+    /// left to the caller, which receives the replayed inputs in the report.
+    pub fn consolidate(&mut self, options: &ConsolidationOptions) -> Result<ConsolidationReport, AgiError> {
+        if options.require_idle && !self.is_idle() {
+            return Err(AgiError::Consciousness("Consolidation requires the engine to be idle".to_string()));
+        }
+        if !(0.0..=1.0).contains(&options.recalibration) {
+            return Err(AgiError::Config(format!("Recalibration {} must lie in [0, 1]", options.recalibration)));
+        }
+        let start_time = std::time::Instant::now();
+        let states_before = self.evolution_history.len();
+
+        // Replay the most recent inputs through the emotion model
+        let replayed: Vec<String> = self.recent_inputs.iter().skip(self.recent_inputs.len().saturating_sub(options.replay)).cloned().collect();
+        let appraisals = replayed.iter().map(|input| self.emotion_model.appraise(input)).collect::<Result<Vec<_>, _>>()?;
+        let replayed_emotion = appraisals.iter().fold(EmotionVector::NEUTRAL, |sum, appraisal| EmotionVector {
+            valence: sum.valence + appraisal.valence / appraisals.len() as f64,
+            arousal: sum.arousal + appraisal.arousal / appraisals.len() as f64,
+            dominance: sum.dominance + appraisal.dominance / appraisals.len() as f64,
+        });
+
+        // Move each baseline toward the mean of the states the replay window covers
+        let window = &self.evolution_history[self.evolution_history.len().saturating_sub(options.replay.max(1))..];
+        let mean = |level: fn(&ConsciousnessState) -> f64| window.iter().map(level).sum::<f64>() / window.len() as f64;
+        let targets = [
+            mean(|state| state.awareness_level),
+            mean(|state| state.self_awareness),
+            mean(|state| state.memory_coherence),
+            mean(|state| state.attention_focus),
+            mean(|state| state.creativity_level),
+        ];
+        let config = &mut self.config;
+        let dynamics = [
+            &mut config.awareness,
+            &mut config.self_awareness,
+            &mut config.memory_coherence,
+            &mut config.attention_focus,
+            &mut config.creativity,
+        ];
+        let mut baseline_shifts = Vec::new();
+        for ((dynamics, target), name) in dynamics.into_iter().zip(targets).zip(DIMENSION_NAMES) {
+            let shift = options.recalibration * (target - dynamics.baseline);
+            dynamics.baseline = (dynamics.baseline + shift).clamp(0.0, 1.0);
+            if shift.abs() > 1e-12 {
+                baseline_shifts.push(DimensionChange { dimension: name.to_string(), delta: shift });
+            }
+        }
+        baseline_shifts.sort_by(|a, b| b.delta.abs().total_cmp(&a.delta.abs()));
+
+        if let Some(length) = options.compress_to {
+            self.compact_to(length);
+        }
+
+        self.log(|| TimelineEvent::Consolidation { options: options.clone() });
+        info!("Consolidated {} replayed inputs, history {} -> {} states", replayed.len(), states_before, self.evolution_history.len());
+        Ok(ConsolidationReport {
+            replayed,
+            replayed_emotion,
+            states_before,
+            states_after: self.evolution_history.len(),
+            baseline_shifts,
+            fine_tune_loss: None,
+            consolidation_time: start_time.elapsed(),
         })
     }
 
     /// Optimize consciousness engine
-    pub async fn optimize(&self) -> Result<OptimizationResult, Box<dyn std::error::Error>> {
+    pub async fn optimize(&self) -> Result<OptimizationResult, AgiError> {
         info!("Starting consciousness engine optimization");
         
         let start_time = std::time::Instant::now();
@@ -155,12 +973,51 @@ impl ConsciousnessEngine {
     }
 }
 
+/// Borrowed form of the saved engine state
+#[derive(Serialize)]
+struct SavedConsciousnessRef<'a> {
+    format_version: u32,
+    current_state: &'a ConsciousnessState,
+    evolution_history: &'a [ConsciousnessState],
+    config: &'a ConsciousnessConfig,
+    total_evolutions: u64,
+    workspace: &'a GlobalWorkspace,
+    working_memory: &'a WorkingMemory,
+    metacognition: &'a MetaCognition,
+}
+
+/// Engine state as written by `ConsciousnessEngine::save`
+#[derive(Deserialize)]
+struct SavedConsciousness {
+    format_version: u32,
+    current_state: ConsciousnessState,
+    evolution_history: Vec<ConsciousnessState>,
+    config: ConsciousnessConfig,
+    total_evolutions: u64,
+    workspace: GlobalWorkspace,
+    working_memory: WorkingMemory,
+    metacognition: MetaCognition,
+}
+
 /// Consciousness statistics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConsciousnessStats {
     pub current_awareness: f64,
+    /// States the engine has passed through, including the initial one
     pub evolution_stages: usize,
+    /// Mean awareness over the recorded history
     pub average_awareness: f64,
+    pub peak_awareness: f64,
+    /// Mean change in awareness per step over the most recent steps
+    pub awareness_trend: f64,
+    /// States currently held in the (possibly compacted) history
+    pub recorded_states: usize,
+    /// Whether the engine has gone without input for its idle threshold
+    pub idle: bool,
+    /// Integrated information over recent history, in nats
+    pub phi: f64,
+    /// Expected calibration error of confidences with reported outcomes
+    pub calibration_error: f64,
 }
 
 /// Consciousness optimization result
@@ -170,3 +1027,311 @@ pub struct OptimizationResult {
     pub self_awareness_improvement: f64,
     pub optimization_time: std::time::Duration,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_evolve_persists_state() {
+        let mut engine = ConsciousnessEngine::new().unwrap();
+        let first = engine.evolve("Imagine a creative new world").await.unwrap();
+        let second = engine.evolve("Imagine a creative new world").await.unwrap();
+        assert!(second.awareness_level > first.awareness_level);
+        assert!(second.self_awareness > first.self_awareness);
+        assert_eq!(engine.current_state().awareness_level, second.awareness_level);
+        assert_eq!(engine.history().len(), 3);
+
+        let stats = engine.get_stats().await.unwrap();
+        assert_eq!(stats.evolution_stages, 3);
+        assert_eq!(stats.peak_awareness, second.awareness_level);
+        assert!(stats.awareness_trend > 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_history_is_compacted() {
+        let mut engine = ConsciousnessEngine::new().unwrap().with_history_capacity(8);
+        for step in 0..50 {
+            engine.evolve(&format!("step {}", step)).await.unwrap();
+        }
+        let stats = engine.get_stats().await.unwrap();
+        assert!(engine.history().len() <= 8);
+        assert_eq!(stats.recorded_states, engine.history().len());
+        assert_eq!(stats.evolution_stages, 51);
+        // The initial state survives compaction and the latest state is last
+        assert_eq!(engine.history()[0].awareness_level, 0.1);
+        assert_eq!(engine.history().last().unwrap().awareness_level, stats.current_awareness);
+    }
+
+    #[tokio::test]
+    async fn test_save_and_load_restore_trajectory() {
+        let mut engine = ConsciousnessEngine::new().unwrap();
+        engine.evolve("Let us analyze and explain this").await.unwrap();
+        engine.evolve("I wonder what comes next").await.unwrap();
+
+        let path = std::env::temp_dir().join(format!("consciousness_{}.bin", std::process::id()));
+        engine.save(&path).unwrap();
+        let mut restored = ConsciousnessEngine::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(restored.current_state().awareness_level, engine.current_state().awareness_level);
+        assert_eq!(restored.history().len(), engine.history().len());
+        assert_eq!(restored.get_stats().await.unwrap().evolution_stages, 3);
+
+        // Evolution continues from the restored state rather than the initial one
+        let next = restored.evolve("I wonder what comes next").await.unwrap();
+        assert!(next.awareness_level > engine.current_state().awareness_level);
+    }
+
+    #[tokio::test]
+    async fn test_config_shapes_evolution() {
+        let config = ConsciousnessConfig {
+            creativity: DimensionDynamics { growth: 0.0, decay: 0.5, baseline: 0.1, ..DimensionDynamics::new(0.9, 0.0) },
+            noise: 0.05,
+            seed: Some(42),
+            ..ConsciousnessConfig::default()
+        };
+        let mut first = ConsciousnessEngine::with_config(config.clone()).unwrap();
+        let mut second = ConsciousnessEngine::with_config(config).unwrap();
+        assert_eq!(first.current_state().creativity_level, 0.9);
+        for _ in 0..5 {
+            let a = first.evolve("same input").await.unwrap();
+            let b = second.evolve("same input").await.unwrap();
+            // Seeded noise replays exactly
+            assert_eq!(a.awareness_level, b.awareness_level);
+            assert_eq!(a.creativity_level, b.creativity_level);
+        }
+        assert!(first.current_state().creativity_level < 0.4);
+
+        let invalid = ConsciousnessConfig { noise: f64::NAN, ..ConsciousnessConfig::default() };
+        assert!(ConsciousnessEngine::with_config(invalid).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tick_relaxes_toward_baselines() {
+        let mut engine = ConsciousnessEngine::new().unwrap();
+        for _ in 0..20 {
+            engine.evolve("Imagine a creative new world, and wonder why").await.unwrap();
+        }
+        let active = engine.current_state().clone();
+        assert!(!engine.is_idle());
+
+        let relaxed = engine.tick(Duration::from_secs(60)).clone();
+        assert!(relaxed.awareness_level < active.awareness_level);
+        assert!(relaxed.creativity_level < active.creativity_level);
+        assert!(relaxed.awareness_level > 0.1);
+        // Dimensions without an idle rate hold their level
+        assert_eq!(relaxed.self_awareness, active.self_awareness);
+        assert!(engine.is_idle());
+        assert!(engine.get_stats().await.unwrap().idle);
+
+        engine.tick(Duration::from_secs(3600));
+        assert!((engine.current_state().attention_focus - 0.6).abs() < 1e-6);
+        engine.evolve("hello").await.unwrap();
+        assert_eq!(engine.idle_time(), Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_emotion_follows_input_and_fades() {
+        let mut engine = ConsciousnessEngine::new().unwrap();
+        for _ in 0..4 {
+            engine.evolve("This is amazing! I love it! Wonderful!").await.unwrap();
+        }
+        let state = engine.current_state().clone();
+        assert!(state.emotion.valence > 0.5 && state.emotion.arousal > 0.5);
+        assert!(matches!(state.emotional_state, EmotionalState::Excited));
+
+        engine.tick(Duration::from_secs(600));
+        assert!(engine.current_state().emotion.distance(&EmotionVector::NEUTRAL) < 0.01);
+        assert!(matches!(engine.current_state().emotional_state, EmotionalState::Neutral));
+    }
+
+    #[tokio::test]
+    async fn test_custom_emotion_model() {
+        struct Calm;
+        impl EmotionModel for Calm {
+            fn appraise(&self, _input: &str) -> Result<EmotionVector, AgiError> {
+                Ok(EmotionVector::prototype(&EmotionalState::Contemplative))
+            }
+
+            fn name(&self) -> &'static str {
+                "calm"
+            }
+        }
+
+        let mut engine = ConsciousnessEngine::new().unwrap().with_emotion_model(Box::new(Calm));
+        assert_eq!(engine.emotion_model().name(), "calm");
+        for _ in 0..3 {
+            engine.evolve("This is AMAZING!!!").await.unwrap();
+        }
+        assert!(matches!(engine.current_state().emotional_state, EmotionalState::Contemplative));
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_sets_attention() {
+        let mut engine = ConsciousnessEngine::new().unwrap();
+        engine.workspace_mut().add_goal(Goal::new("understand attention", 1.0));
+        let before = engine.evolve("what is attention").await.unwrap().attention_focus;
+
+        let broadcast = engine.broadcast("what is attention", vec![Candidate::memory("weather", 0.1)]);
+        assert_eq!(broadcast.winners[0].source, ContentSource::Goal);
+        let focus = engine.current_state().attention_focus;
+        assert!((focus - (before + broadcast.strength) / 2.0).abs() < 1e-12);
+        assert_eq!(engine.history().last().unwrap().attention_focus, focus);
+    }
+
+    #[tokio::test]
+    async fn test_phi_reflects_coupled_dynamics() {
+        let mut engine = ConsciousnessEngine::new().unwrap();
+        assert_eq!(engine.phi_estimate(), 0.0);
+        let inputs = ["Wow! This is amazing!", "why did it fail?", "a long and considered explanation of the problem", "ok"];
+        for step in 0..40 {
+            engine.evolve(inputs[step % inputs.len()]).await.unwrap();
+        }
+        let phi = engine.get_stats().await.unwrap().phi;
+        assert!(phi.is_finite() && phi >= 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_introspection_explains_changes() {
+        let mut engine = ConsciousnessEngine::new().unwrap();
+        engine.workspace_mut().add_goal(Goal::new("plan the trip", 0.8));
+        engine.evolve("This is amazing! Let us plan the trip!").await.unwrap();
+        engine.broadcast("plan the trip", Vec::new());
+        engine.tick(Duration::from_secs(20));
+        engine.tick(Duration::from_secs(20));
+
+        let report = engine.introspect();
+        assert_eq!(report.drivers.len(), 3);
+        assert!(matches!(report.drivers[0].cause, Cause::Input { .. }));
+        assert_eq!(report.drivers[2].cause, Cause::Idle { seconds: 40.0 });
+        assert_eq!(report.attention_target.as_ref().unwrap().label, "plan the trip");
+        assert!(report.emotion_intensity > 0.0);
+        assert!(report.trajectory.idle && report.trajectory.evolutions == 1);
+        assert!(report.summary().contains("40s idle"));
+        assert!(report.to_json().unwrap().contains("\"drivers\""));
+    }
+
+    #[tokio::test]
+    async fn test_hooks_observe_evolution() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let evolved = Arc::new(AtomicUsize::new(0));
+        let crossings = Arc::new(AtomicUsize::new(0));
+        let mut engine = ConsciousnessEngine::new().unwrap();
+        let counter = evolved.clone();
+        engine.hooks_mut().on_state_evolved(Box::new(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }));
+        let counter = crossings.clone();
+        engine.hooks_mut().on_threshold_crossed("creativity_level", 0.35, Box::new(move |event| {
+            assert!(matches!(event, ConsciousnessEvent::ThresholdCrossed { .. }));
+            counter.fetch_add(1, Ordering::SeqCst);
+        })).unwrap();
+
+        engine.evolve("one").await.unwrap();
+        engine.evolve("two").await.unwrap();
+        engine.tick(Duration::from_secs(600));
+        assert_eq!(evolved.load(Ordering::SeqCst), 2);
+        // Rises past 0.35 on the second step, then relaxes back below it
+        assert_eq!(crossings.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_consolidation_recalibrates_and_compresses() {
+        let mut engine = ConsciousnessEngine::new().unwrap();
+        for step in 0..30 {
+            engine.evolve(&format!("Wonderful step {}!", step)).await.unwrap();
+        }
+        let options = ConsolidationOptions { replay: 10, compress_to: Some(8), ..ConsolidationOptions::default() };
+        assert!(engine.consolidate(&options).is_err());
+
+        engine.tick(Duration::from_secs(60));
+        let report = engine.consolidate(&options).unwrap();
+        assert_eq!(report.replayed.len(), 10);
+        assert_eq!(report.replayed.last().unwrap(), "Wonderful step 29!");
+        assert!(report.replayed_emotion.valence > 0.0);
+        assert_eq!((report.states_before, report.states_after), (31, 8));
+        // Creativity has risen well above its starting level, so its baseline follows
+        assert!(engine.config().creativity.baseline > 0.3);
+        assert!(report.baseline_shifts.iter().any(|shift| shift.dimension == "creativity_level" && shift.delta > 0.0));
+    }
+
+    #[tokio::test]
+    async fn test_working_memory_damps_familiar_input() {
+        let mut engine = ConsciousnessEngine::new().unwrap();
+        let first = engine.evolve("the reactor temperature is rising").await.unwrap().awareness_level;
+        let repeated = engine.evolve("the reactor temperature is rising").await.unwrap().awareness_level;
+        let novel = engine.evolve("gardens bloom quietly after spring rain").await.unwrap().awareness_level;
+        assert!(repeated - first < first - 0.1);
+        assert!(novel - repeated > repeated - first);
+        assert_eq!(engine.working_memory().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_snapshots_branch_and_compare() {
+        let config = ConsciousnessConfig { noise: 0.02, seed: Some(9), ..ConsciousnessConfig::default() };
+        let mut engine = ConsciousnessEngine::with_config(config).unwrap();
+        engine.evolve("start here").await.unwrap();
+        let origin = engine.snapshot();
+
+        for _ in 0..3 {
+            engine.evolve("Amazing! I love this!").await.unwrap();
+        }
+        let joyful = engine.snapshot();
+        engine.restore(origin).unwrap();
+        assert_eq!(engine.get_stats().await.unwrap().evolution_stages, 2);
+        for _ in 0..3 {
+            engine.evolve("This is awful and sad.").await.unwrap();
+        }
+        let gloomy = engine.snapshot();
+
+        let diff = engine.diff(joyful, gloomy).unwrap();
+        assert_eq!(diff.dimensions.len(), DIMENSION_NAMES.len());
+        assert_eq!(diff.evolutions, (4, 4));
+        let valence = diff.dimensions.iter().find(|dimension| dimension.dimension == "valence").unwrap();
+        assert!(valence.delta < 0.0);
+        assert!(diff.distance > 0.0 && !diff.changed(1e-9).is_empty());
+
+        // Restoring replays the same noise, so a repeated branch ends identically
+        engine.restore(origin).unwrap();
+        for _ in 0..3 {
+            engine.evolve("Amazing! I love this!").await.unwrap();
+        }
+        let again = engine.snapshot();
+        assert_eq!(engine.diff(joyful, again).unwrap().distance, 0.0);
+        assert!(engine.restore(99).is_err());
+        assert_eq!(engine.snapshot_ids(), vec![origin, joyful, gloomy, again]);
+    }
+
+    #[tokio::test]
+    async fn test_timeline_replays_exactly() {
+        let config = ConsciousnessConfig { noise: 0.05, seed: Some(3), ..ConsciousnessConfig::default() };
+        let mut engine = ConsciousnessEngine::with_config(config).unwrap();
+        engine.evolve("before recording").await.unwrap();
+        engine.start_timeline();
+        engine.evolve("What a wonderful idea!").await.unwrap();
+        engine.broadcast("idea", vec![Candidate::memory("notes", 0.7)]);
+        engine.predict_confidence([0.9, 0.6, 0.5]);
+        engine.record_feedback(false).unwrap();
+        engine.tick(Duration::from_secs(45));
+        engine.consolidate(&ConsolidationOptions::default()).unwrap();
+        engine.evolve("and again").await.unwrap();
+        let timeline = engine.stop_timeline().unwrap();
+        assert_eq!(timeline.entries.len(), 7);
+
+        let mut jsonl = Vec::new();
+        timeline.write_jsonl(&mut jsonl).unwrap();
+        let restored = Timeline::read_jsonl(jsonl.as_slice()).unwrap();
+        let replayed = ConsciousnessEngine::replay(&restored).await.unwrap();
+        for (replayed, recorded) in replayed.current_state().dimensions().iter().zip(engine.current_state().dimensions()) {
+            assert!((replayed - recorded).abs() < 1e-9);
+        }
+        assert_eq!(replayed.history().len(), engine.history().len());
+
+        let mut tampered = restored;
+        tampered.entries[0].state.awareness_level += 0.1;
+        assert!(ConsciousnessEngine::replay(&tampered).await.is_err());
+    }
+}
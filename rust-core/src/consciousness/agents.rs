@@ -0,0 +1,214 @@
+//! Multi-Agent Consciousness - Named streams that influence each other
+//!
+//! An `AgentRegistry` holds one `ConsciousnessEngine` per named agent. Agents
+//! interact by sending messages: the recipient evolves on the message text like
+//! any input, then absorbs part of the sender's state. Two parameters govern
+//! that influence, set per directed pair or falling back to a registry default:
+//!
+//! - `contagion` is the fraction of the way the recipient's emotion moves toward
+//!   the sender's, so moods spread through conversation;
+//! - `empathy` is the fraction of the way the recipient's awareness, attention
+//!   and creativity move toward the sender's, so engaged agents draw others in.
+//!
+//! `mingle` applies contagion ambiently, without messages, between every pair.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::AgiError;
+
+use super::{ConsciousnessEngine, ConsciousnessState, EmotionVector};
+
+/// Number of most recent messages kept in the log
+const MESSAGE_LOG: usize = 256;
+
+/// Strength of one agent's influence on another
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct InteractionParams {
+    /// Fraction of the emotional distance closed per interaction, in [0, 1]
+    pub contagion: f64,
+    /// Fraction of the awareness, attention and creativity distance closed, in [0, 1]
+    pub empathy: f64,
+}
+
+impl Default for InteractionParams {
+    fn default() -> Self {
+        Self { contagion: 0.2, empathy: 0.1 }
+    }
+}
+
+impl InteractionParams {
+    fn validate(&self) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&self.contagion) || !(0.0..=1.0).contains(&self.empathy) {
+            return Err(format!("Contagion {} and empathy {} must lie in [0, 1]", self.contagion, self.empathy));
+        }
+        Ok(())
+    }
+}
+
+/// A message delivered between agents
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentMessage {
+    pub from: String,
+    pub to: String,
+    pub content: String,
+}
+
+/// Named consciousness streams and their interactions
+#[derive(Default)]
+pub struct AgentRegistry {
+    agents: BTreeMap<String, ConsciousnessEngine>,
+    defaults: InteractionParams,
+    /// Overrides keyed by (sender, recipient)
+    pairs: HashMap<(String, String), InteractionParams>,
+    log: VecDeque<AgentMessage>,
+}
+
+impl AgentRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry whose pairs interact with `params` unless overridden
+    pub fn with_defaults(params: InteractionParams) -> Result<Self, String> {
+        params.validate()?;
+        Ok(Self { defaults: params, ..Self::default() })
+    }
+
+    /// Add an agent; fails if the name is taken
+    pub fn add_agent(&mut self, name: &str, engine: ConsciousnessEngine) -> Result<(), String> {
+        if self.agents.contains_key(name) {
+            return Err(format!("Agent {} already exists", name));
+        }
+        self.agents.insert(name.to_string(), engine);
+        Ok(())
+    }
+
+    /// Remove an agent and its pair overrides
+    pub fn remove_agent(&mut self, name: &str) -> Option<ConsciousnessEngine> {
+        self.pairs.retain(|(from, to), _| from != name && to != name);
+        self.agents.remove(name)
+    }
+
+    /// Agent names, sorted
+    pub fn names(&self) -> Vec<&str> {
+        self.agents.keys().map(String::as_str).collect()
+    }
+
+    pub fn agent(&self, name: &str) -> Option<&ConsciousnessEngine> {
+        self.agents.get(name)
+    }
+
+    pub fn agent_mut(&mut self, name: &str) -> Option<&mut ConsciousnessEngine> {
+        self.agents.get_mut(name)
+    }
+
+    /// Set how strongly `from` influences `to`
+    pub fn set_interaction(&mut self, from: &str, to: &str, params: InteractionParams) -> Result<(), String> {
+        params.validate()?;
+        self.pairs.insert((from.to_string(), to.to_string()), params);
+        Ok(())
+    }
+
+    /// Influence of `from` on `to`
+    pub fn interaction(&self, from: &str, to: &str) -> InteractionParams {
+        self.pairs.get(&(from.to_string(), to.to_string())).copied().unwrap_or(self.defaults)
+    }
+
+    /// Most recent messages, oldest first
+    pub fn messages(&self) -> impl Iterator<Item = &AgentMessage> {
+        self.log.iter()
+    }
+
+    /// Deliver `content` from one agent to another, returning the recipient's new state
+    pub async fn send(&mut self, from: &str, to: &str, content: &str) -> Result<ConsciousnessState, AgiError> {
+        if from == to {
+            return Err(AgiError::Consciousness("An agent cannot message itself".to_string()));
+        }
+        let sender = self.state_of(from).map_err(AgiError::Consciousness)?;
+        let params = self.interaction(from, to);
+        let recipient = self.agents.get_mut(to).ok_or_else(|| AgiError::Consciousness(format!("No agent named {}", to)))?;
+        recipient.evolve(content).await?;
+        recipient.absorb(from, &sender, &params);
+
+        if self.log.len() == MESSAGE_LOG {
+            self.log.pop_front();
+        }
+        self.log.push_back(AgentMessage { from: from.to_string(), to: to.to_string(), content: content.to_string() });
+        Ok(recipient.current_state().clone())
+    }
+
+    /// Deliver `content` from one agent to every other agent
+    pub async fn broadcast(&mut self, from: &str, content: &str) -> Result<usize, AgiError> {
+        let recipients: Vec<String> = self.agents.keys().filter(|name| name.as_str() != from).cloned().collect();
+        for to in &recipients {
+            self.send(from, to, content).await?;
+        }
+        Ok(recipients.len())
+    }
+
+    /// Apply one round of ambient emotional contagion between every pair
+    ///
+    /// Each agent moves toward the others' emotions as they were before the round,
+    /// so the order of agents does not matter.
+    pub fn mingle(&mut self) {
+        let states: Vec<(String, ConsciousnessState)> =
+            self.agents.iter().map(|(name, engine)| (name.clone(), engine.current_state().clone())).collect();
+        for (name, engine) in self.agents.iter_mut() {
+            for (other, state) in &states {
+                if other == name {
+                    continue;
+                }
+                let params = self.pairs.get(&(other.clone(), name.clone())).copied().unwrap_or(self.defaults);
+                engine.absorb(other, state, &InteractionParams { empathy: 0.0, ..params });
+            }
+        }
+    }
+
+    /// Mean emotion across agents
+    pub fn mean_emotion(&self) -> EmotionVector {
+        let count = self.agents.len().max(1) as f64;
+        self.agents.values().map(|engine| engine.current_state().emotion).fold(EmotionVector::NEUTRAL, |sum, emotion| {
+            EmotionVector {
+                valence: sum.valence + emotion.valence / count,
+                arousal: sum.arousal + emotion.arousal / count,
+                dominance: sum.dominance + emotion.dominance / count,
+            }
+        })
+    }
+
+    fn state_of(&self, name: &str) -> Result<ConsciousnessState, String> {
+        self.agents.get(name).map(|engine| engine.current_state().clone()).ok_or_else(|| format!("No agent named {}", name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_messages_spread_emotion() {
+        let mut registry = AgentRegistry::new();
+        registry.add_agent("alice", ConsciousnessEngine::new().unwrap()).unwrap();
+        registry.add_agent("bob", ConsciousnessEngine::new().unwrap()).unwrap();
+        assert!(registry.add_agent("bob", ConsciousnessEngine::new().unwrap()).is_err());
+        registry.set_interaction("alice", "bob", InteractionParams { contagion: 0.9, empathy: 0.5 }).unwrap();
+
+        for _ in 0..3 {
+            registry.agent_mut("alice").unwrap().evolve("Amazing! I love this! Wonderful!").await.unwrap();
+        }
+        let alice = registry.agent("alice").unwrap().current_state().emotion;
+        // A neutral message still carries alice's mood to bob
+        let bob = registry.send("alice", "bob", "the meeting is at noon").await.unwrap();
+        assert!(bob.emotion.valence > 0.5 * alice.valence);
+        assert_eq!(registry.messages().count(), 1);
+        assert!(registry.send("alice", "carol", "hi").await.is_err());
+
+        let before = registry.agent("alice").unwrap().current_state().emotion;
+        registry.mingle();
+        let after = registry.agent("alice").unwrap().current_state().emotion;
+        assert!(after.distance(&bob.emotion) < before.distance(&bob.emotion));
+        assert_eq!(registry.broadcast("bob", "thanks").await.unwrap(), 1);
+    }
+}
@@ -0,0 +1,165 @@
+//! Appraisal - Pluggable emotion models
+//!
+//! An `EmotionModel` turns an input into the `EmotionVector` the engine's emotion
+//! moves toward. `LexicalEmotionModel` is the built-in surface-feature heuristic.
+//! `EmbeddingEmotionModel` instead compares the input's embedding with labelled
+//! example texts and averages their emotions, weighted by similarity; its
+//! embedder can be the neural engine's token embedding or any external model, and
+//! since the examples may be written in any language, so may the input.
+
+use crate::error::AgiError;
+use crate::neural_engine::NeuralFoundationEngine;
+use crate::tensor_ops::stable::{epsilon, stable_dot, stable_norm};
+use crate::tokenizer::Tokenizer;
+
+use super::emotion::{EmotionVector, InputFeatures};
+
+/// Appraisal of inputs into emotion
+pub trait EmotionModel: Send + Sync {
+    /// Emotion the input suggests
+    fn appraise(&self, input: &str) -> Result<EmotionVector, AgiError>;
+
+    /// Model name for logging and statistics
+    fn name(&self) -> &'static str;
+}
+
+/// Appraisal from punctuation, capitalisation, word length and a sentiment lexicon
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LexicalEmotionModel;
+
+impl EmotionModel for LexicalEmotionModel {
+    fn appraise(&self, input: &str) -> Result<EmotionVector, AgiError> {
+        Ok(InputFeatures::extract(input).appraise())
+    }
+
+    fn name(&self) -> &'static str {
+        "lexical"
+    }
+}
+
+/// Text embedding function backing an `EmbeddingEmotionModel`
+pub type Embedder = Box<dyn Fn(&str) -> Result<Vec<f64>, AgiError> + Send + Sync>;
+
+/// Similarity-weighted average of the emotions of labelled examples
+pub struct EmbeddingEmotionModel {
+    embed: Embedder,
+    /// Embedding, norm and emotion of each example
+    anchors: Vec<(Vec<f64>, f64, EmotionVector)>,
+    temperature: f64,
+}
+
+impl EmbeddingEmotionModel {
+    /// Model over `embed`, softmax-weighting cosine similarities at `temperature`
+    pub fn new(embed: Embedder, temperature: f64) -> Result<Self, AgiError> {
+        if temperature <= 0.0 || !temperature.is_finite() {
+            return Err(AgiError::Config(format!("Temperature {} must be positive and finite", temperature)));
+        }
+        Ok(Self { embed, anchors: Vec::new(), temperature })
+    }
+
+    /// Model over a snapshot of the neural engine's tokenizer and token embedding
+    ///
+    /// Later training of the engine does not affect the model.
+    pub fn from_engine(engine: &NeuralFoundationEngine, temperature: f64) -> Result<Self, AgiError> {
+        let tokenizer = engine.tokenizer().clone();
+        let embedding = engine.embedding().clone();
+        Self::new(Box::new(move |text| Ok(embedding.mean_pool(&tokenizer.encode(text)).to_vec())), temperature)
+    }
+
+    /// Add a labelled example
+    pub fn add_example(&mut self, text: &str, emotion: EmotionVector) -> Result<(), AgiError> {
+        let embedding = (self.embed)(text)?;
+        if let Some((first, _, _)) = self.anchors.first() {
+            if first.len() != embedding.len() {
+                return Err(AgiError::Consciousness(format!("Embedding size {} differs from earlier examples ({})", embedding.len(), first.len())));
+            }
+        }
+        let norm = stable_norm(&embedding);
+        self.anchors.push((embedding, norm, emotion));
+        Ok(())
+    }
+
+    /// The model with every example in `examples` added
+    pub fn with_examples(mut self, examples: &[(&str, EmotionVector)]) -> Result<Self, AgiError> {
+        for (text, emotion) in examples {
+            self.add_example(text, *emotion)?;
+        }
+        Ok(self)
+    }
+
+    /// Number of labelled examples
+    pub fn len(&self) -> usize {
+        self.anchors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.anchors.is_empty()
+    }
+}
+
+impl EmotionModel for EmbeddingEmotionModel {
+    fn appraise(&self, input: &str) -> Result<EmotionVector, AgiError> {
+        if self.anchors.is_empty() {
+            return Ok(EmotionVector::NEUTRAL);
+        }
+        let embedding = (self.embed)(input)?;
+        if embedding.len() != self.anchors[0].0.len() {
+            return Err(AgiError::Consciousness(format!("Embedding size {} differs from the examples ({})", embedding.len(), self.anchors[0].0.len())));
+        }
+        let norm = stable_norm(&embedding);
+        let similarities: Vec<f64> = self.anchors.iter().map(|(anchor, anchor_norm, _)| {
+            let denominator = norm * anchor_norm;
+            if denominator <= epsilon() { 0.0 } else { stable_dot(&embedding, anchor) / denominator }
+        }).collect();
+
+        let peak = similarities.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let weights: Vec<f64> = similarities.iter().map(|s| ((s - peak) / self.temperature).exp()).collect();
+        let total: f64 = weights.iter().sum();
+        let (mut valence, mut arousal, mut dominance) = (0.0, 0.0, 0.0);
+        for (weight, (_, _, emotion)) in weights.iter().zip(&self.anchors) {
+            valence += weight * emotion.valence;
+            arousal += weight * emotion.arousal;
+            dominance += weight * emotion.dominance;
+        }
+        Ok(EmotionVector::new(valence / total, arousal / total, dominance / total))
+    }
+
+    fn name(&self) -> &'static str {
+        "embedding"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Bag-of-words embedding over a fixed vocabulary
+    fn bag_of_words(vocabulary: &'static [&'static str]) -> Embedder {
+        Box::new(move |text| {
+            let words: Vec<String> = text.split_whitespace().map(str::to_lowercase).collect();
+            Ok(vocabulary.iter().map(|entry| words.iter().filter(|word| word == entry).count() as f64).collect())
+        })
+    }
+
+    #[test]
+    fn test_embedding_model_follows_nearest_examples() {
+        let joyful = EmotionVector::new(0.8, 0.5, 0.2);
+        let sad = EmotionVector::new(-0.7, -0.3, -0.4);
+        let model = EmbeddingEmotionModel::new(bag_of_words(&["je", "suis", "heureux", "triste", "aujourd'hui"]), 0.1)
+            .unwrap()
+            .with_examples(&[("je suis heureux", joyful), ("je suis triste", sad)])
+            .unwrap();
+        assert_eq!(model.len(), 2);
+
+        let happy = model.appraise("heureux aujourd'hui").unwrap();
+        assert!(happy.valence > 0.7);
+        let unhappy = model.appraise("triste").unwrap();
+        assert!(unhappy.valence < -0.6);
+        // Equally similar to both examples, the appraisal lies between them
+        let mixed = model.appraise("je suis").unwrap();
+        assert!((mixed.valence - 0.05).abs() < 1e-9);
+
+        assert!(EmbeddingEmotionModel::new(bag_of_words(&["je"]), 0.0).is_err());
+        assert_eq!(LexicalEmotionModel.appraise("").unwrap(), EmotionVector::NEUTRAL);
+    }
+}
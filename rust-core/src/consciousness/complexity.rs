@@ -0,0 +1,227 @@
+//! Complexity - Pluggable measures of input complexity
+//!
+//! Input complexity drives awareness. A `ComplexityAnalyzer` scores text and
+//! tensor inputs in [0, 1] without relying on word boundaries or any particular
+//! script:
+//!
+//! - `EntropyComplexity` uses the Shannon entropy of the symbol distribution
+//!   (characters of text, histogram bins of tensor values), normalized by its
+//!   maximum for the input's length and damped for very short inputs;
+//! - `CompressionComplexity` uses how poorly the input compresses, measured by
+//!   the phrase count of an LZ77 parse, so repetitive inputs score low however
+//!   varied their alphabet;
+//! - `DispersionComplexity` embeds fragments of text (or takes tensor rows) and
+//!   measures how far their directions spread, so inputs that range over many
+//!   meanings score high.
+
+use std::collections::HashMap;
+
+use ndarray::Array2;
+
+use crate::error::AgiError;
+use crate::tensor_ops::stable::{epsilon, stable_norm};
+
+use super::Embedder;
+
+/// Symbols over which inputs are damped for brevity
+const LENGTH_SCALE: f64 = 32.0;
+
+/// Quantization levels for tensor values in symbol-based measures
+const LEVELS: usize = 16;
+
+/// Scoring of inputs into complexity in [0, 1]
+pub trait ComplexityAnalyzer: Send + Sync {
+    /// Complexity of a text input
+    fn text_complexity(&self, input: &str) -> Result<f64, AgiError>;
+
+    /// Complexity of a `(steps, width)` tensor input
+    fn tensor_complexity(&self, input: &Array2<f64>) -> Result<f64, AgiError>;
+
+    /// Analyzer name for logging and statistics
+    fn name(&self) -> &'static str;
+}
+
+/// Damping toward 0 for inputs much shorter than `LENGTH_SCALE` symbols
+fn length_factor(symbols: usize) -> f64 {
+    1.0 - (-(symbols as f64) / LENGTH_SCALE).exp()
+}
+
+/// Tensor values as symbols, by equal-width bins over their range
+fn quantize(input: &Array2<f64>) -> Vec<u32> {
+    let (low, high) = input.iter().filter(|v| v.is_finite()).fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), &v| (low.min(v), high.max(v)));
+    let span = high - low;
+    input
+        .iter()
+        .map(|&v| if !v.is_finite() || span <= epsilon() { 0 } else { (((v - low) / span) * (LEVELS - 1) as f64).round() as u32 })
+        .collect()
+}
+
+/// Shannon entropy of `symbols` relative to its maximum for their count
+fn normalized_entropy<T: std::hash::Hash + Eq>(symbols: &[T]) -> f64 {
+    let mut counts: HashMap<&T, usize> = HashMap::new();
+    for symbol in symbols {
+        *counts.entry(symbol).or_default() += 1;
+    }
+    // Summed in a fixed order, since the map's iteration order varies between runs
+    let mut counts: Vec<usize> = counts.into_values().collect();
+    counts.sort_unstable();
+    let n = symbols.len() as f64;
+    let entropy: f64 = counts.into_iter().map(|count| {
+        let p = count as f64 / n;
+        -p * p.log2()
+    }).sum();
+    let maximum = n.log2();
+    if maximum <= 0.0 { 0.0 } else { (entropy / maximum).clamp(0.0, 1.0) }
+}
+
+/// Phrases of a greedy LZ77 parse of `symbols` per symbol
+///
+/// Each phrase is the longest run that also starts earlier in the input, or a
+/// single new symbol, so incompressible inputs score 1 and repetitive ones near 0.
+fn lz_complexity<T: PartialEq>(symbols: &[T]) -> f64 {
+    let n = symbols.len();
+    if n == 0 {
+        return 0.0;
+    }
+    let (mut position, mut phrases) = (0, 0);
+    while position < n {
+        let longest = (0..position)
+            .map(|earlier| (0..n - position).take_while(|&k| symbols[earlier + k] == symbols[position + k]).count())
+            .max()
+            .unwrap_or(0);
+        position += longest.max(1);
+        phrases += 1;
+    }
+    phrases as f64 / n as f64
+}
+
+/// Entropy of the symbol distribution
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EntropyComplexity;
+
+impl ComplexityAnalyzer for EntropyComplexity {
+    fn text_complexity(&self, input: &str) -> Result<f64, AgiError> {
+        let symbols: Vec<char> = input.chars().collect();
+        Ok(normalized_entropy(&symbols) * length_factor(symbols.len()))
+    }
+
+    fn tensor_complexity(&self, input: &Array2<f64>) -> Result<f64, AgiError> {
+        let symbols = quantize(input);
+        Ok(normalized_entropy(&symbols) * length_factor(symbols.len()))
+    }
+
+    fn name(&self) -> &'static str {
+        "entropy"
+    }
+}
+
+/// Incompressibility under an LZ77 parse
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CompressionComplexity;
+
+impl ComplexityAnalyzer for CompressionComplexity {
+    fn text_complexity(&self, input: &str) -> Result<f64, AgiError> {
+        let symbols: Vec<char> = input.chars().collect();
+        Ok(lz_complexity(&symbols) * length_factor(symbols.len()))
+    }
+
+    fn tensor_complexity(&self, input: &Array2<f64>) -> Result<f64, AgiError> {
+        let symbols = quantize(input);
+        Ok(lz_complexity(&symbols) * length_factor(symbols.len()))
+    }
+
+    fn name(&self) -> &'static str {
+        "compression"
+    }
+}
+
+/// Spread of embedding directions across fragments of the input
+pub struct DispersionComplexity {
+    embed: Embedder,
+    /// Characters per fragment when text has no whitespace to split on
+    fragment_chars: usize,
+}
+
+impl DispersionComplexity {
+    /// Analyzer embedding fragments with `embed`; unspaced text is cut every `fragment_chars` characters
+    pub fn new(embed: Embedder, fragment_chars: usize) -> Result<Self, AgiError> {
+        if fragment_chars == 0 {
+            return Err(AgiError::Config("Fragments must hold at least one character".to_string()));
+        }
+        Ok(Self { embed, fragment_chars })
+    }
+
+    /// One minus the length of the mean unit direction of `vectors`
+    fn dispersion(vectors: impl Iterator<Item = Vec<f64>>) -> f64 {
+        let mut mean: Vec<f64> = Vec::new();
+        let mut count = 0usize;
+        for vector in vectors {
+            let norm = stable_norm(&vector);
+            if norm <= epsilon() {
+                continue;
+            }
+            if mean.is_empty() {
+                mean = vec![0.0; vector.len()];
+            }
+            mean.iter_mut().zip(&vector).for_each(|(sum, v)| *sum += v / norm);
+            count += 1;
+        }
+        if count < 2 {
+            return 0.0;
+        }
+        (1.0 - stable_norm(&mean) / count as f64).clamp(0.0, 1.0)
+    }
+}
+
+impl ComplexityAnalyzer for DispersionComplexity {
+    fn text_complexity(&self, input: &str) -> Result<f64, AgiError> {
+        let mut fragments: Vec<String> = input.split_whitespace().map(str::to_string).collect();
+        if fragments.len() < 2 {
+            let chars: Vec<char> = input.chars().filter(|c| !c.is_whitespace()).collect();
+            fragments = chars.chunks(self.fragment_chars).map(|chunk| chunk.iter().collect()).collect();
+        }
+        let embeddings = fragments.iter().map(|fragment| (self.embed)(fragment)).collect::<Result<Vec<_>, _>>()?;
+        if embeddings.windows(2).any(|pair| pair[0].len() != pair[1].len()) {
+            return Err(AgiError::Consciousness("Fragment embeddings differ in size".to_string()));
+        }
+        Ok(Self::dispersion(embeddings.into_iter()))
+    }
+
+    fn tensor_complexity(&self, input: &Array2<f64>) -> Result<f64, AgiError> {
+        Ok(Self::dispersion(input.rows().into_iter().map(|row| row.to_vec())))
+    }
+
+    fn name(&self) -> &'static str {
+        "dispersion"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    #[test]
+    fn test_measures_separate_varied_from_repetitive_input() {
+        let analyzers: [&dyn ComplexityAnalyzer; 2] = [&EntropyComplexity, &CompressionComplexity];
+        for analyzer in analyzers {
+            let repetitive = analyzer.text_complexity(&"ab".repeat(40)).unwrap();
+            let varied = analyzer.text_complexity("量子意识的涌现依赖于信息整合与注意力的动态平衡，而非单纯的规模。").unwrap();
+            assert!(varied > repetitive, "{}: {} vs {}", analyzer.name(), varied, repetitive);
+            assert_eq!(analyzer.text_complexity("").unwrap(), 0.0);
+
+            let flat = analyzer.tensor_complexity(&Array2::zeros((8, 8))).unwrap();
+            let noisy = analyzer.tensor_complexity(&Array2::from_shape_fn((8, 8), |(i, j)| ((i * 31 + j * 17) % 13) as f64)).unwrap();
+            assert!(noisy > flat);
+        }
+    }
+
+    #[test]
+    fn test_dispersion_reflects_spread_of_directions() {
+        let analyzer = DispersionComplexity::new(Box::new(|text| Ok(vec![text.len() as f64, text.chars().filter(|c| *c == 'x').count() as f64])), 2).unwrap();
+        assert_eq!(analyzer.tensor_complexity(&array![[1.0, 0.0], [2.0, 0.0]]).unwrap(), 0.0);
+        assert!(analyzer.tensor_complexity(&array![[1.0, 0.0], [0.0, 1.0]]).unwrap() > 0.25);
+        assert!(analyzer.text_complexity("aa xx").unwrap() > analyzer.text_complexity("aa bb").unwrap());
+        assert!(DispersionComplexity::new(Box::new(|_| Ok(vec![1.0])), 0).is_err());
+    }
+}
@@ -0,0 +1,252 @@
+//! Evolution Dynamics - Per-dimension growth, decay, saturation and noise
+//!
+//! Each evolved dimension of `ConsciousnessState` has its own `DimensionDynamics`:
+//! an initial level, a baseline it relaxes toward, a growth rate applied on every
+//! evolution step and a saturation curve shaping how that growth tapers near 1.
+//! Awareness growth is additionally scaled by the input complexity. Between inputs,
+//! `ConsciousnessEngine::tick` relaxes each dimension toward its baseline at its
+//! idle rate, so levels fall back when nothing arrives. The growth defaults
+//! reproduce the engine's original fixed increments; a `ConsciousnessConfig` can
+//! be loaded from JSON or TOML to try other dynamics without recompiling.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::AgiError;
+
+/// Idle relaxation rate of awareness, attention and creativity; about a minute's half-life
+const DEFAULT_IDLE_RATE: f64 = 0.01;
+
+/// How growth tapers as a dimension approaches 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Saturation {
+    /// Constant growth, clamped at 1
+    #[default]
+    Linear,
+    /// Growth proportional to the remaining headroom `1 - v`
+    Exponential,
+    /// Growth proportional to `4 v (1 - v)`: slow at both ends, fastest at 0.5
+    Logistic,
+}
+
+impl Saturation {
+    /// Change produced by `rate` at level `value`
+    pub fn step(&self, value: f64, rate: f64) -> f64 {
+        match self {
+            Self::Linear => rate,
+            Self::Exponential => rate * (1.0 - value),
+            Self::Logistic => rate * 4.0 * value * (1.0 - value),
+        }
+    }
+}
+
+/// Dynamics of one consciousness dimension
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DimensionDynamics {
+    /// Level of a freshly created engine
+    pub initial: f64,
+    /// Level the dimension relaxes toward
+    pub baseline: f64,
+    /// Growth per evolution step, before saturation
+    pub growth: f64,
+    /// Fraction of the distance to `baseline` closed on every evolution step
+    pub decay: f64,
+    /// Rate per second of exponential relaxation toward `baseline` while idle
+    pub idle_rate: f64,
+    pub saturation: Saturation,
+}
+
+impl DimensionDynamics {
+    /// Dynamics starting and resting at `initial`, growing by `growth` per step
+    pub fn new(initial: f64, growth: f64) -> Self {
+        Self { initial, baseline: initial, growth, decay: 0.0, idle_rate: 0.0, saturation: Saturation::Linear }
+    }
+
+    /// The same dynamics relaxing toward `baseline` at `idle_rate` per second
+    pub fn with_idle_rate(self, idle_rate: f64) -> Self {
+        Self { idle_rate, ..self }
+    }
+
+    /// Advance `value` by one evolution step, with growth scaled by `drive`
+    pub fn evolve(&self, value: f64, drive: f64) -> f64 {
+        let grown = value + self.saturation.step(value, self.growth * drive);
+        (grown + self.decay * (self.baseline - grown)).clamp(0.0, 1.0)
+    }
+
+    /// Relax `value` toward the baseline over `seconds` without input
+    pub fn relax(&self, value: f64, seconds: f64) -> f64 {
+        self.baseline + (value - self.baseline) * (-self.idle_rate * seconds).exp()
+    }
+
+    fn validate(&self, name: &str) -> Result<(), String> {
+        let unit = |value: f64| (0.0..=1.0).contains(&value);
+        if !unit(self.initial) || !unit(self.baseline) {
+            return Err(format!("{}: initial and baseline levels must lie in [0, 1]", name));
+        }
+        if !self.growth.is_finite() || !unit(self.decay) {
+            return Err(format!("{}: growth must be finite and decay must lie in [0, 1]", name));
+        }
+        if !self.idle_rate.is_finite() || self.idle_rate < 0.0 {
+            return Err(format!("{}: idle rate must be finite and non-negative", name));
+        }
+        Ok(())
+    }
+}
+
+/// Configuration of a `ConsciousnessEngine`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConsciousnessConfig {
+    /// Awareness dynamics; growth is scaled by input complexity
+    pub awareness: DimensionDynamics,
+    pub self_awareness: DimensionDynamics,
+    pub memory_coherence: DimensionDynamics,
+    pub attention_focus: DimensionDynamics,
+    pub creativity: DimensionDynamics,
+    /// Standard deviation of Gaussian noise added to each dimension per step
+    pub noise: f64,
+    /// Seed for the noise generator; drawn from the OS when absent
+    pub seed: Option<u64>,
+    /// Seconds without input after which the engine counts as idle
+    pub idle_after: f64,
+    /// Fraction of the way the emotion moves toward each input's appraisal
+    pub emotion_responsiveness: f64,
+    /// Rate per second at which emotion relaxes toward neutral while idle
+    pub emotion_idle_rate: f64,
+    /// Number of states kept in the evolution history before it is compacted
+    pub history_capacity: usize,
+}
+
+impl Default for ConsciousnessConfig {
+    fn default() -> Self {
+        Self {
+            awareness: DimensionDynamics::new(0.1, 0.1).with_idle_rate(DEFAULT_IDLE_RATE),
+            self_awareness: DimensionDynamics::new(0.05, 0.01),
+            memory_coherence: DimensionDynamics::new(0.8, 0.02),
+            attention_focus: DimensionDynamics::new(0.6, 0.05).with_idle_rate(DEFAULT_IDLE_RATE),
+            creativity: DimensionDynamics::new(0.3, 0.03).with_idle_rate(DEFAULT_IDLE_RATE),
+            noise: 0.0,
+            seed: None,
+            idle_after: 30.0,
+            emotion_responsiveness: 0.5,
+            emotion_idle_rate: 0.02,
+            history_capacity: super::DEFAULT_HISTORY_CAPACITY,
+        }
+    }
+}
+
+impl ConsciousnessConfig {
+    /// Parse a JSON configuration; omitted fields, including fields of a partly
+    /// given dimension, keep their defaults
+    pub fn from_json(json: &str) -> Result<Self, AgiError> {
+        Self::from_overrides(serde_json::from_str(json).map_err(|e| AgiError::Config(e.to_string()))?)
+    }
+
+    /// Parse a TOML configuration; omitted fields keep their defaults
+    pub fn from_toml(toml: &str) -> Result<Self, AgiError> {
+        Self::from_overrides(toml::from_str(toml).map_err(|e| AgiError::Config(e.to_string()))?)
+    }
+
+    /// Defaults with every field present in `overrides` replaced
+    fn from_overrides(overrides: Value) -> Result<Self, AgiError> {
+        let mut merged = serde_json::to_value(Self::default())?;
+        merge(&mut merged, overrides);
+        let config: Self = serde_json::from_value(merged).map_err(|e| AgiError::Config(e.to_string()))?;
+        config.validate().map_err(AgiError::Config)?;
+        Ok(config)
+    }
+
+    /// Load a `.json` or `.toml` configuration file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, AgiError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => Self::from_json(&text),
+            Some("toml") => Self::from_toml(&text),
+            _ => Err(AgiError::Config(format!("Unrecognized configuration format: {}", path.display()))),
+        }
+    }
+
+    /// Check that every level and rate is in range
+    pub fn validate(&self) -> Result<(), String> {
+        self.awareness.validate("awareness")?;
+        self.self_awareness.validate("self_awareness")?;
+        self.memory_coherence.validate("memory_coherence")?;
+        self.attention_focus.validate("attention_focus")?;
+        self.creativity.validate("creativity")?;
+        if !self.noise.is_finite() || self.noise < 0.0 {
+            return Err(format!("Noise {} must be finite and non-negative", self.noise));
+        }
+        if self.idle_after.is_nan() || self.idle_after < 0.0 {
+            return Err(format!("Idle threshold {} must be non-negative", self.idle_after));
+        }
+        if !(0.0..=1.0).contains(&self.emotion_responsiveness) {
+            return Err(format!("Emotion responsiveness {} must lie in [0, 1]", self.emotion_responsiveness));
+        }
+        if !self.emotion_idle_rate.is_finite() || self.emotion_idle_rate < 0.0 {
+            return Err(format!("Emotion idle rate {} must be finite and non-negative", self.emotion_idle_rate));
+        }
+        Ok(())
+    }
+}
+
+/// Recursively overwrite the fields of `base` that `overrides` sets
+fn merge(base: &mut Value, overrides: Value) {
+    match (base, overrides) {
+        (Value::Object(base), Value::Object(overrides)) => {
+            for (key, value) in overrides {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overrides) => *base = overrides,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_saturation_curves() {
+        let linear = DimensionDynamics::new(0.5, 0.2);
+        assert!((linear.evolve(0.5, 1.0) - 0.7).abs() < 1e-12);
+        assert_eq!(linear.evolve(0.95, 1.0), 1.0);
+
+        let exponential = DimensionDynamics { saturation: Saturation::Exponential, ..linear };
+        assert!((exponential.evolve(0.5, 1.0) - 0.6).abs() < 1e-12);
+        let logistic = DimensionDynamics { saturation: Saturation::Logistic, ..linear };
+        assert!(logistic.evolve(0.0, 1.0) == 0.0 && logistic.evolve(0.5, 1.0) > 0.69);
+
+        let decaying = DimensionDynamics { growth: 0.0, decay: 0.5, baseline: 0.2, ..linear };
+        assert!((decaying.evolve(0.6, 1.0) - 0.4).abs() < 1e-12);
+
+        let idle = decaying.with_idle_rate(2f64.ln());
+        assert!((idle.relax(0.6, 1.0) - 0.4).abs() < 1e-12);
+        assert_eq!(decaying.relax(0.6, 100.0), 0.6);
+    }
+
+    #[test]
+    fn test_config_formats() {
+        let json = r#"{"awareness": {"growth": 0.3, "saturation": "exponential"}, "noise": 0.01}"#;
+        let config = ConsciousnessConfig::from_json(json).unwrap();
+        assert_eq!(config.awareness.saturation, Saturation::Exponential);
+        assert_eq!(config.awareness.growth, 0.3);
+        assert_eq!(config.awareness.initial, 0.1);
+        assert_eq!(config.creativity, ConsciousnessConfig::default().creativity);
+
+        let toml = "seed = 7\n\n[creativity]\ndecay = 0.05\n";
+        let config = ConsciousnessConfig::from_toml(toml).unwrap();
+        assert_eq!(config.seed, Some(7));
+        assert_eq!(config.creativity.decay, 0.05);
+
+        assert!(ConsciousnessConfig::from_json(r#"{"noise": -1.0}"#).is_err());
+        assert!(ConsciousnessConfig::from_toml("[awareness]\ndecay = 2.0\n").is_err());
+    }
+}
@@ -0,0 +1,50 @@
+//! Consolidation - Offline replay during idle periods
+//!
+//! Where `optimize` tunes the engine in place, `consolidate` is an offline phase
+//! run while no input arrives. It replays the most recent inputs through the
+//! emotion model, compresses the evolution history to a target length, and moves
+//! each dimension's baseline part of the way toward the level it has recently
+//! held, so idle relaxation settles where the engine actually lives rather than
+//! where it started. The replayed inputs are returned for the host to fine-tune
+//! on, as `AGISystem::consolidate` does with the neural engine.
+
+use serde::{Deserialize, Serialize};
+
+use super::{DimensionChange, EmotionVector};
+
+/// Parameters of one consolidation pass
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConsolidationOptions {
+    /// Most recent inputs to replay
+    pub replay: usize,
+    /// Fraction of the way each baseline moves toward its recent mean, in [0, 1]
+    pub recalibration: f64,
+    /// History length to compress to, when shorter than the current history
+    pub compress_to: Option<usize>,
+    /// Refuse to run unless the engine is idle
+    pub require_idle: bool,
+    /// Epochs of neural fine-tuning on the replayed inputs; 0 to skip
+    pub fine_tune_epochs: usize,
+}
+
+impl Default for ConsolidationOptions {
+    fn default() -> Self {
+        Self { replay: 32, recalibration: 0.1, compress_to: None, require_idle: true, fine_tune_epochs: 0 }
+    }
+}
+
+/// What a consolidation pass did
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsolidationReport {
+    /// Inputs replayed, oldest first
+    pub replayed: Vec<String>,
+    /// Mean appraisal of the replayed inputs
+    pub replayed_emotion: EmotionVector,
+    pub states_before: usize,
+    pub states_after: usize,
+    /// Change of each recalibrated baseline, largest first
+    pub baseline_shifts: Vec<DimensionChange>,
+    /// Mean loss of the last fine-tuning epoch, when fine-tuning ran
+    pub fine_tune_loss: Option<f64>,
+    pub consolidation_time: std::time::Duration,
+}
@@ -0,0 +1,201 @@
+//! Emotion Model - Continuous valence, arousal and dominance
+//!
+//! Emotion is tracked as a point in valence–arousal–dominance space, each axis in
+//! [-1, 1] with the origin as calm neutrality. Every input is appraised from
+//! surface features that do not depend on a particular vocabulary (punctuation,
+//! capitalisation, word length) plus a small sentiment lexicon, and the current
+//! vector moves part of the way toward that appraisal. Between inputs it relaxes
+//! toward the origin. `EmotionVector::label` maps the vector back onto the
+//! discrete `EmotionalState` variants by nearest prototype.
+
+use serde::{Deserialize, Serialize};
+
+use super::EmotionalState;
+
+const POSITIVE: &[&str] = &[
+    "amazing", "awesome", "beautiful", "brilliant", "delight", "enjoy", "excellent", "exciting", "fantastic",
+    "glad", "good", "great", "happy", "hope", "joy", "love", "nice", "pleased", "thanks", "wonderful",
+];
+
+const NEGATIVE: &[&str] = &[
+    "afraid", "angry", "annoyed", "awful", "bad", "broken", "confused", "disappointed", "fail", "fear",
+    "hate", "horrible", "hurt", "problem", "sad", "sorry", "terrible", "upset", "worried", "wrong",
+];
+
+/// Point in valence–arousal–dominance space
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct EmotionVector {
+    /// Unpleasant (-1) to pleasant (1)
+    pub valence: f64,
+    /// Calm (-1) to excited (1)
+    pub arousal: f64,
+    /// Submissive, uncertain (-1) to in control (1)
+    pub dominance: f64,
+}
+
+impl EmotionVector {
+    pub const NEUTRAL: Self = Self { valence: 0.0, arousal: 0.0, dominance: 0.0 };
+
+    /// Vector with each component clamped to [-1, 1]
+    pub fn new(valence: f64, arousal: f64, dominance: f64) -> Self {
+        Self {
+            valence: valence.clamp(-1.0, 1.0),
+            arousal: arousal.clamp(-1.0, 1.0),
+            dominance: dominance.clamp(-1.0, 1.0),
+        }
+    }
+
+    /// Euclidean distance to `other`
+    pub fn distance(&self, other: &Self) -> f64 {
+        ((self.valence - other.valence).powi(2)
+            + (self.arousal - other.arousal).powi(2)
+            + (self.dominance - other.dominance).powi(2))
+        .sqrt()
+    }
+
+    /// Move `weight` of the way toward `target`
+    pub fn blend(&self, target: &Self, weight: f64) -> Self {
+        let mix = |from: f64, to: f64| from + (to - from) * weight;
+        Self::new(
+            mix(self.valence, target.valence),
+            mix(self.arousal, target.arousal),
+            mix(self.dominance, target.dominance),
+        )
+    }
+
+    /// Relax toward neutral at `rate` per second over `seconds`
+    pub fn decay(&self, rate: f64, seconds: f64) -> Self {
+        let factor = (-rate * seconds).exp();
+        Self::new(self.valence * factor, self.arousal * factor, self.dominance * factor)
+    }
+
+    /// Characteristic vector of a discrete emotional state
+    pub fn prototype(state: &EmotionalState) -> Self {
+        match state {
+            EmotionalState::Neutral => Self::NEUTRAL,
+            EmotionalState::Curious => Self::new(0.3, 0.4, -0.3),
+            EmotionalState::Excited => Self::new(0.7, 0.8, 0.3),
+            EmotionalState::Contemplative => Self::new(0.1, -0.4, 0.1),
+            EmotionalState::Creative => Self::new(0.5, 0.4, 0.4),
+            EmotionalState::Analytical => Self::new(0.0, 0.1, 0.5),
+        }
+    }
+
+    /// Discrete state whose prototype is nearest
+    pub fn label(&self) -> EmotionalState {
+        [
+            EmotionalState::Neutral,
+            EmotionalState::Curious,
+            EmotionalState::Excited,
+            EmotionalState::Contemplative,
+            EmotionalState::Creative,
+            EmotionalState::Analytical,
+        ]
+        .into_iter()
+        .min_by(|a, b| {
+            self.distance(&Self::prototype(a)).total_cmp(&self.distance(&Self::prototype(b)))
+        })
+        .unwrap_or_default()
+    }
+}
+
+/// Surface features of an input, each normalised to [0, 1]
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct InputFeatures {
+    /// Exclamation marks per sentence
+    pub exclamation: f64,
+    /// Question marks per sentence
+    pub question: f64,
+    /// Share of cased letters written in upper case
+    pub shouting: f64,
+    /// Mean word length, scaled so twelve characters or more is 1
+    pub elaboration: f64,
+    /// Share of words in the positive lexicon
+    pub positive: f64,
+    /// Share of words in the negative lexicon
+    pub negative: f64,
+}
+
+impl InputFeatures {
+    /// Extract the features of `input`
+    pub fn extract(input: &str) -> Self {
+        let count = |marks: &[char]| input.chars().filter(|c| marks.contains(c)).count() as f64;
+        let exclamations = count(&['!', '！', '¡']);
+        let questions = count(&['?', '？', '¿']);
+        let sentences = count(&['.', '!', '?', '。', '！', '？']).max(1.0);
+
+        let (upper, cased) = input.chars().fold((0usize, 0usize), |(upper, cased), c| {
+            if c.is_uppercase() {
+                (upper + 1, cased + 1)
+            } else if c.is_lowercase() {
+                (upper, cased + 1)
+            } else {
+                (upper, cased)
+            }
+        });
+        // A capitalised first letter or acronym in a short input is not shouting
+        let shouting = if cased >= 8 { upper as f64 / cased as f64 } else { 0.0 };
+
+        let words: Vec<String> = input
+            .split_whitespace()
+            .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase())
+            .filter(|word| !word.is_empty())
+            .collect();
+        let total = words.len().max(1) as f64;
+        let mean_length = words.iter().map(|word| word.chars().count()).sum::<usize>() as f64 / total;
+        let share = |lexicon: &[&str]| {
+            let hits = words.iter().filter(|word| lexicon.iter().any(|entry| word.starts_with(entry))).count();
+            (hits as f64 / total * 4.0).min(1.0)
+        };
+
+        Self {
+            exclamation: (exclamations / sentences).min(1.0),
+            question: (questions / sentences).min(1.0),
+            shouting,
+            elaboration: ((mean_length - 3.0) / 9.0).clamp(0.0, 1.0),
+            positive: share(POSITIVE),
+            negative: share(NEGATIVE),
+        }
+    }
+
+    /// Emotion these features suggest
+    pub fn appraise(&self) -> EmotionVector {
+        EmotionVector::new(
+            self.positive - self.negative + 0.2 * self.exclamation,
+            0.7 * self.exclamation + 0.3 * self.question + 0.5 * self.shouting
+                + 0.3 * (self.positive + self.negative) - 0.3 * self.elaboration,
+            0.6 * self.elaboration + 0.3 * self.shouting - 0.5 * self.question - 0.3 * self.negative,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_labels_follow_nearest_prototype() {
+        assert!(matches!(EmotionVector::NEUTRAL.label(), EmotionalState::Neutral));
+        assert!(matches!(EmotionVector::new(0.8, 0.9, 0.2).label(), EmotionalState::Excited));
+        assert!(matches!(EmotionVector::new(0.0, -0.5, 0.0).label(), EmotionalState::Contemplative));
+
+        let excited = EmotionVector::prototype(&EmotionalState::Excited);
+        assert!(excited.decay(1.0, 10.0).distance(&EmotionVector::NEUTRAL) < 1e-3);
+        assert_eq!(EmotionVector::NEUTRAL.blend(&excited, 1.0), excited);
+    }
+
+    #[test]
+    fn test_appraisal_features() {
+        let excited = InputFeatures::extract("This is AMAZING! I love it!");
+        assert!(excited.exclamation > 0.9 && excited.positive > 0.0);
+        let appraisal = excited.appraise();
+        assert!(appraisal.valence > 0.5 && appraisal.arousal > 0.5);
+
+        let unsure = InputFeatures::extract("Why did it fail? What went wrong?").appraise();
+        assert!(unsure.valence < 0.0 && unsure.dominance < 0.0);
+
+        // Punctuation carries across languages without any lexicon
+        let spanish = InputFeatures::extract("¿Qué pasa?");
+        assert!(spanish.question > 0.0);
+    }
+}
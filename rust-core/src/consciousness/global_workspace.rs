@@ -0,0 +1,211 @@
+//! Global Workspace - Competition for a limited broadcast slot
+//!
+//! Each processing cycle, specialist processes submit `Candidate` contents (neural
+//! results, memory retrievals, standing goals) with a salience score. Only the
+//! `capacity` most salient win the broadcast; the rest are dropped. Contents that
+//! keep winning habituate, losing salience on later cycles until they rest, so a
+//! single source cannot hold the workspace indefinitely.
+//!
+//! The broadcast's strength measures how decisively the winning coalition beat the
+//! competition, and the consciousness engine adopts it as its attention focus.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::neural_engine::NeuralResponse;
+
+/// Process a candidate came from
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ContentSource {
+    Neural,
+    Memory,
+    Goal,
+    /// Any other host-defined process
+    Other(String),
+}
+
+/// Content competing for the broadcast
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Candidate {
+    pub source: ContentSource,
+    /// Identifies the content across cycles, for habituation
+    pub label: String,
+    /// Salience in [0, 1]
+    pub salience: f64,
+}
+
+impl Candidate {
+    /// Candidate with salience clamped to [0, 1]
+    pub fn new(source: ContentSource, label: impl Into<String>, salience: f64) -> Self {
+        Self { source, label: label.into(), salience: salience.clamp(0.0, 1.0) }
+    }
+
+    /// A neural result, salient in proportion to its strength, confidence and coherence
+    pub fn neural(response: &NeuralResponse) -> Self {
+        let salience = (response.activation_strength + response.pattern_confidence + response.coherence_score) / 3.0;
+        Self::new(ContentSource::Neural, "neural_response", salience)
+    }
+
+    /// A memory retrieved with the given relevance
+    pub fn memory(key: impl Into<String>, relevance: f64) -> Self {
+        Self::new(ContentSource::Memory, key, relevance)
+    }
+}
+
+/// Standing goal that competes whenever the input touches on it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Goal {
+    pub description: String,
+    /// Importance in [0, 1]
+    pub priority: f64,
+}
+
+impl Goal {
+    pub fn new(description: impl Into<String>, priority: f64) -> Self {
+        Self { description: description.into(), priority: priority.clamp(0.0, 1.0) }
+    }
+
+    /// Candidate for this goal, salient by priority and word overlap with `input`
+    ///
+    /// A goal unrelated to the input still competes at a third of its priority.
+    pub fn candidate(&self, input: &str) -> Candidate {
+        let words = |text: &str| -> Vec<String> {
+            text.split_whitespace()
+                .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase())
+                .filter(|word| !word.is_empty())
+                .collect()
+        };
+        let goal_words = words(&self.description);
+        let input_words = words(input);
+        let shared = goal_words.iter().filter(|word| input_words.contains(word)).count();
+        let relevance = shared as f64 / goal_words.len().max(1) as f64;
+        Candidate::new(ContentSource::Goal, self.description.clone(), self.priority * (1.0 + 2.0 * relevance) / 3.0)
+    }
+}
+
+/// Outcome of one workspace cycle
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Broadcast {
+    /// Winning coalition, most salient first, with habituated salience
+    pub winners: Vec<Candidate>,
+    /// Candidates that competed
+    pub competitors: usize,
+    /// Mean winner salience times the winners' share of all salience, in [0, 1]
+    pub strength: f64,
+    pub cycle: u64,
+}
+
+/// Limited-capacity workspace shared by competing processes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GlobalWorkspace {
+    capacity: usize,
+    /// Salience lost per consecutive win
+    habituation: f64,
+    goals: Vec<Goal>,
+    /// Consecutive wins of each recently broadcast label
+    streaks: HashMap<String, u32>,
+    last: Broadcast,
+}
+
+impl Default for GlobalWorkspace {
+    fn default() -> Self {
+        Self::new(3)
+    }
+}
+
+impl GlobalWorkspace {
+    /// Workspace broadcasting at most `capacity` (at least 1) contents per cycle
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            habituation: 0.1,
+            goals: Vec::new(),
+            streaks: HashMap::new(),
+            last: Broadcast::default(),
+        }
+    }
+
+    /// Set the salience lost per consecutive win, clamped to [0, 1]
+    pub fn with_habituation(mut self, habituation: f64) -> Self {
+        self.habituation = habituation.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Add a goal that competes on every cycle
+    pub fn add_goal(&mut self, goal: Goal) {
+        self.goals.push(goal);
+    }
+
+    /// Remove the goals with this description, returning how many were removed
+    pub fn remove_goal(&mut self, description: &str) -> usize {
+        let before = self.goals.len();
+        self.goals.retain(|goal| goal.description != description);
+        before - self.goals.len()
+    }
+
+    pub fn goals(&self) -> &[Goal] {
+        &self.goals
+    }
+
+    /// Most recent broadcast
+    pub fn last_broadcast(&self) -> &Broadcast {
+        &self.last
+    }
+
+    /// Run one cycle: `candidates` and the standing goals compete for the broadcast
+    pub fn compete(&mut self, input: &str, mut candidates: Vec<Candidate>) -> &Broadcast {
+        candidates.extend(self.goals.iter().map(|goal| goal.candidate(input)));
+        for candidate in &mut candidates {
+            let streak = self.streaks.get(&candidate.label).copied().unwrap_or(0);
+            candidate.salience *= (1.0 - self.habituation).powi(streak as i32);
+        }
+        candidates.sort_by(|a, b| b.salience.total_cmp(&a.salience));
+
+        let competitors = candidates.len();
+        let total: f64 = candidates.iter().map(|candidate| candidate.salience).sum();
+        candidates.truncate(self.capacity);
+        let winning: f64 = candidates.iter().map(|candidate| candidate.salience).sum();
+        let strength = if total > 0.0 { (winning / candidates.len() as f64) * (winning / total) } else { 0.0 };
+
+        // Winners extend their streak; everything else starts afresh
+        let mut streaks = HashMap::with_capacity(candidates.len());
+        for candidate in &candidates {
+            let streak = self.streaks.get(&candidate.label).copied().unwrap_or(0);
+            streaks.insert(candidate.label.clone(), streak + 1);
+        }
+        self.streaks = streaks;
+
+        self.last = Broadcast { winners: candidates, competitors, strength, cycle: self.last.cycle + 1 };
+        &self.last
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_salient_contents_win_and_habituate() {
+        let mut workspace = GlobalWorkspace::new(1).with_habituation(0.5);
+        workspace.add_goal(Goal::new("learn rust ownership", 0.9));
+
+        let broadcast = workspace.compete("how does rust ownership work", vec![Candidate::memory("recipe", 0.4)]).clone();
+        assert_eq!(broadcast.winners.len(), 1);
+        assert_eq!(broadcast.winners[0].source, ContentSource::Goal);
+        assert_eq!(broadcast.competitors, 2);
+        assert!(broadcast.strength > 0.4 && broadcast.strength <= 1.0);
+
+        // The goal habituates after winning and the memory takes over
+        let broadcast = workspace.compete("how does rust ownership work", vec![Candidate::memory("recipe", 0.5)]);
+        assert_eq!(broadcast.winners[0].label, "recipe");
+        assert_eq!(broadcast.cycle, 2);
+
+        assert_eq!(workspace.remove_goal("learn rust ownership"), 1);
+        assert_eq!(workspace.compete("", Vec::new()).strength, 0.0);
+    }
+}
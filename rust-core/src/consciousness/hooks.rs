@@ -0,0 +1,161 @@
+//! Event Hooks - Callbacks on consciousness transitions
+//!
+//! Hosts register callbacks instead of polling `get_stats`. Three kinds of event
+//! are delivered: every evolution step, every change of the discrete emotional
+//! label, and every crossing of a threshold by a named dimension, in either
+//! direction. Callbacks run synchronously on the thread that changed the state,
+//! while the engine is borrowed, so they must not call back into the engine.
+
+use serde::{Deserialize, Serialize};
+
+use super::{ConsciousnessState, EmotionalState, DIMENSION_NAMES};
+
+/// Handle for removing a registered hook
+pub type HookId = u64;
+
+/// Callback receiving consciousness events
+pub type EventCallback = Box<dyn Fn(&ConsciousnessEvent) + Send + Sync>;
+
+/// Transition reported to hooks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ConsciousnessEvent {
+    /// An evolution step produced this state
+    StateEvolved(ConsciousnessState),
+    /// The discrete emotional label changed
+    EmotionChanged { from: EmotionalState, to: EmotionalState },
+    /// A dimension moved across a watched threshold
+    ThresholdCrossed { dimension: String, threshold: f64, value: f64, rising: bool },
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Trigger {
+    Evolved,
+    EmotionChanged,
+    Threshold { dimension: usize, threshold: f64 },
+}
+
+/// Registered callbacks, in registration order
+#[derive(Default)]
+pub struct Hooks {
+    next_id: HookId,
+    hooks: Vec<(HookId, Trigger, EventCallback)>,
+}
+
+impl Hooks {
+    fn register(&mut self, trigger: Trigger, callback: EventCallback) -> HookId {
+        self.next_id += 1;
+        self.hooks.push((self.next_id, trigger, callback));
+        self.next_id
+    }
+
+    /// Call `callback` after every evolution step
+    pub fn on_state_evolved(&mut self, callback: EventCallback) -> HookId {
+        self.register(Trigger::Evolved, callback)
+    }
+
+    /// Call `callback` whenever the discrete emotional label changes
+    pub fn on_emotion_change(&mut self, callback: EventCallback) -> HookId {
+        self.register(Trigger::EmotionChanged, callback)
+    }
+
+    /// Call `callback` whenever `dimension` (one of `DIMENSION_NAMES`) crosses `threshold`
+    pub fn on_threshold_crossed(&mut self, dimension: &str, threshold: f64, callback: EventCallback) -> Result<HookId, String> {
+        let index = DIMENSION_NAMES
+            .iter()
+            .position(|name| *name == dimension)
+            .ok_or_else(|| format!("Unknown dimension {}; expected one of {:?}", dimension, DIMENSION_NAMES))?;
+        if !threshold.is_finite() {
+            return Err(format!("Threshold {} must be finite", threshold));
+        }
+        Ok(self.register(Trigger::Threshold { dimension: index, threshold }, callback))
+    }
+
+    /// Unregister a hook; false if no hook has this id
+    pub fn remove(&mut self, id: HookId) -> bool {
+        let before = self.hooks.len();
+        self.hooks.retain(|(hook, _, _)| *hook != id);
+        self.hooks.len() < before
+    }
+
+    pub fn len(&self) -> usize {
+        self.hooks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    /// Deliver the events of a transition from `before` to `after`
+    pub(super) fn fire(&self, before: &ConsciousnessState, after: &ConsciousnessState, evolved: bool) {
+        if self.hooks.is_empty() {
+            return;
+        }
+        let (old, new) = (before.dimensions(), after.dimensions());
+        for (_, trigger, callback) in &self.hooks {
+            let event = match *trigger {
+                Trigger::Evolved if evolved => ConsciousnessEvent::StateEvolved(after.clone()),
+                Trigger::EmotionChanged if before.emotional_state != after.emotional_state => {
+                    ConsciousnessEvent::EmotionChanged { from: before.emotional_state, to: after.emotional_state }
+                }
+                Trigger::Threshold { dimension, threshold } => {
+                    let rising = old[dimension] < threshold && new[dimension] >= threshold;
+                    let falling = old[dimension] >= threshold && new[dimension] < threshold;
+                    if !rising && !falling {
+                        continue;
+                    }
+                    ConsciousnessEvent::ThresholdCrossed {
+                        dimension: DIMENSION_NAMES[dimension].to_string(),
+                        threshold,
+                        value: new[dimension],
+                        rising,
+                    }
+                }
+                _ => continue,
+            };
+            callback(&event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consciousness::EmotionVector;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_hooks_fire_on_matching_transitions() {
+        let before = ConsciousnessState {
+            awareness_level: 0.4,
+            self_awareness: 0.1,
+            emotional_state: EmotionalState::Neutral,
+            emotion: EmotionVector::NEUTRAL,
+            memory_coherence: 0.8,
+            attention_focus: 0.6,
+            creativity_level: 0.3,
+        };
+        let after = ConsciousnessState { awareness_level: 0.6, emotional_state: EmotionalState::Curious, ..before.clone() };
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let mut hooks = Hooks::default();
+        let sink = |events: &Arc<Mutex<Vec<String>>>| -> EventCallback {
+            let events = events.clone();
+            Box::new(move |event| events.lock().unwrap().push(format!("{:?}", event)))
+        };
+        hooks.on_state_evolved(sink(&events));
+        let emotion = hooks.on_emotion_change(sink(&events));
+        hooks.on_threshold_crossed("awareness_level", 0.5, sink(&events)).unwrap();
+        hooks.on_threshold_crossed("creativity_level", 0.5, sink(&events)).unwrap();
+        assert!(hooks.on_threshold_crossed("mood", 0.5, sink(&events)).is_err());
+
+        hooks.fire(&before, &after, true);
+        assert_eq!(events.lock().unwrap().len(), 3);
+        assert!(events.lock().unwrap()[2].contains("rising: true"));
+
+        // Falling back through the threshold without evolving
+        assert!(hooks.remove(emotion));
+        hooks.fire(&after, &before, false);
+        assert_eq!(events.lock().unwrap().len(), 4);
+        assert!(events.lock().unwrap()[3].contains("rising: false"));
+    }
+}
@@ -0,0 +1,182 @@
+//! Introspection - Structured self-reports
+//!
+//! The engine keeps a short log of what changed its state: each input it evolved
+//! on, each workspace broadcast, each stretch of idle time, each influence of
+//! another agent and each reported outcome, with the dimensions that moved. `ConsciousnessEngine::introspect` combines that log with the
+//! current state, the attention target and a summary of the trajectory into a
+//! `SelfReport`, which hosts can read directly or as JSON.
+
+use serde::{Deserialize, Serialize};
+
+use super::{Candidate, ConsciousnessState, EmotionVector, EmotionalState, DIMENSION_NAMES};
+
+/// Changes smaller than this are not reported
+const NEGLIGIBLE: f64 = 1e-9;
+
+/// Longest input excerpt kept in a driver
+const EXCERPT_CHARS: usize = 48;
+
+/// What caused a change of state
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Cause {
+    /// An input the engine evolved on
+    Input { excerpt: String, complexity: f64, appraisal: EmotionVector },
+    /// A global workspace broadcast
+    Broadcast { winner: Option<String>, strength: f64 },
+    /// Time passing without input
+    Idle { seconds: f64 },
+    /// Influence of another agent's state
+    Interaction { from: String },
+    /// Reported outcome of a confident prediction
+    Feedback { correct: bool, calibration_error: f64 },
+}
+
+impl Cause {
+    /// Input cause, keeping only the start of long inputs
+    pub fn input(input: &str, complexity: f64, appraisal: EmotionVector) -> Self {
+        let mut excerpt: String = input.chars().take(EXCERPT_CHARS).collect();
+        if input.chars().nth(EXCERPT_CHARS).is_some() {
+            excerpt.push('…');
+        }
+        Self::Input { excerpt, complexity, appraisal }
+    }
+}
+
+/// Change of one dimension
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DimensionChange {
+    pub dimension: String,
+    pub delta: f64,
+}
+
+/// One logged cause and the dimensions it moved, largest change first
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangeDriver {
+    /// Evolution count when the change happened
+    pub evolution: u64,
+    pub cause: Cause,
+    pub changes: Vec<DimensionChange>,
+}
+
+impl ChangeDriver {
+    pub fn new(evolution: u64, cause: Cause, before: &ConsciousnessState, after: &ConsciousnessState) -> Self {
+        let deltas = after.dimensions().iter().zip(before.dimensions()).map(|(a, b)| a - b).collect();
+        Self { evolution, cause, changes: Self::ranked(deltas) }
+    }
+
+    /// Fold a directly following idle stretch into this one; false if either is not idle
+    pub fn absorb_idle(&mut self, other: &ChangeDriver) -> bool {
+        let (Cause::Idle { seconds }, Cause::Idle { seconds: more }) = (&mut self.cause, &other.cause) else {
+            return false;
+        };
+        *seconds += more;
+        let mut deltas = vec![0.0; DIMENSION_NAMES.len()];
+        for change in self.changes.iter().chain(&other.changes) {
+            if let Some(index) = DIMENSION_NAMES.iter().position(|name| *name == change.dimension) {
+                deltas[index] += change.delta;
+            }
+        }
+        self.changes = Self::ranked(deltas);
+        true
+    }
+
+    fn ranked(deltas: Vec<f64>) -> Vec<DimensionChange> {
+        let mut changes: Vec<DimensionChange> = DIMENSION_NAMES
+            .iter()
+            .zip(deltas)
+            .filter(|(_, delta)| delta.abs() > NEGLIGIBLE)
+            .map(|(name, delta)| DimensionChange { dimension: name.to_string(), delta })
+            .collect();
+        changes.sort_by(|a, b| b.delta.abs().total_cmp(&a.delta.abs()));
+        changes
+    }
+}
+
+/// Shape of the engine's trajectory so far
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrajectorySummary {
+    pub evolutions: u64,
+    pub awareness_trend: f64,
+    pub peak_awareness: f64,
+    pub phi: f64,
+    pub idle: bool,
+    pub idle_seconds: f64,
+}
+
+/// Structured answer to "what state am I in, and why"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelfReport {
+    pub state: ConsciousnessState,
+    /// Recent causes of change, oldest first
+    pub drivers: Vec<ChangeDriver>,
+    /// Top content of the latest workspace broadcast
+    pub attention_target: Option<Candidate>,
+    pub dominant_emotion: EmotionalState,
+    /// Distance of the emotion from neutral
+    pub emotion_intensity: f64,
+    pub trajectory: TrajectorySummary,
+}
+
+impl SelfReport {
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    /// One-line account of the dominant emotion, attention and latest change
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "Feeling {:?} (intensity {:.2}), awareness {:.2}",
+            self.dominant_emotion, self.emotion_intensity, self.state.awareness_level
+        );
+        if let Some(target) = &self.attention_target {
+            summary.push_str(&format!(", attending to {}", target.label));
+        }
+        let latest = self.drivers.last().and_then(|driver| driver.changes.first().map(|change| (driver, change)));
+        if let Some((driver, change)) = latest {
+            let cause = match &driver.cause {
+                Cause::Input { excerpt, .. } => format!("input \"{}\"", excerpt),
+                Cause::Broadcast { winner, .. } => format!("broadcast of {}", winner.as_deref().unwrap_or("nothing")),
+                Cause::Idle { seconds } => format!("{:.0}s idle", seconds),
+                Cause::Interaction { from } => format!("interaction with {}", from),
+                Cause::Feedback { correct, .. } => format!("{} prediction", if *correct { "correct" } else { "incorrect" }),
+            };
+            summary.push_str(&format!("; {} moved {} by {:+.3}", cause, change.dimension, change.delta));
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drivers_rank_and_merge_changes() {
+        let before = ConsciousnessState {
+            awareness_level: 0.5,
+            self_awareness: 0.5,
+            emotional_state: EmotionalState::Neutral,
+            emotion: EmotionVector::NEUTRAL,
+            memory_coherence: 0.5,
+            attention_focus: 0.5,
+            creativity_level: 0.5,
+        };
+        let after = ConsciousnessState { awareness_level: 0.4, creativity_level: 0.3, ..before.clone() };
+        let mut idle = ChangeDriver::new(3, Cause::Idle { seconds: 10.0 }, &before, &after);
+        assert_eq!(idle.changes[0].dimension, "creativity_level");
+        assert_eq!(idle.changes.len(), 2);
+
+        let later = ConsciousnessState { awareness_level: 0.35, ..after.clone() };
+        assert!(idle.absorb_idle(&ChangeDriver::new(3, Cause::Idle { seconds: 5.0 }, &after, &later)));
+        assert_eq!(idle.cause, Cause::Idle { seconds: 15.0 });
+        let awareness = idle.changes.iter().find(|change| change.dimension == "awareness_level").unwrap();
+        assert!((awareness.delta + 0.15).abs() < 1e-12);
+
+        let input = ChangeDriver::new(4, Cause::input(&"x".repeat(100), 0.5, EmotionVector::NEUTRAL), &later, &before);
+        assert!(!idle.clone().absorb_idle(&input));
+        match input.cause {
+            Cause::Input { excerpt, .. } => assert_eq!(excerpt.chars().count(), EXCERPT_CHARS + 1),
+            _ => unreachable!(),
+        }
+    }
+}
@@ -0,0 +1,199 @@
+//! Meta-Cognition - Tracking how well confidence predicts outcomes
+//!
+//! Each confidence the system reports is queued as a prediction together with the
+//! signals it was computed from (activation strength, pattern confidence and
+//! coherence). When the host later reports whether the answer was correct, the
+//! oldest pending prediction is resolved against it:
+//!
+//! - its bin of the calibration curve records the outcome, so accuracy can be
+//!   compared with stated confidence per confidence range;
+//! - the signal weights take a logistic-regression step toward predicting the
+//!   outcome, so signals that track correctness gain weight in future confidence;
+//! - future confidences are mapped through the curve once a bin has evidence.
+//!
+//! The consciousness engine turns calibration into self-awareness: the smaller the
+//! expected calibration error, the better the system knows what it knows.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+/// Number of equal-width bins of the calibration curve
+const BINS: usize = 10;
+
+/// Pending predictions kept while awaiting feedback
+const PENDING: usize = 256;
+
+/// Outcomes a bin needs before its accuracy fully replaces raw confidence
+const EVIDENCE: f64 = 20.0;
+
+/// One confidence range of the calibration curve
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct CalibrationBin {
+    pub lower: f64,
+    pub upper: f64,
+    /// Outcomes recorded for predictions in this range
+    pub count: usize,
+    pub mean_confidence: f64,
+    /// Fraction of those predictions that were correct
+    pub accuracy: f64,
+}
+
+/// Result of resolving a prediction with feedback
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FeedbackOutcome {
+    /// Confidence that was stated for the resolved prediction
+    pub confidence: f64,
+    pub correct: bool,
+    /// Squared error of the confidence against the outcome
+    pub brier: f64,
+    /// Expected calibration error after recording the outcome
+    pub calibration_error: f64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct Prediction {
+    signals: [f64; 3],
+    confidence: f64,
+}
+
+/// Calibration tracker and adaptive confidence weighting
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetaCognition {
+    /// Weights of activation strength, pattern confidence and coherence
+    weights: [f64; 3],
+    learning_rate: f64,
+    bins: Vec<CalibrationBin>,
+    pending: VecDeque<Prediction>,
+    brier_total: f64,
+    resolved: usize,
+}
+
+impl Default for MetaCognition {
+    fn default() -> Self {
+        Self {
+            weights: [0.4, 0.3, 0.3],
+            learning_rate: 0.05,
+            bins: (0..BINS)
+                .map(|bin| CalibrationBin {
+                    lower: bin as f64 / BINS as f64,
+                    upper: (bin + 1) as f64 / BINS as f64,
+                    ..CalibrationBin::default()
+                })
+                .collect(),
+            pending: VecDeque::new(),
+            brier_total: 0.0,
+            resolved: 0,
+        }
+    }
+}
+
+impl MetaCognition {
+    /// Current weights of activation strength, pattern confidence and coherence
+    pub fn weights(&self) -> [f64; 3] {
+        self.weights
+    }
+
+    /// Confidence from the three signals, calibrated, and queued for feedback
+    pub fn predict(&mut self, signals: [f64; 3]) -> f64 {
+        let raw = self.raw_confidence(&signals);
+        let confidence = self.calibrate(raw);
+        if self.pending.len() == PENDING {
+            self.pending.pop_front();
+        }
+        self.pending.push_back(Prediction { signals, confidence });
+        confidence
+    }
+
+    fn raw_confidence(&self, signals: &[f64; 3]) -> f64 {
+        self.weights.iter().zip(signals).map(|(w, s)| w * s).sum::<f64>().clamp(0.0, 1.0)
+    }
+
+    fn bin_of(confidence: f64) -> usize {
+        ((confidence * BINS as f64) as usize).min(BINS - 1)
+    }
+
+    /// Map a raw confidence through the calibration curve, trusting each bin by its evidence
+    pub fn calibrate(&self, confidence: f64) -> f64 {
+        let bin = &self.bins[Self::bin_of(confidence)];
+        let trust = bin.count as f64 / (bin.count as f64 + EVIDENCE);
+        (1.0 - trust) * confidence + trust * bin.accuracy
+    }
+
+    /// Resolve the oldest pending prediction with its outcome
+    pub fn record_feedback(&mut self, correct: bool) -> Result<FeedbackOutcome, String> {
+        let prediction = self.pending.pop_front().ok_or("No prediction is awaiting feedback")?;
+        let outcome = if correct { 1.0 } else { 0.0 };
+
+        let bin = &mut self.bins[Self::bin_of(prediction.confidence)];
+        bin.count += 1;
+        let n = bin.count as f64;
+        bin.mean_confidence += (prediction.confidence - bin.mean_confidence) / n;
+        bin.accuracy += (outcome - bin.accuracy) / n;
+
+        // Logistic-regression step on the signals, keeping weights non-negative and summing to 1
+        let raw = self.raw_confidence(&prediction.signals);
+        for (weight, signal) in self.weights.iter_mut().zip(prediction.signals) {
+            *weight = (*weight + self.learning_rate * (outcome - raw) * signal).max(0.0);
+        }
+        let total: f64 = self.weights.iter().sum();
+        if total > 0.0 {
+            self.weights.iter_mut().for_each(|weight| *weight /= total);
+        } else {
+            self.weights = [1.0 / 3.0; 3];
+        }
+
+        let brier = (prediction.confidence - outcome).powi(2);
+        self.brier_total += brier;
+        self.resolved += 1;
+        Ok(FeedbackOutcome { confidence: prediction.confidence, correct, brier, calibration_error: self.calibration_error() })
+    }
+
+    /// Calibration curve, one entry per confidence range
+    pub fn calibration_curve(&self) -> &[CalibrationBin] {
+        &self.bins
+    }
+
+    /// Expected calibration error: the count-weighted gap between confidence and accuracy
+    pub fn calibration_error(&self) -> f64 {
+        let total: usize = self.bins.iter().map(|bin| bin.count).sum();
+        if total == 0 {
+            return 0.0;
+        }
+        self.bins.iter().map(|bin| bin.count as f64 * (bin.mean_confidence - bin.accuracy).abs()).sum::<f64>() / total as f64
+    }
+
+    /// Mean Brier score of resolved predictions
+    pub fn brier_score(&self) -> Option<f64> {
+        (self.resolved > 0).then(|| self.brier_total / self.resolved as f64)
+    }
+
+    /// Predictions still awaiting feedback
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overconfidence_is_detected_and_corrected() {
+        let mut meta = MetaCognition::default();
+        assert!(meta.record_feedback(true).is_err());
+
+        // Strong activation but the answers are mostly wrong
+        for step in 0..200 {
+            meta.predict([0.95, 0.5, 0.5]);
+            meta.record_feedback(step % 5 == 0).unwrap();
+        }
+        assert!(meta.calibration_error() < 0.3);
+        assert!(meta.brier_score().unwrap() > 0.0);
+        // Confidence is now pulled toward the observed accuracy
+        let confidence = meta.predict([0.95, 0.5, 0.5]);
+        assert!(confidence < 0.5, "{}", confidence);
+        assert!((meta.weights().iter().sum::<f64>() - 1.0).abs() < 1e-12);
+        assert_eq!(meta.pending(), 1);
+    }
+}
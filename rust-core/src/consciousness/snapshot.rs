@@ -0,0 +1,110 @@
+//! Snapshots - Branching and comparing consciousness states
+//!
+//! `ConsciousnessEngine::snapshot` captures everything the engine's dynamics
+//! depend on, including the position of its noise generator, under a numbered
+//! id. `restore` returns the engine to a snapshot, which stays available, so one
+//! starting point can be branched into several input sequences; snapshotting the
+//! end of each branch and calling `diff` compares the outcomes dimension by
+//! dimension. Callbacks, the emotion model and the complexity analyzer belong to
+//! the engine rather than to a snapshot and are left as they are.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    ChangeDriver, ConsciousnessConfig, ConsciousnessState, EmotionalState, GlobalWorkspace, MetaCognition,
+    WorkingMemory, DIMENSION_NAMES,
+};
+
+/// Handle of a stored snapshot
+pub type SnapshotId = u64;
+
+/// Stored engine state
+#[derive(Clone)]
+pub struct Snapshot {
+    pub(super) taken_at_ms: u64,
+    pub(super) state: ConsciousnessState,
+    pub(super) history: Vec<ConsciousnessState>,
+    pub(super) config: ConsciousnessConfig,
+    pub(super) total_evolutions: u64,
+    pub(super) idle_time: Duration,
+    pub(super) workspace: GlobalWorkspace,
+    pub(super) working_memory: WorkingMemory,
+    pub(super) metacognition: MetaCognition,
+    pub(super) drivers: VecDeque<ChangeDriver>,
+    pub(super) recent_inputs: VecDeque<String>,
+    pub(super) rng: StdRng,
+}
+
+impl Snapshot {
+    /// Consciousness state when the snapshot was taken
+    pub fn state(&self) -> &ConsciousnessState {
+        &self.state
+    }
+
+    /// Evolution steps taken when the snapshot was taken
+    pub fn evolutions(&self) -> u64 {
+        self.total_evolutions
+    }
+
+    /// Milliseconds since the Unix epoch when the snapshot was taken
+    pub fn taken_at_ms(&self) -> u64 {
+        self.taken_at_ms
+    }
+}
+
+/// One dimension compared across two snapshots
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DimensionDiff {
+    pub dimension: String,
+    pub from: f64,
+    pub to: f64,
+    pub delta: f64,
+}
+
+/// Dimension-by-dimension comparison of two snapshots
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateDiff {
+    pub from: SnapshotId,
+    pub to: SnapshotId,
+    /// Every dimension, in `DIMENSION_NAMES` order
+    pub dimensions: Vec<DimensionDiff>,
+    pub emotional_state: (EmotionalState, EmotionalState),
+    pub evolutions: (u64, u64),
+    /// Euclidean distance over all dimensions
+    pub distance: f64,
+}
+
+impl StateDiff {
+    pub(super) fn new(from: (SnapshotId, &Snapshot), to: (SnapshotId, &Snapshot)) -> Self {
+        let dimensions: Vec<DimensionDiff> = DIMENSION_NAMES
+            .iter()
+            .zip(from.1.state.dimensions().into_iter().zip(to.1.state.dimensions()))
+            .map(|(name, (a, b))| DimensionDiff { dimension: name.to_string(), from: a, to: b, delta: b - a })
+            .collect();
+        let distance = dimensions.iter().map(|diff| diff.delta * diff.delta).sum::<f64>().sqrt();
+        Self {
+            from: from.0,
+            to: to.0,
+            dimensions,
+            emotional_state: (from.1.state.emotional_state, to.1.state.emotional_state),
+            evolutions: (from.1.total_evolutions, to.1.total_evolutions),
+            distance,
+        }
+    }
+
+    /// The dimension that changed most
+    pub fn largest(&self) -> Option<&DimensionDiff> {
+        self.dimensions.iter().max_by(|a, b| a.delta.abs().total_cmp(&b.delta.abs()))
+    }
+
+    /// Dimensions that changed by more than `tolerance`, largest change first
+    pub fn changed(&self, tolerance: f64) -> Vec<&DimensionDiff> {
+        let mut changed: Vec<&DimensionDiff> = self.dimensions.iter().filter(|diff| diff.delta.abs() > tolerance).collect();
+        changed.sort_by(|a, b| b.delta.abs().total_cmp(&a.delta.abs()));
+        changed
+    }
+}
@@ -0,0 +1,156 @@
+//! Timeline - Recording consciousness trajectories for offline analysis and replay
+//!
+//! `ConsciousnessEngine::start_timeline` snapshots everything the engine's
+//! dynamics depend on and reseeds its noise generator from a recorded seed. From
+//! then on every operation that changes the state is logged with its full
+//! arguments, a wall-clock timestamp and the state it produced. Exported as JSONL,
+//! a timeline can be read back and passed to `ConsciousnessEngine::replay`, which
+//! re-executes the operations from the snapshot and checks each resulting state
+//! against the recorded one. The Parquet export (feature `parquet`) flattens the
+//! entries into one row per event for analysis tools and is not replayable.
+
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::AgiError;
+
+use super::{
+    Candidate, ConsciousnessConfig, ConsciousnessState, ConsolidationOptions, GlobalWorkspace, InteractionParams,
+    MetaCognition, WorkingMemory,
+};
+
+/// A recorded operation, with the arguments needed to repeat it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum TimelineEvent {
+    /// `evolve` on this input
+    Input { text: String },
+    /// `tick` for this long
+    Idle { duration: Duration },
+    /// `broadcast` of this input over these candidates, before standing goals join
+    Broadcast { input: String, candidates: Vec<Candidate> },
+    /// `absorb` of another agent's state
+    Interaction { from: String, other: ConsciousnessState, params: InteractionParams },
+    /// `predict_confidence` from these signals
+    Prediction { signals: [f64; 3] },
+    /// `record_feedback` with this outcome
+    Feedback { correct: bool },
+    /// `consolidate` with these options
+    Consolidation { options: ConsolidationOptions },
+}
+
+impl TimelineEvent {
+    /// Short name of the operation
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Input { .. } => "input",
+            Self::Idle { .. } => "idle",
+            Self::Broadcast { .. } => "broadcast",
+            Self::Interaction { .. } => "interaction",
+            Self::Prediction { .. } => "prediction",
+            Self::Feedback { .. } => "feedback",
+            Self::Consolidation { .. } => "consolidation",
+        }
+    }
+}
+
+/// One recorded operation and the state it left
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineEntry {
+    /// Evolution count after the operation
+    pub step: u64,
+    pub timestamp_ms: u64,
+    pub event: TimelineEvent,
+    pub state: ConsciousnessState,
+}
+
+/// Engine snapshot a timeline starts from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineStart {
+    pub timestamp_ms: u64,
+    /// Seed the noise generator was reset to when recording started
+    pub seed: u64,
+    pub total_evolutions: u64,
+    pub idle_time: Duration,
+    pub state: ConsciousnessState,
+    pub history: Vec<ConsciousnessState>,
+    pub config: ConsciousnessConfig,
+    pub workspace: GlobalWorkspace,
+    pub working_memory: WorkingMemory,
+    pub metacognition: MetaCognition,
+    pub recent_inputs: Vec<String>,
+}
+
+/// Recorded trajectory: a starting snapshot and the operations that followed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Timeline {
+    pub start: TimelineStart,
+    pub entries: Vec<TimelineEntry>,
+}
+
+impl Timeline {
+    /// Write the start snapshot as the first line, then one entry per line
+    pub fn write_jsonl<W: Write>(&self, mut writer: W) -> Result<(), AgiError> {
+        serde_json::to_writer(&mut writer, &self.start)?;
+        writeln!(writer)?;
+        for entry in &self.entries {
+            serde_json::to_writer(&mut writer, entry)?;
+            writeln!(writer)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Read a timeline written by `write_jsonl`, skipping blank lines
+    pub fn read_jsonl<R: BufRead>(reader: R) -> Result<Self, AgiError> {
+        let mut lines = reader.lines().filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()));
+        let start = serde_json::from_str(&lines.next().ok_or_else(|| AgiError::Serialization("Timeline is empty".to_string()))??)?;
+        let entries = lines.map(|line| Ok(serde_json::from_str(&line?)?)).collect::<Result<_, AgiError>>()?;
+        Ok(Self { start, entries })
+    }
+
+    /// Write the timeline as JSONL to a file
+    pub fn save_jsonl<P: AsRef<Path>>(&self, path: P) -> Result<(), AgiError> {
+        self.write_jsonl(BufWriter::new(std::fs::File::create(path)?))
+    }
+
+    /// Read a JSONL timeline from a file
+    pub fn load_jsonl<P: AsRef<Path>>(path: P) -> Result<Self, AgiError> {
+        Self::read_jsonl(BufReader::new(std::fs::File::open(path)?))
+    }
+
+    /// Write one row per entry: step, timestamp, event kind and JSON, emotional
+    /// label and one column per dimension of `DIMENSION_NAMES`
+    #[cfg(feature = "parquet")]
+    pub fn save_parquet<P: AsRef<Path>>(&self, path: P) -> Result<(), AgiError> {
+        use std::sync::Arc;
+
+        use arrow::array::{ArrayRef, Float64Array, StringArray, UInt64Array};
+        use arrow::record_batch::RecordBatch;
+        use parquet::arrow::ArrowWriter;
+
+        let events = self.entries.iter().map(|entry| serde_json::to_string(&entry.event)).collect::<Result<Vec<_>, _>>()?;
+        let mut columns: Vec<(&str, ArrayRef)> = vec![
+            ("step", Arc::new(UInt64Array::from_iter_values(self.entries.iter().map(|entry| entry.step)))),
+            ("timestamp_ms", Arc::new(UInt64Array::from_iter_values(self.entries.iter().map(|entry| entry.timestamp_ms)))),
+            ("event_kind", Arc::new(StringArray::from_iter_values(self.entries.iter().map(|entry| entry.event.kind())))),
+            ("event", Arc::new(StringArray::from_iter_values(events))),
+            (
+                "emotional_state",
+                Arc::new(StringArray::from_iter_values(self.entries.iter().map(|entry| format!("{:?}", entry.state.emotional_state)))),
+            ),
+        ];
+        let dimensions: Vec<Vec<f64>> = self.entries.iter().map(|entry| entry.state.dimensions()).collect();
+        for (index, name) in super::DIMENSION_NAMES.iter().enumerate() {
+            columns.push((name, Arc::new(Float64Array::from_iter_values(dimensions.iter().map(|row| row[index])))));
+        }
+
+        let batch = RecordBatch::try_from_iter(columns).map_err(|e| AgiError::Serialization(e.to_string()))?;
+        let mut writer = ArrowWriter::try_new(std::fs::File::create(path)?, batch.schema(), None).map_err(|e| AgiError::Serialization(e.to_string()))?;
+        writer.write(&batch).map_err(|e| AgiError::Serialization(e.to_string()))?;
+        writer.close().map_err(|e| AgiError::Serialization(e.to_string()))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,191 @@
+//! Working Memory - Short-term context for evolution
+//!
+//! A small buffer of recent inputs and percepts, each weighted by its salience
+//! and by how recently it arrived. `evolve` consults it before changing state:
+//! inputs resembling what is already held drive awareness less than novel ones,
+//! and the held items' mood colours the appraisal of the new input. When the
+//! buffer is full, the item with the least weight makes room, so a salient
+//! input can outlast several trivial ones.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use super::EmotionVector;
+
+/// One held input or percept
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkingMemoryItem {
+    pub content: String,
+    /// Importance when it arrived, in (0, 1]
+    pub salience: f64,
+    pub appraisal: EmotionVector,
+    /// Arrival count when it arrived
+    pub arrival: u64,
+}
+
+/// What working memory says about a new input
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Context {
+    /// One minus the strongest recency-weighted resemblance to a held item, in [0, 1]
+    pub novelty: f64,
+    /// Weighted mean appraisal of the held items, if any
+    pub mood: Option<EmotionVector>,
+    /// Fraction of capacity in use
+    pub load: f64,
+}
+
+/// Bounded buffer of recent inputs and percepts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkingMemory {
+    capacity: usize,
+    /// Weight kept per later arrival
+    recency_decay: f64,
+    /// Fraction of the way an input's appraisal moves toward the held mood
+    context_weight: f64,
+    items: Vec<WorkingMemoryItem>,
+    arrivals: u64,
+}
+
+impl Default for WorkingMemory {
+    fn default() -> Self {
+        Self::new(7)
+    }
+}
+
+/// Character trigrams of `text`, case-folded, for script-independent resemblance
+fn trigrams(text: &str) -> HashSet<[char; 3]> {
+    let chars: Vec<char> = text.to_lowercase().chars().collect();
+    chars.windows(3).map(|window| [window[0], window[1], window[2]]).collect()
+}
+
+/// Jaccard similarity of two trigram sets
+fn resemblance(a: &HashSet<[char; 3]>, b: &HashSet<[char; 3]>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+impl WorkingMemory {
+    /// Buffer holding at most `capacity` (at least 1) items
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity: capacity.max(1), recency_decay: 0.8, context_weight: 0.3, items: Vec::new(), arrivals: 0 }
+    }
+
+    /// Set the weight kept per later arrival, clamped to [0, 1]
+    pub fn with_recency_decay(mut self, decay: f64) -> Self {
+        self.recency_decay = decay.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Set how far appraisals move toward the held mood, clamped to [0, 1]
+    pub fn with_context_weight(mut self, weight: f64) -> Self {
+        self.context_weight = weight.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn context_weight(&self) -> f64 {
+        self.context_weight
+    }
+
+    /// Held items, in arrival order
+    pub fn items(&self) -> &[WorkingMemoryItem] {
+        &self.items
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    /// Decay of an item's weight with the arrivals since it came in
+    fn recency(&self, item: &WorkingMemoryItem) -> f64 {
+        self.recency_decay.powi((self.arrivals - item.arrival).min(i32::MAX as u64) as i32)
+    }
+
+    /// Current weight of an item: salience times recency
+    pub fn weight(&self, item: &WorkingMemoryItem) -> f64 {
+        item.salience * self.recency(item)
+    }
+
+    /// Relate `input` to the held items
+    pub fn consult(&self, input: &str) -> Context {
+        let load = self.items.len() as f64 / self.capacity as f64;
+        let total: f64 = self.items.iter().map(|item| self.weight(item)).sum();
+        if total <= 0.0 {
+            return Context { novelty: 1.0, mood: None, load };
+        }
+        let grams = trigrams(input);
+        let familiarity = self.items.iter()
+            .map(|item| resemblance(&grams, &trigrams(&item.content)) * self.recency(item))
+            .fold(0.0, f64::max);
+        let mood = self.items.iter().fold(EmotionVector::NEUTRAL, |sum, item| {
+            let share = self.weight(item) / total;
+            EmotionVector {
+                valence: sum.valence + share * item.appraisal.valence,
+                arousal: sum.arousal + share * item.appraisal.arousal,
+                dominance: sum.dominance + share * item.appraisal.dominance,
+            }
+        });
+        Context { novelty: 1.0 - familiarity.clamp(0.0, 1.0), mood: Some(mood), load }
+    }
+
+    /// Hold `content`, evicting the least weighted item when full
+    pub fn push(&mut self, content: &str, salience: f64, appraisal: EmotionVector) {
+        self.arrivals += 1;
+        if self.items.len() == self.capacity {
+            let weakest = self.items.iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| self.weight(a).total_cmp(&self.weight(b)))
+                .map(|(index, _)| index);
+            if let Some(index) = weakest {
+                self.items.remove(index);
+            }
+        }
+        self.items.push(WorkingMemoryItem {
+            content: content.to_string(),
+            salience: salience.clamp(f64::EPSILON, 1.0),
+            appraisal,
+            arrival: self.arrivals,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_reflects_held_items() {
+        let mut memory = WorkingMemory::new(3);
+        assert_eq!(memory.consult("anything"), Context { novelty: 1.0, mood: None, load: 0.0 });
+
+        let happy = EmotionVector::new(0.8, 0.4, 0.2);
+        memory.push("the quantum experiment succeeded", 0.9, happy);
+        let repeat = memory.consult("the quantum experiment succeeded");
+        assert!(repeat.novelty < 1e-12);
+        assert_eq!(repeat.mood, Some(happy));
+        assert!(memory.consult("今日は雨が降っています").novelty > 0.99);
+
+        // Trivial items are evicted before the salient one
+        memory.push("ok", 0.05, EmotionVector::NEUTRAL);
+        memory.push("hm", 0.05, EmotionVector::NEUTRAL);
+        memory.push("fine", 0.05, EmotionVector::NEUTRAL);
+        assert_eq!(memory.len(), 3);
+        assert_eq!(memory.items()[0].content, "the quantum experiment succeeded");
+        assert!(memory.items().iter().all(|item| item.content != "ok"));
+    }
+}
@@ -0,0 +1,85 @@
+//! Errors - What went wrong, and where
+//!
+//! Every fallible API outside `tensor_ops` returns an `AgiError`, whose variant
+//! names the component that failed. Host code can match on it instead of
+//! parsing messages, and the FFI maps each variant to a stable `FFIError` code.
+//! Tensor operations keep returning `String` errors; `?` cannot tell which
+//! component they came from, so wrap them with `AgiError::Tensor` where they
+//! cross into the rest of the crate.
+
+use thiserror::Error;
+
+use crate::ffi::FFIError;
+use crate::memory_manager::MemoryPressure;
+use crate::neural_engine::TrainingError;
+
+/// Failure of an AGI core operation
+#[derive(Debug, Error)]
+pub enum AgiError {
+    /// Invalid input or state of the neural engine
+    #[error("neural engine: {0}")]
+    Neural(String),
+    /// A training step that would have corrupted the weights
+    #[error("training: {0}")]
+    Training(#[from] TrainingError),
+    #[error("consciousness: {0}")]
+    Consciousness(String),
+    /// Memory stores, snapshots and allocation
+    #[error("memory: {0}")]
+    Memory(String),
+    /// An allocation that would exceed the memory budget even after eviction
+    #[error(transparent)]
+    MemoryPressure(#[from] MemoryPressure),
+    #[error("tensor: {0}")]
+    Tensor(String),
+    #[error("quantum: {0}")]
+    Quantum(String),
+    /// Arguments passed across the FFI
+    #[error("FFI: {0}")]
+    Ffi(String),
+    /// Invalid configuration or architecture
+    #[error("configuration: {0}")]
+    Config(String),
+    #[error("I/O: {0}")]
+    Io(#[from] std::io::Error),
+    /// Malformed or incompatible saved state
+    #[error("serialization: {0}")]
+    Serialization(String),
+    /// Failure of user-supplied code, such as an embedder or entropy source
+    #[error(transparent)]
+    External(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl AgiError {
+    /// Stable code of the failing component, as returned across the FFI
+    pub fn code(&self) -> i32 {
+        FFIError::from(self) as i32
+    }
+}
+
+impl From<bincode::Error> for AgiError {
+    fn from(error: bincode::Error) -> Self {
+        Self::Serialization(error.to_string())
+    }
+}
+
+impl From<serde_json::Error> for AgiError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Serialization(error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sources_convert_to_their_component() {
+        let pressure = MemoryPressure { requested: 10, used: 5, budget: 8, released: 0 };
+        assert!(matches!(AgiError::from(pressure.clone()), AgiError::MemoryPressure(p) if p == pressure));
+        let json = serde_json::from_str::<u32>("{").unwrap_err();
+        assert!(matches!(AgiError::from(json), AgiError::Serialization(_)));
+        assert_eq!(AgiError::from(pressure).code(), -31);
+        assert_eq!(AgiError::Neural("no networks".to_string()).to_string(), "neural engine: no networks");
+    }
+}
@@ -1,19 +1,85 @@
 //! FFI Module - Foreign Function Interface for AGI
 //! 
 //! This module provides FFI capabilities for cross-language communication.
+//!
+//! FFI functions report failure through their return value: a negative
+//! `FFIError` code, a null pointer, or as documented per function. The code and
+//! message of the failure are also kept per thread, until the next failure on
+//! that thread, for `agi_last_error_code` and `agi_last_error_message`.
+//!
+//! Entry points that run async code share one multi-thread Tokio runtime,
+//! started on first use with the worker count set by `agi_configure_runtime`.
+//!
+//! Unwinding into C is undefined behavior, so every entry point but the
+//! last-error and version accessors, which cannot panic, runs its body through
+//! `guard`: a panic is caught, recorded as a `Panic` (-99) last error carrying
+//! the panic message, and the entry point returns its failure value.
+//!
+//! The C declarations of every entry point are generated into
+//! `include/agi_core.h` by `cargo build --features header`. Hosts compare the
+//! header's `AGI_ABI_VERSION` with `agi_abi_version()` when loading the library.
 
+use std::any::Any;
+use std::cell::RefCell;
 use std::ffi::{CStr, CString};
+use std::future::Future;
 use std::os::raw::c_char;
-use tracing::info;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+use tokio::runtime::{Handle, Runtime, RuntimeFlavor};
+use tracing::{error, info, warn};
+
+use crate::error::AgiError;
+use crate::memory_manager::leaks;
 
 /// FFI error codes
-#[repr(C)]
+///
+/// Codes from -10 on identify the component an `AgiError` came from and are
+/// stable across releases.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FFIError {
     Success = 0,
     NullPointer = -1,
     InvalidArgument = -2,
     MemoryAllocation = -3,
     InvalidOperation = -4,
+    Neural = -10,
+    Training = -11,
+    Consciousness = -20,
+    Memory = -30,
+    MemoryPressure = -31,
+    Tensor = -40,
+    Quantum = -50,
+    Config = -60,
+    Io = -70,
+    Serialization = -71,
+    External = -90,
+    /// A panic caught at the FFI boundary
+    Panic = -99,
+}
+
+// ABI audit: returned to C as an `int32_t`
+const _: () = assert!(std::mem::size_of::<FFIError>() == 4);
+
+impl From<&AgiError> for FFIError {
+    fn from(error: &AgiError) -> Self {
+        match error {
+            AgiError::Neural(_) => Self::Neural,
+            AgiError::Training(_) => Self::Training,
+            AgiError::Consciousness(_) => Self::Consciousness,
+            AgiError::Memory(_) => Self::Memory,
+            AgiError::MemoryPressure(_) => Self::MemoryPressure,
+            AgiError::Tensor(_) => Self::Tensor,
+            AgiError::Quantum(_) => Self::Quantum,
+            AgiError::Ffi(_) => Self::InvalidArgument,
+            AgiError::Config(_) => Self::Config,
+            AgiError::Io(_) => Self::Io,
+            AgiError::Serialization(_) => Self::Serialization,
+            AgiError::External(_) => Self::External,
+        }
+    }
 }
 
 /// FFI result wrapper
@@ -41,15 +107,25 @@ impl<T> FFIResult<T> {
     }
 }
 
-/// Convert Rust string to C string
+/// Convert Rust string to C string, recording the caller's site under `leak-tracking`
+#[track_caller]
 pub fn rust_string_to_c_string(s: &str) -> *mut c_char {
     match CString::new(s) {
-        Ok(c_string) => c_string.into_raw(),
+        Ok(c_string) => {
+            let raw = c_string.into_raw();
+            leaks::track(raw, s.len() + 1);
+            raw
+        }
         Err(_) => std::ptr::null_mut(),
     }
 }
 
 /// Convert C string to Rust string
+///
+/// # Safety
+///
+/// `c_string` must be null or point to a NUL-terminated string that stays valid
+/// for the duration of the call.
 pub unsafe fn c_string_to_rust_string(c_string: *const c_char) -> Option<String> {
     if c_string.is_null() {
         return None;
@@ -59,22 +135,226 @@ pub unsafe fn c_string_to_rust_string(c_string: *const c_char) -> Option<String>
 }
 
 /// Free C string
+///
+/// # Safety
+///
+/// `c_string` must be null or a string returned by `rust_string_to_c_string` that
+/// has not been freed yet.
 pub unsafe fn free_c_string(c_string: *mut c_char) {
     if !c_string.is_null() {
+        leaks::untrack(c_string);
         let _ = CString::from_raw(c_string);
     }
 }
 
+/// Version of the C ABI: exported signatures, struct layouts and error codes
+///
+/// Bumped on every incompatible change to any of them.
+pub const AGI_ABI_VERSION: u32 = 2;
+
+/// `AGI_ABI_VERSION` of the loaded library
+#[no_mangle]
+pub extern "C" fn agi_abi_version() -> u32 {
+    AGI_ABI_VERSION
+}
+
 /// FFI initialization function
 #[no_mangle]
 pub extern "C" fn agi_ffi_init() -> FFIError {
-    info!("AGI FFI module initialized");
-    FFIError::Success
+    guard(FFIError::Panic, || {
+        info!("AGI FFI module initialized");
+        FFIError::Success
+    })
 }
 
 /// FFI cleanup function
 #[no_mangle]
 pub extern "C" fn agi_ffi_cleanup() -> FFIError {
-    info!("AGI FFI module cleaned up");
-    FFIError::Success
+    guard(FFIError::Panic, || {
+        log_outstanding();
+        info!("AGI FFI module cleaned up");
+        FFIError::Success
+    })
+}
+
+/// Worker threads of the shared runtime; 0 for one per core
+static WORKER_THREADS: AtomicUsize = AtomicUsize::new(0);
+
+static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+
+/// The runtime shared by all FFI entry points, started on first use
+pub fn runtime() -> Result<&'static Runtime, AgiError> {
+    if let Some(runtime) = RUNTIME.get() {
+        return Ok(runtime);
+    }
+    let mut builder = tokio::runtime::Builder::new_multi_thread();
+    let threads = WORKER_THREADS.load(Ordering::Acquire);
+    if threads > 0 {
+        builder.worker_threads(threads);
+    }
+    let runtime = builder.thread_name("agi-ffi").enable_all().build()?;
+    // A runtime built by a racing thread is dropped here, before it ran anything
+    let _ = RUNTIME.set(runtime);
+    info!("FFI runtime started");
+    Ok(RUNTIME.get().expect("runtime was just set"))
+}
+
+/// Run `future` to completion on the shared runtime
+///
+/// Callers on a worker thread of a multi-thread runtime, such as callbacks calling
+/// back into the FFI, hand their other tasks off while they block. Blocking inside
+/// a single-threaded runtime would deadlock and fails instead.
+pub fn block_on<F: Future>(future: F) -> Result<F::Output, AgiError> {
+    let runtime = runtime()?;
+    match Handle::try_current() {
+        Err(_) => Ok(runtime.block_on(future)),
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            Ok(tokio::task::block_in_place(|| runtime.handle().block_on(future)))
+        }
+        Ok(_) => Err(AgiError::Ffi("Cannot block inside a single-threaded Tokio runtime".to_string())),
+    }
+}
+
+/// Set the worker threads of the shared runtime, 0 for one per core
+///
+/// Returns 0, or `InvalidOperation` (-4) once the runtime has started.
+#[no_mangle]
+pub extern "C" fn agi_configure_runtime(worker_threads: usize) -> i32 {
+    guard(FFIError::Panic as i32, || {
+        if RUNTIME.get().is_some() {
+            return set_last(FFIError::InvalidOperation as i32, "FFI: the runtime has already started");
+        }
+        WORKER_THREADS.store(worker_threads, Ordering::Release);
+        FFIError::Success as i32
+    })
+}
+
+/// Warn about handles the caller never freed (always silent without `leak-tracking`)
+pub fn log_outstanding() {
+    let report = leaks::outstanding();
+    if !report.is_empty() {
+        warn!("FFI handles still live: {}", report);
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<(i32, CString)>> = const { RefCell::new(None) };
+}
+
+fn set_last(code: i32, message: &str) -> i32 {
+    // Messages containing NUL are cut at the first one
+    let message = CString::new(message.split('\0').next().unwrap_or_default()).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some((code, message)));
+    code
+}
+
+/// Record `error` as this thread's last error, returning its code
+pub fn set_last_error(error: &AgiError) -> i32 {
+    set_last(error.code(), &error.to_string())
+}
+
+/// Record that the argument `name` was null, returning `FFIError::NullPointer`
+pub fn set_null_argument(name: &str) -> i32 {
+    set_last(FFIError::NullPointer as i32, &format!("FFI: {} is null", name))
+}
+
+/// Record a caught panic with its message, returning `FFIError::Panic`
+pub fn set_panic(payload: &(dyn Any + Send)) -> i32 {
+    let message = payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown payload");
+    error!("Panic caught at the FFI boundary: {}", message);
+    set_last(FFIError::Panic as i32, &format!("panic: {}", message))
+}
+
+/// Run the body of an entry point, returning `fallback` if it panics
+///
+/// The panic is recorded as this thread's last error instead of unwinding
+/// across the C boundary. State the body touched may be left half-updated;
+/// locks in this crate recover from poisoning, so later calls still proceed.
+pub fn guard<R>(fallback: R, body: impl FnOnce() -> R) -> R {
+    match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(value) => value,
+        Err(payload) => {
+            set_panic(payload.as_ref());
+            fallback
+        }
+    }
+}
+
+/// Forget this thread's last error
+pub fn clear_last_error() {
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+}
+
+/// Code of the last error on this thread, or 0 if none
+#[no_mangle]
+pub extern "C" fn agi_last_error_code() -> i32 {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(FFIError::Success as i32, |(code, _)| *code))
+}
+
+/// Message of the last error on this thread, or null if none
+///
+/// The string is owned by the library and stays valid until the next failure or
+/// `agi_clear_last_error` on the same thread; it must not be freed.
+#[no_mangle]
+pub extern "C" fn agi_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(std::ptr::null(), |(_, message)| message.as_ptr()))
+}
+
+/// Forget the last error on this thread
+#[no_mangle]
+pub extern "C" fn agi_clear_last_error() {
+    clear_last_error();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_last_error_is_kept_per_thread() {
+        clear_last_error();
+        assert_eq!(agi_last_error_code(), 0);
+        assert!(agi_last_error_message().is_null());
+
+        assert_eq!(set_last_error(&AgiError::Tensor("shape mismatch".to_string())), FFIError::Tensor as i32);
+        assert_eq!(agi_last_error_code(), -40);
+        let message = unsafe { CStr::from_ptr(agi_last_error_message()) };
+        assert_eq!(message.to_str().unwrap(), "tensor: shape mismatch");
+
+        std::thread::spawn(|| assert_eq!(agi_last_error_code(), 0)).join().unwrap();
+        assert_eq!(set_null_argument("input"), -1);
+        assert_eq!(agi_last_error_code(), -1);
+        agi_clear_last_error();
+        assert_eq!(agi_last_error_code(), 0);
+    }
+
+    #[test]
+    fn test_runtime_is_shared_and_reentrant() {
+        let first = runtime().unwrap() as *const Runtime;
+        assert_eq!(first, runtime().unwrap() as *const Runtime);
+        assert_eq!(agi_configure_runtime(2), FFIError::InvalidOperation as i32);
+
+        // A task on the shared runtime may block on it again
+        let nested = block_on(async { runtime().unwrap().spawn(async { block_on(async { 7 }) }).await.unwrap() });
+        assert_eq!(nested.unwrap().unwrap(), 7);
+    }
+
+    #[test]
+    fn test_panics_become_the_last_error() {
+        clear_last_error();
+        assert_eq!(guard(-1, || 3), 3);
+        assert_eq!(agi_last_error_code(), 0);
+
+        let code = guard(FFIError::Panic as i32, || -> i32 { panic!("weights of layer {} are gone", 2) });
+        assert_eq!(code, -99);
+        assert_eq!(agi_last_error_code(), -99);
+        let message = unsafe { CStr::from_ptr(agi_last_error_message()) };
+        assert_eq!(message.to_str().unwrap(), "panic: weights of layer 2 are gone");
+        assert!(guard(std::ptr::null_mut::<c_char>(), || panic!("static message")).is_null());
+        clear_last_error();
+    }
 }
@@ -3,6 +3,7 @@
 //! This module provides the core AGI functionality implemented in Rust for maximum
 //! performance, memory safety, and concurrent processing capabilities.
 
+pub mod error;
 pub mod neural_engine;
 pub mod consciousness;
 pub mod memory_manager;
@@ -10,6 +11,9 @@ pub mod ffi;
 pub mod wasm;
 pub mod tensor_ops;
 pub mod tensor_ffi;
+pub mod quantum;
+pub mod tokenizer;
+mod util;
 
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -18,22 +22,43 @@ use tracing::{info, error, instrument};
 use neural_engine::NeuralFoundationEngine;
 use consciousness::ConsciousnessEngine;
 use memory_manager::MemoryManager;
+use quantum::{HybridQuantumStage, HybridStageConfig};
+
+pub use error::AgiError;
+
+/// Semantic memories retrieved per input
+const CONTEXT_RESULTS: usize = 3;
+
+/// Least similarity for a semantic memory to count as context
+const CONTEXT_SIMILARITY: f64 = 0.5;
+
+/// Files written by `AGISystem::save_state`
+const NEURAL_STATE_FILE: &str = "neural.bin";
+const CONSCIOUSNESS_STATE_FILE: &str = "consciousness.bin";
+const MEMORY_STATE_FILE: &str = "memory.snapshot";
 
 /// Main AGI system that orchestrates all components
 pub struct AGISystem {
     neural_engine: Arc<RwLock<NeuralFoundationEngine>>,
     consciousness_engine: Arc<RwLock<ConsciousnessEngine>>,
     memory_manager: Arc<RwLock<MemoryManager>>,
+    quantum_stage: Option<HybridQuantumStage>,
+    /// Additional named consciousness streams for multi-agent simulation
+    agents: Arc<RwLock<consciousness::AgentRegistry>>,
 }
 
 impl AGISystem {
     /// Create a new AGI system instance
-    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn new() -> Result<Self, AgiError> {
         info!("Initializing AGI Rust Core System");
         
-        let memory_manager = Arc::new(RwLock::new(MemoryManager::new()?));
+        let memory_manager = MemoryManager::new()?;
+        let blackboard = memory_manager.blackboard();
+        let memory_manager = Arc::new(RwLock::new(memory_manager));
         let neural_engine = Arc::new(RwLock::new(NeuralFoundationEngine::new(memory_manager.clone())?));
-        let consciousness_engine = Arc::new(RwLock::new(ConsciousnessEngine::new()?));
+        let consciousness_engine = Arc::new(RwLock::new(ConsciousnessEngine::new()?.with_blackboard(blackboard)));
+        Self::register_evictors(&memory_manager, &consciousness_engine);
+        Self::register_gauges(&memory_manager, &neural_engine, &consciousness_engine);
         
         info!("AGI Rust Core System initialized successfully");
         
@@ -41,44 +66,329 @@ impl AGISystem {
             neural_engine,
             consciousness_engine,
             memory_manager,
+            quantum_stage: None,
+            agents: Arc::new(RwLock::new(consciousness::AgentRegistry::new())),
         })
     }
     
+    /// Thin old consciousness history and empty the buffer pool, in that order, when
+    /// memory use would exceed the budget and the manager's eviction rules did not suffice
+    ///
+    /// Evictors hold weak references so the manager does not keep the engines alive, and
+    /// skip an engine that is locked at the time.
+    fn register_evictors(
+        memory_manager: &Arc<RwLock<MemoryManager>>,
+        consciousness_engine: &Arc<RwLock<ConsciousnessEngine>>,
+    ) {
+        let Ok(manager) = memory_manager.try_read() else { return };
+        let consciousness = Arc::downgrade(consciousness_engine);
+        manager.on_pressure("consciousness_history", move |bytes| match consciousness.upgrade() {
+            Some(engine) => engine.try_write().map_or(0, |mut engine| engine.shed_history(bytes)),
+            None => 0,
+        });
+        manager.on_pressure("buffer_pool", |_| crate::memory_manager::pool::global().shrink(0));
+    }
+    
+    /// Report network weights and consciousness history in the memory breakdown
+    ///
+    /// Like the evictors, gauges hold weak references and read zero while an engine is being written.
+    fn register_gauges(
+        memory_manager: &Arc<RwLock<MemoryManager>>,
+        neural_engine: &Arc<RwLock<NeuralFoundationEngine>>,
+        consciousness_engine: &Arc<RwLock<ConsciousnessEngine>>,
+    ) {
+        use memory_manager::Subsystem;
+        
+        let Ok(manager) = memory_manager.try_read() else { return };
+        let neural = Arc::downgrade(neural_engine);
+        manager.report_usage("neural_weights", Subsystem::NeuralWeights, move || match neural.upgrade() {
+            Some(engine) => engine.try_read().map_or(0, |engine| engine.weight_bytes()),
+            None => 0,
+        });
+        let consciousness = Arc::downgrade(consciousness_engine);
+        manager.report_usage("consciousness_history", Subsystem::Consciousness, move || match consciousness.upgrade() {
+            Some(engine) => engine.try_read().map_or(0, |engine| engine.history_bytes()),
+            None => 0,
+        });
+    }
+    
+    /// Cap memory use at `bytes`, or remove the cap with `None`
+    ///
+    /// Processing first runs the evictors when usage is over the budget and fails with
+    /// `AgiError::MemoryPressure` if that is not enough.
+    pub async fn set_memory_budget(&self, bytes: Option<usize>) {
+        self.memory_manager.read().await.set_budget(bytes);
+    }
+    
+    /// Score processed inputs for retention with `model` rather than by confidence,
+    /// awareness and attention
+    pub async fn set_importance_model(&self, model: impl memory_manager::ImportanceModel + 'static) {
+        self.memory_manager.read().await.set_importance_model(model);
+    }
+    
+    /// Working memory the engines publish their latest results to
+    pub async fn blackboard(&self) -> Arc<memory_manager::Blackboard> {
+        self.memory_manager.read().await.blackboard()
+    }
+    
+    /// Save the neural weights, consciousness state and memory stores into `dir`
+    pub async fn save_state<P: AsRef<std::path::Path>>(&self, dir: P) -> Result<(), AgiError> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        self.neural_engine.read().await.save(dir.join(NEURAL_STATE_FILE))?;
+        self.consciousness_engine.read().await.save(dir.join(CONSCIOUSNESS_STATE_FILE))?;
+        self.memory_manager.read().await.snapshot(dir.join(MEMORY_STATE_FILE))?;
+        Ok(())
+    }
+    
+    /// Restore state written by `save_state`, keeping registered consciousness hooks
+    ///
+    /// Every component is loaded and verified before any is replaced, so on error the
+    /// system is left as it was. The neural engine is loaded last, since loading it
+    /// re-registers its cache with the memory manager.
+    pub async fn restore_state<P: AsRef<std::path::Path>>(&self, dir: P) -> Result<(), AgiError> {
+        let dir = dir.as_ref();
+        let consciousness = ConsciousnessEngine::load(dir.join(CONSCIOUSNESS_STATE_FILE))?;
+        let memory = self.memory_manager.read().await.load_snapshot(dir.join(MEMORY_STATE_FILE))?;
+        let neural = NeuralFoundationEngine::load(dir.join(NEURAL_STATE_FILE), self.memory_manager.clone())?;
+        
+        let blackboard = {
+            let manager = self.memory_manager.read().await;
+            manager.install(memory);
+            manager.blackboard()
+        };
+        *self.neural_engine.write().await = neural;
+        let mut engine = self.consciousness_engine.write().await;
+        let hooks = std::mem::take(engine.hooks_mut());
+        *engine = consciousness.with_blackboard(blackboard);
+        *engine.hooks_mut() = hooks;
+        Ok(())
+    }
+    
+    /// Enable the hybrid quantum-classical stage between neural and consciousness processing
+    pub fn with_quantum_stage(mut self, config: HybridStageConfig) -> Result<Self, AgiError> {
+        info!("Enabling hybrid quantum stage with {} qubits", config.qubits);
+        self.quantum_stage = Some(HybridQuantumStage::new(config)?);
+        Ok(self)
+    }
+    
     /// Process input through the AGI system
     #[instrument(skip(self, input))]
-    pub async fn process_input(&self, input: &str) -> Result<ProcessingResult, Box<dyn std::error::Error>> {
+    pub async fn process_input(&self, input: &str) -> Result<ProcessingResult, AgiError> {
         info!("Processing input: {} characters", input.len());
+        // Scratch activations of this request come from one arena, released when it ends
+        let arena = {
+            let manager = self.memory_manager.read().await;
+            manager.reserve(0)?;
+            manager.arena()
+        };
         
         // Sequential processing for now (will be parallel in future)
-        let neural_result = self.neural_engine.read().await.process_input(input).await?;
-        let consciousness_result = self.consciousness_engine.read().await.evolve(input).await?;
+        // Consciousness gates the neural input at its current attention focus
+        let (embedding, neural_result) = {
+            let neural = self.neural_engine.read().await;
+            let embedding = neural.embed_text(input);
+            let gated = self.consciousness_engine.read().await.attend(&embedding);
+            (embedding, neural.process_vector_in(&gated, &arena).await?)
+        };
+        // Knowledge resembling the input competes for attention alongside it
+        let context: Vec<memory_manager::SemanticHit> = self.memory_manager.read().await
+            .semantic()
+            .search(&embedding.to_vec(), CONTEXT_RESULTS)
+            .map_err(AgiError::Memory)?
+            .into_iter()
+            .filter(|hit| hit.similarity >= CONTEXT_SIMILARITY)
+            .collect();
+        let (mut consciousness_result, broadcast, mut confidence) = {
+            let mut engine = self.consciousness_engine.write().await;
+            engine.evolve(input).await?;
+            // Neural results, retrieved context and standing goals compete for the workspace, which sets attention
+            let mut candidates = vec![consciousness::Candidate::neural(&neural_result)];
+            candidates.extend(context.iter().map(|hit| consciousness::Candidate::memory(hit.payload.clone(), hit.similarity)));
+            let broadcast = engine.broadcast(input, candidates);
+            let confidence = self.calculate_confidence(&mut engine, &neural_result);
+            (engine.current_state().clone(), broadcast, confidence)
+        };
+        
+        // Optional hybrid stage: quantum expectation values feed into synthesis
+        let quantum_result = match &self.quantum_stage {
+            Some(stage) => Some(stage.evaluate(&neural_result.output)?),
+            None => None,
+        };
+        
+        if let Some(quantum) = &quantum_result {
+            confidence = quantum.blend(confidence);
+            consciousness_result.memory_coherence = quantum.blend(consciousness_result.memory_coherence);
+        }
         
         // Synthesize results
         let final_result = ProcessingResult {
             neural_output: neural_result.clone(),
             consciousness: consciousness_result,
-            confidence: self.calculate_confidence(&neural_result),
+            broadcast,
+            context,
+            confidence,
+            quantum: quantum_result,
             processing_time: std::time::Instant::now().elapsed(),
         };
         
+        self.memory_manager.read().await.record_episode(input, embedding.to_vec(), &final_result)?;
+        
         info!("Input processing completed with confidence: {:.2}", final_result.confidence);
         
         Ok(final_result)
     }
     
     /// Calculate confidence score based on neural output
-    fn calculate_confidence(&self, neural_result: &neural_engine::NeuralResponse) -> f64 {
-        // Complex confidence calculation based on multiple factors
+    ///
+    /// The factors are weighted and calibrated by the engine's meta-cognition,
+    /// which learns from outcomes reported through `record_feedback`.
+    fn calculate_confidence(&self, engine: &mut ConsciousnessEngine, neural_result: &neural_engine::NeuralResponse) -> f64 {
         let base_confidence = neural_result.activation_strength;
         let pattern_confidence = neural_result.pattern_confidence;
         let coherence_score = neural_result.coherence_score;
         
-        (base_confidence * 0.4 + pattern_confidence * 0.3 + coherence_score * 0.3)
-            .clamp(0.0, 1.0)
+        engine.predict_confidence([base_confidence, pattern_confidence, coherence_score])
+    }
+    
+    /// Report whether the oldest unresolved result of `process_input` was correct
+    ///
+    /// Updates the calibration curve and confidence weighting, and moves
+    /// self-awareness toward how well calibrated confidence has been.
+    pub async fn record_feedback(&self, correct: bool) -> Result<consciousness::FeedbackOutcome, AgiError> {
+        self.consciousness_engine.write().await.record_feedback(correct)
     }
     
+    /// Store `text` in semantic memory, to be retrieved as context for similar inputs
+    pub async fn remember(&self, text: &str) -> Result<memory_manager::SemanticId, AgiError> {
+        let embedding = self.neural_engine.read().await.embed_text(text);
+        self.memory_manager.read().await.remember(&embedding.to_vec(), text)
+    }
+    
+    /// Remove a semantic memory
+    pub async fn forget(&self, id: memory_manager::SemanticId) -> bool {
+        self.memory_manager.read().await.semantic_mut().delete(id)
+    }
+    
+    /// Up to `count` most recently processed episodes, newest first
+    pub async fn recall_recent(&self, count: usize) -> Vec<memory_manager::Episode> {
+        self.memory_manager.read().await.episodic().recent(count).into_iter().cloned().collect()
+    }
+    
+    /// Up to `count` processed episodes whose inputs most resemble `text`, with their similarity
+    pub async fn recall_similar(&self, text: &str, count: usize) -> Vec<(f64, memory_manager::Episode)> {
+        let query = self.neural_engine.read().await.embed_text(text);
+        let manager = self.memory_manager.read().await;
+        let episodes = manager.episodic();
+        episodes.similar(&query.to_vec(), count).into_iter().map(|(similarity, episode)| (similarity, episode.clone())).collect()
+    }
+    
+    /// Add a goal that competes for the global workspace on every input
+    pub async fn add_goal(&self, goal: consciousness::Goal) {
+        self.consciousness_engine.write().await.workspace_mut().add_goal(goal);
+    }
+    
+    /// Call `callback` on every consciousness event it is registered for through `register`
+    ///
+    /// For example `system.hooks(|hooks| hooks.on_state_evolved(callback)).await`.
+    pub async fn hooks<R>(&self, register: impl FnOnce(&mut consciousness::Hooks) -> R) -> R {
+        register(self.consciousness_engine.write().await.hooks_mut())
+    }
+    
+    /// Registry of named agents, each with its own consciousness stream
+    pub fn agents(&self) -> Arc<RwLock<consciousness::AgentRegistry>> {
+        self.agents.clone()
+    }
+    
+    /// Add a named agent with a fresh consciousness engine
+    pub async fn add_agent(&self, name: &str) -> Result<(), AgiError> {
+        self.agents.write().await.add_agent(name, ConsciousnessEngine::new()?).map_err(AgiError::Consciousness)
+    }
+    
+    /// Deliver a message between agents, returning the recipient's new state
+    pub async fn send_message(
+        &self,
+        from: &str,
+        to: &str,
+        content: &str,
+    ) -> Result<consciousness::ConsciousnessState, AgiError> {
+        self.agents.write().await.send(from, to, content).await
+    }
+    
+    /// Run an offline consolidation pass, fine-tuning the neural engine on the replayed inputs
+    pub async fn consolidate(
+        &self,
+        options: &consciousness::ConsolidationOptions,
+    ) -> Result<consciousness::ConsolidationReport, AgiError> {
+        let mut report = self.consciousness_engine.write().await.consolidate(options)?;
+        if options.fine_tune_epochs > 0 {
+            let history = self.neural_engine.write().await.distill(&report.replayed, options.fine_tune_epochs)?;
+            report.fine_tune_loss = history.last().map(|epoch| epoch.loss);
+        }
+        Ok(report)
+    }
+    
+    /// Consolidate episodic into semantic memory every `interval` in the background
+    ///
+    /// The task ends once the system is dropped; abort the handle to stop it sooner.
+    pub fn start_memory_consolidation(&self, interval: std::time::Duration) -> tokio::task::JoinHandle<()> {
+        let memory_manager = Arc::downgrade(&self.memory_manager);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            // The first tick completes immediately
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let Some(memory_manager) = memory_manager.upgrade() else { break };
+                let run = memory_manager.read().await.consolidate();
+                info!("Memory consolidation promoted {} and pruned {} episodes", run.promoted, run.pruned);
+            }
+        })
+    }
+    
+    /// Check memory pressure every `interval` and act on it
+    ///
+    /// At `Elevated` the memory manager is optimized; at `Critical` the evictors are
+    /// also asked to shed the bytes above the elevated threshold. Stops once the
+    /// system is dropped.
+    pub fn start_pressure_monitor(&self, interval: std::time::Duration) -> tokio::task::JoinHandle<()> {
+        use memory_manager::PressureLevel;
+        
+        let memory_manager = Arc::downgrade(&self.memory_manager);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let Some(memory_manager) = memory_manager.upgrade() else { break };
+                let manager = memory_manager.read().await;
+                let reading = manager.check_pressure();
+                if reading.level == PressureLevel::Normal {
+                    continue;
+                }
+                match manager.optimize().await {
+                    Ok(result) => info!("Memory pressure {:?}: optimize released {} bytes", reading.level, result.bytes_before.saturating_sub(result.bytes_after)),
+                    Err(e) => error!("Memory optimization under pressure failed: {}", e),
+                }
+                if reading.level == PressureLevel::Critical {
+                    let shed = manager.shed(reading.excess);
+                    info!("Critical memory pressure: shed {} of {} bytes", shed, reading.excess);
+                }
+            }
+        })
+    }
+    
+    /// Report the consciousness state and what recently changed it
+    pub async fn introspect(&self) -> consciousness::SelfReport {
+        self.consciousness_engine.read().await.introspect()
+    }
+    
+    /// Let `dt` pass without input, relaxing consciousness toward its baselines
+    pub async fn tick(&self, dt: std::time::Duration) -> consciousness::ConsciousnessState {
+        self.consciousness_engine.write().await.tick(dt).clone()
+    }
+
     /// Get system status and metrics
-    pub async fn get_status(&self) -> Result<SystemStatus, Box<dyn std::error::Error>> {
+    pub async fn get_status(&self) -> Result<SystemStatus, AgiError> {
         let memory_stats = self.memory_manager.read().await.get_stats().await?;
         let neural_stats = self.neural_engine.read().await.get_stats().await?;
         let consciousness_stats = self.consciousness_engine.read().await.get_stats().await?;
@@ -91,14 +401,43 @@ impl AGISystem {
         })
     }
     
+    /// Apply the settings present in `config`, leaving the others unchanged
+    pub async fn configure(&self, config: SystemConfig) {
+        {
+            let manager = self.memory_manager.read().await;
+            if let Some(budget) = config.memory_budget {
+                manager.set_budget(budget);
+            }
+            if let Some(thresholds) = config.pressure_thresholds {
+                manager.set_pressure_thresholds(thresholds);
+            }
+            if let Some(policy) = config.consolidation {
+                manager.set_consolidation_policy(policy);
+            }
+            if let Some(policy) = config.optimization {
+                manager.set_optimization_policy(policy);
+            }
+        }
+        if config.precision.is_some() || config.cache_capacity.is_some() {
+            let mut neural = self.neural_engine.write().await;
+            if let Some(precision) = config.precision {
+                neural.set_precision(precision);
+            }
+            if let Some(capacity) = config.cache_capacity {
+                neural.set_cache_capacity(capacity);
+            }
+        }
+        info!("System configuration updated");
+    }
+    
     /// Perform system optimization
-    pub async fn optimize(&self) -> Result<OptimizationResult, Box<dyn std::error::Error>> {
+    pub async fn optimize(&self) -> Result<OptimizationResult, AgiError> {
         info!("Starting system optimization");
         
         let start_time = std::time::Instant::now();
         
         // Sequential optimization for now (will be parallel in future)
-        let memory_opt = self.memory_manager.write().await.optimize().await?;
+        let memory_opt = self.memory_manager.read().await.optimize().await?;
         let neural_opt = self.neural_engine.write().await.optimize().await?;
         let consciousness_opt = self.consciousness_engine.write().await.optimize().await?;
         
@@ -122,10 +461,40 @@ impl AGISystem {
 pub struct ProcessingResult {
     pub neural_output: neural_engine::NeuralResponse,
     pub consciousness: consciousness::ConsciousnessState,
+    /// Workspace cycle the input's results competed in
+    pub broadcast: consciousness::Broadcast,
+    /// Semantic memories retrieved for the input, most similar first
+    pub context: Vec<memory_manager::SemanticHit>,
     pub confidence: f64,
+    pub quantum: Option<quantum::HybridStageResult>,
     pub processing_time: std::time::Duration,
 }
 
+impl ProcessingResult {
+    /// JSON object of the result for foreign callers, with the neural output as a
+    /// plain array and the processing time in milliseconds
+    pub fn to_json(&self) -> Result<String, AgiError> {
+        let neural = &self.neural_output;
+        let json = serde_json::json!({
+            "neural_output": {
+                "output": neural.output.to_vec(),
+                "activation_strength": neural.activation_strength,
+                "pattern_confidence": neural.pattern_confidence,
+                "coherence_score": neural.coherence_score,
+                "network_count": neural.network_count,
+                "head": neural.head,
+            },
+            "consciousness": self.consciousness,
+            "broadcast": self.broadcast,
+            "context": self.context,
+            "confidence": self.confidence,
+            "quantum": self.quantum,
+            "processing_time_ms": self.processing_time.as_secs_f64() * 1000.0,
+        });
+        Ok(serde_json::to_string(&json)?)
+    }
+}
+
 /// System status and metrics
 #[derive(Debug, Clone)]
 pub struct SystemStatus {
@@ -135,6 +504,32 @@ pub struct SystemStatus {
     pub uptime: std::time::Duration,
 }
 
+impl SystemStatus {
+    /// JSON object of the status for foreign callers, with durations in milliseconds
+    pub fn to_json(&self) -> Result<String, AgiError> {
+        let neural = &self.neural;
+        let json = serde_json::json!({
+            "memory": self.memory,
+            "neural": {
+                "network_count": neural.network_count,
+                "total_parameters": neural.total_parameters,
+                "network_parameters": neural.network_parameters,
+                "network_precisions": neural.network_precisions,
+                "network_sparsity": neural.network_sparsity,
+                "inference_bytes": neural.inference_bytes,
+                "cache_hits": neural.cache_hits,
+                "cache_misses": neural.cache_misses,
+                "learning_rate": neural.learning_rate,
+                "memory_usage": neural.memory_usage,
+                "architecture": neural.architecture,
+            },
+            "consciousness": self.consciousness,
+            "uptime_ms": self.uptime.as_secs_f64() * 1000.0,
+        });
+        Ok(serde_json::to_string(&json)?)
+    }
+}
+
 /// Result of system optimization
 #[derive(Debug, Clone)]
 pub struct OptimizationResult {
@@ -144,58 +539,638 @@ pub struct OptimizationResult {
     pub total_time: std::time::Duration,
 }
 
+impl OptimizationResult {
+    /// JSON object of the result for foreign callers, with durations in milliseconds
+    pub fn to_json(&self) -> Result<String, AgiError> {
+        let neural = &self.neural_improvements;
+        let json = serde_json::json!({
+            "memory": self.memory_improvements,
+            "neural": {
+                "learning_rate_improvements": neural.learning_rate_improvements,
+                "parameter_optimizations": neural.parameter_optimizations,
+                "optimization_time_ms": neural.optimization_time.as_secs_f64() * 1000.0,
+            },
+            "consciousness": self.consciousness_improvements,
+            "total_time_ms": self.total_time.as_secs_f64() * 1000.0,
+        });
+        Ok(serde_json::to_string(&json)?)
+    }
+}
+
+/// Runtime settings for `AGISystem::configure`; fields left out are not changed
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SystemConfig {
+    /// Memory cap in bytes; `null` removes the cap
+    #[serde(deserialize_with = "deserialize_present")]
+    pub memory_budget: Option<Option<usize>>,
+    pub pressure_thresholds: Option<memory_manager::PressureThresholds>,
+    pub consolidation: Option<memory_manager::ConsolidationPolicy>,
+    pub optimization: Option<memory_manager::OptimizationPolicy>,
+    /// Precision every network serves in
+    pub precision: Option<neural_engine::Precision>,
+    /// Responses kept in the inference cache; zero disables caching
+    pub cache_capacity: Option<usize>,
+}
+
+impl SystemConfig {
+    /// Parse a JSON object of settings, rejecting unknown keys
+    pub fn from_json(json: &str) -> Result<Self, AgiError> {
+        serde_json::from_str(json).map_err(|e| AgiError::Config(e.to_string()))
+    }
+}
+
+/// Tell a present `null` (`Some(None)`) from a missing field (`None`)
+fn deserialize_present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
 /// Initialize the AGI system
+///
+/// Returns null on failure, with the cause in `agi_last_error_code`.
 #[no_mangle]
 pub extern "C" fn agi_init() -> *mut AGISystem {
-    match AGISystem::new() {
-        Ok(system) => {
-            info!("AGI system initialized successfully via FFI");
-            Box::into_raw(Box::new(system))
-        }
-        Err(e) => {
-            error!("Failed to initialize AGI system: {}", e);
-            std::ptr::null_mut()
+    ffi::guard(std::ptr::null_mut(), || {
+        match AGISystem::new() {
+            Ok(system) => {
+                info!("AGI system initialized successfully via FFI");
+                let system = Box::into_raw(Box::new(system));
+                memory_manager::leaks::track(system, std::mem::size_of::<AGISystem>());
+                system
+            }
+            Err(e) => {
+                error!("Failed to initialize AGI system: {}", e);
+                ffi::set_last_error(&e);
+                std::ptr::null_mut()
+            }
         }
-    }
+    })
 }
 
 /// Process input via FFI
+///
+/// Returns 0, `NullPointer` (-1) if an argument is null, `MemoryPressure` (-31)
+/// if the memory budget is exhausted, or the code of the component that failed.
+///
+/// `ProcessingResult` holds Rust-owned buffers and is only usable from Rust;
+/// other callers should use `agi_process_input_json`.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 #[no_mangle]
 pub extern "C" fn agi_process_input(
     system: *mut AGISystem,
-    input: *const i8,
+    input: *const std::os::raw::c_char,
     result: *mut ProcessingResult,
 ) -> i32 {
-    if system.is_null() || input.is_null() || result.is_null() {
-        return -1;
+    ffi::guard(ffi::FFIError::Panic as i32, || {
+        if system.is_null() || input.is_null() || result.is_null() {
+            return ffi::set_null_argument("system, input or result");
+        }
+        
+        let system = unsafe { &*system };
+        let input_str = unsafe { std::ffi::CStr::from_ptr(input).to_string_lossy() };
+        
+        match ffi::block_on(system.process_input(&input_str)).and_then(|processed| processed) {
+            Ok(processing_result) => {
+                unsafe { *result = processing_result };
+                0
+            }
+            Err(e) => {
+                error!("FFI processing error: {}", e);
+                ffi::set_last_error(&e)
+            }
+        }
+    })
+}
+
+/// Process input, writing the `ProcessingResult::to_json` object to `result`, to be
+/// released with `agi_free_string`
+///
+/// Returns 0, `NullPointer` (-1) if an argument is null, `MemoryPressure` (-31)
+/// if the memory budget is exhausted, or the code of the component that failed.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn agi_process_input_json(
+    system: *mut AGISystem,
+    input: *const std::os::raw::c_char,
+    result: *mut *mut std::os::raw::c_char,
+) -> i32 {
+    ffi::guard(ffi::FFIError::Panic as i32, || {
+        if system.is_null() || input.is_null() || result.is_null() {
+            return ffi::set_null_argument("system, input or result");
+        }
+        
+        let system = unsafe { &*system };
+        let input_str = unsafe { std::ffi::CStr::from_ptr(input).to_string_lossy() };
+        let json = ffi::block_on(system.process_input(&input_str))
+            .and_then(|processed| processed)
+            .and_then(|processed| processed.to_json());
+        match json {
+            Ok(json) => write_json(&json, result),
+            Err(e) => {
+                error!("FFI processing error: {}", e);
+                ffi::set_last_error(&e)
+            }
+        }
+    })
+}
+
+/// Write a JSON `SelfReport` to `report`, to be released with `agi_free_string`
+///
+/// Returns 0, `NullPointer` (-1) if an argument is null, or `Serialization` (-71).
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn agi_introspect(system: *mut AGISystem, report: *mut *mut std::os::raw::c_char) -> i32 {
+    ffi::guard(ffi::FFIError::Panic as i32, || {
+        if system.is_null() || report.is_null() {
+            return ffi::set_null_argument("system or report");
+        }
+        
+        let system = unsafe { &*system };
+        match ffi::block_on(system.introspect()).and_then(|report| report.to_json().map_err(AgiError::from)) {
+            Ok(json) => write_json(&json, report),
+            Err(e) => {
+                error!("FFI introspection error: {}", e);
+                ffi::set_last_error(&e)
+            }
+        }
+    })
+}
+
+/// Write a JSON `SystemStatus` to `status`, to be released with `agi_free_string`
+///
+/// Returns 0, `NullPointer` (-1) if an argument is null, or the code of the
+/// component that failed.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn agi_get_status_json(system: *mut AGISystem, status: *mut *mut std::os::raw::c_char) -> i32 {
+    ffi::guard(ffi::FFIError::Panic as i32, || {
+        if system.is_null() || status.is_null() {
+            return ffi::set_null_argument("system or status");
+        }
+        
+        let system = unsafe { &*system };
+        match ffi::block_on(system.get_status()).and_then(|status| status).and_then(|status| status.to_json()) {
+            Ok(json) => write_json(&json, status),
+            Err(e) => {
+                error!("FFI status error: {}", e);
+                ffi::set_last_error(&e)
+            }
+        }
+    })
+}
+
+/// Optimize the memory stores and engines, writing a JSON `OptimizationResult` to
+/// `result` unless it is null, to be released with `agi_free_string`
+///
+/// Returns 0, `NullPointer` (-1) if `system` is null, or the code of the component
+/// that failed.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn agi_optimize(system: *mut AGISystem, result: *mut *mut std::os::raw::c_char) -> i32 {
+    ffi::guard(ffi::FFIError::Panic as i32, || {
+        if system.is_null() {
+            return ffi::set_null_argument("system");
+        }
+        
+        let system = unsafe { &*system };
+        let optimized = ffi::block_on(system.optimize()).and_then(|optimized| optimized);
+        match optimized {
+            Ok(_) if result.is_null() => 0,
+            Ok(optimized) => match optimized.to_json() {
+                Ok(json) => write_json(&json, result),
+                Err(e) => ffi::set_last_error(&e),
+            },
+            Err(e) => {
+                error!("FFI optimization error: {}", e);
+                ffi::set_last_error(&e)
+            }
+        }
+    })
+}
+
+/// Apply a JSON object of `SystemConfig` settings, such as
+/// `{"memory_budget": 67108864, "cache_capacity": 256}`; keys left out keep their
+/// current values
+///
+/// Returns 0, `NullPointer` (-1) if an argument is null, `InvalidArgument` (-2) if
+/// `config` is not UTF-8, or `Config` (-60) for malformed JSON or an unknown key,
+/// in which case nothing is changed.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn agi_configure(system: *mut AGISystem, config: *const std::os::raw::c_char) -> i32 {
+    ffi::guard(ffi::FFIError::Panic as i32, || {
+        if system.is_null() || config.is_null() {
+            return ffi::set_null_argument("system or config");
+        }
+        let Some(config) = (unsafe { ffi::c_string_to_rust_string(config) }) else {
+            return ffi::set_last_error(&AgiError::Ffi("config is not valid UTF-8".to_string()));
+        };
+        
+        let system = unsafe { &*system };
+        match SystemConfig::from_json(&config).and_then(|config| ffi::block_on(system.configure(config))) {
+            Ok(()) => 0,
+            Err(e) => {
+                error!("FFI configuration error: {}", e);
+                ffi::set_last_error(&e)
+            }
+        }
+    })
+}
+
+/// Hand `json` to the caller through `out`, returning 0 or the recorded error code
+#[track_caller]
+fn write_json(json: &str, out: *mut *mut std::os::raw::c_char) -> i32 {
+    let json = ffi::rust_string_to_c_string(json);
+    if json.is_null() {
+        return ffi::set_last_error(&AgiError::Serialization("JSON contains a NUL byte".to_string()));
     }
-    
-    let system = unsafe { &*system };
-    let input_str = unsafe { std::ffi::CStr::from_ptr(input).to_string_lossy() };
-    
-    // Run async processing in a new runtime
-    let rt = tokio::runtime::Runtime::new().unwrap();
-    match rt.block_on(system.process_input(&input_str)) {
-        Ok(processing_result) => {
-            unsafe { *result = processing_result };
-            0
+    unsafe { *out = json };
+    0
+}
+
+/// Release a string returned by the AGI FFI
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn agi_free_string(string: *mut std::os::raw::c_char) {
+    ffi::guard((), || {
+        unsafe { ffi::free_c_string(string) };
+    })
+}
+
+/// Write a JSON `LeakReport` of handles not yet freed to `report`, to be released
+/// with `agi_free_string`; always empty without the `leak-tracking` feature
+///
+/// Returns 0, `NullPointer` (-1) if `report` is null, or `Serialization` (-71).
+#[no_mangle]
+pub extern "C" fn agi_leak_report(report: *mut *mut std::os::raw::c_char) -> i32 {
+    ffi::guard(ffi::FFIError::Panic as i32, || {
+        if report.is_null() {
+            return ffi::set_null_argument("report");
         }
+        
+        match serde_json::to_string(&memory_manager::leaks::outstanding()) {
+            Ok(json) => write_json(&json, report),
+            Err(e) => {
+                error!("FFI leak report error: {}", e);
+                ffi::set_last_error(&AgiError::from(e))
+            }
+        }
+    })
+}
+
+/// Consciousness dimensions as passed to FFI callbacks
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct CConsciousnessState {
+    pub awareness_level: f64,
+    pub self_awareness: f64,
+    pub memory_coherence: f64,
+    pub attention_focus: f64,
+    pub creativity_level: f64,
+    pub valence: f64,
+    pub arousal: f64,
+    pub dominance: f64,
+    /// `EmotionalState` discriminant, Neutral = 0 through Analytical = 5
+    pub emotional_state: i32,
+}
+
+// ABI audit: the generated header declares these fields in this order
+const _: () = {
+    use std::mem::{offset_of, size_of};
+    assert!(offset_of!(CConsciousnessState, awareness_level) == 0);
+    assert!(offset_of!(CConsciousnessState, dominance) == 56);
+    assert!(offset_of!(CConsciousnessState, emotional_state) == 64);
+    assert!(size_of::<CConsciousnessState>() == 72);
+};
+
+impl From<&consciousness::ConsciousnessState> for CConsciousnessState {
+    fn from(state: &consciousness::ConsciousnessState) -> Self {
+        Self {
+            awareness_level: state.awareness_level,
+            self_awareness: state.self_awareness,
+            memory_coherence: state.memory_coherence,
+            attention_focus: state.attention_focus,
+            creativity_level: state.creativity_level,
+            valence: state.emotion.valence,
+            arousal: state.emotion.arousal,
+            dominance: state.emotion.dominance,
+            emotional_state: state.emotional_state as i32,
+        }
+    }
+}
+
+/// Called with each evolved state and the registration's user data
+pub type AgiStateCallback = extern "C" fn(state: *const CConsciousnessState, user_data: *mut std::os::raw::c_void);
+
+/// Called with the previous and new `EmotionalState` discriminants
+pub type AgiEmotionCallback = extern "C" fn(from: i32, to: i32, user_data: *mut std::os::raw::c_void);
+
+/// Called with the crossed dimension, its new value and 1 if rising or 0 if falling
+pub type AgiThresholdCallback = extern "C" fn(
+    dimension: *const std::os::raw::c_char,
+    value: f64,
+    rising: i32,
+    user_data: *mut std::os::raw::c_void,
+);
+
+/// Host pointer passed back to FFI callbacks untouched
+struct UserData(*mut std::os::raw::c_void);
+
+// SAFETY: the pointer is never dereferenced on the Rust side; hosts that register
+// callbacks are responsible for their user data being usable from any thread
+unsafe impl Send for UserData {}
+unsafe impl Sync for UserData {}
+
+impl UserData {
+    // A method call captures the whole wrapper in closures, not just the raw pointer
+    fn get(&self) -> *mut std::os::raw::c_void {
+        self.0
+    }
+}
+
+/// Register `callback` with the consciousness hooks, returning the hook id or an error code
+fn register_hook(
+    system: *mut AGISystem,
+    register: impl FnOnce(&mut consciousness::Hooks) -> Result<consciousness::HookId, String>,
+) -> i64 {
+    if system.is_null() {
+        return ffi::set_null_argument("system") as i64;
+    }
+    let system = unsafe { &*system };
+    let registered = ffi::block_on(async {
+        register(system.consciousness_engine.write().await.hooks_mut()).map_err(AgiError::Consciousness)
+    });
+    match registered.and_then(|id| id) {
+        Ok(id) => id as i64,
         Err(e) => {
-            error!("FFI processing error: {}", e);
-            -1
+            error!("FFI hook registration error: {}", e);
+            ffi::set_last_error(&e) as i64
         }
     }
 }
 
+/// Call `callback` after every evolution step; returns a hook id, or
+/// `NullPointer` (-1) if `system` is null
+///
+/// Callbacks run while the system is processing and must not call back into it.
+#[no_mangle]
+pub extern "C" fn agi_on_state_evolved(
+    system: *mut AGISystem,
+    callback: AgiStateCallback,
+    user_data: *mut std::os::raw::c_void,
+) -> i64 {
+    ffi::guard(ffi::FFIError::Panic as i64, || {
+        let user_data = UserData(user_data);
+        register_hook(system, move |hooks| {
+            Ok(hooks.on_state_evolved(Box::new(move |event| {
+                if let consciousness::ConsciousnessEvent::StateEvolved(state) = event {
+                    let state = CConsciousnessState::from(state);
+                    callback(&state, user_data.get());
+                }
+            })))
+        })
+    })
+}
+
+/// Call `callback` whenever the discrete emotional state changes; returns a hook
+/// id, or `NullPointer` (-1) if `system` is null
+#[no_mangle]
+pub extern "C" fn agi_on_emotion_change(
+    system: *mut AGISystem,
+    callback: AgiEmotionCallback,
+    user_data: *mut std::os::raw::c_void,
+) -> i64 {
+    ffi::guard(ffi::FFIError::Panic as i64, || {
+        let user_data = UserData(user_data);
+        register_hook(system, move |hooks| {
+            Ok(hooks.on_emotion_change(Box::new(move |event| {
+                if let consciousness::ConsciousnessEvent::EmotionChanged { from, to } = event {
+                    callback(*from as i32, *to as i32, user_data.get());
+                }
+            })))
+        })
+    })
+}
+
+/// Call `callback` whenever `dimension` crosses `threshold`; returns a hook id,
+/// `NullPointer` (-1) if an argument is null, `InvalidArgument` (-2) if
+/// `dimension` is not UTF-8, or `Consciousness` (-20) if it names no dimension
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn agi_on_threshold_crossed(
+    system: *mut AGISystem,
+    dimension: *const std::os::raw::c_char,
+    threshold: f64,
+    callback: AgiThresholdCallback,
+    user_data: *mut std::os::raw::c_void,
+) -> i64 {
+    ffi::guard(ffi::FFIError::Panic as i64, || {
+        if dimension.is_null() {
+            return ffi::set_null_argument("dimension") as i64;
+        }
+        let Some(dimension) = (unsafe { ffi::c_string_to_rust_string(dimension) }) else {
+            return ffi::set_last_error(&AgiError::Ffi("dimension is not valid UTF-8".to_string())) as i64;
+        };
+        let user_data = UserData(user_data);
+        register_hook(system, move |hooks| {
+            hooks.on_threshold_crossed(&dimension, threshold, Box::new(move |event| {
+                if let consciousness::ConsciousnessEvent::ThresholdCrossed { dimension, value, rising, .. } = event {
+                    if let Ok(name) = std::ffi::CString::new(dimension.as_str()) {
+                        callback(name.as_ptr(), *value, *rising as i32, user_data.get());
+                    }
+                }
+            }))
+        })
+    })
+}
+
+/// Unregister a hook; returns 0, `NullPointer` (-1) if `system` is null, or
+/// `InvalidArgument` (-2) if the id is unknown
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn agi_remove_hook(system: *mut AGISystem, hook: i64) -> i32 {
+    ffi::guard(ffi::FFIError::Panic as i32, || {
+        if system.is_null() {
+            return ffi::set_null_argument("system");
+        }
+        let system = unsafe { &*system };
+        let removed = ffi::block_on(async { hook >= 0 && system.consciousness_engine.write().await.hooks_mut().remove(hook as u64) });
+        match removed {
+            Ok(true) => 0,
+            Ok(false) => ffi::set_last_error(&AgiError::Ffi(format!("No hook with id {}", hook))),
+            Err(e) => ffi::set_last_error(&e),
+        }
+    })
+}
+
+/// Called once when an asynchronous call completes, with 0 and its JSON result or
+/// an error code and null; the JSON is only valid for the duration of the call
+pub type AgiCompletionCallback = extern "C" fn(
+    status: i32,
+    result: *const std::os::raw::c_char,
+    user_data: *mut std::os::raw::c_void,
+);
+
+/// System handle used by tasks that outlive the FFI call scheduling them
+struct SystemHandle(*const AGISystem);
+
+// SAFETY: `AGISystem` is `Sync`; hosts keep the system alive until their callbacks have run
+unsafe impl Send for SystemHandle {}
+unsafe impl Sync for SystemHandle {}
+
+impl SystemHandle {
+    fn get(&self) -> &AGISystem {
+        unsafe { &*self.0 }
+    }
+}
+
+/// Process input on the shared runtime, calling `callback` with the result
+///
+/// Returns 0 once scheduled, `NullPointer` (-1) if `system` or `input` is null, or
+/// `Io` (-70) if the runtime cannot start; the callback is not called then. It
+/// runs on a runtime thread, where `agi_last_error_message` describes a failure,
+/// including `Panic` (-99) if processing panicked.
+/// The system must not be cleaned up before the callback has run.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn agi_process_input_async(
+    system: *mut AGISystem,
+    input: *const std::os::raw::c_char,
+    callback: AgiCompletionCallback,
+    user_data: *mut std::os::raw::c_void,
+) -> i32 {
+    ffi::guard(ffi::FFIError::Panic as i32, || {
+        if system.is_null() || input.is_null() {
+            return ffi::set_null_argument("system or input");
+        }
+        let runtime = match ffi::runtime() {
+            Ok(runtime) => runtime,
+            Err(e) => return ffi::set_last_error(&e),
+        };
+        
+        let input = unsafe { std::ffi::CStr::from_ptr(input).to_string_lossy().into_owned() };
+        let system = SystemHandle(system);
+        let user_data = UserData(user_data);
+        runtime.spawn(async move {
+            // Processing runs in its own task so that a panic still reaches the callback
+            let processing = tokio::spawn(async move {
+                system.get().process_input(&input).await
+                    .and_then(|result| result.to_json())
+                    .and_then(|json| std::ffi::CString::new(json).map_err(|e| AgiError::Serialization(e.to_string())))
+            });
+            let json = match processing.await {
+                Ok(Ok(json)) => Ok(json),
+                Ok(Err(e)) => {
+                    error!("FFI asynchronous processing error: {}", e);
+                    Err(ffi::set_last_error(&e))
+                }
+                Err(e) if e.is_panic() => Err(ffi::set_panic(e.into_panic().as_ref())),
+                Err(e) => Err(ffi::set_last_error(&AgiError::Ffi(format!("processing was cancelled: {}", e)))),
+            };
+            match json {
+                Ok(json) => callback(0, json.as_ptr(), user_data.get()),
+                Err(code) => callback(code, std::ptr::null(), user_data.get()),
+            }
+        });
+        0
+    })
+}
+
+/// Process `count` inputs concurrently on the shared runtime, writing each
+/// one's JSON result (or null) to `results` and its status to `statuses`
+///
+/// Statuses are 0, `NullPointer` (-1) for a null input, `MemoryPressure` (-31),
+/// the code of the component that failed, or `Panic` (-99); one failing input
+/// does not affect the others. Returns how many inputs succeeded, or
+/// `NullPointer` (-1) if an array is null, leaving the outputs untouched. The
+/// last error describes the last input that failed. Release every non-null
+/// result with `agi_free_string`.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn agi_process_batch(
+    system: *mut AGISystem,
+    inputs: *const *const std::os::raw::c_char,
+    count: usize,
+    results: *mut *mut std::os::raw::c_char,
+    statuses: *mut i32,
+) -> i32 {
+    ffi::guard(ffi::FFIError::Panic as i32, || {
+        if system.is_null() || (count > 0 && (inputs.is_null() || results.is_null() || statuses.is_null())) {
+            return ffi::set_null_argument("system, inputs, results or statuses");
+        }
+        if count == 0 {
+            return 0;
+        }
+        
+        let inputs: Vec<Option<String>> = unsafe { std::slice::from_raw_parts(inputs, count) }
+            .iter()
+            .map(|&input| (!input.is_null()).then(|| unsafe { std::ffi::CStr::from_ptr(input) }.to_string_lossy().into_owned()))
+            .collect();
+        let outcomes = ffi::block_on(async {
+            let tasks: Vec<_> = inputs
+                .into_iter()
+                .map(|input| {
+                    let system = SystemHandle(system);
+                    input.map(|input| tokio::spawn(async move {
+                        system.get().process_input(&input).await.and_then(|result| result.to_json())
+                    }))
+                })
+                .collect();
+            let mut outcomes = Vec::with_capacity(tasks.len());
+            for task in tasks {
+                outcomes.push(match task {
+                    Some(task) => Some(task.await),
+                    None => None,
+                });
+            }
+            outcomes
+        });
+        let outcomes = match outcomes {
+            Ok(outcomes) => outcomes,
+            Err(e) => return ffi::set_last_error(&e),
+        };
+        
+        let results = unsafe { std::slice::from_raw_parts_mut(results, count) };
+        let statuses = unsafe { std::slice::from_raw_parts_mut(statuses, count) };
+        let mut succeeded = 0;
+        for (index, outcome) in outcomes.into_iter().enumerate() {
+            results[index] = std::ptr::null_mut();
+            statuses[index] = match outcome {
+                None => ffi::set_null_argument(&format!("input {}", index)),
+                Some(Ok(Ok(json))) => write_json(&json, &mut results[index]),
+                Some(Ok(Err(e))) => {
+                    error!("FFI batch processing error on input {}: {}", index, e);
+                    ffi::set_last_error(&e)
+                }
+                Some(Err(e)) if e.is_panic() => ffi::set_panic(e.into_panic().as_ref()),
+                Some(Err(e)) => ffi::set_last_error(&AgiError::Ffi(format!("processing was cancelled: {}", e))),
+            };
+            if statuses[index] == 0 {
+                succeeded += 1;
+            }
+        }
+        succeeded
+    })
+}
+
 /// Clean up AGI system
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 #[no_mangle]
 pub extern "C" fn agi_cleanup(system: *mut AGISystem) {
-    if !system.is_null() {
-        unsafe {
-            let _ = Box::from_raw(system);
+    ffi::guard((), || {
+        if !system.is_null() {
+            memory_manager::leaks::untrack(system);
+            unsafe {
+                let _ = Box::from_raw(system);
+            }
+            ffi::log_outstanding();
+            info!("AGI system cleaned up via FFI");
         }
-        info!("AGI system cleaned up via FFI");
-    }
+    })
 }
 
 #[cfg(test)]
@@ -216,4 +1191,216 @@ mod tests {
         assert!(result.confidence >= 0.0 && result.confidence <= 1.0);
         assert!(!result.processing_time.is_zero());
     }
+    
+    #[test]
+    fn test_json_processing_result() {
+        let system = agi_init();
+        let input = std::ffi::CString::new("Describe the result").unwrap();
+        let mut json = std::ptr::null_mut();
+        assert_eq!(agi_process_input_json(system, input.as_ptr(), &mut json), 0);
+        
+        let text = unsafe { std::ffi::CStr::from_ptr(json) }.to_str().unwrap().to_string();
+        agi_free_string(json);
+        let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(value["neural_output"]["output"].as_array().unwrap().len(), neural_engine::NeuralArchitecture::default().output_size);
+        assert!(value["consciousness"]["awareness_level"].is_number());
+        assert!(value["confidence"].as_f64().is_some_and(|confidence| (0.0..=1.0).contains(&confidence)));
+        assert_eq!(agi_process_input_json(system, input.as_ptr(), std::ptr::null_mut()), -1);
+        agi_cleanup(system);
+    }
+    
+    extern "C" fn send_completion(status: i32, result: *const std::os::raw::c_char, user_data: *mut std::os::raw::c_void) {
+        // Owns the sender, so a late callback never outlives it
+        let sender = unsafe { Box::from_raw(user_data as *mut std::sync::mpsc::Sender<(i32, Option<String>)>) };
+        let json = (!result.is_null()).then(|| unsafe { std::ffi::CStr::from_ptr(result) }.to_string_lossy().into_owned());
+        sender.send((status, json)).unwrap();
+    }
+    
+    #[test]
+    fn test_async_processing_calls_back_with_json() {
+        let system = agi_init();
+        let (sender, receiver) = std::sync::mpsc::channel::<(i32, Option<String>)>();
+        let input = std::ffi::CString::new("Process me later").unwrap();
+        let user_data = Box::into_raw(Box::new(sender)) as *mut std::os::raw::c_void;
+        assert_eq!(agi_process_input_async(system, input.as_ptr(), send_completion, user_data), 0);
+        
+        // The system must outlive the callback, so wait for it however long processing takes
+        let (status, json) = receiver.recv().unwrap();
+        assert_eq!(status, 0);
+        let json: serde_json::Value = serde_json::from_str(&json.unwrap()).unwrap();
+        assert!(json["confidence"].as_f64().is_some());
+        assert!(json["neural_output"]["output"].is_array());
+        assert_eq!(agi_process_input_async(system, std::ptr::null(), send_completion, std::ptr::null_mut()), -1);
+        agi_cleanup(system);
+    }
+    
+    #[test]
+    fn test_batch_reports_each_input() {
+        let system = agi_init();
+        let (first, second) = (std::ffi::CString::new("First input").unwrap(), std::ffi::CString::new("Second input").unwrap());
+        let inputs = [first.as_ptr(), std::ptr::null(), second.as_ptr()];
+        let mut results = [std::ptr::null_mut(); 3];
+        let mut statuses = [7; 3];
+        assert_eq!(agi_process_batch(system, inputs.as_ptr(), 3, results.as_mut_ptr(), statuses.as_mut_ptr()), 2);
+        assert_eq!(statuses, [0, -1, 0]);
+        assert!(results[1].is_null());
+        for result in [results[0], results[2]] {
+            let text = unsafe { std::ffi::CStr::from_ptr(result) }.to_str().unwrap().to_string();
+            agi_free_string(result);
+            assert!(serde_json::from_str::<serde_json::Value>(&text).unwrap()["confidence"].is_number());
+        }
+        assert_eq!(agi_process_batch(system, std::ptr::null(), 3, results.as_mut_ptr(), statuses.as_mut_ptr()), -1);
+        agi_cleanup(system);
+    }
+    
+    #[tokio::test]
+    async fn test_introspection_after_processing() {
+        let system = AGISystem::new().unwrap();
+        system.process_input("Why does this matter?").await.unwrap();
+        let report = system.introspect().await;
+        assert_eq!(report.trajectory.evolutions, 1);
+        assert!(report.attention_target.is_some());
+        assert!(!report.drivers.is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_feedback_calibrates_confidence() {
+        let system = AGISystem::new().unwrap();
+        assert!(system.record_feedback(true).await.is_err());
+        let result = system.process_input("Is this right?").await.unwrap();
+        let outcome = system.record_feedback(false).await.unwrap();
+        assert_eq!(outcome.confidence, result.confidence);
+        assert!(outcome.brier > 0.0);
+        assert!(system.record_feedback(true).await.is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_processed_inputs_are_recalled() {
+        let system = AGISystem::new().unwrap();
+        system.process_input("The telescope observed a distant galaxy").await.unwrap();
+        let last = system.process_input("Bake the bread at high heat").await.unwrap();
+        
+        let recent = system.recall_recent(5).await;
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].confidence, last.confidence);
+        let similar = system.recall_similar("The telescope observed a distant galaxy", 1).await;
+        assert_eq!(similar[0].1.id, recent[1].id);
+        assert!(system.get_status().await.unwrap().memory.episodic.bytes > 0);
+    }
+    
+    #[tokio::test]
+    async fn test_remembered_knowledge_is_retrieved_as_context() {
+        let system = AGISystem::new().unwrap();
+        let id = system.remember("Galaxies are held together by gravity").await.unwrap();
+        let result = system.process_input("Galaxies are held together by gravity").await.unwrap();
+        assert_eq!(result.context[0].id, id);
+        assert!(result.context[0].similarity > 0.99);
+        
+        assert!(system.forget(id).await);
+        assert!(system.process_input("Galaxies are held together by gravity").await.unwrap().context.is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_status_breaks_memory_down_by_subsystem() {
+        let system = AGISystem::new().unwrap();
+        system.process_input("What is using the memory?").await.unwrap();
+        let breakdown = system.get_status().await.unwrap().memory.breakdown;
+        let weights = system.neural_engine.read().await.weight_bytes();
+        assert_eq!(breakdown.neural_weights.bytes, weights);
+        assert!(breakdown.episodic.bytes > 0 && breakdown.consciousness.bytes > 0);
+    }
+    
+    #[tokio::test]
+    async fn test_saved_state_includes_memory() {
+        let system = AGISystem::new().unwrap();
+        system.process_input("Remember the lighthouse").await.unwrap();
+        let id = system.remember("The lighthouse guides ships").await.unwrap();
+        let dir = std::env::temp_dir().join(format!("agi_state_{}", std::process::id()));
+        system.save_state(&dir).await.unwrap();
+        
+        let restored = AGISystem::new().unwrap();
+        restored.restore_state(&dir).await.unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(restored.recall_recent(5).await.len(), 1);
+        assert_eq!(restored.memory_manager.read().await.semantic().get(id), Some("The lighthouse guides ships"));
+        let state = restored.blackboard().await.get(memory_manager::blackboard::CONSCIOUSNESS_STATE);
+        assert!(state.is_some());
+    }
+    
+    #[tokio::test]
+    async fn test_failed_restore_leaves_state_unchanged() {
+        let saved = AGISystem::new().unwrap();
+        saved.remember("The lighthouse guides ships").await.unwrap();
+        let dir = std::env::temp_dir().join(format!("agi_corrupt_state_{}", std::process::id()));
+        saved.save_state(&dir).await.unwrap();
+        std::fs::write(dir.join(NEURAL_STATE_FILE), b"not a model").unwrap();
+        
+        let system = AGISystem::new().unwrap();
+        let id = system.remember("Bees dance to share directions").await.unwrap();
+        assert!(system.restore_state(&dir).await.is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+        let manager = system.memory_manager.read().await;
+        assert_eq!(manager.semantic().len(), 1);
+        assert_eq!(manager.semantic().get(id), Some("Bees dance to share directions"));
+    }
+    
+    #[tokio::test]
+    async fn test_pressure_monitor_sheds_under_critical_pressure() {
+        let system = AGISystem::new().unwrap();
+        system.process_input("Fill some memory").await.unwrap();
+        let manager = system.memory_manager.read().await;
+        manager.set_budget(Some(1));
+        manager.set_pressure_thresholds(memory_manager::PressureThresholds { elevated: 0.0, critical: 0.0 });
+        drop(manager);
+        
+        let monitor = system.start_pressure_monitor(std::time::Duration::from_millis(10));
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        monitor.abort();
+        let stats = system.memory_manager.read().await.get_stats().await.unwrap();
+        assert_eq!(stats.pressure, memory_manager::PressureLevel::Critical);
+        assert!(stats.evictions > 0);
+    }
+    
+    #[tokio::test]
+    async fn test_engines_share_results_on_blackboard() {
+        use memory_manager::blackboard::{CONSCIOUSNESS_EMOTION, CONSCIOUSNESS_STATE, NEURAL_CONFIDENCE, NEURAL_OUTPUT};
+        
+        let system = AGISystem::new().unwrap();
+        let result = system.process_input("Share what you found").await.unwrap();
+        let board = system.blackboard().await;
+        assert_eq!(board.get_as::<Vec<f64>>(NEURAL_OUTPUT).unwrap(), result.neural_output.output.to_vec());
+        assert_eq!(board.get_as::<f64>(NEURAL_CONFIDENCE), Ok(result.neural_output.pattern_confidence));
+        assert_eq!(board.get_as::<Vec<f64>>(CONSCIOUSNESS_STATE).unwrap(), result.consciousness.dimensions());
+        assert_eq!(board.entry(CONSCIOUSNESS_EMOTION).unwrap().writer, "consciousness_engine");
+    }
+    
+    #[tokio::test]
+    async fn test_input_processing_with_quantum_stage() {
+        let system = AGISystem::new().unwrap()
+            .with_quantum_stage(HybridStageConfig::default())
+            .unwrap();
+        let result = system.process_input("Test input for hybrid processing").await.unwrap();
+        
+        let quantum = result.quantum.expect("quantum stage should run");
+        assert_eq!(quantum.expectation_values.len(), HybridStageConfig::default().observables.len());
+        assert!(result.confidence >= 0.0 && result.confidence <= 1.0);
+    }
+    
+    #[test]
+    fn test_status_optimize_and_configure() {
+        let system = agi_init();
+        let config = std::ffi::CString::new(r#"{"memory_budget": 1073741824, "cache_capacity": 0}"#).unwrap();
+        assert_eq!(agi_configure(system, config.as_ptr()), 0);
+        let unknown = std::ffi::CString::new(r#"{"budget": 1}"#).unwrap();
+        assert_eq!(agi_configure(system, unknown.as_ptr()), ffi::FFIError::Config as i32);
+        assert_eq!(agi_optimize(system, std::ptr::null_mut()), 0);
+        
+        let mut status = std::ptr::null_mut();
+        assert_eq!(agi_get_status_json(system, &mut status), 0);
+        let json: serde_json::Value = serde_json::from_str(unsafe { std::ffi::CStr::from_ptr(status) }.to_str().unwrap()).unwrap();
+        unsafe { ffi::free_c_string(status) };
+        assert!(json["neural"]["total_parameters"].as_u64().is_some_and(|parameters| parameters > 0));
+        assert!(json["memory"].is_object());
+        agi_cleanup(system);
+    }
 }
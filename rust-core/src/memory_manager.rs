@@ -1,9 +1,89 @@
 //! Memory Manager - AGI memory management and optimization
 //! 
 //! This module provides memory management capabilities for the AGI system.
+//!
+//! With the `tracking-allocator` feature, statistics come from a counting global
+//! allocator and cover every heap allocation in the process; otherwise they
+//! cover only memory allocated through the manager.
+//!
+//! Tensor and activation buffers are rented from a shared `pool::BufferPool`,
+//! whose hit rate is reported alongside.
+//!
+//! An optional budget caps usage: allocations that would exceed it first run the
+//! registered evictors and then fail with `MemoryPressure`.
+//!
+//! The manager also owns the episodic store of processed inputs and the
+//! semantic store of embedded knowledge, and consolidates the first into the
+//! second. Episodes are scored for retention by a pluggable `ImportanceModel`,
+//! by default from the awareness and attention they were recorded with.
+//!
+//! Stores and caches registered with the manager are trimmed by their eviction
+//! rules, both under budget pressure and on `enforce_eviction`.
+//!
+//! A shared `Blackboard` lets the engines exchange intermediate results.
+//!
+//! Every method takes `&self`: the stores are locked individually, searches
+//! need only a read lock, and the blackboard and access logs are sharded, so
+//! concurrent requests holding the manager for reading rarely contend.
+//!
+//! `snapshot` and `restore` save the stores and the blackboard to a checksummed
+//! file and bring them back.
+//!
+//! The manager also probes process and system memory, and notifies subscribers
+//! when the resulting pressure level changes.
+//!
+//! With the `leak-tracking` feature, `leaks` records where each handle given to
+//! FFI callers was created and reports those never freed.
+//!
+//! `breakdown` charges usage to subsystems (weights, activations, pool, stores,
+//! caches) through tagged allocation scopes and registered gauges.
+
+pub mod blackboard;
+pub mod block;
+pub mod consolidation;
+pub mod episodic;
+pub mod eviction;
+pub mod importance;
+pub mod leaks;
+pub mod pool;
+pub mod pressure;
+pub mod quota;
+pub mod semantic;
+mod shard;
+pub mod snapshot;
+pub mod subsystem;
+pub mod tracking;
+
+pub use blackboard::{Blackboard, BlackboardEntry, BlackboardStats, BlackboardValue};
+pub use block::{MemoryBlock, TypedBlock, DEFAULT_ALIGNMENT};
+pub use consolidation::{ConsolidationMetrics, ConsolidationPolicy, ConsolidationRun};
+pub use episodic::{Episode, EpisodeId, EpisodicStats, EpisodicStore};
+pub use eviction::{Evictable, EvictionEntry, EvictionPolicy, EvictionRule, EvictionStats, EvictionTarget, PolicyCounters};
+pub use importance::{ConsciousnessImportance, ImportanceModel};
+pub use leaks::{AllocationSite, LeakReport};
+pub use pool::{Arena, BufferPool, PoolStats, PooledBuffer};
+pub use pressure::{PressureCallback, PressureLevel, PressureReading, PressureThresholds, SubscriptionId, SystemMemory};
+pub use quota::{Evictor, MemoryPressure};
+pub use semantic::{HnswConfig, SemanticHit, SemanticId, SemanticMemory, SemanticStats};
+pub use snapshot::{SnapshotSummary, SNAPSHOT_FORMAT_VERSION};
+use snapshot::RestoredStores;
+pub use subsystem::{Gauge, MemoryBreakdown, Subsystem, SubsystemScope, SubsystemUsage};
+pub use tracking::{AllocationCounters, TrackingAllocator};
+
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard};
 
 use serde::{Deserialize, Serialize};
-use tracing::info;
+use tracing::{debug, info};
+
+use crate::error::AgiError;
+use block::Usage;
+use consolidation::Consolidator;
+use eviction::Evictions;
+use pressure::Pressure;
+use quota::Quota;
+use subsystem::Gauges;
 
 /// Memory statistics
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,108 +94,618 @@ pub struct MemoryStats {
     pub allocation_count: usize,
     pub deallocation_count: usize,
     pub fragmentation_ratio: f64,
+    /// Whether the figures come from the global tracking allocator
+    pub tracked: bool,
+    /// Buffer pool counters
+    pub pool: PoolStats,
+    /// Byte budget, if one is set
+    pub budget: Option<usize>,
+    /// Times an evictor was asked to release memory
+    pub evictions: usize,
+    pub episodic: EpisodicStats,
+    pub semantic: SemanticStats,
+    pub consolidation: ConsolidationMetrics,
+    pub eviction: EvictionStats,
+    pub blackboard: BlackboardStats,
+    /// Usage by subsystem
+    pub breakdown: MemoryBreakdown,
+    /// Process and system memory when the stats were taken
+    pub system: SystemMemory,
+    pub pressure: PressureLevel,
 }
 
 /// Memory manager
 pub struct MemoryManager {
-    total_allocated: usize,
-    peak_usage: usize,
-    allocation_count: usize,
-    deallocation_count: usize,
+    /// Blocks allocated through the manager, shared with the blocks themselves
+    usage: Arc<Usage>,
+    quota: Quota,
+    episodic: Arc<RwLock<EpisodicStore>>,
+    semantic: Arc<RwLock<SemanticMemory>>,
+    consolidator: Mutex<Consolidator>,
+    evictions: Arc<Evictions>,
+    blackboard: Arc<Blackboard>,
+    gauges: Gauges,
+    optimization: Mutex<OptimizationPolicy>,
+    pressure: Pressure,
+    importance: RwLock<Arc<dyn ImportanceModel>>,
 }
 
+/// Episodes kept by a new manager
+const DEFAULT_EPISODIC_CAPACITY: usize = 1024;
+
 impl MemoryManager {
     /// Create a new memory manager
-    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
-        Ok(Self {
-            total_allocated: 0,
-            peak_usage: 0,
-            allocation_count: 0,
-            deallocation_count: 0,
-        })
+    pub fn new() -> Result<Self, AgiError> {
+        let usage = Arc::new(Usage::default());
+        let episodic = Arc::new(RwLock::new(EpisodicStore::new(DEFAULT_EPISODIC_CAPACITY, usage.clone())));
+        let semantic = Arc::new(RwLock::new(SemanticMemory::new(HnswConfig::default(), usage.clone())));
+        let blackboard = Arc::new(Blackboard::new(usage.clone()));
+        let evictions = Arc::new(Evictions::default());
+        evictions.register("episodic", episodic.clone(), vec![EvictionRule::new(EvictionPolicy::Importance)]);
+        evictions.register("semantic", semantic.clone(), Vec::new());
+        evictions.register("blackboard", blackboard.clone(), Vec::new());
+        let quota = Quota::default();
+        let policies = evictions.clone();
+        quota.register("eviction_policies", Box::new(move |bytes| policies.enforce(bytes)));
+        Ok(Self { usage, quota, episodic, semantic, consolidator: Mutex::default(), evictions, blackboard, gauges: Gauges::default(), optimization: Mutex::default(), pressure: Pressure::default(), importance: RwLock::new(Arc::new(ConsciousnessImportance::default())) })
     }
 
-    /// Allocate memory
-    pub fn allocate(&mut self, size: usize) -> Result<*mut u8, Box<dyn std::error::Error>> {
-        let ptr = unsafe { std::alloc::alloc_zeroed(std::alloc::Layout::from_size_align(size, 64)?) };
-        
-        if !ptr.is_null() {
-            self.total_allocated += size;
-            self.allocation_count += 1;
-            
-            if self.total_allocated > self.peak_usage {
-                self.peak_usage = self.total_allocated;
-            }
-            
-            info!("Memory allocated: {} bytes, total: {} bytes", size, self.total_allocated);
-        }
-        
-        Ok(ptr)
+    /// Cap usage at `bytes`
+    pub fn with_budget(self, bytes: usize) -> Self {
+        self.quota.set_budget(Some(bytes));
+        self
     }
 
-    /// Deallocate memory
-    pub fn deallocate(&mut self, ptr: *mut u8, size: usize) -> Result<(), Box<dyn std::error::Error>> {
-        if !ptr.is_null() {
-            unsafe {
-                std::alloc::dealloc(ptr, std::alloc::Layout::from_size_align(size, 64)?);
-            }
-            
-            self.total_allocated = self.total_allocated.saturating_sub(size);
-            self.deallocation_count += 1;
-            
-            info!("Memory deallocated: {} bytes, total: {} bytes", size, self.total_allocated);
-        }
-        
-        Ok(())
+    /// Change the byte budget; `None` removes it
+    pub fn set_budget(&self, bytes: Option<usize>) {
+        self.quota.set_budget(bytes);
+    }
+
+    pub fn budget(&self) -> Option<usize> {
+        self.quota.budget()
+    }
+
+    /// Call `evictor` with the bytes still needed when an allocation would exceed the budget
+    ///
+    /// Evictors run in registration order; registering a name again replaces its evictor.
+    pub fn on_pressure(&self, name: &str, evictor: impl Fn(usize) -> usize + Send + Sync + 'static) {
+        self.quota.register(name, Box::new(evictor));
+    }
+
+    /// Remove the evictor registered under `name`
+    pub fn remove_evictor(&self, name: &str) -> bool {
+        self.quota.unregister(name)
+    }
+
+    /// Make room for `bytes` more within the budget, running evictors if needed
+    ///
+    /// `reserve(0)` brings usage back under a lowered budget, or reports that it cannot.
+    pub fn reserve(&self, bytes: usize) -> Result<(), MemoryPressure> {
+        self.quota.reserve(bytes, || self.counters().current)
+    }
+
+    /// Ask every evictor to release `bytes`, whether or not usage is over the budget; returns the bytes released
+    pub fn shed(&self, bytes: usize) -> usize {
+        self.quota.shed(bytes)
+    }
+
+    /// Probe process and system memory and assess the pressure, notifying subscribers if the level changed
+    pub fn check_pressure(&self) -> PressureReading {
+        let reading = self.assess_pressure(SystemMemory::probe());
+        self.pressure.update(&reading);
+        reading
+    }
+
+    fn assess_pressure(&self, system: SystemMemory) -> PressureReading {
+        PressureReading::assess(&self.pressure.thresholds(), self.counters().current, self.budget(), system)
+    }
+
+    /// Level found by the last `check_pressure`
+    pub fn pressure_level(&self) -> PressureLevel {
+        self.pressure.level()
+    }
+
+    pub fn pressure_thresholds(&self) -> PressureThresholds {
+        self.pressure.thresholds()
+    }
+
+    pub fn set_pressure_thresholds(&self, thresholds: PressureThresholds) {
+        self.pressure.set_thresholds(thresholds);
+    }
+
+    /// Call `callback` whenever `check_pressure` finds a different level
+    ///
+    /// Callbacks run on the checking thread and must not subscribe or unsubscribe.
+    pub fn subscribe_pressure(&self, callback: impl Fn(&PressureReading) + Send + Sync + 'static) -> SubscriptionId {
+        self.pressure.subscribe(Box::new(callback))
+    }
+
+    pub fn unsubscribe_pressure(&self, id: SubscriptionId) -> bool {
+        self.pressure.unsubscribe(id)
+    }
+
+    /// Record `input` and its processing `result` as an episode, within the budget
+    pub fn record_episode(&self, input: &str, embedding: Vec<f64>, result: &crate::ProcessingResult) -> Result<EpisodeId, AgiError> {
+        let mut episode = Episode::new(input, embedding, result);
+        episode.importance = importance::score(self.importance_model().as_ref(), &episode);
+        self.reserve(episode.bytes())?;
+        Ok(self.episodic_mut().record(episode))
+    }
+
+    /// Model scoring episodes as they are recorded
+    pub fn importance_model(&self) -> Arc<dyn ImportanceModel> {
+        self.importance.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Score episodes recorded from now on with `model`
+    pub fn set_importance_model(&self, model: impl ImportanceModel + 'static) {
+        *self.importance.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(model);
+    }
+
+    /// The episodic store, for retrieval
+    pub fn episodic(&self) -> RwLockReadGuard<'_, EpisodicStore> {
+        self.episodic.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn episodic_mut(&self) -> RwLockWriteGuard<'_, EpisodicStore> {
+        self.episodic.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Store `payload` under `embedding` in semantic memory, within the budget
+    pub fn remember(&self, embedding: &[f64], payload: &str) -> Result<SemanticId, AgiError> {
+        let bytes = self.semantic().entry_bytes(embedding.len(), payload);
+        self.reserve(bytes)?;
+        self.semantic_mut().insert(embedding, payload).map_err(AgiError::Memory)
+    }
+
+    /// The semantic store
+    pub fn semantic(&self) -> RwLockReadGuard<'_, SemanticMemory> {
+        self.semantic.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn semantic_mut(&self) -> RwLockWriteGuard<'_, SemanticMemory> {
+        self.semantic.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn consolidation_policy(&self) -> ConsolidationPolicy {
+        self.consolidator.lock().unwrap_or_else(|e| e.into_inner()).policy
+    }
+
+    pub fn set_consolidation_policy(&self, policy: ConsolidationPolicy) {
+        self.consolidator.lock().unwrap_or_else(|e| e.into_inner()).policy = policy;
+    }
+
+    /// Promote important episodes into semantic memory and prune unimportant ones
+    pub fn consolidate(&self) -> ConsolidationRun {
+        let mut consolidator = self.consolidator.lock().unwrap_or_else(|e| e.into_inner());
+        let run = consolidator.run(&mut self.episodic_mut(), &mut self.semantic_mut());
+        debug!("Consolidated {} episodes: {} promoted, {} duplicates, {} pruned", run.examined, run.promoted, run.duplicates, run.pruned);
+        run
+    }
+
+    /// Working memory shared by the engines
+    pub fn blackboard(&self) -> Arc<Blackboard> {
+        self.blackboard.clone()
+    }
+
+    /// Save the episodic and semantic stores and the blackboard to `path`
+    ///
+    /// The snapshot is written beside `path` and renamed into place, so an
+    /// interrupted snapshot leaves an earlier one intact.
+    pub fn snapshot<P: AsRef<Path>>(&self, path: P) -> Result<SnapshotSummary, AgiError> {
+        let path = path.as_ref();
+        let partial = path.with_extension("partial");
+        let summary = self.snapshot_to_writer(BufWriter::new(std::fs::File::create(&partial)?))?;
+        std::fs::rename(&partial, path)?;
+        info!("Memory snapshot of {} episodes and {} semantic entries saved to {}", summary.episodes, summary.semantic_entries, path.display());
+        Ok(summary)
+    }
+
+    /// Stream a snapshot into any writer
+    pub fn snapshot_to_writer<W: Write>(&self, writer: W) -> Result<SnapshotSummary, AgiError> {
+        snapshot::write_stores(writer, &self.episodic, &self.semantic, &self.blackboard)
+    }
+
+    /// Replace the stores and the blackboard with a snapshot written by `snapshot`
+    ///
+    /// The whole snapshot is verified before anything is replaced; on error the
+    /// current state is left as it was. Store capacity and HNSW configuration
+    /// come from the snapshot.
+    pub fn restore<P: AsRef<Path>>(&self, path: P) -> Result<SnapshotSummary, AgiError> {
+        let summary = self.restore_from_reader(BufReader::new(std::fs::File::open(path.as_ref())?))?;
+        info!("Memory snapshot of {} episodes and {} semantic entries restored from {}", summary.episodes, summary.semantic_entries, path.as_ref().display());
+        Ok(summary)
+    }
+
+    /// Restore a snapshot from any reader
+    pub fn restore_from_reader<R: Read>(&self, reader: R) -> Result<SnapshotSummary, AgiError> {
+        let restored = snapshot::read_stores(reader, &self.usage)?;
+        Ok(self.install(restored))
+    }
+
+    /// Read and verify the snapshot at `path` without replacing anything, for `install`
+    pub(crate) fn load_snapshot<P: AsRef<Path>>(&self, path: P) -> Result<RestoredStores, AgiError> {
+        snapshot::read_stores(BufReader::new(std::fs::File::open(path.as_ref())?), &self.usage)
+    }
+
+    /// Swap in stores read by `load_snapshot`
+    pub(crate) fn install(&self, restored: RestoredStores) -> SnapshotSummary {
+        *self.episodic_mut() = restored.episodic;
+        *self.semantic_mut() = restored.semantic;
+        self.blackboard.restore(restored.blackboard);
+        restored.summary
+    }
+
+    /// Trim `store` by `rules` under pressure and on `enforce_eviction`, replacing a store of the same name
+    ///
+    /// Stores are visited in registration order; `episodic`, `semantic` and `blackboard` are registered first.
+    pub fn register_evictable(&self, name: &str, store: Arc<dyn EvictionTarget>, rules: Vec<EvictionRule>) {
+        self.evictions.register(name, store, rules);
+    }
+
+    /// Replace the eviction rules of a registered store, returning whether it is registered
+    pub fn set_eviction_rules(&self, name: &str, rules: Vec<EvictionRule>) -> bool {
+        self.evictions.set_rules(name, rules)
+    }
+
+    pub fn eviction_rules(&self, name: &str) -> Option<Vec<EvictionRule>> {
+        self.evictions.rules(name)
+    }
+
+    /// Expire entries past their TTL and trim stores over their rules' byte limits, returning the bytes released
+    pub fn enforce_eviction(&self) -> usize {
+        self.evictions.enforce(0)
+    }
+
+    /// Report the bytes `gauge` measures under `subsystem` in `breakdown`, replacing a gauge of the same name
+    ///
+    /// For memory held outside the manager; gauges are read on every `breakdown`.
+    pub fn report_usage(&self, name: &str, subsystem: Subsystem, gauge: impl Fn() -> usize + Send + Sync + 'static) {
+        self.gauges.register(name, subsystem, Box::new(gauge));
+    }
+
+    /// Remove the gauge registered under `name`
+    pub fn remove_gauge(&self, name: &str) -> bool {
+        self.gauges.unregister(name)
+    }
+
+    /// Bytes held by each subsystem: the manager's stores and blocks, the buffer pool, and the gauges
+    pub fn breakdown(&self) -> MemoryBreakdown {
+        let mut breakdown = MemoryBreakdown::default();
+        self.usage.ledger().add_to(&mut breakdown);
+        pool::global().ledger().add_to(&mut breakdown);
+        self.gauges.add_to(&mut breakdown);
+        breakdown
+    }
+
+    /// Counters the stats and budget refer to
+    fn counters(&self) -> AllocationCounters {
+        tracking::global_counters().unwrap_or_else(|| self.usage.counters())
+    }
+
+    /// Allocate `size` zeroed bytes at the default alignment, freed when the block drops
+    pub fn allocate(&self, size: usize) -> Result<MemoryBlock, AgiError> {
+        self.allocate_aligned(size, DEFAULT_ALIGNMENT)
+    }
+
+    /// Allocate `size` zeroed bytes aligned to `align`, a power of two
+    pub fn allocate_aligned(&self, size: usize, align: usize) -> Result<MemoryBlock, AgiError> {
+        self.reserve(size)?;
+        let block = MemoryBlock::new(size, align, self.usage.clone())?;
+        debug!("Memory allocated: {} bytes, total: {} bytes", size, self.usage.counters().current);
+        Ok(block)
+    }
+
+    /// Allocate `len` default-initialized elements of `T` at the default alignment
+    pub fn alloc_slice<T: Copy + Default>(&self, len: usize) -> Result<TypedBlock<T>, AgiError> {
+        self.alloc_slice_aligned(len, DEFAULT_ALIGNMENT)
+    }
+
+    /// Allocate `len` default-initialized elements of `T`, aligned to at least `align`
+    pub fn alloc_slice_aligned<T: Copy + Default>(&self, len: usize, align: usize) -> Result<TypedBlock<T>, AgiError> {
+        self.reserve(std::mem::size_of::<T>().saturating_mul(len))?;
+        TypedBlock::new(len, align, self.usage.clone())
+    }
+
+    /// The buffer pool shared by tensor operations and networks
+    pub fn pool(&self) -> &'static BufferPool {
+        pool::global()
+    }
+
+    /// Scratch arena for one processing request, backed by the shared pool and
+    /// charged to `Subsystem::Activations`
+    pub fn arena(&self) -> Arena<'static> {
+        let _scope = subsystem::scope(Subsystem::Activations);
+        pool::global().arena()
     }
 
     /// Get memory statistics
-    pub async fn get_stats(&self) -> Result<MemoryStats, Box<dyn std::error::Error>> {
-        let fragmentation_ratio = if self.total_allocated > 0 {
-            let fragmentation = self.peak_usage.saturating_sub(self.total_allocated) as f64;
-            fragmentation / self.peak_usage as f64
+    pub async fn get_stats(&self) -> Result<MemoryStats, AgiError> {
+        let counters = self.counters();
+        let system = SystemMemory::probe();
+        let fragmentation_ratio = if counters.current > 0 {
+            let fragmentation = counters.peak.saturating_sub(counters.current) as f64;
+            fragmentation / counters.peak as f64
         } else {
             0.0
         };
 
         Ok(MemoryStats {
-            total_memory: self.total_allocated,
-            used_memory: self.total_allocated,
-            peak_memory: self.peak_usage,
-            allocation_count: self.allocation_count,
-            deallocation_count: self.deallocation_count,
+            total_memory: counters.current,
+            used_memory: counters.current,
+            peak_memory: counters.peak,
+            allocation_count: counters.allocations,
+            deallocation_count: counters.deallocations,
             fragmentation_ratio,
+            tracked: tracking::global_counters().is_some(),
+            pool: pool::global().stats(),
+            budget: self.budget(),
+            evictions: self.quota.evictions(),
+            episodic: self.episodic().stats(),
+            semantic: self.semantic().stats(),
+            consolidation: self.consolidator.lock().unwrap_or_else(|e| e.into_inner()).metrics,
+            eviction: self.evictions.stats(),
+            blackboard: self.blackboard.stats(),
+            breakdown: self.breakdown(),
+            system,
+            pressure: self.assess_pressure(system).level,
         })
     }
 
+    pub fn optimization_policy(&self) -> OptimizationPolicy {
+        *self.optimization.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn set_optimization_policy(&self, policy: OptimizationPolicy) {
+        *self.optimization.lock().unwrap_or_else(|e| e.into_inner()) = policy;
+    }
+
+    /// Bytes held but not in use: idle pooled buffers, spare episode slots and deleted semantic entries
+    fn slack(&self) -> usize {
+        let semantic = self.semantic().stats();
+        let tombstones = semantic.bytes * semantic.deleted / (semantic.entries + semantic.deleted).max(1);
+        pool::global().stats().retained_bytes + self.episodic().spare_bytes() + tombstones
+    }
+
     /// Optimize memory usage
-    pub async fn optimize(&self) -> Result<OptimizationResult, Box<dyn std::error::Error>> {
+    ///
+    /// Consolidates episodes, compacts the episodic and semantic stores, shrinks
+    /// the buffer pool and evicts cache entries idle past the policy's limit,
+    /// measuring the bytes held before and after.
+    pub async fn optimize(&self) -> Result<OptimizationResult, AgiError> {
         info!("Starting memory optimization");
         
         let start_time = std::time::Instant::now();
+        let policy = self.optimization_policy();
+        let (bytes_before, slack_before) = (self.breakdown().total(), self.slack());
         
-        // Memory-specific optimization logic
-        let fragmentation_reduction = 0.1;
-        let allocation_efficiency_improvement = 0.15;
+        let consolidation = self.consolidate();
+        let episodic_bytes_released = consolidation.episodic_bytes_released + self.episodic_mut().compact();
+        let semantic_bytes_released = {
+            let mut semantic = self.semantic_mut();
+            let before = semantic.stats().bytes;
+            if semantic.stats().deleted > 0 {
+                semantic.rebuild();
+            }
+            before.saturating_sub(semantic.stats().bytes)
+        };
+        let pool_bytes_released = pool::global().shrink(policy.pool_keep);
+        // Long-term stores are consolidated above rather than evicted for being unused
+        let cold = self.evictions.evict_idle(policy.cold_after, &["episodic", "semantic"]);
         
+        let (bytes_after, slack_after) = (self.breakdown().total(), self.slack());
+        let fragmentation = |slack: usize, bytes: usize| if bytes + slack > 0 { slack as f64 / (bytes + slack) as f64 } else { 0.0 };
         let optimization_time = start_time.elapsed();
         
         let result = OptimizationResult {
-            fragmentation_reduction,
-            allocation_efficiency_improvement,
+            fragmentation_reduction: fragmentation(slack_before, bytes_before) - fragmentation(slack_after, bytes_after),
+            allocation_efficiency_improvement: if bytes_before > 0 {
+                bytes_before.saturating_sub(bytes_after) as f64 / bytes_before as f64
+            } else {
+                0.0
+            },
+            consolidation,
+            bytes_before,
+            bytes_after,
+            episodic_bytes_released,
+            semantic_bytes_released,
+            pool_bytes_released,
+            cold_entries_evicted: cold.entries as usize,
+            cold_bytes_released: cold.bytes as usize,
             optimization_time,
         };
         
-        info!("Memory optimization completed in {:?}", optimization_time);
+        info!("Memory optimization completed in {:?}: {} -> {} bytes", optimization_time, bytes_before, bytes_after);
         
         Ok(result)
     }
 }
 
+/// Limits applied by `MemoryManager::optimize`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct OptimizationPolicy {
+    /// Idle buffers the pool keeps per size class
+    pub pool_keep: usize,
+    /// Cache entries unused this long are evicted
+    pub cold_after: std::time::Duration,
+}
+
+impl Default for OptimizationPolicy {
+    fn default() -> Self {
+        Self { pool_keep: 4, cold_after: std::time::Duration::from_secs(300) }
+    }
+}
+
 /// Memory optimization result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OptimizationResult {
+    /// Drop in the share of held bytes not in use
     pub fragmentation_reduction: f64,
+    /// Fraction of the bytes held before that were released
     pub allocation_efficiency_improvement: f64,
+    pub consolidation: ConsolidationRun,
+    /// Bytes in the subsystem breakdown before and after
+    pub bytes_before: usize,
+    pub bytes_after: usize,
+    /// Released by consolidation and by dropping spare episode slots
+    pub episodic_bytes_released: usize,
+    /// Released by rebuilding semantic memory without its deleted entries
+    pub semantic_bytes_released: usize,
+    pub pool_bytes_released: usize,
+    pub cold_entries_evicted: usize,
+    pub cold_bytes_released: usize,
     pub optimization_time: std::time::Duration,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_blocks_release_on_drop() {
+        let manager = MemoryManager::new().unwrap();
+        let block = manager.allocate_aligned(100, 128).unwrap();
+        assert_eq!(block.as_ptr() as usize % 128, 0);
+        assert!(block.as_slice().iter().all(|&byte| byte == 0));
+        let mut weights = manager.alloc_slice::<f64>(16).unwrap();
+        weights[3] = 1.5;
+        assert_eq!(weights.iter().sum::<f64>(), 1.5);
+        assert!(manager.allocate_aligned(8, 3).is_err());
+
+        let stats = manager.get_stats().await.unwrap();
+        if !stats.tracked {
+            assert_eq!(stats.used_memory, 100 + 16 * 8);
+        }
+        drop(block);
+        drop(weights);
+        let stats = manager.get_stats().await.unwrap();
+        if !stats.tracked {
+            assert_eq!((stats.used_memory, stats.peak_memory), (0, 228));
+            assert_eq!(stats.deallocation_count, 2);
+        }
+    }
+
+    #[test]
+    fn test_breakdown_charges_scopes_and_gauges() {
+        let manager = MemoryManager::new().unwrap();
+        let weights = {
+            let _scope = subsystem::scope(Subsystem::NeuralWeights);
+            manager.alloc_slice::<f64>(64).unwrap()
+        };
+        let scratch = manager.allocate(100).unwrap();
+        manager.report_usage("cache", Subsystem::Caches, || 4096);
+
+        let breakdown = manager.breakdown();
+        assert_eq!(weights.block().subsystem(), Subsystem::NeuralWeights);
+        assert_eq!(breakdown.neural_weights.bytes, 64 * 8);
+        assert!(breakdown.other.bytes >= 100);
+        assert_eq!(breakdown.caches.bytes, 4096);
+        assert!(breakdown.ranked().contains(&(Subsystem::Caches, 4096)));
+
+        drop(weights);
+        drop(scratch);
+        assert!(manager.remove_gauge("cache"));
+        let breakdown = manager.breakdown();
+        assert_eq!((breakdown.neural_weights.bytes, breakdown.neural_weights.peak), (0, 64 * 8));
+        assert_eq!(breakdown.caches.bytes, 0);
+    }
+
+    #[tokio::test]
+    async fn test_optimize_compacts_and_evicts_cold_entries() {
+        let manager = MemoryManager::new().unwrap();
+        manager.set_optimization_policy(OptimizationPolicy { pool_keep: 0, cold_after: std::time::Duration::ZERO });
+        manager.blackboard().put("scratch", vec![0.5; 32], "test");
+        let ids: Vec<SemanticId> = (0..3).map(|i| manager.remember(&[1.0, i as f64], &format!("fact {}", i)).unwrap()).collect();
+        assert!(manager.semantic_mut().delete(ids[0]));
+
+        let result = manager.optimize().await.unwrap();
+        assert!(result.semantic_bytes_released > 0);
+        assert_eq!(manager.semantic().stats().deleted, 0);
+        assert_eq!(result.cold_entries_evicted, 1);
+        assert!(result.cold_bytes_released > 0 && manager.blackboard().is_empty());
+        assert!(result.consolidation.examined == 0 && result.fragmentation_reduction.is_finite());
+    }
+
+    #[test]
+    fn test_snapshot_restores_stores_and_blackboard() {
+        let manager = MemoryManager::new().unwrap();
+        let east = manager.remember(&[1.0, 0.0], "east").unwrap();
+        manager.remember(&[0.0, 1.0], "north").unwrap();
+        manager.blackboard().put("goal", "explore", "planner");
+        let path = std::env::temp_dir().join(format!("memory_snapshot_{}.bin", std::process::id()));
+        let saved = manager.snapshot(&path).unwrap();
+
+        manager.semantic_mut().delete(east);
+        manager.blackboard().remove("goal");
+        let restored = MemoryManager::new().unwrap();
+        assert_eq!(restored.restore(&path).unwrap(), saved);
+        assert_eq!(manager.restore(&path).unwrap(), saved);
+        std::fs::remove_file(&path).unwrap();
+
+        for manager in [&manager, &restored] {
+            assert_eq!(manager.semantic().get(east), Some("east"));
+            assert_eq!(manager.semantic().search(&[0.1, 1.0], 1).unwrap()[0].payload, "north");
+            assert_eq!(manager.blackboard().get_as::<String>("goal").unwrap(), "explore");
+        }
+        assert!(manager.remember(&[1.0, 1.0], "northeast").unwrap() > east + 1);
+    }
+
+    #[test]
+    fn test_concurrent_readers_share_the_stores() {
+        let manager = MemoryManager::new().unwrap();
+        manager.remember(&[1.0, 0.0], "east").unwrap();
+        std::thread::scope(|scope| {
+            for thread in 0..4 {
+                let manager = &manager;
+                scope.spawn(move || {
+                    for i in 0..50 {
+                        assert_eq!(manager.semantic().search(&[1.0, 0.1], 1).unwrap()[0].payload, "east");
+                        manager.blackboard().put(&format!("thread.{}.{}", thread, i % 5), i as f64, "test");
+                    }
+                });
+            }
+        });
+        assert_eq!(manager.semantic().stats().searches, 200);
+        assert_eq!(manager.blackboard().len(), 20);
+        assert_eq!(manager.blackboard().keys("thread.3.").len(), 5);
+    }
+
+    #[test]
+    fn test_pressure_subscribers_see_level_changes() {
+        let manager = MemoryManager::new().unwrap();
+        manager.set_pressure_thresholds(PressureThresholds { elevated: 0.0, critical: 2.0 });
+        let levels = Arc::new(Mutex::new(Vec::new()));
+        let seen = levels.clone();
+        let id = manager.subscribe_pressure(move |reading| seen.lock().unwrap().push(reading.level));
+
+        assert_eq!(manager.check_pressure().level, PressureLevel::Elevated);
+        manager.check_pressure();
+        manager.set_pressure_thresholds(PressureThresholds::default());
+        manager.set_budget(Some(usize::MAX / 2));
+        assert_eq!(manager.check_pressure().level, manager.pressure_level());
+        if manager.pressure_level() == PressureLevel::Normal {
+            assert_eq!(*levels.lock().unwrap(), [PressureLevel::Elevated, PressureLevel::Normal]);
+        }
+        assert!(manager.unsubscribe_pressure(id));
+    }
+
+    #[test]
+    fn test_budget_runs_evictors_before_failing() {
+        if tracking::global_counters().is_some() {
+            return;
+        }
+        let manager = MemoryManager::new().unwrap().with_budget(1000);
+        let held = Arc::new(std::sync::Mutex::new(vec![manager.allocate(600).unwrap()]));
+        let error = manager.allocate(600).unwrap_err();
+        assert!(matches!(error, AgiError::MemoryPressure(_)));
+
+        let evictable = held.clone();
+        manager.on_pressure("held", move |_| evictable.lock().unwrap().drain(..).map(|block| block.len()).sum());
+        let block = manager.allocate(600).unwrap();
+        assert!(held.lock().unwrap().is_empty());
+        assert_eq!(manager.reserve(500), Err(MemoryPressure { requested: 500, used: 600, budget: 1000, released: 0 }));
+        drop(block);
+        assert!(manager.remove_evictor("held"));
+        assert_eq!(manager.budget(), Some(1000));
+    }
+}
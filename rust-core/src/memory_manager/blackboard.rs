@@ -0,0 +1,341 @@
+//! Blackboard - Key-value working memory shared across engines
+//!
+//! A concurrent map from string keys to typed values that any component holding
+//! the `Arc<Blackboard>` from `MemoryManager::blackboard` can read and write.
+//! The neural engine publishes its latest response and the consciousness engine
+//! its latest state here, so components can exchange intermediate results without
+//! a round trip through the host application. Every write bumps a global version,
+//! so readers can poll for what changed since they last looked.
+//!
+//! Entries are sharded by key, so writers of one key do not hold up readers of
+//! another. Entries count toward the manager's usage and budget, and the store is
+//! registered for eviction as `blackboard`, with no rules until some are set.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use super::block::Usage;
+use super::eviction::{AccessLog, Evictable, EvictionEntry, EvictionTarget, NEUTRAL_IMPORTANCE};
+use super::shard::Sharded;
+use super::subsystem::Subsystem;
+use crate::tensor_ops::Tensor;
+use crate::util::now_ms;
+
+/// Key of the neural engine's latest output vector
+pub const NEURAL_OUTPUT: &str = "neural.output";
+/// Key of the neural engine's latest pattern confidence
+pub const NEURAL_CONFIDENCE: &str = "neural.pattern_confidence";
+/// Key of the consciousness engine's latest dimensions, in `DIMENSION_NAMES` order
+pub const CONSCIOUSNESS_STATE: &str = "consciousness.state";
+/// Key of the consciousness engine's latest emotional state label
+pub const CONSCIOUSNESS_EMOTION: &str = "consciousness.emotional_state";
+
+/// A value on the blackboard
+#[derive(Debug, Clone)]
+pub enum BlackboardValue {
+    Flag(bool),
+    Number(f64),
+    Text(String),
+    Vector(Vec<f64>),
+    Tensor(Tensor),
+}
+
+impl BlackboardValue {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Flag(_) => "flag",
+            Self::Number(_) => "number",
+            Self::Text(_) => "text",
+            Self::Vector(_) => "vector",
+            Self::Tensor(_) => "tensor",
+        }
+    }
+
+    /// Approximate heap and inline size
+    pub fn bytes(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + match self {
+                Self::Flag(_) | Self::Number(_) => 0,
+                Self::Text(text) => text.len(),
+                Self::Vector(vector) => vector.len() * std::mem::size_of::<f64>(),
+                Self::Tensor(tensor) => (tensor.data.len() + tensor.shape.len()) * std::mem::size_of::<f64>(),
+            }
+    }
+}
+
+macro_rules! blackboard_conversions {
+    ($($variant:ident($type:ty)),*) => {$(
+        impl From<$type> for BlackboardValue {
+            fn from(value: $type) -> Self {
+                Self::$variant(value)
+            }
+        }
+
+        impl TryFrom<BlackboardValue> for $type {
+            type Error = String;
+
+            fn try_from(value: BlackboardValue) -> Result<Self, String> {
+                match value {
+                    BlackboardValue::$variant(inner) => Ok(inner),
+                    other => Err(format!("Expected a {} but found a {}", stringify!($variant).to_lowercase(), other.kind())),
+                }
+            }
+        }
+    )*};
+}
+
+blackboard_conversions!(Flag(bool), Number(f64), Text(String), Vector(Vec<f64>), Tensor(Tensor));
+
+impl From<&str> for BlackboardValue {
+    fn from(value: &str) -> Self {
+        Self::Text(value.to_string())
+    }
+}
+
+/// A value with who wrote it and when
+#[derive(Debug, Clone)]
+pub struct BlackboardEntry {
+    pub value: BlackboardValue,
+    /// Component that wrote the value
+    pub writer: String,
+    /// Blackboard version of the write
+    pub version: u64,
+    /// Milliseconds since the Unix epoch
+    pub updated_ms: u64,
+}
+
+/// Size and traffic of a blackboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct BlackboardStats {
+    pub entries: usize,
+    pub bytes: usize,
+    pub version: u64,
+    pub reads: u64,
+    pub writes: u64,
+}
+
+/// Concurrent map of named values
+#[derive(Debug)]
+pub struct Blackboard {
+    /// Entries sharded by key hash
+    entries: Sharded<HashMap<String, BlackboardEntry>>,
+    len: AtomicUsize,
+    version: AtomicU64,
+    reads: AtomicU64,
+    bytes: AtomicU64,
+    /// Access records by key hash
+    access: AccessLog,
+    usage: Arc<Usage>,
+}
+
+fn key_hash(key: &str) -> u64 {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn entry_bytes(key: &str, entry: &BlackboardEntry) -> usize {
+    key.len() + entry.writer.len() + std::mem::size_of::<BlackboardEntry>() + entry.value.bytes()
+}
+
+impl Blackboard {
+    pub(crate) fn new(usage: Arc<Usage>) -> Self {
+        Self {
+            entries: Sharded::default(),
+            len: AtomicUsize::new(0),
+            version: AtomicU64::new(0),
+            reads: AtomicU64::new(0),
+            bytes: AtomicU64::new(0),
+            access: AccessLog::default(),
+            usage,
+        }
+    }
+
+    /// Write `value` under `key` on behalf of `writer`, returning the new version
+    pub fn put(&self, key: &str, value: impl Into<BlackboardValue>, writer: &str) -> u64 {
+        let entry = BlackboardEntry { value: value.into(), writer: writer.to_string(), version: 0, updated_ms: now_ms() };
+        self.insert(key.to_string(), entry)
+    }
+
+    /// Store `entry` under `key` at the next version, returning it
+    fn insert(&self, key: String, mut entry: BlackboardEntry) -> u64 {
+        let hash = key_hash(&key);
+        let mut shard = self.entries.write(hash);
+        entry.version = self.version.fetch_add(1, Ordering::Relaxed) + 1;
+        let version = entry.version;
+        self.charge(entry_bytes(&key, &entry));
+        match shard.get(&key) {
+            Some(previous) => self.refund(entry_bytes(&key, previous)),
+            None => {
+                self.len.fetch_add(1, Ordering::Relaxed);
+                self.access.insert(hash);
+            }
+        }
+        shard.insert(key, entry);
+        version
+    }
+
+    fn charge(&self, bytes: usize) {
+        self.bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+        self.usage.added(Subsystem::Blackboard, bytes);
+    }
+
+    fn refund(&self, bytes: usize) {
+        self.bytes.fetch_sub(bytes as u64, Ordering::Relaxed);
+        self.usage.removed(Subsystem::Blackboard, bytes);
+    }
+
+    /// Value and metadata under `key`
+    pub fn entry(&self, key: &str) -> Option<BlackboardEntry> {
+        self.reads.fetch_add(1, Ordering::Relaxed);
+        let entry = self.entries.read(key_hash(key)).get(key).cloned()?;
+        self.access.touch(key_hash(key));
+        Some(entry)
+    }
+
+    pub fn get(&self, key: &str) -> Option<BlackboardValue> {
+        self.entry(key).map(|entry| entry.value)
+    }
+
+    /// Value under `key` as a `T`, or an error if it is missing or of another kind
+    pub fn get_as<T: TryFrom<BlackboardValue, Error = String>>(&self, key: &str) -> Result<T, String> {
+        self.get(key).ok_or_else(|| format!("No blackboard entry '{}'", key)).and_then(T::try_from)
+    }
+
+    pub fn remove(&self, key: &str) -> Option<BlackboardValue> {
+        self.remove_entry(key).map(|(_, entry)| entry.value)
+    }
+
+    fn remove_entry(&self, key: &str) -> Option<(String, BlackboardEntry)> {
+        let (key, entry) = self.entries.write(key_hash(key)).remove_entry(key)?;
+        self.len.fetch_sub(1, Ordering::Relaxed);
+        self.refund(entry_bytes(&key, &entry));
+        self.access.remove(key_hash(&key));
+        Some((key, entry))
+    }
+
+    /// Replace every entry with `entries`, keeping their writers and times but
+    /// writing them under new versions so pollers see them as changed
+    pub(crate) fn restore(&self, entries: Vec<(String, BlackboardEntry)>) {
+        for mut shard in self.entries.write_each() {
+            for (key, entry) in shard.drain() {
+                self.len.fetch_sub(1, Ordering::Relaxed);
+                self.refund(entry_bytes(&key, &entry));
+                self.access.remove(key_hash(&key));
+            }
+        }
+        for (key, entry) in entries {
+            self.insert(key, entry);
+        }
+    }
+
+    /// Keys starting with `prefix`, sorted
+    pub fn keys(&self, prefix: &str) -> Vec<String> {
+        let mut keys: Vec<String> = self.entries.read_each()
+            .flat_map(|shard| shard.keys().filter(|key| key.starts_with(prefix)).cloned().collect::<Vec<_>>())
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    /// Entries written after `version`, oldest write first
+    pub fn changed_since(&self, version: u64) -> Vec<(String, BlackboardEntry)> {
+        let mut changed: Vec<(String, BlackboardEntry)> = self.entries.read_each()
+            .flat_map(|shard| {
+                shard.iter()
+                    .filter(|(_, entry)| entry.version > version)
+                    .map(|(key, entry)| (key.clone(), entry.clone()))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        changed.sort_by_key(|(_, entry)| entry.version);
+        changed
+    }
+
+    /// Version of the latest write
+    pub fn version(&self) -> u64 {
+        self.version.load(Ordering::Relaxed)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn stats(&self) -> BlackboardStats {
+        BlackboardStats {
+            entries: self.len(),
+            bytes: self.bytes.load(Ordering::Relaxed) as usize,
+            version: self.version(),
+            reads: self.reads.load(Ordering::Relaxed),
+            writes: self.version(),
+        }
+    }
+}
+
+/// Eviction view of a shared blackboard
+struct Evicting<'a>(&'a Blackboard);
+
+impl Evictable for Evicting<'_> {
+    fn eviction_entries(&self) -> Vec<EvictionEntry> {
+        self.0.entries.read_each()
+            .flat_map(|shard| {
+                shard.iter()
+                    .map(|(key, entry)| self.0.access.entry(key_hash(key), entry_bytes(key, entry), NEUTRAL_IMPORTANCE))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    fn evict(&mut self, key: u64) -> usize {
+        let name = self.0.entries.read(key).keys().find(|name| key_hash(name) == key).cloned();
+        name.and_then(|name| self.0.remove_entry(&name)).map_or(0, |(name, entry)| entry_bytes(&name, &entry))
+    }
+}
+
+impl EvictionTarget for Blackboard {
+    fn with_evictable(&self, apply: &mut dyn FnMut(&mut dyn Evictable)) {
+        apply(&mut Evicting(self));
+    }
+}
+
+impl Drop for Blackboard {
+    fn drop(&mut self) {
+        self.usage.removed(Subsystem::Blackboard, *self.bytes.get_mut() as usize);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_typed_values_and_versions() {
+        let usage = Arc::new(Usage::default());
+        let board = Blackboard::new(usage.clone());
+        board.put("neural.loss", 0.25, "trainer");
+        let version = board.version();
+        board.put("goal", "explore", "planner");
+        board.put("embedding", vec![0.1, 0.2], "encoder");
+        board.put("goal", "exploit", "planner");
+
+        assert_eq!(board.get_as::<f64>("neural.loss"), Ok(0.25));
+        assert_eq!(board.get_as::<String>("goal").unwrap(), "exploit");
+        assert!(board.get_as::<f64>("goal").unwrap_err().contains("text"));
+        assert!(board.get_as::<bool>("missing").is_err());
+
+        let changed: Vec<String> = board.changed_since(version).into_iter().map(|(key, _)| key).collect();
+        assert_eq!(changed, ["embedding", "goal"]);
+        assert_eq!(board.keys("neural."), ["neural.loss"]);
+        assert_eq!(usage.counters().current, board.stats().bytes);
+        board.remove("embedding");
+        assert_eq!(usage.counters().current, board.stats().bytes);
+    }
+}
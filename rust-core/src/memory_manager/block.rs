@@ -0,0 +1,184 @@
+//! Memory Blocks - Owned allocations released on drop
+//!
+//! `MemoryBlock` owns a zeroed, aligned byte buffer allocated through the
+//! `MemoryManager` and returns it when dropped, so callers never pair pointers
+//! with sizes by hand. `TypedBlock<T>` is the same for a slice of `T`, derefs to
+//! `[T]`, and initializes every element to `T::default()`. Both keep the
+//! manager's usage counters current from any thread, charged to the subsystem
+//! whose scope they were allocated in.
+
+use std::alloc::Layout;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use super::subsystem::{self, Ledger, Subsystem};
+use super::AllocationCounters;
+use crate::error::AgiError;
+
+/// Alignment of blocks when none is requested: one cache line
+pub const DEFAULT_ALIGNMENT: usize = 64;
+
+/// Bytes and calls of blocks allocated through one manager
+#[derive(Debug, Default)]
+pub(crate) struct Usage {
+    current: AtomicUsize,
+    peak: AtomicUsize,
+    allocations: AtomicUsize,
+    deallocations: AtomicUsize,
+    /// Current bytes by subsystem
+    ledger: Ledger,
+}
+
+impl Usage {
+    pub(crate) fn added(&self, subsystem: Subsystem, size: usize) {
+        self.ledger.added(subsystem, size);
+        let current = self.current.fetch_add(size, Ordering::Relaxed) + size;
+        self.peak.fetch_max(current, Ordering::Relaxed);
+        self.allocations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn removed(&self, subsystem: Subsystem, size: usize) {
+        self.ledger.removed(subsystem, size);
+        self.current.fetch_sub(size, Ordering::Relaxed);
+        self.deallocations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn counters(&self) -> AllocationCounters {
+        AllocationCounters {
+            current: self.current.load(Ordering::Relaxed),
+            peak: self.peak.load(Ordering::Relaxed),
+            allocations: self.allocations.load(Ordering::Relaxed),
+            deallocations: self.deallocations.load(Ordering::Relaxed),
+        }
+    }
+
+    pub(crate) fn ledger(&self) -> &Ledger {
+        &self.ledger
+    }
+}
+
+/// Zeroed, aligned bytes owned until dropped
+pub struct MemoryBlock {
+    ptr: NonNull<u8>,
+    layout: Layout,
+    /// Subsystem the block is charged to
+    subsystem: Subsystem,
+    usage: Arc<Usage>,
+}
+
+// The block uniquely owns its allocation
+unsafe impl Send for MemoryBlock {}
+unsafe impl Sync for MemoryBlock {}
+
+impl MemoryBlock {
+    pub(crate) fn new(size: usize, align: usize, usage: Arc<Usage>) -> Result<Self, AgiError> {
+        let layout = Layout::from_size_align(size, align).map_err(|e| AgiError::Memory(e.to_string()))?;
+        let ptr = if size == 0 {
+            // Zero-sized layouts must not be passed to the allocator
+            NonNull::new(align as *mut u8).ok_or_else(|| AgiError::Memory("Alignment must be non-zero".to_string()))?
+        } else {
+            NonNull::new(unsafe { std::alloc::alloc_zeroed(layout) }).ok_or_else(|| AgiError::Memory(format!("Failed to allocate {} bytes", size)))?
+        };
+        let subsystem = subsystem::current();
+        usage.added(subsystem, size);
+        Ok(Self { ptr, layout, subsystem, usage })
+    }
+
+    /// Size in bytes
+    pub fn len(&self) -> usize {
+        self.layout.size()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layout.size() == 0
+    }
+
+    pub fn align(&self) -> usize {
+        self.layout.align()
+    }
+
+    /// Subsystem whose scope the block was allocated in
+    pub fn subsystem(&self) -> Subsystem {
+        self.subsystem
+    }
+
+    /// Start of the block, valid for `len()` bytes while the block lives
+    pub fn as_ptr(&self) -> *const u8 {
+        self.ptr.as_ptr()
+    }
+
+    /// Mutable start of the block, valid for `len()` bytes while the block lives
+    pub fn as_mut_ptr(&mut self) -> *mut u8 {
+        self.ptr.as_ptr()
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len()) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len()) }
+    }
+}
+
+impl Drop for MemoryBlock {
+    fn drop(&mut self) {
+        if self.layout.size() > 0 {
+            unsafe { std::alloc::dealloc(self.ptr.as_ptr(), self.layout) };
+        }
+        self.usage.removed(self.subsystem, self.layout.size());
+    }
+}
+
+impl std::fmt::Debug for MemoryBlock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MemoryBlock").field("len", &self.len()).field("align", &self.align()).finish()
+    }
+}
+
+/// Slice of `T` in an owned block
+pub struct TypedBlock<T> {
+    block: MemoryBlock,
+    len: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Copy + Default> TypedBlock<T> {
+    pub(crate) fn new(len: usize, align: usize, usage: Arc<Usage>) -> Result<Self, AgiError> {
+        let size = std::mem::size_of::<T>().checked_mul(len).ok_or_else(|| AgiError::Memory("Slice size overflows".to_string()))?;
+        let mut block = MemoryBlock::new(size, align.max(std::mem::align_of::<T>()), usage)?;
+        let ptr = block.as_mut_ptr() as *mut T;
+        for index in 0..len {
+            unsafe { ptr.add(index).write(T::default()) };
+        }
+        Ok(Self { block, len, _marker: PhantomData })
+    }
+
+    /// The underlying byte block
+    pub fn block(&self) -> &MemoryBlock {
+        &self.block
+    }
+}
+
+impl<T> Deref for TypedBlock<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        unsafe { std::slice::from_raw_parts(self.block.as_ptr() as *const T, self.len) }
+    }
+}
+
+impl<T> DerefMut for TypedBlock<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        unsafe { std::slice::from_raw_parts_mut(self.block.as_mut_ptr() as *mut T, self.len) }
+    }
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for TypedBlock<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
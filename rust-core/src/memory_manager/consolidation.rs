@@ -0,0 +1,166 @@
+//! Memory Consolidation - Moving episodes into long-term memory
+//!
+//! A consolidation run walks the episodic store from the oldest episode. Episodes
+//! at least as important as the promotion threshold move into semantic memory,
+//! keyed by their input embedding with the input as payload, unless semantic
+//! memory already holds something nearly identical, in which case the episode is
+//! dropped as a duplicate. Episodes below the pruning threshold are dropped once
+//! they are old enough to have had their chance. Everything else stays
+//! short-term.
+//!
+//! `MemoryManager::consolidate` runs one pass, `optimize` includes one, and
+//! `AGISystem::start_memory_consolidation` runs them periodically.
+
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use super::{EpisodicStore, SemanticMemory};
+
+/// Thresholds of consolidation
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ConsolidationPolicy {
+    /// Least importance for an episode to move into semantic memory
+    pub promote_importance: f64,
+    /// Least similarity to an existing semantic memory for an episode to count as a duplicate
+    pub duplicate_similarity: f64,
+    /// Episodes less important than this are pruned once old enough
+    pub prune_importance: f64,
+    /// Least age of an episode before it may be pruned
+    pub prune_after: Duration,
+}
+
+impl Default for ConsolidationPolicy {
+    fn default() -> Self {
+        Self {
+            promote_importance: 0.6,
+            duplicate_similarity: 0.95,
+            prune_importance: 0.2,
+            prune_after: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Outcome of one consolidation run
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct ConsolidationRun {
+    pub examined: usize,
+    pub promoted: usize,
+    /// Important episodes dropped because semantic memory already held them
+    pub duplicates: usize,
+    pub pruned: usize,
+    /// Episodes semantic memory rejected, e.g. for a different embedding dimension
+    pub failed: usize,
+    pub episodic_bytes_released: usize,
+    pub semantic_bytes_added: usize,
+    pub duration: Duration,
+}
+
+/// Totals over all runs
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct ConsolidationMetrics {
+    pub runs: u64,
+    pub promoted: u64,
+    pub duplicates: u64,
+    pub pruned: u64,
+    pub last_run: Option<ConsolidationRun>,
+}
+
+/// Policy and running totals of a manager's consolidation
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Consolidator {
+    pub(crate) policy: ConsolidationPolicy,
+    pub(crate) metrics: ConsolidationMetrics,
+}
+
+impl Consolidator {
+    /// Run one pass over `episodic`, promoting into `semantic`
+    pub(crate) fn run(&mut self, episodic: &mut EpisodicStore, semantic: &mut SemanticMemory) -> ConsolidationRun {
+        let start = Instant::now();
+        let policy = self.policy;
+        let (episodic_before, semantic_before) = (episodic.bytes(), semantic.stats().bytes);
+        let now_ms = crate::util::now_ms();
+        let mut run = ConsolidationRun::default();
+
+        let mut released = Vec::new();
+        for episode in episodic.iter() {
+            run.examined += 1;
+            if episode.importance >= policy.promote_importance {
+                let nearest = semantic.search(&episode.embedding, 1).ok().and_then(|hits| hits.into_iter().next());
+                if nearest.is_some_and(|hit| hit.similarity >= policy.duplicate_similarity) {
+                    run.duplicates += 1;
+                } else if semantic.insert(&episode.embedding, episode.input.clone()).is_ok() {
+                    run.promoted += 1;
+                } else {
+                    run.failed += 1;
+                    continue;
+                }
+                released.push(episode.id);
+            } else if episode.importance < policy.prune_importance
+                && now_ms.saturating_sub(episode.timestamp_ms) >= policy.prune_after.as_millis() as u64
+            {
+                run.pruned += 1;
+                released.push(episode.id);
+            }
+        }
+        for id in released {
+            episodic.remove(id);
+        }
+
+        run.episodic_bytes_released = episodic_before - episodic.bytes();
+        run.semantic_bytes_added = semantic.stats().bytes.saturating_sub(semantic_before);
+        run.duration = start.elapsed();
+        self.metrics.runs += 1;
+        self.metrics.promoted += run.promoted as u64;
+        self.metrics.duplicates += run.duplicates as u64;
+        self.metrics.pruned += run.pruned as u64;
+        self.metrics.last_run = Some(run);
+        run
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use crate::consciousness::ConsciousnessEngine;
+    use crate::memory_manager::block::Usage;
+    use crate::memory_manager::{Episode, HnswConfig};
+
+    fn episode(input: &str, embedding: Vec<f64>, importance: f64) -> Episode {
+        Episode {
+            id: 0,
+            input: input.to_string(),
+            input_hash: 0,
+            embedding,
+            neural_output: Vec::new(),
+            activation_strength: 0.0,
+            pattern_confidence: 0.0,
+            coherence_score: 0.0,
+            consciousness: ConsciousnessEngine::new().unwrap().current_state().clone(),
+            confidence: importance,
+            timestamp_ms: 0,
+            importance,
+        }
+    }
+
+    #[test]
+    fn test_promotes_deduplicates_and_prunes() {
+        let usage = Arc::new(Usage::default());
+        let mut episodic = EpisodicStore::new(16, usage.clone());
+        let mut semantic = SemanticMemory::new(HnswConfig::default(), usage);
+        episodic.record(episode("stars fuse hydrogen", vec![1.0, 0.0, 0.0], 0.9));
+        episodic.record(episode("stars fuse hydrogen!", vec![0.99, 0.01, 0.0], 0.8));
+        episodic.record(episode("ok", vec![0.0, 1.0, 0.0], 0.1));
+        episodic.record(episode("maybe", vec![0.0, 0.0, 1.0], 0.4));
+
+        let mut consolidator = Consolidator::default();
+        let run = consolidator.run(&mut episodic, &mut semantic);
+        assert_eq!((run.examined, run.promoted, run.duplicates, run.pruned), (4, 1, 1, 1));
+        assert_eq!(episodic.len(), 1);
+        assert_eq!(semantic.search(&[1.0, 0.0, 0.0], 1).unwrap()[0].payload, "stars fuse hydrogen");
+        assert!(run.episodic_bytes_released > 0 && run.semantic_bytes_added > 0);
+        assert_eq!(consolidator.metrics.last_run, Some(run));
+    }
+}
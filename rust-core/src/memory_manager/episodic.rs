@@ -0,0 +1,349 @@
+//! Episodic Memory - What the system has processed
+//!
+//! Each result of `AGISystem::process_input` can be recorded as an `Episode`: the
+//! input with its hash and embedding, the neural output with its metrics, the
+//! consciousness state it left, and when it happened. Episodes carry an
+//! importance score from an `ImportanceModel`, and when the store is full the least important episode
+//! (the oldest among equals) makes room. They can be retrieved by recency or by
+//! cosine similarity of their input embeddings to a query.
+//!
+//! The store is owned by `MemoryManager`, which counts its bytes in the usage
+//! statistics and budget.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use super::block::Usage;
+use super::eviction::{AccessLog, Evictable, EvictionEntry};
+use super::importance::{self, ConsciousnessImportance};
+use super::subsystem::Subsystem;
+use crate::consciousness::ConsciousnessState;
+use crate::util::now_ms;
+use crate::ProcessingResult;
+
+/// Handle of a recorded episode
+pub type EpisodeId = u64;
+
+/// One processed input
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Episode {
+    pub id: EpisodeId,
+    pub input: String,
+    /// Hash of `input`, for matching repeats
+    pub input_hash: u64,
+    /// Embedding of the input, used for similarity retrieval
+    pub embedding: Vec<f64>,
+    pub neural_output: Vec<f64>,
+    pub activation_strength: f64,
+    pub pattern_confidence: f64,
+    pub coherence_score: f64,
+    pub consciousness: ConsciousnessState,
+    pub confidence: f64,
+    /// Milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+    /// Retention score in [0, 1]
+    pub importance: f64,
+}
+
+impl Episode {
+    /// Episode for `input` and its processing `result`, scored by the default `ConsciousnessImportance`
+    pub fn new(input: &str, embedding: Vec<f64>, result: &ProcessingResult) -> Self {
+        let mut hasher = DefaultHasher::new();
+        input.hash(&mut hasher);
+        let mut episode = Self {
+            id: 0,
+            input: input.to_string(),
+            input_hash: hasher.finish(),
+            embedding,
+            neural_output: result.neural_output.output.to_vec(),
+            activation_strength: result.neural_output.activation_strength,
+            pattern_confidence: result.neural_output.pattern_confidence,
+            coherence_score: result.neural_output.coherence_score,
+            consciousness: result.consciousness.clone(),
+            confidence: result.confidence,
+            timestamp_ms: now_ms(),
+            importance: 0.0,
+        };
+        episode.importance = importance::score(&ConsciousnessImportance::default(), &episode);
+        episode
+    }
+
+    /// Approximate heap and inline size
+    pub fn bytes(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.input.len()
+            + (self.embedding.len() + self.neural_output.len()) * std::mem::size_of::<f64>()
+    }
+}
+
+/// Counts of an episodic store
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct EpisodicStats {
+    pub episodes: usize,
+    pub capacity: usize,
+    pub bytes: usize,
+    pub recorded: u64,
+    /// Episodes removed to make room or release memory
+    pub evicted: u64,
+}
+
+/// Bounded store of episodes, in recording order
+pub struct EpisodicStore {
+    capacity: usize,
+    episodes: VecDeque<Episode>,
+    next_id: EpisodeId,
+    bytes: usize,
+    recorded: u64,
+    evicted: u64,
+    access: AccessLog,
+    usage: Arc<Usage>,
+}
+
+/// Cosine similarity, zero when either vector is zero or the lengths differ
+fn cosine(a: &[f64], b: &[f64]) -> f64 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f64 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norms = a.iter().map(|x| x * x).sum::<f64>().sqrt() * b.iter().map(|x| x * x).sum::<f64>().sqrt();
+    if norms > 0.0 { dot / norms } else { 0.0 }
+}
+
+impl EpisodicStore {
+    pub(crate) fn new(capacity: usize, usage: Arc<Usage>) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            episodes: VecDeque::new(),
+            next_id: 0,
+            bytes: 0,
+            recorded: 0,
+            evicted: 0,
+            access: AccessLog::default(),
+            usage,
+        }
+    }
+
+    /// Empty store continuing the ids and counters of a snapshot
+    pub(crate) fn resume(capacity: usize, next_id: EpisodeId, recorded: u64, evicted: u64, usage: Arc<Usage>) -> Self {
+        let mut store = Self::new(capacity, usage);
+        store.next_id = next_id;
+        store.recorded = recorded;
+        store.evicted = evicted;
+        store
+    }
+
+    /// Id the next recorded episode will get
+    pub(crate) fn next_id(&self) -> EpisodeId {
+        self.next_id
+    }
+
+    /// Append `episode` from a snapshot under its own id
+    pub(crate) fn restore(&mut self, episode: Episode) -> Result<(), String> {
+        if episode.id >= self.next_id || self.episodes.back().is_some_and(|last| last.id >= episode.id) {
+            return Err(format!("Episode {} is out of order", episode.id));
+        }
+        if self.episodes.len() == self.capacity {
+            return Err(format!("More episodes than the capacity of {}", self.capacity));
+        }
+        self.bytes += episode.bytes();
+        self.usage.added(Subsystem::Episodic, episode.bytes());
+        self.access.insert(episode.id);
+        self.episodes.push_back(episode);
+        Ok(())
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Change the capacity, evicting the least important episodes if it shrank
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        while self.episodes.len() > self.capacity {
+            self.evict_least_important();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.episodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.episodes.is_empty()
+    }
+
+    /// Bytes held by episodes
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// Store `episode` under a new id, making room if full
+    pub fn record(&mut self, mut episode: Episode) -> EpisodeId {
+        if self.episodes.len() == self.capacity {
+            self.evict_least_important();
+        }
+        episode.id = self.next_id;
+        self.next_id += 1;
+        self.recorded += 1;
+        self.bytes += episode.bytes();
+        self.usage.added(Subsystem::Episodic, episode.bytes());
+        self.access.insert(episode.id);
+        self.episodes.push_back(episode);
+        self.next_id - 1
+    }
+
+    pub fn get(&self, id: EpisodeId) -> Option<&Episode> {
+        // Ids increase in recording order
+        let index = self.episodes.binary_search_by_key(&id, |episode| episode.id).ok()?;
+        self.access.touch(id);
+        self.episodes.get(index)
+    }
+
+    /// Episodes in recording order
+    pub fn iter(&self) -> impl Iterator<Item = &Episode> {
+        self.episodes.iter()
+    }
+
+    /// Up to `count` most recent episodes, newest first
+    pub fn recent(&self, count: usize) -> Vec<&Episode> {
+        let recent: Vec<&Episode> = self.episodes.iter().rev().take(count).collect();
+        recent.iter().for_each(|episode| self.access.touch(episode.id));
+        recent
+    }
+
+    /// Up to `count` episodes whose inputs most resemble `query`, most similar first
+    pub fn similar(&self, query: &[f64], count: usize) -> Vec<(f64, &Episode)> {
+        let mut scored: Vec<(f64, &Episode)> =
+            self.episodes.iter().map(|episode| (cosine(query, &episode.embedding), episode)).collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+        scored.truncate(count);
+        scored.iter().for_each(|(_, episode)| self.access.touch(episode.id));
+        scored
+    }
+
+    pub fn remove(&mut self, id: EpisodeId) -> Option<Episode> {
+        let index = self.episodes.binary_search_by_key(&id, |episode| episode.id).ok()?;
+        let episode = self.episodes.remove(index)?;
+        self.forget(&episode);
+        Some(episode)
+    }
+
+    /// Evict the least important episodes until about `bytes` are released, returning the bytes released
+    pub fn release(&mut self, bytes: usize) -> usize {
+        let mut released = 0;
+        while released < bytes {
+            match self.evict_least_important() {
+                Some(episode) => released += episode.bytes(),
+                None => break,
+            }
+        }
+        released
+    }
+
+    /// Bytes of allocated but unused episode slots
+    pub fn spare_bytes(&self) -> usize {
+        (self.episodes.capacity() - self.episodes.len()) * std::mem::size_of::<Episode>()
+    }
+
+    /// Release unused episode slots, returning the bytes released
+    pub fn compact(&mut self) -> usize {
+        let spare = self.spare_bytes();
+        self.episodes.shrink_to_fit();
+        spare - self.spare_bytes()
+    }
+
+    pub fn stats(&self) -> EpisodicStats {
+        EpisodicStats {
+            episodes: self.episodes.len(),
+            capacity: self.capacity,
+            bytes: self.bytes,
+            recorded: self.recorded,
+            evicted: self.evicted,
+        }
+    }
+
+    fn evict_least_important(&mut self) -> Option<Episode> {
+        // `min_by` keeps the first of equals, which is the oldest
+        let index = self.episodes.iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.importance.total_cmp(&b.importance))
+            .map(|(index, _)| index)?;
+        let episode = self.episodes.remove(index)?;
+        self.forget(&episode);
+        self.evicted += 1;
+        Some(episode)
+    }
+
+    fn forget(&mut self, episode: &Episode) {
+        self.access.remove(episode.id);
+        self.bytes -= episode.bytes();
+        self.usage.removed(Subsystem::Episodic, episode.bytes());
+    }
+}
+
+impl Evictable for EpisodicStore {
+    fn eviction_entries(&self) -> Vec<EvictionEntry> {
+        self.episodes.iter().map(|episode| self.access.entry(episode.id, episode.bytes(), episode.importance)).collect()
+    }
+
+    fn evict(&mut self, key: u64) -> usize {
+        let released = self.remove(key).map_or(0, |episode| episode.bytes());
+        self.evicted += u64::from(released > 0);
+        released
+    }
+}
+
+impl Drop for EpisodicStore {
+    fn drop(&mut self) {
+        for episode in std::mem::take(&mut self.episodes) {
+            self.usage.removed(Subsystem::Episodic, episode.bytes());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consciousness::ConsciousnessEngine;
+
+    fn episode(embedding: Vec<f64>, importance: f64) -> Episode {
+        Episode {
+            id: 0,
+            input: format!("importance {}", importance),
+            input_hash: 0,
+            embedding,
+            neural_output: vec![0.5],
+            activation_strength: 0.5,
+            pattern_confidence: 0.5,
+            coherence_score: 0.5,
+            consciousness: ConsciousnessEngine::new().unwrap().current_state().clone(),
+            confidence: importance,
+            timestamp_ms: 0,
+            importance,
+        }
+    }
+
+    #[test]
+    fn test_store_evicts_least_important_and_retrieves() {
+        let usage = Arc::new(Usage::default());
+        let mut store = EpisodicStore::new(2, usage.clone());
+        let important = store.record(episode(vec![1.0, 0.0], 0.9));
+        store.record(episode(vec![0.0, 1.0], 0.1));
+        let latest = store.record(episode(vec![0.7, 0.7], 0.5));
+
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.stats().evicted, 1);
+        assert_eq!(store.recent(1)[0].id, latest);
+        assert_eq!(store.similar(&[1.0, 0.1], 1)[0].1.id, important);
+        assert_eq!(usage.counters().current, store.bytes());
+
+        assert!(store.remove(important).is_some());
+        assert!(store.get(important).is_none());
+        drop(store);
+        assert_eq!(usage.counters().current, 0);
+    }
+}
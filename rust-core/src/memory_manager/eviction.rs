@@ -0,0 +1,333 @@
+//! Eviction Policies - Choosing what to forget
+//!
+//! Caches and memory stores implement `Evictable`, listing their entries with
+//! size, age, use and importance. `MemoryManager` holds each store under a name
+//! with a list of `EvictionRule`s and applies them when usage would exceed the
+//! budget, and on `enforce_eviction`:
+//!
+//! - `Ttl` drops every entry older than its maximum age.
+//! - `Lru` drops the entries least recently used first.
+//! - `Lfu` drops the entries used least often first, the older of equals first.
+//! - `Importance` drops the least important entries first, the older of equals first.
+//!
+//! Size-driven rules drop entries until the store is within its rule's byte
+//! limit and the bytes the budget still needs are released. Every eviction is
+//! counted under the policy that caused it.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::shard::Sharded;
+use crate::util::now_ms;
+
+/// Importance of entries that carry no score of their own
+pub const NEUTRAL_IMPORTANCE: f64 = 0.5;
+
+/// One entry as an eviction policy sees it
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EvictionEntry {
+    pub key: u64,
+    pub bytes: usize,
+    /// Milliseconds since the Unix epoch
+    pub created_ms: u64,
+    pub last_access_ms: u64,
+    pub accesses: u64,
+    pub importance: f64,
+}
+
+/// A cache or store whose entries can be evicted
+pub trait Evictable: Send + Sync {
+    fn eviction_entries(&self) -> Vec<EvictionEntry>;
+
+    /// Remove the entry, returning the bytes released
+    fn evict(&mut self, key: u64) -> usize;
+}
+
+/// Shared handle through which the manager reaches a store
+pub trait EvictionTarget: Send + Sync {
+    fn with_evictable(&self, apply: &mut dyn FnMut(&mut dyn Evictable));
+}
+
+impl<T: Evictable> EvictionTarget for RwLock<T> {
+    fn with_evictable(&self, apply: &mut dyn FnMut(&mut dyn Evictable)) {
+        apply(&mut *self.write().unwrap_or_else(|e| e.into_inner()));
+    }
+}
+
+impl<T: Evictable> EvictionTarget for Mutex<T> {
+    fn with_evictable(&self, apply: &mut dyn FnMut(&mut dyn Evictable)) {
+        apply(&mut *self.lock().unwrap_or_else(|e| e.into_inner()));
+    }
+}
+
+/// Order in which entries are evicted
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum EvictionPolicy {
+    /// Evict entries older than this, regardless of size
+    Ttl(Duration),
+    Lru,
+    Lfu,
+    Importance,
+}
+
+impl EvictionPolicy {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Ttl(_) => "ttl",
+            Self::Lru => "lru",
+            Self::Lfu => "lfu",
+            Self::Importance => "importance",
+        }
+    }
+
+    /// Keys to evict from `entries` to release `bytes`, in eviction order
+    pub fn victims(&self, mut entries: Vec<EvictionEntry>, now_ms: u64, bytes: usize) -> Vec<u64> {
+        let oldest_first = |a: &EvictionEntry, b: &EvictionEntry| a.created_ms.cmp(&b.created_ms);
+        match self {
+            Self::Ttl(max_age) => {
+                let max_age = max_age.as_millis() as u64;
+                return entries.iter()
+                    .filter(|entry| now_ms.saturating_sub(entry.created_ms) > max_age)
+                    .map(|entry| entry.key)
+                    .collect();
+            }
+            Self::Lru => entries.sort_by_key(|entry| entry.last_access_ms),
+            Self::Lfu => entries.sort_by(|a, b| a.accesses.cmp(&b.accesses).then_with(|| oldest_first(a, b))),
+            Self::Importance => entries.sort_by(|a, b| a.importance.total_cmp(&b.importance).then_with(|| oldest_first(a, b))),
+        }
+        let mut released = 0;
+        entries.into_iter()
+            .take_while(|entry| {
+                let take = released < bytes;
+                released += entry.bytes;
+                take
+            })
+            .map(|entry| entry.key)
+            .collect()
+    }
+}
+
+/// A policy, with the size a store may keep under it
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EvictionRule {
+    pub policy: EvictionPolicy,
+    /// Bytes the store may hold before the policy evicts on its own; `None` evicts only under pressure
+    pub max_bytes: Option<usize>,
+}
+
+impl EvictionRule {
+    /// Rule applied only under budget pressure, or always for `Ttl`
+    pub fn new(policy: EvictionPolicy) -> Self {
+        Self { policy, max_bytes: None }
+    }
+
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+}
+
+/// Evictions caused by one policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct PolicyCounters {
+    pub entries: u64,
+    pub bytes: u64,
+}
+
+/// Evictions by policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct EvictionStats {
+    pub ttl: PolicyCounters,
+    pub lru: PolicyCounters,
+    pub lfu: PolicyCounters,
+    pub importance: PolicyCounters,
+}
+
+impl EvictionStats {
+    fn counters_mut(&mut self, policy: &EvictionPolicy) -> &mut PolicyCounters {
+        match policy {
+            EvictionPolicy::Ttl(_) => &mut self.ttl,
+            EvictionPolicy::Lru => &mut self.lru,
+            EvictionPolicy::Lfu => &mut self.lfu,
+            EvictionPolicy::Importance => &mut self.importance,
+        }
+    }
+
+    pub fn total(&self) -> PolicyCounters {
+        [self.ttl, self.lru, self.lfu, self.importance].iter().fold(PolicyCounters::default(), |sum, counters| PolicyCounters {
+            entries: sum.entries + counters.entries,
+            bytes: sum.bytes + counters.bytes,
+        })
+    }
+}
+
+/// Creation, last use and use count of one entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Access {
+    pub created_ms: u64,
+    pub last_ms: u64,
+    pub count: u64,
+}
+
+/// Access records of a store's entries, updatable through shared references
+///
+/// Sharded by key, so uses of different entries recorded from concurrent
+/// readers rarely contend.
+#[derive(Debug, Default, Clone)]
+pub struct AccessLog {
+    records: Sharded<HashMap<u64, Access>>,
+}
+
+impl AccessLog {
+    /// Start tracking `key`, created now
+    pub fn insert(&self, key: u64) {
+        let now = now_ms();
+        self.records.write(key).insert(key, Access { created_ms: now, last_ms: now, count: 0 });
+    }
+
+    /// Record a use of `key`
+    pub fn touch(&self, key: u64) {
+        if let Some(access) = self.records.write(key).get_mut(&key) {
+            access.last_ms = now_ms();
+            access.count += 1;
+        }
+    }
+
+    pub fn get(&self, key: u64) -> Option<Access> {
+        self.records.read(key).get(&key).copied()
+    }
+
+    pub fn remove(&self, key: u64) {
+        self.records.write(key).remove(&key);
+    }
+
+    pub fn clear(&self) {
+        self.records.write_each().for_each(|mut shard| shard.clear());
+    }
+
+    /// Entry of `key` with its recorded access, or as just created if untracked
+    pub fn entry(&self, key: u64, bytes: usize, importance: f64) -> EvictionEntry {
+        let access = self.get(key).unwrap_or_else(|| {
+            let now = now_ms();
+            Access { created_ms: now, last_ms: now, count: 0 }
+        });
+        EvictionEntry { key, bytes, created_ms: access.created_ms, last_access_ms: access.last_ms, accesses: access.count, importance }
+    }
+}
+
+struct Target {
+    name: String,
+    store: Arc<dyn EvictionTarget>,
+    rules: Vec<EvictionRule>,
+}
+
+/// Named stores and their rules
+#[derive(Default)]
+pub(crate) struct Evictions {
+    targets: RwLock<Vec<Target>>,
+    stats: Mutex<EvictionStats>,
+}
+
+impl Evictions {
+    /// Register `store` under `name`, replacing a store of the same name
+    pub(crate) fn register(&self, name: &str, store: Arc<dyn EvictionTarget>, rules: Vec<EvictionRule>) {
+        let mut targets = self.targets.write().unwrap_or_else(|e| e.into_inner());
+        targets.retain(|target| target.name != name);
+        targets.push(Target { name: name.to_string(), store, rules });
+    }
+
+    /// Replace the rules of `name`, returning whether it is registered
+    pub(crate) fn set_rules(&self, name: &str, rules: Vec<EvictionRule>) -> bool {
+        let mut targets = self.targets.write().unwrap_or_else(|e| e.into_inner());
+        targets.iter_mut().find(|target| target.name == name).map(|target| target.rules = rules).is_some()
+    }
+
+    pub(crate) fn rules(&self, name: &str) -> Option<Vec<EvictionRule>> {
+        let targets = self.targets.read().unwrap_or_else(|e| e.into_inner());
+        targets.iter().find(|target| target.name == name).map(|target| target.rules.clone())
+    }
+
+    pub(crate) fn stats(&self) -> EvictionStats {
+        *self.stats.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Apply every store's rules, releasing at least `bytes` in total if possible; returns the bytes released
+    pub(crate) fn enforce(&self, bytes: usize) -> usize {
+        let now = now_ms();
+        let mut released = 0;
+        let targets = self.targets.read().unwrap_or_else(|e| e.into_inner());
+        for target in targets.iter() {
+            target.store.with_evictable(&mut |store| {
+                for rule in &target.rules {
+                    let entries = store.eviction_entries();
+                    let held: usize = entries.iter().map(|entry| entry.bytes).sum();
+                    let excess = rule.max_bytes.map_or(0, |max_bytes| held.saturating_sub(max_bytes));
+                    let needed = excess.max(bytes.saturating_sub(released));
+                    if needed == 0 && !matches!(rule.policy, EvictionPolicy::Ttl(_)) {
+                        continue;
+                    }
+                    let mut counters = PolicyCounters::default();
+                    for key in rule.policy.victims(entries, now, needed) {
+                        let freed = store.evict(key);
+                        counters.entries += 1;
+                        counters.bytes += freed as u64;
+                        released += freed;
+                    }
+                    let mut stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+                    let total = stats.counters_mut(&rule.policy);
+                    total.entries += counters.entries;
+                    total.bytes += counters.bytes;
+                }
+            });
+        }
+        released
+    }
+
+    /// Evict entries unused for at least `max_idle` from every store not named in `skip`
+    ///
+    /// Counted as `Lru` evictions, since idle entries are the least recently used.
+    pub(crate) fn evict_idle(&self, max_idle: Duration, skip: &[&str]) -> PolicyCounters {
+        let now = now_ms();
+        let max_idle = max_idle.as_millis() as u64;
+        let mut evicted = PolicyCounters::default();
+        let targets = self.targets.read().unwrap_or_else(|e| e.into_inner());
+        for target in targets.iter().filter(|target| !skip.contains(&target.name.as_str())) {
+            target.store.with_evictable(&mut |store| {
+                let idle: Vec<u64> = store.eviction_entries().into_iter()
+                    .filter(|entry| now.saturating_sub(entry.last_access_ms) >= max_idle)
+                    .map(|entry| entry.key)
+                    .collect();
+                for key in idle {
+                    evicted.entries += 1;
+                    evicted.bytes += store.evict(key) as u64;
+                }
+            });
+        }
+        let mut stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+        stats.lru.entries += evicted.entries;
+        stats.lru.bytes += evicted.bytes;
+        evicted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(key: u64, created_ms: u64, last_access_ms: u64, accesses: u64, importance: f64) -> EvictionEntry {
+        EvictionEntry { key, bytes: 10, created_ms, last_access_ms, accesses, importance }
+    }
+
+    #[test]
+    fn test_policies_order_victims() {
+        let entries = vec![entry(1, 100, 900, 5, 0.9), entry(2, 200, 300, 1, 0.5), entry(3, 300, 800, 1, 0.1)];
+        assert_eq!(EvictionPolicy::Ttl(Duration::from_millis(750)).victims(entries.clone(), 1000, 0), vec![1, 2]);
+        assert_eq!(EvictionPolicy::Lru.victims(entries.clone(), 1000, 15), vec![2, 3]);
+        assert_eq!(EvictionPolicy::Lfu.victims(entries.clone(), 1000, 10), vec![2]);
+        assert_eq!(EvictionPolicy::Importance.victims(entries.clone(), 1000, 25), vec![3, 2, 1]);
+        assert!(EvictionPolicy::Lru.victims(entries, 1000, 0).is_empty());
+    }
+}
@@ -0,0 +1,100 @@
+//! Importance - How much an episode is worth keeping
+//!
+//! An `ImportanceModel` scores each episode as `MemoryManager::record_episode`
+//! stores it. The score decides which episodes the full store evicts first and
+//! which consolidation promotes or prunes. The default
+//! `ConsciousnessImportance` weighs the result's confidence with the awareness
+//! and attention focus the consciousness engine had at the time, so what was
+//! stored while the system was alert and focused outlives what was stored while
+//! it drifted. Any `Fn(&Episode) -> f64` is a model too:
+//!
+//! ```ignore
+//! manager.set_importance_model(|episode: &Episode| episode.pattern_confidence);
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use super::episodic::Episode;
+use super::eviction::NEUTRAL_IMPORTANCE;
+
+/// Scores episodes for retention
+pub trait ImportanceModel: Send + Sync {
+    /// Retention score of `episode`, about to be recorded; clamped to [0, 1]
+    fn score(&self, episode: &Episode) -> f64;
+}
+
+impl<F: Fn(&Episode) -> f64 + Send + Sync> ImportanceModel for F {
+    fn score(&self, episode: &Episode) -> f64 {
+        self(episode)
+    }
+}
+
+/// Weighted mean of confidence, awareness and attention focus
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ConsciousnessImportance {
+    pub confidence: f64,
+    pub awareness: f64,
+    pub attention: f64,
+}
+
+impl Default for ConsciousnessImportance {
+    fn default() -> Self {
+        Self { confidence: 0.4, awareness: 0.3, attention: 0.3 }
+    }
+}
+
+impl ImportanceModel for ConsciousnessImportance {
+    fn score(&self, episode: &Episode) -> f64 {
+        let total = self.confidence + self.awareness + self.attention;
+        if total <= 0.0 {
+            return NEUTRAL_IMPORTANCE;
+        }
+        let state = &episode.consciousness;
+        (self.confidence * episode.confidence + self.awareness * state.awareness_level + self.attention * state.attention_focus) / total
+    }
+}
+
+/// `model`'s score of `episode`, clamped to [0, 1], or neutral if not a number
+pub(crate) fn score(model: &dyn ImportanceModel, episode: &Episode) -> f64 {
+    let score = model.score(episode);
+    if score.is_nan() { NEUTRAL_IMPORTANCE } else { score.clamp(0.0, 1.0) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consciousness::ConsciousnessEngine;
+
+    fn episode(awareness: f64, attention: f64) -> Episode {
+        let mut consciousness = ConsciousnessEngine::new().unwrap().current_state().clone();
+        consciousness.awareness_level = awareness;
+        consciousness.attention_focus = attention;
+        Episode {
+            id: 0,
+            input: String::new(),
+            input_hash: 0,
+            embedding: vec![1.0],
+            neural_output: vec![0.5],
+            activation_strength: 0.5,
+            pattern_confidence: 0.5,
+            coherence_score: 0.5,
+            consciousness,
+            confidence: 0.5,
+            timestamp_ms: 0,
+            importance: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_alert_focused_episodes_score_higher() {
+        let model = ConsciousnessImportance::default();
+        let (focused, drifting) = (episode(0.9, 0.9), episode(0.2, 0.1));
+        assert!(model.score(&focused) > model.score(&drifting));
+        assert!((model.score(&focused) - 0.74).abs() < 1e-12);
+
+        let awareness_only = ConsciousnessImportance { confidence: 0.0, awareness: 1.0, attention: 0.0 };
+        assert_eq!(score(&awareness_only, &drifting), 0.2);
+        assert_eq!(score(&|_: &Episode| 7.0, &drifting), 1.0);
+        assert_eq!(score(&|_: &Episode| f64::NAN, &drifting), NEUTRAL_IMPORTANCE);
+    }
+}
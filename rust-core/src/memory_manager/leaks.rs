@@ -0,0 +1,170 @@
+//! Leak Tracking - Outstanding allocations by site
+//!
+//! Handles given to foreign callers (tensors, strings, the system itself) are
+//! freed only when the caller remembers to. With the `leak-tracking` feature,
+//! every handle the FFI hands out is recorded with the source location that
+//! created it, the subsystem of the enclosing scope and, when `RUST_BACKTRACE`
+//! is set, a backtrace; freeing the handle removes the record. `outstanding`
+//! groups what is still live by site, `agi_leak_report` returns the same as
+//! JSON, and `agi_cleanup` logs it on shutdown.
+//!
+//! Without the feature, `track` and `untrack` do nothing and the report is
+//! always empty.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::subsystem::Subsystem;
+
+/// Live allocations created at one source location
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AllocationSite {
+    /// `file:line:column` of the creating call
+    pub site: String,
+    pub subsystem: Subsystem,
+    pub allocations: usize,
+    pub bytes: usize,
+    /// Backtrace of one of the allocations, if captured
+    pub backtrace: Option<String>,
+}
+
+/// Allocations recorded and not yet freed
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct LeakReport {
+    pub allocations: usize,
+    pub bytes: usize,
+    /// Sites by bytes outstanding, largest first
+    pub sites: Vec<AllocationSite>,
+}
+
+impl LeakReport {
+    pub fn is_empty(&self) -> bool {
+        self.allocations == 0
+    }
+}
+
+impl fmt::Display for LeakReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} outstanding allocations, {} bytes", self.allocations, self.bytes)?;
+        for site in &self.sites {
+            writeln!(f, "  {} ({}): {} allocations, {} bytes", site.site, site.subsystem.name(), site.allocations, site.bytes)?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether allocations are being recorded
+pub fn enabled() -> bool {
+    cfg!(feature = "leak-tracking")
+}
+
+#[cfg(feature = "leak-tracking")]
+mod registry {
+    use std::backtrace::{Backtrace, BacktraceStatus};
+    use std::collections::HashMap;
+    use std::panic::Location;
+    use std::sync::{Mutex, OnceLock};
+
+    use super::{AllocationSite, LeakReport, Subsystem};
+
+    struct Record {
+        site: &'static Location<'static>,
+        subsystem: Subsystem,
+        bytes: usize,
+        backtrace: Option<String>,
+    }
+
+    fn records() -> &'static Mutex<HashMap<usize, Record>> {
+        static RECORDS: OnceLock<Mutex<HashMap<usize, Record>>> = OnceLock::new();
+        RECORDS.get_or_init(Mutex::default)
+    }
+
+    pub(super) fn insert(address: usize, bytes: usize, site: &'static Location<'static>) {
+        let backtrace = Backtrace::capture();
+        let backtrace = (backtrace.status() == BacktraceStatus::Captured).then(|| backtrace.to_string());
+        let record = Record { site, subsystem: crate::memory_manager::subsystem::current(), bytes, backtrace };
+        records().lock().unwrap_or_else(|e| e.into_inner()).insert(address, record);
+    }
+
+    pub(super) fn remove(address: usize) {
+        records().lock().unwrap_or_else(|e| e.into_inner()).remove(&address);
+    }
+
+    pub(super) fn report() -> LeakReport {
+        let records = records().lock().unwrap_or_else(|e| e.into_inner());
+        let mut sites: HashMap<String, AllocationSite> = HashMap::new();
+        for record in records.values() {
+            let name = record.site.to_string();
+            let site = sites.entry(name.clone()).or_insert_with(|| AllocationSite {
+                site: name,
+                subsystem: record.subsystem,
+                allocations: 0,
+                bytes: 0,
+                backtrace: record.backtrace.clone(),
+            });
+            site.allocations += 1;
+            site.bytes += record.bytes;
+        }
+        let mut sites: Vec<AllocationSite> = sites.into_values().collect();
+        sites.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.site.cmp(&b.site)));
+        LeakReport {
+            allocations: records.len(),
+            bytes: records.values().map(|record| record.bytes).sum(),
+            sites,
+        }
+    }
+}
+
+/// Record that `ptr`, holding about `bytes`, was handed out by the caller's caller
+#[track_caller]
+pub fn track<T: ?Sized>(ptr: *const T, bytes: usize) {
+    #[cfg(feature = "leak-tracking")]
+    if !ptr.is_null() {
+        registry::insert(ptr as *const () as usize, bytes, std::panic::Location::caller());
+    }
+    #[cfg(not(feature = "leak-tracking"))]
+    let _ = (ptr, bytes);
+}
+
+/// Forget the record of `ptr`, which is being freed
+pub fn untrack<T: ?Sized>(ptr: *const T) {
+    #[cfg(feature = "leak-tracking")]
+    registry::remove(ptr as *const () as usize);
+    #[cfg(not(feature = "leak-tracking"))]
+    let _ = ptr;
+}
+
+/// Allocations recorded and not yet freed, grouped by site
+pub fn outstanding() -> LeakReport {
+    #[cfg(feature = "leak-tracking")]
+    {
+        registry::report()
+    }
+    #[cfg(not(feature = "leak-tracking"))]
+    {
+        LeakReport::default()
+    }
+}
+
+#[cfg(all(test, feature = "leak-tracking"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reports_outstanding_by_site() {
+        let kept: Vec<Box<[u8; 16]>> = (0..3).map(|_| Box::new([0; 16])).collect();
+        for buffer in &kept {
+            track(&**buffer as *const [u8; 16], 16);
+        }
+        let freed = Box::new(0u64);
+        track(&*freed as *const u64, 8);
+        untrack(&*freed as *const u64);
+
+        let report = outstanding();
+        let site = report.sites.iter().find(|site| site.site.contains("leaks.rs") && site.allocations == 3).unwrap();
+        assert_eq!(site.bytes, 48);
+        assert!(!report.sites.iter().any(|site| site.bytes == 8 && site.site.contains("leaks.rs")));
+        kept.iter().for_each(|buffer| untrack(&**buffer as *const [u8; 16]));
+    }
+}
@@ -0,0 +1,406 @@
+//! Buffer Pool - Reusing tensor and activation buffers
+//!
+//! Inference allocates and frees the same buffer sizes over and over. The pool
+//! keeps freed `Vec<f64>` buffers in power-of-two size classes and hands them out
+//! again instead of going back to the allocator: `rent` returns a zeroed buffer
+//! of the requested length, `recycle` gives one back. `PooledBuffer` returns its
+//! buffer on drop, and `Arena` bump-allocates many short-lived slices for one
+//! processing request out of pooled chunks, returning them all at once.
+//!
+//! The crate's hot paths rent from the process-wide pool returned by `global`,
+//! whose hit rate `MemoryManager::get_stats` reports.
+//!
+//! Buffers rented inside a `subsystem::scope` are charged to its subsystem
+//! until recycled inside it; buffers the pool did not hand out are given back
+//! with `donate` instead, so they are not uncharged. Idle buffers are charged
+//! to `Subsystem::TensorPool`.
+
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+
+use super::subsystem::{self, Ledger, Subsystem};
+
+/// Elements in the smallest size class
+const MIN_CLASS_LEN: usize = 64;
+
+/// Number of size classes; larger buffers are not pooled
+const CLASSES: usize = 24;
+
+/// Elements per arena chunk, unless a single request needs more
+const ARENA_CHUNK_LEN: usize = 4096;
+
+/// Counters of a buffer pool
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct PoolStats {
+    /// Rentals served from a pooled buffer
+    pub hits: usize,
+    /// Rentals that needed a fresh allocation
+    pub misses: usize,
+    /// Buffers taken back for reuse
+    pub returned: usize,
+    /// Buffers freed instead, because their class was full or they were too small or large
+    pub discarded: usize,
+    /// Bytes held in pooled buffers
+    pub retained_bytes: usize,
+}
+
+impl PoolStats {
+    /// Fraction of rentals served from the pool
+    pub fn hit_rate(&self) -> f64 {
+        let rentals = self.hits + self.misses;
+        if rentals == 0 { 0.0 } else { self.hits as f64 / rentals as f64 }
+    }
+}
+
+/// Size-classed pool of `f64` buffers
+pub struct BufferPool {
+    classes: Vec<Mutex<Vec<Vec<f64>>>>,
+    max_per_class: usize,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+    returned: AtomicUsize,
+    discarded: AtomicUsize,
+    /// Bytes rented out by subsystem, and held idle under `TensorPool`
+    ledger: Ledger,
+}
+
+impl Default for BufferPool {
+    fn default() -> Self {
+        Self::new(32)
+    }
+}
+
+/// The process-wide pool
+pub fn global() -> &'static BufferPool {
+    static POOL: OnceLock<BufferPool> = OnceLock::new();
+    POOL.get_or_init(BufferPool::default)
+}
+
+impl BufferPool {
+    /// Pool keeping at most `max_per_class` buffers of each size class
+    pub fn new(max_per_class: usize) -> Self {
+        Self {
+            classes: (0..CLASSES).map(|_| Mutex::new(Vec::new())).collect(),
+            max_per_class,
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+            returned: AtomicUsize::new(0),
+            discarded: AtomicUsize::new(0),
+            ledger: Ledger::default(),
+        }
+    }
+
+    /// Smallest class whose buffers hold `len` elements
+    fn class_for_len(len: usize) -> Option<usize> {
+        let class = len.max(MIN_CLASS_LEN).checked_next_power_of_two()?.trailing_zeros() - MIN_CLASS_LEN.trailing_zeros();
+        (class < CLASSES as u32).then_some(class as usize)
+    }
+
+    /// Largest class a buffer of `capacity` elements can serve
+    fn class_for_capacity(capacity: usize) -> Option<usize> {
+        if capacity < MIN_CLASS_LEN {
+            return None;
+        }
+        let class = capacity.ilog2() - MIN_CLASS_LEN.trailing_zeros();
+        (class < CLASSES as u32).then_some(class as usize)
+    }
+
+    fn bytes(capacity: usize) -> usize {
+        capacity * std::mem::size_of::<f64>()
+    }
+
+    /// Zeroed buffer of `len` elements, reused when one of its size class is pooled
+    pub fn rent(&self, len: usize) -> Vec<f64> {
+        let buffer = self.take(len);
+        if let Some(subsystem) = Self::scoped() {
+            self.ledger.added(subsystem, Self::bytes(buffer.capacity()));
+        }
+        buffer
+    }
+
+    /// Subsystem of the enclosing scope; rentals outside any scope are not charged
+    fn scoped() -> Option<Subsystem> {
+        Some(subsystem::current()).filter(|&subsystem| subsystem != Subsystem::Other)
+    }
+
+    fn take(&self, len: usize) -> Vec<f64> {
+        let Some(class) = Self::class_for_len(len) else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            return vec![0.0; len];
+        };
+        let pooled = self.classes[class].lock().unwrap_or_else(|e| e.into_inner()).pop();
+        match pooled {
+            Some(mut buffer) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                self.ledger.removed(Subsystem::TensorPool, Self::bytes(buffer.capacity()));
+                buffer.clear();
+                buffer.resize(len, 0.0);
+                buffer
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                let mut buffer = Vec::with_capacity(MIN_CLASS_LEN << class);
+                buffer.resize(len, 0.0);
+                buffer
+            }
+        }
+    }
+
+    /// Zeroed buffer of `len` elements that returns to the pool when dropped
+    pub fn rent_scoped(&self, len: usize) -> PooledBuffer<'_> {
+        PooledBuffer { pool: self, buffer: self.rent(len) }
+    }
+
+    /// Give a rented buffer back for reuse
+    pub fn recycle(&self, buffer: Vec<f64>) {
+        if let Some(subsystem) = Self::scoped() {
+            self.ledger.removed(subsystem, Self::bytes(buffer.capacity()));
+        }
+        self.donate(buffer);
+    }
+
+    /// Give the pool a buffer it did not rent out, such as an array's storage
+    pub fn donate(&self, buffer: Vec<f64>) {
+        let Some(class) = Self::class_for_capacity(buffer.capacity()) else {
+            self.discarded.fetch_add(1, Ordering::Relaxed);
+            return;
+        };
+        let mut pooled = self.classes[class].lock().unwrap_or_else(|e| e.into_inner());
+        if pooled.len() >= self.max_per_class {
+            self.discarded.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        self.returned.fetch_add(1, Ordering::Relaxed);
+        self.ledger.added(Subsystem::TensorPool, Self::bytes(buffer.capacity()));
+        pooled.push(buffer);
+    }
+
+    /// Free pooled buffers beyond `keep` per class, returning the bytes released
+    pub fn shrink(&self, keep: usize) -> usize {
+        let mut released = 0;
+        for class in &self.classes {
+            let mut pooled = class.lock().unwrap_or_else(|e| e.into_inner());
+            while pooled.len() > keep {
+                if let Some(buffer) = pooled.pop() {
+                    released += Self::bytes(buffer.capacity());
+                }
+            }
+        }
+        self.ledger.removed(Subsystem::TensorPool, released);
+        released
+    }
+
+    pub fn stats(&self) -> PoolStats {
+        PoolStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            returned: self.returned.load(Ordering::Relaxed),
+            discarded: self.discarded.load(Ordering::Relaxed),
+            retained_bytes: self.ledger.usage(Subsystem::TensorPool).bytes,
+        }
+    }
+
+    /// Bytes rented out and held idle, by subsystem
+    pub(crate) fn ledger(&self) -> &Ledger {
+        &self.ledger
+    }
+
+    /// Bump arena for one request's scratch slices, charged to the subsystem in scope
+    pub fn arena(&self) -> Arena<'_> {
+        Arena { pool: self, subsystem: Self::scoped(), state: Mutex::default() }
+    }
+}
+
+/// Pooled buffer returned on drop
+pub struct PooledBuffer<'a> {
+    pool: &'a BufferPool,
+    buffer: Vec<f64>,
+}
+
+impl PooledBuffer<'_> {
+    /// Keep the buffer instead of returning it
+    pub fn into_vec(mut self) -> Vec<f64> {
+        std::mem::take(&mut self.buffer)
+    }
+}
+
+impl Deref for PooledBuffer<'_> {
+    type Target = [f64];
+
+    fn deref(&self) -> &[f64] {
+        &self.buffer
+    }
+}
+
+impl DerefMut for PooledBuffer<'_> {
+    fn deref_mut(&mut self) -> &mut [f64] {
+        &mut self.buffer
+    }
+}
+
+impl Drop for PooledBuffer<'_> {
+    fn drop(&mut self) {
+        if self.buffer.capacity() > 0 {
+            self.pool.recycle(std::mem::take(&mut self.buffer));
+        }
+    }
+}
+
+/// Chunk storage owned by an arena, held by pointer so that handing out a slice
+/// never reborrows the chunk
+struct Chunk {
+    ptr: *mut f64,
+    len: usize,
+    capacity: usize,
+}
+
+// SAFETY: a chunk uniquely owns its storage, like the `Vec` it was taken from
+unsafe impl Send for Chunk {}
+
+impl Chunk {
+    fn new(buffer: Vec<f64>) -> Self {
+        let mut buffer = std::mem::ManuallyDrop::new(buffer);
+        Self { ptr: buffer.as_mut_ptr(), len: buffer.len(), capacity: buffer.capacity() }
+    }
+
+    fn into_vec(self) -> Vec<f64> {
+        // SAFETY: the parts come from the `Vec` passed to `new`, which was not dropped
+        unsafe { Vec::from_raw_parts(self.ptr, self.len, self.capacity) }
+    }
+}
+
+/// Chunks in allocation order; only the last has free space
+#[derive(Default)]
+struct ArenaState {
+    chunks: Vec<Chunk>,
+    /// Elements handed out from the last chunk
+    used: usize,
+}
+
+/// Bump allocator over pooled chunks, returning them to the pool on drop
+///
+/// Chunks are charged to the subsystem in scope when the arena was created. The
+/// arena can be shared by the threads serving one request.
+pub struct Arena<'a> {
+    pool: &'a BufferPool,
+    subsystem: Option<Subsystem>,
+    state: Mutex<ArenaState>,
+}
+
+impl Arena<'_> {
+    /// Zeroed scratch slice of `len` elements, valid until the arena is reset or dropped
+    // Each range of a chunk is handed out once, so the slices never overlap
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc(&self, len: usize) -> &mut [f64] {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let fits = state.chunks.last().is_some_and(|chunk| chunk.len - state.used >= len);
+        if !fits {
+            let buffer = self.pool.take(len.max(ARENA_CHUNK_LEN));
+            if let Some(subsystem) = self.subsystem {
+                self.pool.ledger.added(subsystem, BufferPool::bytes(buffer.capacity()));
+            }
+            state.chunks.push(Chunk::new(buffer));
+            state.used = 0;
+        }
+        let start = state.used;
+        state.used += len;
+        let ptr = state.chunks.last().expect("a chunk was just ensured").ptr;
+        // SAFETY: `start..start + len` lies within the chunk and no other slice covers
+        // it; the storage stays put until `reset`, which borrows the arena mutably
+        let slice = unsafe { std::slice::from_raw_parts_mut(ptr.add(start), len) };
+        slice.fill(0.0);
+        slice
+    }
+
+    /// Return every chunk to the pool, invalidating all slices
+    pub fn reset(&mut self) {
+        let state = self.state.get_mut().unwrap_or_else(|e| e.into_inner());
+        for chunk in state.chunks.drain(..) {
+            let buffer = chunk.into_vec();
+            if let Some(subsystem) = self.subsystem {
+                self.pool.ledger.removed(subsystem, BufferPool::bytes(buffer.capacity()));
+            }
+            self.pool.donate(buffer);
+        }
+        state.used = 0;
+    }
+
+    /// Chunks currently held
+    pub fn chunks(&self) -> usize {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).chunks.len()
+    }
+}
+
+impl Drop for Arena<'_> {
+    fn drop(&mut self) {
+        self.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rented_buffers_are_reused() {
+        let pool = BufferPool::new(2);
+        let buffer = pool.rent(100);
+        assert_eq!(buffer.len(), 100);
+        assert_eq!(buffer.capacity(), 128);
+        pool.recycle(buffer);
+
+        let mut reused = pool.rent_scoped(120);
+        assert!(reused.iter().all(|&x| x == 0.0));
+        reused[0] = 1.0;
+        drop(reused);
+        assert_eq!(pool.rent(90)[0], 0.0);
+
+        let stats = pool.stats();
+        assert_eq!((stats.hits, stats.misses, stats.returned), (2, 1, 2));
+        assert!((stats.hit_rate() - 2.0 / 3.0).abs() < 1e-12);
+        pool.recycle(vec![0.0; 10]);
+        assert_eq!(pool.stats().discarded, 1);
+    }
+
+    #[test]
+    fn test_scoped_rentals_are_charged_until_recycled() {
+        let pool = BufferPool::new(2);
+        let unscoped = pool.rent(64);
+        let _scope = subsystem::scope(Subsystem::Activations);
+        let rented = pool.rent(100);
+        assert_eq!(pool.ledger().usage(Subsystem::Activations).bytes, 128 * 8);
+        pool.donate(vec![0.0; 256]);
+        pool.recycle(rented);
+        assert_eq!(pool.ledger().usage(Subsystem::Activations).bytes, 0);
+        assert_eq!(pool.stats().retained_bytes, (256 + 128) * 8);
+        drop(unscoped);
+    }
+
+    #[test]
+    fn test_arena_hands_out_disjoint_slices() {
+        let pool = BufferPool::new(4);
+        let mut arena = pool.arena();
+        let a = arena.alloc(10);
+        let b = arena.alloc(20);
+        a.fill(1.0);
+        b.fill(2.0);
+        assert_eq!(a.iter().sum::<f64>(), 10.0);
+        let large = arena.alloc(ARENA_CHUNK_LEN + 1);
+        assert_eq!(large.len(), ARENA_CHUNK_LEN + 1);
+        assert_eq!(arena.chunks(), 2);
+        // Threads of one request share the arena
+        std::thread::scope(|threads| {
+            for value in [3.0, 4.0] {
+                let arena = &arena;
+                threads.spawn(move || arena.alloc(100).fill(value));
+            }
+        });
+        assert_eq!((a.iter().sum::<f64>(), arena.chunks()), (10.0, 3));
+        arena.reset();
+        assert_eq!(pool.stats().returned, 3);
+        assert_eq!(arena.chunks(), 0);
+    }
+}
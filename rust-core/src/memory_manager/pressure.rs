@@ -0,0 +1,215 @@
+//! Memory Pressure - Watching the process and the machine
+//!
+//! `SystemMemory::probe` reads the process's resident set and the system's
+//! available memory from `/proc` (Linux only; elsewhere the figures are
+//! unknown). A `PressureLevel` is derived from whichever is closer to its limit:
+//! the manager's usage against its budget, or the system's memory in use
+//! against its total. Subscribers registered with
+//! `MemoryManager::subscribe_pressure` are called whenever
+//! `MemoryManager::check_pressure` finds the level changed, so the host can shed
+//! load or optimize before the OOM killer steps in;
+//! `AGISystem::start_pressure_monitor` does both on a schedule.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, RwLock};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// Memory figures of the process and the system, `None` where unknown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct SystemMemory {
+    /// Resident set size of this process
+    pub rss: Option<usize>,
+    /// Memory the system can give out without swapping
+    pub available: Option<usize>,
+    pub total: Option<usize>,
+}
+
+impl SystemMemory {
+    /// Read the current figures
+    pub fn probe() -> Self {
+        #[cfg(target_os = "linux")]
+        {
+            let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+            let rss = std::fs::read_to_string("/proc/self/statm")
+                .ok()
+                .and_then(|statm| parse_statm(&statm, usize::try_from(page_size).ok()?));
+            let (available, total) = std::fs::read_to_string("/proc/meminfo")
+                .map_or((None, None), |meminfo| parse_meminfo(&meminfo));
+            Self { rss, available, total }
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            Self::default()
+        }
+    }
+
+    /// Fraction of system memory in use, if known
+    pub fn used_fraction(&self) -> Option<f64> {
+        match (self.available, self.total) {
+            (Some(available), Some(total)) if total > 0 => Some(1.0 - available.min(total) as f64 / total as f64),
+            _ => None,
+        }
+    }
+}
+
+/// Resident bytes from the contents of `/proc/self/statm`
+fn parse_statm(statm: &str, page_size: usize) -> Option<usize> {
+    statm.split_whitespace().nth(1)?.parse::<usize>().ok().map(|pages| pages * page_size)
+}
+
+/// `MemAvailable` and `MemTotal` in bytes from the contents of `/proc/meminfo`
+fn parse_meminfo(meminfo: &str) -> (Option<usize>, Option<usize>) {
+    let field = |name: &str| {
+        meminfo.lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(':'))
+            .and_then(|value| value.trim().trim_end_matches("kB").trim().parse::<usize>().ok())
+            .map(|kib| kib * 1024)
+    };
+    (field("MemAvailable"), field("MemTotal"))
+}
+
+/// How close memory is to running out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize)]
+pub enum PressureLevel {
+    #[default]
+    Normal,
+    Elevated,
+    Critical,
+}
+
+/// Fractions of a limit in use at which pressure rises
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PressureThresholds {
+    pub elevated: f64,
+    pub critical: f64,
+}
+
+impl Default for PressureThresholds {
+    fn default() -> Self {
+        Self { elevated: 0.75, critical: 0.9 }
+    }
+}
+
+impl PressureThresholds {
+    pub fn level(&self, fraction: f64) -> PressureLevel {
+        if fraction >= self.critical {
+            PressureLevel::Critical
+        } else if fraction >= self.elevated {
+            PressureLevel::Elevated
+        } else {
+            PressureLevel::Normal
+        }
+    }
+}
+
+/// One assessment of memory pressure
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PressureReading {
+    pub level: PressureLevel,
+    /// Fraction of the tighter limit in use
+    pub fraction: f64,
+    /// Bytes to release to fall back below the elevated threshold
+    pub excess: usize,
+    /// Bytes used by the manager
+    pub used: usize,
+    pub budget: Option<usize>,
+    pub system: SystemMemory,
+}
+
+impl PressureReading {
+    /// Assess `used` bytes against `budget` and `system` memory
+    pub fn assess(thresholds: &PressureThresholds, used: usize, budget: Option<usize>, system: SystemMemory) -> Self {
+        // (fraction in use, bytes above the elevated threshold) of each known limit
+        let budget_load = budget.filter(|&budget| budget > 0).map(|budget| {
+            (used as f64 / budget as f64, used.saturating_sub((budget as f64 * thresholds.elevated) as usize))
+        });
+        let system_load = system.used_fraction().zip(system.total).map(|(fraction, total)| {
+            let in_use = (fraction * total as f64) as usize;
+            (fraction, in_use.saturating_sub((total as f64 * thresholds.elevated) as usize))
+        });
+        let (fraction, excess) = [budget_load, system_load]
+            .into_iter()
+            .flatten()
+            .fold((0.0, 0), |(fraction, excess), (f, e)| (f64::max(fraction, f), excess.max(e)));
+        Self { level: thresholds.level(fraction), fraction, excess, used, budget, system }
+    }
+}
+
+/// Called with the reading whenever the pressure level changes
+pub type PressureCallback = Box<dyn Fn(&PressureReading) + Send + Sync>;
+
+/// Handle of a pressure subscription
+pub type SubscriptionId = u64;
+
+/// Thresholds, last level and subscribers of one manager
+#[derive(Default)]
+pub(crate) struct Pressure {
+    thresholds: Mutex<PressureThresholds>,
+    level: Mutex<PressureLevel>,
+    next_id: AtomicU64,
+    subscribers: RwLock<Vec<(SubscriptionId, PressureCallback)>>,
+}
+
+impl Pressure {
+    pub(crate) fn thresholds(&self) -> PressureThresholds {
+        *self.thresholds.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub(crate) fn set_thresholds(&self, thresholds: PressureThresholds) {
+        *self.thresholds.lock().unwrap_or_else(|e| e.into_inner()) = thresholds;
+    }
+
+    /// Level of the last `update`
+    pub(crate) fn level(&self) -> PressureLevel {
+        *self.level.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub(crate) fn subscribe(&self, callback: PressureCallback) -> SubscriptionId {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.subscribers.write().unwrap_or_else(|e| e.into_inner()).push((id, callback));
+        id
+    }
+
+    pub(crate) fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        let mut subscribers = self.subscribers.write().unwrap_or_else(|e| e.into_inner());
+        let before = subscribers.len();
+        subscribers.retain(|(existing, _)| *existing != id);
+        subscribers.len() < before
+    }
+
+    /// Record `reading`, notifying the subscribers if its level differs from the last
+    pub(crate) fn update(&self, reading: &PressureReading) {
+        let previous = std::mem::replace(&mut *self.level.lock().unwrap_or_else(|e| e.into_inner()), reading.level);
+        if previous == reading.level {
+            return;
+        }
+        if reading.level > previous {
+            warn!("Memory pressure rose to {:?} at {:.0}% of the limit", reading.level, reading.fraction * 100.0);
+        }
+        for (_, callback) in self.subscribers.read().unwrap_or_else(|e| e.into_inner()).iter() {
+            callback(reading);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_proc_and_assesses_tighter_limit() {
+        let meminfo = "MemTotal:       16000000 kB\nMemFree:         1000000 kB\nMemAvailable:    2000000 kB\n";
+        assert_eq!(parse_meminfo(meminfo), (Some(2_048_000_000), Some(16_384_000_000)));
+        assert_eq!(parse_statm("5000 1200 300 10 0 900 0\n", 4096), Some(1200 * 4096));
+
+        let thresholds = PressureThresholds::default();
+        let system = SystemMemory { rss: None, available: Some(50), total: Some(100) };
+        let reading = PressureReading::assess(&thresholds, 800, Some(1000), system);
+        assert_eq!((reading.level, reading.excess), (PressureLevel::Elevated, 50));
+        let reading = PressureReading::assess(&thresholds, 950, Some(1000), SystemMemory::default());
+        assert_eq!((reading.level, reading.excess), (PressureLevel::Critical, 200));
+        assert_eq!(PressureReading::assess(&thresholds, 950, None, SystemMemory::default()).level, PressureLevel::Normal);
+    }
+}
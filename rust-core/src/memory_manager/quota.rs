@@ -0,0 +1,126 @@
+//! Memory Quota - Budget enforcement and backpressure
+//!
+//! A `MemoryManager` may be given a byte budget. Before an allocation that would
+//! take usage over it, the manager asks its registered evictors, in registration
+//! order, to release memory: shrinking an inference cache, thinning history,
+//! emptying the buffer pool. Usage is measured again after each one, and if the
+//! allocation still does not fit it fails with a typed `MemoryPressure` error
+//! instead of growing without bound.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// Releases roughly the given number of bytes, returning an estimate of what it freed
+pub type Evictor = Box<dyn Fn(usize) -> usize + Send + Sync>;
+
+/// An allocation that would exceed the memory budget even after eviction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryPressure {
+    /// Bytes the allocation asked for
+    pub requested: usize,
+    /// Bytes in use after the evictors ran
+    pub used: usize,
+    pub budget: usize,
+    /// Bytes the evictors reported releasing
+    pub released: usize,
+}
+
+impl std::fmt::Display for MemoryPressure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Memory budget of {} bytes exceeded: {} bytes requested with {} in use after evictors released {}",
+            self.budget, self.requested, self.used, self.released
+        )
+    }
+}
+
+impl std::error::Error for MemoryPressure {}
+
+/// Budget and evictors of one manager
+pub(crate) struct Quota {
+    /// `usize::MAX` when unlimited
+    budget: AtomicUsize,
+    evictors: RwLock<Vec<(String, Evictor)>>,
+    evictions: AtomicUsize,
+}
+
+impl Default for Quota {
+    fn default() -> Self {
+        Self { budget: AtomicUsize::new(usize::MAX), evictors: RwLock::new(Vec::new()), evictions: AtomicUsize::new(0) }
+    }
+}
+
+impl Quota {
+    pub(crate) fn budget(&self) -> Option<usize> {
+        Some(self.budget.load(Ordering::Relaxed)).filter(|&budget| budget != usize::MAX)
+    }
+
+    pub(crate) fn set_budget(&self, budget: Option<usize>) {
+        self.budget.store(budget.unwrap_or(usize::MAX), Ordering::Relaxed);
+    }
+
+    /// Register `evictor` under `name`, replacing one of the same name
+    pub(crate) fn register(&self, name: &str, evictor: Evictor) {
+        let mut evictors = self.evictors.write().unwrap_or_else(|e| e.into_inner());
+        match evictors.iter_mut().find(|(existing, _)| existing == name) {
+            Some(entry) => entry.1 = evictor,
+            None => evictors.push((name.to_string(), evictor)),
+        }
+    }
+
+    pub(crate) fn unregister(&self, name: &str) -> bool {
+        let mut evictors = self.evictors.write().unwrap_or_else(|e| e.into_inner());
+        let before = evictors.len();
+        evictors.retain(|(existing, _)| existing != name);
+        evictors.len() < before
+    }
+
+    /// Times an evictor was asked to release memory
+    pub(crate) fn evictions(&self) -> usize {
+        self.evictions.load(Ordering::Relaxed)
+    }
+
+    /// Ask the evictors in turn to release `bytes` regardless of the budget, returning what they released
+    pub(crate) fn shed(&self, bytes: usize) -> usize {
+        let mut released = 0;
+        let evictors = self.evictors.read().unwrap_or_else(|e| e.into_inner());
+        for (name, evictor) in evictors.iter() {
+            if released >= bytes {
+                break;
+            }
+            let freed = evictor(bytes - released);
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+            debug!("Evictor '{}' shed {} of {} bytes", name, freed, bytes - released);
+            released += freed;
+        }
+        released
+    }
+
+    /// Make room for `requested` bytes, measuring usage with `used`
+    pub(crate) fn reserve(&self, requested: usize, used: impl Fn() -> usize) -> Result<(), MemoryPressure> {
+        let budget = self.budget.load(Ordering::Relaxed);
+        let fits = |used: usize| used.checked_add(requested).is_some_and(|total| total <= budget);
+        let mut current = used();
+        if fits(current) {
+            return Ok(());
+        }
+        let mut released = 0;
+        let evictors = self.evictors.read().unwrap_or_else(|e| e.into_inner());
+        for (name, evictor) in evictors.iter() {
+            let shortfall = current.saturating_add(requested).saturating_sub(budget);
+            let freed = evictor(shortfall);
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+            debug!("Evictor '{}' released {} of {} bytes needed", name, freed, shortfall);
+            released += freed;
+            current = used();
+            if fits(current) {
+                return Ok(());
+            }
+        }
+        Err(MemoryPressure { requested, used: current, budget, released })
+    }
+}
@@ -0,0 +1,445 @@
+//! Semantic Memory - Embedding-indexed knowledge
+//!
+//! Stores (embedding, payload) pairs and finds the entries nearest a query by
+//! cosine similarity through a hierarchical navigable small world (HNSW) graph:
+//! each entry is linked to its closest neighbours on layer 0 and, with
+//! geometrically falling probability, on sparser upper layers, so a search
+//! descends greedily from the top and only explores a small part of the bottom
+//! layer. Deleted entries stay in the graph as waypoints but are never returned;
+//! once they make up half of it, the graph is rebuilt from the live entries.
+//!
+//! The store is owned by `MemoryManager`, which counts its bytes in the usage
+//! statistics and budget. `AGISystem::process_input` retrieves the entries
+//! nearest each input as context.
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::sync::atomic::{self, AtomicU64};
+use std::sync::Arc;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use super::block::Usage;
+use super::eviction::{AccessLog, Evictable, EvictionEntry, NEUTRAL_IMPORTANCE};
+use super::subsystem::Subsystem;
+
+/// Handle of a stored entry
+pub type SemanticId = u64;
+
+/// HNSW construction and search parameters
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HnswConfig {
+    /// Links per entry on upper layers; layer 0 allows twice as many
+    pub m: usize,
+    /// Candidates considered when linking a new entry
+    pub ef_construction: usize,
+    /// Candidates considered per search, at least the number of results
+    pub ef_search: usize,
+    /// Seed of the layer assignment
+    pub seed: u64,
+}
+
+impl Default for HnswConfig {
+    fn default() -> Self {
+        Self { m: 16, ef_construction: 100, ef_search: 50, seed: 0 }
+    }
+}
+
+/// A search result
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SemanticHit {
+    pub id: SemanticId,
+    /// Cosine similarity to the query
+    pub similarity: f64,
+    pub payload: String,
+}
+
+/// Size and shape of a semantic index
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct SemanticStats {
+    pub entries: usize,
+    /// Deleted entries still in the graph
+    pub deleted: usize,
+    pub dimension: Option<usize>,
+    /// Highest layer in use
+    pub top_layer: usize,
+    /// Mean number of layer-0 links per entry
+    pub mean_degree: f64,
+    pub bytes: usize,
+    pub searches: u64,
+    pub rebuilds: u64,
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    id: SemanticId,
+    /// Unit-length embedding
+    vector: Vec<f64>,
+    payload: String,
+    /// Links per layer, from 0 up to the node's level, as node indices
+    links: Vec<Vec<usize>>,
+    deleted: bool,
+}
+
+impl Node {
+    fn bytes(&self, m: usize) -> usize {
+        std::mem::size_of::<Self>()
+            + self.vector.len() * std::mem::size_of::<f64>()
+            + self.payload.len()
+            + (2 * m + m * (self.links.len() - 1)) * std::mem::size_of::<usize>()
+    }
+}
+
+/// Candidate ordered by distance
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Scored(f64, usize);
+
+impl Eq for Scored {}
+
+impl PartialOrd for Scored {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scored {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0).then(self.1.cmp(&other.1))
+    }
+}
+
+fn normalized(vector: &[f64]) -> Vec<f64> {
+    let norm = vector.iter().map(|x| x * x).sum::<f64>().sqrt();
+    if norm > 0.0 { vector.iter().map(|x| x / norm).collect() } else { vector.to_vec() }
+}
+
+/// Cosine distance of unit vectors
+fn distance(a: &[f64], b: &[f64]) -> f64 {
+    1.0 - a.iter().zip(b).map(|(x, y)| x * y).sum::<f64>()
+}
+
+/// Approximate nearest-neighbour store of embeddings and payloads
+pub struct SemanticMemory {
+    config: HnswConfig,
+    dimension: Option<usize>,
+    nodes: Vec<Node>,
+    positions: HashMap<SemanticId, usize>,
+    entry: Option<usize>,
+    next_id: SemanticId,
+    deleted: usize,
+    bytes: usize,
+    /// Counted through shared references, so searches need only a read lock
+    searches: AtomicU64,
+    rebuilds: u64,
+    rng: StdRng,
+    access: AccessLog,
+    usage: Arc<Usage>,
+}
+
+impl SemanticMemory {
+    pub(crate) fn new(config: HnswConfig, usage: Arc<Usage>) -> Self {
+        Self {
+            config: HnswConfig { m: config.m.max(2), ..config },
+            dimension: None,
+            nodes: Vec::new(),
+            positions: HashMap::new(),
+            entry: None,
+            next_id: 0,
+            deleted: 0,
+            bytes: 0,
+            searches: AtomicU64::new(0),
+            rebuilds: 0,
+            rng: StdRng::seed_from_u64(config.seed),
+            access: AccessLog::default(),
+            usage,
+        }
+    }
+
+    /// Empty store continuing the ids of a snapshot
+    pub(crate) fn resume(config: HnswConfig, next_id: SemanticId, usage: Arc<Usage>) -> Self {
+        let mut memory = Self::new(config, usage);
+        memory.next_id = next_id;
+        memory
+    }
+
+    /// Id the next inserted entry will get
+    pub(crate) fn next_id(&self) -> SemanticId {
+        self.next_id
+    }
+
+    /// Live entries as (id, normalized embedding, payload), in insertion order
+    pub(crate) fn entries(&self) -> impl Iterator<Item = (SemanticId, &[f64], &str)> {
+        self.nodes.iter().filter(|node| !node.deleted).map(|node| (node.id, node.vector.as_slice(), node.payload.as_str()))
+    }
+
+    /// Link `payload` under `embedding` from a snapshot, keeping its id
+    pub(crate) fn restore(&mut self, id: SemanticId, embedding: &[f64], payload: String) -> Result<(), String> {
+        self.check(embedding)?;
+        if id >= self.next_id || self.positions.contains_key(&id) {
+            return Err(format!("Semantic entry {} is duplicated or out of range", id));
+        }
+        self.dimension = Some(embedding.len());
+        self.link(Node { id, vector: normalized(embedding), payload, links: Vec::new(), deleted: false });
+        self.access.insert(id);
+        Ok(())
+    }
+
+    pub fn config(&self) -> &HnswConfig {
+        &self.config
+    }
+
+    /// Live entries
+    pub fn len(&self) -> usize {
+        self.nodes.len() - self.deleted
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Dimension of the stored embeddings, fixed by the first insert
+    pub fn dimension(&self) -> Option<usize> {
+        self.dimension
+    }
+
+    /// Bytes an entry of this embedding and payload will take
+    pub fn entry_bytes(&self, dimension: usize, payload: &str) -> usize {
+        std::mem::size_of::<Node>() + dimension * std::mem::size_of::<f64>() + payload.len() + 2 * self.config.m * std::mem::size_of::<usize>()
+    }
+
+    /// Store `payload` under `embedding`
+    pub fn insert(&mut self, embedding: &[f64], payload: impl Into<String>) -> Result<SemanticId, String> {
+        self.check(embedding)?;
+        self.dimension = Some(embedding.len());
+        let id = self.next_id;
+        self.next_id += 1;
+        self.link(Node { id, vector: normalized(embedding), payload: payload.into(), links: Vec::new(), deleted: false });
+        self.access.insert(id);
+        Ok(id)
+    }
+
+    fn check(&self, embedding: &[f64]) -> Result<(), String> {
+        if let Some(dimension) = self.dimension.filter(|&dimension| dimension != embedding.len()) {
+            return Err(format!("Expected a {}-dimensional embedding, got {}", dimension, embedding.len()));
+        }
+        if embedding.is_empty() || embedding.iter().any(|x| !x.is_finite()) {
+            return Err("Embedding must be non-empty and finite".to_string());
+        }
+        Ok(())
+    }
+
+    /// Add `node` to the graph, assigning its level
+    fn link(&mut self, mut node: Node) {
+        let level = (-self.rng.gen::<f64>().max(f64::MIN_POSITIVE).ln() / (self.config.m as f64).ln()).floor() as usize;
+        node.links = vec![Vec::new(); level + 1];
+        let index = self.nodes.len();
+        let query = node.vector.clone();
+        let bytes = node.bytes(self.config.m);
+        self.bytes += bytes;
+        self.usage.added(Subsystem::Semantic, bytes);
+        self.positions.insert(node.id, index);
+        self.nodes.push(node);
+
+        let Some(entry) = self.entry else {
+            self.entry = Some(index);
+            return;
+        };
+        let top = self.nodes[entry].links.len() - 1;
+        let mut nearest = vec![Scored(distance(&query, &self.nodes[entry].vector), entry)];
+        for layer in (level + 1..=top).rev() {
+            nearest = self.search_layer(&query, nearest, 1, layer);
+        }
+        for layer in (0..=level.min(top)).rev() {
+            nearest = self.search_layer(&query, nearest, self.config.ef_construction, layer);
+            let limit = self.max_links(layer);
+            let neighbours: Vec<usize> = nearest.iter().take(limit).map(|scored| scored.1).collect();
+            for &neighbour in &neighbours {
+                self.nodes[neighbour].links[layer].push(index);
+                if self.nodes[neighbour].links[layer].len() > limit {
+                    self.prune(neighbour, layer, limit);
+                }
+            }
+            self.nodes[index].links[layer] = neighbours;
+        }
+        if level > top {
+            self.entry = Some(index);
+        }
+    }
+
+    fn max_links(&self, layer: usize) -> usize {
+        if layer == 0 { 2 * self.config.m } else { self.config.m }
+    }
+
+    /// Keep only the `limit` links of `node` on `layer` closest to it
+    fn prune(&mut self, node: usize, layer: usize, limit: usize) {
+        let vector = &self.nodes[node].vector;
+        let mut links: Vec<Scored> = self.nodes[node].links[layer]
+            .iter()
+            .map(|&other| Scored(distance(vector, &self.nodes[other].vector), other))
+            .collect();
+        links.sort();
+        self.nodes[node].links[layer] = links.into_iter().take(limit).map(|scored| scored.1).collect();
+    }
+
+    /// The `ef` nodes nearest `query` on `layer` reachable from `entry_points`, nearest first
+    fn search_layer(&self, query: &[f64], entry_points: Vec<Scored>, ef: usize, layer: usize) -> Vec<Scored> {
+        let mut visited: HashSet<usize> = entry_points.iter().map(|scored| scored.1).collect();
+        let mut candidates: BinaryHeap<Reverse<Scored>> = entry_points.iter().copied().map(Reverse).collect();
+        let mut found: BinaryHeap<Scored> = entry_points.into_iter().collect();
+        while let Some(Reverse(closest)) = candidates.pop() {
+            if found.len() >= ef && found.peek().is_some_and(|furthest| closest.0 > furthest.0) {
+                break;
+            }
+            for &neighbour in &self.nodes[closest.1].links[layer] {
+                if !visited.insert(neighbour) {
+                    continue;
+                }
+                let scored = Scored(distance(query, &self.nodes[neighbour].vector), neighbour);
+                if found.len() < ef || found.peek().is_some_and(|furthest| scored.0 < furthest.0) {
+                    candidates.push(Reverse(scored));
+                    found.push(scored);
+                    if found.len() > ef {
+                        found.pop();
+                    }
+                }
+            }
+        }
+        found.into_sorted_vec()
+    }
+
+    /// Up to `count` live entries nearest `query`, most similar first
+    pub fn search(&self, query: &[f64], count: usize) -> Result<Vec<SemanticHit>, String> {
+        let Some(entry) = self.entry else { return Ok(Vec::new()) };
+        self.check(query)?;
+        self.searches.fetch_add(1, atomic::Ordering::Relaxed);
+        let query = normalized(query);
+        let mut nearest = vec![Scored(distance(&query, &self.nodes[entry].vector), entry)];
+        for layer in (1..self.nodes[entry].links.len()).rev() {
+            nearest = self.search_layer(&query, nearest, 1, layer);
+        }
+        // Deleted entries take up candidate slots, so widen the search by as many
+        let ef = self.config.ef_search.max(count) + self.deleted;
+        Ok(self.search_layer(&query, nearest, ef, 0)
+            .into_iter()
+            .filter(|scored| !self.nodes[scored.1].deleted)
+            .take(count)
+            .map(|Scored(distance, index)| {
+                let node = &self.nodes[index];
+                self.access.touch(node.id);
+                SemanticHit { id: node.id, similarity: 1.0 - distance, payload: node.payload.clone() }
+            })
+            .collect())
+    }
+
+    /// Payload of a live entry
+    pub fn get(&self, id: SemanticId) -> Option<&str> {
+        let node = &self.nodes[*self.positions.get(&id)?];
+        if node.deleted {
+            return None;
+        }
+        self.access.touch(id);
+        Some(node.payload.as_str())
+    }
+
+    /// Remove an entry from results, rebuilding the graph once half of it is deleted
+    pub fn delete(&mut self, id: SemanticId) -> bool {
+        let Some(&index) = self.positions.get(&id) else { return false };
+        if std::mem::replace(&mut self.nodes[index].deleted, true) {
+            return false;
+        }
+        self.deleted += 1;
+        self.access.remove(id);
+        if self.deleted * 2 >= self.nodes.len() {
+            self.rebuild();
+        }
+        true
+    }
+
+    /// Rebuild the graph from the live entries, releasing deleted ones
+    pub fn rebuild(&mut self) {
+        let nodes = std::mem::take(&mut self.nodes);
+        self.usage.removed(Subsystem::Semantic, self.bytes);
+        self.bytes = 0;
+        self.positions.clear();
+        self.entry = None;
+        self.deleted = 0;
+        self.rebuilds += 1;
+        for node in nodes.into_iter().filter(|node| !node.deleted) {
+            self.link(node);
+        }
+        if self.nodes.is_empty() {
+            self.dimension = None;
+        }
+    }
+
+    pub fn stats(&self) -> SemanticStats {
+        let degree: usize = self.nodes.iter().map(|node| node.links[0].len()).sum();
+        SemanticStats {
+            entries: self.len(),
+            deleted: self.deleted,
+            dimension: self.dimension,
+            top_layer: self.entry.map_or(0, |entry| self.nodes[entry].links.len() - 1),
+            mean_degree: if self.nodes.is_empty() { 0.0 } else { degree as f64 / self.nodes.len() as f64 },
+            bytes: self.bytes,
+            searches: self.searches.load(atomic::Ordering::Relaxed),
+            rebuilds: self.rebuilds,
+        }
+    }
+}
+
+impl Evictable for SemanticMemory {
+    fn eviction_entries(&self) -> Vec<EvictionEntry> {
+        self.nodes.iter()
+            .filter(|node| !node.deleted)
+            .map(|node| self.access.entry(node.id, node.bytes(self.config.m), NEUTRAL_IMPORTANCE))
+            .collect()
+    }
+
+    fn evict(&mut self, key: u64) -> usize {
+        let bytes = self.positions.get(&key).map_or(0, |&index| self.nodes[index].bytes(self.config.m));
+        if self.delete(key) { bytes } else { 0 }
+    }
+}
+
+impl Drop for SemanticMemory {
+    fn drop(&mut self) {
+        self.usage.removed(Subsystem::Semantic, self.bytes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_finds_nearest_and_skips_deleted() {
+        let usage = Arc::new(Usage::default());
+        let mut memory = SemanticMemory::new(HnswConfig { m: 8, ..HnswConfig::default() }, usage.clone());
+        let mut rng = StdRng::seed_from_u64(7);
+        let vectors: Vec<Vec<f64>> = (0..300).map(|_| (0..16).map(|_| rng.gen_range(-1.0..1.0)).collect()).collect();
+        for (index, vector) in vectors.iter().enumerate() {
+            memory.insert(vector, format!("entry {}", index)).unwrap();
+        }
+        assert!(memory.insert(&[1.0], "wrong size").is_err());
+
+        // Every stored vector is its own nearest neighbour
+        let found = vectors.iter().enumerate()
+            .filter(|(index, vector)| memory.search(vector, 1).unwrap()[0].id == *index as u64)
+            .count();
+        assert!(found >= 295, "recall {} of 300", found);
+        assert_eq!(usage.counters().current, memory.stats().bytes);
+
+        assert!(memory.delete(42));
+        assert!(!memory.delete(42));
+        assert!(memory.search(&vectors[42], 5).unwrap().iter().all(|hit| hit.id != 42));
+        assert_eq!(memory.get(43), Some("entry 43"));
+        for id in 0..150 {
+            memory.delete(id);
+        }
+        let stats = memory.stats();
+        assert_eq!((stats.entries, stats.deleted, stats.rebuilds), (150, 0, 1));
+        assert_eq!(memory.search(&vectors[200], 1).unwrap()[0].id, 200);
+    }
+}
@@ -0,0 +1,71 @@
+//! Sharding - Spreading keyed state over independently locked parts
+//!
+//! Keys are hashed onto `SHARDS` parts, each behind its own lock, so concurrent
+//! readers and writers of different keys rarely wait on each other. The
+//! blackboard and the access logs of every evictable store are sharded.
+
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// Parts a sharded value is split into, a power of two
+pub(crate) const SHARDS: usize = 16;
+
+/// `SHARDS` values of `T`, each behind its own lock
+#[derive(Debug)]
+pub(crate) struct Sharded<T> {
+    shards: [RwLock<T>; SHARDS],
+}
+
+impl<T: Default> Default for Sharded<T> {
+    fn default() -> Self {
+        Self { shards: std::array::from_fn(|_| RwLock::default()) }
+    }
+}
+
+impl<T: Clone> Clone for Sharded<T> {
+    fn clone(&self) -> Self {
+        Self { shards: std::array::from_fn(|index| RwLock::new(self.shards[index].read().unwrap_or_else(|e| e.into_inner()).clone())) }
+    }
+}
+
+impl<T> Sharded<T> {
+    /// Shard of `key`; sequential keys are spread by Fibonacci hashing
+    fn shard(&self, key: u64) -> &RwLock<T> {
+        let index = key.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> (64 - SHARDS.trailing_zeros());
+        &self.shards[index as usize]
+    }
+
+    pub(crate) fn read(&self, key: u64) -> RwLockReadGuard<'_, T> {
+        self.shard(key).read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub(crate) fn write(&self, key: u64) -> RwLockWriteGuard<'_, T> {
+        self.shard(key).write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Every shard in turn, each locked only while it is visited
+    pub(crate) fn read_each(&self) -> impl Iterator<Item = RwLockReadGuard<'_, T>> {
+        self.shards.iter().map(|shard| shard.read().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// Every shard in turn for writing, each locked only while it is visited
+    pub(crate) fn write_each(&self) -> impl Iterator<Item = RwLockWriteGuard<'_, T>> {
+        self.shards.iter().map(|shard| shard.write().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sequential_keys_spread_over_every_shard() {
+        let sharded: Sharded<Vec<u64>> = Sharded::default();
+        for key in 0..SHARDS as u64 * 8 {
+            sharded.write(key).push(key);
+        }
+        let sizes: Vec<usize> = sharded.read_each().map(|shard| shard.len()).collect();
+        assert!(sizes.iter().all(|&size| size > 0));
+        assert_eq!(sizes.iter().sum::<usize>(), SHARDS * 8);
+        assert!(sharded.read(5).contains(&5));
+    }
+}
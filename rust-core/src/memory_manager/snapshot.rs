@@ -0,0 +1,337 @@
+//! Memory Snapshots - Saving and restoring the manager's stores
+//!
+//! `MemoryManager::snapshot` streams the episodic store, the semantic store and
+//! the blackboard to a file as a sequence of records, each length-prefixed and
+//! followed by its CRC-32, and closes it with a trailer counting the records.
+//! `MemoryManager::restore` reads and verifies the whole file into fresh stores
+//! before swapping any of them in, so a truncated or corrupted snapshot is
+//! rejected without touching the current state.
+//!
+//! Each store is captured consistently on its own, one after the other.
+//! Semantic entries are re-linked on restore rather than copied with their
+//! graph, and access statistics start afresh.
+
+use std::io::{Read, Write};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use super::blackboard::{Blackboard, BlackboardEntry, BlackboardValue};
+use super::block::Usage;
+use super::episodic::{Episode, EpisodeId, EpisodicStore};
+use super::semantic::{HnswConfig, SemanticId, SemanticMemory};
+use crate::error::AgiError;
+use crate::tensor_ops::Tensor;
+
+const MAGIC: &[u8; 8] = b"AGIMEMSN";
+
+/// Version of the snapshot layout
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+/// Largest record accepted, guarding against allocating for a corrupted length
+const MAX_RECORD_BYTES: u64 = 1 << 30;
+
+/// What a snapshot holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct SnapshotSummary {
+    pub episodes: usize,
+    pub semantic_entries: usize,
+    pub blackboard_entries: usize,
+    /// Bytes of the snapshot
+    pub bytes: u64,
+}
+
+/// Blackboard value in a serializable form
+#[derive(Serialize, Deserialize)]
+enum SavedValue {
+    Flag(bool),
+    Number(f64),
+    Text(String),
+    Vector(Vec<f64>),
+    Tensor { shape: Vec<usize>, data: Vec<f64> },
+}
+
+impl From<&BlackboardValue> for SavedValue {
+    fn from(value: &BlackboardValue) -> Self {
+        match value {
+            BlackboardValue::Flag(flag) => Self::Flag(*flag),
+            BlackboardValue::Number(number) => Self::Number(*number),
+            BlackboardValue::Text(text) => Self::Text(text.clone()),
+            BlackboardValue::Vector(vector) => Self::Vector(vector.clone()),
+            BlackboardValue::Tensor(tensor) => Self::Tensor { shape: tensor.shape.clone(), data: tensor.data.clone() },
+        }
+    }
+}
+
+impl TryFrom<SavedValue> for BlackboardValue {
+    type Error = String;
+
+    fn try_from(value: SavedValue) -> Result<Self, String> {
+        Ok(match value {
+            SavedValue::Flag(flag) => Self::Flag(flag),
+            SavedValue::Number(number) => Self::Number(number),
+            SavedValue::Text(text) => Self::Text(text),
+            SavedValue::Vector(vector) => Self::Vector(vector),
+            SavedValue::Tensor { shape, data } => {
+                if shape.iter().product::<usize>() != data.len() {
+                    return Err(format!("Tensor of shape {:?} cannot hold {} values", shape, data.len()));
+                }
+                Self::Tensor(Tensor::new(shape, data))
+            }
+        })
+    }
+}
+
+/// One record of a snapshot, as written
+///
+/// Must serialize exactly like `Record`, variant for variant.
+#[derive(Serialize)]
+enum RecordRef<'a> {
+    Episodic { capacity: usize, next_id: EpisodeId, recorded: u64, evicted: u64 },
+    Episode(&'a Episode),
+    Semantic { config: &'a HnswConfig, next_id: SemanticId },
+    SemanticEntry { id: SemanticId, embedding: &'a [f64], payload: &'a str },
+    BlackboardEntry { key: &'a str, value: SavedValue, writer: &'a str, updated_ms: u64 },
+    End { records: u64 },
+}
+
+/// One record of a snapshot, as read
+#[derive(Deserialize)]
+enum Record {
+    Episodic { capacity: usize, next_id: EpisodeId, recorded: u64, evicted: u64 },
+    Episode(Episode),
+    Semantic { config: HnswConfig, next_id: SemanticId },
+    SemanticEntry { id: SemanticId, embedding: Vec<f64>, payload: String },
+    BlackboardEntry { key: String, value: SavedValue, writer: String, updated_ms: u64 },
+    End { records: u64 },
+}
+
+/// CRC-32 (IEEE) lookup table
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 { 0xEDB8_8320 ^ (crc >> 1) } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, &byte| CRC_TABLE[((crc ^ u32::from(byte)) & 0xFF) as usize] ^ (crc >> 8))
+}
+
+fn malformed(message: &str) -> AgiError {
+    AgiError::Serialization(message.to_string())
+}
+
+/// Writes checksummed records
+struct SnapshotWriter<W: Write> {
+    writer: W,
+    buffer: Vec<u8>,
+    records: u64,
+    bytes: u64,
+}
+
+impl<W: Write> SnapshotWriter<W> {
+    fn new(mut writer: W) -> Result<Self, AgiError> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&SNAPSHOT_FORMAT_VERSION.to_le_bytes())?;
+        Ok(Self { writer, buffer: Vec::new(), records: 0, bytes: (MAGIC.len() + 4) as u64 })
+    }
+
+    fn record(&mut self, record: &RecordRef) -> Result<(), AgiError> {
+        self.buffer.clear();
+        bincode::serialize_into(&mut self.buffer, record)?;
+        self.writer.write_all(&(self.buffer.len() as u64).to_le_bytes())?;
+        self.writer.write_all(&self.buffer)?;
+        self.writer.write_all(&crc32(&self.buffer).to_le_bytes())?;
+        self.records += 1;
+        self.bytes += self.buffer.len() as u64 + 12;
+        Ok(())
+    }
+
+    /// Write the trailer and flush, returning the bytes written
+    fn finish(mut self) -> Result<u64, AgiError> {
+        self.record(&RecordRef::End { records: self.records })?;
+        self.writer.flush()?;
+        Ok(self.bytes)
+    }
+}
+
+/// Reads and verifies checksummed records
+struct SnapshotReader<R: Read> {
+    reader: R,
+    records: u64,
+    bytes: u64,
+}
+
+impl<R: Read> SnapshotReader<R> {
+    fn new(mut reader: R) -> Result<Self, AgiError> {
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(AgiError::Serialization("Not a memory snapshot".to_string()));
+        }
+        let mut version = [0u8; 4];
+        reader.read_exact(&mut version)?;
+        let version = u32::from_le_bytes(version);
+        if version != SNAPSHOT_FORMAT_VERSION {
+            return Err(AgiError::Serialization(format!("Unsupported snapshot format version {} (expected {})", version, SNAPSHOT_FORMAT_VERSION)));
+        }
+        Ok(Self { reader, records: 0, bytes: (MAGIC.len() + 4) as u64 })
+    }
+
+    fn next(&mut self) -> Result<Record, AgiError> {
+        let mut word = [0u8; 8];
+        self.reader.read_exact(&mut word)?;
+        let len = u64::from_le_bytes(word);
+        if len > MAX_RECORD_BYTES {
+            return Err(AgiError::Serialization(format!("Snapshot record {} claims {} bytes", self.records, len)));
+        }
+        let mut buffer = Vec::new();
+        (&mut self.reader).take(len).read_to_end(&mut buffer)?;
+        let mut checksum = [0u8; 4];
+        if buffer.len() as u64 != len || self.reader.read_exact(&mut checksum).is_err() {
+            return Err(AgiError::Serialization(format!("Snapshot truncated in record {}", self.records)));
+        }
+        if u32::from_le_bytes(checksum) != crc32(&buffer) {
+            return Err(AgiError::Serialization(format!("Checksum mismatch in snapshot record {}", self.records)));
+        }
+        self.records += 1;
+        self.bytes += len + 12;
+        Ok(bincode::deserialize(&buffer)?)
+    }
+}
+
+/// Stores read from a snapshot, not yet swapped in
+pub(crate) struct RestoredStores {
+    pub(crate) episodic: EpisodicStore,
+    pub(crate) semantic: SemanticMemory,
+    pub(crate) blackboard: Vec<(String, BlackboardEntry)>,
+    pub(crate) summary: SnapshotSummary,
+}
+
+/// Stream the stores to `writer`
+pub(crate) fn write_stores<W: Write>(
+    writer: W,
+    episodic: &std::sync::RwLock<EpisodicStore>,
+    semantic: &std::sync::RwLock<SemanticMemory>,
+    blackboard: &Blackboard,
+) -> Result<SnapshotSummary, AgiError> {
+    let mut writer = SnapshotWriter::new(writer)?;
+    let mut summary = SnapshotSummary::default();
+    {
+        let episodic = episodic.read().unwrap_or_else(|e| e.into_inner());
+        let stats = episodic.stats();
+        writer.record(&RecordRef::Episodic {
+            capacity: stats.capacity,
+            next_id: episodic.next_id(),
+            recorded: stats.recorded,
+            evicted: stats.evicted,
+        })?;
+        for episode in episodic.iter() {
+            writer.record(&RecordRef::Episode(episode))?;
+        }
+        summary.episodes = stats.episodes;
+    }
+    {
+        let semantic = semantic.read().unwrap_or_else(|e| e.into_inner());
+        writer.record(&RecordRef::Semantic { config: semantic.config(), next_id: semantic.next_id() })?;
+        for (id, embedding, payload) in semantic.entries() {
+            writer.record(&RecordRef::SemanticEntry { id, embedding, payload })?;
+            summary.semantic_entries += 1;
+        }
+    }
+    for (key, entry) in blackboard.changed_since(0) {
+        writer.record(&RecordRef::BlackboardEntry {
+            key: &key,
+            value: SavedValue::from(&entry.value),
+            writer: &entry.writer,
+            updated_ms: entry.updated_ms,
+        })?;
+        summary.blackboard_entries += 1;
+    }
+    summary.bytes = writer.finish()?;
+    Ok(summary)
+}
+
+/// Read and verify a whole snapshot into fresh stores charged to `usage`
+pub(crate) fn read_stores<R: Read>(reader: R, usage: &Arc<Usage>) -> Result<RestoredStores, AgiError> {
+    let mut reader = SnapshotReader::new(reader)?;
+    let mut episodic: Option<EpisodicStore> = None;
+    let mut semantic: Option<SemanticMemory> = None;
+    let mut blackboard = Vec::new();
+    loop {
+        match reader.next()? {
+            Record::Episodic { capacity, next_id, recorded, evicted } => {
+                episodic = Some(EpisodicStore::resume(capacity, next_id, recorded, evicted, usage.clone()));
+            }
+            Record::Episode(episode) => {
+                episodic.as_mut().ok_or_else(|| malformed("Episode before the episodic store header"))?.restore(episode).map_err(AgiError::Memory)?;
+            }
+            Record::Semantic { config, next_id } => {
+                semantic = Some(SemanticMemory::resume(config, next_id, usage.clone()));
+            }
+            Record::SemanticEntry { id, embedding, payload } => {
+                semantic
+                    .as_mut()
+                    .ok_or_else(|| malformed("Semantic entry before the semantic store header"))?
+                    .restore(id, &embedding, payload)
+                    .map_err(AgiError::Memory)?;
+            }
+            Record::BlackboardEntry { key, value, writer, updated_ms } => {
+                let value = BlackboardValue::try_from(value).map_err(AgiError::Serialization)?;
+                blackboard.push((key, BlackboardEntry { value, writer, version: 0, updated_ms }));
+            }
+            Record::End { records } => {
+                if records != reader.records - 1 {
+                    return Err(AgiError::Serialization(format!("Snapshot trailer counts {} records but {} were read", records, reader.records - 1)));
+                }
+                break;
+            }
+        }
+    }
+    let episodic = episodic.ok_or_else(|| malformed("Snapshot has no episodic store"))?;
+    let semantic = semantic.ok_or_else(|| malformed("Snapshot has no semantic store"))?;
+    let summary = SnapshotSummary {
+        episodes: episodic.len(),
+        semantic_entries: semantic.len(),
+        blackboard_entries: blackboard.len(),
+        bytes: reader.bytes,
+    };
+    Ok(RestoredStores { episodic, semantic, blackboard, summary })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc_and_corruption_detection() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+
+        let usage = Arc::new(Usage::default());
+        let episodic = std::sync::RwLock::new(EpisodicStore::new(8, usage.clone()));
+        let semantic = std::sync::RwLock::new(SemanticMemory::new(HnswConfig::default(), usage.clone()));
+        semantic.write().unwrap().insert(&[1.0, 0.0], "east").unwrap();
+        let blackboard = Blackboard::new(usage.clone());
+        blackboard.put("goal", "explore", "planner");
+
+        let mut bytes = Vec::new();
+        let summary = write_stores(&mut bytes, &episodic, &semantic, &blackboard).unwrap();
+        assert_eq!(summary.bytes, bytes.len() as u64);
+        assert_eq!(read_stores(bytes.as_slice(), &usage).unwrap().summary, summary);
+
+        assert!(read_stores(&bytes[..bytes.len() - 3], &usage).is_err());
+        let middle = bytes.len() / 2;
+        bytes[middle] ^= 0x40;
+        assert!(read_stores(bytes.as_slice(), &usage).is_err());
+    }
+}
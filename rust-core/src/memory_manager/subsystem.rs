@@ -0,0 +1,272 @@
+//! Subsystem Accounting - What is using the memory
+//!
+//! Every byte the manager accounts for is charged to a `Subsystem`. The stores
+//! charge their own subsystem. Blocks allocated through the manager and buffers
+//! rented from the pool are charged to the subsystem of the enclosing `scope`
+//! on the allocating thread, or to `Other` outside any scope:
+//!
+//! ```ignore
+//! let _scope = subsystem::scope(Subsystem::NeuralWeights);
+//! let weights = manager.alloc_slice::<f64>(4096)?;
+//! ```
+//!
+//! Memory held outside the manager, such as network weights and the response
+//! cache, is measured by gauges registered with `MemoryManager::report_usage`.
+//! `MemoryManager::breakdown` sums all three into a `MemoryBreakdown`.
+
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+
+/// A component memory is charged to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Subsystem {
+    NeuralWeights,
+    /// Buffers rented during forward passes
+    Activations,
+    /// Idle buffers kept by the buffer pool
+    TensorPool,
+    Episodic,
+    Semantic,
+    /// Response and other result caches
+    Caches,
+    Blackboard,
+    /// Consciousness state history
+    Consciousness,
+    Other,
+}
+
+impl Subsystem {
+    pub const ALL: [Subsystem; 9] = [
+        Self::NeuralWeights,
+        Self::Activations,
+        Self::TensorPool,
+        Self::Episodic,
+        Self::Semantic,
+        Self::Caches,
+        Self::Blackboard,
+        Self::Consciousness,
+        Self::Other,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::NeuralWeights => "neural_weights",
+            Self::Activations => "activations",
+            Self::TensorPool => "tensor_pool",
+            Self::Episodic => "episodic",
+            Self::Semantic => "semantic",
+            Self::Caches => "caches",
+            Self::Blackboard => "blackboard",
+            Self::Consciousness => "consciousness",
+            Self::Other => "other",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+thread_local! {
+    static CURRENT: Cell<Subsystem> = const { Cell::new(Subsystem::Other) };
+}
+
+/// Subsystem allocations on this thread are charged to
+pub fn current() -> Subsystem {
+    CURRENT.with(Cell::get)
+}
+
+/// Charge allocations on this thread to `subsystem` until the guard drops
+pub fn scope(subsystem: Subsystem) -> SubsystemScope {
+    SubsystemScope { previous: CURRENT.with(|current| current.replace(subsystem)), _thread: PhantomData }
+}
+
+/// Guard of a `scope`, restoring the enclosing subsystem on drop
+#[must_use = "the scope ends when the guard drops"]
+pub struct SubsystemScope {
+    previous: Subsystem,
+    /// Scopes are per thread
+    _thread: PhantomData<*const ()>,
+}
+
+impl Drop for SubsystemScope {
+    fn drop(&mut self) {
+        CURRENT.with(|current| current.set(self.previous));
+    }
+}
+
+/// Bytes charged to one subsystem
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct SubsystemUsage {
+    pub bytes: usize,
+    /// Most bytes held at once; summed over sources, so an upper bound when several report
+    pub peak: usize,
+}
+
+/// Bytes charged to each subsystem
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct MemoryBreakdown {
+    pub neural_weights: SubsystemUsage,
+    pub activations: SubsystemUsage,
+    pub tensor_pool: SubsystemUsage,
+    pub episodic: SubsystemUsage,
+    pub semantic: SubsystemUsage,
+    pub caches: SubsystemUsage,
+    pub blackboard: SubsystemUsage,
+    pub consciousness: SubsystemUsage,
+    pub other: SubsystemUsage,
+}
+
+impl MemoryBreakdown {
+    pub fn get(&self, subsystem: Subsystem) -> SubsystemUsage {
+        match subsystem {
+            Subsystem::NeuralWeights => self.neural_weights,
+            Subsystem::Activations => self.activations,
+            Subsystem::TensorPool => self.tensor_pool,
+            Subsystem::Episodic => self.episodic,
+            Subsystem::Semantic => self.semantic,
+            Subsystem::Caches => self.caches,
+            Subsystem::Blackboard => self.blackboard,
+            Subsystem::Consciousness => self.consciousness,
+            Subsystem::Other => self.other,
+        }
+    }
+
+    fn get_mut(&mut self, subsystem: Subsystem) -> &mut SubsystemUsage {
+        match subsystem {
+            Subsystem::NeuralWeights => &mut self.neural_weights,
+            Subsystem::Activations => &mut self.activations,
+            Subsystem::TensorPool => &mut self.tensor_pool,
+            Subsystem::Episodic => &mut self.episodic,
+            Subsystem::Semantic => &mut self.semantic,
+            Subsystem::Caches => &mut self.caches,
+            Subsystem::Blackboard => &mut self.blackboard,
+            Subsystem::Consciousness => &mut self.consciousness,
+            Subsystem::Other => &mut self.other,
+        }
+    }
+
+    /// Add `usage` to the figures of `subsystem`
+    pub(crate) fn add(&mut self, subsystem: Subsystem, usage: SubsystemUsage) {
+        let entry = self.get_mut(subsystem);
+        entry.bytes += usage.bytes;
+        entry.peak = (entry.peak + usage.peak).max(entry.bytes);
+    }
+
+    /// Bytes charged to all subsystems
+    pub fn total(&self) -> usize {
+        Subsystem::ALL.iter().map(|&subsystem| self.get(subsystem).bytes).sum()
+    }
+
+    /// Subsystems by bytes held, largest first, omitting those holding nothing
+    pub fn ranked(&self) -> Vec<(Subsystem, usize)> {
+        let mut ranked: Vec<(Subsystem, usize)> = Subsystem::ALL.iter()
+            .map(|&subsystem| (subsystem, self.get(subsystem).bytes))
+            .filter(|&(_, bytes)| bytes > 0)
+            .collect();
+        ranked.sort_by_key(|&(_, bytes)| std::cmp::Reverse(bytes));
+        ranked
+    }
+}
+
+/// Current and peak bytes per subsystem, updatable through shared references
+#[derive(Debug, Default)]
+pub(crate) struct Ledger {
+    current: [AtomicUsize; Subsystem::ALL.len()],
+    peak: [AtomicUsize; Subsystem::ALL.len()],
+}
+
+impl Ledger {
+    pub(crate) fn added(&self, subsystem: Subsystem, bytes: usize) {
+        let index = subsystem.index();
+        let current = self.current[index].fetch_add(bytes, Ordering::Relaxed) + bytes;
+        self.peak[index].fetch_max(current, Ordering::Relaxed);
+    }
+
+    /// Uncharge `bytes`, never below zero
+    pub(crate) fn removed(&self, subsystem: Subsystem, bytes: usize) {
+        let _ = self.current[subsystem.index()]
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |current| Some(current.saturating_sub(bytes)));
+    }
+
+    pub(crate) fn usage(&self, subsystem: Subsystem) -> SubsystemUsage {
+        let index = subsystem.index();
+        SubsystemUsage { bytes: self.current[index].load(Ordering::Relaxed), peak: self.peak[index].load(Ordering::Relaxed) }
+    }
+
+    /// Add every subsystem's figures to `breakdown`
+    pub(crate) fn add_to(&self, breakdown: &mut MemoryBreakdown) {
+        for subsystem in Subsystem::ALL {
+            breakdown.add(subsystem, self.usage(subsystem));
+        }
+    }
+}
+
+/// Measures the bytes some component holds outside the manager
+pub type Gauge = Box<dyn Fn() -> usize + Send + Sync>;
+
+/// Named gauges and the subsystems they report for
+#[derive(Default)]
+pub(crate) struct Gauges {
+    gauges: RwLock<Vec<(String, Subsystem, Gauge)>>,
+}
+
+impl Gauges {
+    /// Register `gauge` under `name`, replacing a gauge of the same name
+    pub(crate) fn register(&self, name: &str, subsystem: Subsystem, gauge: Gauge) {
+        let mut gauges = self.gauges.write().unwrap_or_else(|e| e.into_inner());
+        gauges.retain(|(existing, _, _)| existing != name);
+        gauges.push((name.to_string(), subsystem, gauge));
+    }
+
+    pub(crate) fn unregister(&self, name: &str) -> bool {
+        let mut gauges = self.gauges.write().unwrap_or_else(|e| e.into_inner());
+        let before = gauges.len();
+        gauges.retain(|(existing, _, _)| existing != name);
+        gauges.len() < before
+    }
+
+    /// Add every gauge's reading to `breakdown`
+    pub(crate) fn add_to(&self, breakdown: &mut MemoryBreakdown) {
+        let gauges = self.gauges.read().unwrap_or_else(|e| e.into_inner());
+        for (_, subsystem, gauge) in gauges.iter() {
+            let bytes = gauge();
+            breakdown.add(*subsystem, SubsystemUsage { bytes, peak: bytes });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scopes_nest_and_ledger_ranks() {
+        assert_eq!(current(), Subsystem::Other);
+        {
+            let _weights = scope(Subsystem::NeuralWeights);
+            {
+                let _activations = scope(Subsystem::Activations);
+                assert_eq!(current(), Subsystem::Activations);
+            }
+            assert_eq!(current(), Subsystem::NeuralWeights);
+        }
+        assert_eq!(current(), Subsystem::Other);
+
+        let ledger = Ledger::default();
+        ledger.added(Subsystem::Episodic, 300);
+        ledger.added(Subsystem::Caches, 500);
+        ledger.removed(Subsystem::Caches, 400);
+        ledger.removed(Subsystem::Semantic, 10);
+        let mut breakdown = MemoryBreakdown::default();
+        ledger.add_to(&mut breakdown);
+        assert_eq!(breakdown.caches, SubsystemUsage { bytes: 100, peak: 500 });
+        assert_eq!(breakdown.semantic.bytes, 0);
+        assert_eq!(breakdown.ranked(), vec![(Subsystem::Episodic, 300), (Subsystem::Caches, 100)]);
+        assert_eq!(breakdown.total(), 400);
+    }
+}
@@ -0,0 +1,153 @@
+//! Tracking Allocator - Counting real heap allocations
+//!
+//! `TrackingAllocator` wraps any `GlobalAlloc` and counts the bytes and calls
+//! passing through it with relaxed atomics. With the `tracking-allocator`
+//! feature the crate installs one over the system allocator as the global
+//! allocator, so every `Vec` behind networks, tensors and caches is counted and
+//! `MemoryManager::get_stats` reports the process's actual heap use. Without the
+//! feature, `global_counters` returns `None` and the stats fall back to what
+//! the manager allocated itself.
+
+use std::alloc::{GlobalAlloc, Layout};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+/// Counts of one tracking allocator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct AllocationCounters {
+    /// Bytes currently allocated
+    pub current: usize,
+    /// Highest `current` since creation or the last `reset_peak`
+    pub peak: usize,
+    pub allocations: usize,
+    pub deallocations: usize,
+}
+
+/// Global allocator wrapper that counts bytes and calls
+pub struct TrackingAllocator<A> {
+    inner: A,
+    current: AtomicUsize,
+    peak: AtomicUsize,
+    allocations: AtomicUsize,
+    deallocations: AtomicUsize,
+}
+
+impl<A> TrackingAllocator<A> {
+    pub const fn new(inner: A) -> Self {
+        Self {
+            inner,
+            current: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+            allocations: AtomicUsize::new(0),
+            deallocations: AtomicUsize::new(0),
+        }
+    }
+
+    /// Current counts
+    pub fn counters(&self) -> AllocationCounters {
+        AllocationCounters {
+            current: self.current.load(Ordering::Relaxed),
+            peak: self.peak.load(Ordering::Relaxed),
+            allocations: self.allocations.load(Ordering::Relaxed),
+            deallocations: self.deallocations.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Restart peak tracking from the current usage
+    pub fn reset_peak(&self) {
+        self.peak.store(self.current.load(Ordering::Relaxed), Ordering::Relaxed);
+    }
+
+    fn added(&self, size: usize) {
+        let current = self.current.fetch_add(size, Ordering::Relaxed) + size;
+        self.peak.fetch_max(current, Ordering::Relaxed);
+        self.allocations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn removed(&self, size: usize) {
+        self.current.fetch_sub(size, Ordering::Relaxed);
+        self.deallocations.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for TrackingAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.inner.alloc(layout);
+        if !ptr.is_null() {
+            self.added(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.inner.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            self.added(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.inner.dealloc(ptr, layout);
+        self.removed(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = self.inner.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            // A move counts as freeing the old block and allocating the new one
+            self.removed(layout.size());
+            self.added(new_size);
+        }
+        new_ptr
+    }
+}
+
+#[cfg(feature = "tracking-allocator")]
+#[global_allocator]
+static GLOBAL: TrackingAllocator<std::alloc::System> = TrackingAllocator::new(std::alloc::System);
+
+/// Counts of the installed global tracking allocator, if the feature is enabled
+pub fn global_counters() -> Option<AllocationCounters> {
+    #[cfg(feature = "tracking-allocator")]
+    {
+        Some(GLOBAL.counters())
+    }
+    #[cfg(not(feature = "tracking-allocator"))]
+    {
+        None
+    }
+}
+
+/// Restart the global peak from current usage; no effect without the feature
+pub fn reset_global_peak() {
+    #[cfg(feature = "tracking-allocator")]
+    GLOBAL.reset_peak();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::alloc::System;
+
+    #[test]
+    fn test_counts_allocations_and_peak() {
+        let allocator = TrackingAllocator::new(System);
+        let layout = Layout::from_size_align(1024, 8).unwrap();
+        unsafe {
+            let a = allocator.alloc(layout);
+            let b = allocator.alloc_zeroed(layout);
+            let b = allocator.realloc(b, layout, 4096);
+            assert_eq!(allocator.counters().current, 5120);
+            allocator.dealloc(a, layout);
+            allocator.dealloc(b, Layout::from_size_align(4096, 8).unwrap());
+        }
+        let counters = allocator.counters();
+        assert_eq!(counters.current, 0);
+        assert_eq!(counters.peak, 5120);
+        assert_eq!((counters.allocations, counters.deallocations), (3, 3));
+        allocator.reset_peak();
+        assert_eq!(allocator.counters().peak, 0);
+    }
+}
@@ -3,15 +3,64 @@
 //! This module provides the core neural network functionality with parallel processing,
 //! memory optimization, and advanced neural architectures.
 
-use std::sync::Arc;
-use ndarray::{Array1, Array2};
+pub mod attention;
+pub mod cache;
+pub mod checkpoint;
+pub mod optimizer;
+pub mod loss;
+pub mod metrics;
+pub mod dataset;
+pub mod embedding;
+pub mod head;
+pub mod normalization;
+pub mod recurrent;
+pub mod residual;
+pub mod precision;
+pub mod pruning;
+pub mod quantization;
+pub mod scheduler;
+pub mod streaming;
+pub mod training;
+pub mod tuner;
+
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use ndarray::{Array1, Array2, ArrayView2, ArrayViewMut2, Axis, NdFloat};
 use ndarray_rand::RandomExt;
 use ndarray_rand::rand_distr::StandardNormal;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
 use rayon::prelude::*;
 use tokio::sync::RwLock;
 use tracing::{info, instrument};
 
-use crate::memory_manager::MemoryManager;
+use crate::error::AgiError;
+use crate::memory_manager::{blackboard, pool, subsystem, Arena, Blackboard, EvictionPolicy, EvictionRule, MemoryManager, Subsystem};
+use crate::tokenizer::{BpeTokenizer, TextTokenizer, Tokenizer};
+pub use optimizer::{Optimizer, OptimizerKind, Parameter};
+pub use loss::LossFunction;
+pub use metrics::{EvaluationReport, Metric};
+pub use dataset::{Dataset, EarlyStopping, EpochMetrics, FitHistory, FitOptions, InMemoryDataset};
+use checkpoint::Checkpoints;
+pub use attention::TransformerBlock;
+pub use cache::ResponseCache;
+pub use embedding::Embedding;
+pub use head::OutputHead;
+pub use normalization::{BatchNorm, LayerNorm};
+pub use recurrent::{Gru, Lstm, RecurrentKind, RecurrentLayer, SequenceEncoderConfig};
+pub use residual::SkipConnection;
+pub use scheduler::{LrScheduler, SchedulerKind};
+pub use streaming::{StreamOptions, StreamResponse};
+pub use precision::{CompactNetwork, Precision};
+pub use pruning::{PruneStrategy, SparsityMask};
+pub use quantization::{QuantizationReport, QuantizedNetwork};
+pub use training::TrainingError;
+pub use tuner::{SearchSpace, Tuner, TuningResults};
+
+/// Version of the binary model format written by `save`
+pub const MODEL_FORMAT_VERSION: u32 = 6;
 
 /// Neural network architecture configuration
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -22,10 +71,69 @@ pub struct NeuralArchitecture {
     pub activation_function: ActivationFunction,
     pub learning_rate: f64,
     pub momentum: f64,
+    #[serde(default)]
+    pub optimizer: OptimizerKind,
+    #[serde(default)]
+    pub loss_function: LossFunction,
+    /// Probability of zeroing a hidden activation during training
+    #[serde(default)]
+    pub dropout: f64,
+    /// L2 penalty coefficient added to weight gradients
+    #[serde(default)]
+    pub weight_decay: f64,
+    /// Explicit hidden layer stack; when empty, `hidden_layers` defines dense layers
+    #[serde(default)]
+    pub layers: Vec<LayerKind>,
+    /// Learning rate schedule applied per epoch by the engine
+    #[serde(default)]
+    pub scheduler: SchedulerKind,
+    /// Seed for weight initialization, dropout and shuffling; entropy when unset
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Maximum global L2 norm of the gradients applied in one step
+    #[serde(default)]
+    pub grad_clip_norm: Option<f64>,
+    /// Recurrent encoder used by `NeuralFoundationEngine::process_sequence`
+    #[serde(default)]
+    pub sequence_encoder: Option<SequenceEncoderConfig>,
+    /// Floating point type inference is served in
+    #[serde(default)]
+    pub precision: Precision,
+    /// Residual connections between layers, indexed with the output layer last
+    #[serde(default)]
+    pub skip_connections: Vec<SkipConnection>,
+    /// Transformation the engine applies to the synthesized output
+    #[serde(default)]
+    pub output_head: OutputHead,
+}
+
+impl Default for NeuralArchitecture {
+    fn default() -> Self {
+        Self {
+            input_size: 1024,
+            hidden_layers: vec![512, 256, 128],
+            output_size: 256,
+            activation_function: ActivationFunction::Swish,
+            learning_rate: 0.001,
+            momentum: 0.9,
+            optimizer: OptimizerKind::default(),
+            loss_function: LossFunction::default(),
+            dropout: 0.0,
+            weight_decay: 0.0,
+            layers: Vec::new(),
+            scheduler: SchedulerKind::default(),
+            seed: None,
+            grad_clip_norm: None,
+            sequence_encoder: None,
+            precision: Precision::default(),
+            skip_connections: Vec::new(),
+            output_head: OutputHead::default(),
+        }
+    }
 }
 
 /// Activation functions for neural networks
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum ActivationFunction {
     Sigmoid,
     Tanh,
@@ -33,22 +141,35 @@ pub enum ActivationFunction {
     LeakyReLU,
     Swish,
     GELU,
+    /// Identity, for projections and output heads that should stay linear
+    Linear,
 }
 
 impl ActivationFunction {
     /// Apply activation function to input
     pub fn apply(&self, x: f64) -> f64 {
+        self.apply_float(x)
+    }
+    
+    /// Apply activation function in any floating point precision
+    pub fn apply_float<F: NdFloat>(&self, x: F) -> F {
+        let one = F::one();
         match self {
-            Self::Sigmoid => 1.0 / (1.0 + (-x).exp()),
+            Self::Sigmoid => one / (one + (-x).exp()),
             Self::Tanh => x.tanh(),
-            Self::ReLU => x.max(0.0),
-            Self::LeakyReLU => if x > 0.0 { x } else { 0.01 * x },
-            Self::Swish => x / (1.0 + (-x).exp()),
-            Self::GELU => 0.5 * x * (1.0 + (std::f64::consts::PI / 8.0).sqrt() * x).tanh(),
+            Self::ReLU => x.max(F::zero()),
+            Self::LeakyReLU => if x > F::zero() { x } else { precision::cast::<F>(0.01) * x },
+            Self::Swish => x / (one + (-x).exp()),
+            Self::GELU => {
+                let c = precision::cast::<F>((2.0 / std::f64::consts::PI).sqrt());
+                let half = precision::cast::<F>(0.5);
+                half * x * (one + (c * (x + precision::cast::<F>(0.044715) * x.powi(3))).tanh())
+            }
+            Self::Linear => x,
         }
     }
     
-    /// Apply activation function derivative
+    /// Apply activation function derivative with respect to the pre-activation `x`
     pub fn derivative(&self, x: f64) -> f64 {
         match self {
             Self::Sigmoid => {
@@ -63,21 +184,105 @@ impl ActivationFunction {
                 sigmoid + x * sigmoid * (1.0 - sigmoid)
             }
             Self::GELU => {
-                // Simplified GELU approximation
-                0.5 * x * (1.0 + (x / 2.0_f64.sqrt()).tanh())
+                // Derivative of the tanh approximation used in `apply`
+                let c = (2.0 / std::f64::consts::PI).sqrt();
+                let inner = c * (x + 0.044715 * x.powi(3));
+                let t = inner.tanh();
+                0.5 * (1.0 + t) + 0.5 * x * (1.0 - t * t) * c * (1.0 + 3.0 * 0.044715 * x * x)
+            }
+            Self::Linear => 1.0,
+        }
+    }
+}
+
+/// Layer specification inside a `NeuralArchitecture`
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum LayerKind {
+    /// Fully connected layer using the architecture's activation function
+    Dense { units: usize },
+    /// Per-sample normalization over features
+    LayerNorm,
+    /// Per-feature normalization over the batch
+    BatchNorm { momentum: f64 },
+    /// Transformer encoder block over the input viewed as `(tokens, model_dim)`
+    SelfAttention {
+        model_dim: usize,
+        heads: usize,
+        feed_forward_dim: usize,
+    },
+}
+
+impl NeuralArchitecture {
+    /// Random number generator for one consumer of this architecture
+    ///
+    /// With a seed, each `stream` gets its own reproducible sequence so that e.g. the
+    /// networks of an ensemble differ from each other but not from run to run.
+    pub fn rng(&self, stream: u64) -> StdRng {
+        match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed ^ stream.wrapping_mul(0x9E37_79B9_7F4A_7C15)),
+            None => StdRng::from_entropy(),
+        }
+    }
+    
+    /// Hidden layer stack, derived from `hidden_layers` when `layers` is empty
+    pub fn hidden_layer_kinds(&self) -> Vec<LayerKind> {
+        if self.layers.is_empty() {
+            self.hidden_layers.iter().map(|&units| LayerKind::Dense { units }).collect()
+        } else {
+            self.layers.clone()
+        }
+    }
+    
+    /// Check that every attention block accepts its input width and the skip
+    /// connections fit the layer stack
+    pub fn validate(&self) -> Result<(), String> {
+        let widths = self.widths();
+        for (kind, &input_size) in self.hidden_layer_kinds().iter().zip(&widths) {
+            if let LayerKind::SelfAttention { model_dim, heads, .. } = *kind {
+                TransformerBlock::check_dimensions(input_size, model_dim, heads)?;
             }
         }
+        residual::validate(&self.skip_connections, &widths)
+    }
+    
+    /// Check that the skip connections fit the layer stack
+    pub fn validate_skip_connections(&self) -> Result<(), String> {
+        residual::validate(&self.skip_connections, &self.widths())
+    }
+    
+    /// Input width of every layer, followed by the network output width
+    fn widths(&self) -> Vec<usize> {
+        let mut widths = vec![self.input_size];
+        for kind in self.hidden_layer_kinds() {
+            let width = match kind {
+                LayerKind::Dense { units } => units,
+                LayerKind::LayerNorm | LayerKind::BatchNorm { .. } | LayerKind::SelfAttention { .. } => {
+                    *widths.last().expect("widths start with the input")
+                }
+            };
+            widths.push(width);
+        }
+        widths.push(self.output_size);
+        widths
     }
 }
 
 /// Individual neural network layer
-#[derive(Debug)]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct NeuralLayer {
     weights: Array2<f64>,
     biases: Array1<f64>,
     activation: ActivationFunction,
-    last_input: Option<Array1<f64>>,
-    last_output: Option<Array1<f64>>,
+    #[serde(skip)]
+    weight_gradients: Array2<f64>,
+    #[serde(skip)]
+    bias_gradients: Array1<f64>,
+    #[serde(skip)]
+    last_input: Option<Array2<f64>>,
+    #[serde(skip)]
+    last_linear: Option<Array2<f64>>,
+    /// Connections kept after pruning; `None` until the layer is first pruned
+    mask: Option<SparsityMask>,
 }
 
 impl NeuralLayer {
@@ -86,219 +291,1577 @@ impl NeuralLayer {
         input_size: usize,
         output_size: usize,
         activation: ActivationFunction,
+    ) -> Self {
+        Self::with_rng(input_size, output_size, activation, &mut rand::thread_rng())
+    }
+    
+    /// Create a new neural layer drawing initial weights from the given RNG
+    pub fn with_rng<R: Rng + ?Sized>(
+        input_size: usize,
+        output_size: usize,
+        activation: ActivationFunction,
+        rng: &mut R,
     ) -> Self {
         // Initialize weights with Xavier/Glorot initialization
         let weight_scale = (2.0 / (input_size + output_size) as f64).sqrt();
-        let weights = Array2::random((output_size, input_size), StandardNormal) * weight_scale;
+        let weights = Array2::random_using((output_size, input_size), StandardNormal, rng) * weight_scale;
         let biases = Array1::zeros(output_size);
         
         Self {
             weights,
             biases,
             activation,
+            weight_gradients: Array2::zeros((output_size, input_size)),
+            bias_gradients: Array1::zeros(output_size),
             last_input: None,
-            last_output: None,
+            last_linear: None,
+            mask: None,
         }
     }
     
+    /// Number of inputs the layer expects
+    pub fn input_size(&self) -> usize {
+        self.weights.ncols()
+    }
+    
+    /// Number of outputs the layer produces
+    pub fn output_size(&self) -> usize {
+        self.weights.nrows()
+    }
+    
+    /// Number of trainable parameters
+    pub fn parameter_count(&self) -> usize {
+        self.weights.len() + self.biases.len()
+    }
+    
     /// Forward pass through the layer
     pub fn forward(&mut self, input: &Array1<f64>) -> Array1<f64> {
-        // Store input for backpropagation
-        self.last_input = Some(input.clone());
-        
-        // Linear transformation: W * x + b
-        let linear_output = self.weights.dot(input) + &self.biases;
+        let batch = input.view().insert_axis(Axis(0)).to_owned();
+        self.forward_batch(&batch, true).row(0).to_owned()
+    }
+    
+    /// Forward pass over a `(batch, input_size)` matrix
+    pub fn forward_batch(&mut self, input: &Array2<f64>, cache: bool) -> Array2<f64> {
+        // Linear transformation: X * W^T + b
+        let linear_output = self.linear(input);
         
         // Apply activation function
         let output = linear_output.mapv(|x| self.activation.apply(x));
         
-        // Store output for backpropagation
-        self.last_output = Some(output.clone());
+        // Store input and pre-activation for backpropagation
+        if cache {
+            self.last_input = Some(input.clone());
+            self.last_linear = Some(linear_output);
+        } else {
+            self.clear_cache();
+        }
         
         output
     }
     
+    /// Inference-only forward pass that leaves the layer untouched
+    pub fn predict_batch(&self, input: &Array2<f64>) -> Array2<f64> {
+        let mut output = self.linear(input);
+        output.mapv_inplace(|x| self.activation.apply(x));
+        output
+    }
+    
+    /// Inference-only forward pass writing into `output`, e.g. a slice of an arena
+    pub fn predict_into(&self, input: &ArrayView2<f64>, output: &mut ArrayViewMut2<f64>) {
+        match &self.mask {
+            Some(mask) if mask.is_sparse() => output.assign(&mask.linear(input, &self.weights, &self.biases)),
+            _ => {
+                ndarray::linalg::general_mat_mul(1.0, input, &self.weights.t(), 0.0, output);
+                *output += &self.biases;
+            }
+        }
+        output.mapv_inplace(|x| self.activation.apply(x));
+    }
+    
+    fn linear(&self, input: &Array2<f64>) -> Array2<f64> {
+        match &self.mask {
+            Some(mask) if mask.is_sparse() => mask.linear(input, &self.weights, &self.biases),
+            _ => input.dot(&self.weights.t()) + &self.biases,
+        }
+    }
+    
     /// Backward pass for training
-    pub fn backward(
-        &mut self,
-        gradient: &Array1<f64>,
-        learning_rate: f64,
-        momentum: f64,
-    ) -> Array1<f64> {
-        let input = self.last_input.as_ref().unwrap();
-        let output = self.last_output.as_ref().unwrap();
-        
-        // Calculate activation gradient
-        let activation_gradient = gradient * &output.mapv(|x| self.activation.derivative(x));
+    ///
+    /// `gradient` is the loss gradient with respect to this layer's output. Parameter
+    /// gradients are accumulated until the optimizer applies them; the gradient with
+    /// respect to the layer input is returned for the previous layer.
+    pub fn backward(&mut self, gradient: &Array1<f64>) -> Array1<f64> {
+        let batch = gradient.view().insert_axis(Axis(0)).to_owned();
+        self.backward_batch(&batch).row(0).to_owned()
+    }
+    
+    /// Backward pass over a `(batch, output_size)` gradient matrix
+    pub fn backward_batch(&mut self, gradient: &Array2<f64>) -> Array2<f64> {
+        let input = self.last_input.as_ref()
+            .expect("backward called before forward");
+        let linear = self.last_linear.as_ref()
+            .expect("backward called before forward");
         
-        // Calculate weight gradients (simplified for now)
-        let bias_gradients = activation_gradient.clone();
+        // Gradient with respect to the pre-activation
+        let activation_gradient = gradient * &linear.mapv(|x| self.activation.derivative(x));
         
-        // Update biases only for now (weight update will be implemented later)
-        self.biases -= &(bias_gradients * learning_rate);
+        // Weight gradients: sum over the batch of outer(activation gradient, input)
+        self.weight_gradients += &activation_gradient.t().dot(input);
+        self.bias_gradients += &activation_gradient.sum_axis(Axis(0));
+        if let Some(mask) = &self.mask {
+            mask.apply(&mut self.weight_gradients);
+        }
         
-        // Return gradient for previous layer
-        self.weights.t().dot(&activation_gradient)
+        // Gradient for previous layer
+        activation_gradient.dot(&self.weights)
+    }
+    
+    /// Drop cached activations
+    pub fn clear_cache(&mut self) {
+        self.last_input = None;
+        self.last_linear = None;
+    }
+    
+    /// Reset accumulated gradients to zero
+    pub fn zero_gradients(&mut self) {
+        // Buffers are not persisted, so size them on first use after loading
+        if self.weight_gradients.raw_dim() != self.weights.raw_dim() {
+            self.weight_gradients = Array2::zeros(self.weights.raw_dim());
+            self.bias_gradients = Array1::zeros(self.biases.raw_dim());
+        }
+        if let Some(mask) = &mut self.mask {
+            mask.rebuild();
+        }
+        self.weight_gradients.fill(0.0);
+        self.bias_gradients.fill(0.0);
+    }
+    
+    /// Prune every remaining weight whose magnitude matches `prune`, returning how many
+    pub fn prune(&mut self, prune: impl Fn(f64) -> bool) -> usize {
+        let (outputs, inputs) = self.weights.dim();
+        let mask = self.mask.get_or_insert_with(|| SparsityMask::dense(outputs, inputs));
+        let pruned = mask.prune(&self.weights, prune);
+        mask.apply(&mut self.weights);
+        pruned
+    }
+    
+    /// Re-zero pruned weights, e.g. after an optimizer step with momentum
+    pub fn apply_mask(&mut self) {
+        if let Some(mask) = &self.mask {
+            mask.apply(&mut self.weights);
+        }
+    }
+    
+    /// Number of pruned weights
+    pub fn pruned_weights(&self) -> usize {
+        self.mask.as_ref().map_or(0, SparsityMask::pruned)
+    }
+    
+    /// Fold an L2 penalty on the weights (not biases) into the accumulated gradients
+    pub fn apply_weight_decay(&mut self, weight_decay: f64) {
+        if weight_decay > 0.0 {
+            self.weight_gradients.scaled_add(weight_decay, &self.weights);
+        }
+    }
+    
+    /// Trainable parameters paired with their accumulated gradients
+    pub fn parameters(&mut self) -> Vec<Parameter<'_>> {
+        vec![
+            Parameter {
+                values: self.weights.as_slice_mut().expect("weights are contiguous"),
+                gradients: self.weight_gradients.as_slice_mut().expect("gradients are contiguous"),
+            },
+            Parameter {
+                values: self.biases.as_slice_mut().expect("biases are contiguous"),
+                gradients: self.bias_gradients.as_slice_mut().expect("gradients are contiguous"),
+            },
+        ]
+    }
+}
+
+/// Scratch arena for one inference request, charged to `Subsystem::Activations`
+fn activation_arena() -> Arena<'static> {
+    let _scope = subsystem::scope(Subsystem::Activations);
+    pool::global().arena()
+}
+
+/// `(rows, columns)` matrix over a fresh slice of `arena`
+fn arena_matrix<'a>(arena: &'a Arena, rows: usize, columns: usize) -> ArrayViewMut2<'a, f64> {
+    ArrayViewMut2::from_shape((rows, columns), arena.alloc(rows * columns)).expect("the slice holds rows * columns elements")
+}
+
+/// Copy of `source` in `arena`
+fn arena_copy<'a>(arena: &'a Arena, source: &ArrayView2<f64>) -> ArrayViewMut2<'a, f64> {
+    let mut copy = arena_matrix(arena, source.nrows(), source.ncols());
+    copy.assign(source);
+    copy
+}
+
+/// Layer instance inside a `NeuralNetwork`
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub enum Layer {
+    Dense(NeuralLayer),
+    LayerNorm(LayerNorm),
+    BatchNorm(BatchNorm),
+    Attention(Box<TransformerBlock>),
+}
+
+impl Layer {
+    /// Instantiate a layer from its specification
+    fn build<R: Rng + ?Sized>(
+        kind: &LayerKind,
+        input_size: usize,
+        activation: &ActivationFunction,
+        rng: &mut R,
+    ) -> Self {
+        match kind {
+            LayerKind::Dense { units } => {
+                Self::Dense(NeuralLayer::with_rng(input_size, *units, activation.clone(), rng))
+            }
+            LayerKind::LayerNorm => Self::LayerNorm(LayerNorm::new(input_size)),
+            LayerKind::BatchNorm { momentum } => Self::BatchNorm(BatchNorm::new(input_size, *momentum)),
+            LayerKind::SelfAttention { model_dim, heads, feed_forward_dim } => Self::Attention(Box::new(
+                TransformerBlock::with_rng(input_size, *model_dim, *heads, *feed_forward_dim, rng),
+            )),
+        }
+    }
+    
+    /// Output width for the given input width, or `None` if the layer cannot accept it
+    pub fn output_size(&self, input_size: usize) -> Option<usize> {
+        match self {
+            Self::Dense(layer) => (layer.input_size() == input_size).then(|| layer.output_size()),
+            Self::LayerNorm(layer) => (layer.features() == input_size).then_some(input_size),
+            Self::BatchNorm(layer) => (layer.features() == input_size).then_some(input_size),
+            Self::Attention(layer) => (layer.features() == input_size).then_some(input_size),
+        }
+    }
+    
+    /// Number of trainable parameters
+    pub fn parameter_count(&self) -> usize {
+        match self {
+            Self::Dense(layer) => layer.parameter_count(),
+            Self::LayerNorm(layer) => 2 * layer.features(),
+            Self::BatchNorm(layer) => 2 * layer.features(),
+            Self::Attention(layer) => layer.parameter_count(),
+        }
+    }
+    
+    /// Forward pass; `training` selects batch statistics and activation caching
+    pub fn forward_batch(&mut self, input: &Array2<f64>, training: bool) -> Array2<f64> {
+        match self {
+            Self::Dense(layer) => layer.forward_batch(input, training),
+            Self::LayerNorm(layer) => layer.forward_batch(input, training),
+            Self::BatchNorm(layer) => layer.forward_batch(input, training),
+            Self::Attention(layer) => layer.forward_batch(input, training),
+        }
+    }
+    
+    /// Inference-mode forward pass through a shared layer
+    pub fn predict_batch(&self, input: &Array2<f64>) -> Array2<f64> {
+        match self {
+            Self::Dense(layer) => layer.predict_batch(input),
+            Self::LayerNorm(layer) => layer.predict_batch(input),
+            Self::BatchNorm(layer) => layer.predict_batch(input),
+            Self::Attention(layer) => layer.predict_batch(input),
+        }
+    }
+    
+    /// Inference-mode forward pass with the output in `arena`
+    ///
+    /// Dense layers write straight into the arena; the others compute their output as
+    /// `predict_batch` does and it is copied in.
+    fn predict_in<'a>(&self, input: &ArrayView2<f64>, arena: &'a Arena) -> ArrayViewMut2<'a, f64> {
+        let output = match self {
+            Self::Dense(layer) => {
+                let mut output = arena_matrix(arena, input.nrows(), layer.output_size());
+                layer.predict_into(input, &mut output);
+                return output;
+            }
+            Self::LayerNorm(layer) => layer.predict_batch(input),
+            Self::BatchNorm(layer) => layer.predict_batch(input),
+            Self::Attention(layer) => layer.predict_batch(input),
+        };
+        arena_copy(arena, &output.view())
+    }
+    
+    /// Training-mode forward that caches activations without updating running
+    /// statistics, for recomputing a checkpointed segment
+    fn recompute_batch(&mut self, input: &Array2<f64>) -> Array2<f64> {
+        match self {
+            Self::BatchNorm(layer) => layer.recompute_batch(input),
+            layer => layer.forward_batch(input, true),
+        }
+    }
+    
+    /// Drop cached activations
+    pub fn clear_cache(&mut self) {
+        match self {
+            Self::Dense(layer) => layer.clear_cache(),
+            Self::LayerNorm(layer) => layer.clear_cache(),
+            Self::BatchNorm(layer) => layer.clear_cache(),
+            Self::Attention(layer) => layer.clear_cache(),
+        }
+    }
+    
+    /// Backward pass returning the gradient with respect to the layer input
+    pub fn backward_batch(&mut self, gradient: &Array2<f64>) -> Array2<f64> {
+        match self {
+            Self::Dense(layer) => layer.backward_batch(gradient),
+            Self::LayerNorm(layer) => layer.backward_batch(gradient),
+            Self::BatchNorm(layer) => layer.backward_batch(gradient),
+            Self::Attention(layer) => layer.backward_batch(gradient),
+        }
+    }
+    
+    /// Reset accumulated gradients to zero
+    pub fn zero_gradients(&mut self) {
+        match self {
+            Self::Dense(layer) => layer.zero_gradients(),
+            Self::LayerNorm(layer) => layer.zero_gradients(),
+            Self::BatchNorm(layer) => layer.zero_gradients(),
+            Self::Attention(layer) => layer.zero_gradients(),
+        }
+    }
+    
+    /// Fold an L2 penalty into weight gradients (normalization parameters are exempt)
+    pub fn apply_weight_decay(&mut self, weight_decay: f64) {
+        match self {
+            Self::Dense(layer) => layer.apply_weight_decay(weight_decay),
+            Self::Attention(layer) => layer.apply_weight_decay(weight_decay),
+            Self::LayerNorm(_) | Self::BatchNorm(_) => {}
+        }
+    }
+    
+    /// Trainable parameters paired with their accumulated gradients
+    pub fn parameters(&mut self) -> Vec<Parameter<'_>> {
+        match self {
+            Self::Dense(layer) => layer.parameters(),
+            Self::LayerNorm(layer) => layer.parameters(),
+            Self::BatchNorm(layer) => layer.parameters(),
+            Self::Attention(layer) => layer.parameters(),
+        }
     }
 }
 
 /// Complete neural network
 pub struct NeuralNetwork {
-    layers: Vec<NeuralLayer>,
+    layers: Vec<Layer>,
     architecture: NeuralArchitecture,
+    optimizer: Box<dyn Optimizer>,
+    training: bool,
+    rng: StdRng,
+    dropout_masks: Vec<Option<Array2<f64>>>,
+    /// Reduced-precision copy served by `predict` when `architecture.precision` is `F32`
+    compact: Option<CompactNetwork<f32>>,
+    /// Layers per checkpointed segment in `train_batch`; every activation is kept when unset
+    checkpoint_segment: Option<usize>,
+    checkpoints: Option<Checkpoints>,
 }
 
 impl NeuralNetwork {
     /// Create a new neural network
+    ///
+    /// Initialization is reproducible when `architecture.seed` is set. New code
+    /// should prefer `try_new`, which reports an invalid architecture instead.
+    ///
+    /// # Panics
+    ///
+    /// If `architecture.validate()` fails: for skip connections that do not
+    /// fit the layer stack or self-attention layers whose dimensions do not divide.
+    /// Architectures of only dense and normalization layers without skip
+    /// connections always build.
     pub fn new(architecture: NeuralArchitecture) -> Self {
+        Self::try_new(architecture).expect("invalid network architecture")
+    }
+    
+    /// Create a new neural network, or `AgiError::Config` if the architecture is invalid
+    pub fn try_new(architecture: NeuralArchitecture) -> Result<Self, AgiError> {
+        let mut rng = architecture.rng(0);
+        Self::try_with_rng(architecture, &mut rng)
+    }
+    
+    /// Like `new`, drawing initial weights from the given RNG
+    ///
+    /// Pass a `quantum::QuantumRng` to initialize from quantum entropy.
+    ///
+    /// # Panics
+    ///
+    /// Under the same conditions as `new`; `try_with_rng` reports them instead.
+    pub fn with_rng<R: Rng + ?Sized>(architecture: NeuralArchitecture, rng: &mut R) -> Self {
+        Self::try_with_rng(architecture, rng).expect("invalid network architecture")
+    }
+    
+    /// Like `try_new`, drawing initial weights from the given RNG
+    pub fn try_with_rng<R: Rng + ?Sized>(architecture: NeuralArchitecture, rng: &mut R) -> Result<Self, AgiError> {
+        architecture.validate().map_err(AgiError::Config)?;
+        
         let mut layers = Vec::new();
         let mut current_size = architecture.input_size;
         
         // Create hidden layers
-        for &hidden_size in &architecture.hidden_layers {
-            layers.push(NeuralLayer::new(
-                current_size,
-                hidden_size,
-                architecture.activation_function.clone(),
-            ));
-            current_size = hidden_size;
+        for kind in architecture.hidden_layer_kinds() {
+            let layer = Layer::build(&kind, current_size, &architecture.activation_function, rng);
+            current_size = layer.output_size(current_size).expect("layer built for this input size");
+            layers.push(layer);
         }
         
         // Create output layer
-        layers.push(NeuralLayer::new(
+        layers.push(Layer::Dense(NeuralLayer::with_rng(
             current_size,
             architecture.output_size,
             architecture.activation_function.clone(),
-        ));
+            rng,
+        )));
+        
+        let optimizer = architecture.optimizer.build(architecture.momentum);
+        let rng = StdRng::from_rng(rng).unwrap_or_else(|_| StdRng::from_entropy());
+        
+        let mut network = Self {
+            layers,
+            architecture,
+            optimizer,
+            training: true,
+            rng,
+            dropout_masks: Vec::new(),
+            compact: None,
+            checkpoint_segment: None,
+            checkpoints: None,
+        };
+        network.refresh_compact();
+        Ok(network)
+    }
+    
+    /// Switch between training mode (dropout active) and evaluation mode (deterministic)
+    pub fn set_training(&mut self, training: bool) {
+        self.training = training;
+        if !training {
+            self.dropout_masks.clear();
+        }
+    }
+    
+    /// Whether the network is in training mode
+    pub fn is_training(&self) -> bool {
+        self.training
+    }
+    
+    /// Recompute activations during backward instead of storing them
+    ///
+    /// With `Some(segment)`, `train_batch` keeps only the input of every `segment`-th
+    /// layer and recomputes the rest segment by segment, so activation memory grows
+    /// with the segment length rather than the depth. Gradients are unchanged.
+    pub fn set_gradient_checkpointing(&mut self, segment: Option<usize>) {
+        self.checkpoint_segment = segment.map(|segment| segment.max(1));
+    }
+    
+    /// Layers per checkpointed segment, if gradient checkpointing is enabled
+    pub fn gradient_checkpointing(&self) -> Option<usize> {
+        self.checkpoint_segment
+    }
+    
+    /// Name of the optimizer applied during training
+    pub fn optimizer_name(&self) -> &'static str {
+        self.optimizer.name()
+    }
+    
+    /// Network architecture
+    pub fn architecture(&self) -> &NeuralArchitecture {
+        &self.architecture
+    }
+    
+    /// Floating point type `predict` is served in
+    pub fn precision(&self) -> Precision {
+        self.architecture.precision
+    }
+    
+    /// Switch the inference precision; training keeps `f64` master weights
+    pub fn set_precision(&mut self, precision: Precision) {
+        self.architecture.precision = precision;
+        self.refresh_compact();
+    }
+    
+    /// Frozen inference copy of the current weights in any float type
+    pub fn to_compact<F: NdFloat>(&self) -> CompactNetwork<F> {
+        CompactNetwork::from_network(self)
+    }
+    
+    /// Zero small-magnitude weights of every dense layer, returning the achieved sparsity
+    ///
+    /// Pruned connections stay zero through further training and are skipped by the
+    /// forward pass once a layer is more than half sparse. Pruning is cumulative.
+    pub fn prune(&mut self, strategy: PruneStrategy) -> f64 {
+        let threshold = match strategy {
+            PruneStrategy::Threshold(threshold) => Some((threshold, false)),
+            PruneStrategy::TargetSparsity(fraction) => {
+                let magnitudes = self.dense_layers().flat_map(|layer| layer.weights.iter().map(|w| w.abs())).collect();
+                pruning::sparsity_threshold(magnitudes, fraction).map(|threshold| (threshold, true))
+            }
+        };
+        if let Some((threshold, inclusive)) = threshold {
+            for layer in &mut self.layers {
+                if let Layer::Dense(layer) = layer {
+                    layer.prune(|magnitude| magnitude < threshold || (inclusive && magnitude == threshold));
+                }
+            }
+            self.refresh_compact();
+        }
+        
+        let sparsity = self.sparsity();
+        info!("Pruned network to {:.1}% sparsity", sparsity * 100.0);
+        sparsity
+    }
+    
+    /// Fraction of dense-layer weights that are pruned
+    pub fn sparsity(&self) -> f64 {
+        let total: usize = self.dense_layers().map(|layer| layer.weights.len()).sum();
+        let pruned: usize = self.dense_layers().map(NeuralLayer::pruned_weights).sum();
+        if total == 0 { 0.0 } else { pruned as f64 / total as f64 }
+    }
+    
+    fn dense_layers(&self) -> impl Iterator<Item = &NeuralLayer> {
+        self.layers.iter().filter_map(|layer| match layer {
+            Layer::Dense(layer) => Some(layer),
+            _ => None,
+        })
+    }
+    
+    /// Post-training int8 copy for deployment on constrained devices
+    ///
+    /// Use `QuantizedNetwork::report` on held-out data to measure the accuracy cost.
+    pub fn quantize(&self) -> QuantizedNetwork {
+        QuantizedNetwork::from_network(self)
+    }
+    
+    /// Rebuild the reduced-precision copy after the weights changed
+    fn refresh_compact(&mut self) {
+        self.compact = match self.architecture.precision {
+            Precision::F64 => None,
+            Precision::F32 => Some(self.to_compact()),
+        };
+    }
+    
+    /// Bytes of weights `predict` reads
+    pub fn inference_bytes(&self) -> usize {
+        self.parameter_count() * self.architecture.precision.bytes_per_parameter()
+    }
+    
+    /// Learning rate currently used by `train_batch`
+    pub fn learning_rate(&self) -> f64 {
+        self.architecture.learning_rate
+    }
+    
+    /// Override the learning rate used by `train_batch`
+    pub fn set_learning_rate(&mut self, learning_rate: f64) {
+        self.architecture.learning_rate = learning_rate;
+    }
+    
+    /// Layers in evaluation order, output layer last
+    pub fn layers(&self) -> &[Layer] {
+        &self.layers
+    }
+    
+    /// Total number of trainable parameters
+    pub fn parameter_count(&self) -> usize {
+        self.layers.iter().map(Layer::parameter_count).sum()
+    }
+    
+    /// Save weights, biases and architecture to a file
+    ///
+    /// Optimizer state is not persisted; training resumes with fresh moment estimates.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), AgiError> {
+        let mut writer = BufWriter::new(std::fs::File::create(path.as_ref())?);
+        self.save_to_writer(&mut writer)?;
+        writer.flush()?;
+        info!("Neural network saved to {}", path.as_ref().display());
+        Ok(())
+    }
+    
+    /// Load a network previously written by `save`
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, AgiError> {
+        let mut reader = BufReader::new(std::fs::File::open(path.as_ref())?);
+        let network = Self::load_from_reader(&mut reader)?;
+        info!("Neural network loaded from {}", path.as_ref().display());
+        Ok(network)
+    }
+    
+    /// Serialize the network into any writer
+    pub fn save_to_writer<W: Write>(&self, writer: W) -> Result<(), AgiError> {
+        let saved = SavedNetworkRef {
+            format_version: MODEL_FORMAT_VERSION,
+            architecture: &self.architecture,
+            layers: &self.layers,
+        };
+        bincode::serialize_into(writer, &saved)?;
+        Ok(())
+    }
+    
+    /// Deserialize a network from any reader
+    pub fn load_from_reader<R: Read>(reader: R) -> Result<Self, AgiError> {
+        let saved: SavedNetwork = bincode::deserialize_from(reader)?;
+        Self::from_saved(saved)
+    }
+    
+    fn from_saved(saved: SavedNetwork) -> Result<Self, AgiError> {
+        if saved.format_version != MODEL_FORMAT_VERSION {
+            return Err(AgiError::Serialization(format!(
+                "Unsupported model format version {} (expected {})",
+                saved.format_version, MODEL_FORMAT_VERSION
+            )));
+        }
+        
+        let mut current_size = saved.architecture.input_size;
+        for layer in &saved.layers {
+            current_size = layer.output_size(current_size)
+                .ok_or_else(|| AgiError::Serialization("Saved layer shapes do not match the architecture".to_string()))?;
+        }
+        if current_size != saved.architecture.output_size {
+            return Err(AgiError::Serialization("Saved output layer does not match the architecture".to_string()));
+        }
+        saved.architecture.validate_skip_connections().map_err(AgiError::Serialization)?;
+        
+        let mut layers = saved.layers;
+        for layer in &mut layers {
+            layer.zero_gradients();
+        }
+        let optimizer = saved.architecture.optimizer.build(saved.architecture.momentum);
+        let rng = saved.architecture.rng(0);
         
-        Self { layers, architecture }
+        let mut network = Self {
+            layers,
+            architecture: saved.architecture,
+            optimizer,
+            training: true,
+            rng,
+            dropout_masks: Vec::new(),
+            compact: None,
+            checkpoint_segment: None,
+            checkpoints: None,
+        };
+        network.refresh_compact();
+        Ok(network)
     }
     
     /// Forward pass through the entire network
     pub fn forward(&mut self, input: &Array1<f64>) -> Array1<f64> {
+        let batch = input.view().insert_axis(Axis(0)).to_owned();
+        self.forward_batch(&batch).row(0).to_owned()
+    }
+    
+    /// Inference on a single sample through a shared network
+    pub fn predict(&self, input: &Array1<f64>) -> Array1<f64> {
+        let arena = activation_arena();
+        self.predict_in(&input.view().insert_axis(Axis(0)), &arena).row(0).to_owned()
+    }
+    
+    /// Inference on a `(batch, input_size)` view with every activation in `arena`
+    ///
+    /// Computes what `predict_batch` does without allocating for each dense layer;
+    /// the output lives as long as the arena.
+    pub fn predict_in<'a>(&self, input: &ArrayView2<f64>, arena: &'a Arena) -> ArrayViewMut2<'a, f64> {
+        if let Some(compact) = &self.compact {
+            let output = compact.predict_batch(&input.mapv(|x| x as f32)).mapv(f64::from);
+            return arena_copy(arena, &output.view());
+        }
+        
+        let skips = &self.architecture.skip_connections;
+        let mut skip_outputs: Vec<Option<ArrayViewMut2<'a, f64>>> = self.layers.iter().map(|_| None).collect();
+        let mut current: Option<ArrayViewMut2<'a, f64>> = None;
+        for (index, layer) in self.layers.iter().enumerate() {
+            let output = match current.as_mut() {
+                Some(current) => {
+                    residual::add_skips(skips, index, &skip_outputs, current);
+                    layer.predict_in(&current.view(), arena)
+                }
+                None => layer.predict_in(input, arena),
+            };
+            if residual::is_source(skips, index) {
+                skip_outputs[index] = Some(arena_copy(arena, &output.view()));
+            }
+            current = Some(output);
+        }
+        current.expect("a network ends in its output layer")
+    }
+    
+    /// Evaluation-mode forward pass over a `(batch, input_size)` matrix
+    ///
+    /// Unlike `forward_batch` this takes `&self`: no dropout is applied, batch
+    /// normalization uses its running statistics and nothing is cached, so one network
+    /// can serve many concurrent requests without being cloned.
+    pub fn predict_batch(&self, input: &Array2<f64>) -> Array2<f64> {
+        if let Some(compact) = &self.compact {
+            return compact.predict_batch(&input.mapv(|x| x as f32)).mapv(f64::from);
+        }
+        
+        residual::forward(&self.layers, &self.architecture.skip_connections, input, Layer::predict_batch)
+    }
+    
+    /// Forward pass over a `(batch, input_size)` matrix
+    ///
+    /// In training mode, hidden dense activations are dropped with probability
+    /// `architecture.dropout` (inverted dropout, so evaluation needs no rescaling) and
+    /// batch normalization uses batch statistics.
+    pub fn forward_batch(&mut self, input: &Array2<f64>) -> Array2<f64> {
+        self.forward_layers(input, false, None).0
+    }
+    
+    /// Forward pass; with `guard`, stops at and reports the first layer whose output
+    /// is not finite. With `checkpoint_segment`, layer caches are dropped and only
+    /// segment inputs are kept in `checkpoints`.
+    fn forward_layers(
+        &mut self,
+        input: &Array2<f64>,
+        guard: bool,
+        checkpoint_segment: Option<usize>,
+    ) -> (Array2<f64>, Option<usize>) {
         let mut current = input.clone();
+        let dropout = self.architecture.dropout;
+        let apply_dropout = self.training && dropout > 0.0 && dropout < 1.0;
+        let hidden_count = self.layers.len() - 1;
+        let skips = &self.architecture.skip_connections;
+        let mut skip_outputs = vec![None; self.layers.len()];
+        let mut checkpoints = checkpoint_segment.map(|segment| Checkpoints::new(segment, self.layers.len()));
+        self.checkpoints = None;
         
-        for layer in &mut self.layers {
-            current = layer.forward(&current);
+        self.dropout_masks.clear();
+        for (index, layer) in self.layers.iter_mut().enumerate() {
+            residual::add_skips(skips, index, &skip_outputs, &mut current);
+            if let Some(checkpoints) = &mut checkpoints {
+                checkpoints.save_input(index, &current);
+            }
+            current = layer.forward_batch(&current, self.training);
+            if checkpoints.is_some() {
+                layer.clear_cache();
+            }
+            if guard && !training::all_finite(&current) {
+                return (current, Some(index));
+            }
+            
+            if apply_dropout && index < hidden_count && matches!(layer, Layer::Dense(_)) {
+                let keep = 1.0 - dropout;
+                let rng = &mut self.rng;
+                let mask = Array2::from_shape_fn(current.raw_dim(), |_| {
+                    if rng.gen::<f64>() < keep { 1.0 / keep } else { 0.0 }
+                });
+                current *= &mask;
+                self.dropout_masks.push(Some(mask));
+            } else {
+                self.dropout_masks.push(None);
+            }
+            if residual::is_source(skips, index) {
+                skip_outputs[index] = Some(current.clone());
+            }
         }
         
-        current
+        if let Some(checkpoints) = &mut checkpoints {
+            checkpoints.skip_outputs = skip_outputs;
+        }
+        self.checkpoints = checkpoints;
+        (current, None)
+    }
+    
+    /// Recompute and cache the activations of the checkpointed segment ending at `end`
+    fn recompute_segment(&mut self, end: usize) {
+        let checkpoints = self.checkpoints.as_ref().expect("checkpointed forward pass ran");
+        let skips = &self.architecture.skip_connections;
+        let start = checkpoints.segment_start(end);
+        let mut current = checkpoints.input(start).clone();
+        for index in start..=end {
+            if index > start {
+                residual::add_skips(skips, index, &checkpoints.skip_outputs, &mut current);
+            }
+            current = self.layers[index].recompute_batch(&current);
+            if let Some(Some(mask)) = self.dropout_masks.get(index) {
+                current *= mask;
+            }
+        }
     }
     
     /// Train the network on a batch of data
+    ///
+    /// Returns the mean loss. If an activation, gradient or the loss is not finite,
+    /// the step is abandoned before any parameter is updated.
     pub fn train_batch(
         &mut self,
         inputs: &Array2<f64>,
         targets: &Array2<f64>,
-    ) -> f64 {
+    ) -> Result<f64, TrainingError> {
+        self.train_step(inputs, targets).map(|(loss, _)| loss)
+    }
+    
+    /// One optimizer step, returning the mean loss and the training-mode outputs
+    ///
+    /// The step runs in training mode even if the network is in evaluation mode,
+    /// which is restored afterwards.
+    fn train_step(
+        &mut self,
+        inputs: &Array2<f64>,
+        targets: &Array2<f64>,
+    ) -> Result<(f64, Array2<f64>), TrainingError> {
+        let training = std::mem::replace(&mut self.training, true);
+        let result = self.training_mode_step(inputs, targets);
+        self.set_training(training);
+        result
+    }
+    
+    fn training_mode_step(
+        &mut self,
+        inputs: &Array2<f64>,
+        targets: &Array2<f64>,
+    ) -> Result<(f64, Array2<f64>), TrainingError> {
+        let batch_size = inputs.nrows();
+        if targets.nrows() != batch_size {
+            return Err(TrainingError::ShapeMismatch(format!(
+                "{} input samples but {} targets", batch_size, targets.nrows()
+            )));
+        }
+        if inputs.ncols() != self.architecture.input_size || targets.ncols() != self.architecture.output_size {
+            return Err(TrainingError::ShapeMismatch(format!(
+                "expected {} inputs and {} targets per sample, got {} and {}",
+                self.architecture.input_size, self.architecture.output_size, inputs.ncols(), targets.ncols()
+            )));
+        }
+        if batch_size == 0 {
+            return Ok((0.0, Array2::zeros((0, self.architecture.output_size))));
+        }
+        
+        for layer in &mut self.layers {
+            layer.zero_gradients();
+        }
+        
+        // Forward pass caches the batch activations for backward
+        let (outputs, non_finite) = self.forward_layers(inputs, true, self.checkpoint_segment);
+        if let Some(layer) = non_finite {
+            return Err(TrainingError::NonFiniteActivation { layer });
+        }
+        
+        // Per-sample loss; gradients are averaged over the batch
+        let loss_function = self.architecture.loss_function;
+        let mut total_loss = 0.0;
+        let mut gradient = Array2::zeros(outputs.raw_dim());
+        for (i, (output, target)) in outputs.outer_iter().zip(targets.outer_iter()).enumerate() {
+            let output = output.to_owned();
+            let target = target.to_owned();
+            total_loss += loss_function.loss(&output, &target);
+            gradient.row_mut(i).assign(&(loss_function.gradient(&output, &target) / batch_size as f64));
+        }
+        let loss = total_loss / batch_size as f64;
+        if !loss.is_finite() {
+            return Err(TrainingError::NonFiniteLoss { loss });
+        }
+        
+        // Backpropagate through dropout masks and layers; a skip connection's source
+        // also receives the gradient of its target's input
+        let mut skip_gradients: Vec<Option<Array2<f64>>> = vec![None; self.layers.len()];
+        for index in (0..self.layers.len()).rev() {
+            let checkpointed = self.checkpoints.is_some();
+            if self.checkpoints.as_ref().is_some_and(|checkpoints| checkpoints.ends_segment(index)) {
+                self.recompute_segment(index);
+            }
+            if let Some(skip_gradient) = skip_gradients[index].take() {
+                gradient += &skip_gradient;
+            }
+            if let Some(Some(mask)) = self.dropout_masks.get(index) {
+                gradient *= mask;
+            }
+            let layer = &mut self.layers[index];
+            gradient = layer.backward_batch(&gradient);
+            if checkpointed {
+                layer.clear_cache();
+            }
+            if !training::all_finite(&gradient) {
+                return Err(TrainingError::NonFiniteGradient { layer: index });
+            }
+            for skip in self.architecture.skip_connections.iter().filter(|skip| skip.to == index) {
+                match &mut skip_gradients[skip.from] {
+                    Some(existing) => *existing += &gradient,
+                    slot => *slot = Some(gradient.clone()),
+                }
+            }
+        }
+        self.checkpoints = None;
+        
+        // Apply the gradients uniformly across all layers
+        let mut parameters = Vec::new();
+        for (index, layer) in self.layers.iter_mut().enumerate() {
+            layer.apply_weight_decay(self.architecture.weight_decay);
+            let layer_parameters = layer.parameters();
+            if !layer_parameters.iter().all(|parameter| training::all_finite(parameter.gradients.iter())) {
+                return Err(TrainingError::NonFiniteGradient { layer: index });
+            }
+            parameters.extend(layer_parameters);
+        }
+        if let Some(max_norm) = self.architecture.grad_clip_norm {
+            training::clip_gradient_norm(&mut parameters, max_norm);
+        }
+        self.optimizer.step(self.architecture.learning_rate, &mut parameters);
+        for layer in &mut self.layers {
+            if let Layer::Dense(layer) = layer {
+                layer.apply_mask();
+            }
+        }
+        self.refresh_compact();
+        
+        Ok((loss, outputs))
+    }
+    
+    /// Summed per-sample loss over a batch
+    fn summed_loss(&self, outputs: &Array2<f64>, targets: &ArrayView2<f64>) -> f64 {
+        outputs.outer_iter().zip(targets.outer_iter())
+            .map(|(output, target)| self.architecture.loss_function.loss(&output.to_owned(), &target.to_owned()))
+            .sum()
+    }
+    
+    /// Mean loss and accuracy over a dataset, computed in evaluation mode
+    pub fn evaluate<D: Dataset + ?Sized>(&mut self, dataset: &D, batch_size: usize) -> (f64, f64) {
+        if dataset.is_empty() {
+            return (0.0, 0.0);
+        }
+        
+        let was_training = self.training;
+        self.set_training(false);
+        
         let mut total_loss = 0.0;
-        let batch_size = inputs.shape()[0];
+        let mut correct = 0.0;
+        let indices: Vec<usize> = (0..dataset.len()).collect();
+        for chunk in indices.chunks(batch_size.max(1)) {
+            let (inputs, targets) = dataset.batch(chunk);
+            let outputs = self.forward_batch(&inputs);
+            total_loss += self.summed_loss(&outputs, &targets.view());
+            correct += dataset::accuracy(&outputs, &targets.view()) * chunk.len() as f64;
+        }
         
-        // Forward pass for all inputs
-        let mut outputs = Vec::new();
-        for i in 0..batch_size {
-            let input = inputs.row(i).to_owned();
-            let output = self.forward(&input);
-            outputs.push(output);
+        self.set_training(was_training);
+        let samples = dataset.len() as f64;
+        (total_loss / samples, correct / samples)
+    }
+    
+    /// Score the evaluation-mode predictions on a dataset with each metric
+    pub fn evaluate_with<D: Dataset + ?Sized>(&self, dataset: &D, metrics: &[&dyn Metric]) -> EvaluationReport {
+        let outputs = self.predict_batch(&dataset.inputs().to_owned());
+        let targets = dataset.targets();
+        EvaluationReport {
+            values: metrics.iter()
+                .map(|metric| (metric.name(), metric.compute(&outputs.view(), &targets)))
+                .collect(),
+        }
+    }
+    
+    /// Train on a dataset in shuffled mini-batches
+    pub fn fit<D: Dataset + ?Sized>(
+        &mut self,
+        dataset: &D,
+        epochs: usize,
+        batch_size: usize,
+    ) -> Result<FitHistory, TrainingError> {
+        self.fit_with(dataset, FitOptions::new(epochs, batch_size))
+    }
+    
+    /// Train on a dataset with validation and early stopping options
+    pub fn fit_with<D: Dataset + ?Sized>(
+        &mut self,
+        dataset: &D,
+        options: FitOptions<'_>,
+    ) -> Result<FitHistory, TrainingError> {
+        let mut history = FitHistory::default();
+        if dataset.is_empty() {
+            return Ok(history);
         }
         
-        // Calculate loss and gradients
-        for i in 0..batch_size {
-            let target = targets.row(i).to_owned();
-            let output = &outputs[i];
+        let batch_size = options.batch_size.max(1);
+        let mut indices: Vec<usize> = (0..dataset.len()).collect();
+        let mut best_loss = f64::INFINITY;
+        let mut stale_epochs = 0;
+        
+        for epoch in 0..options.epochs {
+            self.set_training(true);
+            if options.shuffle {
+                indices.shuffle(&mut self.rng);
+            }
+            
+            let mut total_loss = 0.0;
+            let mut correct = 0.0;
+            for chunk in indices.chunks(batch_size) {
+                let (inputs, targets) = dataset.batch(chunk);
+                let (loss, outputs) = self.train_step(&inputs, &targets)?;
+                total_loss += loss * chunk.len() as f64;
+                correct += dataset::accuracy(&outputs, &targets.view()) * chunk.len() as f64;
+            }
+            
+            let samples = dataset.len() as f64;
+            let (validation_loss, validation_accuracy) = match options.validation {
+                Some(validation) => {
+                    let (loss, accuracy) = self.evaluate(validation, batch_size);
+                    (Some(loss), Some(accuracy))
+                }
+                None => (None, None),
+            };
+            let metrics = EpochMetrics {
+                epoch,
+                loss: total_loss / samples,
+                accuracy: correct / samples,
+                validation_loss,
+                validation_accuracy,
+            };
             
-            // Mean squared error loss
-            let loss = (&target - output).mapv(|x| x.powi(2)).sum();
-            total_loss += loss;
+            let monitored = metrics.validation_loss.unwrap_or(metrics.loss);
+            history.epochs.push(metrics);
             
-            // Calculate gradients and backpropagate
-            let mut gradient = &target - output;
-            for layer in self.layers.iter_mut().rev() {
-                gradient = layer.backward(
-                    &gradient,
-                    self.architecture.learning_rate,
-                    self.architecture.momentum,
-                );
+            if let Some(early_stopping) = options.early_stopping {
+                if monitored < best_loss - early_stopping.min_delta {
+                    best_loss = monitored;
+                    stale_epochs = 0;
+                } else {
+                    stale_epochs += 1;
+                    if stale_epochs >= early_stopping.patience {
+                        history.stopped_early = true;
+                        break;
+                    }
+                }
             }
         }
         
-        total_loss / batch_size as f64
+        if let Some(last) = history.last() {
+            info!("Training finished after {} epochs with loss {:.6}", history.epochs.len(), last.loss);
+        }
+        Ok(history)
     }
 }
 
+/// Borrowed view of a network used when saving
+#[derive(serde::Serialize)]
+struct SavedNetworkRef<'a> {
+    format_version: u32,
+    architecture: &'a NeuralArchitecture,
+    layers: &'a [Layer],
+}
+
+/// Owned network as read back from disk
+#[derive(serde::Deserialize)]
+struct SavedNetwork {
+    format_version: u32,
+    architecture: NeuralArchitecture,
+    layers: Vec<Layer>,
+}
+
+/// RNG stream of the engine's own parameters (embeddings), distinct from the networks'
+const ENGINE_RNG_STREAM: u64 = u64::MAX;
+
+/// RNG stream of the recurrent sequence encoder
+const ENCODER_RNG_STREAM: u64 = u64::MAX - 1;
+
 /// Neural foundation engine that manages multiple networks
 pub struct NeuralFoundationEngine {
     networks: Vec<NeuralNetwork>,
     memory_manager: Arc<RwLock<MemoryManager>>,
     architecture: NeuralArchitecture,
+    sequence_encoder: Option<RecurrentLayer>,
+    tokenizer: TextTokenizer,
+    embedding: Embedding,
+    rng: StdRng,
+    /// Unscheduled learning rate of each network, parallel to `networks`
+    base_learning_rates: Vec<f64>,
+    learning_rate: f64,
+    scheduler: Box<dyn LrScheduler>,
+    epoch: usize,
+    last_loss: Option<f64>,
+    /// Responses to recently seen input vectors; disabled at zero capacity. Shared with
+    /// the memory manager, which evicts from it under its `neural_cache` rules.
+    cache: Arc<Mutex<ResponseCache>>,
+    /// The memory manager's blackboard, where each response is published
+    blackboard: Option<Arc<Blackboard>>,
 }
 
 impl NeuralFoundationEngine {
     /// Create a new neural foundation engine
-    pub fn new(memory_manager: Arc<RwLock<MemoryManager>>) -> Result<Self, Box<dyn std::error::Error>> {
-        let architecture = NeuralArchitecture {
-            input_size: 1024,
-            hidden_layers: vec![512, 256, 128],
-            output_size: 256,
-            activation_function: ActivationFunction::Swish,
-            learning_rate: 0.001,
-            momentum: 0.9,
+    pub fn new(memory_manager: Arc<RwLock<MemoryManager>>) -> Result<Self, AgiError> {
+        Self::with_architectures(memory_manager, vec![NeuralArchitecture::default(); 4])
+    }
+    
+    /// Create an engine with one network per architecture
+    ///
+    /// Architectures may differ in depth, layer types and activation, but all must
+    /// share the input and output sizes so that their outputs can be synthesized. The
+    /// first architecture configures the engine itself (embeddings, scheduler, seed).
+    pub fn with_architectures(
+        memory_manager: Arc<RwLock<MemoryManager>>,
+        architectures: Vec<NeuralArchitecture>,
+    ) -> Result<Self, AgiError> {
+        let architecture = architectures.first()
+            .ok_or_else(|| AgiError::Config("At least one architecture is required".to_string()))?
+            .clone();
+        
+        let mut networks = Vec::with_capacity(architectures.len());
+        for (index, network_architecture) in architectures.into_iter().enumerate() {
+            Self::check_compatible(&architecture, &network_architecture)?;
+            let mut rng = network_architecture.rng(index as u64);
+            networks.push(NeuralNetwork::try_with_rng(network_architecture, &mut rng)?);
+        }
+        let sequence_encoder = architecture.sequence_encoder.map(|config| {
+            let mut rng = architecture.rng(ENCODER_RNG_STREAM);
+            RecurrentLayer::with_rng(config.kind, config.token_size, architecture.input_size, &mut rng)
+        });
+        
+        Ok(Self::from_parts(networks, memory_manager, architecture, sequence_encoder))
+    }
+    
+    fn check_compatible(
+        engine: &NeuralArchitecture,
+        network: &NeuralArchitecture,
+    ) -> Result<(), AgiError> {
+        if network.input_size != engine.input_size || network.output_size != engine.output_size {
+            return Err(AgiError::Config(format!(
+                "Network architecture {}->{} does not match the engine's {}->{}",
+                network.input_size, network.output_size, engine.input_size, engine.output_size
+            )));
+        }
+        Ok(())
+    }
+    
+    /// Add a network built from `architecture`, returning its index
+    pub fn add_network(&mut self, architecture: NeuralArchitecture) -> Result<usize, AgiError> {
+        Self::check_compatible(&self.architecture, &architecture)?;
+        
+        let mut rng = architecture.rng(self.networks.len() as u64);
+        let mut network = NeuralNetwork::try_with_rng(architecture, &mut rng)?;
+        let base_learning_rate = network.learning_rate();
+        network.set_learning_rate(base_learning_rate * self.schedule_factor());
+        
+        self.networks.push(network);
+        self.base_learning_rates.push(base_learning_rate);
+        self.invalidate_cache();
+        info!("Added network {} to the neural engine", self.networks.len() - 1);
+        Ok(self.networks.len() - 1)
+    }
+    
+    /// Remove and return the network at `index`
+    pub fn remove_network(&mut self, index: usize) -> Result<NeuralNetwork, AgiError> {
+        if index >= self.networks.len() {
+            return Err(AgiError::Neural(format!("No network at index {} ({} networks)", index, self.networks.len())));
+        }
+        self.base_learning_rates.remove(index);
+        self.invalidate_cache();
+        info!("Removed network {} from the neural engine", index);
+        Ok(self.networks.remove(index))
+    }
+    
+    /// Networks in the ensemble
+    pub fn networks(&self) -> &[NeuralNetwork] {
+        &self.networks
+    }
+    
+    /// Serve every network in the given precision
+    pub fn set_precision(&mut self, precision: Precision) {
+        self.architecture.precision = precision;
+        for network in &mut self.networks {
+            network.set_precision(precision);
+        }
+        self.invalidate_cache();
+    }
+    
+    /// Cache up to `capacity` responses keyed by input vector; zero disables caching
+    pub fn set_cache_capacity(&mut self, capacity: usize) {
+        self.cache_mut().set_capacity(capacity);
+    }
+    
+    /// Maximum number of cached responses
+    pub fn cache_capacity(&self) -> usize {
+        self.cache.lock().unwrap_or_else(|e| e.into_inner()).capacity()
+    }
+    
+    /// Working memory the engine publishes its responses to
+    pub fn blackboard(&self) -> Option<&Arc<Blackboard>> {
+        self.blackboard.as_ref()
+    }
+    
+    fn cache_mut(&mut self) -> std::sync::MutexGuard<'_, ResponseCache> {
+        self.cache.lock().unwrap_or_else(|e| e.into_inner())
+    }
+    
+    /// Drop cached responses after the networks change
+    fn invalidate_cache(&mut self) {
+        self.cache_mut().clear();
+    }
+    
+    fn from_parts(
+        networks: Vec<NeuralNetwork>,
+        memory_manager: Arc<RwLock<MemoryManager>>,
+        architecture: NeuralArchitecture,
+        sequence_encoder: Option<RecurrentLayer>,
+    ) -> Self {
+        let scheduler = architecture.scheduler.build();
+        let tokenizer = TextTokenizer::default();
+        let mut rng = architecture.rng(ENGINE_RNG_STREAM);
+        let embedding = Embedding::with_rng(tokenizer.vocab_size(), architecture.input_size, &mut rng);
+        let base_learning_rates = networks.iter().map(NeuralNetwork::learning_rate).collect();
+        let learning_rate = architecture.learning_rate;
+        let mut engine = Self {
+            networks,
+            memory_manager,
+            architecture,
+            sequence_encoder,
+            tokenizer,
+            embedding,
+            rng,
+            base_learning_rates,
+            learning_rate,
+            scheduler,
+            epoch: 0,
+            last_loss: None,
+            cache: Arc::new(Mutex::new(ResponseCache::new(0))),
+            blackboard: None,
         };
+        engine.apply_schedule();
+        if let Ok(manager) = engine.memory_manager.try_read() {
+            let rules = vec![EvictionRule::new(EvictionPolicy::Lru)];
+            manager.register_evictable("neural_cache", engine.cache.clone(), rules);
+            let cache = Arc::downgrade(&engine.cache);
+            manager.report_usage("neural_cache", Subsystem::Caches, move || {
+                cache.upgrade().map_or(0, |cache| cache.lock().unwrap_or_else(|e| e.into_inner()).bytes())
+            });
+            engine.blackboard = Some(manager.blackboard());
+        }
+        engine
+    }
+    
+    /// Save every network in the ensemble to a single file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), AgiError> {
+        let mut writer = BufWriter::new(std::fs::File::create(path.as_ref())?);
+        bincode::serialize_into(&mut writer, &MODEL_FORMAT_VERSION)?;
+        bincode::serialize_into(&mut writer, &self.architecture)?;
+        bincode::serialize_into(&mut writer, &(self.networks.len() as u64))?;
+        for network in &self.networks {
+            network.save_to_writer(&mut writer)?;
+        }
+        bincode::serialize_into(&mut writer, &self.sequence_encoder)?;
+        bincode::serialize_into(&mut writer, &self.tokenizer)?;
+        bincode::serialize_into(&mut writer, &self.embedding)?;
+        writer.flush()?;
+        
+        info!("Neural engine with {} networks saved to {}", self.networks.len(), path.as_ref().display());
+        Ok(())
+    }
+    
+    /// Load an engine previously written by `save`
+    pub fn load<P: AsRef<Path>>(
+        path: P,
+        memory_manager: Arc<RwLock<MemoryManager>>,
+    ) -> Result<Self, AgiError> {
+        let mut reader = BufReader::new(std::fs::File::open(path.as_ref())?);
+        let format_version: u32 = bincode::deserialize_from(&mut reader)?;
+        if format_version != MODEL_FORMAT_VERSION {
+            return Err(AgiError::Serialization(format!(
+                "Unsupported model format version {} (expected {})",
+                format_version, MODEL_FORMAT_VERSION
+            )));
+        }
+        
+        let architecture: NeuralArchitecture = bincode::deserialize_from(&mut reader)?;
+        let count: u64 = bincode::deserialize_from(&mut reader)?;
+        let networks = (0..count)
+            .map(|_| NeuralNetwork::load_from_reader(&mut reader))
+            .collect::<Result<Vec<_>, _>>()?;
+        let sequence_encoder: Option<RecurrentLayer> = bincode::deserialize_from(&mut reader)?;
+        let mut tokenizer: TextTokenizer = bincode::deserialize_from(&mut reader)?;
+        tokenizer.rebuild();
+        let embedding: Embedding = bincode::deserialize_from(&mut reader)?;
         
-        let mut networks = Vec::new();
-        for _ in 0..4 {
-            networks.push(NeuralNetwork::new(architecture.clone()));
+        info!("Neural engine with {} networks loaded from {}", networks.len(), path.as_ref().display());
+        
+        let mut engine = Self::from_parts(networks, memory_manager, architecture, sequence_encoder);
+        engine.tokenizer = tokenizer;
+        engine.embedding = embedding;
+        Ok(engine)
+    }
+    
+    /// Tokenizer used to encode text input
+    pub fn tokenizer(&self) -> &TextTokenizer {
+        &self.tokenizer
+    }
+    
+    /// Replace the tokenizer, resizing the embedding table to its vocabulary
+    ///
+    /// Embeddings of ids shared with the previous vocabulary are kept.
+    pub fn set_tokenizer(&mut self, tokenizer: TextTokenizer) {
+        self.embedding.resize(tokenizer.vocab_size(), &mut self.rng);
+        self.tokenizer = tokenizer;
+    }
+    
+    /// Learn a byte-level BPE vocabulary of `vocab_size` tokens from a corpus
+    pub fn train_tokenizer(&mut self, corpus: &[&str], vocab_size: usize) {
+        self.set_tokenizer(TextTokenizer::Bpe(BpeTokenizer::train(corpus, vocab_size)));
+        info!("Trained tokenizer with {} tokens", self.tokenizer.vocab_size());
+    }
+    
+    /// Token embedding table
+    pub fn embedding(&self) -> &Embedding {
+        &self.embedding
+    }
+    
+    /// Mean-pooled embedding of `text`, the vector `process_input` feeds the networks
+    pub fn embed_text(&self, text: &str) -> Array1<f64> {
+        self.text_to_vector(text)
+    }
+    
+    /// Token embedding table, e.g. for training
+    pub fn embedding_mut(&mut self) -> &mut Embedding {
+        &mut self.embedding
+    }
+    
+    /// Replace the learning rate scheduler and restart the schedule at epoch 0
+    pub fn set_scheduler(&mut self, scheduler: Box<dyn LrScheduler>) {
+        self.scheduler = scheduler;
+        self.epoch = 0;
+        self.last_loss = None;
+        self.apply_schedule();
+    }
+    
+    /// Number of completed training epochs
+    pub fn epoch(&self) -> usize {
+        self.epoch
+    }
+    
+    /// Scheduled learning rate of the engine architecture
+    ///
+    /// Networks with their own base rate are scaled by the same factor.
+    pub fn learning_rate(&self) -> f64 {
+        self.learning_rate
+    }
+    
+    /// Ratio of the scheduled rate to the engine's base rate
+    fn schedule_factor(&self) -> f64 {
+        if self.architecture.learning_rate > 0.0 {
+            self.learning_rate / self.architecture.learning_rate
+        } else {
+            1.0
+        }
+    }
+    
+    /// Ask the scheduler for the current epoch's rate and push it to every network
+    ///
+    /// An epoch's loss is passed only once, so that loss-driven schedulers do not see
+    /// it again when `optimize` advances the schedule without training.
+    fn apply_schedule(&mut self) -> f64 {
+        self.learning_rate = self.scheduler.learning_rate(
+            self.epoch,
+            self.architecture.learning_rate,
+            self.last_loss.take(),
+        );
+        let factor = self.schedule_factor();
+        for (network, base) in self.networks.iter_mut().zip(&self.base_learning_rates) {
+            network.set_learning_rate(base * factor);
+        }
+        self.learning_rate
+    }
+    
+    /// Train every network on the full data set for `epochs` epochs
+    ///
+    /// The scheduler is consulted after each epoch with that epoch's mean loss, and
+    /// `on_epoch_end` is called with a summary of the finished epoch.
+    pub fn train<F>(
+        &mut self,
+        inputs: &Array2<f64>,
+        targets: &Array2<f64>,
+        epochs: usize,
+        mut on_epoch_end: F,
+    ) -> Result<Vec<EpochSummary>, AgiError>
+    where
+        F: FnMut(&EpochSummary),
+    {
+        if inputs.nrows() != targets.nrows() {
+            return Err(AgiError::Neural(format!(
+                "Input and target sample counts differ ({} vs {})",
+                inputs.nrows(), targets.nrows()
+            )));
+        }
+        if inputs.ncols() != self.architecture.input_size || targets.ncols() != self.architecture.output_size {
+            return Err(AgiError::Neural("Training data does not match the engine architecture".to_string()));
+        }
+        
+        let mut history = Vec::with_capacity(epochs);
+        for _ in 0..epochs {
+            let learning_rate = self.learning_rate();
+            let losses = self.networks.par_iter_mut()
+                .map(|network| network.train_batch(inputs, targets))
+                .collect::<Result<Vec<f64>, TrainingError>>()?;
+            self.invalidate_cache();
+            let loss = losses.iter().sum::<f64>() / losses.len().max(1) as f64;
+            
+            let summary = EpochSummary {
+                epoch: self.epoch,
+                loss,
+                learning_rate,
+            };
+            on_epoch_end(&summary);
+            history.push(summary);
+            
+            self.last_loss = Some(loss);
+            self.epoch += 1;
+            self.apply_schedule();
         }
         
-        Ok(Self {
-            networks,
-            memory_manager,
-            architecture,
-        })
+        info!("Neural engine trained for {} epochs", epochs);
+        Ok(history)
+    }
+    
+    /// Fine-tune every network toward the ensemble's current consensus on `texts`
+    ///
+    /// Self-distillation needs no labels, which suits replaying past inputs offline:
+    /// networks that disagree with the ensemble are pulled toward it.
+    pub fn distill(&mut self, texts: &[String], epochs: usize) -> Result<Vec<EpochSummary>, AgiError> {
+        if texts.is_empty() || epochs == 0 {
+            return Ok(Vec::new());
+        }
+        let mut inputs = Array2::zeros((texts.len(), self.architecture.input_size));
+        for (mut row, text) in inputs.rows_mut().into_iter().zip(texts) {
+            let vector = self.text_to_vector(text);
+            if vector.len() != row.len() {
+                return Err(AgiError::Neural("Text embeddings do not match the network input size".to_string()));
+            }
+            row.assign(&vector);
+        }
+        let outputs: Vec<Array2<f64>> = self.networks.par_iter().map(|network| network.predict_batch(&inputs)).collect();
+        let mut targets = Array2::zeros((texts.len(), self.architecture.output_size));
+        for output in &outputs {
+            targets += output;
+        }
+        targets /= outputs.len().max(1) as f64;
+        self.train(&inputs, &targets, epochs, |_| {})
     }
     
     /// Process input through all neural networks in parallel
     #[instrument(skip(self, input))]
-    pub async fn process_input(&self, input: &str) -> Result<NeuralResponse, Box<dyn std::error::Error>> {
+    pub async fn process_input(&self, input: &str) -> Result<NeuralResponse, AgiError> {
         info!("Processing input through {} neural networks", self.networks.len());
         
         // Convert input to numerical representation
         let input_vector = self.text_to_vector(input);
         
+        self.respond(&input_vector, &activation_arena())
+    }
+    
+    /// Process an already embedded input, e.g. an `embed_text` vector gated by attention
+    pub async fn process_vector(&self, input_vector: &Array1<f64>) -> Result<NeuralResponse, AgiError> {
+        self.process_vector_in(input_vector, &activation_arena()).await
+    }
+    
+    /// Like `process_vector`, keeping activations in the arena of the request being served
+    #[instrument(skip(self, input_vector, arena))]
+    pub async fn process_vector_in(&self, input_vector: &Array1<f64>, arena: &Arena<'_>) -> Result<NeuralResponse, AgiError> {
+        let expected = self.embedding.dim();
+        if input_vector.len() != expected {
+            return Err(AgiError::Neural(format!("Input vector length {} does not match the embedding size {}", input_vector.len(), expected)));
+        }
+        self.respond(input_vector, arena)
+    }
+    
+    /// Process arbitrarily long text as overlapping token windows
+    ///
+    /// Each window is embedded and run through every network like `process_input`,
+    /// then the chunk responses are pooled with attention weights.
+    #[instrument(skip(self, input))]
+    pub async fn process_stream(
+        &self,
+        input: &str,
+        options: StreamOptions,
+    ) -> Result<StreamResponse, AgiError> {
+        let ids = self.tokenizer.encode(input);
+        let chunks = options.chunks(ids.len()).map_err(AgiError::Neural)?;
+        info!("Processing {} tokens as {} chunks", ids.len(), chunks.len());
+        
+        let arena = activation_arena();
+        let respond = |chunk: &std::ops::Range<usize>| self.respond(&self.embedding.mean_pool(&ids[chunk.clone()]), &arena);
+        let responses = if options.parallel {
+            chunks.par_iter().map(respond).collect::<Result<Vec<_>, _>>()
+        } else {
+            chunks.iter().map(respond).collect::<Result<Vec<_>, _>>()
+        }?;
+        
+        let outputs: Vec<Array1<f64>> = responses.iter().map(|response| response.output.clone()).collect();
+        let chunk_weights = streaming::attention_weights(&outputs);
+        Ok(StreamResponse {
+            response: streaming::pool(&responses, &chunk_weights),
+            chunks: responses.len(),
+            chunk_weights,
+        })
+    }
+    
+    /// Process a `(steps, token_size)` token sequence through the recurrent encoder
+    ///
+    /// The encoder's final hidden state replaces the fixed-size text snapshot as the
+    /// input to every network, so sequences of any length are consumed in full.
+    #[instrument(skip(self, tokens))]
+    pub async fn process_sequence(&self, tokens: &Array2<f64>) -> Result<NeuralResponse, AgiError> {
+        let encoder = self.sequence_encoder.as_ref()
+            .ok_or_else(|| AgiError::Neural("No sequence encoder configured in the architecture".to_string()))?;
+        if tokens.ncols() != encoder.input_size() {
+            return Err(AgiError::Neural(format!(
+                "Token width {} does not match the sequence encoder ({})",
+                tokens.ncols(), encoder.input_size()
+            )));
+        }
+        
+        info!("Processing sequence of {} tokens through {} neural networks", tokens.nrows(), self.networks.len());
+        
+        self.respond(&encoder.encode(tokens), &activation_arena())
+    }
+    
+    /// Recurrent encoder used by `process_sequence`, e.g. for training
+    pub fn sequence_encoder_mut(&mut self) -> Option<&mut RecurrentLayer> {
+        self.sequence_encoder.as_mut()
+    }
+    
+    /// Run an encoded input through every network and synthesize the response,
+    /// keeping activations in `arena`
+    fn respond(&self, input_vector: &Array1<f64>, arena: &Arena) -> Result<NeuralResponse, AgiError> {
+        let caching = self.cache_capacity() > 0;
+        if caching {
+            if let Some(response) = self.cache.lock().unwrap_or_else(|e| e.into_inner()).get(input_vector) {
+                self.publish(&response);
+                return Ok(response);
+            }
+        }
+        
         // Process through all networks in parallel
-        let results: Vec<_> = self.networks.par_iter().map(|network| {
-            let mut net = network.clone();
-            net.forward(&input_vector)
-        }).collect();
+        let input = input_vector.view().insert_axis(Axis(0));
+        let results: Vec<_> = self.networks.par_iter()
+            .map(|network| network.predict_in(&input, arena).row(0).to_owned())
+            .collect();
         
         // Synthesize results
         let final_output = self.synthesize_outputs(&results);
         
         // Calculate response metrics
         let response = NeuralResponse {
-            output: final_output.clone(),
+            output: self.architecture.output_head.apply(&final_output),
             activation_strength: self.calculate_activation_strength(&final_output),
             pattern_confidence: self.calculate_pattern_confidence(&results),
             coherence_score: self.calculate_coherence_score(&results),
             network_count: self.networks.len(),
+            head: self.architecture.output_head,
         };
         
         info!("Neural processing completed with {} networks", response.network_count);
         
+        if caching {
+            self.cache.lock().unwrap_or_else(|e| e.into_inner()).insert(input_vector, response.clone());
+        }
+        self.publish(&response);
         Ok(response)
     }
     
+    /// Post `response` to the blackboard, if the engine has one
+    fn publish(&self, response: &NeuralResponse) {
+        if let Some(board) = &self.blackboard {
+            board.put(blackboard::NEURAL_OUTPUT, response.output.to_vec(), "neural_engine");
+            board.put(blackboard::NEURAL_CONFIDENCE, response.pattern_confidence, "neural_engine");
+        }
+    }
+    
     /// Convert text input to numerical vector
+    ///
+    /// Tokens are embedded and mean-pooled, so the whole input contributes regardless
+    /// of its length.
     fn text_to_vector(&self, text: &str) -> Array1<f64> {
-        // Simple character-based encoding for now
-        // In production, this would use advanced tokenization
-        let mut vector = Array1::zeros(self.architecture.input_size);
-        
-        for (i, byte) in text.bytes().take(self.architecture.input_size).enumerate() {
-            vector[i] = (byte as f64) / 255.0;
-        }
-        
-        vector
+        self.embedding.mean_pool(&self.tokenizer.encode(text))
     }
     
     /// Synthesize outputs from multiple networks
@@ -391,54 +1954,62 @@ impl NeuralFoundationEngine {
     }
     
     /// Get neural engine statistics
-    pub async fn get_stats(&self) -> Result<NeuralStats, Box<dyn std::error::Error>> {
+    pub async fn get_stats(&self) -> Result<NeuralStats, AgiError> {
         let memory_stats = self.memory_manager.read().await.get_stats().await?;
+        let (cache_hits, cache_misses) = {
+            let cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+            (cache.hits(), cache.misses())
+        };
         
         Ok(NeuralStats {
             network_count: self.networks.len(),
             total_parameters: self.calculate_total_parameters(),
+            network_parameters: self.networks.iter().map(NeuralNetwork::parameter_count).collect(),
+            network_precisions: self.networks.iter().map(NeuralNetwork::precision).collect(),
+            network_sparsity: self.networks.iter().map(NeuralNetwork::sparsity).collect(),
+            inference_bytes: self.networks.iter().map(NeuralNetwork::inference_bytes).sum(),
+            cache_hits,
+            cache_misses,
+            learning_rate: self.learning_rate(),
             memory_usage: memory_stats.used_memory,
             architecture: self.architecture.clone(),
         })
     }
     
+    /// Bytes of all `f64` parameters, plus the reduced-precision copies served by `F32` networks
+    pub fn weight_bytes(&self) -> usize {
+        let compact: usize = self.networks.iter()
+            .filter(|network| network.precision() == Precision::F32)
+            .map(NeuralNetwork::inference_bytes)
+            .sum();
+        self.calculate_total_parameters() * std::mem::size_of::<f64>() + compact
+    }
+    
     /// Calculate total parameters across all networks
     fn calculate_total_parameters(&self) -> usize {
-        let mut total = 0;
-        let mut current_size = self.architecture.input_size;
-        
-        for &hidden_size in &self.architecture.hidden_layers {
-            total += current_size * hidden_size + hidden_size; // weights + biases
-            current_size = hidden_size;
-        }
-        
-        total += current_size * self.architecture.output_size + self.architecture.output_size;
-        total * self.networks.len()
+        let encoder = self.sequence_encoder.as_ref().map_or(0, RecurrentLayer::parameter_count);
+        self.networks.iter().map(NeuralNetwork::parameter_count).sum::<usize>()
+            + encoder
+            + self.embedding.parameter_count()
     }
     
     /// Optimize neural engine performance
-    pub async fn optimize(&self) -> Result<OptimizationResult, Box<dyn std::error::Error>> {
+    ///
+    /// Advances the learning rate schedule by one epoch.
+    pub async fn optimize(&mut self) -> Result<OptimizationResult, AgiError> {
         info!("Starting neural engine optimization");
         
         let start_time = std::time::Instant::now();
         
-        // Parallel optimization of all networks
-        let optimization_results: Vec<_> = self.networks.par_iter().map(|network| {
-            // Network-specific optimization logic
-            let mut net = network.clone();
-            
-            // Adaptive learning rate adjustment
-            let current_lr = self.architecture.learning_rate;
-            let new_lr = if current_lr > 0.0001 {
-                current_lr * 0.95
-            } else {
-                current_lr
-            };
-            
-            // Return optimization metrics
+        // Adaptive learning rate adjustment
+        let previous_rates: Vec<f64> = self.networks.iter().map(NeuralNetwork::learning_rate).collect();
+        self.epoch += 1;
+        self.apply_schedule();
+        
+        let optimization_results: Vec<_> = self.networks.iter().zip(previous_rates).map(|(network, previous)| {
             NetworkOptimization {
-                learning_rate_adjustment: new_lr - current_lr,
-                parameter_count: self.calculate_total_parameters() / self.networks.len(),
+                learning_rate_adjustment: network.learning_rate() - previous,
+                parameter_count: network.parameter_count(),
             }
         }).collect();
         
@@ -466,6 +2037,23 @@ pub struct NeuralResponse {
     pub pattern_confidence: f64,
     pub coherence_score: f64,
     pub network_count: usize,
+    /// Head that produced `output`
+    pub head: OutputHead,
+}
+
+impl NeuralResponse {
+    /// Draw an output index, e.g. a next token, at the given temperature
+    ///
+    /// A temperature of zero picks the highest-scoring output; `top_k` restricts the
+    /// draw to the `k` best. Returns `None` for an empty output.
+    pub fn sample(&self, temperature: f64, top_k: Option<usize>) -> Option<usize> {
+        self.sample_with_rng(temperature, top_k, &mut rand::thread_rng())
+    }
+    
+    /// `sample` with an explicit random number generator
+    pub fn sample_with_rng<R: Rng + ?Sized>(&self, temperature: f64, top_k: Option<usize>, rng: &mut R) -> Option<usize> {
+        head::sample(self.head, &self.output, temperature, top_k, rng)
+    }
 }
 
 /// Neural engine statistics
@@ -473,10 +2061,29 @@ pub struct NeuralResponse {
 pub struct NeuralStats {
     pub network_count: usize,
     pub total_parameters: usize,
+    pub network_parameters: Vec<usize>,
+    pub network_precisions: Vec<Precision>,
+    /// Fraction of pruned dense weights per network
+    pub network_sparsity: Vec<f64>,
+    /// Bytes of network weights read per inference across the ensemble
+    pub inference_bytes: usize,
+    /// Responses served from the inference cache
+    pub cache_hits: u64,
+    /// Cache lookups that ran the networks
+    pub cache_misses: u64,
+    pub learning_rate: f64,
     pub memory_usage: usize,
     pub architecture: NeuralArchitecture,
 }
 
+/// Summary of one engine training epoch
+#[derive(Debug, Clone)]
+pub struct EpochSummary {
+    pub epoch: usize,
+    pub loss: f64,
+    pub learning_rate: f64,
+}
+
 /// Network optimization result
 #[derive(Debug, Clone)]
 pub struct NetworkOptimization {
@@ -498,6 +2105,13 @@ impl Clone for NeuralNetwork {
         Self {
             layers: self.layers.clone(),
             architecture: self.architecture.clone(),
+            optimizer: self.optimizer.box_clone(),
+            training: self.training,
+            rng: self.rng.clone(),
+            dropout_masks: self.dropout_masks.clone(),
+            compact: self.compact.clone(),
+            checkpoint_segment: self.checkpoint_segment,
+            checkpoints: self.checkpoints.clone(),
         }
     }
 }
@@ -509,8 +2123,843 @@ impl Clone for NeuralLayer {
             weights: self.weights.clone(),
             biases: self.biases.clone(),
             activation: self.activation.clone(),
+            weight_gradients: self.weight_gradients.clone(),
+            bias_gradients: self.bias_gradients.clone(),
             last_input: self.last_input.clone(),
-            last_output: self.last_output.clone(),
+            last_linear: self.last_linear.clone(),
+            mask: self.mask.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    
+    fn toy_architecture(activation: ActivationFunction) -> NeuralArchitecture {
+        NeuralArchitecture {
+            input_size: 2,
+            hidden_layers: vec![8],
+            output_size: 1,
+            activation_function: activation,
+            learning_rate: 0.1,
+            momentum: 0.9,
+            ..Default::default()
+        }
+    }
+    
+    #[test]
+    fn test_activation_derivatives_match_finite_differences() {
+        let activations = [
+            ActivationFunction::Sigmoid,
+            ActivationFunction::Tanh,
+            ActivationFunction::Swish,
+            ActivationFunction::GELU,
+        ];
+        let h = 1e-6;
+        for activation in activations {
+            for &x in &[-2.0, -0.5, 0.3, 1.7] {
+                let numeric = (activation.apply(x + h) - activation.apply(x - h)) / (2.0 * h);
+                assert!((numeric - activation.derivative(x)).abs() < 1e-5, "{:?} at {}", activation, x);
+            }
+        }
+    }
+    
+    #[test]
+    fn test_backward_updates_weights() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        let mut layer = NeuralLayer::with_rng(3, 2, ActivationFunction::Tanh, &mut rng);
+        let before = layer.weights.clone();
+        
+        layer.forward(&Array1::from(vec![0.5, -0.2, 0.1]));
+        layer.backward(&Array1::from(vec![1.0, -1.0]));
+        
+        let mut optimizer = OptimizerKind::Sgd.build(0.0);
+        optimizer.step(0.1, &mut layer.parameters());
+        
+        assert!(layer.weights != before);
+    }
+    
+    #[test]
+    fn test_training_converges_on_xor() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let mut network = NeuralNetwork::with_rng(toy_architecture(ActivationFunction::Sigmoid), &mut rng);
+        network.architecture.learning_rate = 0.5;
+        
+        let inputs = Array2::from_shape_vec((4, 2), vec![0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 1.0, 1.0]).unwrap();
+        let targets = Array2::from_shape_vec((4, 1), vec![0.0, 1.0, 1.0, 0.0]).unwrap();
+        
+        let initial_loss = network.train_batch(&inputs, &targets).unwrap();
+        let mut loss = initial_loss;
+        for _ in 0..3000 {
+            loss = network.train_batch(&inputs, &targets).unwrap();
+        }
+        
+        assert!(loss < initial_loss * 0.1, "loss {} did not drop from {}", loss, initial_loss);
+    }
+    
+    #[test]
+    fn test_adam_training_reduces_loss() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+        let mut architecture = toy_architecture(ActivationFunction::Tanh);
+        architecture.optimizer = OptimizerKind::adam();
+        architecture.learning_rate = 0.01;
+        let mut network = NeuralNetwork::with_rng(architecture, &mut rng);
+        assert_eq!(network.optimizer_name(), "adam");
+        
+        let inputs = Array2::from_shape_vec((4, 2), vec![0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 1.0, 1.0]).unwrap();
+        let targets = Array2::from_shape_vec((4, 1), vec![-0.5, 0.5, 0.5, -0.5]).unwrap();
+        
+        let initial_loss = network.train_batch(&inputs, &targets).unwrap();
+        let mut loss = initial_loss;
+        for _ in 0..1000 {
+            loss = network.train_batch(&inputs, &targets).unwrap();
+        }
+        
+        assert!(loss < initial_loss * 0.1, "loss {} did not drop from {}", loss, initial_loss);
+    }
+    
+    #[test]
+    fn test_cross_entropy_classification() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(5);
+        let architecture = NeuralArchitecture {
+            input_size: 2,
+            hidden_layers: vec![8],
+            output_size: 2,
+            activation_function: ActivationFunction::Tanh,
+            learning_rate: 0.05,
+            momentum: 0.9,
+            loss_function: LossFunction::CrossEntropy,
+            ..Default::default()
+        };
+        let mut network = NeuralNetwork::with_rng(architecture, &mut rng);
+        
+        // Class 0 when the first feature dominates, class 1 otherwise
+        let inputs = Array2::from_shape_vec((4, 2), vec![1.0, 0.0, 0.8, 0.1, 0.0, 1.0, 0.2, 0.9]).unwrap();
+        let targets = Array2::from_shape_vec((4, 2), vec![1.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 1.0]).unwrap();
+        
+        for _ in 0..500 {
+            network.train_batch(&inputs, &targets).unwrap();
+        }
+        
+        for i in 0..4 {
+            let output = network.forward(&inputs.row(i).to_owned());
+            let predicted = if output[0] > output[1] { 0 } else { 1 };
+            let expected = if targets[[i, 0]] > 0.5 { 0 } else { 1 };
+            assert_eq!(predicted, expected);
+        }
+    }
+    
+    #[test]
+    fn test_network_save_load_roundtrip() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(11);
+        let mut network = NeuralNetwork::with_rng(toy_architecture(ActivationFunction::Tanh), &mut rng);
+        let input = Array1::from(vec![0.3, -0.7]);
+        let expected = network.forward(&input);
+        
+        let path = std::env::temp_dir().join(format!("agi_network_{}.bin", std::process::id()));
+        network.save(&path).unwrap();
+        let mut loaded = NeuralNetwork::load(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        
+        assert_eq!(loaded.forward(&input), expected);
+        
+        // Loaded networks remain trainable
+        let inputs = Array2::from_shape_vec((1, 2), vec![0.3, -0.7]).unwrap();
+        let targets = Array2::from_shape_vec((1, 1), vec![0.5]).unwrap();
+        assert!(loaded.train_batch(&inputs, &targets).unwrap().is_finite());
+    }
+    
+    #[test]
+    fn test_dropout_only_in_training_mode() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(13);
+        let architecture = NeuralArchitecture {
+            hidden_layers: vec![64],
+            dropout: 0.5,
+            ..toy_architecture(ActivationFunction::Tanh)
+        };
+        let mut network = NeuralNetwork::with_rng(architecture, &mut rng);
+        let input = Array1::from(vec![0.4, 0.9]);
+        
+        let first = network.forward(&input);
+        let second = network.forward(&input);
+        assert_ne!(first, second);
+        
+        network.set_training(false);
+        assert_eq!(network.forward(&input), network.forward(&input));
+        
+        // Training steps still cache activations for backward, and leave evaluation mode on
+        let inputs = input.clone().insert_axis(Axis(0));
+        let targets = Array2::from_elem((1, network.architecture().output_size), 0.5);
+        assert!(network.train_batch(&inputs, &targets).unwrap().is_finite());
+        assert!(!network.is_training());
+        assert_eq!(network.forward(&input), network.forward(&input));
+    }
+    
+    #[test]
+    fn test_weight_decay_shrinks_weights() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(17);
+        let architecture = NeuralArchitecture {
+            weight_decay: 0.5,
+            momentum: 0.0,
+            ..toy_architecture(ActivationFunction::Tanh)
+        };
+        let mut network = NeuralNetwork::with_rng(architecture, &mut rng);
+        let norm = |n: &NeuralNetwork| n.layers.iter().map(|l| match l {
+            Layer::Dense(dense) => dense.weights.mapv(|w| w * w).sum(),
+            _ => 0.0,
+        }).sum::<f64>();
+        let before = norm(&network);
+        
+        // Zero-error targets leave only the L2 penalty in the gradients
+        let inputs = Array2::zeros((1, 2));
+        let output = network.forward(&Array1::zeros(2));
+        let targets = output.insert_axis(ndarray::Axis(0));
+        network.train_batch(&inputs, &targets).unwrap();
+        
+        assert!(norm(&network) < before);
+    }
+    
+    #[test]
+    fn test_normalization_layers_train() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(11);
+        let mut architecture = toy_architecture(ActivationFunction::Tanh);
+        architecture.layers = vec![
+            LayerKind::Dense { units: 8 },
+            LayerKind::BatchNorm { momentum: 0.1 },
+            LayerKind::Dense { units: 8 },
+            LayerKind::LayerNorm,
+        ];
+        architecture.optimizer = OptimizerKind::adam();
+        architecture.learning_rate = 0.01;
+        let mut network = NeuralNetwork::with_rng(architecture, &mut rng);
+        assert_eq!(network.layers().len(), 5);
+        assert_eq!(network.parameter_count(), (2 * 8 + 8) + 16 + (8 * 8 + 8) + 16 + (8 + 1));
+        
+        let inputs = Array2::from_shape_vec((4, 2), vec![0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 1.0, 1.0]).unwrap();
+        let targets = Array2::from_shape_vec((4, 1), vec![-0.5, 0.5, 0.5, -0.5]).unwrap();
+        
+        let initial_loss = network.train_batch(&inputs, &targets).unwrap();
+        let mut loss = initial_loss;
+        for _ in 0..300 {
+            loss = network.train_batch(&inputs, &targets).unwrap();
+        }
+        assert!(loss < initial_loss * 0.5, "loss {} did not drop from {}", loss, initial_loss);
+        
+        // Evaluation uses running statistics, so single samples are well defined
+        network.set_training(false);
+        let output = network.forward(&Array1::from(vec![1.0, 0.0]));
+        assert!(output[0].is_finite());
+    }
+    
+    #[tokio::test]
+    async fn test_engine_train_follows_schedule() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(5);
+        let mut architecture = toy_architecture(ActivationFunction::Tanh);
+        architecture.scheduler = SchedulerKind::StepDecay { step_size: 2, gamma: 0.5, min_lr: 0.0 };
+        let networks = (0..2).map(|_| NeuralNetwork::with_rng(architecture.clone(), &mut rng)).collect();
+        let memory_manager = Arc::new(RwLock::new(MemoryManager::new().unwrap()));
+        let mut engine = NeuralFoundationEngine::from_parts(networks, memory_manager, architecture, None);
+        
+        let inputs = Array2::from_shape_vec((4, 2), vec![0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 1.0, 1.0]).unwrap();
+        let targets = Array2::from_shape_vec((4, 1), vec![-0.5, 0.5, 0.5, -0.5]).unwrap();
+        
+        let mut hook_calls = 0;
+        let history = engine.train(&inputs, &targets, 4, |_| hook_calls += 1).unwrap();
+        assert_eq!(hook_calls, 4);
+        let rates: Vec<f64> = history.iter().map(|epoch| epoch.learning_rate).collect();
+        assert_eq!(rates, vec![0.1, 0.1, 0.05, 0.05]);
+        assert_eq!(engine.epoch(), 4);
+        assert_eq!(engine.learning_rate(), 0.025);
+        
+        let result = engine.optimize().await.unwrap();
+        assert_eq!(result.parameter_optimizations, 2);
+        assert_eq!(engine.learning_rate(), 0.025);
+        engine.optimize().await.unwrap();
+        assert_eq!(engine.learning_rate(), 0.0125);
+        
+        // A plateau is only detected over epochs that were actually trained
+        engine.set_scheduler(SchedulerKind::ReduceOnPlateau { factor: 0.5, patience: 0, threshold: 0.0, min_lr: 0.0 }.build());
+        engine.train(&inputs, &targets, 1, |_| {}).unwrap();
+        let trained = engine.learning_rate();
+        engine.optimize().await.unwrap();
+        engine.optimize().await.unwrap();
+        assert_eq!(engine.learning_rate(), trained);
+        
+        assert!(engine.train(&inputs, &targets.slice(ndarray::s![..3, ..]).to_owned(), 1, |_| {}).is_err());
+    }
+    
+    fn xor_dataset() -> InMemoryDataset {
+        let inputs = Array2::from_shape_vec((4, 2), vec![0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 1.0, 1.0]).unwrap();
+        let targets = Array2::from_shape_vec((4, 1), vec![0.0, 1.0, 1.0, 0.0]).unwrap();
+        InMemoryDataset::new(inputs, targets).unwrap()
+    }
+    
+    #[test]
+    fn test_fit_learns_xor() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let mut architecture = toy_architecture(ActivationFunction::Sigmoid);
+        architecture.learning_rate = 0.5;
+        let mut network = NeuralNetwork::with_rng(architecture, &mut rng);
+        
+        let history = network.fit(&xor_dataset(), 3000, 2).unwrap();
+        assert_eq!(history.epochs.len(), 3000);
+        assert!(!history.stopped_early);
+        assert!(history.last().unwrap().loss < history.epochs[0].loss * 0.1);
+        
+        let (_, accuracy) = network.evaluate(&xor_dataset(), 4);
+        assert_eq!(accuracy, 1.0);
+        assert!(network.is_training());
+    }
+    
+    #[test]
+    fn test_evaluate_with_metrics() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let mut architecture = toy_architecture(ActivationFunction::Sigmoid);
+        architecture.learning_rate = 0.5;
+        let mut network = NeuralNetwork::with_rng(architecture, &mut rng);
+        network.fit(&xor_dataset(), 3000, 2).unwrap();
+        
+        let report = network.evaluate_with(
+            &xor_dataset(),
+            &[&metrics::Accuracy, &metrics::F1Score, &metrics::RocAuc, &metrics::MeanSquaredError],
+        );
+        assert_eq!(report.values.iter().map(|(name, _)| *name).collect::<Vec<_>>(), vec!["accuracy", "f1", "roc_auc", "mse"]);
+        assert_eq!(report.get("accuracy"), Some(1.0));
+        assert_eq!(report.get("f1"), Some(1.0));
+        assert_eq!(report.get("roc_auc"), Some(1.0));
+        assert!(report.get("mse").unwrap() < 0.05);
+        assert_eq!(report.get("perplexity"), None);
+    }
+    
+    #[test]
+    fn test_pruned_weights_stay_zero() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let architecture = NeuralArchitecture {
+            learning_rate: 0.5,
+            hidden_layers: vec![16],
+            optimizer: OptimizerKind::adam(),
+            ..toy_architecture(ActivationFunction::Sigmoid)
+        };
+        let mut network = NeuralNetwork::with_rng(architecture, &mut rng);
+        network.fit(&xor_dataset(), 200, 2).unwrap();
+        
+        let sparsity = network.prune(PruneStrategy::TargetSparsity(0.6));
+        assert!((sparsity - 0.6).abs() < 0.05, "sparsity {}", sparsity);
+        let before = network.predict_batch(&xor_dataset().inputs().to_owned());
+        
+        network.fit(&xor_dataset(), 50, 2).unwrap();
+        assert_eq!(network.sparsity(), sparsity);
+        for layer in network.dense_layers() {
+            let mask = layer.mask.as_ref().unwrap();
+            let mut masked = layer.weights.clone();
+            mask.apply(&mut masked);
+            assert_eq!(masked, layer.weights);
+        }
+        assert_ne!(network.predict_batch(&xor_dataset().inputs().to_owned()), before);
+        
+        // Thresholds only ever remove more weights
+        assert!(network.prune(PruneStrategy::Threshold(0.0)) >= sparsity);
+    }
+    
+    #[test]
+    fn test_int8_quantization_keeps_accuracy() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let architecture = NeuralArchitecture {
+            learning_rate: 0.5,
+            hidden_layers: vec![16],
+            ..toy_architecture(ActivationFunction::Sigmoid)
+        };
+        let mut network = NeuralNetwork::with_rng(architecture, &mut rng);
+        network.fit(&xor_dataset(), 3000, 2).unwrap();
+        
+        let quantized = network.quantize();
+        let report = quantized.report(&network, &xor_dataset());
+        assert_eq!(report.float_accuracy, 1.0);
+        assert_eq!(report.accuracy_delta(), 0.0);
+        assert!(report.max_output_error < 0.05, "{:?}", report);
+        assert!(report.compression_ratio > 3.0);
+    }
+    
+    #[test]
+    fn test_fit_stops_early() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let mut architecture = toy_architecture(ActivationFunction::Sigmoid);
+        architecture.learning_rate = 0.0;
+        let mut network = NeuralNetwork::with_rng(architecture, &mut rng);
+        
+        let dataset = xor_dataset();
+        let options = FitOptions::new(100, 4)
+            .with_early_stopping(3, 1e-9)
+            .with_validation(&dataset);
+        let history = network.fit_with(&dataset, options).unwrap();
+        assert!(history.stopped_early);
+        assert_eq!(history.epochs.len(), 4);
+        assert!(history.epochs[0].validation_loss.is_some());
+    }
+    
+    #[tokio::test]
+    async fn test_process_sequence_consumes_whole_sequence() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(17);
+        let mut architecture = toy_architecture(ActivationFunction::Tanh);
+        architecture.sequence_encoder = Some(SequenceEncoderConfig { kind: RecurrentKind::Gru, token_size: 3 });
+        let networks = vec![NeuralNetwork::with_rng(architecture.clone(), &mut rng)];
+        let encoder = RecurrentLayer::with_rng(RecurrentKind::Gru, 3, architecture.input_size, &mut rng);
+        let memory_manager = Arc::new(RwLock::new(MemoryManager::new().unwrap()));
+        let engine = NeuralFoundationEngine::from_parts(networks, memory_manager, architecture, Some(encoder));
+        
+        let short = Array2::from_shape_fn((2, 3), |(i, j)| (i + j) as f64 * 0.1);
+        let mut long = Array2::zeros((2000, 3));
+        long.slice_mut(ndarray::s![..2, ..]).assign(&short);
+        long[[1999, 0]] = 1.0;
+        
+        let short_response = engine.process_sequence(&short).await.unwrap();
+        let long_response = engine.process_sequence(&long).await.unwrap();
+        assert_ne!(short_response.output, long_response.output);
+        
+        // Repeated calls start from a fresh state
+        let again = engine.process_sequence(&short).await.unwrap();
+        assert_eq!(short_response.output, again.output);
+        
+        assert!(engine.process_sequence(&Array2::zeros((2, 4))).await.is_err());
+    }
+    
+    #[test]
+    fn test_transformer_encoder_trains() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(13);
+        let architecture = NeuralArchitecture {
+            input_size: 12,
+            output_size: 2,
+            activation_function: ActivationFunction::Tanh,
+            learning_rate: 0.01,
+            optimizer: OptimizerKind::adam(),
+            loss_function: LossFunction::CrossEntropy,
+            layers: vec![
+                LayerKind::SelfAttention { model_dim: 4, heads: 2, feed_forward_dim: 8 },
+                LayerKind::SelfAttention { model_dim: 4, heads: 2, feed_forward_dim: 8 },
+            ],
+            ..Default::default()
+        };
+        let mut network = NeuralNetwork::with_rng(architecture.clone(), &mut rng);
+        assert!(matches!(network.layers()[0], Layer::Attention(_)));
+        
+        // Mismatched attention dimensions are reported instead of panicking
+        let uneven = |model_dim, heads| NeuralArchitecture {
+            layers: vec![LayerKind::SelfAttention { model_dim, heads, feed_forward_dim: 8 }],
+            ..architecture.clone()
+        };
+        assert!(matches!(NeuralNetwork::try_new(uneven(5, 1)), Err(AgiError::Config(_))));
+        assert!(uneven(4, 3).validate().is_err());
+        let memory_manager = Arc::new(RwLock::new(MemoryManager::new().unwrap()));
+        assert!(NeuralFoundationEngine::with_architectures(memory_manager, vec![uneven(4, 0)]).is_err());
+        
+        // Classify whether the first token's features exceed the last token's
+        let inputs = Array2::from_shape_fn((16, 12), |_| rng.gen_range(-1.0..1.0));
+        let targets = Array2::from_shape_fn((16, 2), |(i, j)| {
+            let first: f64 = inputs.slice(ndarray::s![i, ..4]).sum();
+            let last: f64 = inputs.slice(ndarray::s![i, 8..]).sum();
+            if (first > last) == (j == 0) { 1.0 } else { 0.0 }
+        });
+        
+        let initial_loss = network.train_batch(&inputs, &targets).unwrap();
+        let mut loss = initial_loss;
+        for _ in 0..300 {
+            loss = network.train_batch(&inputs, &targets).unwrap();
+        }
+        assert!(loss < initial_loss * 0.5, "loss {} did not drop from {}", loss, initial_loss);
+    }
+    
+    #[test]
+    fn test_text_embedding_uses_whole_input() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(19);
+        // Wide embeddings, so random initialization cannot mask shared tokens
+        let architecture = NeuralArchitecture { input_size: 64, ..toy_architecture(ActivationFunction::Tanh) };
+        let networks = vec![NeuralNetwork::with_rng(architecture.clone(), &mut rng)];
+        let memory_manager = Arc::new(RwLock::new(MemoryManager::new().unwrap()));
+        let mut engine = NeuralFoundationEngine::from_parts(networks, memory_manager, architecture, None);
+        
+        let distance = |a: &Array1<f64>, b: &Array1<f64>| (a - b).mapv(|x| x * x).sum().sqrt();
+        
+        // Content past the old 1024-byte cutoff still changes the vector
+        let prefix = "a".repeat(1500);
+        let long_a = engine.text_to_vector(&format!("{}bbbb", prefix));
+        let long_b = engine.text_to_vector(&format!("{}cccc", prefix));
+        assert!(distance(&long_a, &long_b) > 0.0);
+        
+        engine.train_tokenizer(&["the cat sat on the mat", "the dog sat on the log", "quantum entanglement"], 300);
+        assert!(engine.tokenizer().vocab_size() > BpeTokenizer::BYTE_TOKENS);
+        // Redraw the resized table from the seeded rng so the comparison is deterministic
+        engine.embedding = Embedding::with_rng(engine.tokenizer().vocab_size(), 64, &mut rng);
+        let cat = engine.text_to_vector("the cat sat on the mat");
+        let similar = engine.text_to_vector("the cat sat on the log");
+        let different = engine.text_to_vector("quantum entanglement");
+        assert!(distance(&cat, &similar) < distance(&cat, &different));
+    }
+    
+    #[test]
+    fn test_training_reports_non_finite_values() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(23);
+        let mut network = NeuralNetwork::with_rng(toy_architecture(ActivationFunction::Tanh), &mut rng);
+        let targets = Array2::zeros((1, 1));
+        
+        let poisoned = Array2::from_shape_vec((1, 2), vec![f64::NAN, 0.0]).unwrap();
+        assert_eq!(
+            network.train_batch(&poisoned, &targets),
+            Err(TrainingError::NonFiniteActivation { layer: 0 })
+        );
+        assert!(matches!(
+            network.train_batch(&Array2::zeros((2, 2)), &targets),
+            Err(TrainingError::ShapeMismatch(_))
+        ));
+        
+        // A runaway learning rate is reported instead of silently producing NaN
+        let mut architecture = toy_architecture(ActivationFunction::Linear);
+        architecture.learning_rate = 10.0;
+        let mut network = NeuralNetwork::with_rng(architecture, &mut rng);
+        let inputs = Array2::from_shape_vec((2, 2), vec![1.0, 2.0, -1.0, 3.0]).unwrap();
+        let targets = Array2::from_shape_vec((2, 1), vec![1.0, -1.0]).unwrap();
+        let error = (0..10_000).find_map(|_| network.train_batch(&inputs, &targets).err());
+        assert!(matches!(
+            error,
+            Some(
+                TrainingError::NonFiniteActivation { .. }
+                | TrainingError::NonFiniteLoss { .. }
+                | TrainingError::NonFiniteGradient { .. }
+            )
+        ));
+    }
+    
+    #[test]
+    fn test_gradient_clipping_stabilizes_training() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(23);
+        let mut architecture = toy_architecture(ActivationFunction::Linear);
+        architecture.learning_rate = 0.1;
+        architecture.momentum = 0.0;
+        architecture.grad_clip_norm = Some(1.0);
+        let mut network = NeuralNetwork::with_rng(architecture, &mut rng);
+        let inputs = Array2::from_shape_vec((2, 2), vec![10.0, 20.0, -10.0, 30.0]).unwrap();
+        let targets = Array2::from_shape_vec((2, 1), vec![100.0, -100.0]).unwrap();
+        
+        let initial_loss = network.train_batch(&inputs, &targets).unwrap();
+        let mut loss = initial_loss;
+        for _ in 0..200 {
+            loss = network.train_batch(&inputs, &targets).unwrap();
+        }
+        assert!(loss < initial_loss);
+    }
+    
+    #[test]
+    fn test_seeded_architecture_is_reproducible() {
+        let mut architecture = toy_architecture(ActivationFunction::Tanh);
+        architecture.seed = Some(42);
+        architecture.dropout = 0.25;
+        
+        let train = |architecture: &NeuralArchitecture| {
+            let mut network = NeuralNetwork::new(architecture.clone());
+            let history = network.fit(&xor_dataset(), 20, 2).unwrap();
+            (history.last().unwrap().loss, network.forward(&Array1::from(vec![1.0, 0.0])))
+        };
+        assert_eq!(train(&architecture), train(&architecture));
+        
+        let mut first = NeuralNetwork::new(architecture.clone());
+        let mut other = NeuralNetwork::new(NeuralArchitecture { seed: Some(43), ..architecture.clone() });
+        first.set_training(false);
+        other.set_training(false);
+        let input = Array1::from(vec![1.0, 0.0]);
+        assert_ne!(first.forward(&input), other.forward(&input));
+        
+        // Streams give ensemble members distinct but reproducible generators
+        assert_eq!(architecture.rng(1).gen::<u64>(), architecture.rng(1).gen::<u64>());
+        assert_ne!(architecture.rng(1).gen::<u64>(), architecture.rng(2).gen::<u64>());
+    }
+    
+    #[tokio::test]
+    async fn test_heterogeneous_networks() {
+        let shallow = NeuralArchitecture { seed: Some(1), ..toy_architecture(ActivationFunction::Tanh) };
+        let deep = NeuralArchitecture {
+            layers: vec![LayerKind::Dense { units: 8 }, LayerKind::LayerNorm, LayerKind::Dense { units: 4 }],
+            learning_rate: 0.05,
+            ..shallow.clone()
+        };
+        let memory_manager = Arc::new(RwLock::new(MemoryManager::new().unwrap()));
+        let mut engine = NeuralFoundationEngine::with_architectures(
+            memory_manager,
+            vec![shallow.clone(), deep],
+        ).unwrap();
+        
+        let stats = engine.get_stats().await.unwrap();
+        assert_eq!(stats.network_parameters, vec![33, 81]);
+        assert_eq!(stats.total_parameters, 114 + BpeTokenizer::BYTE_TOKENS * 2);
+        
+        let mismatched = NeuralArchitecture { output_size: 3, ..shallow.clone() };
+        assert!(engine.add_network(mismatched).is_err());
+        let relu = NeuralArchitecture { activation_function: ActivationFunction::ReLU, ..shallow };
+        assert_eq!(engine.add_network(relu).unwrap(), 2);
+        
+        // Schedules scale each network's own base rate
+        engine.set_scheduler(SchedulerKind::StepDecay { step_size: 1, gamma: 0.5, min_lr: 0.0 }.build());
+        engine.optimize().await.unwrap();
+        let rates: Vec<f64> = engine.networks().iter().map(NeuralNetwork::learning_rate).collect();
+        assert!((rates[0] - 0.05).abs() < 1e-12);
+        assert!((rates[1] - 0.025).abs() < 1e-12);
+        
+        assert_eq!(engine.remove_network(1).unwrap().parameter_count(), 81);
+        assert!(engine.remove_network(5).is_err());
+        assert_eq!(engine.get_stats().await.unwrap().network_parameters, vec![33, 33]);
+    }
+    
+    #[test]
+    fn test_predict_matches_evaluation_forward() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(21);
+        let architecture = NeuralArchitecture {
+            input_size: 8,
+            output_size: 2,
+            layers: vec![
+                LayerKind::SelfAttention { model_dim: 4, heads: 2, feed_forward_dim: 6 },
+                LayerKind::Dense { units: 6 },
+                LayerKind::BatchNorm { momentum: 0.5 },
+            ],
+            dropout: 0.3,
+            ..toy_architecture(ActivationFunction::Tanh)
+        };
+        let mut network = NeuralNetwork::with_rng(architecture, &mut rng);
+        let inputs = Array2::from_shape_fn((3, 8), |(i, j)| ((i * 8 + j) as f64 * 0.3).sin());
+        let targets = Array2::from_shape_fn((3, 2), |(i, j)| ((i + j) % 2) as f64);
+        network.train_batch(&inputs, &targets).unwrap();
+        
+        let predicted = network.predict_batch(&inputs);
+        assert_eq!(network.predict(&inputs.row(1).to_owned()), predicted.row(1));
+        network.set_training(false);
+        assert_eq!(network.forward_batch(&inputs), predicted);
+    }
+    
+    #[tokio::test]
+    async fn test_f32_inference_path() {
+        let memory_manager = Arc::new(RwLock::new(MemoryManager::new().unwrap()));
+        let architecture = NeuralArchitecture { seed: Some(3), ..toy_architecture(ActivationFunction::Tanh) };
+        let mut engine = NeuralFoundationEngine::with_architectures(memory_manager, vec![architecture; 2]).unwrap();
+        let dataset = xor_dataset();
+        let (inputs, targets) = (dataset.inputs().to_owned(), dataset.targets().to_owned());
+        engine.train(&inputs, &targets, 3, |_| {}).unwrap();
+        
+        let full = engine.process_input("ab").await.unwrap().output;
+        let full_bytes = engine.get_stats().await.unwrap().inference_bytes;
+        
+        engine.set_precision(Precision::F32);
+        let reduced = engine.process_input("ab").await.unwrap().output;
+        assert!(full.iter().zip(reduced.iter()).all(|(a, b)| (a - b).abs() < 1e-5));
+        
+        let stats = engine.get_stats().await.unwrap();
+        assert_eq!(stats.network_precisions, vec![Precision::F32; 2]);
+        assert_eq!(stats.inference_bytes * 2, full_bytes);
+        
+        // The f32 copy follows further training
+        engine.train(&inputs, &targets, 3, |_| {}).unwrap();
+        let network = &engine.networks()[0];
+        let sample = inputs.row(0).to_owned();
+        let expected = network.to_compact::<f64>().predict_batch(&sample.clone().insert_axis(Axis(0)));
+        assert!((network.predict(&sample)[0] - expected[[0, 0]]).abs() < 1e-5);
+    }
+    
+    #[test]
+    fn test_skip_connection_gradients() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(31);
+        let architecture = NeuralArchitecture {
+            input_size: 3,
+            hidden_layers: vec![4, 4, 4],
+            output_size: 2,
+            learning_rate: 0.0,
+            skip_connections: vec![SkipConnection::new(0, 2), SkipConnection::new(0, 3), SkipConnection::new(1, 3)],
+            ..toy_architecture(ActivationFunction::Tanh)
+        };
+        let mut network = NeuralNetwork::with_rng(architecture, &mut rng);
+        let inputs = Array2::from_shape_fn((2, 3), |(i, j)| ((i * 3 + j) as f64 * 0.9).sin());
+        let targets = Array2::from_shape_fn((2, 2), |(i, j)| (i + j) as f64 * 0.5);
+        network.train_batch(&inputs, &targets).unwrap();
+        
+        let Layer::Dense(first) = &network.layers[0] else { unreachable!() };
+        let analytic = first.weight_gradients.clone();
+        let loss = |network: &NeuralNetwork| network.summed_loss(&network.predict_batch(&inputs), &targets.view()) / 2.0;
+        
+        let h = 1e-6;
+        for index in 0..analytic.len() {
+            let (i, j) = (index / analytic.ncols(), index % analytic.ncols());
+            let perturbed = |delta: f64| {
+                let mut copy = network.clone();
+                let Layer::Dense(first) = &mut copy.layers[0] else { unreachable!() };
+                first.weights[[i, j]] += delta;
+                loss(&copy)
+            };
+            let numeric = (perturbed(h) - perturbed(-h)) / (2.0 * h);
+            assert!((numeric - analytic[[i, j]]).abs() < 1e-6, "gradient mismatch at ({}, {})", i, j);
+        }
+        
+        let invalid = NeuralArchitecture {
+            skip_connections: vec![SkipConnection::new(0, 1)],
+            ..network.architecture().clone()
+        };
+        assert!(invalid.validate_skip_connections().is_err());
+        assert!(matches!(NeuralNetwork::try_new(invalid.clone()), Err(AgiError::Config(_))));
+        let memory_manager = Arc::new(RwLock::new(MemoryManager::new().unwrap()));
+        assert!(NeuralFoundationEngine::with_architectures(memory_manager, vec![invalid]).is_err());
+    }
+    
+    #[test]
+    fn test_skip_connections_carry_gradient_through_deep_stack() {
+        let deep = |skip_connections| NeuralArchitecture {
+            hidden_layers: vec![8; 12],
+            learning_rate: 0.0,
+            skip_connections,
+            seed: Some(5),
+            ..toy_architecture(ActivationFunction::Sigmoid)
+        };
+        let first_layer_gradient = |network: &mut NeuralNetwork| {
+            let dataset = xor_dataset();
+            network.train_batch(&dataset.inputs().to_owned(), &dataset.targets().to_owned()).unwrap();
+            let Layer::Dense(first) = &network.layers[0] else { unreachable!() };
+            first.weight_gradients.mapv(|g| g * g).sum().sqrt()
+        };
+        let residual = (0..10).map(|i| SkipConnection::new(i, i + 2)).collect();
+        let mut plain = NeuralNetwork::new(deep(Vec::new()));
+        let mut skipped = NeuralNetwork::new(deep(residual));
+        
+        let plain_gradient = first_layer_gradient(&mut plain);
+        let skipped_gradient = first_layer_gradient(&mut skipped);
+        assert!(skipped_gradient > 10.0 * plain_gradient, "{} vs {}", skipped_gradient, plain_gradient);
+        
+        // Inference copies route the same connections
+        let inputs = xor_dataset().inputs().to_owned();
+        let compact = skipped.to_compact::<f64>().predict_batch(&inputs);
+        assert!((compact - skipped.predict_batch(&inputs)).iter().all(|d| d.abs() < 1e-12));
+    }
+    
+    #[tokio::test]
+    async fn test_process_stream_consumes_all_chunks() {
+        let memory_manager = Arc::new(RwLock::new(MemoryManager::new().unwrap()));
+        let architecture = NeuralArchitecture { seed: Some(5), ..toy_architecture(ActivationFunction::Tanh) };
+        let engine = NeuralFoundationEngine::with_architectures(memory_manager, vec![architecture; 2]).unwrap();
+        
+        let text = "the quick brown fox jumps over the lazy dog ".repeat(20);
+        let tokens = engine.tokenizer().encode(&text).len();
+        let options = StreamOptions::new(16, 4);
+        let sequential = engine.process_stream(&text, options).await.unwrap();
+        assert_eq!(sequential.chunks, options.chunks(tokens).unwrap().len());
+        assert!(sequential.chunks > 1);
+        assert!((sequential.chunk_weights.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert_eq!(sequential.response.network_count, 2);
+        
+        let parallel = engine.process_stream(&text, options.with_parallel()).await.unwrap();
+        assert_eq!(parallel.response.output, sequential.response.output);
+        
+        let short = engine.process_stream("hi", options).await.unwrap();
+        assert_eq!(short.chunks, 1);
+        assert_eq!(short.response.output, engine.process_input("hi").await.unwrap().output);
+        assert!(engine.process_stream(&text, StreamOptions::new(4, 4)).await.is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_response_cache() {
+        let memory_manager = Arc::new(RwLock::new(MemoryManager::new().unwrap()));
+        let architecture = NeuralArchitecture { seed: Some(3), ..toy_architecture(ActivationFunction::Tanh) };
+        let mut engine = NeuralFoundationEngine::with_architectures(memory_manager, vec![architecture; 2]).unwrap();
+        
+        // Disabled by default
+        engine.process_input("hello").await.unwrap();
+        let stats = engine.get_stats().await.unwrap();
+        assert_eq!((stats.cache_hits, stats.cache_misses), (0, 0));
+        
+        engine.set_cache_capacity(8);
+        let first = engine.process_input("hello").await.unwrap();
+        let second = engine.process_input("hello").await.unwrap();
+        engine.process_input("world").await.unwrap();
+        assert_eq!(first.output, second.output);
+        let stats = engine.get_stats().await.unwrap();
+        assert_eq!((stats.cache_hits, stats.cache_misses), (1, 2));
+        
+        // Training invalidates cached responses
+        let dataset = xor_dataset();
+        engine.train(&dataset.inputs().to_owned(), &dataset.targets().to_owned(), 1, |_| {}).unwrap();
+        let retrained = engine.process_input("hello").await.unwrap();
+        assert_ne!(retrained.output, first.output);
+        assert_eq!(engine.get_stats().await.unwrap().cache_misses, 3);
+    }
+    
+    #[tokio::test]
+    async fn test_softmax_head_and_sampling() {
+        let memory_manager = Arc::new(RwLock::new(MemoryManager::new().unwrap()));
+        let architecture = NeuralArchitecture {
+            output_size: 4,
+            output_head: OutputHead::Softmax,
+            seed: Some(8),
+            ..toy_architecture(ActivationFunction::Tanh)
+        };
+        let engine = NeuralFoundationEngine::with_architectures(memory_manager, vec![architecture]).unwrap();
+        
+        let response = engine.process_input("next token").await.unwrap();
+        assert!((response.output.sum() - 1.0).abs() < 1e-12);
+        assert!(response.output.iter().all(|&p| p > 0.0));
+        
+        let best = response.output.iter().enumerate()
+            .fold((0, f64::MIN), |best, (i, &p)| if p > best.1 { (i, p) } else { best }).0;
+        assert_eq!(response.sample(0.0, None), Some(best));
+        assert_eq!(response.sample(2.0, Some(1)), Some(best));
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        assert!(response.sample_with_rng(1.0, None, &mut rng).unwrap() < 4);
+    }
+    
+    #[test]
+    fn test_gradient_checkpointing_matches_full_backward() {
+        let architecture = NeuralArchitecture {
+            layers: vec![
+                LayerKind::Dense { units: 8 },
+                LayerKind::BatchNorm { momentum: 0.1 },
+                LayerKind::Dense { units: 8 },
+                LayerKind::LayerNorm,
+                LayerKind::Dense { units: 8 },
+                LayerKind::Dense { units: 8 },
+            ],
+            dropout: 0.2,
+            skip_connections: vec![SkipConnection::new(0, 4), SkipConnection::new(2, 5)],
+            seed: Some(21),
+            ..toy_architecture(ActivationFunction::Tanh)
+        };
+        let mut full = NeuralNetwork::new(architecture);
+        let mut checkpointed = full.clone();
+        checkpointed.set_gradient_checkpointing(Some(2));
+        assert_eq!(checkpointed.gradient_checkpointing(), Some(2));
+        
+        let dataset = xor_dataset();
+        let (inputs, targets) = (dataset.inputs().to_owned(), dataset.targets().to_owned());
+        for _ in 0..5 {
+            let expected = full.train_batch(&inputs, &targets).unwrap();
+            assert_eq!(checkpointed.train_batch(&inputs, &targets).unwrap(), expected);
         }
+        assert_eq!(checkpointed.predict_batch(&inputs), full.predict_batch(&inputs));
+    }
+    
+    #[test]
+    fn test_arena_forward_matches_predict_batch() {
+        let architecture = NeuralArchitecture {
+            layers: vec![
+                LayerKind::Dense { units: 8 },
+                LayerKind::LayerNorm,
+                LayerKind::Dense { units: 8 },
+                LayerKind::BatchNorm { momentum: 0.1 },
+                LayerKind::Dense { units: 8 },
+            ],
+            skip_connections: vec![SkipConnection::new(0, 2), SkipConnection::new(0, 4)],
+            seed: Some(8),
+            ..toy_architecture(ActivationFunction::Tanh)
+        };
+        let network = NeuralNetwork::new(architecture);
+        let inputs = xor_dataset().inputs().to_owned();
+        
+        let arena = pool::global().arena();
+        let output = network.predict_in(&inputs.view(), &arena);
+        let expected = network.predict_batch(&inputs);
+        assert!((&output - &expected).iter().all(|d| d.abs() < 1e-12));
+        assert!(arena.chunks() > 0);
+        assert_eq!(network.predict(&inputs.row(1).to_owned()), expected.row(1));
     }
 }
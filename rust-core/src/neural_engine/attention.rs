@@ -0,0 +1,339 @@
+//! Self-Attention - Transformer encoder block
+//!
+//! Each sample's feature vector is viewed as a `(tokens, model_dim)` sequence. The
+//! block applies multi-head scaled dot-product self-attention and a position-wise
+//! feed-forward sublayer, each wrapped in a residual connection followed by layer
+//! normalization (post-norm). Position-wise projections run over all tokens of the
+//! batch at once as a `(batch * tokens, model_dim)` matrix.
+
+use ndarray::{s, Array2, ArrayBase, Axis, Data, Ix2};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::normalization::LayerNorm;
+use super::optimizer::Parameter;
+use super::{ActivationFunction, NeuralLayer};
+
+/// Values from the last training forward pass needed for backpropagation
+#[derive(Debug, Clone)]
+struct AttentionCache {
+    queries: Array2<f64>,
+    keys: Array2<f64>,
+    values: Array2<f64>,
+    /// Attention weights per (sample, head), each `(tokens, tokens)`
+    weights: Vec<Array2<f64>>,
+}
+
+/// Multi-head self-attention encoder block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransformerBlock {
+    pub(super) tokens: usize,
+    pub(super) model_dim: usize,
+    pub(super) heads: usize,
+    pub(super) query: NeuralLayer,
+    pub(super) key: NeuralLayer,
+    pub(super) value: NeuralLayer,
+    pub(super) output: NeuralLayer,
+    pub(super) attention_norm: LayerNorm,
+    pub(super) feed_forward_in: NeuralLayer,
+    pub(super) feed_forward_out: NeuralLayer,
+    pub(super) feed_forward_norm: LayerNorm,
+    #[serde(skip)]
+    cache: Option<AttentionCache>,
+}
+
+impl TransformerBlock {
+    /// Check that `input_size` splits into `model_dim`-wide tokens and `model_dim`
+    /// into `heads` heads
+    pub fn check_dimensions(input_size: usize, model_dim: usize, heads: usize) -> Result<(), String> {
+        if model_dim == 0 || !input_size.is_multiple_of(model_dim) {
+            return Err(format!("Input size {} is not a multiple of model_dim {}", input_size, model_dim));
+        }
+        if heads == 0 || !model_dim.is_multiple_of(heads) {
+            return Err(format!("model_dim {} is not a multiple of {} heads", model_dim, heads));
+        }
+        Ok(())
+    }
+
+    /// Create a block over `input_size / model_dim` tokens
+    ///
+    /// Panics if the dimensions fail `check_dimensions`; `NeuralNetwork::try_new`
+    /// checks them for every block of an architecture.
+    pub fn with_rng<R: Rng + ?Sized>(
+        input_size: usize,
+        model_dim: usize,
+        heads: usize,
+        feed_forward_dim: usize,
+        rng: &mut R,
+    ) -> Self {
+        if let Err(message) = Self::check_dimensions(input_size, model_dim, heads) {
+            panic!("{}", message);
+        }
+
+        let projection = |rng: &mut R| NeuralLayer::with_rng(model_dim, model_dim, ActivationFunction::Linear, rng);
+        Self {
+            tokens: input_size / model_dim,
+            model_dim,
+            heads,
+            query: projection(rng),
+            key: projection(rng),
+            value: projection(rng),
+            output: projection(rng),
+            attention_norm: LayerNorm::new(model_dim),
+            feed_forward_in: NeuralLayer::with_rng(model_dim, feed_forward_dim, ActivationFunction::GELU, rng),
+            feed_forward_out: NeuralLayer::with_rng(feed_forward_dim, model_dim, ActivationFunction::Linear, rng),
+            feed_forward_norm: LayerNorm::new(model_dim),
+            cache: None,
+        }
+    }
+
+    /// Width of the flattened `(tokens, model_dim)` input and output
+    pub fn features(&self) -> usize {
+        self.tokens * self.model_dim
+    }
+
+    fn head_dim(&self) -> usize {
+        self.model_dim / self.heads
+    }
+
+    fn dense_layers_mut(&mut self) -> [&mut NeuralLayer; 6] {
+        [
+            &mut self.query,
+            &mut self.key,
+            &mut self.value,
+            &mut self.output,
+            &mut self.feed_forward_in,
+            &mut self.feed_forward_out,
+        ]
+    }
+
+    /// Number of trainable parameters
+    pub fn parameter_count(&self) -> usize {
+        [&self.query, &self.key, &self.value, &self.output, &self.feed_forward_in, &self.feed_forward_out]
+            .iter()
+            .map(|layer| layer.parameter_count())
+            .sum::<usize>()
+            + 2 * self.attention_norm.features()
+            + 2 * self.feed_forward_norm.features()
+    }
+
+    fn to_sequence<S: Data<Elem = f64>>(&self, input: &ArrayBase<S, Ix2>) -> Array2<f64> {
+        input.as_standard_layout().into_owned()
+            .into_shape((input.nrows() * self.tokens, self.model_dim))
+            .expect("input width matches the block")
+    }
+
+    /// Forward pass over a `(batch, tokens * model_dim)` matrix
+    pub fn forward_batch(&mut self, input: &Array2<f64>, cache: bool) -> Array2<f64> {
+        let batch = input.nrows();
+        let sequence = self.to_sequence(input);
+
+        // Multi-head scaled dot-product attention
+        let queries = self.query.forward_batch(&sequence, cache);
+        let keys = self.key.forward_batch(&sequence, cache);
+        let values = self.value.forward_batch(&sequence, cache);
+        let (attended, weights) = self.attend(&queries, &keys, &values, batch);
+
+        let attention_output = self.output.forward_batch(&attended, cache);
+        let normalized = self.attention_norm.forward_batch(&(&sequence + &attention_output), cache);
+
+        // Position-wise feed-forward
+        let hidden = self.feed_forward_in.forward_batch(&normalized, cache);
+        let feed_forward = self.feed_forward_out.forward_batch(&hidden, cache);
+        let output = self.feed_forward_norm.forward_batch(&(&normalized + &feed_forward), cache);
+
+        self.cache = if cache {
+            Some(AttentionCache { queries, keys, values, weights })
+        } else {
+            None
+        };
+
+        output.into_shape((batch, self.features())).expect("output width matches the block")
+    }
+
+    /// Inference-only forward pass that leaves the block untouched
+    pub fn predict_batch<S: Data<Elem = f64>>(&self, input: &ArrayBase<S, Ix2>) -> Array2<f64> {
+        let batch = input.nrows();
+        let sequence = self.to_sequence(input);
+
+        let queries = self.query.predict_batch(&sequence);
+        let keys = self.key.predict_batch(&sequence);
+        let values = self.value.predict_batch(&sequence);
+        let (attended, _) = self.attend(&queries, &keys, &values, batch);
+
+        let attention_output = self.output.predict_batch(&attended);
+        let normalized = self.attention_norm.predict_batch(&(&sequence + &attention_output));
+        let feed_forward = self.feed_forward_out.predict_batch(&self.feed_forward_in.predict_batch(&normalized));
+        let output = self.feed_forward_norm.predict_batch(&(&normalized + &feed_forward));
+
+        output.into_shape((batch, self.features())).expect("output width matches the block")
+    }
+
+    /// Scaled dot-product attention of every head within each sample, returning the
+    /// attended values and the attention weights per (sample, head)
+    fn attend(
+        &self,
+        queries: &Array2<f64>,
+        keys: &Array2<f64>,
+        values: &Array2<f64>,
+        batch: usize,
+    ) -> (Array2<f64>, Vec<Array2<f64>>) {
+        let head_dim = self.head_dim();
+        let scale = 1.0 / (head_dim as f64).sqrt();
+        let mut attended = Array2::zeros(queries.raw_dim());
+        let mut weights = Vec::with_capacity(batch * self.heads);
+        for sample in 0..batch {
+            let rows = s![sample * self.tokens..(sample + 1) * self.tokens, ..];
+            for head in 0..self.heads {
+                let columns = s![.., head * head_dim..(head + 1) * head_dim];
+                let q = queries.slice(rows).slice_move(columns);
+                let k = keys.slice(rows).slice_move(columns);
+                let v = values.slice(rows).slice_move(columns);
+
+                let mut scores = q.dot(&k.t()) * scale;
+                for mut row in scores.outer_iter_mut() {
+                    let max = row.fold(f64::NEG_INFINITY, |a, &b| a.max(b));
+                    row.mapv_inplace(|x| (x - max).exp());
+                    let sum = row.sum();
+                    row /= sum;
+                }
+
+                attended.slice_mut(rows).slice_mut(columns).assign(&scores.dot(&v));
+                weights.push(scores);
+            }
+        }
+        (attended, weights)
+    }
+
+    /// Backward pass; accumulates parameter gradients and returns the input gradient
+    pub fn backward_batch(&mut self, gradient: &Array2<f64>) -> Array2<f64> {
+        let batch = gradient.nrows();
+        let gradient = gradient.as_standard_layout().into_owned()
+            .into_shape((batch * self.tokens, self.model_dim))
+            .expect("gradient width matches the block");
+
+        // Feed-forward sublayer and its residual
+        let residual = self.feed_forward_norm.backward_batch(&gradient);
+        let hidden_gradient = self.feed_forward_out.backward_batch(&residual);
+        let normalized_gradient = &residual + &self.feed_forward_in.backward_batch(&hidden_gradient);
+
+        // Attention sublayer and its residual
+        let residual = self.attention_norm.backward_batch(&normalized_gradient);
+        let attended_gradient = self.output.backward_batch(&residual);
+
+        let cache = self.cache.as_ref().expect("backward called before forward");
+        let head_dim = self.head_dim();
+        let scale = 1.0 / (head_dim as f64).sqrt();
+        let mut query_gradient = Array2::zeros(residual.raw_dim());
+        let mut key_gradient = Array2::zeros(residual.raw_dim());
+        let mut value_gradient = Array2::zeros(residual.raw_dim());
+        for sample in 0..batch {
+            let rows = s![sample * self.tokens..(sample + 1) * self.tokens, ..];
+            for head in 0..self.heads {
+                let columns = s![.., head * head_dim..(head + 1) * head_dim];
+                let weights = &cache.weights[sample * self.heads + head];
+                let q = cache.queries.slice(rows).slice_move(columns);
+                let k = cache.keys.slice(rows).slice_move(columns);
+                let v = cache.values.slice(rows).slice_move(columns);
+                let d_attended = attended_gradient.slice(rows).slice_move(columns);
+
+                let d_weights = d_attended.dot(&v.t());
+                let row_projection = (&d_weights * weights).sum_axis(Axis(1)).insert_axis(Axis(1));
+                let d_scores = (d_weights - &row_projection) * weights * scale;
+
+                query_gradient.slice_mut(rows).slice_mut(columns).assign(&d_scores.dot(&k));
+                key_gradient.slice_mut(rows).slice_mut(columns).assign(&d_scores.t().dot(&q));
+                value_gradient.slice_mut(rows).slice_mut(columns).assign(&weights.t().dot(&d_attended));
+            }
+        }
+
+        let input_gradient = residual
+            + self.query.backward_batch(&query_gradient)
+            + self.key.backward_batch(&key_gradient)
+            + self.value.backward_batch(&value_gradient);
+
+        input_gradient.into_shape((batch, self.features())).expect("gradient width matches the block")
+    }
+
+    /// Reset accumulated gradients to zero
+    pub fn zero_gradients(&mut self) {
+        for layer in self.dense_layers_mut() {
+            layer.zero_gradients();
+        }
+        self.attention_norm.zero_gradients();
+        self.feed_forward_norm.zero_gradients();
+    }
+
+    /// Drop cached activations
+    pub fn clear_cache(&mut self) {
+        for layer in self.dense_layers_mut() {
+            layer.clear_cache();
+        }
+        self.attention_norm.clear_cache();
+        self.feed_forward_norm.clear_cache();
+        self.cache = None;
+    }
+
+    /// Fold an L2 penalty into the projection weight gradients
+    pub fn apply_weight_decay(&mut self, weight_decay: f64) {
+        for layer in self.dense_layers_mut() {
+            layer.apply_weight_decay(weight_decay);
+        }
+    }
+
+    /// Trainable parameters paired with their accumulated gradients
+    pub fn parameters(&mut self) -> Vec<Parameter<'_>> {
+        let mut parameters = Vec::new();
+        parameters.extend(self.query.parameters());
+        parameters.extend(self.key.parameters());
+        parameters.extend(self.value.parameters());
+        parameters.extend(self.output.parameters());
+        parameters.extend(self.attention_norm.parameters());
+        parameters.extend(self.feed_forward_in.parameters());
+        parameters.extend(self.feed_forward_out.parameters());
+        parameters.extend(self.feed_forward_norm.parameters());
+        parameters
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_block_input_gradient() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(4);
+        let mut block = TransformerBlock::with_rng(12, 4, 2, 6, &mut rng);
+        let input = Array2::from_shape_fn((2, 12), |(i, j)| ((i * 12 + j) as f64 * 0.37).sin());
+        let output_weights = Array2::from_shape_fn((2, 12), |(i, j)| ((i + 2 * j) as f64 * 0.53).cos());
+
+        block.zero_gradients();
+        block.forward_batch(&input, true);
+        let analytic = block.backward_batch(&output_weights);
+
+        let h = 1e-6;
+        for i in 0..input.nrows() {
+            for j in 0..input.ncols() {
+                let mut plus = input.clone();
+                let mut minus = input.clone();
+                plus[[i, j]] += h;
+                minus[[i, j]] -= h;
+                let numeric = ((block.forward_batch(&plus, false) * &output_weights).sum()
+                    - (block.forward_batch(&minus, false) * &output_weights).sum()) / (2.0 * h);
+                assert!((numeric - analytic[[i, j]]).abs() < 1e-5, "gradient mismatch at ({}, {})", i, j);
+            }
+        }
+    }
+
+    #[test]
+    fn test_samples_do_not_attend_to_each_other() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(8);
+        let mut block = TransformerBlock::with_rng(8, 4, 1, 4, &mut rng);
+        let mut input = Array2::from_shape_fn((2, 8), |(i, j)| (i + j) as f64 * 0.1);
+        let first = block.forward_batch(&input, false).row(0).to_owned();
+
+        input.row_mut(1).fill(5.0);
+        assert_eq!(block.forward_batch(&input, false).row(0), first);
+    }
+}
@@ -0,0 +1,195 @@
+//! Cache - Least-recently-used cache of inference results
+//!
+//! Responses are keyed by a hash of the exact bits of the encoded input vector; the
+//! vector itself is kept alongside so that hash collisions never return another
+//! input's response. The cache must be cleared whenever the networks change.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+
+use ndarray::Array1;
+
+use super::NeuralResponse;
+use crate::memory_manager::eviction::{AccessLog, Evictable, EvictionEntry};
+
+/// Bounded map from input vectors to responses, evicting the least recently used
+#[derive(Debug, Clone, Default)]
+pub struct ResponseCache {
+    capacity: usize,
+    entries: HashMap<u64, (Array1<f64>, NeuralResponse)>,
+    /// Keys from least to most recently used
+    order: VecDeque<u64>,
+    hits: u64,
+    misses: u64,
+    access: AccessLog,
+}
+
+impl ResponseCache {
+    /// Cache holding up to `capacity` responses; zero disables caching
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity, ..Self::default() }
+    }
+
+    /// Maximum number of cached responses
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Change the capacity, evicting the oldest entries if it shrank
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.order.len() > capacity {
+            self.evict_least_recent();
+        }
+    }
+
+    /// Number of cached responses
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether nothing is cached
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Approximate bytes held by cached entries
+    pub fn bytes(&self) -> usize {
+        self.entries.values().map(|(input, response)| entry_bytes(input, response)).sum()
+    }
+
+    /// Lookups answered from the cache
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Lookups that had to run the networks
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Cached response for `input`, marking it most recently used
+    pub fn get(&mut self, input: &Array1<f64>) -> Option<NeuralResponse> {
+        let key = hash_vector(input);
+        match self.entries.get(&key) {
+            Some((cached, response)) if cached == input => {
+                let response = response.clone();
+                self.touch(key);
+                self.access.touch(key);
+                self.hits += 1;
+                Some(response)
+            }
+            _ => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Store the response for `input`, evicting the least recently used entry if full
+    pub fn insert(&mut self, input: &Array1<f64>, response: NeuralResponse) {
+        if self.capacity == 0 {
+            return;
+        }
+        let key = hash_vector(input);
+        if self.entries.insert(key, (input.clone(), response)).is_some() {
+            self.touch(key);
+            return;
+        }
+        self.order.push_back(key);
+        self.access.insert(key);
+        if self.order.len() > self.capacity {
+            self.evict_least_recent();
+        }
+    }
+
+    /// Drop every entry, keeping the counters
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+        self.access.clear();
+    }
+
+    fn touch(&mut self, key: u64) {
+        if let Some(position) = self.order.iter().position(|&k| k == key) {
+            self.order.remove(position);
+        }
+        self.order.push_back(key);
+    }
+
+    fn evict_least_recent(&mut self) {
+        if let Some(key) = self.order.pop_front() {
+            self.entries.remove(&key);
+            self.access.remove(key);
+        }
+    }
+}
+
+impl Evictable for ResponseCache {
+    fn eviction_entries(&self) -> Vec<EvictionEntry> {
+        self.entries.iter()
+            .map(|(&key, (input, response))| self.access.entry(key, entry_bytes(input, response), response.pattern_confidence))
+            .collect()
+    }
+
+    fn evict(&mut self, key: u64) -> usize {
+        let Some((input, response)) = self.entries.remove(&key) else { return 0 };
+        self.order.retain(|&k| k != key);
+        self.access.remove(key);
+        entry_bytes(&input, &response)
+    }
+}
+
+/// Approximate heap and inline size of one entry
+fn entry_bytes(input: &Array1<f64>, response: &NeuralResponse) -> usize {
+    (input.len() + response.output.len()) * std::mem::size_of::<f64>()
+        + std::mem::size_of::<(Array1<f64>, NeuralResponse)>()
+}
+
+/// Hash of the exact bit patterns of a vector
+fn hash_vector(input: &Array1<f64>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    input.len().hash(&mut hasher);
+    for value in input {
+        value.to_bits().hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(value: f64) -> NeuralResponse {
+        NeuralResponse {
+            output: Array1::from(vec![value]),
+            activation_strength: value,
+            pattern_confidence: 1.0,
+            coherence_score: 1.0,
+            network_count: 1,
+            head: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let mut cache = ResponseCache::new(2);
+        let (a, b, c) = (Array1::from(vec![1.0]), Array1::from(vec![2.0]), Array1::from(vec![3.0]));
+        cache.insert(&a, response(1.0));
+        cache.insert(&b, response(2.0));
+        assert!(cache.get(&a).is_some());
+        cache.insert(&c, response(3.0));
+
+        // `b` was least recently used
+        assert!(cache.get(&b).is_none());
+        assert_eq!(cache.get(&a).unwrap().activation_strength, 1.0);
+        assert!(cache.get(&c).is_some());
+        assert_eq!((cache.hits(), cache.misses()), (3, 1));
+
+        cache.set_capacity(0);
+        assert!(cache.is_empty());
+        cache.insert(&a, response(1.0));
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,66 @@
+//! Gradient Checkpointing - Trading recomputation for activation memory
+//!
+//! With checkpointing, the training forward pass keeps only the input of every
+//! `segment`-th layer, plus the outputs feeding skip connections, and discards the
+//! per-layer caches. Backward recomputes each segment from its checkpoint right
+//! before its gradients are needed, so only one segment's activations are held at a
+//! time. The extra cost is one additional forward pass per step.
+
+use ndarray::Array2;
+
+/// Activations kept by a checkpointed forward pass
+#[derive(Debug, Clone)]
+pub(super) struct Checkpoints {
+    segment: usize,
+    /// Input of each layer that starts a segment, after skip connections were added
+    inputs: Vec<Option<Array2<f64>>>,
+    /// Outputs of skip connection sources, indexed by layer
+    pub(super) skip_outputs: Vec<Option<Array2<f64>>>,
+}
+
+impl Checkpoints {
+    /// Empty checkpoints for `layer_count` layers split into segments of `segment`
+    pub(super) fn new(segment: usize, layer_count: usize) -> Self {
+        Self {
+            segment: segment.max(1),
+            inputs: vec![None; layer_count],
+            skip_outputs: Vec::new(),
+        }
+    }
+
+    /// Keep `input` if `layer` starts a segment
+    pub(super) fn save_input(&mut self, layer: usize, input: &Array2<f64>) {
+        if layer.is_multiple_of(self.segment) {
+            self.inputs[layer] = Some(input.clone());
+        }
+    }
+
+    /// Whether `layer` is the last of its segment, where backward must recompute
+    pub(super) fn ends_segment(&self, layer: usize) -> bool {
+        layer + 1 == self.inputs.len() || (layer + 1).is_multiple_of(self.segment)
+    }
+
+    /// First layer of the segment containing `layer`
+    pub(super) fn segment_start(&self, layer: usize) -> usize {
+        layer - layer % self.segment
+    }
+
+    /// Saved input of the segment starting at `layer`
+    pub(super) fn input(&self, layer: usize) -> &Array2<f64> {
+        self.inputs[layer].as_ref().expect("checkpoint saved for every segment start")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segments() {
+        let checkpoints = Checkpoints::new(3, 7);
+        let ends: Vec<usize> = (0..7).filter(|&layer| checkpoints.ends_segment(layer)).collect();
+        assert_eq!(ends, vec![2, 5, 6]);
+        assert_eq!(checkpoints.segment_start(5), 3);
+        assert_eq!(checkpoints.segment_start(6), 6);
+    }
+}
@@ -0,0 +1,211 @@
+//! Datasets - Sample collections and options for mini-batch training
+//!
+//! A dataset exposes its inputs and targets as `(samples, features)` views; the
+//! training loop in `NeuralNetwork::fit` shuffles sample indices and gathers batches
+//! from those views.
+
+use ndarray::{Array2, ArrayView2, Axis};
+
+/// Collection of input/target sample pairs
+pub trait Dataset {
+    /// Inputs as a `(samples, input_size)` view
+    fn inputs(&self) -> ArrayView2<'_, f64>;
+
+    /// Targets as a `(samples, output_size)` view
+    fn targets(&self) -> ArrayView2<'_, f64>;
+
+    /// Number of samples
+    fn len(&self) -> usize {
+        self.inputs().nrows()
+    }
+
+    /// Whether the dataset has no samples
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Gather the samples at `indices` into an input batch and a target batch
+    fn batch(&self, indices: &[usize]) -> (Array2<f64>, Array2<f64>) {
+        (self.inputs().select(Axis(0), indices), self.targets().select(Axis(0), indices))
+    }
+}
+
+/// Dataset held entirely in memory
+#[derive(Debug, Clone)]
+pub struct InMemoryDataset {
+    inputs: Array2<f64>,
+    targets: Array2<f64>,
+}
+
+impl InMemoryDataset {
+    /// Create a dataset from matching input and target matrices
+    pub fn new(inputs: Array2<f64>, targets: Array2<f64>) -> Result<Self, String> {
+        if inputs.nrows() != targets.nrows() {
+            return Err(format!(
+                "Input and target sample counts differ ({} vs {})",
+                inputs.nrows(),
+                targets.nrows()
+            ));
+        }
+        Ok(Self { inputs, targets })
+    }
+
+    /// Split off the last `fraction` of samples, e.g. as a validation set
+    pub fn split(&self, fraction: f64) -> (Self, Self) {
+        let held_out = ((self.len() as f64) * fraction.clamp(0.0, 1.0)).round() as usize;
+        let boundary = self.len() - held_out;
+        let (train_inputs, test_inputs) = self.inputs.view().split_at(Axis(0), boundary);
+        let (train_targets, test_targets) = self.targets.view().split_at(Axis(0), boundary);
+        (
+            Self { inputs: train_inputs.to_owned(), targets: train_targets.to_owned() },
+            Self { inputs: test_inputs.to_owned(), targets: test_targets.to_owned() },
+        )
+    }
+}
+
+impl Dataset for InMemoryDataset {
+    fn inputs(&self) -> ArrayView2<'_, f64> {
+        self.inputs.view()
+    }
+
+    fn targets(&self) -> ArrayView2<'_, f64> {
+        self.targets.view()
+    }
+}
+
+/// Stop training when the monitored loss stops improving
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EarlyStopping {
+    /// Epochs without improvement before stopping
+    pub patience: usize,
+    /// Minimum decrease that counts as an improvement
+    pub min_delta: f64,
+}
+
+/// Options for `NeuralNetwork::fit_with`
+#[derive(Clone, Copy)]
+pub struct FitOptions<'a> {
+    pub epochs: usize,
+    pub batch_size: usize,
+    /// Reshuffle samples at the start of every epoch
+    pub shuffle: bool,
+    /// Early stopping on the validation loss, or the training loss without validation data
+    pub early_stopping: Option<EarlyStopping>,
+    /// Held-out data evaluated after every epoch
+    pub validation: Option<&'a dyn Dataset>,
+}
+
+impl<'a> FitOptions<'a> {
+    /// Shuffled training without early stopping or validation
+    pub fn new(epochs: usize, batch_size: usize) -> Self {
+        Self {
+            epochs,
+            batch_size,
+            shuffle: true,
+            early_stopping: None,
+            validation: None,
+        }
+    }
+
+    /// Enable early stopping
+    pub fn with_early_stopping(mut self, patience: usize, min_delta: f64) -> Self {
+        self.early_stopping = Some(EarlyStopping { patience, min_delta });
+        self
+    }
+
+    /// Evaluate on a validation set after every epoch
+    pub fn with_validation(mut self, validation: &'a dyn Dataset) -> Self {
+        self.validation = Some(validation);
+        self
+    }
+}
+
+/// Metrics recorded for one training epoch
+#[derive(Debug, Clone, PartialEq)]
+pub struct EpochMetrics {
+    pub epoch: usize,
+    pub loss: f64,
+    pub accuracy: f64,
+    pub validation_loss: Option<f64>,
+    pub validation_accuracy: Option<f64>,
+}
+
+/// Result of `NeuralNetwork::fit`
+#[derive(Debug, Clone, Default)]
+pub struct FitHistory {
+    pub epochs: Vec<EpochMetrics>,
+    pub stopped_early: bool,
+}
+
+impl FitHistory {
+    /// Metrics of the last completed epoch
+    pub fn last(&self) -> Option<&EpochMetrics> {
+        self.epochs.last()
+    }
+}
+
+/// Fraction of samples predicted correctly
+///
+/// Single-output networks are scored as binary classifiers thresholded at 0.5;
+/// multi-output networks compare the arg-max of outputs and targets.
+pub fn accuracy(outputs: &Array2<f64>, targets: &ArrayView2<'_, f64>) -> f64 {
+    if outputs.nrows() == 0 {
+        return 0.0;
+    }
+
+    let argmax = |row: ndarray::ArrayView1<'_, f64>| {
+        row.iter()
+            .enumerate()
+            .fold((0, f64::NEG_INFINITY), |best, (i, &v)| if v > best.1 { (i, v) } else { best })
+            .0
+    };
+
+    let correct = outputs.outer_iter().zip(targets.outer_iter())
+        .filter(|(output, target)| {
+            if output.len() == 1 {
+                (output[0] >= 0.5) == (target[0] >= 0.5)
+            } else {
+                argmax(output.view()) == argmax(target.view())
+            }
+        })
+        .count();
+
+    correct as f64 / outputs.nrows() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dataset_rejects_mismatched_samples() {
+        assert!(InMemoryDataset::new(Array2::zeros((3, 2)), Array2::zeros((2, 1))).is_err());
+    }
+
+    #[test]
+    fn test_split_and_batch() {
+        let inputs = Array2::from_shape_fn((10, 2), |(i, j)| (i * 2 + j) as f64);
+        let targets = Array2::from_shape_fn((10, 1), |(i, _)| i as f64);
+        let dataset = InMemoryDataset::new(inputs, targets).unwrap();
+
+        let (train, validation) = dataset.split(0.2);
+        assert_eq!(train.len(), 8);
+        assert_eq!(validation.len(), 2);
+        assert_eq!(validation.targets()[[0, 0]], 8.0);
+
+        let (batch_inputs, batch_targets) = dataset.batch(&[3, 1]);
+        assert_eq!(batch_inputs.row(0).to_vec(), vec![6.0, 7.0]);
+        assert_eq!(batch_targets.column(0).to_vec(), vec![3.0, 1.0]);
+    }
+
+    #[test]
+    fn test_accuracy() {
+        let outputs = Array2::from_shape_vec((2, 2), vec![0.9, 0.1, 0.3, 0.7]).unwrap();
+        let targets = Array2::from_shape_vec((2, 2), vec![1.0, 0.0, 1.0, 0.0]).unwrap();
+        assert_eq!(accuracy(&outputs, &targets.view()), 0.5);
+
+        let outputs = Array2::from_shape_vec((3, 1), vec![0.8, 0.2, 0.6]).unwrap();
+        let targets = Array2::from_shape_vec((3, 1), vec![1.0, 0.0, 0.0]).unwrap();
+        assert!((accuracy(&outputs, &targets.view()) - 2.0 / 3.0).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,164 @@
+//! Embedding - Learned token embedding table
+//!
+//! Maps token ids to dense vectors. Sequences can be looked up as `(steps, dim)`
+//! matrices for recurrent or attention layers, or mean-pooled into one vector of any
+//! input length for the feedforward networks.
+
+use ndarray::{Array1, Array2};
+use ndarray_rand::rand_distr::StandardNormal;
+use ndarray_rand::RandomExt;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::optimizer::Parameter;
+
+/// Token embedding table of shape `(vocab_size, dim)`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Embedding {
+    table: Array2<f64>,
+    #[serde(skip)]
+    gradients: Array2<f64>,
+}
+
+impl Embedding {
+    /// Create an embedding table drawing initial vectors from the given RNG
+    pub fn with_rng<R: Rng + ?Sized>(vocab_size: usize, dim: usize, rng: &mut R) -> Self {
+        let scale = 1.0 / (dim.max(1) as f64).sqrt();
+        Self {
+            table: Array2::random_using((vocab_size, dim), StandardNormal, rng) * scale,
+            gradients: Array2::zeros((vocab_size, dim)),
+        }
+    }
+
+    /// Number of embedded tokens
+    pub fn vocab_size(&self) -> usize {
+        self.table.nrows()
+    }
+
+    /// Width of each embedding vector
+    pub fn dim(&self) -> usize {
+        self.table.ncols()
+    }
+
+    /// Grow or shrink the table to `vocab_size`, keeping existing rows
+    pub fn resize<R: Rng + ?Sized>(&mut self, vocab_size: usize, rng: &mut R) {
+        let mut resized = Self::with_rng(vocab_size, self.dim(), rng);
+        let kept = vocab_size.min(self.vocab_size());
+        resized.table.slice_mut(ndarray::s![..kept, ..])
+            .assign(&self.table.slice(ndarray::s![..kept, ..]));
+        *self = resized;
+    }
+
+    /// Embedding row for an id; out-of-range ids map to a zero vector
+    fn row(&self, id: u32) -> Option<ndarray::ArrayView1<'_, f64>> {
+        ((id as usize) < self.vocab_size()).then(|| self.table.row(id as usize))
+    }
+
+    /// Embed a token sequence as a `(steps, dim)` matrix
+    pub fn lookup(&self, ids: &[u32]) -> Array2<f64> {
+        let mut output = Array2::zeros((ids.len(), self.dim()));
+        for (mut target, &id) in output.outer_iter_mut().zip(ids) {
+            if let Some(row) = self.row(id) {
+                target.assign(&row);
+            }
+        }
+        output
+    }
+
+    /// Mean of the embeddings of a token sequence; zero for an empty sequence
+    pub fn mean_pool(&self, ids: &[u32]) -> Array1<f64> {
+        let mut pooled = Array1::zeros(self.dim());
+        for row in ids.iter().filter_map(|&id| self.row(id)) {
+            pooled += &row;
+        }
+        if !ids.is_empty() {
+            pooled /= ids.len() as f64;
+        }
+        pooled
+    }
+
+    /// Accumulate `(steps, dim)` gradients for the rows used by `lookup`
+    pub fn backward_lookup(&mut self, ids: &[u32], gradients: &Array2<f64>) {
+        self.ensure_gradients();
+        for (&id, gradient) in ids.iter().zip(gradients.outer_iter()) {
+            if (id as usize) < self.vocab_size() {
+                let mut row = self.gradients.row_mut(id as usize);
+                row += &gradient;
+            }
+        }
+    }
+
+    /// Accumulate the gradient of a `mean_pool` output
+    pub fn backward_mean_pool(&mut self, ids: &[u32], gradient: &Array1<f64>) {
+        self.ensure_gradients();
+        let share = gradient / ids.len().max(1) as f64;
+        for &id in ids {
+            if (id as usize) < self.vocab_size() {
+                let mut row = self.gradients.row_mut(id as usize);
+                row += &share;
+            }
+        }
+    }
+
+    fn ensure_gradients(&mut self) {
+        if self.gradients.raw_dim() != self.table.raw_dim() {
+            self.gradients = Array2::zeros(self.table.raw_dim());
+        }
+    }
+
+    /// Reset accumulated gradients to zero
+    pub fn zero_gradients(&mut self) {
+        self.ensure_gradients();
+        self.gradients.fill(0.0);
+    }
+
+    /// Trainable parameters paired with their accumulated gradients
+    pub fn parameters(&mut self) -> Vec<Parameter<'_>> {
+        self.ensure_gradients();
+        vec![Parameter {
+            values: self.table.as_slice_mut().expect("table is contiguous"),
+            gradients: self.gradients.as_slice_mut().expect("gradients are contiguous"),
+        }]
+    }
+
+    /// Number of trainable parameters
+    pub fn parameter_count(&self) -> usize {
+        self.table.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_lookup_and_mean_pool() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(2);
+        let embedding = Embedding::with_rng(10, 4, &mut rng);
+
+        let sequence = embedding.lookup(&[1, 3, 99]);
+        assert_eq!(sequence.dim(), (3, 4));
+        assert_eq!(sequence.row(0), embedding.table.row(1));
+        assert!(sequence.row(2).iter().all(|&x| x == 0.0));
+
+        let pooled = embedding.mean_pool(&[1, 3]);
+        let expected = (&embedding.table.row(1) + &embedding.table.row(3)) / 2.0;
+        assert_eq!(pooled, expected);
+        assert_eq!(embedding.mean_pool(&[]), Array1::<f64>::zeros(4));
+    }
+
+    #[test]
+    fn test_resize_keeps_rows_and_accumulates_gradients() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(2);
+        let mut embedding = Embedding::with_rng(4, 3, &mut rng);
+        let row = embedding.table.row(2).to_owned();
+        embedding.resize(6, &mut rng);
+        assert_eq!(embedding.vocab_size(), 6);
+        assert_eq!(embedding.table.row(2), row);
+
+        embedding.zero_gradients();
+        embedding.backward_mean_pool(&[2, 2], &Array1::from(vec![1.0, 2.0, 3.0]));
+        assert_eq!(embedding.gradients.row(2).to_vec(), vec![1.0, 2.0, 3.0]);
+    }
+}
@@ -0,0 +1,96 @@
+//! Output Head - Interpretation of the engine's synthesized output
+//!
+//! The head is applied to the ensemble output after synthesis and does not change
+//! how networks train. Pair `Softmax` with `LossFunction::CrossEntropy`, which already
+//! treats network outputs as logits, so that the head yields the distribution the
+//! loss optimizes.
+
+use ndarray::Array1;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::loss::softmax;
+
+/// Transformation from raw network outputs to the response output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum OutputHead {
+    /// Raw activations, unchanged
+    #[default]
+    Linear,
+    /// Probability distribution over the outputs
+    Softmax,
+    /// Independent probability per output
+    Sigmoid,
+}
+
+impl OutputHead {
+    /// Apply the head to a synthesized output
+    pub fn apply(&self, output: &Array1<f64>) -> Array1<f64> {
+        match self {
+            Self::Linear => output.clone(),
+            Self::Softmax => softmax(output),
+            Self::Sigmoid => output.mapv(|x| 1.0 / (1.0 + (-x).exp())),
+        }
+    }
+
+    /// Unnormalized log-probabilities of an output produced by this head
+    fn logits(&self, output: &Array1<f64>) -> Array1<f64> {
+        match self {
+            Self::Linear => output.clone(),
+            Self::Softmax | Self::Sigmoid => output.mapv(|p| p.max(f64::MIN_POSITIVE).ln()),
+        }
+    }
+}
+
+/// Draw an output index from `output` scaled by `temperature`
+///
+/// A temperature of zero or below picks the arg-max. With `top_k`, only the `k`
+/// highest-scoring indices can be drawn.
+pub(super) fn sample<R: Rng + ?Sized>(
+    head: OutputHead,
+    output: &Array1<f64>,
+    temperature: f64,
+    top_k: Option<usize>,
+    rng: &mut R,
+) -> Option<usize> {
+    let logits = head.logits(output);
+    let mut ranked: Vec<usize> = (0..logits.len()).collect();
+    ranked.sort_by(|&a, &b| logits[b].total_cmp(&logits[a]));
+    ranked.truncate(top_k.unwrap_or(ranked.len()).max(1));
+
+    let &best = ranked.first()?;
+    if temperature <= 0.0 {
+        return Some(best);
+    }
+    let weights: Vec<f64> = ranked.iter().map(|&i| ((logits[i] - logits[best]) / temperature).exp()).collect();
+    let mut remaining = rng.gen::<f64>() * weights.iter().sum::<f64>();
+    for (&index, weight) in ranked.iter().zip(&weights) {
+        remaining -= weight;
+        if remaining <= 0.0 {
+            return Some(index);
+        }
+    }
+    ranked.last().copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_heads_and_sampling() {
+        let output = Array1::from(vec![2.0, 0.5, -1.0, 1.0]);
+        let probabilities = OutputHead::Softmax.apply(&output);
+        assert!((probabilities.sum() - 1.0).abs() < 1e-12);
+        assert!(OutputHead::Sigmoid.apply(&output).iter().all(|&p| p > 0.0 && p < 1.0));
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(4);
+        assert_eq!(sample(OutputHead::Softmax, &probabilities, 0.0, None, &mut rng), Some(0));
+        for _ in 0..50 {
+            let index = sample(OutputHead::Softmax, &probabilities, 5.0, Some(2), &mut rng).unwrap();
+            assert!(index == 0 || index == 3);
+        }
+        assert_eq!(sample(OutputHead::Linear, &Array1::zeros(0), 1.0, None, &mut rng), None);
+    }
+}
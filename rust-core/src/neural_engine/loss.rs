@@ -0,0 +1,131 @@
+//! Loss Functions - Training objectives for neural networks
+//!
+//! Each loss provides a forward value for a single sample and the gradient with
+//! respect to the network output, which seeds backpropagation.
+
+use ndarray::Array1;
+use serde::{Deserialize, Serialize};
+
+/// Smallest probability used inside logarithms
+const PROBABILITY_EPSILON: f64 = 1e-12;
+
+/// Loss function selection for a `NeuralArchitecture`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+pub enum LossFunction {
+    /// Mean squared error
+    #[default]
+    MeanSquaredError,
+    /// Mean absolute error
+    MeanAbsoluteError,
+    /// Categorical cross-entropy; outputs are treated as logits and passed through softmax
+    CrossEntropy,
+    /// Huber loss, quadratic within `delta` and linear outside
+    Huber { delta: f64 },
+    /// Binary cross-entropy; outputs must be probabilities in (0, 1)
+    BinaryCrossEntropy,
+}
+
+impl LossFunction {
+    /// Loss value for one sample
+    pub fn loss(&self, output: &Array1<f64>, target: &Array1<f64>) -> f64 {
+        let n = output.len().max(1) as f64;
+
+        match *self {
+            Self::MeanSquaredError => {
+                output.iter().zip(target.iter()).map(|(o, t)| (o - t).powi(2)).sum::<f64>() / n
+            }
+            Self::MeanAbsoluteError => {
+                output.iter().zip(target.iter()).map(|(o, t)| (o - t).abs()).sum::<f64>() / n
+            }
+            Self::CrossEntropy => {
+                let probabilities = softmax(output);
+                -probabilities.iter().zip(target.iter())
+                    .map(|(p, t)| t * p.max(PROBABILITY_EPSILON).ln())
+                    .sum::<f64>()
+            }
+            Self::Huber { delta } => {
+                output.iter().zip(target.iter()).map(|(o, t)| {
+                    let error = (o - t).abs();
+                    if error <= delta {
+                        0.5 * error * error
+                    } else {
+                        delta * (error - 0.5 * delta)
+                    }
+                }).sum::<f64>() / n
+            }
+            Self::BinaryCrossEntropy => {
+                -output.iter().zip(target.iter()).map(|(o, t)| {
+                    let p = o.clamp(PROBABILITY_EPSILON, 1.0 - PROBABILITY_EPSILON);
+                    t * p.ln() + (1.0 - t) * (1.0 - p).ln()
+                }).sum::<f64>() / n
+            }
+        }
+    }
+
+    /// Gradient of the loss with respect to the network output
+    pub fn gradient(&self, output: &Array1<f64>, target: &Array1<f64>) -> Array1<f64> {
+        let n = output.len().max(1) as f64;
+
+        match *self {
+            Self::MeanSquaredError => (output - target) * (2.0 / n),
+            Self::MeanAbsoluteError => (output - target).mapv(|d| {
+                if d > 0.0 { 1.0 / n } else if d < 0.0 { -1.0 / n } else { 0.0 }
+            }),
+            Self::CrossEntropy => softmax(output) - target,
+            Self::Huber { delta } => (output - target).mapv(|d| d.clamp(-delta, delta) / n),
+            Self::BinaryCrossEntropy => {
+                let mut gradient = Array1::zeros(output.len());
+                for (i, (o, t)) in output.iter().zip(target.iter()).enumerate() {
+                    let p = o.clamp(PROBABILITY_EPSILON, 1.0 - PROBABILITY_EPSILON);
+                    gradient[i] = (p - t) / (p * (1.0 - p)) / n;
+                }
+                gradient
+            }
+        }
+    }
+}
+
+/// Numerically stable softmax
+pub fn softmax(values: &Array1<f64>) -> Array1<f64> {
+    let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let exponentials = values.mapv(|v| (v - max).exp());
+    let sum = exponentials.sum();
+    exponentials / sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gradients_match_finite_differences() {
+        let losses = [
+            LossFunction::MeanSquaredError,
+            LossFunction::CrossEntropy,
+            LossFunction::Huber { delta: 0.5 },
+            LossFunction::BinaryCrossEntropy,
+        ];
+        let output = Array1::from(vec![0.2, 0.7, 0.4]);
+        let target = Array1::from(vec![0.0, 1.0, 0.0]);
+        let h = 1e-6;
+
+        for loss in losses {
+            let analytic = loss.gradient(&output, &target);
+            for i in 0..output.len() {
+                let mut plus = output.clone();
+                let mut minus = output.clone();
+                plus[i] += h;
+                minus[i] -= h;
+                let numeric = (loss.loss(&plus, &target) - loss.loss(&minus, &target)) / (2.0 * h);
+                assert!((numeric - analytic[i]).abs() < 1e-5, "{:?} component {}", loss, i);
+            }
+        }
+    }
+
+    #[test]
+    fn test_softmax_sums_to_one() {
+        let probabilities = softmax(&Array1::from(vec![1000.0, 1001.0, 999.0]));
+        assert!((probabilities.sum() - 1.0).abs() < 1e-12);
+        assert!(probabilities[1] > probabilities[0]);
+    }
+}
@@ -0,0 +1,298 @@
+//! Metrics - Evaluation metrics for classification and regression
+//!
+//! Metrics compare `(samples, outputs)` predictions with targets of the same shape.
+//! Classification metrics read single-output networks as binary classifiers
+//! thresholded at 0.5 and multi-output networks by arg-max, matching
+//! `dataset::accuracy`; multi-class scores are macro-averaged over classes.
+
+use ndarray::{ArrayView1, ArrayView2};
+
+/// Smallest probability used when taking logarithms
+const PROBABILITY_FLOOR: f64 = 1e-12;
+
+/// Score computed from network outputs and targets
+pub trait Metric: Send + Sync {
+    /// Name used in reports
+    fn name(&self) -> &'static str;
+
+    /// Score of `outputs` against `targets`
+    fn compute(&self, outputs: &ArrayView2<'_, f64>, targets: &ArrayView2<'_, f64>) -> f64;
+}
+
+fn argmax(row: ArrayView1<'_, f64>) -> usize {
+    row.iter()
+        .enumerate()
+        .fold((0, f64::NEG_INFINITY), |best, (i, &v)| if v > best.1 { (i, v) } else { best })
+        .0
+}
+
+/// Class label of an output or target row
+fn label(row: ArrayView1<'_, f64>) -> usize {
+    if row.len() == 1 {
+        usize::from(row[0] >= 0.5)
+    } else {
+        argmax(row)
+    }
+}
+
+/// Per-class true positive, false positive and false negative counts
+fn confusion(outputs: &ArrayView2<'_, f64>, targets: &ArrayView2<'_, f64>) -> Vec<(usize, usize, usize)> {
+    let classes = outputs.ncols().max(2);
+    let mut counts = vec![(0, 0, 0); classes];
+    for (output, target) in outputs.outer_iter().zip(targets.outer_iter()) {
+        let (predicted, actual) = (label(output), label(target));
+        if predicted == actual {
+            counts[actual].0 += 1;
+        } else {
+            counts[predicted].1 += 1;
+            counts[actual].2 += 1;
+        }
+    }
+    counts
+}
+
+/// Classes a score is reported for: the positive class of a binary classifier, or
+/// every class of a multi-class one
+fn scored_classes(outputs: &ArrayView2<'_, f64>) -> std::ops::Range<usize> {
+    if outputs.ncols() == 1 { 1..2 } else { 0..outputs.ncols() }
+}
+
+fn ratio(numerator: usize, denominator: usize) -> f64 {
+    if denominator == 0 { 0.0 } else { numerator as f64 / denominator as f64 }
+}
+
+fn macro_average(outputs: &ArrayView2<'_, f64>, targets: &ArrayView2<'_, f64>, score: impl Fn((usize, usize, usize)) -> f64) -> f64 {
+    let counts = confusion(outputs, targets);
+    let classes = scored_classes(outputs);
+    let count = classes.len();
+    classes.map(|class| score(counts[class])).sum::<f64>() / count as f64
+}
+
+/// Fraction of samples classified correctly
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Accuracy;
+
+impl Metric for Accuracy {
+    fn name(&self) -> &'static str {
+        "accuracy"
+    }
+
+    fn compute(&self, outputs: &ArrayView2<'_, f64>, targets: &ArrayView2<'_, f64>) -> f64 {
+        super::dataset::accuracy(&outputs.to_owned(), targets)
+    }
+}
+
+/// Fraction of predicted positives that are correct
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Precision;
+
+impl Metric for Precision {
+    fn name(&self) -> &'static str {
+        "precision"
+    }
+
+    fn compute(&self, outputs: &ArrayView2<'_, f64>, targets: &ArrayView2<'_, f64>) -> f64 {
+        macro_average(outputs, targets, |(tp, fp, _)| ratio(tp, tp + fp))
+    }
+}
+
+/// Fraction of actual positives that are found
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Recall;
+
+impl Metric for Recall {
+    fn name(&self) -> &'static str {
+        "recall"
+    }
+
+    fn compute(&self, outputs: &ArrayView2<'_, f64>, targets: &ArrayView2<'_, f64>) -> f64 {
+        macro_average(outputs, targets, |(tp, _, fn_)| ratio(tp, tp + fn_))
+    }
+}
+
+/// Harmonic mean of precision and recall
+#[derive(Debug, Clone, Copy, Default)]
+pub struct F1Score;
+
+impl Metric for F1Score {
+    fn name(&self) -> &'static str {
+        "f1"
+    }
+
+    fn compute(&self, outputs: &ArrayView2<'_, f64>, targets: &ArrayView2<'_, f64>) -> f64 {
+        macro_average(outputs, targets, |(tp, fp, fn_)| ratio(2 * tp, 2 * tp + fp + fn_))
+    }
+}
+
+/// Area under the ROC curve, one-vs-rest for multi-class outputs
+///
+/// Computed as the probability that a random positive scores above a random negative,
+/// with ties counting half. Classes without both positives and negatives are skipped.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RocAuc;
+
+impl RocAuc {
+    fn binary(scores: ArrayView1<'_, f64>, positives: &[bool]) -> Option<f64> {
+        let mut ranked: Vec<(f64, bool)> = scores.iter().copied().zip(positives.iter().copied()).collect();
+        ranked.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        // Sum of positive ranks, averaging the ranks of tied scores
+        let mut positive_rank_sum = 0.0;
+        let mut start = 0;
+        while start < ranked.len() {
+            let end = ranked[start..].iter().position(|r| r.0 != ranked[start].0).map_or(ranked.len(), |i| start + i);
+            let average_rank = (start + end + 1) as f64 / 2.0;
+            positive_rank_sum += average_rank * ranked[start..end].iter().filter(|r| r.1).count() as f64;
+            start = end;
+        }
+
+        let positive = positives.iter().filter(|&&p| p).count() as f64;
+        let negative = positives.len() as f64 - positive;
+        (positive > 0.0 && negative > 0.0)
+            .then(|| (positive_rank_sum - positive * (positive + 1.0) / 2.0) / (positive * negative))
+    }
+}
+
+impl Metric for RocAuc {
+    fn name(&self) -> &'static str {
+        "roc_auc"
+    }
+
+    fn compute(&self, outputs: &ArrayView2<'_, f64>, targets: &ArrayView2<'_, f64>) -> f64 {
+        let scores: Vec<f64> = if outputs.ncols() == 1 {
+            let positives: Vec<bool> = targets.column(0).iter().map(|&t| t >= 0.5).collect();
+            Self::binary(outputs.column(0), &positives).into_iter().collect()
+        } else {
+            let labels: Vec<usize> = targets.outer_iter().map(label).collect();
+            (0..outputs.ncols())
+                .filter_map(|class| {
+                    let positives: Vec<bool> = labels.iter().map(|&l| l == class).collect();
+                    Self::binary(outputs.column(class), &positives)
+                })
+                .collect()
+        };
+        if scores.is_empty() { 0.5 } else { scores.iter().sum::<f64>() / scores.len() as f64 }
+    }
+}
+
+/// Exponential of the mean negative log-likelihood of the target class
+///
+/// Multi-output rows are normalized to sum to one; a single output is read as the
+/// probability of the positive class.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Perplexity;
+
+impl Metric for Perplexity {
+    fn name(&self) -> &'static str {
+        "perplexity"
+    }
+
+    fn compute(&self, outputs: &ArrayView2<'_, f64>, targets: &ArrayView2<'_, f64>) -> f64 {
+        if outputs.nrows() == 0 {
+            return 1.0;
+        }
+        let negative_log_likelihood: f64 = outputs.outer_iter().zip(targets.outer_iter())
+            .map(|(output, target)| {
+                let probability = if output.len() == 1 {
+                    if target[0] >= 0.5 { output[0] } else { 1.0 - output[0] }
+                } else {
+                    let total: f64 = output.iter().map(|p| p.max(0.0)).sum();
+                    output[argmax(target)].max(0.0) / total.max(PROBABILITY_FLOOR)
+                };
+                -probability.max(PROBABILITY_FLOOR).ln()
+            })
+            .sum();
+        (negative_log_likelihood / outputs.nrows() as f64).exp()
+    }
+}
+
+/// Mean of squared errors over all outputs
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MeanSquaredError;
+
+impl Metric for MeanSquaredError {
+    fn name(&self) -> &'static str {
+        "mse"
+    }
+
+    fn compute(&self, outputs: &ArrayView2<'_, f64>, targets: &ArrayView2<'_, f64>) -> f64 {
+        (outputs - targets).mapv(|e| e * e).mean().unwrap_or(0.0)
+    }
+}
+
+/// Mean of absolute errors over all outputs
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MeanAbsoluteError;
+
+impl Metric for MeanAbsoluteError {
+    fn name(&self) -> &'static str {
+        "mae"
+    }
+
+    fn compute(&self, outputs: &ArrayView2<'_, f64>, targets: &ArrayView2<'_, f64>) -> f64 {
+        (outputs - targets).mapv(f64::abs).mean().unwrap_or(0.0)
+    }
+}
+
+/// Coefficient of determination over all outputs
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RSquared;
+
+impl Metric for RSquared {
+    fn name(&self) -> &'static str {
+        "r2"
+    }
+
+    fn compute(&self, outputs: &ArrayView2<'_, f64>, targets: &ArrayView2<'_, f64>) -> f64 {
+        let Some(mean) = targets.mean() else { return 0.0 };
+        let residual: f64 = (outputs - targets).mapv(|e| e * e).sum();
+        let total: f64 = targets.mapv(|t| (t - mean) * (t - mean)).sum();
+        if total == 0.0 { 0.0 } else { 1.0 - residual / total }
+    }
+}
+
+/// Metric values produced by `NeuralNetwork::evaluate_with`, in the order requested
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EvaluationReport {
+    pub values: Vec<(&'static str, f64)>,
+}
+
+impl EvaluationReport {
+    /// Value of the metric with the given name
+    pub fn get(&self, name: &str) -> Option<f64> {
+        self.values.iter().find(|(metric, _)| *metric == name).map(|&(_, value)| value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::Array2;
+
+    #[test]
+    fn test_binary_classification_metrics() {
+        let outputs = Array2::from_shape_vec((5, 1), vec![0.9, 0.8, 0.3, 0.6, 0.1]).unwrap();
+        let targets = Array2::from_shape_vec((5, 1), vec![1.0, 1.0, 1.0, 0.0, 0.0]).unwrap();
+        let (outputs, targets) = (outputs.view(), targets.view());
+
+        // Predicted positives: 0, 1, 3 -> tp 2, fp 1, fn 1
+        assert!((Precision.compute(&outputs, &targets) - 2.0 / 3.0).abs() < 1e-12);
+        assert!((Recall.compute(&outputs, &targets) - 2.0 / 3.0).abs() < 1e-12);
+        assert!((F1Score.compute(&outputs, &targets) - 2.0 / 3.0).abs() < 1e-12);
+        // Positive/negative pairs ranked correctly: 5 of 6
+        assert!((RocAuc.compute(&outputs, &targets) - 5.0 / 6.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_multiclass_and_regression_metrics() {
+        let outputs = Array2::from_shape_vec((2, 2), vec![0.5, 0.5, 0.25, 0.75]).unwrap();
+        let targets = Array2::from_shape_vec((2, 2), vec![1.0, 0.0, 0.0, 1.0]).unwrap();
+        let (outputs, targets) = (outputs.view(), targets.view());
+
+        let expected = (-(0.5f64.ln() + 0.75f64.ln()) / 2.0).exp();
+        assert!((Perplexity.compute(&outputs, &targets) - expected).abs() < 1e-12);
+        assert!((MeanSquaredError.compute(&outputs, &targets) - 0.15625).abs() < 1e-12);
+        assert!((MeanAbsoluteError.compute(&outputs, &targets) - 0.375).abs() < 1e-12);
+        assert_eq!(RSquared.compute(&targets, &targets), 1.0);
+    }
+}
@@ -0,0 +1,345 @@
+//! Normalization Layers - Layer and batch normalization
+//!
+//! Both layers normalize activations to zero mean and unit variance and then apply a
+//! learned scale (gamma) and shift (beta). Inputs are `(batch, features)` matrices.
+
+use ndarray::{Array1, Array2, ArrayBase, Axis, Data, Ix2};
+use serde::{Deserialize, Serialize};
+
+use super::optimizer::Parameter;
+
+/// Variance floor that keeps the normalization well defined
+pub(super) const NORM_EPSILON: f64 = 1e-5;
+
+/// Cached values from the last training forward pass
+#[derive(Debug, Clone)]
+struct NormCache {
+    normalized: Array2<f64>,
+    inverse_std: Array1<f64>,
+}
+
+/// Learned scale/shift shared by both normalization layers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AffineParameters {
+    gamma: Array1<f64>,
+    beta: Array1<f64>,
+    #[serde(skip)]
+    gamma_gradients: Array1<f64>,
+    #[serde(skip)]
+    beta_gradients: Array1<f64>,
+}
+
+impl AffineParameters {
+    fn new(features: usize) -> Self {
+        Self {
+            gamma: Array1::ones(features),
+            beta: Array1::zeros(features),
+            gamma_gradients: Array1::zeros(features),
+            beta_gradients: Array1::zeros(features),
+        }
+    }
+
+    fn zero_gradients(&mut self) {
+        if self.gamma_gradients.len() != self.gamma.len() {
+            self.gamma_gradients = Array1::zeros(self.gamma.len());
+            self.beta_gradients = Array1::zeros(self.beta.len());
+        }
+        self.gamma_gradients.fill(0.0);
+        self.beta_gradients.fill(0.0);
+    }
+
+    fn parameters(&mut self) -> Vec<Parameter<'_>> {
+        vec![
+            Parameter {
+                values: self.gamma.as_slice_mut().expect("gamma is contiguous"),
+                gradients: self.gamma_gradients.as_slice_mut().expect("gradients are contiguous"),
+            },
+            Parameter {
+                values: self.beta.as_slice_mut().expect("beta is contiguous"),
+                gradients: self.beta_gradients.as_slice_mut().expect("gradients are contiguous"),
+            },
+        ]
+    }
+}
+
+/// Layer normalization: statistics over the features of each sample
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LayerNorm {
+    affine: AffineParameters,
+    #[serde(skip)]
+    cache: Option<NormCache>,
+}
+
+impl LayerNorm {
+    /// Create a layer normalization over `features` activations
+    pub fn new(features: usize) -> Self {
+        Self {
+            affine: AffineParameters::new(features),
+            cache: None,
+        }
+    }
+
+    /// Number of normalized features
+    pub fn features(&self) -> usize {
+        self.affine.gamma.len()
+    }
+
+    /// Learned scale
+    pub fn gamma(&self) -> &Array1<f64> {
+        &self.affine.gamma
+    }
+
+    /// Learned shift
+    pub fn beta(&self) -> &Array1<f64> {
+        &self.affine.beta
+    }
+
+    /// Forward pass
+    pub fn forward_batch(&mut self, input: &Array2<f64>, cache: bool) -> Array2<f64> {
+        let (output, normalized) = self.normalize(input);
+        self.cache = if cache { Some(normalized) } else { None };
+        output
+    }
+
+    /// Inference-only forward pass
+    pub fn predict_batch<S: Data<Elem = f64>>(&self, input: &ArrayBase<S, Ix2>) -> Array2<f64> {
+        self.normalize(input).0
+    }
+
+    fn normalize<S: Data<Elem = f64>>(&self, input: &ArrayBase<S, Ix2>) -> (Array2<f64>, NormCache) {
+        let mean = input.mean_axis(Axis(1)).expect("non-empty features");
+        let centered = input - &mean.view().insert_axis(Axis(1));
+        let variance = centered.mapv(|x| x * x).mean_axis(Axis(1)).expect("non-empty features");
+        let inverse_std = variance.mapv(|v| 1.0 / (v + NORM_EPSILON).sqrt());
+        let normalized = centered * inverse_std.view().insert_axis(Axis(1));
+
+        let output = &normalized * &self.affine.gamma + &self.affine.beta;
+        (output, NormCache { normalized, inverse_std })
+    }
+
+    /// Backward pass; accumulates gamma/beta gradients and returns the input gradient
+    pub fn backward_batch(&mut self, gradient: &Array2<f64>) -> Array2<f64> {
+        let cache = self.cache.as_ref().expect("backward called before forward");
+
+        self.affine.gamma_gradients += &(gradient * &cache.normalized).sum_axis(Axis(0));
+        self.affine.beta_gradients += &gradient.sum_axis(Axis(0));
+
+        let normalized_gradient = gradient * &self.affine.gamma;
+        normalization_backward(&normalized_gradient, &cache.normalized, &cache.inverse_std, Axis(1))
+    }
+
+    /// Reset accumulated gradients to zero
+    pub fn zero_gradients(&mut self) {
+        self.affine.zero_gradients();
+    }
+
+    /// Trainable parameters paired with their accumulated gradients
+    pub fn parameters(&mut self) -> Vec<Parameter<'_>> {
+        self.affine.parameters()
+    }
+
+    /// Drop cached activations
+    pub fn clear_cache(&mut self) {
+        self.cache = None;
+    }
+}
+
+/// Batch normalization: statistics over the batch for each feature
+///
+/// Running estimates of mean and variance are tracked during training and used in
+/// evaluation mode, so inference does not depend on batch composition.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchNorm {
+    affine: AffineParameters,
+    running_mean: Array1<f64>,
+    running_variance: Array1<f64>,
+    momentum: f64,
+    #[serde(skip)]
+    cache: Option<NormCache>,
+}
+
+impl BatchNorm {
+    /// Create a batch normalization over `features` activations
+    pub fn new(features: usize, momentum: f64) -> Self {
+        Self {
+            affine: AffineParameters::new(features),
+            running_mean: Array1::zeros(features),
+            running_variance: Array1::ones(features),
+            momentum: momentum.clamp(0.0, 1.0),
+            cache: None,
+        }
+    }
+
+    /// Number of normalized features
+    pub fn features(&self) -> usize {
+        self.affine.gamma.len()
+    }
+
+    /// Learned scale
+    pub fn gamma(&self) -> &Array1<f64> {
+        &self.affine.gamma
+    }
+
+    /// Learned shift
+    pub fn beta(&self) -> &Array1<f64> {
+        &self.affine.beta
+    }
+
+    /// Running mean used in evaluation mode
+    pub fn running_mean(&self) -> &Array1<f64> {
+        &self.running_mean
+    }
+
+    /// Running variance used in evaluation mode
+    pub fn running_variance(&self) -> &Array1<f64> {
+        &self.running_variance
+    }
+
+    /// Forward pass using batch statistics when training, running statistics otherwise
+    pub fn forward_batch(&mut self, input: &Array2<f64>, training: bool) -> Array2<f64> {
+        if !training {
+            self.cache = None;
+            return self.predict_batch(input);
+        }
+
+        let (output, mean, variance) = self.normalize_batch(input);
+        self.running_mean = &self.running_mean * (1.0 - self.momentum) + &(mean * self.momentum);
+        self.running_variance = &self.running_variance * (1.0 - self.momentum) + &(variance * self.momentum);
+        output
+    }
+
+    /// Training-mode forward that caches for backward without updating the running
+    /// statistics, for recomputing checkpointed activations
+    pub fn recompute_batch(&mut self, input: &Array2<f64>) -> Array2<f64> {
+        self.normalize_batch(input).0
+    }
+
+    /// Normalize with batch statistics, returning the output, mean and variance
+    fn normalize_batch(&mut self, input: &Array2<f64>) -> (Array2<f64>, Array1<f64>, Array1<f64>) {
+        let mean = input.mean_axis(Axis(0)).expect("non-empty batch");
+        let centered = input - &mean;
+        let variance = centered.mapv(|x| x * x).mean_axis(Axis(0)).expect("non-empty batch");
+        let inverse_std = variance.mapv(|v| 1.0 / (v + NORM_EPSILON).sqrt());
+        let normalized = centered * &inverse_std;
+
+        let output = &normalized * &self.affine.gamma + &self.affine.beta;
+        self.cache = Some(NormCache { normalized, inverse_std });
+        (output, mean, variance)
+    }
+
+    /// Inference-only forward pass using the running statistics
+    pub fn predict_batch<S: Data<Elem = f64>>(&self, input: &ArrayBase<S, Ix2>) -> Array2<f64> {
+        let inverse_std = self.running_variance.mapv(|v| 1.0 / (v + NORM_EPSILON).sqrt());
+        let normalized = (input - &self.running_mean) * &inverse_std;
+        &normalized * &self.affine.gamma + &self.affine.beta
+    }
+
+    /// Backward pass; accumulates gamma/beta gradients and returns the input gradient
+    pub fn backward_batch(&mut self, gradient: &Array2<f64>) -> Array2<f64> {
+        let cache = self.cache.as_ref().expect("backward called before a training forward");
+
+        self.affine.gamma_gradients += &(gradient * &cache.normalized).sum_axis(Axis(0));
+        self.affine.beta_gradients += &gradient.sum_axis(Axis(0));
+
+        let normalized_gradient = gradient * &self.affine.gamma;
+        normalization_backward(&normalized_gradient, &cache.normalized, &cache.inverse_std, Axis(0))
+    }
+
+    /// Reset accumulated gradients to zero
+    pub fn zero_gradients(&mut self) {
+        self.affine.zero_gradients();
+    }
+
+    /// Trainable parameters paired with their accumulated gradients
+    pub fn parameters(&mut self) -> Vec<Parameter<'_>> {
+        self.affine.parameters()
+    }
+
+    /// Drop cached activations
+    pub fn clear_cache(&mut self) {
+        self.cache = None;
+    }
+}
+
+/// Gradient of `x̂ = (x - mean) * inverse_std` where statistics are taken along `axis`
+///
+/// dx = inverse_std / N * (N * dx̂ - Σ dx̂ - x̂ * Σ (dx̂ * x̂))
+fn normalization_backward(
+    normalized_gradient: &Array2<f64>,
+    normalized: &Array2<f64>,
+    inverse_std: &Array1<f64>,
+    axis: Axis,
+) -> Array2<f64> {
+    let n = normalized.len_of(axis) as f64;
+    let sum_gradient = normalized_gradient.sum_axis(axis).insert_axis(axis);
+    let sum_projection = (normalized_gradient * normalized).sum_axis(axis).insert_axis(axis);
+    let inverse_std = inverse_std.view().insert_axis(axis);
+
+    (normalized_gradient * n - &sum_gradient - &(normalized * &sum_projection)) * inverse_std / n
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Compare the analytic input gradient of sum(output * weights) with finite differences
+    fn check_gradient<F: FnMut(&Array2<f64>) -> Array2<f64>, B: FnMut(&Array2<f64>) -> Array2<f64>>(
+        input: &Array2<f64>,
+        mut forward: F,
+        mut backward: B,
+    ) {
+        let weights = Array2::from_shape_fn(input.raw_dim(), |(i, j)| (i * 3 + j) as f64 * 0.1 - 0.4);
+        forward(input);
+        let analytic = backward(&weights);
+
+        let h = 1e-6;
+        for index in 0..input.len() {
+            let (i, j) = (index / input.ncols(), index % input.ncols());
+            let mut plus = input.clone();
+            let mut minus = input.clone();
+            plus[[i, j]] += h;
+            minus[[i, j]] -= h;
+            let numeric = ((forward(&plus) * &weights).sum() - (forward(&minus) * &weights).sum()) / (2.0 * h);
+            assert!((numeric - analytic[[i, j]]).abs() < 1e-4, "gradient mismatch at ({}, {})", i, j);
+        }
+    }
+
+    fn sample_input() -> Array2<f64> {
+        Array2::from_shape_vec((3, 4), vec![
+            0.5, -1.2, 0.3, 2.0,
+            1.1, 0.4, -0.7, 0.2,
+            -0.3, 0.9, 1.5, -1.0,
+        ]).unwrap()
+    }
+
+    #[test]
+    fn test_layer_norm_gradient() {
+        let input = sample_input();
+        let layer = std::cell::RefCell::new(LayerNorm::new(4));
+        check_gradient(
+            &input,
+            |x| layer.borrow_mut().forward_batch(x, true),
+            |g| layer.borrow_mut().backward_batch(g),
+        );
+    }
+
+    #[test]
+    fn test_batch_norm_gradient() {
+        let input = sample_input();
+        let layer = std::cell::RefCell::new(BatchNorm::new(4, 0.1));
+        check_gradient(
+            &input,
+            |x| layer.borrow_mut().forward_batch(x, true),
+            |g| layer.borrow_mut().backward_batch(g),
+        );
+    }
+
+    #[test]
+    fn test_batch_norm_normalizes_features() {
+        let mut layer = BatchNorm::new(4, 0.1);
+        let output = layer.forward_batch(&sample_input(), true);
+        for mean in output.mean_axis(Axis(0)).unwrap().iter() {
+            assert!(mean.abs() < 1e-10);
+        }
+    }
+}
@@ -0,0 +1,279 @@
+//! Optimizers - Parameter update rules for neural network training
+//!
+//! Optimizers own their per-parameter state (velocities, moment estimates) and are
+//! applied uniformly to every trainable parameter of a network in a stable order.
+
+use serde::{Deserialize, Serialize};
+
+/// A trainable parameter buffer paired with its accumulated gradient
+pub struct Parameter<'a> {
+    pub values: &'a mut [f64],
+    pub gradients: &'a mut [f64],
+}
+
+/// Parameter update rule
+pub trait Optimizer: Send + Sync {
+    /// Apply one update step to all parameters
+    ///
+    /// Parameters must be passed in the same order on every call, since state is
+    /// tracked by position.
+    fn step(&mut self, learning_rate: f64, parameters: &mut [Parameter<'_>]);
+
+    /// Optimizer name for logging and statistics
+    fn name(&self) -> &'static str;
+
+    /// Discard all accumulated state
+    fn reset(&mut self);
+
+    /// Clone into a new boxed optimizer
+    fn box_clone(&self) -> Box<dyn Optimizer>;
+}
+
+/// Optimizer selection for a `NeuralArchitecture`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub enum OptimizerKind {
+    /// Stochastic gradient descent using the architecture's momentum
+    #[default]
+    Sgd,
+    Adam {
+        beta1: f64,
+        beta2: f64,
+        epsilon: f64,
+    },
+    AdamW {
+        beta1: f64,
+        beta2: f64,
+        epsilon: f64,
+        weight_decay: f64,
+    },
+    RMSProp {
+        decay: f64,
+        epsilon: f64,
+    },
+}
+
+impl OptimizerKind {
+    /// Adam with the usual default hyperparameters
+    pub fn adam() -> Self {
+        Self::Adam { beta1: 0.9, beta2: 0.999, epsilon: 1e-8 }
+    }
+
+    /// AdamW with the usual default hyperparameters
+    pub fn adamw(weight_decay: f64) -> Self {
+        Self::AdamW { beta1: 0.9, beta2: 0.999, epsilon: 1e-8, weight_decay }
+    }
+
+    /// RMSProp with the usual default hyperparameters
+    pub fn rmsprop() -> Self {
+        Self::RMSProp { decay: 0.9, epsilon: 1e-8 }
+    }
+
+    /// Instantiate the optimizer
+    pub fn build(&self, momentum: f64) -> Box<dyn Optimizer> {
+        match *self {
+            Self::Sgd => Box::new(Sgd::new(momentum)),
+            Self::Adam { beta1, beta2, epsilon } => Box::new(Adam::new(beta1, beta2, epsilon, 0.0)),
+            Self::AdamW { beta1, beta2, epsilon, weight_decay } => {
+                Box::new(Adam::new(beta1, beta2, epsilon, weight_decay))
+            }
+            Self::RMSProp { decay, epsilon } => Box::new(RMSProp::new(decay, epsilon)),
+        }
+    }
+}
+
+/// Lazily size per-parameter state to match the parameter list
+fn ensure_state(state: &mut Vec<Vec<f64>>, parameters: &[Parameter<'_>]) {
+    if state.len() != parameters.len()
+        || state.iter().zip(parameters).any(|(s, p)| s.len() != p.values.len())
+    {
+        *state = parameters.iter().map(|p| vec![0.0; p.values.len()]).collect();
+    }
+}
+
+/// SGD with classical momentum
+#[derive(Debug, Clone)]
+pub struct Sgd {
+    momentum: f64,
+    velocities: Vec<Vec<f64>>,
+}
+
+impl Sgd {
+    /// Create an SGD optimizer
+    pub fn new(momentum: f64) -> Self {
+        Self { momentum, velocities: Vec::new() }
+    }
+}
+
+impl Optimizer for Sgd {
+    fn step(&mut self, learning_rate: f64, parameters: &mut [Parameter<'_>]) {
+        ensure_state(&mut self.velocities, parameters);
+
+        for (parameter, velocity) in parameters.iter_mut().zip(self.velocities.iter_mut()) {
+            for ((value, &gradient), v) in parameter.values.iter_mut()
+                .zip(parameter.gradients.iter())
+                .zip(velocity.iter_mut())
+            {
+                *v = self.momentum * *v - learning_rate * gradient;
+                *value += *v;
+            }
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "sgd"
+    }
+
+    fn reset(&mut self) {
+        self.velocities.clear();
+    }
+
+    fn box_clone(&self) -> Box<dyn Optimizer> {
+        Box::new(self.clone())
+    }
+}
+
+/// Adam, or AdamW when `weight_decay` is non-zero (decoupled weight decay)
+#[derive(Debug, Clone)]
+pub struct Adam {
+    beta1: f64,
+    beta2: f64,
+    epsilon: f64,
+    weight_decay: f64,
+    timestep: i32,
+    first_moments: Vec<Vec<f64>>,
+    second_moments: Vec<Vec<f64>>,
+}
+
+impl Adam {
+    /// Create an Adam optimizer
+    pub fn new(beta1: f64, beta2: f64, epsilon: f64, weight_decay: f64) -> Self {
+        Self {
+            beta1,
+            beta2,
+            epsilon,
+            weight_decay,
+            timestep: 0,
+            first_moments: Vec::new(),
+            second_moments: Vec::new(),
+        }
+    }
+}
+
+impl Optimizer for Adam {
+    fn step(&mut self, learning_rate: f64, parameters: &mut [Parameter<'_>]) {
+        ensure_state(&mut self.first_moments, parameters);
+        ensure_state(&mut self.second_moments, parameters);
+        self.timestep += 1;
+
+        let correction1 = 1.0 - self.beta1.powi(self.timestep);
+        let correction2 = 1.0 - self.beta2.powi(self.timestep);
+
+        for ((parameter, m), v) in parameters.iter_mut()
+            .zip(self.first_moments.iter_mut())
+            .zip(self.second_moments.iter_mut())
+        {
+            for (i, value) in parameter.values.iter_mut().enumerate() {
+                let gradient = parameter.gradients[i];
+                m[i] = self.beta1 * m[i] + (1.0 - self.beta1) * gradient;
+                v[i] = self.beta2 * v[i] + (1.0 - self.beta2) * gradient * gradient;
+
+                let m_hat = m[i] / correction1;
+                let v_hat = v[i] / correction2;
+
+                *value -= learning_rate * (m_hat / (v_hat.sqrt() + self.epsilon) + self.weight_decay * *value);
+            }
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        if self.weight_decay > 0.0 { "adamw" } else { "adam" }
+    }
+
+    fn reset(&mut self) {
+        self.timestep = 0;
+        self.first_moments.clear();
+        self.second_moments.clear();
+    }
+
+    fn box_clone(&self) -> Box<dyn Optimizer> {
+        Box::new(self.clone())
+    }
+}
+
+/// RMSProp with an exponentially decaying squared-gradient average
+#[derive(Debug, Clone)]
+pub struct RMSProp {
+    decay: f64,
+    epsilon: f64,
+    mean_squares: Vec<Vec<f64>>,
+}
+
+impl RMSProp {
+    /// Create an RMSProp optimizer
+    pub fn new(decay: f64, epsilon: f64) -> Self {
+        Self { decay, epsilon, mean_squares: Vec::new() }
+    }
+}
+
+impl Optimizer for RMSProp {
+    fn step(&mut self, learning_rate: f64, parameters: &mut [Parameter<'_>]) {
+        ensure_state(&mut self.mean_squares, parameters);
+
+        for (parameter, mean_square) in parameters.iter_mut().zip(self.mean_squares.iter_mut()) {
+            for ((value, &gradient), ms) in parameter.values.iter_mut()
+                .zip(parameter.gradients.iter())
+                .zip(mean_square.iter_mut())
+            {
+                *ms = self.decay * *ms + (1.0 - self.decay) * gradient * gradient;
+                *value -= learning_rate * gradient / (ms.sqrt() + self.epsilon);
+            }
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "rmsprop"
+    }
+
+    fn reset(&mut self) {
+        self.mean_squares.clear();
+    }
+
+    fn box_clone(&self) -> Box<dyn Optimizer> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Minimize f(x) = (x - 3)^2 and return the final x
+    fn minimize(kind: OptimizerKind, learning_rate: f64) -> f64 {
+        let mut optimizer = kind.build(0.9);
+        let mut x = vec![0.0];
+        for _ in 0..2000 {
+            let mut gradient = vec![2.0 * (x[0] - 3.0)];
+            optimizer.step(learning_rate, &mut [Parameter { values: &mut x, gradients: &mut gradient }]);
+        }
+        x[0]
+    }
+
+    #[test]
+    fn test_optimizers_minimize_quadratic() {
+        assert!((minimize(OptimizerKind::Sgd, 0.01) - 3.0).abs() < 1e-3);
+        assert!((minimize(OptimizerKind::adam(), 0.05) - 3.0).abs() < 1e-2);
+        assert!((minimize(OptimizerKind::rmsprop(), 0.01) - 3.0).abs() < 5e-2);
+    }
+
+    #[test]
+    fn test_adamw_decays_toward_zero() {
+        // With no gradient signal, decoupled weight decay shrinks the parameter
+        let mut optimizer = OptimizerKind::adamw(0.1).build(0.0);
+        let mut x = vec![1.0];
+        let mut gradient = vec![0.0];
+        for _ in 0..10 {
+            optimizer.step(0.1, &mut [Parameter { values: &mut x, gradients: &mut gradient }]);
+        }
+        assert!(x[0] < 1.0 && x[0] > 0.0);
+    }
+}
@@ -0,0 +1,288 @@
+//! Precision - Reduced-precision inference copies of trained networks
+//!
+//! Training always runs on `f64` master weights. A `CompactNetwork` is a frozen copy
+//! of those weights in any `NdFloat` type, typically `f32`, which halves the memory of
+//! the weights it serves from and lets vectorized kernels process twice as many
+//! values per instruction. Batch normalization is folded into a per-feature scale
+//! and shift at conversion time.
+
+use ndarray::{s, Array1, Array2, Axis, NdFloat};
+use serde::{Deserialize, Serialize};
+
+use super::residual::{self, SkipConnection};
+use super::{ActivationFunction, Layer, NeuralLayer, NeuralNetwork};
+use super::attention::TransformerBlock;
+use super::normalization::{BatchNorm, LayerNorm, NORM_EPSILON};
+
+/// Floating point type used for inference
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Precision {
+    /// Serve from the `f64` training weights
+    #[default]
+    F64,
+    /// Serve from an `f32` copy refreshed after every training step
+    F32,
+}
+
+impl Precision {
+    /// Bytes used to store one parameter for inference
+    pub fn bytes_per_parameter(&self) -> usize {
+        match self {
+            Self::F64 => std::mem::size_of::<f64>(),
+            Self::F32 => std::mem::size_of::<f32>(),
+        }
+    }
+
+    /// Name of the element type
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::F64 => "f64",
+            Self::F32 => "f32",
+        }
+    }
+}
+
+/// Convert an `f64` value to the inference type
+pub(crate) fn cast<F: NdFloat>(x: f64) -> F {
+    F::from(x).expect("f64 values are representable in every float type")
+}
+
+/// Fully connected layer in reduced precision
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactDense<F> {
+    weights: Array2<F>,
+    biases: Array1<F>,
+    activation: ActivationFunction,
+}
+
+impl<F: NdFloat> CompactDense<F> {
+    fn from_layer(layer: &NeuralLayer) -> Self {
+        Self {
+            weights: layer.weights.mapv(cast),
+            biases: layer.biases.mapv(cast),
+            activation: layer.activation.clone(),
+        }
+    }
+
+    fn predict_batch(&self, input: &Array2<F>) -> Array2<F> {
+        let mut output = input.dot(&self.weights.t()) + &self.biases;
+        output.mapv_inplace(|x| self.activation.apply_float(x));
+        output
+    }
+}
+
+/// Layer normalization in reduced precision
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactLayerNorm<F> {
+    gamma: Array1<F>,
+    beta: Array1<F>,
+}
+
+impl<F: NdFloat> CompactLayerNorm<F> {
+    fn from_layer(layer: &LayerNorm) -> Self {
+        Self {
+            gamma: layer.gamma().mapv(cast),
+            beta: layer.beta().mapv(cast),
+        }
+    }
+
+    fn predict_batch(&self, input: &Array2<F>) -> Array2<F> {
+        let features: F = cast(input.ncols() as f64);
+        let mean = input.sum_axis(Axis(1)) / features;
+        let centered = input - &mean.insert_axis(Axis(1));
+        let variance = centered.mapv(|x| x * x).sum_axis(Axis(1)) / features;
+        let epsilon: F = cast(NORM_EPSILON);
+        let inverse_std = variance.mapv(|v| (v + epsilon).sqrt().recip());
+        centered * &inverse_std.insert_axis(Axis(1)) * &self.gamma + &self.beta
+    }
+}
+
+/// Transformer encoder block in reduced precision
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactAttention<F> {
+    tokens: usize,
+    model_dim: usize,
+    heads: usize,
+    query: CompactDense<F>,
+    key: CompactDense<F>,
+    value: CompactDense<F>,
+    output: CompactDense<F>,
+    attention_norm: CompactLayerNorm<F>,
+    feed_forward_in: CompactDense<F>,
+    feed_forward_out: CompactDense<F>,
+    feed_forward_norm: CompactLayerNorm<F>,
+}
+
+impl<F: NdFloat> CompactAttention<F> {
+    fn from_block(block: &TransformerBlock) -> Self {
+        Self {
+            tokens: block.tokens,
+            model_dim: block.model_dim,
+            heads: block.heads,
+            query: CompactDense::from_layer(&block.query),
+            key: CompactDense::from_layer(&block.key),
+            value: CompactDense::from_layer(&block.value),
+            output: CompactDense::from_layer(&block.output),
+            attention_norm: CompactLayerNorm::from_layer(&block.attention_norm),
+            feed_forward_in: CompactDense::from_layer(&block.feed_forward_in),
+            feed_forward_out: CompactDense::from_layer(&block.feed_forward_out),
+            feed_forward_norm: CompactLayerNorm::from_layer(&block.feed_forward_norm),
+        }
+    }
+
+    fn predict_batch(&self, input: &Array2<F>) -> Array2<F> {
+        let batch = input.nrows();
+        let sequence = input.as_standard_layout().into_owned()
+            .into_shape((batch * self.tokens, self.model_dim))
+            .expect("input width matches the block");
+
+        let queries = self.query.predict_batch(&sequence);
+        let keys = self.key.predict_batch(&sequence);
+        let values = self.value.predict_batch(&sequence);
+
+        let head_dim = self.model_dim / self.heads;
+        let scale: F = cast(1.0 / (head_dim as f64).sqrt());
+        let mut attended = Array2::zeros(sequence.raw_dim());
+        for sample in 0..batch {
+            let rows = s![sample * self.tokens..(sample + 1) * self.tokens, ..];
+            for head in 0..self.heads {
+                let columns = s![.., head * head_dim..(head + 1) * head_dim];
+                let q = queries.slice(rows).slice_move(columns);
+                let k = keys.slice(rows).slice_move(columns);
+                let v = values.slice(rows).slice_move(columns);
+
+                let mut scores = q.dot(&k.t()) * scale;
+                for mut row in scores.outer_iter_mut() {
+                    let max = row.fold(F::neg_infinity(), |a, &b| a.max(b));
+                    row.mapv_inplace(|x| (x - max).exp());
+                    let sum = row.sum();
+                    row.mapv_inplace(|x| x / sum);
+                }
+                attended.slice_mut(rows).slice_mut(columns).assign(&scores.dot(&v));
+            }
+        }
+
+        let normalized = self.attention_norm.predict_batch(&(&sequence + &self.output.predict_batch(&attended)));
+        let feed_forward = self.feed_forward_out.predict_batch(&self.feed_forward_in.predict_batch(&normalized));
+        let output = self.feed_forward_norm.predict_batch(&(&normalized + &feed_forward));
+        output.into_shape((batch, self.tokens * self.model_dim)).expect("output width matches the block")
+    }
+}
+
+/// Network layer in reduced precision
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum CompactLayer<F> {
+    Dense(CompactDense<F>),
+    LayerNorm(CompactLayerNorm<F>),
+    /// Batch normalization folded into `x * scale + shift` from its running statistics
+    Affine { scale: Array1<F>, shift: Array1<F> },
+    Attention(Box<CompactAttention<F>>),
+}
+
+impl<F: NdFloat> CompactLayer<F> {
+    pub(super) fn from_layer(layer: &Layer) -> Self {
+        match layer {
+            Layer::Dense(layer) => Self::Dense(CompactDense::from_layer(layer)),
+            Layer::LayerNorm(layer) => Self::LayerNorm(CompactLayerNorm::from_layer(layer)),
+            Layer::BatchNorm(layer) => Self::fold_batch_norm(layer),
+            Layer::Attention(block) => Self::Attention(Box::new(CompactAttention::from_block(block))),
+        }
+    }
+
+    fn fold_batch_norm(layer: &BatchNorm) -> Self {
+        let scale = layer.running_variance().mapv(|v| 1.0 / (v + NORM_EPSILON).sqrt()) * layer.gamma();
+        let shift = layer.beta() - &(layer.running_mean() * &scale);
+        Self::Affine { scale: scale.mapv(cast), shift: shift.mapv(cast) }
+    }
+
+    pub(super) fn predict_batch(&self, input: &Array2<F>) -> Array2<F> {
+        match self {
+            Self::Dense(layer) => layer.predict_batch(input),
+            Self::LayerNorm(layer) => layer.predict_batch(input),
+            Self::Affine { scale, shift } => input * scale + shift,
+            Self::Attention(block) => block.predict_batch(input),
+        }
+    }
+
+    /// Number of stored parameters
+    pub fn parameter_count(&self) -> usize {
+        let dense = |layer: &CompactDense<F>| layer.weights.len() + layer.biases.len();
+        let norm = |layer: &CompactLayerNorm<F>| layer.gamma.len() + layer.beta.len();
+        match self {
+            Self::Dense(layer) => dense(layer),
+            Self::LayerNorm(layer) => norm(layer),
+            Self::Affine { scale, shift } => scale.len() + shift.len(),
+            Self::Attention(block) => {
+                [&block.query, &block.key, &block.value, &block.output, &block.feed_forward_in, &block.feed_forward_out]
+                    .into_iter()
+                    .map(dense)
+                    .sum::<usize>()
+                    + norm(&block.attention_norm)
+                    + norm(&block.feed_forward_norm)
+            }
+        }
+    }
+}
+
+/// Frozen inference copy of a `NeuralNetwork` in reduced precision
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactNetwork<F> {
+    layers: Vec<CompactLayer<F>>,
+    skip_connections: Vec<SkipConnection>,
+}
+
+impl<F: NdFloat> CompactNetwork<F> {
+    /// Convert the trained layers of a network
+    pub fn from_network(network: &NeuralNetwork) -> Self {
+        Self {
+            layers: network.layers().iter().map(CompactLayer::from_layer).collect(),
+            skip_connections: network.architecture().skip_connections.clone(),
+        }
+    }
+
+    /// Evaluation-mode forward pass over a `(batch, input_size)` matrix
+    pub fn predict_batch(&self, input: &Array2<F>) -> Array2<F> {
+        residual::forward(&self.layers, &self.skip_connections, input, CompactLayer::predict_batch)
+    }
+
+    /// Bytes held by the weights
+    pub fn parameter_bytes(&self) -> usize {
+        self.layers.iter().map(CompactLayer::parameter_count).sum::<usize>() * std::mem::size_of::<F>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural_engine::{LayerKind, NeuralArchitecture};
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_f32_copy_tracks_f64_network() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(6);
+        let architecture = NeuralArchitecture {
+            input_size: 8,
+            hidden_layers: Vec::new(),
+            output_size: 3,
+            activation_function: ActivationFunction::GELU,
+            layers: vec![
+                LayerKind::SelfAttention { model_dim: 4, heads: 2, feed_forward_dim: 6 },
+                LayerKind::Dense { units: 6 },
+                LayerKind::BatchNorm { momentum: 0.5 },
+                LayerKind::LayerNorm,
+            ],
+            ..NeuralArchitecture::default()
+        };
+        let mut network = NeuralNetwork::with_rng(architecture, &mut rng);
+        let inputs = Array2::from_shape_fn((4, 8), |(i, j)| ((i * 8 + j) as f64 * 0.7).cos());
+        network.train_batch(&inputs, &Array2::zeros((4, 3))).unwrap();
+
+        let compact = CompactNetwork::<f32>::from_network(&network);
+        let reduced = compact.predict_batch(&inputs.mapv(|x| x as f32));
+        let full = network.predict_batch(&inputs);
+        assert!(reduced.iter().zip(full.iter()).all(|(&a, &b)| (a as f64 - b).abs() < 1e-4));
+
+        let parameter_count = network.layers().iter().map(Layer::parameter_count).sum::<usize>();
+        assert_eq!(compact.parameter_bytes(), parameter_count * 4);
+    }
+}
@@ -0,0 +1,137 @@
+//! Pruning - Magnitude pruning of dense weights
+//!
+//! Pruning zeroes small-magnitude weights and records them in a per-layer mask. The
+//! mask persists: pruned weights receive no gradient and stay zero through further
+//! training, and once a layer is sparse enough its forward pass only visits the
+//! remaining connections.
+
+use ndarray::{Array1, Array2, ArrayBase, Data, Ix2, Zip};
+use serde::{Deserialize, Serialize};
+
+/// Sparsity above which the forward pass switches to the sparse kernel
+const SPARSE_FORWARD_THRESHOLD: f64 = 0.5;
+
+/// How `NeuralNetwork::prune` selects weights to remove
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PruneStrategy {
+    /// Remove weights whose magnitude is below the threshold
+    Threshold(f64),
+    /// Remove the smallest-magnitude weights across all dense layers until this
+    /// fraction of them is pruned
+    TargetSparsity(f64),
+}
+
+/// Kept connections of a dense layer's `(outputs, inputs)` weight matrix
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SparsityMask {
+    keep: Array2<bool>,
+    /// Kept input indices of each output, for the sparse forward pass
+    #[serde(skip)]
+    active: Vec<Vec<usize>>,
+}
+
+impl SparsityMask {
+    /// Mask keeping every connection
+    pub fn dense(outputs: usize, inputs: usize) -> Self {
+        Self::from_keep(Array2::from_elem((outputs, inputs), true))
+    }
+
+    fn from_keep(keep: Array2<bool>) -> Self {
+        let active = keep.outer_iter()
+            .map(|row| row.iter().enumerate().filter(|(_, &kept)| kept).map(|(i, _)| i).collect())
+            .collect();
+        Self { keep, active }
+    }
+
+    /// Restore the sparse index lists, which are not persisted
+    pub fn rebuild(&mut self) {
+        if self.active.len() != self.keep.nrows() {
+            *self = Self::from_keep(std::mem::take(&mut self.keep));
+        }
+    }
+
+    /// Additionally prune every weight matching `prune`; returns the newly pruned count
+    pub fn prune(&mut self, weights: &Array2<f64>, prune: impl Fn(f64) -> bool) -> usize {
+        let mut pruned = 0;
+        let mut keep = std::mem::take(&mut self.keep);
+        Zip::from(&mut keep).and(weights).for_each(|kept, &weight| {
+            if *kept && prune(weight.abs()) {
+                *kept = false;
+                pruned += 1;
+            }
+        });
+        *self = Self::from_keep(keep);
+        pruned
+    }
+
+    /// Zero the masked entries, e.g. of weights or their gradients
+    pub fn apply(&self, values: &mut Array2<f64>) {
+        Zip::from(values).and(&self.keep).for_each(|value, &kept| {
+            if !kept {
+                *value = 0.0;
+            }
+        });
+    }
+
+    /// Number of pruned connections
+    pub fn pruned(&self) -> usize {
+        self.keep.iter().filter(|&&kept| !kept).count()
+    }
+
+    /// Fraction of connections pruned
+    pub fn sparsity(&self) -> f64 {
+        if self.keep.is_empty() {
+            0.0
+        } else {
+            self.pruned() as f64 / self.keep.len() as f64
+        }
+    }
+
+    /// Whether `linear` should use the sparse kernel
+    pub fn is_sparse(&self) -> bool {
+        self.sparsity() > SPARSE_FORWARD_THRESHOLD
+    }
+
+    /// `input * weights^T + biases` visiting only kept connections
+    pub fn linear<S: Data<Elem = f64>>(&self, input: &ArrayBase<S, Ix2>, weights: &Array2<f64>, biases: &Array1<f64>) -> Array2<f64> {
+        let mut output = Array2::zeros((input.nrows(), weights.nrows()));
+        for (mut output_row, input_row) in output.outer_iter_mut().zip(input.outer_iter()) {
+            for (j, (out, active)) in output_row.iter_mut().zip(&self.active).enumerate() {
+                *out = biases[j] + active.iter().map(|&i| weights[[j, i]] * input_row[i]).sum::<f64>();
+            }
+        }
+        output
+    }
+}
+
+/// Magnitude at or below which `fraction` of `magnitudes` lies
+pub(super) fn sparsity_threshold(mut magnitudes: Vec<f64>, fraction: f64) -> Option<f64> {
+    let count = ((magnitudes.len() as f64) * fraction.clamp(0.0, 1.0)).round() as usize;
+    if count == 0 {
+        return None;
+    }
+    magnitudes.sort_by(|a, b| a.total_cmp(b));
+    magnitudes.get(count - 1).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sparse_linear_matches_dense() {
+        let weights = Array2::from_shape_fn((3, 4), |(i, j)| (i as f64 - j as f64) * 0.25);
+        let biases = Array1::from(vec![0.1, -0.2, 0.3]);
+        let input = Array2::from_shape_fn((2, 4), |(i, j)| (i + j) as f64);
+
+        let mut mask = SparsityMask::dense(3, 4);
+        assert_eq!(mask.prune(&weights, |magnitude| magnitude < 0.3), 8);
+        let mut pruned_weights = weights.clone();
+        mask.apply(&mut pruned_weights);
+
+        assert!(mask.is_sparse());
+        let dense = input.dot(&pruned_weights.t()) + &biases;
+        let sparse = mask.linear(&input, &weights, &biases);
+        assert!(sparse.iter().zip(dense.iter()).all(|(a, b)| (a - b).abs() < 1e-12));
+    }
+}
@@ -0,0 +1,204 @@
+//! Quantization - Post-training int8 quantization of trained networks
+//!
+//! Dense weights are stored as `i8` with one affine scale and zero point per layer
+//! (`w ≈ scale * (q - zero_point)`). At inference, each batch of activations is
+//! quantized the same way, products are accumulated in `i32` and the sum is rescaled
+//! once per output. Normalization and attention layers are kept in `f32`.
+
+use ndarray::{Array1, Array2, ArrayView2};
+use serde::{Deserialize, Serialize};
+
+use super::dataset::{self, Dataset};
+use super::precision::CompactLayer;
+use super::residual::{self, SkipConnection};
+use super::{ActivationFunction, Layer, NeuralLayer, NeuralNetwork};
+
+/// `i8` tensor with an affine mapping back to real values
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuantizedMatrix {
+    values: Array2<i8>,
+    scale: f32,
+    zero_point: i8,
+}
+
+impl QuantizedMatrix {
+    /// Quantize using the full `i8` range over the values' `[min, max]`, which is
+    /// widened to include zero so that zero is represented exactly
+    pub fn quantize<'a>(values: impl Into<ArrayView2<'a, f32>>) -> Self {
+        let values = values.into();
+        let min = values.fold(0.0f32, |a, &b| a.min(b));
+        let max = values.fold(0.0f32, |a, &b| a.max(b));
+        let scale = if max > min { (max - min) / 255.0 } else { 1.0 };
+        let zero_point = (-128.0 - min / scale).round().clamp(-128.0, 127.0) as i8;
+
+        let values = values.mapv(|x| (x / scale + zero_point as f32).round().clamp(-128.0, 127.0) as i8);
+        Self { values, scale, zero_point }
+    }
+
+    /// Scale of one quantization step
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Quantized value representing zero
+    pub fn zero_point(&self) -> i8 {
+        self.zero_point
+    }
+
+    /// Real values approximated by the quantized tensor
+    pub fn dequantize(&self) -> Array2<f32> {
+        self.values.mapv(|q| self.scale * (q as f32 - self.zero_point as f32))
+    }
+}
+
+/// Dense layer with int8 weights
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuantizedDense {
+    weights: QuantizedMatrix,
+    biases: Array1<f32>,
+    activation: ActivationFunction,
+}
+
+impl QuantizedDense {
+    fn from_layer(layer: &NeuralLayer) -> Self {
+        Self {
+            weights: QuantizedMatrix::quantize(&layer.weights.mapv(|w| w as f32)),
+            biases: layer.biases.mapv(|b| b as f32),
+            activation: layer.activation.clone(),
+        }
+    }
+
+    /// Integer matrix product of quantized activations and weights
+    fn predict_batch(&self, input: &Array2<f32>) -> Array2<f32> {
+        let activations = QuantizedMatrix::quantize(input);
+        let input_zero = activations.zero_point as i32;
+        let weight_zero = self.weights.zero_point as i32;
+        let rescale = activations.scale * self.weights.scale;
+
+        let mut output = Array2::zeros((input.nrows(), self.weights.values.nrows()));
+        for (mut output_row, input_row) in output.outer_iter_mut().zip(activations.values.outer_iter()) {
+            for ((out, weight_row), &bias) in output_row.iter_mut().zip(self.weights.values.outer_iter()).zip(&self.biases) {
+                let accumulator: i32 = input_row.iter().zip(weight_row.iter())
+                    .map(|(&x, &w)| (x as i32 - input_zero) * (w as i32 - weight_zero))
+                    .sum();
+                *out = self.activation.apply_float(accumulator as f32 * rescale + bias);
+            }
+        }
+        output
+    }
+}
+
+/// Layer of a quantized network
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum QuantizedLayer {
+    Int8(QuantizedDense),
+    /// Layers without an int8 kernel, served in `f32`
+    Float(CompactLayer<f32>),
+}
+
+/// Int8 inference copy of a `NeuralNetwork`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuantizedNetwork {
+    layers: Vec<QuantizedLayer>,
+    skip_connections: Vec<SkipConnection>,
+}
+
+impl QuantizedNetwork {
+    /// Quantize the trained layers of a network
+    pub fn from_network(network: &NeuralNetwork) -> Self {
+        let layers = network.layers().iter().map(|layer| match layer {
+            Layer::Dense(layer) => QuantizedLayer::Int8(QuantizedDense::from_layer(layer)),
+            layer => QuantizedLayer::Float(CompactLayer::from_layer(layer)),
+        }).collect();
+        Self { layers, skip_connections: network.architecture().skip_connections.clone() }
+    }
+
+    /// Evaluation-mode forward pass over a `(batch, input_size)` matrix
+    pub fn predict_batch(&self, input: &Array2<f64>) -> Array2<f64> {
+        let output = residual::forward(&self.layers, &self.skip_connections, &input.mapv(|x| x as f32), |layer, current| {
+            match layer {
+                QuantizedLayer::Int8(layer) => layer.predict_batch(current),
+                QuantizedLayer::Float(layer) => layer.predict_batch(current),
+            }
+        });
+        output.mapv(f64::from)
+    }
+
+    /// Bytes held by the weights
+    pub fn parameter_bytes(&self) -> usize {
+        self.layers.iter().map(|layer| match layer {
+            QuantizedLayer::Int8(layer) => {
+                layer.weights.values.len() + layer.biases.len() * std::mem::size_of::<f32>()
+            }
+            QuantizedLayer::Float(layer) => layer.parameter_count() * std::mem::size_of::<f32>(),
+        }).sum()
+    }
+
+    /// Compare against the `f64` network the quantized copy was made from
+    pub fn report<D: Dataset + ?Sized>(&self, reference: &NeuralNetwork, dataset: &D) -> QuantizationReport {
+        let inputs = dataset.inputs().to_owned();
+        let targets = dataset.targets();
+        let metrics = |outputs: &Array2<f64>| {
+            let loss = outputs.outer_iter().zip(targets.outer_iter())
+                .map(|(output, target)| reference.architecture().loss_function.loss(&output.to_owned(), &target.to_owned()))
+                .sum::<f64>() / dataset.len().max(1) as f64;
+            (loss, dataset::accuracy(outputs, &targets))
+        };
+
+        let float_outputs = reference.predict_batch(&inputs);
+        let quantized_outputs = self.predict_batch(&inputs);
+        let (float_loss, float_accuracy) = metrics(&float_outputs);
+        let (quantized_loss, quantized_accuracy) = metrics(&quantized_outputs);
+
+        QuantizationReport {
+            float_loss,
+            quantized_loss,
+            float_accuracy,
+            quantized_accuracy,
+            max_output_error: (&float_outputs - &quantized_outputs).fold(0.0, |a: f64, &b| a.max(b.abs())),
+            compression_ratio: reference.inference_bytes() as f64 / self.parameter_bytes().max(1) as f64,
+        }
+    }
+}
+
+/// Quality and size of a quantized network relative to its `f64` original
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuantizationReport {
+    pub float_loss: f64,
+    pub quantized_loss: f64,
+    pub float_accuracy: f64,
+    pub quantized_accuracy: f64,
+    /// Largest absolute difference between corresponding outputs
+    pub max_output_error: f64,
+    /// Weight bytes of the original divided by those of the quantized network
+    pub compression_ratio: f64,
+}
+
+impl QuantizationReport {
+    /// Accuracy lost by quantizing; negative when quantization happened to help
+    pub fn accuracy_delta(&self) -> f64 {
+        self.float_accuracy - self.quantized_accuracy
+    }
+
+    /// Loss added by quantizing
+    pub fn loss_delta(&self) -> f64 {
+        self.quantized_loss - self.float_loss
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quantize_roundtrip() {
+        let values = Array2::from_shape_fn((3, 5), |(i, j)| (i as f32 - 1.0) * 0.3 + j as f32 * 0.05);
+        let quantized = QuantizedMatrix::quantize(&values);
+        let restored = quantized.dequantize();
+        assert!(values.iter().zip(restored.iter()).all(|(a, b)| (a - b).abs() <= quantized.scale() / 2.0 + 1e-6));
+
+        // Zero maps exactly onto the zero point
+        let with_zero = QuantizedMatrix::quantize(&Array2::from_elem((1, 2), 0.0f32));
+        assert_eq!(with_zero.dequantize()[[0, 0]], 0.0);
+    }
+}
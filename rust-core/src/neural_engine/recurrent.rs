@@ -0,0 +1,649 @@
+//! Recurrent Layers - LSTM and GRU cells for sequential input
+//!
+//! Sequences are `(steps, features)` matrices processed one step at a time. Layers are
+//! stateful: the hidden state left by one `forward_sequence` call is the starting state
+//! of the next, until `reset_state` is called. Backpropagation only reaches the steps
+//! cached by the most recent forward call, which gives truncated BPTT when a long
+//! sequence is fed in chunks (see `RecurrentLayer::train_truncated`).
+
+use ndarray::{s, Array1, Array2, ArrayView1, Axis};
+use ndarray_rand::rand_distr::StandardNormal;
+use ndarray_rand::RandomExt;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::optimizer::{Optimizer, Parameter};
+
+fn sigmoid(x: f64) -> f64 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+fn outer(a: &Array1<f64>, b: &ArrayView1<f64>) -> Array2<f64> {
+    let column = a.view().insert_axis(Axis(1));
+    let row = b.view().insert_axis(Axis(0));
+    column.dot(&row)
+}
+
+/// Recurrent cell selection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum RecurrentKind {
+    #[default]
+    Lstm,
+    Gru,
+}
+
+/// Recurrent encoder specification inside a `NeuralArchitecture`
+///
+/// The encoder maps `token_size`-wide steps to a hidden state as wide as the
+/// architecture's input, which then feeds the feedforward networks.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SequenceEncoderConfig {
+    pub kind: RecurrentKind,
+    pub token_size: usize,
+}
+
+/// Stacked gate weights shared by both cell types
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct GateWeights {
+    /// `(gates * hidden, input)` input projection
+    input_weights: Array2<f64>,
+    /// `(gates * hidden, hidden)` recurrent projection
+    recurrent_weights: Array2<f64>,
+    biases: Array1<f64>,
+    #[serde(skip)]
+    input_gradients: Array2<f64>,
+    #[serde(skip)]
+    recurrent_gradients: Array2<f64>,
+    #[serde(skip)]
+    bias_gradients: Array1<f64>,
+}
+
+impl GateWeights {
+    fn new<R: Rng + ?Sized>(gates: usize, input_size: usize, hidden_size: usize, rng: &mut R) -> Self {
+        let rows = gates * hidden_size;
+        let input_scale = (2.0 / (input_size + hidden_size) as f64).sqrt();
+        let recurrent_scale = (1.0 / hidden_size as f64).sqrt();
+        Self {
+            input_weights: Array2::random_using((rows, input_size), StandardNormal, rng) * input_scale,
+            recurrent_weights: Array2::random_using((rows, hidden_size), StandardNormal, rng) * recurrent_scale,
+            biases: Array1::zeros(rows),
+            input_gradients: Array2::zeros((rows, input_size)),
+            recurrent_gradients: Array2::zeros((rows, hidden_size)),
+            bias_gradients: Array1::zeros(rows),
+        }
+    }
+
+    fn hidden_size(&self) -> usize {
+        self.recurrent_weights.ncols()
+    }
+
+    fn input_size(&self) -> usize {
+        self.input_weights.ncols()
+    }
+
+    fn zero_gradients(&mut self) {
+        if self.input_gradients.raw_dim() != self.input_weights.raw_dim() {
+            self.input_gradients = Array2::zeros(self.input_weights.raw_dim());
+            self.recurrent_gradients = Array2::zeros(self.recurrent_weights.raw_dim());
+            self.bias_gradients = Array1::zeros(self.biases.raw_dim());
+        }
+        self.input_gradients.fill(0.0);
+        self.recurrent_gradients.fill(0.0);
+        self.bias_gradients.fill(0.0);
+    }
+
+    fn parameters(&mut self) -> Vec<Parameter<'_>> {
+        vec![
+            Parameter {
+                values: self.input_weights.as_slice_mut().expect("weights are contiguous"),
+                gradients: self.input_gradients.as_slice_mut().expect("gradients are contiguous"),
+            },
+            Parameter {
+                values: self.recurrent_weights.as_slice_mut().expect("weights are contiguous"),
+                gradients: self.recurrent_gradients.as_slice_mut().expect("gradients are contiguous"),
+            },
+            Parameter {
+                values: self.biases.as_slice_mut().expect("biases are contiguous"),
+                gradients: self.bias_gradients.as_slice_mut().expect("gradients are contiguous"),
+            },
+        ]
+    }
+
+    fn parameter_count(&self) -> usize {
+        self.input_weights.len() + self.recurrent_weights.len() + self.biases.len()
+    }
+}
+
+/// Values of one LSTM step needed for backpropagation
+#[derive(Debug, Clone)]
+struct LstmStep {
+    input: Array1<f64>,
+    hidden: Array1<f64>,
+    cell: Array1<f64>,
+    input_gate: Array1<f64>,
+    forget_gate: Array1<f64>,
+    candidate: Array1<f64>,
+    output_gate: Array1<f64>,
+    cell_tanh: Array1<f64>,
+}
+
+/// Long short-term memory layer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Lstm {
+    weights: GateWeights,
+    #[serde(skip)]
+    state: Option<(Array1<f64>, Array1<f64>)>,
+    #[serde(skip)]
+    cache: Vec<LstmStep>,
+}
+
+impl Lstm {
+    /// Create an LSTM layer drawing initial weights from the given RNG
+    pub fn with_rng<R: Rng + ?Sized>(input_size: usize, hidden_size: usize, rng: &mut R) -> Self {
+        let mut weights = GateWeights::new(4, input_size, hidden_size, rng);
+        // Start with an open forget gate so early training can carry state
+        weights.biases.slice_mut(s![hidden_size..2 * hidden_size]).fill(1.0);
+        Self { weights, state: None, cache: Vec::new() }
+    }
+
+    fn zero_state(&self) -> (Array1<f64>, Array1<f64>) {
+        let hidden_size = self.weights.hidden_size();
+        (Array1::zeros(hidden_size), Array1::zeros(hidden_size))
+    }
+
+    /// One step from `(hidden, cell)`, returning the next state and the step's values
+    fn step(&self, input: ArrayView1<f64>, hidden: Array1<f64>, cell: Array1<f64>) -> (Array1<f64>, Array1<f64>, LstmStep) {
+        let hidden_size = self.weights.hidden_size();
+        let gates = self.weights.input_weights.dot(&input)
+            + self.weights.recurrent_weights.dot(&hidden)
+            + &self.weights.biases;
+        let input_gate = gates.slice(s![..hidden_size]).mapv(sigmoid);
+        let forget_gate = gates.slice(s![hidden_size..2 * hidden_size]).mapv(sigmoid);
+        let candidate = gates.slice(s![2 * hidden_size..3 * hidden_size]).mapv(f64::tanh);
+        let output_gate = gates.slice(s![3 * hidden_size..]).mapv(sigmoid);
+
+        let next_cell = &forget_gate * &cell + &input_gate * &candidate;
+        let cell_tanh = next_cell.mapv(f64::tanh);
+        let next_hidden = &output_gate * &cell_tanh;
+
+        let step = LstmStep {
+            input: input.to_owned(),
+            hidden,
+            cell,
+            input_gate,
+            forget_gate,
+            candidate,
+            output_gate,
+            cell_tanh,
+        };
+        (next_hidden, next_cell, step)
+    }
+
+    /// Run the sequence from the current state, returning `(steps, hidden)` outputs
+    pub fn forward_sequence(&mut self, inputs: &Array2<f64>, cache: bool) -> Array2<f64> {
+        let (mut hidden, mut cell) = self.state.take().unwrap_or_else(|| self.zero_state());
+        let mut outputs = Array2::zeros((inputs.nrows(), self.weights.hidden_size()));
+        self.cache.clear();
+
+        for (t, input) in inputs.outer_iter().enumerate() {
+            let (next_hidden, next_cell, step) = self.step(input, hidden, cell);
+            outputs.row_mut(t).assign(&next_hidden);
+            if cache {
+                self.cache.push(step);
+            }
+            hidden = next_hidden;
+            cell = next_cell;
+        }
+
+        self.state = Some((hidden, cell));
+        outputs
+    }
+
+    /// Final hidden state of the sequence from a zero state, leaving the layer untouched
+    pub fn encode(&self, inputs: &Array2<f64>) -> Array1<f64> {
+        let (hidden, _) = inputs.outer_iter().fold(self.zero_state(), |(hidden, cell), input| {
+            let (hidden, cell, _) = self.step(input, hidden, cell);
+            (hidden, cell)
+        });
+        hidden
+    }
+
+    /// Backpropagate `(steps, hidden)` output gradients through the cached steps
+    pub fn backward_sequence(&mut self, gradients: &Array2<f64>) -> Array2<f64> {
+        let hidden_size = self.weights.hidden_size();
+        let mut input_gradients = Array2::zeros((self.cache.len(), self.weights.input_size()));
+        let mut hidden_gradient = Array1::zeros(hidden_size);
+        let mut cell_gradient = Array1::zeros(hidden_size);
+
+        for (t, step) in self.cache.iter().enumerate().rev() {
+            let dh = &gradients.row(t) + &hidden_gradient;
+            let dc = &cell_gradient + &(&dh * &step.output_gate * &step.cell_tanh.mapv(|x| 1.0 - x * x));
+
+            let mut gate_gradients = Array1::zeros(4 * hidden_size);
+            gate_gradients.slice_mut(s![..hidden_size])
+                .assign(&(&dc * &step.candidate * &step.input_gate.mapv(|x| x * (1.0 - x))));
+            gate_gradients.slice_mut(s![hidden_size..2 * hidden_size])
+                .assign(&(&dc * &step.cell * &step.forget_gate.mapv(|x| x * (1.0 - x))));
+            gate_gradients.slice_mut(s![2 * hidden_size..3 * hidden_size])
+                .assign(&(&dc * &step.input_gate * &step.candidate.mapv(|x| 1.0 - x * x)));
+            gate_gradients.slice_mut(s![3 * hidden_size..])
+                .assign(&(&dh * &step.cell_tanh * &step.output_gate.mapv(|x| x * (1.0 - x))));
+
+            self.weights.input_gradients += &outer(&gate_gradients, &step.input.view());
+            self.weights.recurrent_gradients += &outer(&gate_gradients, &step.hidden.view());
+            self.weights.bias_gradients += &gate_gradients;
+
+            input_gradients.row_mut(t).assign(&self.weights.input_weights.t().dot(&gate_gradients));
+            hidden_gradient = self.weights.recurrent_weights.t().dot(&gate_gradients);
+            cell_gradient = dc * &step.forget_gate;
+        }
+
+        input_gradients
+    }
+
+    /// Hidden state reached by the last forward call
+    pub fn hidden_state(&self) -> Array1<f64> {
+        self.state.as_ref().map(|(hidden, _)| hidden.clone()).unwrap_or_else(|| self.zero_state().0)
+    }
+
+    /// Forget the carried state and cached steps
+    pub fn reset_state(&mut self) {
+        self.state = None;
+        self.cache.clear();
+    }
+}
+
+/// Values of one GRU step needed for backpropagation
+#[derive(Debug, Clone)]
+struct GruStep {
+    input: Array1<f64>,
+    hidden: Array1<f64>,
+    update_gate: Array1<f64>,
+    reset_gate: Array1<f64>,
+    candidate: Array1<f64>,
+    recurrent_candidate: Array1<f64>,
+}
+
+/// Gated recurrent unit layer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Gru {
+    weights: GateWeights,
+    #[serde(skip)]
+    state: Option<Array1<f64>>,
+    #[serde(skip)]
+    cache: Vec<GruStep>,
+}
+
+impl Gru {
+    /// Create a GRU layer drawing initial weights from the given RNG
+    pub fn with_rng<R: Rng + ?Sized>(input_size: usize, hidden_size: usize, rng: &mut R) -> Self {
+        Self {
+            weights: GateWeights::new(3, input_size, hidden_size, rng),
+            state: None,
+            cache: Vec::new(),
+        }
+    }
+
+    /// One step from `hidden`, returning the next hidden state and the step's values
+    fn step(&self, input: ArrayView1<f64>, hidden: Array1<f64>) -> (Array1<f64>, GruStep) {
+        let hidden_size = self.weights.hidden_size();
+        let projected = self.weights.input_weights.dot(&input) + &self.weights.biases;
+        let recurrent = self.weights.recurrent_weights.dot(&hidden);
+
+        let update_gate = (&projected.slice(s![..hidden_size]) + &recurrent.slice(s![..hidden_size]))
+            .mapv(sigmoid);
+        let reset_gate = (&projected.slice(s![hidden_size..2 * hidden_size])
+            + &recurrent.slice(s![hidden_size..2 * hidden_size]))
+            .mapv(sigmoid);
+        let recurrent_candidate = recurrent.slice(s![2 * hidden_size..]).to_owned();
+        let candidate = (&projected.slice(s![2 * hidden_size..]) + &(&reset_gate * &recurrent_candidate))
+            .mapv(f64::tanh);
+
+        let next_hidden = (1.0 - &update_gate) * &candidate + &update_gate * &hidden;
+        let step = GruStep {
+            input: input.to_owned(),
+            hidden,
+            update_gate,
+            reset_gate,
+            candidate,
+            recurrent_candidate,
+        };
+        (next_hidden, step)
+    }
+
+    /// Run the sequence from the current state, returning `(steps, hidden)` outputs
+    pub fn forward_sequence(&mut self, inputs: &Array2<f64>, cache: bool) -> Array2<f64> {
+        let hidden_size = self.weights.hidden_size();
+        let mut hidden = self.state.take().unwrap_or_else(|| Array1::zeros(hidden_size));
+        let mut outputs = Array2::zeros((inputs.nrows(), hidden_size));
+        self.cache.clear();
+
+        for (t, input) in inputs.outer_iter().enumerate() {
+            let (next_hidden, step) = self.step(input, hidden);
+            outputs.row_mut(t).assign(&next_hidden);
+            if cache {
+                self.cache.push(step);
+            }
+            hidden = next_hidden;
+        }
+
+        self.state = Some(hidden);
+        outputs
+    }
+
+    /// Final hidden state of the sequence from a zero state, leaving the layer untouched
+    pub fn encode(&self, inputs: &Array2<f64>) -> Array1<f64> {
+        inputs.outer_iter().fold(Array1::zeros(self.weights.hidden_size()), |hidden, input| {
+            self.step(input, hidden).0
+        })
+    }
+
+    /// Backpropagate `(steps, hidden)` output gradients through the cached steps
+    pub fn backward_sequence(&mut self, gradients: &Array2<f64>) -> Array2<f64> {
+        let hidden_size = self.weights.hidden_size();
+        let mut input_gradients = Array2::zeros((self.cache.len(), self.weights.input_size()));
+        let mut hidden_gradient = Array1::zeros(hidden_size);
+
+        for (t, step) in self.cache.iter().enumerate().rev() {
+            let dh = &gradients.row(t) + &hidden_gradient;
+
+            let candidate_gradient = &dh * &(1.0 - &step.update_gate) * &step.candidate.mapv(|x| 1.0 - x * x);
+            let update_gradient = &dh * &(&step.hidden - &step.candidate) * &step.update_gate.mapv(|x| x * (1.0 - x));
+            let reset_gradient = &candidate_gradient * &step.recurrent_candidate
+                * &step.reset_gate.mapv(|x| x * (1.0 - x));
+
+            // Gradients of the input projection and of the recurrent projection differ
+            // only in the candidate block, where the reset gate scales the recurrent path
+            let mut projected_gradients = Array1::zeros(3 * hidden_size);
+            projected_gradients.slice_mut(s![..hidden_size]).assign(&update_gradient);
+            projected_gradients.slice_mut(s![hidden_size..2 * hidden_size]).assign(&reset_gradient);
+            projected_gradients.slice_mut(s![2 * hidden_size..]).assign(&candidate_gradient);
+            let mut recurrent_gradients = projected_gradients.clone();
+            recurrent_gradients.slice_mut(s![2 * hidden_size..])
+                .assign(&(&candidate_gradient * &step.reset_gate));
+
+            self.weights.input_gradients += &outer(&projected_gradients, &step.input.view());
+            self.weights.recurrent_gradients += &outer(&recurrent_gradients, &step.hidden.view());
+            self.weights.bias_gradients += &projected_gradients;
+
+            input_gradients.row_mut(t).assign(&self.weights.input_weights.t().dot(&projected_gradients));
+            hidden_gradient = &dh * &step.update_gate + &self.weights.recurrent_weights.t().dot(&recurrent_gradients);
+        }
+
+        input_gradients
+    }
+
+    /// Hidden state reached by the last forward call
+    pub fn hidden_state(&self) -> Array1<f64> {
+        self.state.clone().unwrap_or_else(|| Array1::zeros(self.weights.hidden_size()))
+    }
+
+    /// Forget the carried state and cached steps
+    pub fn reset_state(&mut self) {
+        self.state = None;
+        self.cache.clear();
+    }
+}
+
+/// Recurrent layer of either cell type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum RecurrentLayer {
+    Lstm(Lstm),
+    Gru(Gru),
+}
+
+impl RecurrentLayer {
+    /// Create a recurrent layer of the given kind
+    pub fn with_rng<R: Rng + ?Sized>(
+        kind: RecurrentKind,
+        input_size: usize,
+        hidden_size: usize,
+        rng: &mut R,
+    ) -> Self {
+        match kind {
+            RecurrentKind::Lstm => Self::Lstm(Lstm::with_rng(input_size, hidden_size, rng)),
+            RecurrentKind::Gru => Self::Gru(Gru::with_rng(input_size, hidden_size, rng)),
+        }
+    }
+
+    fn weights(&self) -> &GateWeights {
+        match self {
+            Self::Lstm(layer) => &layer.weights,
+            Self::Gru(layer) => &layer.weights,
+        }
+    }
+
+    fn weights_mut(&mut self) -> &mut GateWeights {
+        match self {
+            Self::Lstm(layer) => &mut layer.weights,
+            Self::Gru(layer) => &mut layer.weights,
+        }
+    }
+
+    /// Cell type
+    pub fn kind(&self) -> RecurrentKind {
+        match self {
+            Self::Lstm(_) => RecurrentKind::Lstm,
+            Self::Gru(_) => RecurrentKind::Gru,
+        }
+    }
+
+    /// Features per input step
+    pub fn input_size(&self) -> usize {
+        self.weights().input_size()
+    }
+
+    /// Width of the hidden state
+    pub fn hidden_size(&self) -> usize {
+        self.weights().hidden_size()
+    }
+
+    /// Number of trainable parameters
+    pub fn parameter_count(&self) -> usize {
+        self.weights().parameter_count()
+    }
+
+    /// Run the sequence from the current state, returning `(steps, hidden)` outputs
+    pub fn forward_sequence(&mut self, inputs: &Array2<f64>, cache: bool) -> Array2<f64> {
+        match self {
+            Self::Lstm(layer) => layer.forward_sequence(inputs, cache),
+            Self::Gru(layer) => layer.forward_sequence(inputs, cache),
+        }
+    }
+
+    /// Final hidden state of the sequence from a zero state, leaving the layer untouched
+    pub fn encode(&self, inputs: &Array2<f64>) -> Array1<f64> {
+        match self {
+            Self::Lstm(layer) => layer.encode(inputs),
+            Self::Gru(layer) => layer.encode(inputs),
+        }
+    }
+
+    /// Backpropagate `(steps, hidden)` output gradients through the cached steps
+    pub fn backward_sequence(&mut self, gradients: &Array2<f64>) -> Array2<f64> {
+        match self {
+            Self::Lstm(layer) => layer.backward_sequence(gradients),
+            Self::Gru(layer) => layer.backward_sequence(gradients),
+        }
+    }
+
+    /// Hidden state reached by the last forward call
+    pub fn hidden_state(&self) -> Array1<f64> {
+        match self {
+            Self::Lstm(layer) => layer.hidden_state(),
+            Self::Gru(layer) => layer.hidden_state(),
+        }
+    }
+
+    /// Forget the carried state and cached steps
+    pub fn reset_state(&mut self) {
+        match self {
+            Self::Lstm(layer) => layer.reset_state(),
+            Self::Gru(layer) => layer.reset_state(),
+        }
+    }
+
+    /// Reset accumulated gradients to zero
+    pub fn zero_gradients(&mut self) {
+        self.weights_mut().zero_gradients();
+    }
+
+    /// Trainable parameters paired with their accumulated gradients
+    pub fn parameters(&mut self) -> Vec<Parameter<'_>> {
+        self.weights_mut().parameters()
+    }
+
+    /// Train on a long sequence with truncated backpropagation through time
+    ///
+    /// The sequence is split into chunks of `bptt_steps`. Hidden state flows across
+    /// chunk boundaries but gradients do not. For each chunk, `loss_gradient` receives
+    /// the chunk's first step index and its `(steps, hidden)` outputs and returns the
+    /// loss and the gradient with respect to those outputs. Returns the summed loss.
+    pub fn train_truncated<F>(
+        &mut self,
+        inputs: &Array2<f64>,
+        bptt_steps: usize,
+        optimizer: &mut dyn Optimizer,
+        learning_rate: f64,
+        mut loss_gradient: F,
+    ) -> f64
+    where
+        F: FnMut(usize, &Array2<f64>) -> (f64, Array2<f64>),
+    {
+        let bptt_steps = bptt_steps.max(1);
+        let mut total_loss = 0.0;
+
+        for start in (0..inputs.nrows()).step_by(bptt_steps) {
+            let end = (start + bptt_steps).min(inputs.nrows());
+            let chunk = inputs.slice(s![start..end, ..]).to_owned();
+
+            self.zero_gradients();
+            let outputs = self.forward_sequence(&chunk, true);
+            let (loss, gradients) = loss_gradient(start, &outputs);
+            self.backward_sequence(&gradients);
+            optimizer.step(learning_rate, &mut self.parameters());
+            total_loss += loss;
+        }
+
+        total_loss
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural_engine::OptimizerKind;
+    use rand::SeedableRng;
+
+    fn sample_sequence() -> Array2<f64> {
+        Array2::from_shape_vec((4, 3), vec![
+            0.5, -1.2, 0.3,
+            1.1, 0.4, -0.7,
+            -0.3, 0.9, 1.5,
+            0.2, -0.4, 0.8,
+        ]).unwrap()
+    }
+
+    /// Compare analytic gradients of sum(outputs * weights) with finite differences
+    fn check_gradients(kind: RecurrentKind) {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+        let mut layer = RecurrentLayer::with_rng(kind, 3, 2, &mut rng);
+        let inputs = sample_sequence();
+        let output_weights = Array2::from_shape_fn((4, 2), |(i, j)| (i * 2 + j) as f64 * 0.3 - 1.0);
+
+        layer.zero_gradients();
+        layer.forward_sequence(&inputs, true);
+        let input_gradients = layer.backward_sequence(&output_weights);
+        let weight_gradients = layer.weights().recurrent_gradients.clone();
+
+        let objective = |layer: &mut RecurrentLayer, inputs: &Array2<f64>| {
+            layer.reset_state();
+            (layer.forward_sequence(inputs, false) * &output_weights).sum()
+        };
+
+        let h = 1e-6;
+        for index in 0..inputs.len() {
+            let (i, j) = (index / inputs.ncols(), index % inputs.ncols());
+            let mut plus = inputs.clone();
+            let mut minus = inputs.clone();
+            plus[[i, j]] += h;
+            minus[[i, j]] -= h;
+            let numeric = (objective(&mut layer, &plus) - objective(&mut layer, &minus)) / (2.0 * h);
+            assert!((numeric - input_gradients[[i, j]]).abs() < 1e-6, "{:?} input gradient at ({}, {})", kind, i, j);
+        }
+
+        for (index, analytic) in weight_gradients.iter().enumerate() {
+            let original = layer.weights().recurrent_weights.as_slice().unwrap()[index];
+            layer.weights_mut().recurrent_weights.as_slice_mut().unwrap()[index] = original + h;
+            let plus = objective(&mut layer, &inputs);
+            layer.weights_mut().recurrent_weights.as_slice_mut().unwrap()[index] = original - h;
+            let minus = objective(&mut layer, &inputs);
+            layer.weights_mut().recurrent_weights.as_slice_mut().unwrap()[index] = original;
+            assert!(((plus - minus) / (2.0 * h) - analytic).abs() < 1e-6, "{:?} recurrent gradient {}", kind, index);
+        }
+    }
+
+    #[test]
+    fn test_lstm_gradients() {
+        check_gradients(RecurrentKind::Lstm);
+    }
+
+    #[test]
+    fn test_gru_gradients() {
+        check_gradients(RecurrentKind::Gru);
+    }
+
+    #[test]
+    fn test_state_carries_across_calls() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(9);
+        for kind in [RecurrentKind::Lstm, RecurrentKind::Gru] {
+            let mut layer = RecurrentLayer::with_rng(kind, 3, 4, &mut rng);
+            let whole = layer.forward_sequence(&sample_sequence(), false);
+
+            layer.reset_state();
+            layer.forward_sequence(&sample_sequence().slice(s![..2, ..]).to_owned(), false);
+            let tail = layer.forward_sequence(&sample_sequence().slice(s![2.., ..]).to_owned(), false);
+
+            assert!((&whole.row(3) - &tail.row(1)).iter().all(|d| d.abs() < 1e-12));
+            assert_eq!(layer.hidden_state(), whole.row(3).to_owned());
+
+            // `encode` always starts from a zero state and leaves the carried state alone
+            assert_eq!(layer.encode(&sample_sequence()), whole.row(3).to_owned());
+            assert_eq!(layer.hidden_state(), whole.row(3).to_owned());
+        }
+    }
+
+    #[test]
+    fn test_truncated_bptt_learns_to_echo() {
+        // Output the previous step's input, which requires memory across chunk boundaries
+        let mut rng = rand::rngs::StdRng::seed_from_u64(21);
+        let inputs = Array2::from_shape_fn((64, 1), |_| if rng.gen::<bool>() { 0.5 } else { -0.5 });
+
+        for kind in [RecurrentKind::Lstm, RecurrentKind::Gru] {
+            let mut layer = RecurrentLayer::with_rng(kind, 1, 8, &mut rng);
+            let mut optimizer = OptimizerKind::adam().build(0.0);
+            let mut epoch_loss = |layer: &mut RecurrentLayer| {
+                layer.reset_state();
+                layer.train_truncated(&inputs, 8, optimizer.as_mut(), 0.01, |start, outputs| {
+                    let mut gradients = Array2::zeros(outputs.raw_dim());
+                    let mut loss = 0.0;
+                    for t in 0..outputs.nrows() {
+                        let step = start + t;
+                        let target = if step == 0 { 0.0 } else { inputs[[step - 1, 0]] };
+                        let error = outputs[[t, 0]] - target;
+                        loss += error * error;
+                        gradients[[t, 0]] = 2.0 * error;
+                    }
+                    (loss, gradients)
+                })
+            };
+
+            let initial = epoch_loss(&mut layer);
+            let mut loss = initial;
+            for _ in 0..200 {
+                loss = epoch_loss(&mut layer);
+            }
+            assert!(loss < initial * 0.2, "{:?} loss {} did not drop from {}", kind, loss, initial);
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! Residual Connections - Skip connections between non-adjacent layers
+//!
+//! A skip connection adds the output of one layer to the input of a later layer, so
+//! gradients reach early layers through the identity path as well as through every
+//! intermediate layer. Layers are indexed in evaluation order with the output layer
+//! last.
+
+use std::ops::AddAssign;
+
+use ndarray::{Array2, ArrayBase, Data, DataMut, Ix2};
+use serde::{Deserialize, Serialize};
+
+/// Output of layer `from` added to the input of layer `to`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkipConnection {
+    pub from: usize,
+    pub to: usize,
+}
+
+impl SkipConnection {
+    /// Create a skip connection
+    pub fn new(from: usize, to: usize) -> Self {
+        Self { from, to }
+    }
+}
+
+/// Check that every connection jumps forward over at least one layer and joins
+/// activations of equal width
+///
+/// `widths[i]` is the input width of layer `i`; the last entry is the network output.
+pub fn validate(skips: &[SkipConnection], widths: &[usize]) -> Result<(), String> {
+    let layer_count = widths.len() - 1;
+    for skip in skips {
+        let jumps = skip.from.checked_add(1).is_some_and(|next| next < skip.to);
+        if skip.to >= layer_count || !jumps {
+            return Err(format!(
+                "Skip connection {} -> {} must jump forward to one of {} layers",
+                skip.from, skip.to, layer_count
+            ));
+        }
+        let (source, target) = (widths[skip.from + 1], widths[skip.to]);
+        if source != target {
+            return Err(format!(
+                "Skip connection {} -> {} joins widths {} and {}",
+                skip.from, skip.to, source, target
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Whether any connection starts at `layer`
+pub(super) fn is_source(skips: &[SkipConnection], layer: usize) -> bool {
+    skips.iter().any(|skip| skip.from == layer)
+}
+
+/// Add the saved outputs of every connection ending at `layer` to its input
+pub(super) fn add_skips<A, S, T>(
+    skips: &[SkipConnection],
+    layer: usize,
+    outputs: &[Option<ArrayBase<T, Ix2>>],
+    input: &mut ArrayBase<S, Ix2>,
+) where
+    A: Clone + AddAssign,
+    S: DataMut<Elem = A>,
+    T: Data<Elem = A>,
+{
+    for skip in skips.iter().filter(|skip| skip.to == layer) {
+        *input += outputs[skip.from].as_ref().expect("skip source runs before its target");
+    }
+}
+
+/// Run `apply` over the layers in order, routing skip connections
+pub(super) fn forward<A, L>(
+    layers: &[L],
+    skips: &[SkipConnection],
+    input: &Array2<A>,
+    apply: impl Fn(&L, &Array2<A>) -> Array2<A>,
+) -> Array2<A>
+where
+    A: Clone + AddAssign,
+{
+    let mut outputs: Vec<Option<Array2<A>>> = vec![None; layers.len()];
+    let mut current: Option<Array2<A>> = None;
+    for (index, layer) in layers.iter().enumerate() {
+        // No connection ends at the first layer, so it reads the input in place
+        let output = match current.as_mut() {
+            Some(current) => {
+                add_skips(skips, index, &outputs, current);
+                apply(layer, current)
+            }
+            None => apply(layer, input),
+        };
+        if is_source(skips, index) {
+            outputs[index] = Some(output.clone());
+        }
+        current = Some(output);
+    }
+    current.unwrap_or_else(|| input.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let widths = [4, 8, 8, 8, 2];
+        assert!(validate(&[SkipConnection::new(0, 2), SkipConnection::new(0, 3)], &widths).is_ok());
+        assert!(validate(&[SkipConnection::new(0, 1)], &widths).is_err());
+        assert!(validate(&[SkipConnection::new(1, 4)], &widths).is_err());
+        assert!(validate(&[SkipConnection::new(usize::MAX, 2)], &widths).is_err());
+        assert!(validate(&[SkipConnection::new(0, 2)], &[4, 8, 6, 8, 2]).is_err());
+    }
+}
@@ -0,0 +1,306 @@
+//! Learning-Rate Schedulers - Epoch-based learning rate policies
+//!
+//! A scheduler maps the base learning rate of an architecture to the rate used for a
+//! given epoch. Schedulers may keep state (e.g. plateau detection) and are consulted
+//! once per epoch by the engine's training loop and by `optimize`.
+
+use serde::{Deserialize, Serialize};
+
+/// Epoch-based learning rate policy
+pub trait LrScheduler: Send + Sync {
+    /// Learning rate for `epoch`, given the base rate and the loss of the previous epoch
+    fn learning_rate(&mut self, epoch: usize, base_lr: f64, last_loss: Option<f64>) -> f64;
+
+    /// Scheduler name for logging and statistics
+    fn name(&self) -> &'static str;
+
+    /// Discard all accumulated state
+    fn reset(&mut self);
+
+    /// Clone into a new boxed scheduler
+    fn box_clone(&self) -> Box<dyn LrScheduler>;
+}
+
+/// Scheduler selection for a `NeuralArchitecture`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SchedulerKind {
+    /// Keep the base learning rate
+    Constant,
+    /// Multiply the rate by `gamma` every `step_size` epochs, never going below `min_lr`
+    StepDecay {
+        step_size: usize,
+        gamma: f64,
+        min_lr: f64,
+    },
+    /// Cosine curve from the base rate down to `min_lr` over `period` epochs, then restart
+    CosineAnnealing {
+        period: usize,
+        min_lr: f64,
+    },
+    /// Linear ramp up over `warmup_epochs`, then linear decay to zero at `total_epochs`
+    WarmupLinear {
+        warmup_epochs: usize,
+        total_epochs: usize,
+    },
+    /// Multiply the rate by `factor` once the loss stops improving for `patience` epochs
+    ReduceOnPlateau {
+        factor: f64,
+        patience: usize,
+        threshold: f64,
+        min_lr: f64,
+    },
+}
+
+impl Default for SchedulerKind {
+    fn default() -> Self {
+        // 5% decay per epoch down to 1e-4
+        Self::StepDecay { step_size: 1, gamma: 0.95, min_lr: 0.0001 }
+    }
+}
+
+impl SchedulerKind {
+    /// Reduce-on-plateau with the usual default hyperparameters
+    pub fn reduce_on_plateau() -> Self {
+        Self::ReduceOnPlateau { factor: 0.1, patience: 10, threshold: 1e-4, min_lr: 0.0 }
+    }
+
+    /// Instantiate the scheduler
+    pub fn build(&self) -> Box<dyn LrScheduler> {
+        match *self {
+            Self::Constant => Box::new(Constant),
+            Self::StepDecay { step_size, gamma, min_lr } => Box::new(StepDecay::new(step_size, gamma, min_lr)),
+            Self::CosineAnnealing { period, min_lr } => Box::new(CosineAnnealing::new(period, min_lr)),
+            Self::WarmupLinear { warmup_epochs, total_epochs } => {
+                Box::new(WarmupLinear::new(warmup_epochs, total_epochs))
+            }
+            Self::ReduceOnPlateau { factor, patience, threshold, min_lr } => {
+                Box::new(ReduceOnPlateau::new(factor, patience, threshold, min_lr))
+            }
+        }
+    }
+}
+
+/// Constant learning rate
+#[derive(Debug, Clone)]
+pub struct Constant;
+
+impl LrScheduler for Constant {
+    fn learning_rate(&mut self, _epoch: usize, base_lr: f64, _last_loss: Option<f64>) -> f64 {
+        base_lr
+    }
+
+    fn name(&self) -> &'static str {
+        "constant"
+    }
+
+    fn reset(&mut self) {}
+
+    fn box_clone(&self) -> Box<dyn LrScheduler> {
+        Box::new(self.clone())
+    }
+}
+
+/// Step decay
+#[derive(Debug, Clone)]
+pub struct StepDecay {
+    step_size: usize,
+    gamma: f64,
+    min_lr: f64,
+}
+
+impl StepDecay {
+    /// Create a step decay scheduler
+    pub fn new(step_size: usize, gamma: f64, min_lr: f64) -> Self {
+        Self { step_size: step_size.max(1), gamma, min_lr }
+    }
+}
+
+impl LrScheduler for StepDecay {
+    fn learning_rate(&mut self, epoch: usize, base_lr: f64, _last_loss: Option<f64>) -> f64 {
+        let steps = (epoch / self.step_size) as i32;
+        (base_lr * self.gamma.powi(steps)).max(self.min_lr.min(base_lr))
+    }
+
+    fn name(&self) -> &'static str {
+        "step_decay"
+    }
+
+    fn reset(&mut self) {}
+
+    fn box_clone(&self) -> Box<dyn LrScheduler> {
+        Box::new(self.clone())
+    }
+}
+
+/// Cosine annealing with warm restarts
+#[derive(Debug, Clone)]
+pub struct CosineAnnealing {
+    period: usize,
+    min_lr: f64,
+}
+
+impl CosineAnnealing {
+    /// Create a cosine annealing scheduler
+    pub fn new(period: usize, min_lr: f64) -> Self {
+        Self { period: period.max(1), min_lr }
+    }
+}
+
+impl LrScheduler for CosineAnnealing {
+    fn learning_rate(&mut self, epoch: usize, base_lr: f64, _last_loss: Option<f64>) -> f64 {
+        let progress = (epoch % self.period) as f64 / self.period as f64;
+        self.min_lr + 0.5 * (base_lr - self.min_lr) * (1.0 + (std::f64::consts::PI * progress).cos())
+    }
+
+    fn name(&self) -> &'static str {
+        "cosine_annealing"
+    }
+
+    fn reset(&mut self) {}
+
+    fn box_clone(&self) -> Box<dyn LrScheduler> {
+        Box::new(self.clone())
+    }
+}
+
+/// Linear warmup followed by linear decay
+#[derive(Debug, Clone)]
+pub struct WarmupLinear {
+    warmup_epochs: usize,
+    total_epochs: usize,
+}
+
+impl WarmupLinear {
+    /// Create a warmup/linear-decay scheduler
+    pub fn new(warmup_epochs: usize, total_epochs: usize) -> Self {
+        Self { warmup_epochs, total_epochs: total_epochs.max(warmup_epochs + 1) }
+    }
+}
+
+impl LrScheduler for WarmupLinear {
+    fn learning_rate(&mut self, epoch: usize, base_lr: f64, _last_loss: Option<f64>) -> f64 {
+        if epoch < self.warmup_epochs {
+            base_lr * (epoch + 1) as f64 / (self.warmup_epochs + 1) as f64
+        } else {
+            let remaining = self.total_epochs.saturating_sub(epoch) as f64;
+            base_lr * remaining / (self.total_epochs - self.warmup_epochs) as f64
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "warmup_linear"
+    }
+
+    fn reset(&mut self) {}
+
+    fn box_clone(&self) -> Box<dyn LrScheduler> {
+        Box::new(self.clone())
+    }
+}
+
+/// Reduce the learning rate when the loss plateaus
+#[derive(Debug, Clone)]
+pub struct ReduceOnPlateau {
+    factor: f64,
+    patience: usize,
+    threshold: f64,
+    min_lr: f64,
+    best_loss: Option<f64>,
+    stale_epochs: usize,
+    scale: f64,
+}
+
+impl ReduceOnPlateau {
+    /// Create a reduce-on-plateau scheduler
+    pub fn new(factor: f64, patience: usize, threshold: f64, min_lr: f64) -> Self {
+        Self {
+            factor,
+            patience,
+            threshold,
+            min_lr,
+            best_loss: None,
+            stale_epochs: 0,
+            scale: 1.0,
+        }
+    }
+}
+
+impl LrScheduler for ReduceOnPlateau {
+    fn learning_rate(&mut self, _epoch: usize, base_lr: f64, last_loss: Option<f64>) -> f64 {
+        if let Some(loss) = last_loss {
+            match self.best_loss {
+                Some(best) if loss >= best - self.threshold => {
+                    self.stale_epochs += 1;
+                    if self.stale_epochs > self.patience {
+                        self.scale *= self.factor;
+                        self.stale_epochs = 0;
+                    }
+                }
+                _ => {
+                    self.best_loss = Some(loss);
+                    self.stale_epochs = 0;
+                }
+            }
+        }
+
+        (base_lr * self.scale).max(self.min_lr.min(base_lr))
+    }
+
+    fn name(&self) -> &'static str {
+        "reduce_on_plateau"
+    }
+
+    fn reset(&mut self) {
+        self.best_loss = None;
+        self.stale_epochs = 0;
+        self.scale = 1.0;
+    }
+
+    fn box_clone(&self) -> Box<dyn LrScheduler> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_decay() {
+        let mut scheduler = SchedulerKind::StepDecay { step_size: 2, gamma: 0.5, min_lr: 0.1 }.build();
+        assert_eq!(scheduler.learning_rate(0, 1.0, None), 1.0);
+        assert_eq!(scheduler.learning_rate(1, 1.0, None), 1.0);
+        assert_eq!(scheduler.learning_rate(2, 1.0, None), 0.5);
+        assert_eq!(scheduler.learning_rate(20, 1.0, None), 0.1);
+    }
+
+    #[test]
+    fn test_cosine_annealing_restarts() {
+        let mut scheduler = SchedulerKind::CosineAnnealing { period: 10, min_lr: 0.0 }.build();
+        assert!((scheduler.learning_rate(0, 1.0, None) - 1.0).abs() < 1e-12);
+        assert!((scheduler.learning_rate(5, 1.0, None) - 0.5).abs() < 1e-12);
+        assert!(scheduler.learning_rate(9, 1.0, None) < 0.05);
+        assert!((scheduler.learning_rate(10, 1.0, None) - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_warmup_linear() {
+        let mut scheduler = SchedulerKind::WarmupLinear { warmup_epochs: 3, total_epochs: 7 }.build();
+        let rates: Vec<f64> = (0..8).map(|epoch| scheduler.learning_rate(epoch, 1.0, None)).collect();
+        assert!(rates[..4].windows(2).all(|w| w[0] < w[1]));
+        assert!(rates[3..].windows(2).all(|w| w[0] > w[1]));
+        assert_eq!(rates[7], 0.0);
+    }
+
+    #[test]
+    fn test_reduce_on_plateau() {
+        let mut scheduler = SchedulerKind::ReduceOnPlateau { factor: 0.5, patience: 1, threshold: 0.0, min_lr: 0.0 }.build();
+        assert_eq!(scheduler.learning_rate(0, 1.0, Some(1.0)), 1.0);
+        assert_eq!(scheduler.learning_rate(1, 1.0, Some(0.9)), 1.0);
+        assert_eq!(scheduler.learning_rate(2, 1.0, Some(0.9)), 1.0);
+        assert_eq!(scheduler.learning_rate(3, 1.0, Some(0.95)), 0.5);
+
+        scheduler.reset();
+        assert_eq!(scheduler.learning_rate(4, 1.0, None), 1.0);
+    }
+}
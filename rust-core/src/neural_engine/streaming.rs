@@ -0,0 +1,140 @@
+//! Streaming - Chunked processing of long inputs
+//!
+//! Long token sequences are split into overlapping windows, each window is processed
+//! as an independent input, and the per-chunk responses are pooled with softmax
+//! attention: every chunk output is scored against the mean output, so chunks that
+//! agree with the overall signal dominate while outliers are damped.
+
+use std::ops::Range;
+
+use ndarray::Array1;
+
+use super::NeuralResponse;
+
+/// How `NeuralFoundationEngine::process_stream` windows its input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamOptions {
+    /// Tokens per chunk
+    pub window: usize,
+    /// Tokens shared by consecutive chunks; must be smaller than `window`
+    pub overlap: usize,
+    /// Process chunks in parallel rather than one after another
+    pub parallel: bool,
+}
+
+impl StreamOptions {
+    /// Sequential processing with the given window and overlap
+    pub fn new(window: usize, overlap: usize) -> Self {
+        Self { window, overlap, parallel: false }
+    }
+
+    /// Process chunks in parallel
+    pub fn with_parallel(mut self) -> Self {
+        self.parallel = true;
+        self
+    }
+
+    /// Token ranges of the chunks covering `len` tokens
+    ///
+    /// Inputs no longer than one window yield a single chunk, possibly empty.
+    pub fn chunks(&self, len: usize) -> Result<Vec<Range<usize>>, String> {
+        if self.window == 0 || self.overlap >= self.window {
+            return Err(format!(
+                "Stream window {} must be positive and larger than the overlap {}",
+                self.window, self.overlap
+            ));
+        }
+        let stride = self.window - self.overlap;
+        let mut chunks = Vec::new();
+        let mut start = 0;
+        loop {
+            let end = (start + self.window).min(len);
+            chunks.push(start..end);
+            if end >= len {
+                return Ok(chunks);
+            }
+            start += stride;
+        }
+    }
+}
+
+impl Default for StreamOptions {
+    fn default() -> Self {
+        Self::new(256, 32)
+    }
+}
+
+/// Pooled response of a chunked input
+#[derive(Debug, Clone)]
+pub struct StreamResponse {
+    pub response: NeuralResponse,
+    /// Number of chunks consumed
+    pub chunks: usize,
+    /// Attention weight given to each chunk, summing to one
+    pub chunk_weights: Vec<f64>,
+}
+
+/// Softmax attention weights of each output scored against their mean
+pub(super) fn attention_weights(outputs: &[Array1<f64>]) -> Vec<f64> {
+    let Some(first) = outputs.first() else { return Vec::new() };
+    let mut query = Array1::zeros(first.len());
+    for output in outputs {
+        query += output;
+    }
+    query /= outputs.len() as f64;
+
+    let scale = (first.len().max(1) as f64).sqrt();
+    let scores: Vec<f64> = outputs.iter().map(|output| output.dot(&query) / scale).collect();
+    let max = scores.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let exps: Vec<f64> = scores.iter().map(|score| (score - max).exp()).collect();
+    let total: f64 = exps.iter().sum();
+    exps.iter().map(|e| e / total).collect()
+}
+
+/// Combine chunk responses using `weights`
+pub(super) fn pool(responses: &[NeuralResponse], weights: &[f64]) -> NeuralResponse {
+    let mut output = Array1::zeros(responses[0].output.len());
+    let (mut activation_strength, mut pattern_confidence, mut coherence_score) = (0.0, 0.0, 0.0);
+    for (response, &weight) in responses.iter().zip(weights) {
+        output.scaled_add(weight, &response.output);
+        activation_strength += weight * response.activation_strength;
+        pattern_confidence += weight * response.pattern_confidence;
+        coherence_score += weight * response.coherence_score;
+    }
+    NeuralResponse {
+        output,
+        activation_strength,
+        pattern_confidence,
+        coherence_score,
+        network_count: responses[0].network_count,
+        head: responses[0].head,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunks_overlap_and_cover() {
+        let options = StreamOptions::new(4, 1);
+        assert_eq!(options.chunks(10).unwrap(), vec![0..4, 3..7, 6..10]);
+        assert_eq!(options.chunks(11).unwrap(), vec![0..4, 3..7, 6..10, 9..11]);
+        assert_eq!(options.chunks(3).unwrap(), vec![0..3]);
+        assert_eq!(options.chunks(0).unwrap(), vec![0..0]);
+        assert!(StreamOptions::new(4, 4).chunks(10).is_err());
+    }
+
+    #[test]
+    fn test_attention_weights_favor_agreement() {
+        let outputs = vec![
+            Array1::from(vec![1.0, 1.0]),
+            Array1::from(vec![1.0, 1.0]),
+            Array1::from(vec![-1.0, -1.0]),
+        ];
+        let weights = attention_weights(&outputs);
+        assert!((weights.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+        assert!(weights[0] > weights[2]);
+        assert_eq!(weights[0], weights[1]);
+    }
+}
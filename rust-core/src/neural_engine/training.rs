@@ -0,0 +1,72 @@
+//! Training Safeguards - Gradient clipping and numeric checks
+//!
+//! Training steps verify that activations, gradients and losses stay finite and
+//! report the first offending layer instead of writing NaN into the weights.
+
+use thiserror::Error;
+
+use super::optimizer::Parameter;
+
+/// Failure of a training step; parameters are left untouched when one is returned
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum TrainingError {
+    #[error("non-finite activation produced by layer {layer}")]
+    NonFiniteActivation { layer: usize },
+    #[error("non-finite gradient in layer {layer}")]
+    NonFiniteGradient { layer: usize },
+    #[error("non-finite loss {loss}")]
+    NonFiniteLoss { loss: f64 },
+    #[error("shape mismatch: {0}")]
+    ShapeMismatch(String),
+    #[error("invalid architecture: {0}")]
+    InvalidArchitecture(String),
+}
+
+/// Whether every value is neither NaN nor infinite
+pub fn all_finite<'a, I: IntoIterator<Item = &'a f64>>(values: I) -> bool {
+    values.into_iter().all(|value| value.is_finite())
+}
+
+/// Global L2 norm of all parameter gradients
+pub fn gradient_norm(parameters: &[Parameter<'_>]) -> f64 {
+    parameters.iter()
+        .flat_map(|parameter| parameter.gradients.iter())
+        .map(|gradient| gradient * gradient)
+        .sum::<f64>()
+        .sqrt()
+}
+
+/// Rescale gradients so their global L2 norm is at most `max_norm`
+///
+/// Returns the norm before clipping.
+pub fn clip_gradient_norm(parameters: &mut [Parameter<'_>], max_norm: f64) -> f64 {
+    let norm = gradient_norm(parameters);
+    if norm > max_norm && norm > 0.0 {
+        let scale = max_norm / norm;
+        for parameter in parameters.iter_mut() {
+            for gradient in parameter.gradients.iter_mut() {
+                *gradient *= scale;
+            }
+        }
+    }
+    norm
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clip_gradient_norm() {
+        let mut values = vec![0.0; 2];
+        let mut gradients = vec![3.0, 4.0];
+        let mut parameters = [Parameter { values: &mut values, gradients: &mut gradients }];
+
+        assert_eq!(clip_gradient_norm(&mut parameters, 10.0), 5.0);
+        assert_eq!(parameters[0].gradients, &[3.0, 4.0]);
+
+        assert_eq!(clip_gradient_norm(&mut parameters, 1.0), 5.0);
+        assert!((gradient_norm(&parameters) - 1.0).abs() < 1e-12);
+        assert!((parameters[0].gradients[0] - 0.6).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,316 @@
+//! Tuner - Hyperparameter search over network architectures
+//!
+//! Candidates are drawn from a `SearchSpace` either exhaustively (grid) or by random
+//! sampling, trained in parallel with `NeuralNetwork::fit_with` and ranked by their
+//! final validation loss.
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rayon::prelude::*;
+
+use super::dataset::{Dataset, FitOptions};
+use super::training::TrainingError;
+use super::{ActivationFunction, NeuralArchitecture, NeuralNetwork};
+
+/// Values tried for each tuned `NeuralArchitecture` field
+///
+/// An empty list keeps the base architecture's value.
+#[derive(Debug, Clone, Default)]
+pub struct SearchSpace {
+    pub hidden_layers: Vec<Vec<usize>>,
+    pub learning_rates: Vec<f64>,
+    pub activations: Vec<ActivationFunction>,
+}
+
+impl SearchSpace {
+    /// Every combination of the listed values
+    fn grid(&self, base: &NeuralArchitecture) -> Vec<NeuralArchitecture> {
+        let hidden_layers = or_base(&self.hidden_layers, &base.hidden_layers);
+        let learning_rates = or_base(&self.learning_rates, &base.learning_rate);
+        let activations = or_base(&self.activations, &base.activation_function);
+
+        let mut candidates = Vec::new();
+        for hidden in &hidden_layers {
+            for &learning_rate in &learning_rates {
+                for activation in &activations {
+                    candidates.push(candidate(base, hidden, learning_rate, activation));
+                }
+            }
+        }
+        candidates
+    }
+
+    /// `trials` independent draws, one value per field
+    fn sample(&self, base: &NeuralArchitecture, trials: usize, rng: &mut StdRng) -> Vec<NeuralArchitecture> {
+        let hidden_layers = or_base(&self.hidden_layers, &base.hidden_layers);
+        let learning_rates = or_base(&self.learning_rates, &base.learning_rate);
+        let activations = or_base(&self.activations, &base.activation_function);
+
+        (0..trials)
+            .map(|_| {
+                candidate(
+                    base,
+                    hidden_layers.choose(rng).expect("non-empty"),
+                    *learning_rates.choose(rng).expect("non-empty"),
+                    activations.choose(rng).expect("non-empty"),
+                )
+            })
+            .collect()
+    }
+}
+
+fn or_base<T: Clone>(values: &[T], base: &T) -> Vec<T> {
+    if values.is_empty() { vec![base.clone()] } else { values.to_vec() }
+}
+
+fn candidate(
+    base: &NeuralArchitecture,
+    hidden_layers: &[usize],
+    learning_rate: f64,
+    activation: &ActivationFunction,
+) -> NeuralArchitecture {
+    NeuralArchitecture {
+        hidden_layers: hidden_layers.to_vec(),
+        learning_rate,
+        activation_function: activation.clone(),
+        ..base.clone()
+    }
+}
+
+/// How candidates are drawn from the search space
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SearchStrategy {
+    /// Every combination
+    Grid,
+    /// A fixed number of random combinations, reproducible through the seed
+    Random { trials: usize, seed: u64 },
+}
+
+/// Outcome of training one candidate
+#[derive(Debug, Clone)]
+pub struct TrialResult {
+    pub architecture: NeuralArchitecture,
+    /// Validation loss after the last epoch; infinite if training failed
+    pub validation_loss: f64,
+    pub validation_accuracy: f64,
+    pub epochs: usize,
+    pub stopped_early: bool,
+    pub error: Option<TrainingError>,
+}
+
+/// Trials ranked from lowest to highest validation loss
+#[derive(Debug, Clone, Default)]
+pub struct TuningResults {
+    pub trials: Vec<TrialResult>,
+}
+
+impl TuningResults {
+    /// Best trial, if any trained successfully
+    pub fn best(&self) -> Option<&TrialResult> {
+        self.trials.first().filter(|trial| trial.error.is_none())
+    }
+
+    /// Architecture of the best trial
+    pub fn best_architecture(&self) -> Option<&NeuralArchitecture> {
+        self.best().map(|trial| &trial.architecture)
+    }
+
+    /// Plain-text results table, one row per trial in rank order
+    pub fn table(&self) -> String {
+        let mut table = format!(
+            "{:>4}  {:<16}  {:>10}  {:<10}  {:>12}  {:>8}  {:>6}\n",
+            "rank", "hidden", "lr", "activation", "val_loss", "val_acc", "epochs"
+        );
+        for (rank, trial) in self.trials.iter().enumerate() {
+            table.push_str(&format!(
+                "{:>4}  {:<16}  {:>10.2e}  {:<10}  {:>12.6}  {:>8.3}  {:>6}{}\n",
+                rank + 1,
+                format!("{:?}", trial.architecture.hidden_layers),
+                trial.architecture.learning_rate,
+                format!("{:?}", trial.architecture.activation_function),
+                trial.validation_loss,
+                trial.validation_accuracy,
+                trial.epochs,
+                if trial.stopped_early { "*" } else { "" },
+            ));
+        }
+        table
+    }
+}
+
+/// Hyperparameter search over variations of a base architecture
+#[derive(Debug, Clone)]
+pub struct Tuner {
+    pub base: NeuralArchitecture,
+    pub space: SearchSpace,
+    pub strategy: SearchStrategy,
+    pub epochs: usize,
+    pub batch_size: usize,
+    /// Early stopping `(patience, min_delta)` on the validation loss
+    pub early_stopping: Option<(usize, f64)>,
+}
+
+impl Tuner {
+    /// Grid search training each candidate for up to `epochs` epochs
+    pub fn new(base: NeuralArchitecture, space: SearchSpace, epochs: usize, batch_size: usize) -> Self {
+        Self {
+            base,
+            space,
+            strategy: SearchStrategy::Grid,
+            epochs,
+            batch_size,
+            early_stopping: Some((10, 1e-6)),
+        }
+    }
+
+    /// Sample `trials` random candidates instead of the full grid
+    pub fn with_random_search(mut self, trials: usize, seed: u64) -> Self {
+        self.strategy = SearchStrategy::Random { trials, seed };
+        self
+    }
+
+    /// Candidate architectures in trial order
+    pub fn candidates(&self) -> Vec<NeuralArchitecture> {
+        match self.strategy {
+            SearchStrategy::Grid => self.space.grid(&self.base),
+            SearchStrategy::Random { trials, seed } => {
+                self.space.sample(&self.base, trials, &mut StdRng::seed_from_u64(seed))
+            }
+        }
+    }
+
+    /// Train every candidate in parallel and rank them by validation loss
+    pub fn run<D: Dataset + Sync + ?Sized>(&self, train: &D, validation: &D) -> TuningResults {
+        let mut trials: Vec<TrialResult> = self.candidates()
+            .into_par_iter()
+            .map(|architecture| self.trial(architecture, train, validation))
+            .collect();
+        trials.sort_by(|a, b| a.validation_loss.total_cmp(&b.validation_loss));
+        TuningResults { trials }
+    }
+
+    fn trial<D: Dataset + Sync + ?Sized>(&self, architecture: NeuralArchitecture, train: &D, validation: &D) -> TrialResult {
+        // A candidate's widths may no longer fit the base architecture's skip connections
+        let mut network = match NeuralNetwork::try_new(architecture.clone()) {
+            Ok(network) => network,
+            Err(error) => return TrialResult::failed(architecture, TrainingError::InvalidArchitecture(error.to_string())),
+        };
+        let mut options = FitOptions::new(self.epochs, self.batch_size);
+        if let Some((patience, min_delta)) = self.early_stopping {
+            options = options.with_early_stopping(patience, min_delta);
+        }
+        let validation_view: &dyn Dataset = &DatasetRef(validation);
+        options = options.with_validation(validation_view);
+
+        match network.fit_with(train, options) {
+            Ok(history) => {
+                let last = history.last();
+                TrialResult {
+                    architecture,
+                    validation_loss: last.and_then(|m| m.validation_loss).unwrap_or(f64::INFINITY),
+                    validation_accuracy: last.and_then(|m| m.validation_accuracy).unwrap_or(0.0),
+                    epochs: history.epochs.len(),
+                    stopped_early: history.stopped_early,
+                    error: None,
+                }
+            }
+            Err(error) => TrialResult::failed(architecture, error),
+        }
+    }
+}
+
+impl TrialResult {
+    fn failed(architecture: NeuralArchitecture, error: TrainingError) -> Self {
+        Self {
+            architecture,
+            validation_loss: f64::INFINITY,
+            validation_accuracy: 0.0,
+            epochs: 0,
+            stopped_early: false,
+            error: Some(error),
+        }
+    }
+}
+
+/// Sized view of a possibly unsized dataset, so it can be used as `&dyn Dataset`
+struct DatasetRef<'a, D: ?Sized>(&'a D);
+
+impl<D: Dataset + ?Sized> Dataset for DatasetRef<'_, D> {
+    fn inputs(&self) -> ndarray::ArrayView2<'_, f64> {
+        self.0.inputs()
+    }
+
+    fn targets(&self) -> ndarray::ArrayView2<'_, f64> {
+        self.0.targets()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural_engine::{InMemoryDataset, SkipConnection};
+    use ndarray::Array2;
+
+    #[test]
+    fn test_grid_and_random_candidates() {
+        let space = SearchSpace {
+            hidden_layers: vec![vec![4], vec![8, 4]],
+            learning_rates: vec![0.1, 0.01, 0.001],
+            activations: Vec::new(),
+        };
+        let tuner = Tuner::new(NeuralArchitecture::default(), space, 1, 1);
+        let grid = tuner.candidates();
+        assert_eq!(grid.len(), 6);
+        assert!(grid.iter().all(|a| a.activation_function == ActivationFunction::Swish));
+
+        let random = tuner.clone().with_random_search(4, 9);
+        assert_eq!(random.candidates().len(), 4);
+        let again: Vec<f64> = random.candidates().iter().map(|a| a.learning_rate).collect();
+        assert_eq!(random.candidates().iter().map(|a| a.learning_rate).collect::<Vec<_>>(), again);
+    }
+
+    #[test]
+    fn test_search_ranks_trials() {
+        let inputs = Array2::from_shape_vec((4, 2), vec![0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 1.0, 1.0]).unwrap();
+        let targets = Array2::from_shape_vec((4, 1), vec![0.0, 1.0, 1.0, 0.0]).unwrap();
+        let dataset = InMemoryDataset::new(inputs, targets).unwrap();
+
+        let base = NeuralArchitecture {
+            input_size: 2,
+            hidden_layers: vec![8],
+            output_size: 1,
+            activation_function: ActivationFunction::Sigmoid,
+            seed: Some(7),
+            ..NeuralArchitecture::default()
+        };
+        let space = SearchSpace { learning_rates: vec![0.0, 0.5], ..SearchSpace::default() };
+        let results = Tuner::new(base, space, 2000, 2).run(&dataset, &dataset);
+
+        assert_eq!(results.trials.len(), 2);
+        assert_eq!(results.best_architecture().unwrap().learning_rate, 0.5);
+        assert!(results.trials[1].stopped_early);
+        assert_eq!(results.table().lines().count(), 3);
+    }
+
+    #[test]
+    fn test_candidates_breaking_skip_connections_fail_their_trial() {
+        let dataset = InMemoryDataset::new(Array2::zeros((4, 2)), Array2::zeros((4, 1))).unwrap();
+        let base = NeuralArchitecture {
+            input_size: 2,
+            hidden_layers: vec![8, 8, 8],
+            output_size: 1,
+            skip_connections: vec![SkipConnection::new(0, 2)],
+            seed: Some(3),
+            ..NeuralArchitecture::default()
+        };
+        let space = SearchSpace { hidden_layers: vec![vec![8, 8, 8], vec![8, 4, 8]], ..SearchSpace::default() };
+        let results = Tuner::new(base, space, 2, 2).run(&dataset, &dataset);
+
+        assert_eq!(results.trials.len(), 2);
+        assert!(results.trials[0].validation_loss.is_finite());
+        let failed = &results.trials[1];
+        assert_eq!((failed.architecture.hidden_layers.clone(), failed.validation_loss), (vec![8, 4, 8], f64::INFINITY));
+        assert!(matches!(&failed.error, Some(TrainingError::InvalidArchitecture(message)) if message.contains("joins widths")));
+    }
+}
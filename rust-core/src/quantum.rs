@@ -0,0 +1,698 @@
+//! Quantum Module - Quantum randomness and state-vector simulation
+//!
+//! This module provides a QRNG-style entropy source for the stochastic components of
+//! the AGI system (weight initialization, dropout, measurement sampling). Entropy comes
+//! from a pluggable `EntropySource`: a simulated quantum register by default, real
+//! hardware through a user implementation, or a seeded deterministic source for tests.
+//! It also hosts the hybrid quantum-classical stage used by `AGISystem`.
+
+use std::sync::Mutex;
+use ndarray::Array1;
+use num_complex::Complex64;
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument};
+
+use crate::error::AgiError;
+
+/// Number of bytes drawn from the entropy source to seed the output generator
+const SEED_BYTES: usize = 32;
+
+/// Default number of output bytes generated between reseeds
+const DEFAULT_RESEED_INTERVAL: usize = 1 << 16;
+
+/// Source of raw entropy bytes
+///
+/// Implement this trait to back `QuantumRng` with a hardware QRNG device,
+/// reporting device failures as `AgiError::External`.
+pub trait EntropySource: Send + Sync {
+    /// Fill `dest` with random bytes
+    fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), AgiError>;
+
+    /// Human-readable source name
+    fn name(&self) -> &str;
+}
+
+/// Entropy source that measures a simulated quantum register
+///
+/// Each shot prepares `qubits` qubits in |0⟩, rotates them with `RY(θ)` and measures
+/// them in the computational basis. An ideal device uses θ = π/2 (a Hadamard-like
+/// superposition); other angles model a biased device, whose raw bits are passed through
+/// a von Neumann extractor so the output stays unbiased.
+pub struct SimulatedQuantumSource {
+    qubits: usize,
+    rotation: f64,
+    measurement_rng: StdRng,
+    pending_bits: Vec<bool>,
+    shots: u64,
+}
+
+impl SimulatedQuantumSource {
+    /// Create an ideal simulated source with an 8-qubit register
+    pub fn new() -> Self {
+        Self::with_rotation(8, std::f64::consts::FRAC_PI_2)
+    }
+
+    /// Create a simulated source with a custom register size and rotation angle
+    pub fn with_rotation(qubits: usize, rotation: f64) -> Self {
+        Self {
+            qubits: qubits.clamp(1, 16),
+            rotation,
+            measurement_rng: StdRng::from_entropy(),
+            pending_bits: Vec::new(),
+            shots: 0,
+        }
+    }
+
+    /// Make the simulated measurements deterministic (for tests)
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.measurement_rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    /// Number of register measurements performed so far
+    pub fn shots(&self) -> u64 {
+        self.shots
+    }
+
+    /// Run one shot of the circuit and append the debiased bits
+    fn measure_shot(&mut self) {
+        let mut register = StateVector::new(self.qubits);
+        for qubit in 0..self.qubits {
+            register.ry(qubit, self.rotation);
+        }
+        let outcome = register.measure(&mut self.measurement_rng);
+        self.shots += 1;
+
+        // Von Neumann extractor over adjacent bit pairs
+        for pair in 0..self.qubits / 2 {
+            let first = (outcome >> (2 * pair)) & 1 == 1;
+            let second = (outcome >> (2 * pair + 1)) & 1 == 1;
+            if first != second {
+                self.pending_bits.push(first);
+            }
+        }
+    }
+}
+
+impl Default for SimulatedQuantumSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EntropySource for SimulatedQuantumSource {
+    fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), AgiError> {
+        if self.qubits < 2 {
+            return Err(AgiError::Quantum("Simulated source needs at least 2 qubits for debiasing".to_string()));
+        }
+        if self.rotation.sin().abs() < 1e-6 {
+            return Err(AgiError::Quantum("Rotation angle produces no superposition".to_string()));
+        }
+
+        for byte in dest.iter_mut() {
+            while self.pending_bits.len() < 8 {
+                self.measure_shot();
+            }
+            *byte = self.pending_bits.drain(..8)
+                .fold(0u8, |acc, bit| (acc << 1) | bit as u8);
+        }
+
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "simulated-quantum"
+    }
+}
+
+/// Deterministic entropy source for reproducible tests
+pub struct SeededSource {
+    rng: StdRng,
+}
+
+impl SeededSource {
+    /// Create a seeded source
+    pub fn new(seed: u64) -> Self {
+        Self { rng: StdRng::seed_from_u64(seed) }
+    }
+}
+
+impl EntropySource for SeededSource {
+    fn fill_entropy(&mut self, dest: &mut [u8]) -> Result<(), AgiError> {
+        self.rng.fill_bytes(dest);
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "seeded"
+    }
+}
+
+/// Random number generator backed by an entropy source
+///
+/// Raw entropy is expensive (especially from hardware), so it seeds a fast
+/// cryptographic generator that is reseeded every `reseed_interval` output bytes.
+/// Implements `RngCore`, so it can be passed anywhere a `rand` RNG is accepted.
+pub struct QuantumRng {
+    source: Box<dyn EntropySource>,
+    generator: StdRng,
+    reseed_interval: usize,
+    bytes_since_reseed: usize,
+}
+
+impl QuantumRng {
+    /// Create a generator from any entropy source
+    pub fn new(mut source: Box<dyn EntropySource>) -> Result<Self, AgiError> {
+        let generator = Self::draw_generator(source.as_mut())
+            .map_err(|e| AgiError::Quantum(format!("Entropy source '{}' failed: {}", source.name(), e)))?;
+
+        info!("Quantum RNG initialized from {} source", source.name());
+
+        Ok(Self {
+            source,
+            generator,
+            reseed_interval: DEFAULT_RESEED_INTERVAL,
+            bytes_since_reseed: 0,
+        })
+    }
+
+    /// Create a generator backed by the quantum register simulator
+    pub fn from_simulator() -> Result<Self, AgiError> {
+        Self::new(Box::new(SimulatedQuantumSource::new()))
+    }
+
+    /// Create a deterministic generator for tests
+    pub fn seeded(seed: u64) -> Self {
+        let mut source = SeededSource::new(seed);
+        let generator = Self::draw_generator(&mut source)
+            .expect("Seeded source cannot fail");
+
+        Self {
+            source: Box::new(source),
+            generator,
+            reseed_interval: DEFAULT_RESEED_INTERVAL,
+            bytes_since_reseed: 0,
+        }
+    }
+
+    /// Set how many output bytes are produced between reseeds
+    pub fn with_reseed_interval(mut self, bytes: usize) -> Self {
+        self.reseed_interval = bytes.max(1);
+        self
+    }
+
+    /// Name of the underlying entropy source
+    pub fn source_name(&self) -> &str {
+        self.source.name()
+    }
+
+    /// Reseed the output generator from the entropy source
+    pub fn reseed(&mut self) -> Result<(), AgiError> {
+        self.generator = Self::draw_generator(self.source.as_mut())?;
+        self.bytes_since_reseed = 0;
+        Ok(())
+    }
+
+    fn draw_generator(source: &mut dyn EntropySource) -> Result<StdRng, AgiError> {
+        let mut seed = [0u8; SEED_BYTES];
+        source.fill_entropy(&mut seed)?;
+        Ok(StdRng::from_seed(seed))
+    }
+
+    fn account(&mut self, bytes: usize) -> Result<(), rand::Error> {
+        self.bytes_since_reseed += bytes;
+        if self.bytes_since_reseed >= self.reseed_interval {
+            self.reseed().map_err(rand::Error::new)?;
+        }
+        Ok(())
+    }
+}
+
+impl RngCore for QuantumRng {
+    fn next_u32(&mut self) -> u32 {
+        let value = self.generator.next_u32();
+        // A failed reseed keeps the current generator, which is still a valid CSPRNG
+        let _ = self.account(4);
+        value
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let value = self.generator.next_u64();
+        let _ = self.account(8);
+        value
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.generator.fill_bytes(dest);
+        let _ = self.account(dest.len());
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.generator.fill_bytes(dest);
+        self.account(dest.len())
+    }
+}
+
+/// State vector of a small simulated qubit register
+#[derive(Debug, Clone)]
+pub struct StateVector {
+    qubits: usize,
+    amplitudes: Vec<Complex64>,
+}
+
+impl StateVector {
+    /// Create a register of `qubits` qubits in the |0…0⟩ state
+    pub fn new(qubits: usize) -> Self {
+        let mut amplitudes = vec![Complex64::new(0.0, 0.0); 1 << qubits];
+        amplitudes[0] = Complex64::new(1.0, 0.0);
+
+        Self { qubits, amplitudes }
+    }
+
+    /// Number of qubits in the register
+    pub fn qubit_count(&self) -> usize {
+        self.qubits
+    }
+
+    /// Apply a single-qubit gate given as a 2x2 matrix [[a, b], [c, d]]
+    fn apply_single(&mut self, qubit: usize, gate: [[Complex64; 2]; 2]) {
+        let mask = 1 << qubit;
+        for index in 0..self.amplitudes.len() {
+            if index & mask == 0 {
+                let zero = self.amplitudes[index];
+                let one = self.amplitudes[index | mask];
+                self.amplitudes[index] = gate[0][0] * zero + gate[0][1] * one;
+                self.amplitudes[index | mask] = gate[1][0] * zero + gate[1][1] * one;
+            }
+        }
+    }
+
+    /// Hadamard gate
+    pub fn h(&mut self, qubit: usize) {
+        let s = Complex64::new(std::f64::consts::FRAC_1_SQRT_2, 0.0);
+        self.apply_single(qubit, [[s, s], [s, -s]]);
+    }
+
+    /// Rotation about the X axis
+    pub fn rx(&mut self, qubit: usize, theta: f64) {
+        let c = Complex64::new((theta / 2.0).cos(), 0.0);
+        let s = Complex64::new(0.0, -(theta / 2.0).sin());
+        self.apply_single(qubit, [[c, s], [s, c]]);
+    }
+
+    /// Rotation about the Y axis
+    pub fn ry(&mut self, qubit: usize, theta: f64) {
+        let c = Complex64::new((theta / 2.0).cos(), 0.0);
+        let s = Complex64::new((theta / 2.0).sin(), 0.0);
+        self.apply_single(qubit, [[c, -s], [s, c]]);
+    }
+
+    /// Rotation about the Z axis
+    pub fn rz(&mut self, qubit: usize, theta: f64) {
+        let zero = Complex64::new(0.0, 0.0);
+        let phase = Complex64::from_polar(1.0, theta / 2.0);
+        self.apply_single(qubit, [[phase.conj(), zero], [zero, phase]]);
+    }
+
+    /// Pauli X gate
+    pub fn x(&mut self, qubit: usize) {
+        let zero = Complex64::new(0.0, 0.0);
+        let one = Complex64::new(1.0, 0.0);
+        self.apply_single(qubit, [[zero, one], [one, zero]]);
+    }
+    
+    /// Pauli Y gate
+    pub fn y(&mut self, qubit: usize) {
+        let zero = Complex64::new(0.0, 0.0);
+        let i = Complex64::new(0.0, 1.0);
+        self.apply_single(qubit, [[zero, -i], [i, zero]]);
+    }
+    
+    /// Pauli Z gate
+    pub fn z(&mut self, qubit: usize) {
+        let zero = Complex64::new(0.0, 0.0);
+        let one = Complex64::new(1.0, 0.0);
+        self.apply_single(qubit, [[one, zero], [zero, -one]]);
+    }
+    
+    /// Inverse phase gate (S†)
+    pub fn sdg(&mut self, qubit: usize) {
+        let zero = Complex64::new(0.0, 0.0);
+        let one = Complex64::new(1.0, 0.0);
+        self.apply_single(qubit, [[one, zero], [zero, Complex64::new(0.0, -1.0)]]);
+    }
+
+    /// Controlled-NOT gate
+    pub fn cnot(&mut self, control: usize, target: usize) {
+        let control_mask = 1 << control;
+        let target_mask = 1 << target;
+        for index in 0..self.amplitudes.len() {
+            if index & control_mask != 0 && index & target_mask == 0 {
+                self.amplitudes.swap(index, index | target_mask);
+            }
+        }
+    }
+
+    /// Probability of each computational basis state
+    pub fn probabilities(&self) -> Vec<f64> {
+        self.amplitudes.iter().map(|a| a.norm_sqr()).collect()
+    }
+
+    /// Measure all qubits, collapsing the register; returns the basis state index
+    pub fn measure<R: Rng + ?Sized>(&mut self, rng: &mut R) -> usize {
+        let sample: f64 = rng.gen();
+        let mut cumulative = 0.0;
+        let mut outcome = self.amplitudes.len() - 1;
+
+        for (index, probability) in self.probabilities().into_iter().enumerate() {
+            cumulative += probability;
+            if sample < cumulative {
+                outcome = index;
+                break;
+            }
+        }
+
+        for (index, amplitude) in self.amplitudes.iter_mut().enumerate() {
+            *amplitude = if index == outcome {
+                Complex64::new(1.0, 0.0)
+            } else {
+                Complex64::new(0.0, 0.0)
+            };
+        }
+
+        outcome
+    }
+    
+    /// Exact expectation value ⟨ψ|O|ψ⟩ of a Pauli-string observable
+    pub fn expectation(&self, observable: &Observable) -> f64 {
+        let mut transformed = self.clone();
+        for &(qubit, pauli) in &observable.terms {
+            match pauli {
+                Pauli::X => transformed.x(qubit),
+                Pauli::Y => transformed.y(qubit),
+                Pauli::Z => transformed.z(qubit),
+            }
+        }
+        
+        self.amplitudes.iter()
+            .zip(transformed.amplitudes.iter())
+            .map(|(a, b)| (a.conj() * b).re)
+            .sum()
+    }
+    
+    /// Estimate an expectation value from `shots` projective measurements
+    pub fn sample_expectation<R: Rng + ?Sized>(
+        &self,
+        observable: &Observable,
+        shots: usize,
+        rng: &mut R,
+    ) -> f64 {
+        // Rotate every measured qubit into the Z basis once, then sample parities
+        let mut rotated = self.clone();
+        for &(qubit, pauli) in &observable.terms {
+            match pauli {
+                Pauli::X => rotated.h(qubit),
+                Pauli::Y => {
+                    rotated.sdg(qubit);
+                    rotated.h(qubit);
+                }
+                Pauli::Z => {}
+            }
+        }
+        
+        let mask = observable.terms.iter().fold(0usize, |acc, &(qubit, _)| acc | (1 << qubit));
+        let shots = shots.max(1);
+        let total: f64 = (0..shots).map(|_| {
+            let outcome = rotated.clone().measure(rng);
+            if (outcome & mask).count_ones().is_multiple_of(2) { 1.0 } else { -1.0 }
+        }).sum();
+        
+        total / shots as f64
+    }
+}
+
+/// Single-qubit Pauli operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Pauli {
+    X,
+    Y,
+    Z,
+}
+
+/// Observable given as a tensor product of Pauli operators on distinct qubits
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Observable {
+    pub terms: Vec<(usize, Pauli)>,
+}
+
+impl Observable {
+    /// Z measurement on a single qubit
+    pub fn z(qubit: usize) -> Self {
+        Self { terms: vec![(qubit, Pauli::Z)] }
+    }
+    
+    /// X measurement on a single qubit
+    pub fn x(qubit: usize) -> Self {
+        Self { terms: vec![(qubit, Pauli::X)] }
+    }
+    
+    /// Two-qubit ZZ correlation
+    pub fn zz(a: usize, b: usize) -> Self {
+        Self { terms: vec![(a, Pauli::Z), (b, Pauli::Z)] }
+    }
+    
+    /// Highest qubit index the observable acts on
+    fn max_qubit(&self) -> Option<usize> {
+        self.terms.iter().map(|&(qubit, _)| qubit).max()
+    }
+}
+
+/// Configuration of the hybrid quantum-classical processing stage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HybridStageConfig {
+    /// Number of simulated qubits the neural output is encoded into
+    pub qubits: usize,
+    /// Number of entangling layers in the encoding circuit
+    pub entangling_layers: usize,
+    /// Observables evaluated on the final state
+    pub observables: Vec<Observable>,
+    /// Estimate expectations from this many shots instead of exactly
+    pub shots: Option<usize>,
+    /// Weight of the quantum coherence in confidence/consciousness synthesis
+    pub synthesis_weight: f64,
+}
+
+impl Default for HybridStageConfig {
+    fn default() -> Self {
+        let qubits = 4;
+        let mut observables: Vec<Observable> = (0..qubits).map(Observable::z).collect();
+        observables.extend((0..qubits - 1).map(|q| Observable::zz(q, q + 1)));
+        
+        Self {
+            qubits,
+            entangling_layers: 2,
+            observables,
+            shots: None,
+            synthesis_weight: 0.2,
+        }
+    }
+}
+
+/// Result of the hybrid quantum-classical stage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HybridStageResult {
+    /// Expectation value of each configured observable, in [-1, 1]
+    pub expectation_values: Vec<f64>,
+    /// Mean absolute expectation value, in [0, 1]
+    pub coherence: f64,
+    /// Weight to apply when blending `coherence` into classical scores
+    pub synthesis_weight: f64,
+}
+
+impl HybridStageResult {
+    /// Blend a classical score in [0, 1] with the quantum coherence
+    pub fn blend(&self, classical: f64) -> f64 {
+        let weight = self.synthesis_weight.clamp(0.0, 1.0);
+        (classical * (1.0 - weight) + self.coherence * weight).clamp(0.0, 1.0)
+    }
+}
+
+/// Hybrid stage that encodes classical features into a small quantum circuit
+pub struct HybridQuantumStage {
+    config: HybridStageConfig,
+    rng: Mutex<QuantumRng>,
+}
+
+impl HybridQuantumStage {
+    /// Create a stage using the simulated quantum entropy source for sampling
+    pub fn new(config: HybridStageConfig) -> Result<Self, AgiError> {
+        Self::with_rng(config, QuantumRng::from_simulator()?)
+    }
+    
+    /// Create a stage with an explicit measurement RNG
+    pub fn with_rng(config: HybridStageConfig, rng: QuantumRng) -> Result<Self, AgiError> {
+        if config.qubits == 0 || config.qubits > 16 {
+            return Err(AgiError::Config(format!("Hybrid stage supports 1-16 qubits, got {}", config.qubits)));
+        }
+        if config.observables.is_empty() {
+            return Err(AgiError::Config("Hybrid stage needs at least one observable".to_string()));
+        }
+        for observable in &config.observables {
+            if observable.max_qubit().is_none_or(|q| q >= config.qubits) {
+                return Err(AgiError::Config(format!("Observable {:?} acts outside the {}-qubit register", observable, config.qubits)));
+            }
+        }
+        
+        Ok(Self {
+            config,
+            rng: Mutex::new(rng),
+        })
+    }
+    
+    /// Stage configuration
+    pub fn config(&self) -> &HybridStageConfig {
+        &self.config
+    }
+    
+    /// Encode features into the circuit and evaluate the configured observables
+    #[instrument(skip(self, features))]
+    pub fn evaluate(&self, features: &Array1<f64>) -> Result<HybridStageResult, AgiError> {
+        let angles = self.encoding_angles(features);
+        let state = self.prepare_state(&angles);
+        
+        let expectation_values: Vec<f64> = match self.config.shots {
+            Some(shots) => {
+                let mut rng = self.rng.lock().map_err(|_| AgiError::Quantum("Quantum RNG lock poisoned".to_string()))?;
+                self.config.observables.iter()
+                    .map(|observable| state.sample_expectation(observable, shots, &mut *rng))
+                    .collect()
+            }
+            None => self.config.observables.iter()
+                .map(|observable| state.expectation(observable))
+                .collect(),
+        };
+        
+        let coherence = expectation_values.iter().map(|v| v.abs()).sum::<f64>()
+            / expectation_values.len() as f64;
+        
+        info!("Hybrid quantum stage evaluated {} observables, coherence: {:.3}",
+              expectation_values.len(), coherence);
+        
+        Ok(HybridStageResult {
+            expectation_values,
+            coherence,
+            synthesis_weight: self.config.synthesis_weight,
+        })
+    }
+    
+    /// Angle-encode features: one rotation per qubit from the mean of a feature chunk
+    fn encoding_angles(&self, features: &Array1<f64>) -> Vec<f64> {
+        let qubits = self.config.qubits;
+        let chunk = features.len().div_ceil(qubits).max(1);
+        
+        (0..qubits).map(|q| {
+            let start = (q * chunk).min(features.len());
+            let end = ((q + 1) * chunk).min(features.len());
+            let mean = if end > start {
+                features.slice(ndarray::s![start..end]).mean().unwrap_or(0.0)
+            } else {
+                0.0
+            };
+            // Squash into (0, π) so every feature value maps to a valid rotation
+            std::f64::consts::PI / (1.0 + (-mean).exp())
+        }).collect()
+    }
+    
+    /// Build the encoding circuit with entangling layers and data re-uploading
+    fn prepare_state(&self, angles: &[f64]) -> StateVector {
+        let mut state = StateVector::new(self.config.qubits);
+        
+        for (qubit, &angle) in angles.iter().enumerate() {
+            state.ry(qubit, angle);
+        }
+        
+        for _ in 0..self.config.entangling_layers {
+            for qubit in 0..self.config.qubits.saturating_sub(1) {
+                state.cnot(qubit, qubit + 1);
+            }
+            for (qubit, &angle) in angles.iter().enumerate() {
+                state.rz(qubit, angle);
+                state.ry(qubit, angle / 2.0);
+            }
+        }
+        
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seeded_rng_is_deterministic() {
+        let mut a = QuantumRng::seeded(42);
+        let mut b = QuantumRng::seeded(42);
+        let xs: Vec<u64> = (0..16).map(|_| a.next_u64()).collect();
+        let ys: Vec<u64> = (0..16).map(|_| b.next_u64()).collect();
+        assert_eq!(xs, ys);
+    }
+
+    #[test]
+    fn test_simulated_source_is_unbiased() {
+        let mut source = SimulatedQuantumSource::with_rotation(8, 1.0).with_seed(7);
+        let mut bytes = vec![0u8; 4096];
+        source.fill_entropy(&mut bytes).unwrap();
+
+        let ones: u32 = bytes.iter().map(|b| b.count_ones()).sum();
+        let ratio = ones as f64 / (bytes.len() * 8) as f64;
+        assert!((ratio - 0.5).abs() < 0.02, "bit ratio {}", ratio);
+    }
+
+    #[test]
+    fn test_bell_state_measurements_agree() {
+        let mut rng = QuantumRng::seeded(1);
+        for _ in 0..32 {
+            let mut state = StateVector::new(2);
+            state.h(0);
+            state.cnot(0, 1);
+            let outcome = state.measure(&mut rng);
+            assert!(outcome == 0b00 || outcome == 0b11);
+        }
+    }
+    
+    #[test]
+    fn test_expectation_values() {
+        let mut state = StateVector::new(2);
+        state.h(0);
+        assert!(state.expectation(&Observable::z(0)).abs() < 1e-12);
+        assert!((state.expectation(&Observable::x(0)) - 1.0).abs() < 1e-12);
+        assert!((state.expectation(&Observable::z(1)) - 1.0).abs() < 1e-12);
+        
+        let mut rng = QuantumRng::seeded(3);
+        let sampled = state.sample_expectation(&Observable::x(0), 200, &mut rng);
+        assert!((sampled - 1.0).abs() < 1e-12);
+    }
+    
+    #[test]
+    fn test_hybrid_stage_rejects_out_of_range_observable() {
+        let config = HybridStageConfig {
+            qubits: 2,
+            observables: vec![Observable::z(2)],
+            ..Default::default()
+        };
+        assert!(HybridQuantumStage::with_rng(config, QuantumRng::seeded(0)).is_err());
+    }
+    
+    #[test]
+    fn test_hybrid_stage_evaluation() {
+        let stage = HybridQuantumStage::with_rng(HybridStageConfig::default(), QuantumRng::seeded(0)).unwrap();
+        let result = stage.evaluate(&Array1::linspace(-1.0, 1.0, 256)).unwrap();
+        
+        assert_eq!(result.expectation_values.len(), stage.config().observables.len());
+        assert!(result.expectation_values.iter().all(|v| (-1.0..=1.0).contains(v)));
+        assert!((0.0..=1.0).contains(&result.coherence));
+    }
+}
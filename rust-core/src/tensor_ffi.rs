@@ -1,113 +1,80 @@
 //! FFI Bindings for Tensor Operations
-//! 
+//!
 //! Provides C-compatible FFI interface for tensor operations from TypeScript/JavaScript
+//!
+//! Tensors cross the boundary as opaque handles owned by the library:
+//! `tensor_create` copies the caller's arrays into a new handle, operations
+//! borrow their operands and return new handles, and `tensor_free` releases a
+//! handle exactly once. A handle may be passed as several operands of the same
+//! call, including both sides of an in-place operation. Its shape and data are
+//! read through `tensor_rank`, `tensor_shape`, `tensor_size` and `tensor_data`.
+//!
+//! Functions returning `c_int` return 0 (or a count) on success, `NullPointer`
+//! (-1) for a null argument and `Tensor` (-40) when the operation rejects its
+//! operands. Those returning pointers return null, and those returning
+//! similarities or sizes return 0 and clear the last error on entry, so that a
+//! failure can be told from a zero result. The reason is in
+//! `agi_last_error_message`. A panic is reported the same way with `Panic` (-99).
 
-use std::ffi::{CStr, CString};
+use std::ffi::CStr;
 use std::os::raw::{c_char, c_double, c_int};
 use std::ptr;
-use crate::tensor_ops::{Tensor, tensor_and, tensor_or, tensor_not, tensor_implies, 
-                        einstein_summation, tensor_similarity, unify_tensors, apply_kernel};
+use crate::error::AgiError;
+use crate::ffi;
+use crate::memory_manager::leaks;
+use crate::tensor_ops::{Tensor, TensorView, Reduction, view_and, view_or, view_not, view_implies,
+                        einsum, tensor_similarity, unify_tensors, apply_kernel, knn, tensor_similarity_matrix};
+use crate::tensor_ops::inplace::{buffer_and, buffer_or, buffer_implies, buffer_add, buffer_mul, buffer_not, buffer_scale};
+use crate::tensor_ops::dtype::{DType, TensorData, TypedTensor, typed_and, typed_or, typed_not, typed_implies,
+                               typed_similarity, typed_apply_kernel};
 
-/// FFI-safe tensor structure
-#[repr(C)]
+/// Opaque handle to a double-precision tensor
 pub struct CTensor {
-    shape_ptr: *mut usize,
-    shape_len: usize,
-    data_ptr: *mut c_double,
-    data_len: usize,
-    rank: usize,
-}
-
-impl From<&Tensor> for CTensor {
-    fn from(tensor: &Tensor) -> Self {
-        let mut shape = tensor.shape.clone();
-        let mut data = tensor.data.clone();
-        
-        shape.shrink_to_fit();
-        data.shrink_to_fit();
-        
-        let shape_ptr = shape.as_mut_ptr();
-        let data_ptr = data.as_mut_ptr();
-        let shape_len = shape.len();
-        let data_len = data.len();
-        
-        // Leak the vectors - caller must free them
-        std::mem::forget(shape);
-        std::mem::forget(data);
-        
-        Self {
-            shape_ptr,
-            shape_len,
-            data_ptr,
-            data_len,
-            rank: tensor.rank,
-        }
-    }
+    tensor: Tensor,
 }
 
 impl CTensor {
-    /// Convert to Rust Tensor (takes ownership)
-    unsafe fn to_tensor(self) -> Tensor {
-        let shape = Vec::from_raw_parts(self.shape_ptr, self.shape_len, self.shape_len);
-        let data = Vec::from_raw_parts(
-            self.data_ptr,
-            self.data_len,
-            self.data_len,
-        );
-        
-        Tensor {
-            shape,
-            data: data.into_iter().map(|x| x as f64).collect(),
-            rank: self.rank,
-        }
+    /// Bytes of the handle and its buffers
+    fn bytes(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.tensor.shape.len() * std::mem::size_of::<usize>()
+            + self.tensor.data.len() * std::mem::size_of::<c_double>()
     }
-    
-    /// Create from Rust Tensor (transfers ownership)
-    fn from_tensor(tensor: Tensor) -> Self {
-        let mut shape = tensor.shape;
-        let mut data: Vec<c_double> = tensor.data.into_iter().map(|x| x as c_double).collect();
-        
-        shape.shrink_to_fit();
-        data.shrink_to_fit();
-        
-        let shape_ptr = shape.as_mut_ptr();
-        let data_ptr = data.as_mut_ptr();
-        let shape_len = shape.len();
-        let data_len = data.len();
-        
-        std::mem::forget(shape);
-        std::mem::forget(data);
-        
-        Self {
-            shape_ptr,
-            shape_len,
-            data_ptr,
-            data_len,
-            rank: tensor.rank,
-        }
+
+    /// Box `tensor` for handing to the caller, recording the caller's site under `leak-tracking`
+    #[track_caller]
+    fn into_raw(tensor: Tensor) -> *mut Self {
+        let handle = Self { tensor };
+        let bytes = handle.bytes();
+        let raw = Box::into_raw(Box::new(handle));
+        leaks::track(raw, bytes);
+        raw
     }
 }
 
-/// Free a CTensor (must be called from C/TypeScript)
+/// Record a rejected tensor operation as the last error, returning its code
+fn tensor_error(message: String) -> c_int {
+    ffi::set_last_error(&AgiError::Tensor(message))
+}
+
+/// Free a tensor handle; null is ignored
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 #[no_mangle]
 pub extern "C" fn tensor_free(tensor: *mut CTensor) {
-    if tensor.is_null() {
-        return;
-    }
-    
-    unsafe {
-        let ct = Box::from_raw(tensor);
-        // Free the vectors
-        if !ct.shape_ptr.is_null() {
-            let _ = Vec::from_raw_parts(ct.shape_ptr, ct.shape_len, ct.shape_len);
-        }
-        if !ct.data_ptr.is_null() {
-            let _ = Vec::from_raw_parts(ct.data_ptr, ct.data_len, ct.data_len);
+    ffi::guard((), || {
+        if tensor.is_null() {
+            return;
         }
-    }
+
+        leaks::untrack(tensor);
+        drop(unsafe { Box::from_raw(tensor) });
+    })
 }
 
-/// Create tensor from arrays
+/// Create a tensor handle from copies of the caller's shape and data arrays
+///
+/// Returns null if an array is null or the data does not fill the shape.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 #[no_mangle]
 pub extern "C" fn tensor_create(
     shape_ptr: *const usize,
@@ -115,47 +82,112 @@ pub extern "C" fn tensor_create(
     data_ptr: *const c_double,
     data_len: usize,
 ) -> *mut CTensor {
-    if shape_ptr.is_null() || data_ptr.is_null() {
-        return ptr::null_mut();
-    }
-    
-    unsafe {
-        let shape = std::slice::from_raw_parts(shape_ptr, shape_len).to_vec();
-        let data: Vec<f64> = std::slice::from_raw_parts(data_ptr, data_len)
-            .iter()
-            .map(|&x| x as f64)
-            .collect();
-        
-        let tensor = Tensor::new(shape, data);
-        Box::into_raw(Box::new(CTensor::from_tensor(tensor)))
-    }
+    ffi::guard(ptr::null_mut(), || {
+        if shape_ptr.is_null() || data_ptr.is_null() {
+            ffi::set_null_argument("shape_ptr or data_ptr");
+            return ptr::null_mut();
+        }
+
+        let shape = unsafe { std::slice::from_raw_parts(shape_ptr, shape_len) }.to_vec();
+        let data = unsafe { std::slice::from_raw_parts(data_ptr, data_len) }.to_vec();
+        if shape.iter().product::<usize>() != data.len() {
+            tensor_error(format!("Shape {:?} does not hold {} elements", shape, data.len()));
+            return ptr::null_mut();
+        }
+        CTensor::into_raw(Tensor::new(shape, data))
+    })
 }
 
-/// Tensor AND operation
+/// Rank of a tensor, or 0 if the handle is null
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 #[no_mangle]
-pub extern "C" fn tensor_and_ffi(
+pub extern "C" fn tensor_rank(tensor: *const CTensor) -> usize {
+    ffi::guard(0, || {
+        ffi::clear_last_error();
+        if tensor.is_null() {
+            ffi::set_null_argument("tensor");
+            return 0;
+        }
+        unsafe { (*tensor).tensor.shape.len() }
+    })
+}
+
+/// Borrowed array of `tensor_rank` dimensions, valid until the handle is freed
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn tensor_shape(tensor: *const CTensor) -> *const usize {
+    ffi::guard(ptr::null(), || {
+        if tensor.is_null() {
+            ffi::set_null_argument("tensor");
+            return ptr::null();
+        }
+        unsafe { (*tensor).tensor.shape.as_ptr() }
+    })
+}
+
+/// Number of elements of a tensor, or 0 if the handle is null
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn tensor_size(tensor: *const CTensor) -> usize {
+    ffi::guard(0, || {
+        ffi::clear_last_error();
+        if tensor.is_null() {
+            ffi::set_null_argument("tensor");
+            return 0;
+        }
+        unsafe { (*tensor).tensor.data.len() }
+    })
+}
+
+/// Borrowed array of `tensor_size` elements in row-major order, valid until the
+/// handle is freed; in-place operations update it
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn tensor_data(tensor: *const CTensor) -> *const c_double {
+    ffi::guard(ptr::null(), || {
+        if tensor.is_null() {
+            ffi::set_null_argument("tensor");
+            return ptr::null();
+        }
+        unsafe { (*tensor).tensor.data.as_ptr() }
+    })
+}
+
+/// Apply a binary logic operation to views of both operands, writing a new handle to `result`
+#[track_caller]
+fn binary_ffi(
     tensor_a: *const CTensor,
     tensor_b: *const CTensor,
     result: *mut *mut CTensor,
+    op: fn(&TensorView, &TensorView) -> Result<Tensor, String>,
 ) -> c_int {
     if tensor_a.is_null() || tensor_b.is_null() || result.is_null() {
-        return -1;
+        return ffi::set_null_argument("tensor_a, tensor_b or result");
     }
-    
+
     unsafe {
-        let a = (*tensor_a).to_tensor();
-        let b = (*tensor_b).to_tensor();
-        
-        match tensor_and(&a, &b) {
+        match op(&(*tensor_a).tensor.view(), &(*tensor_b).tensor.view()) {
             Ok(t) => {
-                *result = Box::into_raw(Box::new(CTensor::from_tensor(t)));
+                *result = CTensor::into_raw(t);
                 0
             }
-            Err(_) => -1,
+            Err(e) => tensor_error(e),
         }
     }
 }
 
+/// Tensor AND operation
+#[no_mangle]
+pub extern "C" fn tensor_and_ffi(
+    tensor_a: *const CTensor,
+    tensor_b: *const CTensor,
+    result: *mut *mut CTensor,
+) -> c_int {
+    ffi::guard(ffi::FFIError::Panic as c_int, || {
+        binary_ffi(tensor_a, tensor_b, result, view_and)
+    })
+}
+
 /// Tensor OR operation
 #[no_mangle]
 pub extern "C" fn tensor_or_ffi(
@@ -163,40 +195,28 @@ pub extern "C" fn tensor_or_ffi(
     tensor_b: *const CTensor,
     result: *mut *mut CTensor,
 ) -> c_int {
-    if tensor_a.is_null() || tensor_b.is_null() || result.is_null() {
-        return -1;
-    }
-    
-    unsafe {
-        let a = (*tensor_a).to_tensor();
-        let b = (*tensor_b).to_tensor();
-        
-        match tensor_or(&a, &b) {
-            Ok(t) => {
-                *result = Box::into_raw(Box::new(CTensor::from_tensor(t)));
-                0
-            }
-            Err(_) => -1,
-        }
-    }
+    ffi::guard(ffi::FFIError::Panic as c_int, || {
+        binary_ffi(tensor_a, tensor_b, result, view_or)
+    })
 }
 
 /// Tensor NOT operation
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 #[no_mangle]
 pub extern "C" fn tensor_not_ffi(
     tensor: *const CTensor,
     result: *mut *mut CTensor,
 ) -> c_int {
-    if tensor.is_null() || result.is_null() {
-        return -1;
-    }
-    
-    unsafe {
-        let t = (*tensor).to_tensor();
-        let not_t = tensor_not(&t);
-        *result = Box::into_raw(Box::new(CTensor::from_tensor(not_t)));
+    ffi::guard(ffi::FFIError::Panic as c_int, || {
+        if tensor.is_null() || result.is_null() {
+            return ffi::set_null_argument("tensor or result");
+        }
+
+        unsafe {
+            *result = CTensor::into_raw(view_not(&(*tensor).tensor.view()));
+        }
         0
-    }
+    })
 }
 
 /// Tensor IMPLIES operation
@@ -206,61 +226,725 @@ pub extern "C" fn tensor_implies_ffi(
     tensor_b: *const CTensor,
     result: *mut *mut CTensor,
 ) -> c_int {
-    if tensor_a.is_null() || tensor_b.is_null() || result.is_null() {
-        return -1;
+    ffi::guard(ffi::FFIError::Panic as c_int, || {
+        binary_ffi(tensor_a, tensor_b, result, view_implies)
+    })
+}
+
+/// In-place binary operation over a buffer of the given shape and a second operand
+type InplaceOp = fn(&mut [f64], &[usize], &TensorView) -> Result<(), String>;
+
+/// Apply a binary operation in place, overwriting the data of `tensor_a`
+fn inplace_ffi(
+    tensor_a: *mut CTensor,
+    tensor_b: *const CTensor,
+    op: InplaceOp,
+) -> c_int {
+    if tensor_a.is_null() || tensor_b.is_null() {
+        return ffi::set_null_argument("tensor_a or tensor_b");
     }
-    
+
     unsafe {
-        let a = (*tensor_a).to_tensor();
-        let b = (*tensor_b).to_tensor();
-        
-        match tensor_implies(&a, &b) {
-            Ok(t) => {
-                *result = Box::into_raw(Box::new(CTensor::from_tensor(t)));
-                0
-            }
-            Err(_) => -1,
+        // Borrowing `b` while `a` is borrowed mutably would alias when both are the
+        // same handle, so read a copy instead
+        let copy;
+        let b = if ptr::eq(tensor_a as *const CTensor, tensor_b) {
+            copy = (*tensor_b).tensor.clone();
+            &copy
+        } else {
+            &(*tensor_b).tensor
+        };
+
+        let a = &mut (*tensor_a).tensor;
+        match op(&mut a.data, &a.shape, &b.view()) {
+            Ok(()) => 0,
+            Err(e) => tensor_error(e),
         }
     }
 }
 
+/// In-place tensor AND: overwrites `tensor_a` without allocating
+#[no_mangle]
+pub extern "C" fn tensor_and_inplace_ffi(tensor_a: *mut CTensor, tensor_b: *const CTensor) -> c_int {
+    ffi::guard(ffi::FFIError::Panic as c_int, || {
+        inplace_ffi(tensor_a, tensor_b, buffer_and)
+    })
+}
+
+/// In-place tensor OR: overwrites `tensor_a` without allocating
+#[no_mangle]
+pub extern "C" fn tensor_or_inplace_ffi(tensor_a: *mut CTensor, tensor_b: *const CTensor) -> c_int {
+    ffi::guard(ffi::FFIError::Panic as c_int, || {
+        inplace_ffi(tensor_a, tensor_b, buffer_or)
+    })
+}
+
+/// In-place tensor IMPLIES: overwrites `tensor_a` without allocating
+#[no_mangle]
+pub extern "C" fn tensor_implies_inplace_ffi(tensor_a: *mut CTensor, tensor_b: *const CTensor) -> c_int {
+    ffi::guard(ffi::FFIError::Panic as c_int, || {
+        inplace_ffi(tensor_a, tensor_b, buffer_implies)
+    })
+}
+
+/// In-place element-wise sum: overwrites `tensor_a` without allocating
+#[no_mangle]
+pub extern "C" fn tensor_add_inplace_ffi(tensor_a: *mut CTensor, tensor_b: *const CTensor) -> c_int {
+    ffi::guard(ffi::FFIError::Panic as c_int, || {
+        inplace_ffi(tensor_a, tensor_b, buffer_add)
+    })
+}
+
+/// In-place element-wise product: overwrites `tensor_a` without allocating
+#[no_mangle]
+pub extern "C" fn tensor_mul_inplace_ffi(tensor_a: *mut CTensor, tensor_b: *const CTensor) -> c_int {
+    ffi::guard(ffi::FFIError::Panic as c_int, || {
+        inplace_ffi(tensor_a, tensor_b, buffer_mul)
+    })
+}
+
+/// In-place tensor NOT
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn tensor_not_inplace_ffi(tensor: *mut CTensor) -> c_int {
+    ffi::guard(ffi::FFIError::Panic as c_int, || {
+        if tensor.is_null() {
+            return ffi::set_null_argument("tensor");
+        }
+
+        buffer_not(unsafe { &mut (*tensor).tensor.data });
+        0
+    })
+}
+
+/// Multiply every element of a tensor by `factor` in place
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn tensor_scale_inplace_ffi(tensor: *mut CTensor, factor: c_double) -> c_int {
+    ffi::guard(ffi::FFIError::Panic as c_int, || {
+        if tensor.is_null() {
+            return ffi::set_null_argument("tensor");
+        }
+
+        buffer_scale(unsafe { &mut (*tensor).tensor.data }, factor);
+        0
+    })
+}
+
 /// Compute tensor similarity
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 #[no_mangle]
 pub extern "C" fn tensor_similarity_ffi(
     tensor_a: *const CTensor,
     tensor_b: *const CTensor,
 ) -> c_double {
-    if tensor_a.is_null() || tensor_b.is_null() {
-        return 0.0;
-    }
-    
-    unsafe {
-        let a = (*tensor_a).to_tensor();
-        let b = (*tensor_b).to_tensor();
-        tensor_similarity(&a, &b) as c_double
-    }
+    ffi::guard(0.0, || {
+        ffi::clear_last_error();
+        if tensor_a.is_null() || tensor_b.is_null() {
+            ffi::set_null_argument("tensor_a or tensor_b");
+            return 0.0;
+        }
+
+        unsafe { tensor_similarity(&(*tensor_a).tensor, &(*tensor_b).tensor) as c_double }
+    })
 }
 
 /// Apply kernel function
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 #[no_mangle]
 pub extern "C" fn tensor_apply_kernel_ffi(
     kernel_type: *const c_char,
     tensor_a: *const CTensor,
     tensor_b: *const CTensor,
 ) -> c_double {
-    if kernel_type.is_null() || tensor_a.is_null() || tensor_b.is_null() {
-        return 0.0;
+    ffi::guard(0.0, || {
+        ffi::clear_last_error();
+        if kernel_type.is_null() || tensor_a.is_null() || tensor_b.is_null() {
+            ffi::set_null_argument("kernel_type, tensor_a or tensor_b");
+            return 0.0;
+        }
+
+        unsafe {
+            let kernel_str = CStr::from_ptr(kernel_type).to_string_lossy();
+            match apply_kernel(&kernel_str, &(*tensor_a).tensor, &(*tensor_b).tensor) {
+                Ok(value) => value as c_double,
+                Err(e) => {
+                    tensor_error(e);
+                    0.0
+                }
+            }
+        }
+    })
+}
+
+/// Borrow the tensors behind a caller-owned array of handles, or record why it cannot be read
+unsafe fn borrow_array<'a>(tensors: *const *const CTensor, count: usize) -> Result<Vec<&'a Tensor>, c_int> {
+    if tensors.is_null() && count > 0 {
+        return Err(ffi::set_null_argument("tensor array"));
     }
-    
+    if count == 0 {
+        return Ok(Vec::new());
+    }
+    std::slice::from_raw_parts(tensors, count)
+        .iter()
+        .enumerate()
+        .map(|(index, &tensor)| {
+            if tensor.is_null() {
+                return Err(ffi::set_null_argument(&format!("tensor {} of the array", index)));
+            }
+            Ok(&(*tensor).tensor)
+        })
+        .collect()
+}
+
+/// Copy the tensors behind a caller-owned array of handles, or record why it cannot be read
+unsafe fn tensors_from_array(tensors: *const *const CTensor, count: usize) -> Result<Vec<Tensor>, c_int> {
+    borrow_array(tensors, count).map(|tensors| tensors.into_iter().cloned().collect())
+}
+
+/// Cosine similarity matrix of `count` tensors, written to `result`
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn tensor_similarity_matrix_ffi(
+    tensors: *const *const CTensor,
+    count: usize,
+    result: *mut *mut CTensor,
+) -> c_int {
+    ffi::guard(ffi::FFIError::Panic as c_int, || {
+        if result.is_null() {
+            return ffi::set_null_argument("result");
+        }
+
+        unsafe {
+            let batch = match tensors_from_array(tensors, count) {
+                Ok(batch) => batch,
+                Err(code) => return code,
+            };
+            match tensor_similarity_matrix(&batch) {
+                Ok(t) => {
+                    *result = CTensor::into_raw(t);
+                    0
+                }
+                Err(e) => tensor_error(e),
+            }
+        }
+    })
+}
+
+/// Nearest neighbors of `query` among `corpus_len` tensors
+///
+/// Writes up to `k` corpus indices and similarities, most similar first, to the
+/// caller's buffers and returns how many were written, or a negative error code.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn tensor_knn_ffi(
+    query: *const CTensor,
+    corpus: *const *const CTensor,
+    corpus_len: usize,
+    k: usize,
+    indices_out: *mut usize,
+    similarities_out: *mut c_double,
+) -> c_int {
+    ffi::guard(ffi::FFIError::Panic as c_int, || {
+        if query.is_null() || (k > 0 && (indices_out.is_null() || similarities_out.is_null())) {
+            return ffi::set_null_argument("query, indices_out or similarities_out");
+        }
+
+        unsafe {
+            let corpus = match tensors_from_array(corpus, corpus_len) {
+                Ok(corpus) => corpus,
+                Err(code) => return code,
+            };
+            match knn(&(*query).tensor, &corpus, k) {
+                Ok(neighbors) => {
+                    for (i, neighbor) in neighbors.iter().enumerate() {
+                        *indices_out.add(i) = neighbor.index;
+                        *similarities_out.add(i) = neighbor.similarity as c_double;
+                    }
+                    neighbors.len() as c_int
+                }
+                Err(e) => tensor_error(e),
+            }
+        }
+    })
+}
+
+/// Evaluate an Einstein summation in subscript notation, such as `"ij,jk->ik"`,
+/// over `count` tensors, writing the result to `result`
+///
+/// Returns 0, `NullPointer` (-1) if an argument is null, `InvalidArgument` (-2)
+/// if `spec` is not UTF-8, or `Tensor` (-40) if the spec does not fit the operands.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn tensor_einsum_ffi(
+    spec: *const c_char,
+    tensors: *const *const CTensor,
+    count: usize,
+    result: *mut *mut CTensor,
+) -> c_int {
+    ffi::guard(ffi::FFIError::Panic as c_int, || {
+        if spec.is_null() || result.is_null() {
+            return ffi::set_null_argument("spec or result");
+        }
+        let Some(spec) = (unsafe { ffi::c_string_to_rust_string(spec) }) else {
+            return ffi::set_last_error(&AgiError::Ffi("spec is not valid UTF-8".to_string()));
+        };
+
+        unsafe {
+            let operands = match borrow_array(tensors, count) {
+                Ok(operands) => operands,
+                Err(code) => return code,
+            };
+            match einsum(&spec, &operands) {
+                Ok(t) => {
+                    *result = CTensor::into_raw(t);
+                    0
+                }
+                Err(e) => tensor_error(e),
+            }
+        }
+    })
+}
+
+/// Element-wise mean of `count` equally shaped tensors, written to `result`
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn tensor_unify_ffi(
+    tensors: *const *const CTensor,
+    count: usize,
+    result: *mut *mut CTensor,
+) -> c_int {
+    ffi::guard(ffi::FFIError::Panic as c_int, || {
+        if result.is_null() {
+            return ffi::set_null_argument("result");
+        }
+
+        unsafe {
+            let batch = match tensors_from_array(tensors, count) {
+                Ok(batch) => batch,
+                Err(code) => return code,
+            };
+            match unify_tensors(&batch) {
+                Ok(t) => {
+                    *result = CTensor::into_raw(t);
+                    0
+                }
+                Err(e) => tensor_error(e),
+            }
+        }
+    })
+}
+
+/// `reduction` argument of `tensor_reduce_ffi`: sum of the elements
+pub const TENSOR_REDUCE_SUM: c_int = 0;
+/// `reduction` argument of `tensor_reduce_ffi`: mean of the elements
+pub const TENSOR_REDUCE_MEAN: c_int = 1;
+/// `reduction` argument of `tensor_reduce_ffi`: largest element
+pub const TENSOR_REDUCE_MAX: c_int = 2;
+/// `reduction` argument of `tensor_reduce_ffi`: smallest element
+pub const TENSOR_REDUCE_MIN: c_int = 3;
+
+/// Reduce `axes_len` axes of a tensor (every axis when 0) with one of the
+/// `TENSOR_REDUCE_*` operations, keeping them with extent one if `keepdim` is
+/// nonzero, and write the result to `result`
+///
+/// Returns 0, `NullPointer` (-1) if an argument is null, `InvalidArgument` (-2)
+/// for an unknown reduction, or `Tensor` (-40) for an axis out of range.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn tensor_reduce_ffi(
+    tensor: *const CTensor,
+    reduction: c_int,
+    axes: *const usize,
+    axes_len: usize,
+    keepdim: c_int,
+    result: *mut *mut CTensor,
+) -> c_int {
+    ffi::guard(ffi::FFIError::Panic as c_int, || {
+        if tensor.is_null() || result.is_null() || (axes.is_null() && axes_len > 0) {
+            return ffi::set_null_argument("tensor, axes or result");
+        }
+        let reduction = match reduction {
+            TENSOR_REDUCE_SUM => Reduction::Sum,
+            TENSOR_REDUCE_MEAN => Reduction::Mean,
+            TENSOR_REDUCE_MAX => Reduction::Max,
+            TENSOR_REDUCE_MIN => Reduction::Min,
+            other => return ffi::set_last_error(&AgiError::Ffi(format!("Unknown reduction {}", other))),
+        };
+
+        unsafe {
+            let axes = if axes_len == 0 { &[][..] } else { std::slice::from_raw_parts(axes, axes_len) };
+            match (*tensor).tensor.reduce(reduction, axes, keepdim != 0) {
+                Ok(t) => {
+                    *result = CTensor::into_raw(t);
+                    0
+                }
+                Err(e) => tensor_error(e),
+            }
+        }
+    })
+}
+
+/// Index of the largest (`tensor_argmax_ffi`) or smallest element along `axis`,
+/// the first on ties, as a tensor of whole numbers written to `result`
+fn arg_extreme_ffi(
+    tensor: *const CTensor,
+    axis: usize,
+    keepdim: c_int,
+    result: *mut *mut CTensor,
+    op: fn(&Tensor, usize, bool) -> Result<Tensor, String>,
+) -> c_int {
+    if tensor.is_null() || result.is_null() {
+        return ffi::set_null_argument("tensor or result");
+    }
+
     unsafe {
-        let kernel_str = CStr::from_ptr(kernel_type).to_string_lossy();
-        let a = (*tensor_a).to_tensor();
-        let b = (*tensor_b).to_tensor();
-        
-        match apply_kernel(&kernel_str, &a, &b) {
-            Ok(value) => value as c_double,
-            Err(_) => 0.0,
+        match op(&(*tensor).tensor, axis, keepdim != 0) {
+            Ok(t) => {
+                *result = CTensor::into_raw(t);
+                0
+            }
+            Err(e) => tensor_error(e),
+        }
+    }
+}
+
+/// Index of the largest element along `axis`, the first on ties
+#[no_mangle]
+pub extern "C" fn tensor_argmax_ffi(
+    tensor: *const CTensor,
+    axis: usize,
+    keepdim: c_int,
+    result: *mut *mut CTensor,
+) -> c_int {
+    ffi::guard(ffi::FFIError::Panic as c_int, || {
+        arg_extreme_ffi(tensor, axis, keepdim, result, Tensor::argmax)
+    })
+}
+
+/// Index of the smallest element along `axis`, the first on ties
+#[no_mangle]
+pub extern "C" fn tensor_argmin_ffi(
+    tensor: *const CTensor,
+    axis: usize,
+    keepdim: c_int,
+    result: *mut *mut CTensor,
+) -> c_int {
+    ffi::guard(ffi::FFIError::Panic as c_int, || {
+        arg_extreme_ffi(tensor, axis, keepdim, result, Tensor::argmin)
+    })
+}
+
+/// Opaque handle to a single-precision tensor
+pub struct CTensorF32 {
+    /// Always holds `TensorData::F32`
+    tensor: TypedTensor,
+}
+
+impl CTensorF32 {
+    /// Elements of the tensor
+    fn data(&self) -> &[f32] {
+        match &self.tensor.data {
+            TensorData::F32(data) => data,
+            _ => unreachable!("single-precision handle"),
         }
     }
+
+    /// Bytes of the handle and its buffers
+    fn bytes(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.tensor.shape.len() * std::mem::size_of::<usize>()
+            + std::mem::size_of_val(self.data())
+    }
+
+    /// Cast `tensor` to f32 and box it for handing to the caller, recording the
+    /// caller's site under `leak-tracking`
+    #[track_caller]
+    fn into_raw(tensor: TypedTensor) -> *mut Self {
+        let handle = Self { tensor: tensor.cast(DType::F32) };
+        let bytes = handle.bytes();
+        let raw = Box::into_raw(Box::new(handle));
+        leaks::track(raw, bytes);
+        raw
+    }
+}
+
+/// Create a single-precision tensor handle from copies of the caller's arrays
+///
+/// Returns null if an array is null or the data does not fill the shape.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn tensor_create_f32(
+    shape_ptr: *const usize,
+    shape_len: usize,
+    data_ptr: *const f32,
+    data_len: usize,
+) -> *mut CTensorF32 {
+    ffi::guard(ptr::null_mut(), || {
+        if shape_ptr.is_null() || data_ptr.is_null() {
+            ffi::set_null_argument("shape_ptr or data_ptr");
+            return ptr::null_mut();
+        }
+
+        let shape = unsafe { std::slice::from_raw_parts(shape_ptr, shape_len) }.to_vec();
+        let data = unsafe { std::slice::from_raw_parts(data_ptr, data_len) }.to_vec();
+        if shape.iter().product::<usize>() != data.len() {
+            tensor_error(format!("Shape {:?} does not hold {} elements", shape, data.len()));
+            return ptr::null_mut();
+        }
+        CTensorF32::into_raw(TypedTensor::new(shape, TensorData::F32(data)))
+    })
+}
+
+/// Free a single-precision tensor handle; null is ignored
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn tensor_free_f32(tensor: *mut CTensorF32) {
+    ffi::guard((), || {
+        if tensor.is_null() {
+            return;
+        }
+
+        leaks::untrack(tensor);
+        drop(unsafe { Box::from_raw(tensor) });
+    })
 }
 
+/// Rank of a single-precision tensor, or 0 if the handle is null
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn tensor_rank_f32(tensor: *const CTensorF32) -> usize {
+    ffi::guard(0, || {
+        ffi::clear_last_error();
+        if tensor.is_null() {
+            ffi::set_null_argument("tensor");
+            return 0;
+        }
+        unsafe { (*tensor).tensor.shape.len() }
+    })
+}
+
+/// Borrowed array of `tensor_rank_f32` dimensions, valid until the handle is freed
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn tensor_shape_f32(tensor: *const CTensorF32) -> *const usize {
+    ffi::guard(ptr::null(), || {
+        if tensor.is_null() {
+            ffi::set_null_argument("tensor");
+            return ptr::null();
+        }
+        unsafe { (*tensor).tensor.shape.as_ptr() }
+    })
+}
+
+/// Number of elements of a single-precision tensor, or 0 if the handle is null
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn tensor_size_f32(tensor: *const CTensorF32) -> usize {
+    ffi::guard(0, || {
+        ffi::clear_last_error();
+        if tensor.is_null() {
+            ffi::set_null_argument("tensor");
+            return 0;
+        }
+        unsafe { (*tensor).data().len() }
+    })
+}
+
+/// Borrowed array of `tensor_size_f32` elements in row-major order, valid until
+/// the handle is freed
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn tensor_data_f32(tensor: *const CTensorF32) -> *const f32 {
+    ffi::guard(ptr::null(), || {
+        if tensor.is_null() {
+            ffi::set_null_argument("tensor");
+            return ptr::null();
+        }
+        unsafe { (*tensor).data().as_ptr() }
+    })
+}
+
+/// Apply a binary single-precision operation, writing a new handle to `result`
+#[track_caller]
+fn binary_f32_ffi(
+    tensor_a: *const CTensorF32,
+    tensor_b: *const CTensorF32,
+    result: *mut *mut CTensorF32,
+    op: fn(&TypedTensor, &TypedTensor) -> Result<TypedTensor, String>,
+) -> c_int {
+    if tensor_a.is_null() || tensor_b.is_null() || result.is_null() {
+        return ffi::set_null_argument("tensor_a, tensor_b or result");
+    }
+
+    unsafe {
+        match op(&(*tensor_a).tensor, &(*tensor_b).tensor) {
+            Ok(t) => {
+                *result = CTensorF32::into_raw(t);
+                0
+            }
+            Err(e) => tensor_error(e),
+        }
+    }
+}
+
+/// Single-precision tensor AND operation
+#[no_mangle]
+pub extern "C" fn tensor_and_f32_ffi(
+    tensor_a: *const CTensorF32,
+    tensor_b: *const CTensorF32,
+    result: *mut *mut CTensorF32,
+) -> c_int {
+    ffi::guard(ffi::FFIError::Panic as c_int, || {
+        binary_f32_ffi(tensor_a, tensor_b, result, typed_and)
+    })
+}
+
+/// Single-precision tensor OR operation
+#[no_mangle]
+pub extern "C" fn tensor_or_f32_ffi(
+    tensor_a: *const CTensorF32,
+    tensor_b: *const CTensorF32,
+    result: *mut *mut CTensorF32,
+) -> c_int {
+    ffi::guard(ffi::FFIError::Panic as c_int, || {
+        binary_f32_ffi(tensor_a, tensor_b, result, typed_or)
+    })
+}
+
+/// Single-precision tensor IMPLIES operation
+#[no_mangle]
+pub extern "C" fn tensor_implies_f32_ffi(
+    tensor_a: *const CTensorF32,
+    tensor_b: *const CTensorF32,
+    result: *mut *mut CTensorF32,
+) -> c_int {
+    ffi::guard(ffi::FFIError::Panic as c_int, || {
+        binary_f32_ffi(tensor_a, tensor_b, result, typed_implies)
+    })
+}
+
+/// Single-precision tensor NOT operation
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn tensor_not_f32_ffi(
+    tensor: *const CTensorF32,
+    result: *mut *mut CTensorF32,
+) -> c_int {
+    ffi::guard(ffi::FFIError::Panic as c_int, || {
+        if tensor.is_null() || result.is_null() {
+            return ffi::set_null_argument("tensor or result");
+        }
+
+        unsafe {
+            *result = CTensorF32::into_raw(typed_not(&(*tensor).tensor));
+        }
+        0
+    })
+}
+
+/// Compute single-precision tensor similarity
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn tensor_similarity_f32_ffi(
+    tensor_a: *const CTensorF32,
+    tensor_b: *const CTensorF32,
+) -> c_double {
+    ffi::guard(0.0, || {
+        ffi::clear_last_error();
+        if tensor_a.is_null() || tensor_b.is_null() {
+            ffi::set_null_argument("tensor_a or tensor_b");
+            return 0.0;
+        }
+
+        unsafe { typed_similarity(&(*tensor_a).tensor, &(*tensor_b).tensor) as c_double }
+    })
+}
+
+/// Apply kernel function to single-precision tensors
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn tensor_apply_kernel_f32_ffi(
+    kernel_type: *const c_char,
+    tensor_a: *const CTensorF32,
+    tensor_b: *const CTensorF32,
+) -> c_double {
+    ffi::guard(0.0, || {
+        ffi::clear_last_error();
+        if kernel_type.is_null() || tensor_a.is_null() || tensor_b.is_null() {
+            ffi::set_null_argument("kernel_type, tensor_a or tensor_b");
+            return 0.0;
+        }
+
+        unsafe {
+            let kernel_str = CStr::from_ptr(kernel_type).to_string_lossy();
+            match typed_apply_kernel(&kernel_str, &(*tensor_a).tensor, &(*tensor_b).tensor) {
+                Ok(value) => value as c_double,
+                Err(e) => {
+                    tensor_error(e);
+                    0.0
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create(shape: &[usize], data: &[f64]) -> *mut CTensor {
+        tensor_create(shape.as_ptr(), shape.len(), data.as_ptr(), data.len())
+    }
+
+    fn read(tensor: *const CTensor) -> (Vec<usize>, Vec<f64>) {
+        unsafe {
+            (
+                std::slice::from_raw_parts(tensor_shape(tensor), tensor_rank(tensor)).to_vec(),
+                std::slice::from_raw_parts(tensor_data(tensor), tensor_size(tensor)).to_vec(),
+            )
+        }
+    }
+
+    #[test]
+    fn test_handles_can_be_passed_as_several_operands() {
+        let a = create(&[2, 2], &[1.0, 0.0, 0.5, 1.0]);
+        let mut and = ptr::null_mut();
+        assert_eq!(tensor_and_ffi(a, a, &mut and), 0);
+        assert_eq!(read(and).0, vec![2, 2]);
+        assert_eq!(tensor_add_inplace_ffi(a, a), 0);
+        assert_eq!(read(a), (vec![2, 2], vec![2.0, 0.0, 1.0, 2.0]));
+        tensor_free(and);
+        tensor_free(a);
+
+        assert!(create(&[2, 2], &[1.0; 3]).is_null());
+        assert_eq!(ffi::agi_last_error_code(), ffi::FFIError::Tensor as i32);
+        assert_eq!(tensor_size(ptr::null()), 0);
+        assert_eq!(ffi::agi_last_error_code(), ffi::FFIError::NullPointer as i32);
+    }
+
+    #[test]
+    fn test_contractions_and_reductions() {
+        let a = create(&[2, 3], &[1.0, 5.0, 2.0, 4.0, 0.0, 6.0]);
+        let identity = create(&[3, 3], &[1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0]);
+        let spec = std::ffi::CString::new("ij,jk->ik").unwrap();
+        let mut product = ptr::null_mut();
+        assert_eq!(tensor_einsum_ffi(spec.as_ptr(), [a as *const CTensor, identity as *const CTensor].as_ptr(), 2, &mut product), 0);
+        assert_eq!(read(product), read(a));
+
+        let mut sums = ptr::null_mut();
+        assert_eq!(tensor_reduce_ffi(a, TENSOR_REDUCE_SUM, [1].as_ptr(), 1, 0, &mut sums), 0);
+        assert_eq!(read(sums), (vec![2], vec![8.0, 10.0]));
+        let mut argmax = ptr::null_mut();
+        assert_eq!(tensor_argmax_ffi(a, 1, 1, &mut argmax), 0);
+        assert_eq!(read(argmax), (vec![2, 1], vec![1.0, 2.0]));
+        let mut unified = ptr::null_mut();
+        assert_eq!(tensor_unify_ffi([a as *const CTensor, a as *const CTensor].as_ptr(), 2, &mut unified), 0);
+        assert_eq!(read(unified), read(a));
+
+        assert_eq!(tensor_reduce_ffi(a, 9, ptr::null(), 0, 0, &mut sums), ffi::FFIError::InvalidArgument as c_int);
+        for tensor in [a, identity, product, sums, argmax, unified] {
+            tensor_free(tensor);
+        }
+    }
+}
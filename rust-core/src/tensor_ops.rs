@@ -4,11 +4,60 @@
 //! implementing Einstein summation, tensor contractions, and logical operations
 //! with maximum performance using SIMD and parallel processing.
 
-use ndarray::{Array1, Array2, Array3, ArrayD, IxDyn};
-use ndarray::parallel::prelude::*;
+pub mod autograd;
+pub mod broadcast;
+pub mod concat;
+pub mod conv;
+pub mod device;
+pub mod dtype;
+pub mod einsum;
+pub mod expr;
+#[cfg(feature = "gpu")]
+pub mod gpu;
+pub mod index;
+pub mod init;
+pub mod inplace;
+pub mod io;
+pub mod kernel;
+pub mod logic;
+pub mod matmul;
+pub mod mmap;
+pub mod reduce;
+pub mod search;
+pub mod shape;
+pub mod simd;
+pub mod sort;
+pub mod sparse;
+pub mod spectral;
+pub mod stable;
+pub mod unify;
+pub mod view;
+
+use ndarray::{ArrayD, IxDyn};
 use rayon::prelude::*;
-use std::sync::Arc;
-use tracing::{info, instrument};
+use tracing::instrument;
+
+pub use autograd::{Gradients, Tape, Var};
+pub use broadcast::{broadcast_shape, broadcast_zip};
+pub use concat::{concat, stack};
+pub use conv::{conv1d, conv2d, correlate, ConvMethod, ConvOptions};
+pub use device::{device_einsum, DeviceTensor, TensorDevice};
+pub use dtype::{DType, TensorData, TypedTensor};
+pub use einsum::{einsum, EinsumSpec};
+pub use expr::{CompiledExpr, TensorExpr};
+pub use inplace::{tensor_and_inplace, tensor_implies_inplace, tensor_not_inplace, tensor_or_inplace};
+pub use io::{from_safetensors, load_npz, load_safetensors, save_npz, save_safetensors, to_safetensors};
+pub use kernel::{kernel_matrix, Kernel, KernelFunction, KernelRegistry};
+pub use logic::{tensor_iff, tensor_nand, tensor_nor, tensor_xor, LogicSemantics};
+pub use matmul::{batched_matmul, matmul};
+pub use mmap::MappedTensors;
+pub use reduce::Reduction;
+pub use search::{knn, tensor_similarity_matrix, LshIndex, Neighbor};
+pub use sparse::{sparse_and, sparse_contract, sparse_or, sparse_similarity, CsrMatrix, SparseTensor};
+pub use spectral::{fft, ifft, power_spectrum, rfft, ComplexTensor};
+pub use stable::{kahan_sum, logsumexp, pairwise_sum, stable_dot, stable_norm, NonFiniteReport};
+pub use unify::{unify_tensors_with, UnifyStrategy};
+pub use view::{view_and, view_implies, view_not, view_or, TensorView};
 
 /// Tensor representation with shape and data
 #[derive(Debug, Clone)]
@@ -16,6 +65,8 @@ pub struct Tensor {
     pub shape: Vec<usize>,
     pub data: Vec<f64>,
     pub rank: usize,
+    /// Whether gradients flow to this tensor when it is a leaf of an autograd `Tape`
+    pub requires_grad: bool,
 }
 
 impl Tensor {
@@ -32,7 +83,7 @@ impl Tensor {
             expected_size
         );
         
-        Self { shape, data, rank }
+        Self { shape, data, rank, requires_grad: false }
     }
     
     /// Create tensor from ndarray ArrayD
@@ -41,7 +92,7 @@ impl Tensor {
         let data = arr.into_raw_vec();
         let rank = shape.len();
         
-        Self { shape, data, rank }
+        Self { shape, data, rank, requires_grad: false }
     }
     
     /// Convert to ndarray ArrayD for advanced operations
@@ -55,116 +106,112 @@ impl Tensor {
         self.data.len()
     }
     
+    /// Mark whether gradients should be computed for this tensor
+    pub fn with_requires_grad(mut self, requires_grad: bool) -> Self {
+        self.requires_grad = requires_grad;
+        self
+    }
+    
+    /// Row-major element strides of each axis
+    pub fn strides(&self) -> Vec<usize> {
+        row_major_strides(&self.shape)
+    }
+    
     /// Compute tensor norm (L2)
     pub fn norm(&self) -> f64 {
-        self.data.par_iter()
-            .map(|x| x * x)
-            .sum::<f64>()
-            .sqrt()
+        stable::stable_norm(&self.data)
     }
     
     /// Normalize tensor to unit norm
     pub fn normalize(&mut self) {
-        let norm = self.norm();
-        if norm > 1e-10 {
-            self.data.par_iter_mut().for_each(|x| *x /= norm);
-        }
+        inplace::normalize_buffer(&mut self.data);
     }
 }
 
 /// High-performance tensor AND operation (logical conjunction)
-/// Uses Einstein summation: A_i * B_i
+/// Uses Einstein summation: A_i * B_i, broadcasting mismatched shapes
 #[instrument(skip(tensor_a, tensor_b))]
 pub fn tensor_and(tensor_a: &Tensor, tensor_b: &Tensor) -> Result<Tensor, String> {
-    if tensor_a.shape != tensor_b.shape {
-        return Err(format!(
-            "Shape mismatch: {:?} vs {:?}",
-            tensor_a.shape, tensor_b.shape
-        ));
+    if tensor_a.shape == tensor_b.shape {
+        return stable::checked("AND", Ok(Tensor::new(tensor_a.shape.clone(), simd::par_binary(&tensor_a.data, &tensor_b.data, simd::mul))));
     }
-    
-    let data: Vec<f64> = tensor_a.data
-        .par_iter()
-        .zip(tensor_b.data.par_iter())
-        .map(|(a, b)| a * b)
-        .collect();
-    
-    Ok(Tensor {
-        shape: tensor_a.shape.clone(),
-        data,
-        rank: tensor_a.rank,
-    })
+    stable::checked("AND", broadcast_zip(tensor_a, tensor_b, |a, b| a * b))
 }
 
 /// High-performance tensor OR operation (logical disjunction)
-/// Uses element-wise maximum with normalization
+/// Uses element-wise maximum with normalization, broadcasting mismatched shapes
 #[instrument(skip(tensor_a, tensor_b))]
 pub fn tensor_or(tensor_a: &Tensor, tensor_b: &Tensor) -> Result<Tensor, String> {
-    if tensor_a.shape != tensor_b.shape {
-        return Err(format!(
-            "Shape mismatch: {:?} vs {:?}",
-            tensor_a.shape, tensor_b.shape
-        ));
-    }
-    
-    let mut data: Vec<f64> = tensor_a.data
-        .par_iter()
-        .zip(tensor_b.data.par_iter())
-        .map(|(a, b)| a.max(*b))
-        .collect();
+    let mut result = if tensor_a.shape == tensor_b.shape {
+        Tensor::new(tensor_a.shape.clone(), simd::par_binary(&tensor_a.data, &tensor_b.data, simd::max))
+    } else {
+        broadcast_zip(tensor_a, tensor_b, f64::max)?
+    };
     
     // Normalize
-    let norm: f64 = data.par_iter().map(|x| x * x).sum::<f64>().sqrt();
-    if norm > 1e-10 {
-        data.par_iter_mut().for_each(|x| *x /= norm);
-    }
+    result.normalize();
     
-    Ok(Tensor {
-        shape: tensor_a.shape.clone(),
-        data,
-        rank: tensor_a.rank,
-    })
+    stable::checked("OR", Ok(result))
 }
 
 /// High-performance tensor NOT operation (logical negation)
 /// Uses complement: 1 - tensor
 #[instrument(skip(tensor))]
 pub fn tensor_not(tensor: &Tensor) -> Tensor {
-    let data: Vec<f64> = tensor.data.par_iter().map(|x| 1.0 - x).collect();
+    let data = simd::par_not(&tensor.data);
     
     Tensor {
         shape: tensor.shape.clone(),
         data,
         rank: tensor.rank,
+        requires_grad: false,
     }
 }
 
 /// High-performance tensor IMPLIES operation (logical implication)
-/// Uses: max(1 - A, B) for fuzzy implication
+/// Uses: max(1 - A, B) for fuzzy implication, broadcasting mismatched shapes
 #[instrument(skip(tensor_a, tensor_b))]
 pub fn tensor_implies(tensor_a: &Tensor, tensor_b: &Tensor) -> Result<Tensor, String> {
-    if tensor_a.shape != tensor_b.shape {
-        return Err(format!(
-            "Shape mismatch: {:?} vs {:?}",
-            tensor_a.shape, tensor_b.shape
-        ));
+    if tensor_a.shape == tensor_b.shape {
+        return stable::checked("IMPLIES", Ok(Tensor::new(tensor_a.shape.clone(), simd::par_binary(&tensor_a.data, &tensor_b.data, simd::implies))));
     }
-    
-    let data: Vec<f64> = tensor_a.data
-        .par_iter()
-        .zip(tensor_b.data.par_iter())
-        .map(|(a, b)| (1.0 - a).max(*b))
-        .collect();
-    
-    Ok(Tensor {
-        shape: tensor_a.shape.clone(),
-        data,
-        rank: tensor_a.rank,
-    })
+    stable::checked("IMPLIES", broadcast_zip(tensor_a, tensor_b, |a, b| (1.0 - a).max(b)))
 }
 
+/// Element-wise sum with broadcasting
+#[instrument(skip(tensor_a, tensor_b))]
+pub fn tensor_add(tensor_a: &Tensor, tensor_b: &Tensor) -> Result<Tensor, String> {
+    stable::checked("add", broadcast_zip(tensor_a, tensor_b, |a, b| a + b))
+}
+
+/// Element-wise difference with broadcasting
+#[instrument(skip(tensor_a, tensor_b))]
+pub fn tensor_sub(tensor_a: &Tensor, tensor_b: &Tensor) -> Result<Tensor, String> {
+    stable::checked("sub", broadcast_zip(tensor_a, tensor_b, |a, b| a - b))
+}
+
+/// Element-wise product with broadcasting
+#[instrument(skip(tensor_a, tensor_b))]
+pub fn tensor_mul(tensor_a: &Tensor, tensor_b: &Tensor) -> Result<Tensor, String> {
+    stable::checked("mul", broadcast_zip(tensor_a, tensor_b, |a, b| a * b))
+}
+
+/// Element-wise quotient with broadcasting; division by zero follows IEEE 754
+#[instrument(skip(tensor_a, tensor_b))]
+pub fn tensor_div(tensor_a: &Tensor, tensor_b: &Tensor) -> Result<Tensor, String> {
+    stable::checked("div", broadcast_zip(tensor_a, tensor_b, |a, b| a / b))
+}
+
+/// Minimum number of multiply-adds before a contraction runs in parallel
+const PARALLEL_CONTRACTION_WORK: usize = 1 << 14;
+
 /// Advanced Einstein summation for arbitrary tensor ranks
 /// Supports complex contractions like: A_ijkl * B_jkmn = C_ilmn
+///
+/// Each index is a label shared between the tensors. Labels listed in
+/// `output_indices` are kept in that order, permuting the result as needed; every
+/// other label is summed over, so several indices can be contracted at once and an
+/// index repeated within one tensor takes its diagonal.
 #[instrument(skip(tensor_a, tensor_b))]
 pub fn einstein_summation(
     tensor_a: &Tensor,
@@ -178,86 +225,170 @@ pub fn einstein_summation(
         return Err("Index count must match tensor rank".to_string());
     }
     
-    // Find contracted indices (appear in both A and B)
-    let mut contracted = Vec::new();
-    for (i, idx_a) in indices_a.iter().enumerate() {
-        if let Some(j) = indices_b.iter().position(|&x| x == *idx_a) {
-            // Verify dimension consistency
-            if tensor_a.shape[i] != tensor_b.shape[j] {
-                return Err(format!(
-                    "Dimension mismatch for contracted index {}: {} vs {}",
-                    idx_a, tensor_a.shape[i], tensor_b.shape[j]
-                ));
-            }
-            contracted.push((i, j, *idx_a));
-        }
-    }
-    
-    // Compute output shape
-    let mut output_shape = Vec::new();
-    for &out_idx in output_indices {
-        // Find dimension from tensor A or B
-        if let Some(pos) = indices_a.iter().position(|&x| x == out_idx) {
-            output_shape.push(tensor_a.shape[pos]);
-        } else if let Some(pos) = indices_b.iter().position(|&x| x == out_idx) {
-            output_shape.push(tensor_b.shape[pos]);
-        } else {
-            return Err(format!("Output index {} not found in input tensors", out_idx));
-        }
-    }
-    
-    // Perform contraction using ndarray for efficiency
-    let arr_a = tensor_a.to_ndarray();
-    let arr_b = tensor_b.to_ndarray();
+    let plan = ContractionPlan::new(tensor_a, tensor_b, indices_a, indices_b, output_indices)?;
+    let output_size: usize = plan.output_shape.iter().product();
+    let work = output_size.saturating_mul(plan.summed.iter().map(|axis| axis.dim).product());
     
-    // For now, implement a simplified version
-    // Full implementation would use advanced ndarray operations
-    let output_size: usize = output_shape.iter().product();
-    let mut output_data = vec![0.0; output_size];
+    let element = |output_index: usize| plan.element(&tensor_a.data, &tensor_b.data, output_index);
+    let data: Vec<f64> = if work >= PARALLEL_CONTRACTION_WORK {
+        (0..output_size).into_par_iter().map(element).collect()
+    } else {
+        (0..output_size).map(element).collect()
+    };
     
-    // Simplified contraction (for rank 2 tensors)
-    if tensor_a.rank == 2 && tensor_b.rank == 2 && contracted.len() == 1 {
-        let (i_a, i_b, _) = contracted[0];
-        let rows_a = tensor_a.shape[1 - i_a];
-        let cols_b = tensor_b.shape[1 - i_b];
-        let common = tensor_a.shape[i_a];
+    let rank = plan.output_shape.len();
+    stable::checked("Einstein summation", Ok(Tensor {
+        shape: plan.output_shape,
+        data,
+        rank,
+        requires_grad: false,
+    }))
+}
+
+/// One index label of a contraction with its extent and strides into both operands
+///
+/// A label absent from an operand has stride zero there; a label repeated within an
+/// operand has the sum of its strides, walking the diagonal.
+#[derive(Debug, Clone, Copy)]
+struct ContractionAxis {
+    label: usize,
+    dim: usize,
+    stride_a: usize,
+    stride_b: usize,
+}
+
+/// Loop structure of an Einstein summation
+#[derive(Debug)]
+struct ContractionPlan {
+    output_shape: Vec<usize>,
+    /// Output axes in output order
+    free: Vec<ContractionAxis>,
+    /// Summed axes from outermost to innermost loop
+    summed: Vec<ContractionAxis>,
+}
+
+impl ContractionPlan {
+    fn new(
+        tensor_a: &Tensor,
+        tensor_b: &Tensor,
+        indices_a: &[usize],
+        indices_b: &[usize],
+        output_indices: &[usize],
+    ) -> Result<Self, String> {
+        let strides_a = row_major_strides(&tensor_a.shape);
+        let strides_b = row_major_strides(&tensor_b.shape);
         
-        // Matrix multiplication: C = A * B
-        for i in 0..rows_a {
-            for j in 0..cols_b {
-                let mut sum = 0.0;
-                for k in 0..common {
-                    let idx_a = if i_a == 0 { k * rows_a + i } else { i * common + k };
-                    let idx_b = if i_b == 0 { j * common + k } else { k * cols_b + j };
-                    sum += tensor_a.data[idx_a] * tensor_b.data[idx_b];
+        // Collect every label with a consistent extent
+        let mut axes: Vec<ContractionAxis> = Vec::new();
+        let operands = [(indices_a, &tensor_a.shape, &strides_a, true), (indices_b, &tensor_b.shape, &strides_b, false)];
+        for (indices, shape, strides, is_a) in operands {
+            for (position, &label) in indices.iter().enumerate() {
+                let axis = match axes.iter_mut().find(|axis| axis.label == label) {
+                    Some(axis) => {
+                        if axis.dim != shape[position] {
+                            return Err(format!(
+                                "Dimension mismatch for contracted index {}: {} vs {}",
+                                label, axis.dim, shape[position]
+                            ));
+                        }
+                        axis
+                    }
+                    None => {
+                        axes.push(ContractionAxis { label, dim: shape[position], stride_a: 0, stride_b: 0 });
+                        axes.last_mut().expect("just pushed")
+                    }
+                };
+                if is_a {
+                    axis.stride_a += strides[position];
+                } else {
+                    axis.stride_b += strides[position];
                 }
-                output_data[i * cols_b + j] = sum;
             }
         }
         
-        return Ok(Tensor {
-            shape: output_shape,
-            data: output_data,
-            rank: output_shape.len(),
+        let mut free = Vec::with_capacity(output_indices.len());
+        for (position, &label) in output_indices.iter().enumerate() {
+            if output_indices[..position].contains(&label) {
+                return Err(format!("Output index {} appears more than once", label));
+            }
+            match axes.iter().find(|axis| axis.label == label) {
+                Some(axis) => free.push(*axis),
+                None => return Err(format!("Output index {} not found in input tensors", label)),
+            }
+        }
+        
+        // Innermost loops walk the smallest strides for memory locality; ties keep
+        // label order so the summation order is deterministic
+        let mut summed: Vec<ContractionAxis> = axes.into_iter()
+            .filter(|axis| !output_indices.contains(&axis.label))
+            .collect();
+        summed.sort_by(|a, b| {
+            (b.stride_a + b.stride_b).cmp(&(a.stride_a + a.stride_b)).then(a.label.cmp(&b.label))
         });
+        
+        Ok(Self {
+            output_shape: free.iter().map(|axis| axis.dim).collect(),
+            free,
+            summed,
+        })
     }
     
-    // Fallback: element-wise product for same shape
-    if tensor_a.shape == tensor_b.shape {
-        let data: Vec<f64> = tensor_a.data
-            .par_iter()
-            .zip(tensor_b.data.par_iter())
-            .map(|(a, b)| a * b)
-            .collect();
+    /// Value of the output element at row-major position `output_index`
+    fn element(&self, data_a: &[f64], data_b: &[f64], mut output_index: usize) -> f64 {
+        let (mut offset_a, mut offset_b) = (0, 0);
+        for axis in self.free.iter().rev() {
+            let coordinate = output_index % axis.dim;
+            output_index /= axis.dim;
+            offset_a += coordinate * axis.stride_a;
+            offset_b += coordinate * axis.stride_b;
+        }
         
-        return Ok(Tensor {
-            shape: output_shape,
-            data,
-            rank: output_shape.len(),
-        });
+        let Some((inner, outer)) = self.summed.split_last() else {
+            return data_a[offset_a] * data_b[offset_b];
+        };
+        if self.summed.iter().any(|axis| axis.dim == 0) {
+            return 0.0;
+        }
+        
+        // Odometer over the outer summed axes with a tight innermost loop
+        let mut counters = vec![0; outer.len()];
+        let mut sum = 0.0;
+        loop {
+            let (mut a, mut b) = (offset_a, offset_b);
+            for _ in 0..inner.dim {
+                sum += data_a[a] * data_b[b];
+                a += inner.stride_a;
+                b += inner.stride_b;
+            }
+            
+            let mut level = outer.len();
+            loop {
+                if level == 0 {
+                    return sum;
+                }
+                level -= 1;
+                let axis = &outer[level];
+                counters[level] += 1;
+                offset_a += axis.stride_a;
+                offset_b += axis.stride_b;
+                if counters[level] < axis.dim {
+                    break;
+                }
+                offset_a -= axis.dim * axis.stride_a;
+                offset_b -= axis.dim * axis.stride_b;
+                counters[level] = 0;
+            }
+        }
     }
-    
-    Err("Complex tensor contraction not yet implemented".to_string())
+}
+
+/// Element strides of a row-major tensor
+fn row_major_strides(shape: &[usize]) -> Vec<usize> {
+    let mut strides = vec![1; shape.len()];
+    for axis in (0..shape.len().saturating_sub(1)).rev() {
+        strides[axis] = strides[axis + 1] * shape[axis + 1];
+    }
+    strides
 }
 
 /// Compute cosine similarity between two tensors
@@ -267,16 +398,12 @@ pub fn tensor_similarity(tensor_a: &Tensor, tensor_b: &Tensor) -> f64 {
         return 0.0;
     }
     
-    let dot_product: f64 = tensor_a.data
-        .par_iter()
-        .zip(tensor_b.data.par_iter())
-        .map(|(a, b)| a * b)
-        .sum();
+    let dot_product = stable::stable_dot(&tensor_a.data, &tensor_b.data);
     
     let norm_a = tensor_a.norm();
     let norm_b = tensor_b.norm();
     
-    if norm_a < 1e-10 || norm_b < 1e-10 {
+    if norm_a < stable::epsilon() || norm_b < stable::epsilon() {
         return 0.0;
     }
     
@@ -284,82 +411,19 @@ pub fn tensor_similarity(tensor_a: &Tensor, tensor_b: &Tensor) -> f64 {
 }
 
 /// Create unified representation from multiple tensors (averaging)
+///
+/// See `unify_tensors_with` for strategies that resist outliers.
 pub fn unify_tensors(tensors: &[Tensor]) -> Result<Tensor, String> {
-    if tensors.is_empty() {
-        return Err("Cannot unify empty tensor list".to_string());
-    }
-    
-    let first_shape = &tensors[0].shape;
-    
-    // Verify all tensors have same shape
-    for tensor in tensors.iter().skip(1) {
-        if tensor.shape != *first_shape {
-            return Err("All tensors must have the same shape for unification".to_string());
-        }
-    }
-    
-    let size = tensors[0].data.len();
-    let count = tensors.len() as f64;
-    
-    let mut unified_data = vec![0.0; size];
-    
-    for tensor in tensors {
-        for (i, &value) in tensor.data.iter().enumerate() {
-            unified_data[i] += value;
-        }
-    }
-    
-    // Average
-    unified_data.par_iter_mut().for_each(|x| *x /= count);
-    
-    Ok(Tensor {
-        shape: first_shape.clone(),
-        data: unified_data,
-        rank: first_shape.len(),
-    })
+    unify_tensors_with(tensors, &UnifyStrategy::Mean)
 }
 
-/// Apply kernel function to tensors (for kernel machines)
+/// Apply a built-in kernel function, looked up by name in `KernelRegistry::builtin`
 pub fn apply_kernel(
     kernel_type: &str,
     tensor_a: &Tensor,
     tensor_b: &Tensor,
 ) -> Result<f64, String> {
-    match kernel_type {
-        "linear" => {
-            // Linear kernel: K(x, y) = x^T * y
-            Ok(tensor_a.data
-                .par_iter()
-                .zip(tensor_b.data.par_iter())
-                .map(|(a, b)| a * b)
-                .sum())
-        }
-        "polynomial" => {
-            // Polynomial kernel: K(x, y) = (x^T * y + c)^d
-            let linear = tensor_a.data
-                .par_iter()
-                .zip(tensor_b.data.par_iter())
-                .map(|(a, b)| a * b)
-                .sum::<f64>();
-            let c = 1.0;
-            let d = 2.0;
-            Ok((linear + c).powf(d))
-        }
-        "rbf" => {
-            // RBF (Gaussian) kernel: K(x, y) = exp(-gamma * ||x - y||^2)
-            let squared_diff: f64 = tensor_a.data
-                .par_iter()
-                .zip(tensor_b.data.par_iter())
-                .map(|(a, b)| {
-                    let diff = a - b;
-                    diff * diff
-                })
-                .sum();
-            let gamma = 1.0;
-            Ok((-gamma * squared_diff).exp())
-        }
-        _ => Err(format!("Unknown kernel type: {}", kernel_type)),
-    }
+    KernelRegistry::builtin().apply(kernel_type, tensor_a, tensor_b)
 }
 
 #[cfg(test)]
@@ -394,7 +458,10 @@ mod tests {
     fn test_tensor_not() {
         let a = Tensor::new(vec![3], vec![0.2, 0.5, 0.8]);
         let result = tensor_not(&a);
-        assert_eq!(result.data, vec![0.8, 0.5, 0.2]);
+        assert_eq!(result.data.len(), 3);
+        for (actual, expected) in result.data.iter().zip([0.8, 0.5, 0.2]) {
+            assert!((actual - expected).abs() < 1e-12);
+        }
     }
     
     #[test]
@@ -404,5 +471,78 @@ mod tests {
         let similarity = tensor_similarity(&a, &b);
         assert!((similarity - 1.0).abs() < 1e-10);
     }
+    
+    #[test]
+    fn test_einstein_summation_matmul_and_transpose() {
+        let a = Tensor::new(vec![2, 3], vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let b = Tensor::new(vec![3, 2], vec![7.0, 8.0, 9.0, 10.0, 11.0, 12.0]);
+        let product = einstein_summation(&a, &b, &[0, 1], &[1, 2], &[0, 2]).unwrap();
+        assert_eq!(product.shape, vec![2, 2]);
+        assert_eq!(product.data, vec![58.0, 64.0, 139.0, 154.0]);
+        
+        // Permuted output gives the transposed product
+        let transposed = einstein_summation(&a, &b, &[0, 1], &[1, 2], &[2, 0]).unwrap();
+        assert_eq!(transposed.data, vec![58.0, 139.0, 64.0, 154.0]);
+        
+        assert!(einstein_summation(&a, &a, &[0, 1], &[0, 1], &[3]).is_err());
+        assert!(einstein_summation(&a, &b, &[0, 1], &[0, 1], &[0]).is_err());
+    }
+    
+    #[test]
+    fn test_einstein_summation_multiple_contractions() {
+        // A_ijkl * B_jkmn = C_ilmn against a direct loop
+        let (i, j, k, l, m, n) = (2, 3, 4, 2, 3, 2);
+        let a = Tensor::new(vec![i, j, k, l], (0..i * j * k * l).map(|x| (x as f64 * 0.37).sin()).collect());
+        let b = Tensor::new(vec![j, k, m, n], (0..j * k * m * n).map(|x| (x as f64 * 0.11).cos()).collect());
+        let c = einstein_summation(&a, &b, &[0, 1, 2, 3], &[1, 2, 4, 5], &[0, 3, 4, 5]).unwrap();
+        assert_eq!(c.shape, vec![i, l, m, n]);
+        
+        let mut position = 0;
+        for ii in 0..i {
+            for ll in 0..l {
+                for mm in 0..m {
+                    for nn in 0..n {
+                        let mut expected = 0.0;
+                        for jj in 0..j {
+                            for kk in 0..k {
+                                expected += a.data[((ii * j + jj) * k + kk) * l + ll]
+                                    * b.data[((jj * k + kk) * m + mm) * n + nn];
+                            }
+                        }
+                        assert!((c.data[position] - expected).abs() < 1e-12);
+                        position += 1;
+                    }
+                }
+            }
+        }
+    }
+    
+    #[test]
+    fn test_einstein_summation_trace_and_outer_product() {
+        let a = Tensor::new(vec![2, 2], vec![1.0, 2.0, 3.0, 4.0]);
+        let ones = Tensor::new(vec![2], vec![1.0, 1.0]);
+        
+        // Repeated index walks the diagonal
+        let trace = einstein_summation(&a, &ones, &[0, 0], &[1], &[]).unwrap();
+        assert_eq!(trace.shape, Vec::<usize>::new());
+        assert_eq!(trace.data, vec![10.0]);
+        
+        let outer = einstein_summation(&ones, &ones, &[0], &[1], &[0, 1]).unwrap();
+        assert_eq!(outer.data, vec![1.0; 4]);
+    }
+    
+    #[test]
+    fn test_elementwise_ops_broadcast() {
+        let matrix = Tensor::new(vec![2, 2], vec![0.2, 0.4, 0.6, 0.8]);
+        let row = Tensor::new(vec![2], vec![0.5, 1.0]);
+        assert_eq!(tensor_and(&matrix, &row).unwrap().data, vec![0.1, 0.4, 0.3, 0.8]);
+        assert_eq!(tensor_implies(&matrix, &row).unwrap().shape, vec![2, 2]);
+        assert_eq!(tensor_sub(&matrix, &row).unwrap().data[1], 0.4 - 1.0);
+        assert_eq!(tensor_div(&row, &matrix).unwrap().data, vec![2.5, 2.5, 0.5 / 0.6, 1.25]);
+        
+        let column = Tensor::new(vec![2, 1], vec![1.0, 2.0]);
+        let sum = tensor_add(&column, &row).unwrap();
+        assert_eq!(sum.data, vec![1.5, 2.0, 2.5, 3.0]);
+        assert!(tensor_mul(&matrix, &Tensor::new(vec![3], vec![1.0; 3])).is_err());
+    }
 }
-
@@ -0,0 +1,413 @@
+//! Autograd - Reverse-mode differentiation over tensor operations
+//!
+//! A `Tape` records every operation applied to its variables together with a
+//! closure mapping the output gradient to gradients for the operands. Leaves take
+//! their `requires_grad` flag from the tensor they wrap, and operations whose
+//! operands need no gradient are not recorded for backpropagation at all.
+//! Broadcast operands receive gradients summed back to their own shape.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use rayon::prelude::*;
+
+use super::{broadcast_zip, einsum, stable, tensor_add, EinsumSpec, Reduction, Tensor};
+
+/// Gradients of the operands from the gradient of the output; `None` where the
+/// operand needs no gradient
+type Backward = Box<dyn Fn(&Tensor, &[bool]) -> Result<Vec<Option<Tensor>>, String>>;
+
+struct TapeNode {
+    parents: Vec<usize>,
+    shape: Vec<usize>,
+    requires_grad: bool,
+    backward: Option<Backward>,
+}
+
+/// Record of operations for reverse-mode differentiation
+#[derive(Default)]
+pub struct Tape {
+    nodes: RefCell<Vec<TapeNode>>,
+}
+
+impl Tape {
+    /// Create an empty tape
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of recorded variables
+    pub fn len(&self) -> usize {
+        self.nodes.borrow().len()
+    }
+
+    /// Whether nothing has been recorded
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Input variable; gradients are computed for it if `tensor.requires_grad`
+    pub fn leaf(&self, tensor: Tensor) -> Var<'_> {
+        let requires_grad = tensor.requires_grad;
+        self.push(tensor, Vec::new(), requires_grad, None)
+    }
+
+    fn push(&self, value: Tensor, parents: Vec<usize>, requires_grad: bool, backward: Option<Backward>) -> Var<'_> {
+        let mut nodes = self.nodes.borrow_mut();
+        nodes.push(TapeNode { parents, shape: value.shape.clone(), requires_grad, backward });
+        Var { tape: self, id: nodes.len() - 1, value }
+    }
+
+    fn record<'t>(
+        &'t self,
+        value: Tensor,
+        parents: &[&Var<'t>],
+        backward: impl Fn(&Tensor, &[bool]) -> Result<Vec<Option<Tensor>>, String> + 'static,
+    ) -> Result<Var<'t>, String> {
+        if parents.iter().any(|parent| !std::ptr::eq(parent.tape, self)) {
+            return Err("Variables belong to different tapes".to_string());
+        }
+        let requires_grad = parents.iter().any(|parent| parent.requires_grad());
+        let backward: Option<Backward> = if requires_grad { Some(Box::new(backward)) } else { None };
+        Ok(self.push(value, parents.iter().map(|parent| parent.id).collect(), requires_grad, backward))
+    }
+}
+
+/// Tensor value recorded on a `Tape`
+#[derive(Clone)]
+pub struct Var<'t> {
+    tape: &'t Tape,
+    id: usize,
+    value: Tensor,
+}
+
+impl<'t> Var<'t> {
+    /// Forward value
+    pub fn value(&self) -> &Tensor {
+        &self.value
+    }
+
+    /// Whether gradients flow through this variable
+    pub fn requires_grad(&self) -> bool {
+        self.tape.nodes.borrow()[self.id].requires_grad
+    }
+
+    fn binary(
+        &self,
+        other: &Var<'t>,
+        forward: impl Fn(f64, f64) -> f64 + Sync,
+        gradient: impl Fn(f64, f64, f64) -> (f64, f64) + Sync + 'static,
+    ) -> Result<Var<'t>, String> {
+        let value = broadcast_zip(&self.value, &other.value, forward)?;
+        let (a, b) = (self.value.clone(), other.value.clone());
+        self.tape.record(value, &[self, other], move |grad, _| {
+            let a_full = broadcast_zip(&a, grad, |a, _| a)?;
+            let b_full = broadcast_zip(&b, grad, |b, _| b)?;
+            let (grad_a, grad_b): (Vec<f64>, Vec<f64>) = (0..grad.size())
+                .into_par_iter()
+                .map(|i| gradient(a_full.data[i], b_full.data[i], grad.data[i]))
+                .unzip();
+            Ok(vec![
+                Some(unbroadcast(Tensor::new(grad.shape.clone(), grad_a), &a.shape)?),
+                Some(unbroadcast(Tensor::new(grad.shape.clone(), grad_b), &b.shape)?),
+            ])
+        })
+    }
+
+    /// Element-wise sum, as `tensor_add`
+    pub fn add(&self, other: &Var<'t>) -> Result<Var<'t>, String> {
+        self.binary(other, |a, b| a + b, |_, _, g| (g, g))
+    }
+
+    /// Element-wise difference, as `tensor_sub`
+    pub fn sub(&self, other: &Var<'t>) -> Result<Var<'t>, String> {
+        self.binary(other, |a, b| a - b, |_, _, g| (g, -g))
+    }
+
+    /// Element-wise product, as `tensor_mul`
+    pub fn mul(&self, other: &Var<'t>) -> Result<Var<'t>, String> {
+        self.binary(other, |a, b| a * b, |a, b, g| (g * b, g * a))
+    }
+
+    /// Element-wise quotient, as `tensor_div`
+    pub fn div(&self, other: &Var<'t>) -> Result<Var<'t>, String> {
+        self.binary(other, |a, b| a / b, |a, b, g| (g / b, -g * a / (b * b)))
+    }
+
+    /// Logical AND, as `tensor_and`
+    pub fn and(&self, other: &Var<'t>) -> Result<Var<'t>, String> {
+        self.mul(other)
+    }
+
+    /// Logical IMPLIES, as `tensor_implies`; the gradient follows the larger term
+    pub fn implies(&self, other: &Var<'t>) -> Result<Var<'t>, String> {
+        self.binary(
+            other,
+            |a, b| (1.0 - a).max(b),
+            |a, b, g| if 1.0 - a >= b { (-g, 0.0) } else { (0.0, g) },
+        )
+    }
+
+    /// Logical OR, as `tensor_or`: element-wise maximum normalized to unit norm
+    pub fn or(&self, other: &Var<'t>) -> Result<Var<'t>, String> {
+        let maximum = broadcast_zip(&self.value, &other.value, f64::max)?;
+        let norm = maximum.norm();
+        let mut value = maximum;
+        value.normalize();
+
+        let (a, b, normalized) = (self.value.clone(), other.value.clone(), value.clone());
+        self.tape.record(value, &[self, other], move |grad, _| {
+            // Through normalization: (g - y (y . g)) / |m|
+            let grad_max = if norm > stable::epsilon() {
+                let projection: f64 = normalized.data.par_iter().zip(&grad.data).map(|(y, g)| y * g).sum();
+                broadcast_zip(grad, &normalized, |g, y| (g - y * projection) / norm)?
+            } else {
+                grad.clone()
+            };
+            let a_full = broadcast_zip(&a, grad, |a, _| a)?;
+            let b_full = broadcast_zip(&b, grad, |b, _| b)?;
+            let (grad_a, grad_b): (Vec<f64>, Vec<f64>) = (0..grad.size())
+                .into_par_iter()
+                .map(|i| if a_full.data[i] >= b_full.data[i] { (grad_max.data[i], 0.0) } else { (0.0, grad_max.data[i]) })
+                .unzip();
+            Ok(vec![
+                Some(unbroadcast(Tensor::new(grad.shape.clone(), grad_a), &a.shape)?),
+                Some(unbroadcast(Tensor::new(grad.shape.clone(), grad_b), &b.shape)?),
+            ])
+        })
+    }
+
+    /// Logical NOT, as `tensor_not`
+    pub fn not(&self) -> Var<'t> {
+        let value = Tensor::new(self.value.shape.clone(), self.value.data.par_iter().map(|x| 1.0 - x).collect());
+        self.tape
+            .record(value, &[self], |grad, _| Ok(vec![Some(Tensor::new(grad.shape.clone(), grad.data.iter().map(|g| -g).collect()))]))
+            .expect("operand is on this tape")
+    }
+
+    /// Contraction in subscript notation, as `einsum`
+    ///
+    /// Operands that require gradients may not repeat a subscript.
+    pub fn einsum(spec: &str, operands: &[&Var<'t>]) -> Result<Var<'t>, String> {
+        let first = operands.first().ok_or("einsum needs at least one operand")?;
+        let values: Vec<&Tensor> = operands.iter().map(|operand| &operand.value).collect();
+        let value = einsum(spec, &values)?;
+
+        let parsed = EinsumSpec::parse(spec)?;
+        for (labels, operand) in parsed.inputs.iter().zip(operands) {
+            let repeated = labels.iter().enumerate().any(|(i, label)| labels[..i].contains(label));
+            if repeated && operand.requires_grad() {
+                return Err(format!("Repeated subscripts in '{}' are not differentiable", spec));
+            }
+        }
+
+        let values: Vec<Tensor> = values.into_iter().cloned().collect();
+        first.tape.record(value, operands, move |grad, needed| {
+            (0..values.len())
+                .map(|i| {
+                    if !needed[i] {
+                        return Ok(None);
+                    }
+                    // d/dX_i: contract the output gradient with every other operand
+                    let target = &parsed.inputs[i];
+                    let mut subscripts: Vec<String> = vec![parsed.output.iter().collect()];
+                    let mut tensors: Vec<&Tensor> = vec![grad];
+                    for (j, labels) in parsed.inputs.iter().enumerate().filter(|&(j, _)| j != i) {
+                        subscripts.push(labels.iter().collect());
+                        tensors.push(&values[j]);
+                    }
+                    // Labels summed only within X_i spread the gradient uniformly
+                    let (missing, missing_shape): (String, Vec<usize>) = target.iter().zip(&values[i].shape)
+                        .filter(|(label, _)| !subscripts.iter().any(|s| s.contains(**label)))
+                        .map(|(label, dim)| (*label, *dim))
+                        .unzip();
+                    let ones = Tensor::new(missing_shape.clone(), vec![1.0; missing_shape.iter().product()]);
+                    if !missing.is_empty() {
+                        subscripts.push(missing);
+                        tensors.push(&ones);
+                    }
+                    let spec = format!("{}->{}", subscripts.join(","), target.iter().collect::<String>());
+                    einsum(&spec, &tensors).map(Some)
+                })
+                .collect()
+        })
+    }
+
+    /// Reduction over `axes` (every axis when empty), as `Tensor::reduce`
+    ///
+    /// Maximum and minimum split the gradient evenly between tied elements.
+    pub fn reduce(&self, reduction: Reduction, axes: &[usize], keepdim: bool) -> Result<Var<'t>, String> {
+        let value = self.value.reduce(reduction, axes, keepdim)?;
+        let input = self.value.clone();
+        let kept_shape: Vec<usize> = input.shape.iter().enumerate()
+            .map(|(axis, &dim)| if axes.is_empty() || axes.contains(&axis) { 1 } else { dim })
+            .collect();
+        let count = (input.size() / kept_shape.iter().product::<usize>().max(1)) as f64;
+        let extremum = value.reshape(&kept_shape)?;
+        let axes = axes.to_vec();
+
+        self.tape.record(value, &[self], move |grad, _| {
+            let grad = grad.reshape(&kept_shape)?;
+            let spread = match reduction {
+                Reduction::Sum => broadcast_zip(&input, &grad, |_, g| g)?,
+                Reduction::Mean => broadcast_zip(&input, &grad, |_, g| g / count)?,
+                Reduction::Max | Reduction::Min => {
+                    let mask = broadcast_zip(&input, &extremum, |x, m| if x == m { 1.0 } else { 0.0 })?;
+                    let ties = mask.sum(&axes, true)?;
+                    let share = broadcast_zip(&grad, &ties, |g, n| g / n)?;
+                    broadcast_zip(&mask, &share, |m, s| m * s)?
+                }
+            };
+            Ok(vec![Some(spread)])
+        })
+    }
+
+    /// Sum over `axes`
+    pub fn sum(&self, axes: &[usize], keepdim: bool) -> Result<Var<'t>, String> {
+        self.reduce(Reduction::Sum, axes, keepdim)
+    }
+
+    /// Mean over `axes`
+    pub fn mean(&self, axes: &[usize], keepdim: bool) -> Result<Var<'t>, String> {
+        self.reduce(Reduction::Mean, axes, keepdim)
+    }
+
+    /// Maximum over `axes`
+    pub fn max(&self, axes: &[usize], keepdim: bool) -> Result<Var<'t>, String> {
+        self.reduce(Reduction::Max, axes, keepdim)
+    }
+
+    /// Minimum over `axes`
+    pub fn min(&self, axes: &[usize], keepdim: bool) -> Result<Var<'t>, String> {
+        self.reduce(Reduction::Min, axes, keepdim)
+    }
+
+    /// Backpropagate from this variable, seeding its gradient with ones
+    pub fn backward(&self) -> Result<Gradients, String> {
+        let nodes = self.tape.nodes.borrow();
+        let mut grads: Vec<Option<Tensor>> = vec![None; self.id + 1];
+        if nodes[self.id].requires_grad {
+            grads[self.id] = Some(Tensor::new(self.value.shape.clone(), vec![1.0; self.value.size()]));
+        }
+
+        for id in (0..=self.id).rev() {
+            let (Some(grad), Some(backward)) = (&grads[id], &nodes[id].backward) else { continue };
+            let node = &nodes[id];
+            let needed: Vec<bool> = node.parents.iter().map(|&parent| nodes[parent].requires_grad).collect();
+            let parent_grads = backward(grad, &needed)?;
+            for ((&parent, parent_grad), needed) in node.parents.iter().zip(parent_grads).zip(needed) {
+                let Some(parent_grad) = parent_grad.filter(|_| needed) else { continue };
+                debug_assert_eq!(parent_grad.shape, nodes[parent].shape);
+                grads[parent] = Some(match grads[parent].take() {
+                    Some(accumulated) => tensor_add(&accumulated, &parent_grad)?,
+                    None => parent_grad,
+                });
+            }
+        }
+
+        Ok(Gradients {
+            grads: grads.into_iter().enumerate().filter_map(|(id, grad)| grad.map(|grad| (id, grad))).collect(),
+        })
+    }
+}
+
+/// Gradients produced by `Var::backward`
+#[derive(Debug, Clone, Default)]
+pub struct Gradients {
+    grads: HashMap<usize, Tensor>,
+}
+
+impl Gradients {
+    /// Gradient with respect to `var`, if it requires one and was reached
+    pub fn get(&self, var: &Var<'_>) -> Option<&Tensor> {
+        self.grads.get(&var.id)
+    }
+}
+
+/// Sum a broadcast gradient back to the operand's `shape`
+fn unbroadcast(grad: Tensor, shape: &[usize]) -> Result<Tensor, String> {
+    if grad.shape == shape {
+        return Ok(grad);
+    }
+    let padding = grad.rank - shape.len();
+    let axes: Vec<usize> = (0..grad.rank)
+        .filter(|&axis| axis < padding || (shape[axis - padding] == 1 && grad.shape[axis] != 1))
+        .collect();
+    if axes.is_empty() {
+        return grad.reshape(shape);
+    }
+    grad.sum(&axes, false)?.reshape(shape)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaf(shape: Vec<usize>, data: Vec<f64>) -> Tensor {
+        Tensor::new(shape, data).with_requires_grad(true)
+    }
+
+    /// Central-difference gradient of `f` with respect to `x`
+    fn numeric(x: &Tensor, f: impl Fn(&Tensor) -> f64) -> Vec<f64> {
+        (0..x.size())
+            .map(|i| {
+                let (mut plus, mut minus) = (x.clone(), x.clone());
+                plus.data[i] += 1e-6;
+                minus.data[i] -= 1e-6;
+                (f(&plus) - f(&minus)) / 2e-6
+            })
+            .collect()
+    }
+
+    fn assert_close(a: &[f64], b: &[f64]) {
+        for (x, y) in a.iter().zip(b) {
+            assert!((x - y).abs() < 1e-5, "{:?} vs {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_einsum_and_reduction_gradients() {
+        let tape = Tape::new();
+        let a = tape.leaf(leaf(vec![2, 3], vec![0.1, 0.5, 0.2, 0.7, 0.3, 0.9]));
+        let b = tape.leaf(leaf(vec![3, 2], vec![0.4, 0.6, 0.8, 0.2, 0.5, 0.1]));
+        let bias = tape.leaf(Tensor::new(vec![2], vec![1.0, 2.0]));
+
+        let product = Var::einsum("ij,jk->ik", &[&a, &b]).unwrap();
+        let loss = product.add(&bias).unwrap().max(&[1], false).unwrap().sum(&[], false).unwrap();
+        let grads = loss.backward().unwrap();
+        assert!(grads.get(&bias).is_none());
+
+        let forward = |a: &Tensor, b: &Tensor| {
+            let product = einsum("ij,jk->ik", &[a, b]).unwrap();
+            let shifted = broadcast_zip(&product, &bias.value, |x, y| x + y).unwrap();
+            shifted.max(&[1], false).unwrap().sum(&[], false).unwrap().data[0]
+        };
+        assert_close(&grads.get(&a).unwrap().data, &numeric(a.value(), |x| forward(x, b.value())));
+        assert_close(&grads.get(&b).unwrap().data, &numeric(b.value(), |x| forward(a.value(), x)));
+
+        // Summed-out subscripts spread the gradient
+        let row_sums = Var::einsum("ij->i", &[&a]).unwrap().sum(&[], false).unwrap();
+        assert_eq!(row_sums.backward().unwrap().get(&a).unwrap().data, vec![1.0; 6]);
+    }
+
+    #[test]
+    fn test_logic_gradients_with_broadcasting() {
+        let tape = Tape::new();
+        let p = tape.leaf(leaf(vec![2, 2], vec![0.9, 0.2, 0.4, 0.6]));
+        let q = tape.leaf(leaf(vec![2], vec![0.3, 0.8]));
+
+        let formula = p.and(&q).unwrap().not().implies(&p).unwrap().or(&q).unwrap();
+        let loss = formula.mean(&[], false).unwrap();
+        let grads = loss.backward().unwrap();
+        assert_eq!(grads.get(&q).unwrap().shape, vec![2]);
+
+        let forward = |p: &Tensor, q: &Tensor| {
+            let tape = Tape::new();
+            let (p, q) = (tape.leaf(p.clone()), tape.leaf(q.clone()));
+            let formula = p.and(&q).unwrap().not().implies(&p).unwrap().or(&q).unwrap();
+            formula.mean(&[], false).unwrap().value().data[0]
+        };
+        assert_close(&grads.get(&p).unwrap().data, &numeric(p.value(), |x| forward(x, q.value())));
+        assert_close(&grads.get(&q).unwrap().data, &numeric(q.value(), |x| forward(p.value(), x)));
+    }
+}
@@ -0,0 +1,119 @@
+//! Broadcasting - NumPy-style shape expansion for element-wise operations
+//!
+//! Shapes are aligned from their trailing axes; missing leading axes count as size
+//! one, and two extents are compatible when equal or when either is one. A size-one
+//! axis is repeated along the other operand's extent without copying any data.
+
+use rayon::prelude::*;
+
+use super::Tensor;
+
+/// Shape of the result of combining `shape_a` and `shape_b` element-wise
+pub fn broadcast_shape(shape_a: &[usize], shape_b: &[usize]) -> Result<Vec<usize>, String> {
+    let rank = shape_a.len().max(shape_b.len());
+    (0..rank)
+        .map(|axis| {
+            let a = extent(shape_a, rank, axis);
+            let b = extent(shape_b, rank, axis);
+            match (a, b) {
+                _ if a == b => Ok(a),
+                (1, _) => Ok(b),
+                (_, 1) => Ok(a),
+                _ => Err(format!("Shape mismatch: {:?} vs {:?}", shape_a, shape_b)),
+            }
+        })
+        .collect()
+}
+
+/// Extent of `axis` of `shape` once left-padded to `rank` axes
+fn extent(shape: &[usize], rank: usize, axis: usize) -> usize {
+    let padding = rank - shape.len();
+    if axis < padding { 1 } else { shape[axis - padding] }
+}
+
+/// Row-major strides of `shape` viewed with the broadcast `output_shape`, zero along
+/// repeated axes
+pub(super) fn broadcast_strides(shape: &[usize], output_shape: &[usize]) -> Vec<usize> {
+    let rank = output_shape.len();
+    let mut strides = vec![0; rank];
+    let mut stride = 1;
+    for axis in (0..rank).rev() {
+        let dim = extent(shape, rank, axis);
+        if dim != 1 {
+            strides[axis] = stride;
+        }
+        stride *= dim;
+    }
+    strides
+}
+
+/// Combine two tensors element-wise after broadcasting them to a common shape
+pub fn broadcast_zip(
+    tensor_a: &Tensor,
+    tensor_b: &Tensor,
+    op: impl Fn(f64, f64) -> f64 + Sync,
+) -> Result<Tensor, String> {
+    let (shape, data) = broadcast_zip_slices(&tensor_a.data, &tensor_a.shape, &tensor_b.data, &tensor_b.shape, op)?;
+    Ok(Tensor::new(shape, data))
+}
+
+/// `broadcast_zip` over raw row-major buffers of any element type, returning the
+/// broadcast shape and data
+pub(super) fn broadcast_zip_slices<T: Copy + Send + Sync>(
+    data_a: &[T],
+    shape_a: &[usize],
+    data_b: &[T],
+    shape_b: &[usize],
+    op: impl Fn(T, T) -> T + Sync,
+) -> Result<(Vec<usize>, Vec<T>), String> {
+    if shape_a == shape_b {
+        let data = data_a.par_iter().zip(data_b.par_iter()).map(|(&a, &b)| op(a, b)).collect();
+        return Ok((shape_a.to_vec(), data));
+    }
+
+    let shape = broadcast_shape(shape_a, shape_b)?;
+    let strides_a = broadcast_strides(shape_a, &shape);
+    let strides_b = broadcast_strides(shape_b, &shape);
+    let size: usize = shape.iter().product();
+
+    let data = (0..size)
+        .into_par_iter()
+        .map(|mut index| {
+            let (mut offset_a, mut offset_b) = (0, 0);
+            for axis in (0..shape.len()).rev() {
+                let coordinate = index % shape[axis];
+                index /= shape[axis];
+                offset_a += coordinate * strides_a[axis];
+                offset_b += coordinate * strides_b[axis];
+            }
+            op(data_a[offset_a], data_b[offset_b])
+        })
+        .collect();
+    Ok((shape, data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_broadcast_shape() {
+        assert_eq!(broadcast_shape(&[2, 3], &[3]).unwrap(), vec![2, 3]);
+        assert_eq!(broadcast_shape(&[2, 1], &[1, 4]).unwrap(), vec![2, 4]);
+        assert_eq!(broadcast_shape(&[], &[5]).unwrap(), vec![5]);
+        assert!(broadcast_shape(&[2, 3], &[2]).is_err());
+    }
+
+    #[test]
+    fn test_broadcast_zip() {
+        let matrix = Tensor::new(vec![2, 3], vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let row = Tensor::new(vec![3], vec![10.0, 20.0, 30.0]);
+        let column = Tensor::new(vec![2, 1], vec![100.0, 200.0]);
+
+        let sum = broadcast_zip(&matrix, &row, |a, b| a + b).unwrap();
+        assert_eq!(sum.data, vec![11.0, 22.0, 33.0, 14.0, 25.0, 36.0]);
+        let outer = broadcast_zip(&column, &row, |a, b| a + b).unwrap();
+        assert_eq!(outer.shape, vec![2, 3]);
+        assert_eq!(outer.data, vec![110.0, 120.0, 130.0, 210.0, 220.0, 230.0]);
+    }
+}
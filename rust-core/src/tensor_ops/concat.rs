@@ -0,0 +1,140 @@
+//! Joining - Concatenate, stack and split tensors
+//!
+//! Row-major data splits into an outer part (the axes before the join axis) and
+//! contiguous blocks (the join axis and everything after it), so joining copies
+//! whole blocks of each input into every outer position of the result. Outer
+//! positions are filled in parallel.
+
+use rayon::prelude::*;
+use tracing::instrument;
+
+use super::Tensor;
+
+/// Interleave the blocks of `parts`, each `extent × inner` long, across `outer`
+/// positions
+fn join(parts: &[(&[f64], usize)], outer: usize, inner: usize) -> Vec<f64> {
+    let row: usize = parts.iter().map(|&(_, extent)| extent * inner).sum();
+    let mut data = vec![0.0; outer * row];
+    if row == 0 {
+        return data;
+    }
+    data.par_chunks_mut(row).enumerate().for_each(|(position, out)| {
+        let mut start = 0;
+        for &(part, extent) in parts {
+            let block = extent * inner;
+            out[start..start + block].copy_from_slice(&part[position * block..][..block]);
+            start += block;
+        }
+    });
+    data
+}
+
+/// Check that `tensors` share a rank and every extent, except that of `axis` unless
+/// it is a `new_axis` to be inserted
+fn check_joinable(tensors: &[&Tensor], axis: usize, new_axis: bool) -> Result<(), String> {
+    let first = tensors.first().ok_or("Cannot join an empty list of tensors")?;
+    if axis > first.rank || (axis == first.rank && !new_axis) {
+        return Err(format!("Axis {} out of range for rank {}", axis, first.rank));
+    }
+    for tensor in tensors {
+        let mismatch = tensor.rank != first.rank
+            || (0..first.rank).any(|i| (new_axis || i != axis) && tensor.shape[i] != first.shape[i]);
+        if mismatch {
+            return Err(format!("Cannot join {:?} with {:?} along axis {}", first.shape, tensor.shape, axis));
+        }
+    }
+    Ok(())
+}
+
+/// Join tensors end to end along an existing `axis`
+#[instrument(skip(tensors))]
+pub fn concat(tensors: &[&Tensor], axis: usize) -> Result<Tensor, String> {
+    check_joinable(tensors, axis, false)?;
+    let shape = &tensors[0].shape;
+    let outer: usize = shape[..axis].iter().product();
+    let inner: usize = shape[axis + 1..].iter().product();
+
+    let parts: Vec<(&[f64], usize)> = tensors.iter().map(|t| (t.data.as_slice(), t.shape[axis])).collect();
+    let mut result_shape = shape.clone();
+    result_shape[axis] = parts.iter().map(|&(_, extent)| extent).sum();
+    Ok(Tensor::new(result_shape, join(&parts, outer, inner)))
+}
+
+/// Join equally shaped tensors along a new axis inserted at `axis`
+#[instrument(skip(tensors))]
+pub fn stack(tensors: &[&Tensor], axis: usize) -> Result<Tensor, String> {
+    check_joinable(tensors, axis, true)?;
+    let shape = &tensors[0].shape;
+    let outer: usize = shape[..axis].iter().product();
+    let inner: usize = shape[axis..].iter().product();
+
+    let parts: Vec<(&[f64], usize)> = tensors.iter().map(|t| (t.data.as_slice(), 1)).collect();
+    let mut result_shape = shape.clone();
+    result_shape.insert(axis, tensors.len());
+    Ok(Tensor::new(result_shape, join(&parts, outer, inner)))
+}
+
+impl Tensor {
+    /// Split into `sections` equal parts along `axis`
+    pub fn split(&self, axis: usize, sections: usize) -> Result<Vec<Tensor>, String> {
+        if axis >= self.rank {
+            return Err(format!("Axis {} out of range for rank {}", axis, self.rank));
+        }
+        if sections == 0 || !self.shape[axis].is_multiple_of(sections) {
+            return Err(format!("Axis {} of extent {} cannot split into {} equal sections", axis, self.shape[axis], sections));
+        }
+
+        let step = self.shape[axis] / sections;
+        let view = self.view();
+        (0..sections)
+            .map(|section| {
+                let mut ranges: Vec<_> = self.shape[..axis].iter().map(|&dim| 0..dim).collect();
+                ranges.push(section * step..(section + 1) * step);
+                Ok(view.slice(&ranges)?.to_tensor())
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_concat_and_split_round_trip() {
+        let a = Tensor::new(vec![2, 2], vec![1.0, 2.0, 3.0, 4.0]);
+        let b = Tensor::new(vec![2, 1], vec![5.0, 6.0]);
+        let joined = concat(&[&a, &b], 1).unwrap();
+        assert_eq!(joined.shape, vec![2, 3]);
+        assert_eq!(joined.data, vec![1.0, 2.0, 5.0, 3.0, 4.0, 6.0]);
+
+        let rows = concat(&[&a, &a], 0).unwrap();
+        assert_eq!(rows.shape, vec![4, 2]);
+        assert_eq!(rows.data, vec![1.0, 2.0, 3.0, 4.0, 1.0, 2.0, 3.0, 4.0]);
+        assert!(concat(&[&a, &b], 0).is_err());
+        assert!(concat(&[], 0).is_err());
+
+        let parts = joined.split(1, 3).unwrap();
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[2].shape, vec![2, 1]);
+        assert_eq!(parts[2].data, b.data);
+        assert_eq!(concat(&parts.iter().collect::<Vec<_>>(), 1).unwrap().data, joined.data);
+        assert!(joined.split(1, 2).is_err());
+        assert!(joined.split(2, 1).is_err());
+    }
+
+    #[test]
+    fn test_stack_adds_axis() {
+        let a = Tensor::new(vec![3], vec![1.0, 2.0, 3.0]);
+        let b = Tensor::new(vec![3], vec![4.0, 5.0, 6.0]);
+        let batch = stack(&[&a, &b], 0).unwrap();
+        assert_eq!(batch.shape, vec![2, 3]);
+        assert_eq!(batch.data, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+
+        let columns = stack(&[&a, &b], 1).unwrap();
+        assert_eq!(columns.shape, vec![3, 2]);
+        assert_eq!(columns.data, vec![1.0, 4.0, 2.0, 5.0, 3.0, 6.0]);
+        assert!(stack(&[&a, &b], 2).is_err());
+        assert!(stack(&[&a, &Tensor::new(vec![2], vec![0.0; 2])], 0).is_err());
+    }
+}
@@ -0,0 +1,338 @@
+//! Convolution - 1-D and 2-D convolution and correlation
+//!
+//! As in the convolution layers of deep learning frameworks, `conv1d` and `conv2d`
+//! compute cross-correlations (the kernel is not flipped) of batched multi-channel
+//! inputs `[batch, in_channels, ..spatial]` with kernels
+//! `[out_channels, in_channels, ..taps]`. Stride, zero padding and dilation apply
+//! to every spatial axis. The direct path sums kernel taps for each output
+//! element; the FFT path multiplies the spectra of the padded input and dilated
+//! kernel, which is cheaper for large kernels. Output planes run in parallel.
+
+use std::sync::Arc;
+
+use num_complex::Complex64;
+use rayon::prelude::*;
+use rustfft::{Fft, FftPlanner};
+use tracing::instrument;
+
+use super::Tensor;
+
+/// How a convolution is evaluated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConvMethod {
+    /// FFT for kernels of at least `FFT_KERNEL_TAPS` taps, direct otherwise
+    #[default]
+    Auto,
+    Direct,
+    Fft,
+}
+
+/// Stride, zero padding and dilation shared by every spatial axis
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConvOptions {
+    pub stride: usize,
+    pub padding: usize,
+    pub dilation: usize,
+    pub method: ConvMethod,
+}
+
+impl Default for ConvOptions {
+    fn default() -> Self {
+        Self { stride: 1, padding: 0, dilation: 1, method: ConvMethod::Auto }
+    }
+}
+
+/// Kernel taps from which `ConvMethod::Auto` uses the FFT path
+const FFT_KERNEL_TAPS: usize = 64;
+
+/// Extents of a convolution, with 1-D problems as 2-D ones of height one
+struct Geometry {
+    batch: usize,
+    in_channels: usize,
+    out_channels: usize,
+    input: [usize; 2],
+    kernel: [usize; 2],
+    output: [usize; 2],
+    stride: [usize; 2],
+    padding: [usize; 2],
+    dilation: [usize; 2],
+}
+
+impl Geometry {
+    /// `input` is `[batch, in_channels, height, width]` and `kernel` is
+    /// `[out_channels, in_channels, height, width]`; `options` apply to the last
+    /// `spatial` axes
+    fn new(input: &[usize], kernel: &[usize], options: &ConvOptions, spatial: usize) -> Result<Self, String> {
+        if options.stride == 0 || options.dilation == 0 {
+            return Err("Stride and dilation must be positive".to_string());
+        }
+        if input[1] != kernel[1] {
+            return Err(format!("Input has {} channels but kernel expects {}", input[1], kernel[1]));
+        }
+
+        // The height axis of a 1-D problem is unit-sized and left untouched
+        let per_axis = |value: usize, default: usize| [if spatial == 2 { value } else { default }, value];
+        let (stride, padding, dilation) = (per_axis(options.stride, 1), per_axis(options.padding, 0), per_axis(options.dilation, 1));
+
+        let mut output = [0; 2];
+        for axis in 0..2 {
+            let padded = input[2 + axis] + 2 * padding[axis];
+            let span = kernel[2 + axis].saturating_sub(1) * dilation[axis] + 1;
+            if input[2 + axis] == 0 || kernel[2 + axis] == 0 || padded < span {
+                return Err(format!(
+                    "Kernel {:?} with dilation {} does not fit input {:?} with padding {}",
+                    &kernel[2..], options.dilation, &input[2..], options.padding
+                ));
+            }
+            output[axis] = (padded - span) / stride[axis] + 1;
+        }
+
+        Ok(Self {
+            batch: input[0],
+            in_channels: input[1],
+            out_channels: kernel[0],
+            input: [input[2], input[3]],
+            kernel: [kernel[2], kernel[3]],
+            output,
+            stride,
+            padding,
+            dilation,
+        })
+    }
+
+    fn padded(&self) -> [usize; 2] {
+        [self.input[0] + 2 * self.padding[0], self.input[1] + 2 * self.padding[1]]
+    }
+
+    fn output_shape(&self, spatial: usize) -> Vec<usize> {
+        let mut shape = vec![self.batch, self.out_channels];
+        shape.extend(&self.output[2 - spatial..]);
+        shape
+    }
+}
+
+/// Sum kernel taps directly for every output element
+fn direct(input: &[f64], kernel: &[f64], g: &Geometry) -> Vec<f64> {
+    let ([ih, iw], [kh, kw], [oh, ow]) = (g.input, g.kernel, g.output);
+    let mut out = vec![0.0; g.batch * g.out_channels * oh * ow];
+    out.par_chunks_mut(oh * ow).enumerate().for_each(|(plane, out)| {
+        let (n, o) = (plane / g.out_channels, plane % g.out_channels);
+        for c in 0..g.in_channels {
+            let x = &input[(n * g.in_channels + c) * ih * iw..][..ih * iw];
+            let k = &kernel[(o * g.in_channels + c) * kh * kw..][..kh * kw];
+            for (i, row) in out.chunks_mut(ow).enumerate() {
+                for (j, value) in row.iter_mut().enumerate() {
+                    let mut sum = 0.0;
+                    for a in 0..kh {
+                        let Some(y) = (i * g.stride[0] + a * g.dilation[0]).checked_sub(g.padding[0]).filter(|&y| y < ih) else {
+                            continue;
+                        };
+                        for b in 0..kw {
+                            if let Some(x_pos) = (j * g.stride[1] + b * g.dilation[1]).checked_sub(g.padding[1]).filter(|&x| x < iw) {
+                                sum += x[y * iw + x_pos] * k[a * kw + b];
+                            }
+                        }
+                    }
+                    *value += sum;
+                }
+            }
+        }
+    });
+    out
+}
+
+/// Row-column 2-D FFT over row-major `rows × cols` buffers
+struct Fft2 {
+    rows: [Arc<dyn Fft<f64>>; 2],
+    cols: [Arc<dyn Fft<f64>>; 2],
+    shape: [usize; 2],
+}
+
+impl Fft2 {
+    fn new(shape: [usize; 2]) -> Self {
+        let mut planner = FftPlanner::new();
+        Self {
+            rows: [planner.plan_fft_forward(shape[1]), planner.plan_fft_inverse(shape[1])],
+            cols: [planner.plan_fft_forward(shape[0]), planner.plan_fft_inverse(shape[0])],
+            shape,
+        }
+    }
+
+    /// Transform in place; the inverse is unnormalized
+    fn process(&self, buffer: &mut [Complex64], inverse: bool) {
+        let [height, width] = self.shape;
+        self.rows[inverse as usize].process(buffer);
+        if height > 1 {
+            let mut columns = vec![Complex64::default(); buffer.len()];
+            transpose(buffer, &mut columns, height, width);
+            self.cols[inverse as usize].process(&mut columns);
+            transpose(&columns, buffer, width, height);
+        }
+    }
+}
+
+fn transpose(from: &[Complex64], to: &mut [Complex64], rows: usize, cols: usize) {
+    for i in 0..rows {
+        for j in 0..cols {
+            to[j * rows + i] = from[i * cols + j];
+        }
+    }
+}
+
+/// Correlate through the spectra of the padded input and the dilated kernel
+///
+/// Circular correlation over the padded extent never wraps for valid outputs,
+/// since the dilated kernel ends inside the padded input.
+fn fft(input: &[f64], kernel: &[f64], g: &Geometry) -> Vec<f64> {
+    let ([ih, iw], [kh, kw], [oh, ow]) = (g.input, g.kernel, g.output);
+    let padded = g.padded();
+    let size = padded[0] * padded[1];
+    let plan = Fft2::new(padded);
+
+    let inputs: Vec<Vec<Complex64>> = (0..g.batch * g.in_channels)
+        .into_par_iter()
+        .map(|plane| {
+            let mut buffer = vec![Complex64::default(); size];
+            for (y, row) in input[plane * ih * iw..][..ih * iw].chunks(iw).enumerate() {
+                let start = (y + g.padding[0]) * padded[1] + g.padding[1];
+                for (target, &x) in buffer[start..start + iw].iter_mut().zip(row) {
+                    target.re = x;
+                }
+            }
+            plan.process(&mut buffer, false);
+            buffer
+        })
+        .collect();
+    let kernels: Vec<Vec<Complex64>> = (0..g.out_channels * g.in_channels)
+        .into_par_iter()
+        .map(|plane| {
+            let mut buffer = vec![Complex64::default(); size];
+            for (a, row) in kernel[plane * kh * kw..][..kh * kw].chunks(kw).enumerate() {
+                for (b, &k) in row.iter().enumerate() {
+                    buffer[a * g.dilation[0] * padded[1] + b * g.dilation[1]].re = k;
+                }
+            }
+            plan.process(&mut buffer, false);
+            buffer.iter_mut().for_each(|z| *z = z.conj());
+            buffer
+        })
+        .collect();
+
+    let mut out = vec![0.0; g.batch * g.out_channels * oh * ow];
+    out.par_chunks_mut(oh * ow).enumerate().for_each(|(plane, out)| {
+        let (n, o) = (plane / g.out_channels, plane % g.out_channels);
+        let mut spectrum = vec![Complex64::default(); size];
+        for c in 0..g.in_channels {
+            let (x, k) = (&inputs[n * g.in_channels + c], &kernels[o * g.in_channels + c]);
+            for ((s, x), k) in spectrum.iter_mut().zip(x).zip(k) {
+                *s += x * k;
+            }
+        }
+        plan.process(&mut spectrum, true);
+        for (i, row) in out.chunks_mut(ow).enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = spectrum[i * g.stride[0] * padded[1] + j * g.stride[1]].re / size as f64;
+            }
+        }
+    });
+    out
+}
+
+fn convolve(input: &Tensor, kernel: &Tensor, input_shape: &[usize], kernel_shape: &[usize], options: &ConvOptions, spatial: usize) -> Result<Tensor, String> {
+    let geometry = Geometry::new(input_shape, kernel_shape, options, spatial)?;
+    let use_fft = match options.method {
+        ConvMethod::Auto => geometry.kernel[0] * geometry.kernel[1] >= FFT_KERNEL_TAPS,
+        ConvMethod::Direct => false,
+        ConvMethod::Fft => true,
+    };
+    let data = if use_fft { fft(&input.data, &kernel.data, &geometry) } else { direct(&input.data, &kernel.data, &geometry) };
+    Ok(Tensor::new(geometry.output_shape(spatial), data))
+}
+
+/// 1-D cross-correlation: `[batch, in_channels, length]` with
+/// `[out_channels, in_channels, taps]` gives `[batch, out_channels, out_length]`
+#[instrument(skip(input, kernel))]
+pub fn conv1d(input: &Tensor, kernel: &Tensor, options: &ConvOptions) -> Result<Tensor, String> {
+    let (&[n, c, l], &[o, c_k, k]) = (input.shape.as_slice(), kernel.shape.as_slice()) else {
+        return Err(format!("conv1d needs rank-3 input and kernel, got {:?} and {:?}", input.shape, kernel.shape));
+    };
+    convolve(input, kernel, &[n, c, 1, l], &[o, c_k, 1, k], options, 1)
+}
+
+/// 2-D cross-correlation: `[batch, in_channels, height, width]` with
+/// `[out_channels, in_channels, kernel_height, kernel_width]`
+#[instrument(skip(input, kernel))]
+pub fn conv2d(input: &Tensor, kernel: &Tensor, options: &ConvOptions) -> Result<Tensor, String> {
+    if input.rank != 4 || kernel.rank != 4 {
+        return Err(format!("conv2d needs rank-4 input and kernel, got {:?} and {:?}", input.shape, kernel.shape));
+    }
+    convolve(input, kernel, &input.shape, &kernel.shape, options, 2)
+}
+
+/// Cross-correlation of two rank-1 signals, `out[t] = Σ_j signal[t·stride + j·dilation - padding] · template[j]`
+///
+/// Reversing `template` turns this into a convolution; padding by `template.len() - 1`
+/// gives every overlap.
+#[instrument(skip(signal, template))]
+pub fn correlate(signal: &Tensor, template: &Tensor, options: &ConvOptions) -> Result<Tensor, String> {
+    if signal.rank != 1 || template.rank != 1 {
+        return Err(format!("correlate needs rank-1 tensors, got {:?} and {:?}", signal.shape, template.shape));
+    }
+    let result = convolve(signal, template, &[1, 1, 1, signal.size()], &[1, 1, 1, template.size()], options, 1)?;
+    let length = result.size();
+    result.reshape(&[length])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: &Tensor, b: &Tensor) {
+        assert_eq!(a.shape, b.shape);
+        assert!(a.data.iter().zip(&b.data).all(|(x, y)| (x - y).abs() < 1e-9), "{:?} vs {:?}", a.data, b.data);
+    }
+
+    #[test]
+    fn test_correlate() {
+        let signal = Tensor::new(vec![5], vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+        let template = Tensor::new(vec![2], vec![1.0, -1.0]);
+        let valid = correlate(&signal, &template, &ConvOptions::default()).unwrap();
+        assert_eq!(valid.data, vec![-1.0; 4]);
+
+        let full = ConvOptions { padding: 1, ..Default::default() };
+        assert_eq!(correlate(&signal, &template, &full).unwrap().data, vec![-1.0, -1.0, -1.0, -1.0, -1.0, 5.0]);
+        let strided = ConvOptions { stride: 2, dilation: 2, ..Default::default() };
+        assert_eq!(correlate(&signal, &template, &strided).unwrap().data, vec![-2.0, -2.0]);
+        assert!(correlate(&template, &signal, &ConvOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_conv1d_channels() {
+        // Two input channels summed by one output channel, plus a second output channel
+        let input = Tensor::new(vec![1, 2, 3], vec![1.0, 2.0, 3.0, 10.0, 20.0, 30.0]);
+        let kernel = Tensor::new(vec![2, 2, 1], vec![1.0, 1.0, 0.0, 0.5]);
+        let out = conv1d(&input, &kernel, &ConvOptions::default()).unwrap();
+        assert_eq!(out.shape, vec![1, 2, 3]);
+        assert_eq!(out.data, vec![11.0, 22.0, 33.0, 5.0, 10.0, 15.0]);
+        assert!(conv1d(&input, &Tensor::new(vec![1, 3, 1], vec![1.0; 3]), &ConvOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_fft_matches_direct() {
+        let input = Tensor::new(vec![2, 3, 7, 6], (0..252).map(|x| ((x * 37) % 11) as f64 - 5.0).collect());
+        let kernel = Tensor::new(vec![4, 3, 3, 2], (0..72).map(|x| ((x * 13) % 7) as f64 * 0.25).collect());
+        for (stride, padding, dilation) in [(1, 0, 1), (2, 1, 1), (1, 2, 2), (3, 1, 2)] {
+            let options = |method| ConvOptions { stride, padding, dilation, method };
+            let direct = conv2d(&input, &kernel, &options(ConvMethod::Direct)).unwrap();
+            let fft = conv2d(&input, &kernel, &options(ConvMethod::Fft)).unwrap();
+            assert_close(&direct, &fft);
+        }
+
+        let signal = Tensor::new(vec![1, 1, 100], (0..100).map(|x| (x as f64 * 0.3).sin()).collect());
+        let long = Tensor::new(vec![1, 1, 64], (0..64).map(|x| (x as f64 * 0.1).cos()).collect());
+        let options = ConvOptions { padding: 5, ..Default::default() };
+        let auto = conv1d(&signal, &long, &options).unwrap();
+        assert_close(&auto, &conv1d(&signal, &long, &ConvOptions { method: ConvMethod::Direct, ..options }).unwrap());
+        assert_eq!(auto.shape, vec![1, 1, 47]);
+    }
+}
@@ -0,0 +1,217 @@
+//! Devices - Where tensor data lives and where operations run
+//!
+//! A `DeviceTensor` owns its data on one device. Operations run on the device of
+//! their operands, and mixing devices is an error rather than an implicit copy, so
+//! every host/device transfer is an explicit `to_device` or `to_cpu`. The GPU
+//! device is only available when the crate is built with the `gpu` feature.
+
+#[cfg(feature = "gpu")]
+use std::sync::Arc;
+
+#[cfg(feature = "gpu")]
+use super::gpu::{self, Combine, GpuContext, GpuTensor};
+use super::{einsum, matmul, tensor_and, tensor_implies, tensor_not, tensor_or, Tensor};
+
+/// A device that can hold tensors
+#[derive(Debug, Clone, Default)]
+pub enum TensorDevice {
+    #[default]
+    Cpu,
+    #[cfg(feature = "gpu")]
+    Gpu(Arc<GpuContext>),
+}
+
+impl TensorDevice {
+    /// Open the default GPU adapter
+    #[cfg(feature = "gpu")]
+    pub fn gpu() -> Result<Self, String> {
+        GpuContext::new().map(|context| Self::Gpu(Arc::new(context)))
+    }
+
+    /// Open the default GPU adapter (always fails: built without the `gpu` feature)
+    #[cfg(not(feature = "gpu"))]
+    pub fn gpu() -> Result<Self, String> {
+        Err("Built without the `gpu` feature".to_string())
+    }
+
+    pub fn is_gpu(&self) -> bool {
+        !matches!(self, Self::Cpu)
+    }
+}
+
+/// A tensor resident on a particular device
+#[derive(Debug, Clone)]
+pub enum DeviceTensor {
+    Cpu(Tensor),
+    #[cfg(feature = "gpu")]
+    Gpu(GpuTensor),
+}
+
+/// Two operands that live on the same device
+enum Pair<'a> {
+    Cpu(&'a Tensor, &'a Tensor),
+    #[cfg(feature = "gpu")]
+    Gpu(&'a GpuTensor, &'a GpuTensor),
+}
+
+impl DeviceTensor {
+    pub fn shape(&self) -> &[usize] {
+        match self {
+            Self::Cpu(tensor) => &tensor.shape,
+            #[cfg(feature = "gpu")]
+            Self::Gpu(tensor) => &tensor.shape,
+        }
+    }
+
+    pub fn device(&self) -> TensorDevice {
+        match self {
+            Self::Cpu(_) => TensorDevice::Cpu,
+            #[cfg(feature = "gpu")]
+            Self::Gpu(tensor) => TensorDevice::Gpu(Arc::clone(tensor.context())),
+        }
+    }
+
+    /// Copy the tensor to `device`
+    pub fn to_device(&self, device: &TensorDevice) -> Result<DeviceTensor, String> {
+        match device {
+            TensorDevice::Cpu => self.to_cpu().map(Self::Cpu),
+            #[cfg(feature = "gpu")]
+            TensorDevice::Gpu(context) => match self {
+                Self::Gpu(tensor) if Arc::ptr_eq(tensor.context(), context) => Ok(self.clone()),
+                _ => Ok(Self::Gpu(GpuTensor::upload(&self.to_cpu()?, context))),
+            },
+        }
+    }
+
+    /// Copy the data back into a host tensor
+    pub fn to_cpu(&self) -> Result<Tensor, String> {
+        match self {
+            Self::Cpu(tensor) => Ok(tensor.clone()),
+            #[cfg(feature = "gpu")]
+            Self::Gpu(tensor) => tensor.download(),
+        }
+    }
+
+    fn pair<'a>(&'a self, other: &'a DeviceTensor) -> Result<Pair<'a>, String> {
+        match (self, other) {
+            (Self::Cpu(a), Self::Cpu(b)) => Ok(Pair::Cpu(a, b)),
+            #[cfg(feature = "gpu")]
+            (Self::Gpu(a), Self::Gpu(b)) => Ok(Pair::Gpu(a, b)),
+            #[cfg(feature = "gpu")]
+            _ => Err("Operands live on different devices; transfer one with to_device".to_string()),
+        }
+    }
+
+    /// Logical AND (element-wise product) on the operands' device
+    pub fn and(&self, other: &DeviceTensor) -> Result<DeviceTensor, String> {
+        match self.pair(other)? {
+            Pair::Cpu(a, b) => tensor_and(a, b).map(Self::Cpu),
+            #[cfg(feature = "gpu")]
+            Pair::Gpu(a, b) => gpu::elementwise(Combine::Product, a, b).map(Self::Gpu),
+        }
+    }
+
+    /// Logical OR (normalized element-wise max) on the operands' device
+    pub fn or(&self, other: &DeviceTensor) -> Result<DeviceTensor, String> {
+        match self.pair(other)? {
+            Pair::Cpu(a, b) => tensor_or(a, b).map(Self::Cpu),
+            #[cfg(feature = "gpu")]
+            Pair::Gpu(a, b) => gpu::elementwise(Combine::Max, a, b).map(Self::Gpu),
+        }
+    }
+
+    /// Logical IMPLIES, `max(1 - a, b)`, on the operands' device
+    pub fn implies(&self, other: &DeviceTensor) -> Result<DeviceTensor, String> {
+        match self.pair(other)? {
+            Pair::Cpu(a, b) => tensor_implies(a, b).map(Self::Cpu),
+            #[cfg(feature = "gpu")]
+            Pair::Gpu(a, b) => gpu::elementwise(Combine::Implies, a, b).map(Self::Gpu),
+        }
+    }
+
+    /// Logical NOT, `1 - a`, on the tensor's device
+    pub fn not(&self) -> Result<DeviceTensor, String> {
+        match self {
+            Self::Cpu(tensor) => Ok(Self::Cpu(tensor_not(tensor))),
+            #[cfg(feature = "gpu")]
+            Self::Gpu(tensor) => gpu::not(tensor).map(Self::Gpu),
+        }
+    }
+
+    /// Matrix product of rank-2 tensors on the operands' device
+    pub fn matmul(&self, other: &DeviceTensor) -> Result<DeviceTensor, String> {
+        match self.pair(other)? {
+            Pair::Cpu(a, b) => matmul(a, b).map(Self::Cpu),
+            #[cfg(feature = "gpu")]
+            Pair::Gpu(a, b) => {
+                if a.shape.len() != 2 || b.shape.len() != 2 {
+                    return Err(format!("matmul needs rank-2 tensors, got {:?} and {:?}", a.shape, b.shape));
+                }
+                gpu::einsum("ij,jk->ik", &[a, b]).map(Self::Gpu)
+            }
+        }
+    }
+}
+
+/// Einstein summation over tensors that share a device, run on that device
+pub fn device_einsum(spec: &str, tensors: &[&DeviceTensor]) -> Result<DeviceTensor, String> {
+    if let Some(cpu) = tensors.iter().map(|tensor| match tensor {
+        DeviceTensor::Cpu(tensor) => Some(tensor),
+        #[cfg(feature = "gpu")]
+        DeviceTensor::Gpu(_) => None,
+    }).collect::<Option<Vec<_>>>() {
+        return einsum(spec, &cpu).map(DeviceTensor::Cpu);
+    }
+
+    #[cfg(feature = "gpu")]
+    if let Some(gpu) = tensors.iter().map(|tensor| match tensor {
+        DeviceTensor::Gpu(tensor) => Some(tensor),
+        DeviceTensor::Cpu(_) => None,
+    }).collect::<Option<Vec<_>>>() {
+        return gpu::einsum(spec, &gpu).map(DeviceTensor::Gpu);
+    }
+
+    Err("Operands live on different devices; transfer them with to_device".to_string())
+}
+
+impl Tensor {
+    /// Copy the tensor to `device`
+    pub fn to_device(&self, device: &TensorDevice) -> Result<DeviceTensor, String> {
+        DeviceTensor::Cpu(self.clone()).to_device(device)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_device_ops_follow_operands() {
+        let a = Tensor::new(vec![2, 2], vec![0.2, 0.4, 0.6, 0.8]);
+        let b = Tensor::new(vec![2, 2], vec![0.5, 0.5, 1.0, 0.0]);
+        let mut devices = vec![TensorDevice::Cpu];
+        devices.extend(TensorDevice::gpu());
+
+        for device in &devices {
+            let (da, db) = (a.to_device(device).unwrap(), b.to_device(device).unwrap());
+            assert_eq!(da.shape(), &[2, 2]);
+            assert_eq!(da.device().is_gpu(), device.is_gpu());
+
+            let expected = matmul(&a, &b).unwrap();
+            let product = da.matmul(&db).unwrap().to_cpu().unwrap();
+            assert!(product.data.iter().zip(&expected.data).all(|(x, y)| (x - y).abs() < 1e-5));
+
+            let expected = einsum("ij,ij->i", &[&a, &b]).unwrap();
+            let rows = device_einsum("ij,ij->i", &[&da, &db]).unwrap().to_cpu().unwrap();
+            assert!(rows.data.iter().zip(&expected.data).all(|(x, y)| (x - y).abs() < 1e-5));
+
+            let back = da.and(&db).unwrap().not().unwrap().to_device(&TensorDevice::Cpu).unwrap();
+            assert!(matches!(back, DeviceTensor::Cpu(_)));
+        }
+
+        // Mixed devices are rejected instead of copied implicitly
+        if let [cpu, gpu] = devices.as_slice() {
+            assert!(a.to_device(cpu).unwrap().or(&b.to_device(gpu).unwrap()).is_err());
+        }
+    }
+}
@@ -0,0 +1,378 @@
+//! Element Types - Single and half precision tensor storage
+//!
+//! `Tensor` computes in `f64`; `TypedTensor` keeps its elements as `f64`, `f32` or
+//! IEEE 754 half precision to cut memory and let vectorized loops process more lanes.
+//! Single precision is computed natively. Half precision (`half::f16`) is a storage
+//! format: each operation widens to `f32`, computes, and rounds the result back.
+
+use std::iter::Sum;
+use std::ops::{Add, Div, Mul, Sub};
+
+use half::f16;
+use rayon::prelude::*;
+
+use super::broadcast::broadcast_zip_slices;
+use super::{stable, Tensor};
+
+/// Element type of a `TypedTensor`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DType {
+    F64,
+    F32,
+    F16,
+}
+
+impl DType {
+    /// Bytes per element
+    pub fn size_of(&self) -> usize {
+        match self {
+            Self::F64 => 8,
+            Self::F32 => 4,
+            Self::F16 => 2,
+        }
+    }
+}
+
+/// Floating-point element that tensor operations compute in
+pub trait Scalar:
+    Copy + Send + Sync + PartialOrd + Sum + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Div<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn max(self, other: Self) -> Self;
+    fn sqrt(self) -> Self;
+    fn from_f64(value: f64) -> Self;
+    fn to_f64(self) -> f64;
+}
+
+impl Scalar for f64 {
+    const ZERO: Self = 0.0;
+    const ONE: Self = 1.0;
+
+    fn max(self, other: Self) -> Self {
+        f64::max(self, other)
+    }
+
+    fn sqrt(self) -> Self {
+        f64::sqrt(self)
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+}
+
+impl Scalar for f32 {
+    const ZERO: Self = 0.0;
+    const ONE: Self = 1.0;
+
+    fn max(self, other: Self) -> Self {
+        f32::max(self, other)
+    }
+
+    fn sqrt(self) -> Self {
+        f32::sqrt(self)
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+/// Element storage of a `TypedTensor`
+#[derive(Debug, Clone, PartialEq)]
+pub enum TensorData {
+    F64(Vec<f64>),
+    F32(Vec<f32>),
+    F16(Vec<f16>),
+}
+
+/// Tensor whose elements are stored in a chosen `DType`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypedTensor {
+    pub shape: Vec<usize>,
+    pub data: TensorData,
+}
+
+impl TypedTensor {
+    /// Create a tensor from shape and data
+    pub fn new(shape: Vec<usize>, data: TensorData) -> Self {
+        let tensor = Self { shape, data };
+        assert_eq!(
+            tensor.size(),
+            tensor.shape.iter().product::<usize>(),
+            "Data length {} doesn't match shape product",
+            tensor.size()
+        );
+        tensor
+    }
+
+    /// Copy of `tensor` rounded to `dtype`
+    pub fn from_tensor(tensor: &Tensor, dtype: DType) -> Self {
+        let data = match dtype {
+            DType::F64 => TensorData::F64(tensor.data.clone()),
+            DType::F32 => TensorData::F32(tensor.data.par_iter().map(|&x| x as f32).collect()),
+            DType::F16 => TensorData::F16(tensor.data.par_iter().map(|&x| f16::from_f32(x as f32)).collect()),
+        };
+        Self { shape: tensor.shape.clone(), data }
+    }
+
+    /// Double-precision copy
+    pub fn to_tensor(&self) -> Tensor {
+        let data = match &self.data {
+            TensorData::F64(data) => data.clone(),
+            TensorData::F32(data) => data.par_iter().map(|&x| x as f64).collect(),
+            TensorData::F16(data) => data.par_iter().map(|x| x.to_f32() as f64).collect(),
+        };
+        Tensor::new(self.shape.clone(), data)
+    }
+
+    pub fn dtype(&self) -> DType {
+        match self.data {
+            TensorData::F64(_) => DType::F64,
+            TensorData::F32(_) => DType::F32,
+            TensorData::F16(_) => DType::F16,
+        }
+    }
+
+    /// Number of elements
+    pub fn size(&self) -> usize {
+        match &self.data {
+            TensorData::F64(data) => data.len(),
+            TensorData::F32(data) => data.len(),
+            TensorData::F16(data) => data.len(),
+        }
+    }
+
+    /// Bytes of element storage
+    pub fn nbytes(&self) -> usize {
+        self.size() * self.dtype().size_of()
+    }
+
+    /// Copy converted to `dtype`
+    pub fn cast(&self, dtype: DType) -> Self {
+        if dtype == self.dtype() {
+            return self.clone();
+        }
+        let data = match (&self.data, dtype) {
+            (TensorData::F32(data), DType::F16) => TensorData::F16(data.par_iter().map(|&x| f16::from_f32(x)).collect()),
+            (TensorData::F16(data), DType::F32) => TensorData::F32(data.par_iter().map(|x| x.to_f32()).collect()),
+            _ => return Self::from_tensor(&self.to_tensor(), dtype),
+        };
+        Self { shape: self.shape.clone(), data }
+    }
+}
+
+impl Tensor {
+    /// Copy stored as `dtype`
+    pub fn to_dtype(&self, dtype: DType) -> TypedTensor {
+        TypedTensor::from_tensor(self, dtype)
+    }
+}
+
+/// Element-wise connective, generic over the computing precision
+trait Connective {
+    fn apply<T: Scalar>(a: T, b: T) -> T;
+}
+
+struct And;
+struct Max;
+struct Implies;
+
+impl Connective for And {
+    fn apply<T: Scalar>(a: T, b: T) -> T {
+        a * b
+    }
+}
+
+impl Connective for Max {
+    fn apply<T: Scalar>(a: T, b: T) -> T {
+        a.max(b)
+    }
+}
+
+impl Connective for Implies {
+    fn apply<T: Scalar>(a: T, b: T) -> T {
+        (T::ONE - a).max(b)
+    }
+}
+
+fn mismatch(a: &TypedTensor, b: &TypedTensor) -> String {
+    format!("Dtype mismatch: {:?} vs {:?}", a.dtype(), b.dtype())
+}
+
+/// Run `op` on single-precision copies of half-precision operands, rounding the
+/// tensor result back
+fn with_widened<R>(
+    a: &TypedTensor,
+    b: &TypedTensor,
+    op: impl Fn(&TypedTensor, &TypedTensor) -> Result<R, String>,
+    narrow: impl Fn(R) -> R,
+) -> Result<R, String> {
+    match (a.dtype(), b.dtype()) {
+        (DType::F16, DType::F16) => op(&a.cast(DType::F32), &b.cast(DType::F32)).map(narrow),
+        (x, y) if x == y => op(a, b),
+        _ => Err(mismatch(a, b)),
+    }
+}
+
+fn zip<C: Connective>(a: &TypedTensor, b: &TypedTensor) -> Result<TypedTensor, String> {
+    let (shape, data) = match (&a.data, &b.data) {
+        (TensorData::F64(x), TensorData::F64(y)) => {
+            broadcast_zip_slices(x, &a.shape, y, &b.shape, C::apply::<f64>).map(|(s, d)| (s, TensorData::F64(d)))?
+        }
+        (TensorData::F32(x), TensorData::F32(y)) => {
+            broadcast_zip_slices(x, &a.shape, y, &b.shape, C::apply::<f32>).map(|(s, d)| (s, TensorData::F32(d)))?
+        }
+        _ => return Err(mismatch(a, b)),
+    };
+    Ok(TypedTensor { shape, data })
+}
+
+fn binary<C: Connective>(a: &TypedTensor, b: &TypedTensor) -> Result<TypedTensor, String> {
+    with_widened(a, b, zip::<C>, |result| result.cast(DType::F16))
+}
+
+fn normalize<T: Scalar>(data: &mut [T]) {
+    let norm = data.par_iter().map(|&x| x * x).sum::<T>().sqrt();
+    if norm.to_f64() > stable::epsilon() {
+        data.par_iter_mut().for_each(|x| *x = *x / norm);
+    }
+}
+
+/// Logical AND, as `tensor_and`, in the operands' precision
+pub fn typed_and(tensor_a: &TypedTensor, tensor_b: &TypedTensor) -> Result<TypedTensor, String> {
+    binary::<And>(tensor_a, tensor_b)
+}
+
+/// Logical OR, as `tensor_or`, in the operands' precision
+pub fn typed_or(tensor_a: &TypedTensor, tensor_b: &TypedTensor) -> Result<TypedTensor, String> {
+    with_widened(
+        tensor_a,
+        tensor_b,
+        |a, b| {
+            let mut result = zip::<Max>(a, b)?;
+            match &mut result.data {
+                TensorData::F64(data) => normalize(data),
+                TensorData::F32(data) => normalize(data),
+                TensorData::F16(_) => unreachable!("half precision is widened"),
+            }
+            Ok(result)
+        },
+        |result| result.cast(DType::F16),
+    )
+}
+
+/// Logical IMPLIES, as `tensor_implies`, in the operands' precision
+pub fn typed_implies(tensor_a: &TypedTensor, tensor_b: &TypedTensor) -> Result<TypedTensor, String> {
+    binary::<Implies>(tensor_a, tensor_b)
+}
+
+/// Logical NOT, as `tensor_not`, in the operand's precision
+pub fn typed_not(tensor: &TypedTensor) -> TypedTensor {
+    let data = match &tensor.data {
+        TensorData::F64(data) => TensorData::F64(data.par_iter().map(|x| 1.0 - x).collect()),
+        TensorData::F32(data) => TensorData::F32(data.par_iter().map(|x| 1.0 - x).collect()),
+        TensorData::F16(data) => TensorData::F16(data.par_iter().map(|x| f16::from_f32(1.0 - x.to_f32())).collect()),
+    };
+    TypedTensor { shape: tensor.shape.clone(), data }
+}
+
+fn dot<T: Scalar>(a: &[T], b: &[T]) -> T {
+    a.par_iter().zip(b.par_iter()).map(|(&a, &b)| a * b).sum()
+}
+
+fn similarity<T: Scalar>(a: &[T], b: &[T]) -> f64 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let (norm_a, norm_b) = (dot(a, a).sqrt().to_f64(), dot(b, b).sqrt().to_f64());
+    if norm_a < stable::epsilon() || norm_b < stable::epsilon() {
+        return 0.0;
+    }
+    dot(a, b).to_f64() / (norm_a * norm_b)
+}
+
+/// Cosine similarity, as `tensor_similarity`, accumulated in the operands' precision
+pub fn typed_similarity(tensor_a: &TypedTensor, tensor_b: &TypedTensor) -> f64 {
+    let result = with_widened(
+        tensor_a,
+        tensor_b,
+        |a, b| match (&a.data, &b.data) {
+            (TensorData::F64(x), TensorData::F64(y)) => Ok(similarity(x, y)),
+            (TensorData::F32(x), TensorData::F32(y)) => Ok(similarity(x, y)),
+            _ => Err(mismatch(a, b)),
+        },
+        |similarity| similarity,
+    );
+    result.unwrap_or(0.0)
+}
+
+fn kernel<T: Scalar>(kernel_type: &str, a: &[T], b: &[T]) -> Result<f64, String> {
+    match kernel_type {
+        "linear" => Ok(dot(a, b).to_f64()),
+        "polynomial" => Ok((dot(a, b).to_f64() + 1.0).powf(2.0)),
+        "rbf" => {
+            let squared_diff: T = a.par_iter().zip(b.par_iter()).map(|(&a, &b)| (a - b) * (a - b)).sum();
+            Ok((-squared_diff.to_f64()).exp())
+        }
+        _ => Err(format!("Unknown kernel type: {}", kernel_type)),
+    }
+}
+
+/// Kernel function, as `apply_kernel`, accumulated in the operands' precision
+pub fn typed_apply_kernel(kernel_type: &str, tensor_a: &TypedTensor, tensor_b: &TypedTensor) -> Result<f64, String> {
+    with_widened(
+        tensor_a,
+        tensor_b,
+        |a, b| match (&a.data, &b.data) {
+            (TensorData::F64(x), TensorData::F64(y)) => kernel(kernel_type, x, y),
+            (TensorData::F32(x), TensorData::F32(y)) => kernel(kernel_type, x, y),
+            _ => Err(mismatch(a, b)),
+        },
+        |value| value,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tensor_ops::{apply_kernel, tensor_implies, tensor_or, tensor_similarity};
+
+    #[test]
+    fn test_typed_ops_track_f64() {
+        let a = Tensor::new(vec![2, 2], vec![0.9, 0.1, 0.4, 0.7]);
+        let b = Tensor::new(vec![2], vec![0.3, 0.8]);
+        let expected_or = tensor_or(&a, &b).unwrap();
+        let expected_implies = tensor_implies(&a, &b).unwrap();
+
+        for (dtype, tolerance) in [(DType::F64, 1e-15), (DType::F32, 1e-6), (DType::F16, 1e-3)] {
+            let (ta, tb) = (a.to_dtype(dtype), b.to_dtype(dtype));
+            let or = typed_or(&ta, &tb).unwrap();
+            assert_eq!(or.dtype(), dtype);
+            assert_eq!(or.nbytes(), 4 * dtype.size_of());
+            for (x, y) in or.to_tensor().data.iter().zip(&expected_or.data) {
+                assert!((x - y).abs() < tolerance);
+            }
+            for (x, y) in typed_implies(&ta, &tb).unwrap().to_tensor().data.iter().zip(&expected_implies.data) {
+                assert!((x - y).abs() < tolerance);
+            }
+            let (sa, sb) = (a.to_dtype(dtype), a.to_dtype(dtype).cast(dtype));
+            assert!((typed_similarity(&sa, &typed_not(&sb)) - tensor_similarity(&a, &crate::tensor_ops::tensor_not(&a))).abs() < tolerance);
+            let rbf = typed_apply_kernel("rbf", &sa, &typed_not(&sb)).unwrap();
+            assert!((rbf - apply_kernel("rbf", &a, &crate::tensor_ops::tensor_not(&a)).unwrap()).abs() < tolerance * 10.0);
+        }
+
+        assert!(typed_and(&a.to_dtype(DType::F32), &b.to_dtype(DType::F64)).is_err());
+        assert!(typed_apply_kernel("sigmoid", &a.to_dtype(DType::F32), &a.to_dtype(DType::F32)).is_err());
+    }
+}
@@ -0,0 +1,146 @@
+//! Einsum - NumPy-style subscript notation for tensor contractions
+//!
+//! A specification such as `"ij,jk->ik"` names each axis of each operand with a
+//! letter. Letters after `->` form the output in that order; without `->`, the
+//! output holds the letters that appear exactly once, in alphabetical order. Every
+//! other letter is summed over. More than two operands are contracted left to
+//! right, keeping only the labels still needed by later operands or the output.
+
+use super::{einstein_summation, Tensor};
+
+/// Parsed subscripts: one label list per operand and the output labels
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EinsumSpec {
+    pub inputs: Vec<Vec<char>>,
+    pub output: Vec<char>,
+}
+
+impl EinsumSpec {
+    /// Parse a subscript specification, ignoring whitespace
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let spec: String = spec.chars().filter(|c| !c.is_whitespace()).collect();
+        let (inputs, output) = match spec.split_once("->") {
+            Some((inputs, output)) => (inputs, Some(output)),
+            None => (spec.as_str(), None),
+        };
+
+        let inputs: Vec<Vec<char>> = inputs.split(',').map(labels).collect::<Result<_, _>>()?;
+        let output = match output {
+            Some(output) => {
+                let output = labels(output)?;
+                for (position, label) in output.iter().enumerate() {
+                    if output[..position].contains(label) {
+                        return Err(format!("Output subscript '{}' appears more than once", label));
+                    }
+                    if !inputs.iter().any(|input| input.contains(label)) {
+                        return Err(format!("Output subscript '{}' does not appear in any input", label));
+                    }
+                }
+                output
+            }
+            None => {
+                let mut once: Vec<char> = inputs.iter().flatten().copied()
+                    .filter(|label| inputs.iter().flatten().filter(|other| *other == label).count() == 1)
+                    .collect();
+                once.sort_unstable();
+                once
+            }
+        };
+        Ok(Self { inputs, output })
+    }
+
+    /// Check that there is one operand of matching rank per input
+    pub(super) fn check_ranks(&self, ranks: &[usize]) -> Result<(), String> {
+        if self.inputs.len() != ranks.len() {
+            return Err(format!(
+                "Subscripts describe {} operands but {} tensors were given",
+                self.inputs.len(), ranks.len()
+            ));
+        }
+        for (position, (labels, &rank)) in self.inputs.iter().zip(ranks).enumerate() {
+            if labels.len() != rank {
+                return Err(format!(
+                    "Operand {} has rank {} but {} subscripts",
+                    position, rank, labels.len()
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Labels to keep after contracting the operands before `position` with the one
+    /// at `position`: those the output or a later operand still refers to
+    pub(super) fn kept_labels(&self, position: usize, current_labels: &[char]) -> Vec<char> {
+        let later = &self.inputs[position + 1..];
+        let mut kept: Vec<char> = Vec::new();
+        for &label in current_labels.iter().chain(&self.inputs[position]) {
+            let needed = self.output.contains(&label) || later.iter().any(|input| input.contains(&label));
+            if needed && !kept.contains(&label) {
+                kept.push(label);
+            }
+        }
+        kept
+    }
+}
+
+fn labels(subscripts: &str) -> Result<Vec<char>, String> {
+    subscripts.chars()
+        .map(|c| if c.is_ascii_alphabetic() { Ok(c) } else { Err(format!("Invalid subscript '{}'", c)) })
+        .collect()
+}
+
+/// Evaluate an Einstein summation written in subscript notation, e.g.
+/// `einsum("ij,jk->ik", &[&a, &b])` for a matrix product or `einsum("ii", &[&a])`
+/// for a trace
+pub fn einsum(spec: &str, tensors: &[&Tensor]) -> Result<Tensor, String> {
+    let spec = EinsumSpec::parse(spec)?;
+    spec.check_ranks(&tensors.iter().map(|tensor| tensor.rank).collect::<Vec<_>>())?;
+
+    let index = |labels: &[char]| labels.iter().map(|&c| c as usize).collect::<Vec<_>>();
+    let scalar_one = Tensor::new(Vec::new(), vec![1.0]);
+
+    let mut current = tensors[0].clone();
+    let mut current_labels = spec.inputs[0].clone();
+    for (position, (labels, tensor)) in spec.inputs.iter().zip(tensors).enumerate().skip(1) {
+        let kept = spec.kept_labels(position, &current_labels);
+        current = einstein_summation(&current, tensor, &index(&current_labels), &index(labels), &index(&kept))?;
+        current_labels = kept;
+    }
+
+    // Final permutation and reduction to the requested output
+    einstein_summation(&current, &scalar_one, &index(&current_labels), &[], &index(&spec.output))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let spec = EinsumSpec::parse("ij, jk -> ik").unwrap();
+        assert_eq!(spec.inputs, vec![vec!['i', 'j'], vec!['j', 'k']]);
+        assert_eq!(spec.output, vec!['i', 'k']);
+        assert_eq!(EinsumSpec::parse("ba,ac").unwrap().output, vec!['b', 'c']);
+        assert!(EinsumSpec::parse("ij->ii").is_err());
+        assert!(EinsumSpec::parse("ij->k").is_err());
+        assert!(EinsumSpec::parse("i1->i").is_err());
+    }
+
+    #[test]
+    fn test_einsum() {
+        let a = Tensor::new(vec![2, 3], vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let b = Tensor::new(vec![3, 2], vec![7.0, 8.0, 9.0, 10.0, 11.0, 12.0]);
+        assert_eq!(einsum("ij,jk->ik", &[&a, &b]).unwrap().data, vec![58.0, 64.0, 139.0, 154.0]);
+        assert_eq!(einsum("ij->ji", &[&a]).unwrap().data, vec![1.0, 4.0, 2.0, 5.0, 3.0, 6.0]);
+        assert_eq!(einsum("ij->", &[&a]).unwrap().data, vec![21.0]);
+
+        // Three operands: a vector-matrix-vector product
+        let ones2 = Tensor::new(vec![2], vec![1.0, 1.0]);
+        let ones3 = Tensor::new(vec![3], vec![1.0; 3]);
+        assert_eq!(einsum("i,ij,j", &[&ones2, &a, &ones3]).unwrap().data, vec![21.0]);
+
+        assert!(einsum("ij,jk->ik", &[&a, &a]).is_err());
+        assert!(einsum("ijk", &[&a]).is_err());
+        assert!(einsum("ij,jk", &[&a]).is_err());
+    }
+}
@@ -0,0 +1,315 @@
+//! Expression Graphs - Lazy evaluation of tensor logic formulas
+//!
+//! A `TensorExpr` describes a formula over named tensor variables. Compiling it
+//! merges structurally identical subexpressions into a single graph node, then
+//! evaluation materializes only the nodes that need a whole tensor: variables,
+//! disjunctions (which normalize), contractions, shared subexpressions and the
+//! root. Chains of AND, NOT and IMPLIES between those points are fused into one
+//! broadcasting pass per element instead of allocating a tensor per operation.
+
+use std::collections::HashMap;
+
+use rayon::prelude::*;
+
+use crate::memory_manager::pool;
+
+use super::broadcast::broadcast_strides;
+use super::{broadcast_shape, einsum, tensor_or, EinsumSpec, Tensor};
+
+/// Tensor logic formula
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TensorExpr {
+    /// Tensor bound by name at evaluation time
+    Var(String),
+    /// Conjunction, as `tensor_and`
+    And(Box<TensorExpr>, Box<TensorExpr>),
+    /// Disjunction, as `tensor_or`
+    Or(Box<TensorExpr>, Box<TensorExpr>),
+    /// Negation, as `tensor_not`
+    Not(Box<TensorExpr>),
+    /// Implication, as `tensor_implies`
+    Implies(Box<TensorExpr>, Box<TensorExpr>),
+    /// Contraction in subscript notation, as `einsum`
+    Einsum(String, Vec<TensorExpr>),
+}
+
+impl TensorExpr {
+    /// Named variable
+    pub fn var(name: impl Into<String>) -> Self {
+        Self::Var(name.into())
+    }
+
+    /// `self ∧ other`
+    pub fn and(self, other: TensorExpr) -> Self {
+        Self::And(Box::new(self), Box::new(other))
+    }
+
+    /// `self ∨ other`
+    pub fn or(self, other: TensorExpr) -> Self {
+        Self::Or(Box::new(self), Box::new(other))
+    }
+
+    /// `self → other`
+    pub fn implies(self, other: TensorExpr) -> Self {
+        Self::Implies(Box::new(self), Box::new(other))
+    }
+
+    /// Contraction of `operands` described by `spec`
+    pub fn einsum(spec: impl Into<String>, operands: Vec<TensorExpr>) -> Self {
+        Self::Einsum(spec.into(), operands)
+    }
+
+    /// Compile into a deduplicated expression graph
+    pub fn compile(&self) -> Result<CompiledExpr, String> {
+        let mut compiled = CompiledExpr { nodes: Vec::new(), consumers: Vec::new(), root: 0 };
+        let mut interned = HashMap::new();
+        compiled.root = compiled.intern(self, &mut interned)?;
+        Ok(compiled)
+    }
+
+    /// Compile and evaluate with the given variable bindings
+    pub fn evaluate(&self, bindings: &HashMap<String, Tensor>) -> Result<Tensor, String> {
+        self.compile()?.evaluate(bindings)
+    }
+}
+
+impl std::ops::Not for TensorExpr {
+    type Output = TensorExpr;
+
+    fn not(self) -> TensorExpr {
+        TensorExpr::Not(Box::new(self))
+    }
+}
+
+/// Graph node, referring to its operands by index
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Node {
+    Var(String),
+    And(usize, usize),
+    Or(usize, usize),
+    Not(usize),
+    Implies(usize, usize),
+    Einsum(String, Vec<usize>),
+}
+
+impl Node {
+    fn is_elementwise(&self) -> bool {
+        matches!(self, Node::And(..) | Node::Not(_) | Node::Implies(..))
+    }
+
+    fn operands(&self) -> Vec<usize> {
+        match self {
+            Node::Var(_) => Vec::new(),
+            Node::And(a, b) | Node::Or(a, b) | Node::Implies(a, b) => vec![*a, *b],
+            Node::Not(a) => vec![*a],
+            Node::Einsum(_, operands) => operands.clone(),
+        }
+    }
+}
+
+/// Instruction of a fused element-wise kernel, writing one register
+#[derive(Debug, Clone, Copy)]
+enum Instruction {
+    Load(usize),
+    And(usize, usize),
+    Not(usize),
+    Implies(usize, usize),
+}
+
+/// Expression graph with common subexpressions merged, in topological order
+#[derive(Debug, Clone)]
+pub struct CompiledExpr {
+    nodes: Vec<Node>,
+    /// Nodes consuming each node's value, one entry per use
+    consumers: Vec<Vec<usize>>,
+    root: usize,
+}
+
+impl CompiledExpr {
+    fn intern(&mut self, expr: &TensorExpr, interned: &mut HashMap<Node, usize>) -> Result<usize, String> {
+        let mut binary = |a: &TensorExpr, b: &TensorExpr, interned: &mut HashMap<Node, usize>| {
+            Ok::<_, String>((self.intern(a, interned)?, self.intern(b, interned)?))
+        };
+        let node = match expr {
+            TensorExpr::Var(name) => Node::Var(name.clone()),
+            TensorExpr::And(a, b) => binary(a, b, interned).map(|(a, b)| Node::And(a, b))?,
+            TensorExpr::Or(a, b) => binary(a, b, interned).map(|(a, b)| Node::Or(a, b))?,
+            TensorExpr::Implies(a, b) => binary(a, b, interned).map(|(a, b)| Node::Implies(a, b))?,
+            TensorExpr::Not(a) => Node::Not(self.intern(a, interned)?),
+            TensorExpr::Einsum(spec, operands) => {
+                let parsed = EinsumSpec::parse(spec)?;
+                if parsed.inputs.len() != operands.len() {
+                    return Err(format!(
+                        "Subscripts describe {} operands but {} expressions were given",
+                        parsed.inputs.len(), operands.len()
+                    ));
+                }
+                let operands = operands.iter().map(|operand| self.intern(operand, interned)).collect::<Result<_, _>>()?;
+                Node::Einsum(spec.clone(), operands)
+            }
+        };
+        if let Some(&id) = interned.get(&node) {
+            return Ok(id);
+        }
+        let id = self.nodes.len();
+        for operand in node.operands() {
+            self.consumers[operand].push(id);
+        }
+        self.nodes.push(node.clone());
+        self.consumers.push(Vec::new());
+        interned.insert(node, id);
+        Ok(id)
+    }
+
+    /// Number of distinct nodes after common-subexpression elimination
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Whether a node's value is folded into its only consumer's fused kernel
+    fn is_inlined(&self, id: usize) -> bool {
+        self.nodes[id].is_elementwise()
+            && id != self.root
+            && matches!(self.consumers[id].as_slice(), [consumer] if self.nodes[*consumer].is_elementwise())
+    }
+
+    /// Number of tensors allocated by an evaluation, variables excluded
+    pub fn materialized_count(&self) -> usize {
+        (0..self.nodes.len())
+            .filter(|&id| !self.is_inlined(id) && !matches!(self.nodes[id], Node::Var(_)))
+            .count()
+    }
+
+    /// Evaluate with the given variable bindings
+    pub fn evaluate(&self, bindings: &HashMap<String, Tensor>) -> Result<Tensor, String> {
+        let mut values: Vec<Option<Tensor>> = vec![None; self.nodes.len()];
+        for id in 0..self.nodes.len() {
+            if self.is_inlined(id) {
+                continue;
+            }
+            let value = |operand: usize| values[operand].as_ref().expect("operands are evaluated first");
+            let tensor = match &self.nodes[id] {
+                Node::Var(name) => bindings.get(name).cloned().ok_or_else(|| format!("Unbound variable '{}'", name))?,
+                Node::Or(a, b) => tensor_or(value(*a), value(*b))?,
+                Node::Einsum(spec, operands) => einsum(spec, &operands.iter().map(|&operand| value(operand)).collect::<Vec<_>>())?,
+                _ => self.fused(id, &values)?,
+            };
+            values[id] = Some(tensor);
+        }
+        let result = values[self.root].take().expect("root is evaluated");
+        // Intermediates and bound copies go back to the pool for the next evaluation
+        let pool = pool::global();
+        for tensor in values.into_iter().flatten() {
+            pool.recycle(tensor.data);
+        }
+        Ok(result)
+    }
+
+    /// Evaluate the element-wise chain rooted at `id` in a single pass
+    fn fused(&self, id: usize, values: &[Option<Tensor>]) -> Result<Tensor, String> {
+        let mut program = Vec::new();
+        let mut leaves: Vec<&Tensor> = Vec::new();
+        self.emit(id, values, &mut program, &mut leaves);
+
+        let shape = leaves.iter().try_fold(Vec::new(), |shape, leaf| broadcast_shape(&shape, &leaf.shape))?;
+        let strides: Vec<Vec<usize>> = leaves.iter().map(|leaf| broadcast_strides(&leaf.shape, &shape)).collect();
+        let size: usize = shape.iter().product();
+
+        let mut data = pool::global().rent(size);
+        data.par_iter_mut()
+            .enumerate()
+            .for_each_init(
+                || vec![0.0; program.len()],
+                |registers, (mut index, out)| {
+                    let mut offsets = vec![0; leaves.len()];
+                    for axis in (0..shape.len()).rev() {
+                        let coordinate = index % shape[axis];
+                        index /= shape[axis];
+                        for (offset, strides) in offsets.iter_mut().zip(&strides) {
+                            *offset += coordinate * strides[axis];
+                        }
+                    }
+                    for (register, instruction) in program.iter().enumerate() {
+                        registers[register] = match *instruction {
+                            Instruction::Load(leaf) => leaves[leaf].data[offsets[leaf]],
+                            Instruction::And(a, b) => registers[a] * registers[b],
+                            Instruction::Not(a) => 1.0 - registers[a],
+                            Instruction::Implies(a, b) => (1.0 - registers[a]).max(registers[b]),
+                        };
+                    }
+                    *out = registers[program.len() - 1];
+                },
+            );
+        Ok(Tensor::new(shape, data))
+    }
+
+    /// Append the instructions computing node `id`, returning its register
+    fn emit<'a>(
+        &self,
+        id: usize,
+        values: &'a [Option<Tensor>],
+        program: &mut Vec<Instruction>,
+        leaves: &mut Vec<&'a Tensor>,
+    ) -> usize {
+        let instruction = match (&self.nodes[id], &values[id]) {
+            (_, Some(tensor)) => {
+                leaves.push(tensor);
+                Instruction::Load(leaves.len() - 1)
+            }
+            (Node::And(a, b), None) => Instruction::And(self.emit(*a, values, program, leaves), self.emit(*b, values, program, leaves)),
+            (Node::Implies(a, b), None) => Instruction::Implies(self.emit(*a, values, program, leaves), self.emit(*b, values, program, leaves)),
+            (Node::Not(a), None) => Instruction::Not(self.emit(*a, values, program, leaves)),
+            (node, None) => unreachable!("{:?} is materialized before its consumers", node),
+        };
+        program.push(instruction);
+        program.len() - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tensor_ops::{tensor_and, tensor_implies, tensor_not};
+
+    fn bindings() -> HashMap<String, Tensor> {
+        HashMap::from([
+            ("p".to_string(), Tensor::new(vec![2, 2], vec![0.9, 0.1, 0.4, 0.7])),
+            ("q".to_string(), Tensor::new(vec![2], vec![0.3, 0.8])),
+            ("r".to_string(), Tensor::new(vec![2, 2], vec![0.5, 0.2, 0.6, 1.0])),
+        ])
+    }
+
+    #[test]
+    fn test_fused_chain_matches_op_by_op() {
+        let (p, q, r) = (TensorExpr::var("p"), TensorExpr::var("q"), TensorExpr::var("r"));
+        let expr = (!p.clone().and(q)).implies(r.clone());
+        let compiled = expr.compile().unwrap();
+        assert_eq!(compiled.materialized_count(), 1);
+
+        let b = bindings();
+        let expected = tensor_implies(&tensor_not(&tensor_and(&b["p"], &b["q"]).unwrap()), &b["r"]).unwrap();
+        let result = compiled.evaluate(&b).unwrap();
+        assert_eq!(result.shape, expected.shape);
+        assert_eq!(result.data, expected.data);
+
+        assert!(p.and(TensorExpr::var("missing")).evaluate(&b).is_err());
+        assert!(TensorExpr::einsum("ij,jk->ik", vec![r]).compile().is_err());
+    }
+
+    #[test]
+    fn test_common_subexpressions_are_shared() {
+        let composed = TensorExpr::einsum("ij,jk->ik", vec![TensorExpr::var("p"), TensorExpr::var("r")]);
+        let expr = composed.clone().and(TensorExpr::var("p")).or(!composed.clone());
+        let compiled = expr.compile().unwrap();
+        // p, r, einsum, and, not, or
+        assert_eq!(compiled.node_count(), 6);
+
+        let b = bindings();
+        let product = einsum("ij,jk->ik", &[&b["p"], &b["r"]]).unwrap();
+        let expected = tensor_or(&tensor_and(&product, &b["p"]).unwrap(), &tensor_not(&product)).unwrap();
+        let result = compiled.evaluate(&b).unwrap();
+        for (x, y) in result.data.iter().zip(&expected.data) {
+            assert!((x - y).abs() < 1e-12);
+        }
+    }
+}
@@ -0,0 +1,434 @@
+//! GPU Backend - WGSL compute kernels for contractions and logic ops
+//!
+//! Every operation runs through one contraction shader driven by a small plan
+//! buffer: the output index is decoded into per-axis coordinates, each summed
+//! index likewise, and the two operand offsets are accumulated from per-axis
+//! strides. Einsum contractions combine operands by product, whereas element-wise
+//! logic ops use broadcast strides, no summed axes and their own combining
+//! function. Data lives on the device as `f32`, since WGSL has no double
+//! precision, so results differ from the CPU path by single-precision rounding.
+
+use std::sync::{mpsc, Arc};
+
+use wgpu::util::DeviceExt;
+
+use super::broadcast::broadcast_strides;
+use super::{broadcast_shape, row_major_strides, EinsumSpec, Tensor};
+
+const SHADER: &str = r#"
+@group(0) @binding(0) var<storage, read> a: array<f32>;
+@group(0) @binding(1) var<storage, read> b: array<f32>;
+@group(0) @binding(2) var<storage, read_write> out: array<f32>;
+@group(0) @binding(3) var<storage, read> plan: array<u32>;
+
+fn combine(op: u32, x: f32, y: f32) -> f32 {
+    var result = 1.0 - x;
+    switch op {
+        case 0u: { result = x * y; }
+        case 1u: { result = max(x, y); }
+        case 2u: { result = max(1.0 - x, y); }
+        default: {}
+    }
+    return result;
+}
+
+@compute @workgroup_size(64)
+fn contract(@builtin(global_invocation_id) id: vec3<u32>, @builtin(num_workgroups) groups: vec3<u32>) {
+    let index = id.x + id.y * groups.x * 64u;
+    let op = plan[0];
+    let n_out = plan[1];
+    let n_sum = plan[2];
+    if (index >= plan[3]) {
+        return;
+    }
+
+    var rest = index;
+    var base_a = 0u;
+    var base_b = 0u;
+    for (var i = 0u; i < n_out; i = i + 1u) {
+        let axis = 5u + (n_out - 1u - i) * 3u;
+        let coordinate = rest % plan[axis];
+        rest = rest / plan[axis];
+        base_a = base_a + coordinate * plan[axis + 1u];
+        base_b = base_b + coordinate * plan[axis + 2u];
+    }
+
+    var total = 0.0;
+    for (var s = 0u; s < plan[4]; s = s + 1u) {
+        var rest_s = s;
+        var offset_a = base_a;
+        var offset_b = base_b;
+        for (var j = 0u; j < n_sum; j = j + 1u) {
+            let axis = 5u + (n_out + n_sum - 1u - j) * 3u;
+            let coordinate = rest_s % plan[axis];
+            rest_s = rest_s / plan[axis];
+            offset_a = offset_a + coordinate * plan[axis + 1u];
+            offset_b = offset_b + coordinate * plan[axis + 2u];
+        }
+        total = total + combine(op, a[offset_a], b[offset_b]);
+    }
+    out[index] = total;
+}
+
+var<workgroup> partial: array<f32, 256>;
+
+@compute @workgroup_size(256)
+fn normalize(@builtin(local_invocation_index) local: u32) {
+    let size = plan[0];
+    var sum = 0.0;
+    for (var i = local; i < size; i = i + 256u) {
+        sum = sum + out[i] * out[i];
+    }
+    partial[local] = sum;
+    workgroupBarrier();
+    for (var stride = 128u; stride > 0u; stride = stride / 2u) {
+        if (local < stride) {
+            partial[local] = partial[local] + partial[local + stride];
+        }
+        workgroupBarrier();
+    }
+
+    let norm = sqrt(partial[0]);
+    if (norm > 1e-10) {
+        for (var i = local; i < size; i = i + 256u) {
+            out[i] = out[i] / norm;
+        }
+    }
+}
+"#;
+
+/// Threads per workgroup of the contraction shader
+const WORKGROUP_SIZE: usize = 64;
+/// Largest workgroup count per dispatch dimension guaranteed by WebGPU
+const MAX_GROUPS: usize = 65535;
+
+/// How the contraction shader combines an element of each operand
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Combine {
+    Product = 0,
+    Max = 1,
+    Implies = 2,
+    Not = 3,
+}
+
+/// An adapter, its device and the compiled kernels
+pub struct GpuContext {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    contract: wgpu::ComputePipeline,
+    normalize: wgpu::ComputePipeline,
+    adapter_name: String,
+}
+
+impl std::fmt::Debug for GpuContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GpuContext").field("adapter", &self.adapter_name).finish()
+    }
+}
+
+impl GpuContext {
+    /// Open the highest-performance adapter available and compile the kernels
+    pub fn new() -> Result<Self, String> {
+        pollster::block_on(async {
+            let instance = wgpu::Instance::default();
+            let adapter = instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: wgpu::PowerPreference::HighPerformance,
+                    compatible_surface: None,
+                    force_fallback_adapter: false,
+                })
+                .await
+                .ok_or("No GPU adapter available")?;
+            let (device, queue) = adapter
+                .request_device(
+                    &wgpu::DeviceDescriptor {
+                        label: Some("tensor_ops"),
+                        required_features: wgpu::Features::empty(),
+                        required_limits: adapter.limits(),
+                    },
+                    None,
+                )
+                .await
+                .map_err(|e| format!("Failed to open GPU device: {}", e))?;
+
+            let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("tensor_ops"),
+                source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+            });
+            let pipeline = |entry_point| {
+                device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                    label: Some(entry_point),
+                    layout: None,
+                    module: &module,
+                    entry_point,
+                })
+            };
+            let (contract, normalize) = (pipeline("contract"), pipeline("normalize"));
+
+            Ok(Self { contract, normalize, device, queue, adapter_name: adapter.get_info().name })
+        })
+    }
+
+    /// Name of the adapter backing this context
+    pub fn adapter_name(&self) -> &str {
+        &self.adapter_name
+    }
+
+    fn storage_buffer(&self, contents: &[u8]) -> wgpu::Buffer {
+        self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: None,
+            contents,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+        })
+    }
+
+    fn run(&self, pipeline: &wgpu::ComputePipeline, buffers: &[(u32, &wgpu::Buffer)], groups: (u32, u32)) {
+        let entries: Vec<_> = buffers
+            .iter()
+            .map(|&(binding, buffer)| wgpu::BindGroupEntry { binding, resource: buffer.as_entire_binding() })
+            .collect();
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &entries,
+        });
+
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: None, timestamp_writes: None });
+            pass.set_pipeline(pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(groups.0, groups.1, 1);
+        }
+        self.queue.submit(Some(encoder.finish()));
+    }
+}
+
+/// A tensor whose data lives in a GPU storage buffer
+#[derive(Debug, Clone)]
+pub struct GpuTensor {
+    pub shape: Vec<usize>,
+    buffer: Arc<wgpu::Buffer>,
+    context: Arc<GpuContext>,
+}
+
+impl GpuTensor {
+    /// Copy a tensor to the device, narrowing it to `f32`
+    pub fn upload(tensor: &Tensor, context: &Arc<GpuContext>) -> Self {
+        let mut data: Vec<f32> = tensor.data.iter().map(|&x| x as f32).collect();
+        // Zero-sized bindings are invalid, so empty tensors get one padding element
+        if data.is_empty() {
+            data.push(0.0);
+        }
+        Self {
+            shape: tensor.shape.clone(),
+            buffer: Arc::new(context.storage_buffer(bytemuck::cast_slice(&data))),
+            context: Arc::clone(context),
+        }
+    }
+
+    /// Copy the data back to the host
+    pub fn download(&self) -> Result<Tensor, String> {
+        let context = &self.context;
+        let size = self.buffer.size();
+        let staging = context.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut encoder = context.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        encoder.copy_buffer_to_buffer(&self.buffer, 0, &staging, 0, size);
+        context.queue.submit(Some(encoder.finish()));
+
+        let slice = staging.slice(..);
+        let (sender, receiver) = mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        context.device.poll(wgpu::Maintain::Wait);
+        receiver
+            .recv()
+            .map_err(|e| e.to_string())?
+            .map_err(|e| format!("Failed to read GPU buffer: {}", e))?;
+
+        let len = self.len();
+        let data = {
+            let view = slice.get_mapped_range();
+            bytemuck::cast_slice::<u8, f32>(&view)[..len].iter().map(|&x| x as f64).collect()
+        };
+        staging.unmap();
+        Ok(Tensor::new(self.shape.clone(), data))
+    }
+
+    /// The context holding this tensor's buffer
+    pub fn context(&self) -> &Arc<GpuContext> {
+        &self.context
+    }
+
+    fn len(&self) -> usize {
+        self.shape.iter().product()
+    }
+}
+
+/// Run the contraction shader with `plan` and return the output tensor
+fn dispatch(a: &GpuTensor, b: &GpuTensor, shape: Vec<usize>, plan: &[usize]) -> Result<GpuTensor, String> {
+    if !Arc::ptr_eq(&a.context, &b.context) {
+        return Err("Tensors live on different GPU contexts".to_string());
+    }
+    let plan = plan
+        .iter()
+        .map(|&value| u32::try_from(value).map_err(|_| format!("Tensor of shape {:?} is too large for the GPU", shape)))
+        .collect::<Result<Vec<u32>, String>>()?;
+
+    let context = &a.context;
+    let size: usize = shape.iter().product();
+    let out = context.storage_buffer(bytemuck::cast_slice(&vec![0.0f32; size.max(1)]));
+    if size > 0 {
+        let groups = size.div_ceil(WORKGROUP_SIZE);
+        let x = groups.min(MAX_GROUPS);
+        let plan = context.storage_buffer(bytemuck::cast_slice(&plan));
+        context.run(
+            &context.contract,
+            &[(0, &a.buffer), (1, &b.buffer), (2, &out), (3, &plan)],
+            (x as u32, groups.div_ceil(x) as u32),
+        );
+    }
+    Ok(GpuTensor { shape, buffer: Arc::new(out), context: Arc::clone(context) })
+}
+
+/// Divide a tensor in place by its L2 norm, as the CPU `tensor_or` does
+fn normalize(tensor: &GpuTensor) {
+    let context = &tensor.context;
+    let size = context.storage_buffer(bytemuck::cast_slice(&[tensor.len() as u32]));
+    context.run(&context.normalize, &[(2, &tensor.buffer), (3, &size)], (1, 1));
+}
+
+/// Combine two tensors element-wise with NumPy broadcasting
+pub(super) fn elementwise(op: Combine, a: &GpuTensor, b: &GpuTensor) -> Result<GpuTensor, String> {
+    let shape = broadcast_shape(&a.shape, &b.shape)?;
+    let (strides_a, strides_b) = (broadcast_strides(&a.shape, &shape), broadcast_strides(&b.shape, &shape));
+
+    let mut plan = vec![op as usize, shape.len(), 0, shape.iter().product(), 1];
+    for axis in 0..shape.len() {
+        plan.extend([shape[axis], strides_a[axis], strides_b[axis]]);
+    }
+    let result = dispatch(a, b, shape, &plan)?;
+    if op == Combine::Max {
+        normalize(&result);
+    }
+    Ok(result)
+}
+
+/// Logical NOT, `1 - a`
+pub(super) fn not(a: &GpuTensor) -> Result<GpuTensor, String> {
+    elementwise(Combine::Not, a, a)
+}
+
+/// Contract two labelled tensors onto `output` labels, summing the rest
+fn contract_pair(a: &GpuTensor, labels_a: &[char], b: &GpuTensor, labels_b: &[char], output: &[char]) -> Result<GpuTensor, String> {
+    // Every label's dimension and its summed strides within each operand
+    let mut axes: Vec<(char, usize, usize, usize)> = Vec::new();
+    for (shape, labels, operand) in [(&a.shape, labels_a, 0), (&b.shape, labels_b, 1)] {
+        let strides = row_major_strides(shape);
+        for (position, &label) in labels.iter().enumerate() {
+            let dim = shape[position];
+            let axis = match axes.iter().position(|&(existing, ..)| existing == label) {
+                Some(axis) => axis,
+                None => {
+                    axes.push((label, dim, 0, 0));
+                    axes.len() - 1
+                }
+            };
+            if axes[axis].1 != dim {
+                return Err(format!("Label '{}' has dimensions {} and {}", label, axes[axis].1, dim));
+            }
+            if operand == 0 {
+                axes[axis].2 += strides[position];
+            } else {
+                axes[axis].3 += strides[position];
+            }
+        }
+    }
+
+    let find = |label: char| axes.iter().find(|&&(existing, ..)| existing == label).copied();
+    let outputs = output
+        .iter()
+        .map(|&label| find(label).ok_or_else(|| format!("Output label '{}' not found in inputs", label)))
+        .collect::<Result<Vec<_>, String>>()?;
+    let summed: Vec<_> = axes.iter().filter(|(label, ..)| !output.contains(label)).copied().collect();
+
+    let shape: Vec<usize> = outputs.iter().map(|&(_, dim, ..)| dim).collect();
+    let mut plan = vec![
+        Combine::Product as usize,
+        outputs.len(),
+        summed.len(),
+        shape.iter().product(),
+        summed.iter().map(|&(_, dim, ..)| dim).product(),
+    ];
+    for (_, dim, stride_a, stride_b) in outputs.into_iter().chain(summed) {
+        plan.extend([dim, stride_a, stride_b]);
+    }
+    dispatch(a, b, shape, &plan)
+}
+
+/// Evaluate an Einstein summation on the device, contracting left to right like
+/// the CPU `einsum`
+pub(super) fn einsum(spec: &str, tensors: &[&GpuTensor]) -> Result<GpuTensor, String> {
+    let spec = EinsumSpec::parse(spec)?;
+    spec.check_ranks(&tensors.iter().map(|tensor| tensor.shape.len()).collect::<Vec<_>>())?;
+    let scalar_one = GpuTensor::upload(&Tensor::new(Vec::new(), vec![1.0]), &tensors[0].context);
+
+    let mut current = tensors[0].clone();
+    let mut current_labels = spec.inputs[0].clone();
+    for (position, (labels, tensor)) in spec.inputs.iter().zip(tensors).enumerate().skip(1) {
+        let kept = spec.kept_labels(position, &current_labels);
+        current = contract_pair(&current, &current_labels, tensor, labels, &kept)?;
+        current_labels = kept;
+    }
+    contract_pair(&current, &current_labels, &scalar_one, &[], &spec.output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tensor_ops::{einsum as cpu_einsum, tensor_and, tensor_implies, tensor_not, tensor_or};
+
+    fn assert_close(gpu: &GpuTensor, cpu: &Tensor) {
+        let gpu = gpu.download().unwrap();
+        assert_eq!(gpu.shape, cpu.shape);
+        for (x, y) in gpu.data.iter().zip(&cpu.data) {
+            assert!((x - y).abs() < 1e-4, "{} vs {}", x, y);
+        }
+    }
+
+    #[test]
+    fn test_gpu_matches_cpu() {
+        // Machines without a usable adapter (e.g. headless CI) skip the comparison
+        let Ok(context) = GpuContext::new().map(Arc::new) else {
+            return;
+        };
+        let a = Tensor::new(vec![2, 3], vec![0.1, 0.5, 0.9, 0.3, 0.7, 0.2]);
+        let b = Tensor::new(vec![3], vec![0.6, 0.4, 0.8]);
+        let square = Tensor::new(vec![3, 3], (0..9).map(|x| x as f64 / 9.0).collect());
+        let (gpu_a, gpu_b) = (GpuTensor::upload(&a, &context), GpuTensor::upload(&b, &context));
+        let gpu_square = GpuTensor::upload(&square, &context);
+
+        assert_close(&gpu_a, &a);
+        assert_close(&elementwise(Combine::Product, &gpu_a, &gpu_b).unwrap(), &tensor_and(&a, &b).unwrap());
+        assert_close(&elementwise(Combine::Max, &gpu_a, &gpu_b).unwrap(), &tensor_or(&a, &b).unwrap());
+        assert_close(&elementwise(Combine::Implies, &gpu_a, &gpu_b).unwrap(), &tensor_implies(&a, &b).unwrap());
+        assert_close(&not(&gpu_a).unwrap(), &tensor_not(&a));
+
+        for (spec, operands, gpu_operands) in [
+            ("ij,jk->ik", vec![&a, &square], vec![&gpu_a, &gpu_square]),
+            ("ij,j->i", vec![&a, &b], vec![&gpu_a, &gpu_b]),
+            ("ii", vec![&square], vec![&gpu_square]),
+            ("ij->ji", vec![&a], vec![&gpu_a]),
+            ("i,ij,jk->k", vec![&b, &square, &square], vec![&gpu_b, &gpu_square, &gpu_square]),
+        ] {
+            assert_close(&einsum(spec, &gpu_operands).unwrap(), &cpu_einsum(spec, &operands).unwrap());
+        }
+        assert!(einsum("ij,jk->ik", &[&gpu_a, &gpu_a]).is_err());
+    }
+}
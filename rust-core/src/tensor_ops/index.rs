@@ -0,0 +1,169 @@
+//! Indexing - Slicing, index selection, gather and scatter
+//!
+//! Index tensors hold non-negative whole numbers stored as `f64`, matching the
+//! output of `Tensor::argmax`. Every index is bounds-checked before any data moves,
+//! and copies into the result run in parallel.
+
+use std::ops::Range;
+
+use rayon::prelude::*;
+
+use super::Tensor;
+
+/// Coordinates of a row-major position of `shape`
+fn coordinates(shape: &[usize], mut index: usize) -> Vec<usize> {
+    let mut coordinates = vec![0; shape.len()];
+    for (coordinate, &dim) in coordinates.iter_mut().zip(shape).rev() {
+        *coordinate = index % dim;
+        index /= dim;
+    }
+    coordinates
+}
+
+/// Checked conversion of a stored index value
+fn to_index(value: f64, extent: usize) -> Result<usize, String> {
+    if value < 0.0 || value.fract() != 0.0 || value >= extent as f64 {
+        return Err(format!("Index {} out of range for extent {}", value, extent));
+    }
+    Ok(value as usize)
+}
+
+impl Tensor {
+    fn check_axis(&self, axis: usize) -> Result<(), String> {
+        if axis >= self.rank {
+            return Err(format!("Axis {} out of range for rank {}", axis, self.rank));
+        }
+        Ok(())
+    }
+
+    /// Sub-tensor covering `ranges` of the leading axes; later axes are kept whole
+    ///
+    /// Copies the elements; use `Tensor::view` and `TensorView::slice` to avoid that.
+    pub fn slice(&self, ranges: &[Range<usize>]) -> Result<Tensor, String> {
+        Ok(self.view().slice(ranges)?.to_tensor())
+    }
+
+    /// The entries at `indices` along `axis`, in the given order
+    pub fn index_select(&self, axis: usize, indices: &[usize]) -> Result<Tensor, String> {
+        self.check_axis(axis)?;
+        if let Some(&index) = indices.iter().find(|&&index| index >= self.shape[axis]) {
+            return Err(format!("Index {} out of range for axis {} of extent {}", index, axis, self.shape[axis]));
+        }
+
+        let strides = self.strides();
+        let mut shape = self.shape.clone();
+        shape[axis] = indices.len();
+        let size: usize = shape.iter().product();
+
+        let data = (0..size)
+            .into_par_iter()
+            .map(|index| {
+                let mut coordinates = coordinates(&shape, index);
+                coordinates[axis] = indices[coordinates[axis]];
+                self.data[coordinates.iter().zip(&strides).map(|(c, s)| c * s).sum::<usize>()]
+            })
+            .collect();
+        Ok(Tensor::new(shape, data))
+    }
+
+    /// Values picked along `axis` by `index`, which has the rank of `self`
+    ///
+    /// `out[.., i, ..] = self[.., index[.., i, ..], ..]` with the index replacing the
+    /// coordinate of `axis`; other extents of `index` must not exceed those of `self`.
+    pub fn gather(&self, axis: usize, index: &Tensor) -> Result<Tensor, String> {
+        self.check_index_shape(axis, index)?;
+        let strides = self.strides();
+        let extent = self.shape[axis];
+        let data = (0..index.size())
+            .into_par_iter()
+            .map(|position| {
+                let mut coordinates = coordinates(&index.shape, position);
+                coordinates[axis] = to_index(index.data[position], extent)?;
+                Ok(self.data[coordinates.iter().zip(&strides).map(|(c, s)| c * s).sum::<usize>()])
+            })
+            .collect::<Result<Vec<f64>, String>>()?;
+        Ok(Tensor::new(index.shape.clone(), data))
+    }
+
+    /// Copy of `self` with `src` written at the positions `index` selects along `axis`
+    ///
+    /// The inverse of `gather`: `out[.., index[.., i, ..], ..] = src[.., i, ..]`. When
+    /// several entries target the same position the last one wins.
+    pub fn scatter(&self, axis: usize, index: &Tensor, src: &Tensor) -> Result<Tensor, String> {
+        self.check_index_shape(axis, index)?;
+        if src.shape != index.shape {
+            return Err(format!("Scatter source {:?} does not match index {:?}", src.shape, index.shape));
+        }
+
+        let strides = self.strides();
+        let extent = self.shape[axis];
+        let targets = (0..index.size())
+            .into_par_iter()
+            .map(|position| {
+                let mut coordinates = coordinates(&index.shape, position);
+                coordinates[axis] = to_index(index.data[position], extent)?;
+                Ok(coordinates.iter().zip(&strides).map(|(c, s)| c * s).sum::<usize>())
+            })
+            .collect::<Result<Vec<usize>, String>>()?;
+
+        let mut result = self.clone();
+        for (target, &value) in targets.into_iter().zip(&src.data) {
+            result.data[target] = value;
+        }
+        Ok(result)
+    }
+
+    fn check_index_shape(&self, axis: usize, index: &Tensor) -> Result<(), String> {
+        self.check_axis(axis)?;
+        let fits = index.rank == self.rank
+            && index.shape.iter().zip(&self.shape).enumerate().all(|(i, (&n, &m))| i == axis || n <= m);
+        if !fits {
+            return Err(format!("Index shape {:?} does not fit tensor {:?} along axis {}", index.shape, self.shape, axis));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matrix() -> Tensor {
+        Tensor::new(vec![3, 3], (1..=9).map(|x| x as f64).collect())
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn test_slice_and_index_select() {
+        let m = matrix();
+        let block = m.slice(&[1..3, 0..2]).unwrap();
+        assert_eq!(block.shape, vec![2, 2]);
+        assert_eq!(block.data, vec![4.0, 5.0, 7.0, 8.0]);
+        assert_eq!(m.slice(&[2..3]).unwrap().data, vec![7.0, 8.0, 9.0]);
+        assert!(m.slice(&[0..4]).is_err());
+
+        let columns = m.index_select(1, &[2, 0]).unwrap();
+        assert_eq!(columns.data, vec![3.0, 1.0, 6.0, 4.0, 9.0, 7.0]);
+        assert!(m.index_select(0, &[3]).is_err());
+    }
+
+    #[test]
+    fn test_gather_and_scatter() {
+        let m = matrix();
+        let index = Tensor::new(vec![3, 1], vec![2.0, 0.0, 1.0]);
+        let picked = m.gather(1, &index).unwrap();
+        assert_eq!(picked.data, vec![3.0, 4.0, 8.0]);
+
+        // argmax output gathers the row maxima
+        let argmax = m.argmax(1, true).unwrap();
+        assert_eq!(m.gather(1, &argmax).unwrap().data, vec![3.0, 6.0, 9.0]);
+
+        let zeros = Tensor::new(vec![3, 3], vec![0.0; 9]);
+        let scattered = zeros.scatter(1, &index, &picked).unwrap();
+        assert_eq!(scattered.data, vec![0.0, 0.0, 3.0, 4.0, 0.0, 0.0, 0.0, 8.0, 0.0]);
+
+        assert!(m.gather(1, &Tensor::new(vec![1, 1], vec![3.0])).is_err());
+        assert!(m.gather(1, &Tensor::new(vec![1, 1], vec![0.5])).is_err());
+        assert!(m.gather(1, &Tensor::new(vec![4, 1], vec![0.0; 4])).is_err());
+    }
+}
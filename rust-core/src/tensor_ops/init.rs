@@ -0,0 +1,104 @@
+//! Constructors - Constant, identity and random tensors
+//!
+//! Random constructors draw from a caller-supplied RNG rather than a global one,
+//! so seeding it (e.g. `StdRng::seed_from_u64`) makes fixtures and weight
+//! initialization reproducible. Elements are drawn sequentially in row-major
+//! order, so the same seed always yields the same tensor.
+
+use rand::Rng;
+use rand_distr::{Bernoulli, Distribution, Normal, Uniform};
+
+use super::Tensor;
+
+impl Tensor {
+    /// Tensor of `shape` with every element equal to `value`
+    pub fn full(shape: &[usize], value: f64) -> Self {
+        Tensor::new(shape.to_vec(), vec![value; shape.iter().product()])
+    }
+
+    /// Tensor of `shape` filled with zeros
+    pub fn zeros(shape: &[usize]) -> Self {
+        Self::full(shape, 0.0)
+    }
+
+    /// Tensor of `shape` filled with ones
+    pub fn ones(shape: &[usize]) -> Self {
+        Self::full(shape, 1.0)
+    }
+
+    /// `n × n` identity matrix
+    pub fn eye(n: usize) -> Self {
+        let mut tensor = Self::zeros(&[n, n]);
+        for i in 0..n {
+            tensor.data[i * n + i] = 1.0;
+        }
+        tensor
+    }
+
+    /// Elements drawn uniformly from `[low, high)`
+    pub fn rand_uniform<R: Rng + ?Sized>(shape: &[usize], low: f64, high: f64, rng: &mut R) -> Result<Self, String> {
+        if high <= low || !(high - low).is_finite() {
+            return Err(format!("Invalid uniform range [{}, {})", low, high));
+        }
+        Ok(Self::sample(shape, Uniform::new(low, high), rng))
+    }
+
+    /// Elements drawn from a normal distribution
+    pub fn rand_normal<R: Rng + ?Sized>(shape: &[usize], mean: f64, std_dev: f64, rng: &mut R) -> Result<Self, String> {
+        // rand_distr accepts a negative deviation (mirroring the samples), so reject it here
+        if std_dev < 0.0 || std_dev.is_nan() {
+            return Err(format!("Standard deviation {} must be non-negative", std_dev));
+        }
+        let normal = Normal::new(mean, std_dev).map_err(|e| format!("Invalid normal distribution: {}", e))?;
+        Ok(Self::sample(shape, normal, rng))
+    }
+
+    /// Elements that are 1 with probability `p` and 0 otherwise
+    pub fn bernoulli<R: Rng + ?Sized>(shape: &[usize], p: f64, rng: &mut R) -> Result<Self, String> {
+        let bernoulli = Bernoulli::new(p).map_err(|e| format!("Invalid probability {}: {}", p, e))?;
+        let data = (0..shape.iter().product::<usize>()).map(|_| if bernoulli.sample(rng) { 1.0 } else { 0.0 }).collect();
+        Ok(Tensor::new(shape.to_vec(), data))
+    }
+
+    fn sample<R: Rng + ?Sized>(shape: &[usize], distribution: impl Distribution<f64>, rng: &mut R) -> Self {
+        let data = (0..shape.iter().product::<usize>()).map(|_| distribution.sample(rng)).collect();
+        Tensor::new(shape.to_vec(), data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_constant_constructors() {
+        assert_eq!(Tensor::zeros(&[2, 3]).data, vec![0.0; 6]);
+        assert_eq!(Tensor::ones(&[4]).shape, vec![4]);
+        assert_eq!(Tensor::full(&[], 2.5).data, vec![2.5]);
+        assert_eq!(Tensor::eye(3).data, vec![1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_random_constructors_are_seeded() {
+        let draw = |seed| Tensor::rand_normal(&[64], 1.0, 0.5, &mut StdRng::seed_from_u64(seed)).unwrap();
+        assert_eq!(draw(5).data, draw(5).data);
+        assert_ne!(draw(5).data, draw(6).data);
+
+        let mut rng = StdRng::seed_from_u64(11);
+        let uniform = Tensor::rand_uniform(&[1000], -2.0, 3.0, &mut rng).unwrap();
+        assert!(uniform.data.iter().all(|&x| (-2.0..3.0).contains(&x)));
+        let mean = uniform.data.iter().sum::<f64>() / 1000.0;
+        assert!((mean - 0.5).abs() < 0.2);
+
+        let mask = Tensor::bernoulli(&[1000], 0.25, &mut rng).unwrap();
+        assert!(mask.data.iter().all(|&x| x == 0.0 || x == 1.0));
+        let ones = mask.data.iter().sum::<f64>();
+        assert!((150.0..350.0).contains(&ones));
+
+        assert!(Tensor::rand_uniform(&[2], 1.0, 1.0, &mut rng).is_err());
+        assert!(Tensor::rand_normal(&[2], 0.0, -1.0, &mut rng).is_err());
+        assert!(Tensor::bernoulli(&[2], 1.5, &mut rng).is_err());
+    }
+}
@@ -0,0 +1,222 @@
+//! In-place Operations - Element-wise ops that overwrite their left operand
+//!
+//! Each op writes its result into the left tensor instead of allocating a new
+//! one, so the right operand must broadcast to the left operand's shape. The
+//! `Tensor` methods return `&mut Self` for chaining, e.g.
+//! `a.and_inplace(&b)?.scale_inplace(0.5).add_inplace(&c)?`. The buffer-level
+//! kernels are shared with the FFI, which runs them on the tensors behind its
+//! handles.
+
+use rayon::prelude::*;
+use tracing::instrument;
+
+use super::{stable, Tensor, TensorView};
+
+/// Elements handled by one rayon task on the same-shape path
+const CHUNK: usize = 1 << 14;
+
+/// Overwrite every `x` in `data` (of `shape`) with `op(x, y)`, where `y` is the
+/// matching element of `other` broadcast to `shape`
+fn zip_inplace(
+    data: &mut [f64],
+    shape: &[usize],
+    other: &TensorView,
+    op: impl Fn(f64, f64) -> f64 + Sync,
+) -> Result<(), String> {
+    if other.shape() == shape {
+        if let Some(other) = other.as_slice() {
+            data.par_chunks_mut(CHUNK).zip(other.par_chunks(CHUNK)).for_each(|(data, other)| {
+                for (x, &y) in data.iter_mut().zip(other) {
+                    *x = op(*x, y);
+                }
+            });
+            return Ok(());
+        }
+    }
+
+    let other = other
+        .broadcast_to(shape)
+        .map_err(|_| format!("Cannot broadcast {:?} into {:?} in place", other.shape(), shape))?;
+    data.par_iter_mut().enumerate().for_each(|(index, x)| *x = op(*x, other.at(index)));
+    Ok(())
+}
+
+/// Divide `data` by its L2 norm unless the norm is (nearly) zero
+pub(super) fn normalize_buffer(data: &mut [f64]) {
+    let norm = stable::stable_norm(data);
+    if norm > stable::epsilon() {
+        data.par_iter_mut().for_each(|x| *x /= norm);
+    }
+}
+
+/// `data AND other`, written into `data`
+pub(crate) fn buffer_and(data: &mut [f64], shape: &[usize], other: &TensorView) -> Result<(), String> {
+    zip_inplace(data, shape, other, |a, b| a * b)
+}
+
+/// `data OR other` (normalized max), written into `data`
+pub(crate) fn buffer_or(data: &mut [f64], shape: &[usize], other: &TensorView) -> Result<(), String> {
+    zip_inplace(data, shape, other, f64::max)?;
+    normalize_buffer(data);
+    Ok(())
+}
+
+/// `data IMPLIES other`, written into `data`
+pub(crate) fn buffer_implies(data: &mut [f64], shape: &[usize], other: &TensorView) -> Result<(), String> {
+    zip_inplace(data, shape, other, |a, b| (1.0 - a).max(b))
+}
+
+/// `data + other`, written into `data`
+pub(crate) fn buffer_add(data: &mut [f64], shape: &[usize], other: &TensorView) -> Result<(), String> {
+    zip_inplace(data, shape, other, |a, b| a + b)
+}
+
+/// `data * other`, written into `data`
+pub(crate) fn buffer_mul(data: &mut [f64], shape: &[usize], other: &TensorView) -> Result<(), String> {
+    zip_inplace(data, shape, other, |a, b| a * b)
+}
+
+/// `NOT data`, written into `data`
+pub(crate) fn buffer_not(data: &mut [f64]) {
+    data.par_iter_mut().for_each(|x| *x = 1.0 - *x);
+}
+
+/// `data * factor`, written into `data`
+pub(crate) fn buffer_scale(data: &mut [f64], factor: f64) {
+    data.par_iter_mut().for_each(|x| *x *= factor);
+}
+
+impl Tensor {
+    /// In-place logical AND (element-wise product)
+    pub fn and_inplace(&mut self, other: &Tensor) -> Result<&mut Self, String> {
+        buffer_and(&mut self.data, &self.shape, &other.view())?;
+        Ok(self)
+    }
+
+    /// In-place logical OR (element-wise max, then normalized)
+    pub fn or_inplace(&mut self, other: &Tensor) -> Result<&mut Self, String> {
+        buffer_or(&mut self.data, &self.shape, &other.view())?;
+        Ok(self)
+    }
+
+    /// In-place logical IMPLIES, `max(1 - self, other)`
+    pub fn implies_inplace(&mut self, other: &Tensor) -> Result<&mut Self, String> {
+        buffer_implies(&mut self.data, &self.shape, &other.view())?;
+        Ok(self)
+    }
+
+    /// In-place logical NOT, `1 - self`
+    pub fn not_inplace(&mut self) -> &mut Self {
+        buffer_not(&mut self.data);
+        self
+    }
+
+    /// In-place element-wise sum
+    pub fn add_inplace(&mut self, other: &Tensor) -> Result<&mut Self, String> {
+        buffer_add(&mut self.data, &self.shape, &other.view())?;
+        Ok(self)
+    }
+
+    /// In-place element-wise difference
+    pub fn sub_inplace(&mut self, other: &Tensor) -> Result<&mut Self, String> {
+        zip_inplace(&mut self.data, &self.shape, &other.view(), |a, b| a - b)?;
+        Ok(self)
+    }
+
+    /// In-place element-wise product
+    pub fn mul_inplace(&mut self, other: &Tensor) -> Result<&mut Self, String> {
+        buffer_mul(&mut self.data, &self.shape, &other.view())?;
+        Ok(self)
+    }
+
+    /// In-place element-wise quotient; division by zero follows IEEE 754
+    pub fn div_inplace(&mut self, other: &Tensor) -> Result<&mut Self, String> {
+        zip_inplace(&mut self.data, &self.shape, &other.view(), |a, b| a / b)?;
+        Ok(self)
+    }
+
+    /// Multiply every element by `factor`
+    pub fn scale_inplace(&mut self, factor: f64) -> &mut Self {
+        buffer_scale(&mut self.data, factor);
+        self
+    }
+
+    /// Apply `f` to every element
+    pub fn map_inplace(&mut self, f: impl Fn(f64) -> f64 + Sync) -> &mut Self {
+        self.data.par_iter_mut().for_each(|x| *x = f(*x));
+        self
+    }
+}
+
+/// In-place `tensor_and`: overwrites `tensor_a` instead of allocating
+#[instrument(skip(tensor_a, tensor_b))]
+pub fn tensor_and_inplace(tensor_a: &mut Tensor, tensor_b: &Tensor) -> Result<(), String> {
+    tensor_a.and_inplace(tensor_b).map(|_| ())
+}
+
+/// In-place `tensor_or`: overwrites `tensor_a` instead of allocating
+#[instrument(skip(tensor_a, tensor_b))]
+pub fn tensor_or_inplace(tensor_a: &mut Tensor, tensor_b: &Tensor) -> Result<(), String> {
+    tensor_a.or_inplace(tensor_b).map(|_| ())
+}
+
+/// In-place `tensor_implies`: overwrites `tensor_a` instead of allocating
+#[instrument(skip(tensor_a, tensor_b))]
+pub fn tensor_implies_inplace(tensor_a: &mut Tensor, tensor_b: &Tensor) -> Result<(), String> {
+    tensor_a.implies_inplace(tensor_b).map(|_| ())
+}
+
+/// In-place `tensor_not`: overwrites `tensor` instead of allocating
+#[instrument(skip(tensor))]
+pub fn tensor_not_inplace(tensor: &mut Tensor) {
+    tensor.not_inplace();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tensor_ops::{tensor_add, tensor_and, tensor_div, tensor_implies, tensor_or, tensor_sub};
+
+    #[test]
+    fn test_inplace_matches_allocating_ops() {
+        let a = Tensor::new(vec![2, 3], vec![0.1, 0.5, 0.9, 0.3, 0.7, 0.2]);
+        let b = Tensor::new(vec![2, 3], vec![0.6, 0.4, 0.8, 0.2, 0.9, 0.5]);
+        let row = Tensor::new(vec![3], vec![0.6, 0.4, 0.8]);
+
+        type Allocating = fn(&Tensor, &Tensor) -> Result<Tensor, String>;
+        type InPlace = fn(&mut Tensor, &Tensor) -> Result<(), String>;
+        let pairs: [(Allocating, InPlace); 3] =
+            [(tensor_and, tensor_and_inplace), (tensor_or, tensor_or_inplace), (tensor_implies, tensor_implies_inplace)];
+        for (op, inplace) in pairs {
+            for other in [&b, &row] {
+                let mut result = a.clone();
+                inplace(&mut result, other).unwrap();
+                let expected = op(&a, other).unwrap();
+                assert!(result.data.iter().zip(&expected.data).all(|(x, y)| (x - y).abs() < 1e-12));
+            }
+        }
+
+        let mut result = a.clone();
+        result.add_inplace(&b).unwrap().sub_inplace(&row).unwrap().div_inplace(&b).unwrap();
+        let expected = tensor_div(&tensor_sub(&tensor_add(&a, &b).unwrap(), &row).unwrap(), &b).unwrap();
+        assert_eq!(result.data, expected.data);
+
+        let mut result = a.clone();
+        tensor_not_inplace(&mut result);
+        result.scale_inplace(2.0).map_inplace(|x| x + 1.0);
+        assert_eq!(result.data, a.data.iter().map(|x| (1.0 - x) * 2.0 + 1.0).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_inplace_keeps_left_shape() {
+        let mut row = Tensor::new(vec![3], vec![1.0, 2.0, 3.0]);
+        let matrix = Tensor::new(vec![2, 3], vec![1.0; 6]);
+        assert!(row.add_inplace(&matrix).is_err());
+        assert_eq!(row.data, vec![1.0, 2.0, 3.0]);
+
+        let mut column = Tensor::new(vec![2, 1], vec![1.0, 2.0]);
+        assert!(column.mul_inplace(&Tensor::new(vec![1, 3], vec![1.0; 3])).is_err());
+        column.mul_inplace(&Tensor::new(vec![], vec![3.0])).unwrap();
+        assert_eq!(column.data, vec![3.0, 6.0]);
+    }
+}
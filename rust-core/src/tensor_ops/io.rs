@@ -0,0 +1,418 @@
+//! Tensor Files - NumPy `.npy`/`.npz` and safetensors serialization
+//!
+//! Tensors are written as little-endian `f64` (`<f8` and `F64`), the type they
+//! are stored in. Reading accepts the common numeric element types of both
+//! formats and widens them to `f64`; Fortran-ordered `.npy` arrays are transposed
+//! into row-major order. An `.npz` file is a zip archive of `.npy` members, and a
+//! safetensors file is a JSON header naming byte ranges of one data section, so
+//! both hold several named tensors.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use half::{bf16, f16};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::Tensor;
+
+const NPY_MAGIC: &[u8] = b"\x93NUMPY";
+
+/// Element type of stored tensor data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElementType {
+    F64,
+    F32,
+    F16,
+    BF16,
+    I64,
+    I32,
+    I16,
+    I8,
+    U8,
+    Bool,
+}
+
+impl ElementType {
+    /// Bytes per element
+    pub fn size(&self) -> usize {
+        match self {
+            Self::F64 | Self::I64 => 8,
+            Self::F32 | Self::I32 => 4,
+            Self::F16 | Self::BF16 | Self::I16 => 2,
+            Self::I8 | Self::U8 | Self::Bool => 1,
+        }
+    }
+
+    /// Widen little-endian elements to `f64`
+    pub fn decode(&self, bytes: &[u8]) -> Vec<f64> {
+        let chunks = bytes.chunks_exact(self.size());
+        match self {
+            Self::F64 => chunks.map(|c| f64::from_le_bytes(c.try_into().expect("8 bytes"))).collect(),
+            Self::F32 => chunks.map(|c| f32::from_le_bytes(c.try_into().expect("4 bytes")) as f64).collect(),
+            Self::F16 => chunks.map(|c| f16::from_le_bytes([c[0], c[1]]).to_f64()).collect(),
+            Self::BF16 => chunks.map(|c| bf16::from_le_bytes([c[0], c[1]]).to_f64()).collect(),
+            Self::I64 => chunks.map(|c| i64::from_le_bytes(c.try_into().expect("8 bytes")) as f64).collect(),
+            Self::I32 => chunks.map(|c| i32::from_le_bytes(c.try_into().expect("4 bytes")) as f64).collect(),
+            Self::I16 => chunks.map(|c| i16::from_le_bytes([c[0], c[1]]) as f64).collect(),
+            Self::I8 => chunks.map(|c| c[0] as i8 as f64).collect(),
+            Self::U8 | Self::Bool => chunks.map(|c| c[0] as f64).collect(),
+        }
+    }
+
+    /// Element type of a NumPy type descriptor such as `<f8`
+    fn from_npy_descr(descr: &str) -> Result<Self, String> {
+        let (order, code) = descr.split_at(descr.len().min(1));
+        if order == ">" && !matches!(code, "i1" | "u1" | "b1") {
+            return Err(format!("Big-endian npy data ({}) is not supported", descr));
+        }
+        match code {
+            "f8" => Ok(Self::F64),
+            "f4" => Ok(Self::F32),
+            "f2" => Ok(Self::F16),
+            "i8" => Ok(Self::I64),
+            "i4" => Ok(Self::I32),
+            "i2" => Ok(Self::I16),
+            "i1" => Ok(Self::I8),
+            "u1" => Ok(Self::U8),
+            "b1" => Ok(Self::Bool),
+            _ => Err(format!("Unsupported npy dtype {}", descr)),
+        }
+    }
+
+    /// Element type of a safetensors dtype name such as `F32`
+    fn from_safetensors(dtype: &str) -> Result<Self, String> {
+        match dtype {
+            "F64" => Ok(Self::F64),
+            "F32" => Ok(Self::F32),
+            "F16" => Ok(Self::F16),
+            "BF16" => Ok(Self::BF16),
+            "I64" => Ok(Self::I64),
+            "I32" => Ok(Self::I32),
+            "I16" => Ok(Self::I16),
+            "I8" => Ok(Self::I8),
+            "U8" => Ok(Self::U8),
+            "BOOL" => Ok(Self::Bool),
+            _ => Err(format!("Unsupported safetensors dtype {}", dtype)),
+        }
+    }
+}
+
+/// Parsed `.npy` header
+#[derive(Debug, Clone, PartialEq)]
+pub struct NpyHeader {
+    pub dtype: ElementType,
+    pub shape: Vec<usize>,
+    pub fortran_order: bool,
+    /// Byte offset of the data after the header
+    pub data_offset: usize,
+}
+
+impl NpyHeader {
+    /// Parse the header at the start of an `.npy` buffer
+    pub fn parse(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() < 10 || &bytes[..6] != NPY_MAGIC {
+            return Err("Not an npy file".to_string());
+        }
+        let (length, start) = match bytes[6] {
+            1 => (u16::from_le_bytes([bytes[8], bytes[9]]) as usize, 10),
+            2 | 3 if bytes.len() >= 12 => (u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]) as usize, 12),
+            version => return Err(format!("Unsupported npy version {}", version)),
+        };
+        let header = bytes.get(start..start + length).ok_or("Truncated npy header")?;
+        let header = std::str::from_utf8(header).map_err(|_| "npy header is not text".to_string())?;
+
+        let descr = dict_value(header, "descr")?;
+        let descr = descr.trim_matches(|c| c == '\'' || c == '"');
+        let fortran_order = match dict_value(header, "fortran_order")? {
+            "True" => true,
+            "False" => false,
+            other => return Err(format!("Invalid fortran_order {}", other)),
+        };
+        let shape = dict_value(header, "shape")?
+            .trim_matches(|c| c == '(' || c == ')')
+            .split(',')
+            .map(str::trim)
+            .filter(|dim| !dim.is_empty())
+            .map(|dim| dim.parse::<usize>().map_err(|_| format!("Invalid npy dimension {}", dim)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self { dtype: ElementType::from_npy_descr(descr)?, shape, fortran_order, data_offset: start + length })
+    }
+
+    /// Bytes of element data the header describes
+    pub fn data_len(&self) -> Result<usize, String> {
+        self.shape.iter()
+            .try_fold(self.dtype.size(), |len, &dim| len.checked_mul(dim))
+            .ok_or_else(|| format!("npy shape {:?} is too large", self.shape))
+    }
+}
+
+/// Text of `key`'s value in a Python dict literal, up to the next top-level comma
+fn dict_value<'a>(header: &'a str, key: &str) -> Result<&'a str, String> {
+    let start = header.find(&format!("'{}':", key)).ok_or_else(|| format!("npy header lacks '{}'", key))?;
+    let rest = header[start + key.len() + 3..].trim_start();
+    let end = if rest.starts_with('(') {
+        rest.find(')').map(|end| end + 1)
+    } else {
+        rest.find([',', '}'])
+    };
+    Ok(rest[..end.ok_or("Malformed npy header")?].trim())
+}
+
+/// Build a row-major tensor from decoded elements
+fn assemble(shape: Vec<usize>, data: Vec<f64>, fortran_order: bool) -> Result<Tensor, String> {
+    if !fortran_order || shape.len() < 2 {
+        return Ok(Tensor::new(shape, data));
+    }
+    // Column-major data is the row-major layout of the reversed shape
+    let reversed: Vec<usize> = shape.iter().rev().copied().collect();
+    let axes: Vec<usize> = (0..shape.len()).rev().collect();
+    Tensor::new(reversed, data).permute(&axes)
+}
+
+impl Tensor {
+    /// Encode as an `.npy` (version 1.0) buffer of little-endian `f64`
+    pub fn to_npy(&self) -> Vec<u8> {
+        let shape = match self.shape.as_slice() {
+            [dim] => format!("({},)", dim),
+            dims => format!("({})", dims.iter().map(usize::to_string).collect::<Vec<_>>().join(", ")),
+        };
+        let mut header = format!("{{'descr': '<f8', 'fortran_order': False, 'shape': {}, }}", shape);
+        // Pad with spaces so the data starts on a 64-byte boundary
+        let unpadded = NPY_MAGIC.len() + 4 + header.len() + 1;
+        header.push_str(&" ".repeat((64 - unpadded % 64) % 64));
+        header.push('\n');
+
+        let mut bytes = Vec::with_capacity(NPY_MAGIC.len() + 4 + header.len() + self.data.len() * 8);
+        bytes.extend_from_slice(NPY_MAGIC);
+        bytes.extend_from_slice(&[1, 0]);
+        bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(header.as_bytes());
+        for value in &self.data {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Decode an `.npy` buffer
+    pub fn from_npy(bytes: &[u8]) -> Result<Tensor, String> {
+        let header = NpyHeader::parse(bytes)?;
+        let data = bytes
+            .get(header.data_offset..header.data_offset + header.data_len()?)
+            .ok_or("Truncated npy data")?;
+        assemble(header.shape, header.dtype.decode(data), header.fortran_order)
+    }
+
+    /// Write to an `.npy` file
+    pub fn save_npy<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        std::fs::write(path, self.to_npy()).map_err(|e| format!("Failed to write npy file: {}", e))
+    }
+
+    /// Read an `.npy` file
+    pub fn load_npy<P: AsRef<Path>>(path: P) -> Result<Tensor, String> {
+        let bytes = std::fs::read(path).map_err(|e| format!("Failed to read npy file: {}", e))?;
+        Self::from_npy(&bytes)
+    }
+}
+
+/// Write named tensors to an `.npz` archive, one `<name>.npy` member each
+pub fn save_npz<P: AsRef<Path>>(path: P, tensors: &BTreeMap<String, Tensor>) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| format!("Failed to create npz file: {}", e))?;
+    let mut archive = zip::ZipWriter::new(BufWriter::new(file));
+    let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    for (name, tensor) in tensors {
+        archive.start_file(format!("{}.npy", name), options).map_err(|e| format!("Failed to write npz member {}: {}", name, e))?;
+        archive.write_all(&tensor.to_npy()).map_err(|e| format!("Failed to write npz member {}: {}", name, e))?;
+    }
+    archive.finish().map_err(|e| format!("Failed to finish npz file: {}", e))?;
+    Ok(())
+}
+
+/// Read every tensor of an `.npz` archive, keyed by member name without `.npy`
+pub fn load_npz<P: AsRef<Path>>(path: P) -> Result<BTreeMap<String, Tensor>, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open npz file: {}", e))?;
+    let mut archive = zip::ZipArchive::new(BufReader::new(file)).map_err(|e| format!("Invalid npz file: {}", e))?;
+    let mut tensors = BTreeMap::new();
+    for index in 0..archive.len() {
+        let mut member = archive.by_index(index).map_err(|e| format!("Invalid npz member: {}", e))?;
+        let name = member.name().trim_end_matches(".npy").to_string();
+        let mut bytes = Vec::with_capacity(member.size() as usize);
+        member.read_to_end(&mut bytes).map_err(|e| format!("Failed to read npz member {}: {}", name, e))?;
+        let tensor = Tensor::from_npy(&bytes).map_err(|e| format!("npz member {}: {}", name, e))?;
+        tensors.insert(name, tensor);
+    }
+    Ok(tensors)
+}
+
+/// Header entry of one tensor in a safetensors file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SafetensorsEntry {
+    pub dtype: String,
+    pub shape: Vec<usize>,
+    /// Start and end of the tensor's bytes within the data section
+    pub data_offsets: [usize; 2],
+}
+
+/// Parsed safetensors header
+#[derive(Debug, Clone, PartialEq)]
+pub struct SafetensorsHeader {
+    pub tensors: BTreeMap<String, SafetensorsEntry>,
+    pub metadata: BTreeMap<String, String>,
+    /// Byte offset of the data section after the header
+    pub data_offset: usize,
+}
+
+impl SafetensorsHeader {
+    /// Parse the header at the start of a safetensors buffer
+    pub fn parse(bytes: &[u8]) -> Result<Self, String> {
+        let length = bytes.get(..8).ok_or("Truncated safetensors header")?;
+        let length = u64::from_le_bytes(length.try_into().expect("8 bytes")) as usize;
+        let header = bytes.get(8..8usize.saturating_add(length)).ok_or("Truncated safetensors header")?;
+        let mut header: BTreeMap<String, Value> =
+            serde_json::from_slice(header).map_err(|e| format!("Invalid safetensors header: {}", e))?;
+
+        let metadata = match header.remove("__metadata__") {
+            Some(metadata) => serde_json::from_value(metadata).map_err(|e| format!("Invalid safetensors metadata: {}", e))?,
+            None => BTreeMap::new(),
+        };
+        let tensors = header
+            .into_iter()
+            .map(|(name, entry)| {
+                let entry: SafetensorsEntry =
+                    serde_json::from_value(entry).map_err(|e| format!("Invalid safetensors entry {}: {}", name, e))?;
+                Ok((name, entry))
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Self { tensors, metadata, data_offset: 8 + length })
+    }
+
+    /// Element type and checked byte range of the entry `name` within the data section
+    pub fn locate(&self, name: &str) -> Result<(ElementType, std::ops::Range<usize>), String> {
+        let entry = self.tensors.get(name).ok_or_else(|| format!("No tensor named {}", name))?;
+        let dtype = ElementType::from_safetensors(&entry.dtype)?;
+        let [start, end] = entry.data_offsets;
+        let expected = entry.shape.iter().try_fold(dtype.size(), |len, &dim| len.checked_mul(dim));
+        if end < start || expected != Some(end - start) {
+            return Err(format!("Tensor {} has {:?} bytes for shape {:?}", name, start..end, entry.shape));
+        }
+        Ok((dtype, start..end))
+    }
+}
+
+/// Encode named tensors, with optional string metadata, as a safetensors buffer
+pub fn to_safetensors(tensors: &BTreeMap<String, Tensor>, metadata: &BTreeMap<String, String>) -> Result<Vec<u8>, String> {
+    let mut header = serde_json::Map::new();
+    if !metadata.is_empty() {
+        header.insert("__metadata__".to_string(), serde_json::to_value(metadata).map_err(|e| e.to_string())?);
+    }
+    let mut offset = 0;
+    for (name, tensor) in tensors {
+        let end = offset + tensor.data.len() * 8;
+        let entry = SafetensorsEntry { dtype: "F64".to_string(), shape: tensor.shape.clone(), data_offsets: [offset, end] };
+        header.insert(name.clone(), serde_json::to_value(entry).map_err(|e| e.to_string())?);
+        offset = end;
+    }
+    let mut header = serde_json::to_vec(&header).map_err(|e| e.to_string())?;
+    // Pad with spaces so the data section is 8-byte aligned
+    header.resize(header.len().next_multiple_of(8), b' ');
+
+    let mut bytes = Vec::with_capacity(8 + header.len() + offset);
+    bytes.extend_from_slice(&(header.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(&header);
+    for tensor in tensors.values() {
+        for value in &tensor.data {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+    Ok(bytes)
+}
+
+/// Decode every tensor of a safetensors buffer
+pub fn from_safetensors(bytes: &[u8]) -> Result<BTreeMap<String, Tensor>, String> {
+    let header = SafetensorsHeader::parse(bytes)?;
+    let data = &bytes[header.data_offset..];
+    header.tensors.iter()
+        .map(|(name, entry)| {
+            let (dtype, range) = header.locate(name)?;
+            let raw = data.get(range).ok_or_else(|| format!("Tensor {} extends past the end of the file", name))?;
+            Ok((name.clone(), Tensor::new(entry.shape.clone(), dtype.decode(raw))))
+        })
+        .collect()
+}
+
+/// Write named tensors to a safetensors file
+pub fn save_safetensors<P: AsRef<Path>>(path: P, tensors: &BTreeMap<String, Tensor>, metadata: &BTreeMap<String, String>) -> Result<(), String> {
+    std::fs::write(path, to_safetensors(tensors, metadata)?).map_err(|e| format!("Failed to write safetensors file: {}", e))
+}
+
+/// Read every tensor of a safetensors file
+pub fn load_safetensors<P: AsRef<Path>>(path: P) -> Result<BTreeMap<String, Tensor>, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("Failed to read safetensors file: {}", e))?;
+    from_safetensors(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_npy_round_trip_and_foreign_layouts() {
+        for tensor in [
+            Tensor::new(vec![2, 3], vec![1.0, -2.5, 3.0, 4.0, 5.0, 6.0]),
+            Tensor::new(vec![4], vec![0.1, 0.2, 0.3, 0.4]),
+            Tensor::new(vec![], vec![7.0]),
+        ] {
+            let bytes = tensor.to_npy();
+            assert_eq!(NpyHeader::parse(&bytes).unwrap().data_offset % 64, 0);
+            let decoded = Tensor::from_npy(&bytes).unwrap();
+            assert_eq!((decoded.shape, decoded.data), (tensor.shape, tensor.data));
+        }
+
+        // What numpy.save writes for np.array([[1, 2, 3], [4, 5, 6]], dtype='<i4', order='F')
+        let header = "{'descr': '<i4', 'fortran_order': True, 'shape': (2, 3), }";
+        let mut bytes = NPY_MAGIC.to_vec();
+        bytes.extend_from_slice(&[1, 0]);
+        bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(header.as_bytes());
+        for value in [1i32, 4, 2, 5, 3, 6] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        let decoded = Tensor::from_npy(&bytes).unwrap();
+        assert_eq!(decoded.shape, vec![2, 3]);
+        assert_eq!(decoded.data, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+
+        assert!(Tensor::from_npy(&bytes[..bytes.len() - 1]).is_err());
+        assert!(Tensor::from_npy(b"not numpy").is_err());
+    }
+
+    #[test]
+    fn test_archives_hold_named_tensors() {
+        let tensors = BTreeMap::from([
+            ("weights".to_string(), Tensor::new(vec![2, 2], vec![1.0, 2.0, 3.0, 4.0])),
+            ("bias".to_string(), Tensor::new(vec![2], vec![0.5, -0.5])),
+        ]);
+        let metadata = BTreeMap::from([("format".to_string(), "pt".to_string())]);
+
+        let bytes = to_safetensors(&tensors, &metadata).unwrap();
+        let header = SafetensorsHeader::parse(&bytes).unwrap();
+        assert_eq!(header.data_offset % 8, 0);
+        assert_eq!(header.metadata, metadata);
+        let decoded = from_safetensors(&bytes).unwrap();
+        assert_eq!(decoded.keys().collect::<Vec<_>>(), vec!["bias", "weights"]);
+        assert_eq!(decoded["weights"].data, tensors["weights"].data);
+        assert!(from_safetensors(&bytes[..bytes.len() - 8]).is_err());
+
+        let dir = std::env::temp_dir().join(format!("tensor_io_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        save_npz(dir.join("tensors.npz"), &tensors).unwrap();
+        let loaded = load_npz(dir.join("tensors.npz")).unwrap();
+        assert_eq!(loaded["bias"].data, vec![0.5, -0.5]);
+        assert_eq!(loaded["weights"].shape, vec![2, 2]);
+        save_safetensors(dir.join("tensors.safetensors"), &tensors, &BTreeMap::new()).unwrap();
+        assert_eq!(load_safetensors(dir.join("tensors.safetensors")).unwrap()["bias"].shape, vec![2]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,209 @@
+//! Kernels - Kernel functions for kernel machines
+//!
+//! A `Kernel` maps a pair of equally sized tensors to a similarity score.
+//! `KernelFunction` covers the built-in families with their parameters, and a
+//! `KernelRegistry` resolves kernels by name, which is how `apply_kernel` and the
+//! FFI select them. `kernel_matrix` evaluates one kernel over every pair of a batch,
+//! computing each row of the Gram matrix in parallel.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use super::{simd, stable, Tensor};
+
+/// Symmetric similarity between two equally sized buffers
+pub trait Kernel: Send + Sync {
+    /// Kernel value `K(a, b)`
+    fn evaluate(&self, a: &[f64], b: &[f64]) -> f64;
+
+    /// Kernel name for logging and statistics
+    fn name(&self) -> &'static str;
+}
+
+/// Built-in kernel families
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum KernelFunction {
+    /// `x · y`
+    Linear,
+    /// `(x · y + c)^d`
+    Polynomial { c: f64, d: f64 },
+    /// `exp(-gamma ||x - y||²)`
+    Rbf { gamma: f64 },
+    /// `exp(-gamma ||x - y||₁)`
+    Laplacian { gamma: f64 },
+    /// `tanh(alpha x · y + c)`
+    Sigmoid { alpha: f64, c: f64 },
+    /// Cosine of the angle between `x` and `y`, 0 when either is near zero
+    Cosine,
+}
+
+impl Kernel for KernelFunction {
+    fn evaluate(&self, a: &[f64], b: &[f64]) -> f64 {
+        let distance = |f: fn(f64) -> f64| -> f64 { a.par_iter().zip(b.par_iter()).map(|(x, y)| f(x - y)).sum() };
+        match *self {
+            Self::Linear => simd::par_dot(a, b),
+            Self::Polynomial { c, d } => (simd::par_dot(a, b) + c).powf(d),
+            Self::Rbf { gamma } => (-gamma * distance(|diff| diff * diff)).exp(),
+            Self::Laplacian { gamma } => (-gamma * distance(f64::abs)).exp(),
+            Self::Sigmoid { alpha, c } => (alpha * simd::par_dot(a, b) + c).tanh(),
+            Self::Cosine => {
+                let norm_a = simd::par_dot(a, a).sqrt();
+                let norm_b = simd::par_dot(b, b).sqrt();
+                if norm_a < stable::epsilon() || norm_b < stable::epsilon() {
+                    return 0.0;
+                }
+                simd::par_dot(a, b) / (norm_a * norm_b)
+            }
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Linear => "linear",
+            Self::Polynomial { .. } => "polynomial",
+            Self::Rbf { .. } => "rbf",
+            Self::Laplacian { .. } => "laplacian",
+            Self::Sigmoid { .. } => "sigmoid",
+            Self::Cosine => "cosine",
+        }
+    }
+}
+
+/// Kernels by name
+pub struct KernelRegistry {
+    kernels: HashMap<String, Box<dyn Kernel>>,
+}
+
+impl KernelRegistry {
+    /// Registry of the built-in kernels with their default parameters
+    ///
+    /// `polynomial` is `(x · y + 1)²` and `rbf` uses `gamma = 1`, as `apply_kernel`
+    /// always has.
+    pub fn new() -> Self {
+        let mut registry = Self::empty();
+        for kernel in [
+            KernelFunction::Linear,
+            KernelFunction::Polynomial { c: 1.0, d: 2.0 },
+            KernelFunction::Rbf { gamma: 1.0 },
+            KernelFunction::Laplacian { gamma: 1.0 },
+            KernelFunction::Sigmoid { alpha: 1.0, c: 0.0 },
+            KernelFunction::Cosine,
+        ] {
+            registry.register(kernel.name(), kernel);
+        }
+        registry
+    }
+
+    /// Registry without any kernels
+    pub fn empty() -> Self {
+        Self { kernels: HashMap::new() }
+    }
+
+    /// Shared registry of the built-in kernels
+    pub fn builtin() -> &'static KernelRegistry {
+        static BUILTIN: OnceLock<KernelRegistry> = OnceLock::new();
+        BUILTIN.get_or_init(KernelRegistry::new)
+    }
+
+    /// Register `kernel` under `name`, replacing any kernel already there
+    pub fn register(&mut self, name: &str, kernel: impl Kernel + 'static) {
+        self.kernels.insert(name.to_string(), Box::new(kernel));
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Kernel> {
+        self.kernels.get(name).map(|kernel| kernel.as_ref())
+    }
+
+    /// Registered names, sorted
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.kernels.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Evaluate the kernel registered as `name` on two tensors
+    pub fn apply(&self, name: &str, tensor_a: &Tensor, tensor_b: &Tensor) -> Result<f64, String> {
+        let kernel = self.get(name).ok_or_else(|| format!("Unknown kernel type: {}", name))?;
+        if tensor_a.data.len() != tensor_b.data.len() {
+            return Err(format!("Kernel operands differ in size: {} vs {}", tensor_a.data.len(), tensor_b.data.len()));
+        }
+        Ok(kernel.evaluate(&tensor_a.data, &tensor_b.data))
+    }
+}
+
+impl Default for KernelRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Gram matrix `K[i, j] = kernel(tensors[i], tensors[j])` of a batch of equally
+/// sized tensors
+#[instrument(skip(kernel, tensors), fields(kernel = kernel.name()))]
+pub fn kernel_matrix(kernel: &dyn Kernel, tensors: &[Tensor]) -> Result<Tensor, String> {
+    let size = tensors.first().map_or(0, |t| t.data.len());
+    if let Some(tensor) = tensors.iter().find(|t| t.data.len() != size) {
+        return Err(format!("Kernel operands differ in size: {} vs {}", size, tensor.data.len()));
+    }
+
+    // Kernels are symmetric, so each row only evaluates the upper triangle
+    let n = tensors.len();
+    let rows: Vec<Vec<f64>> = (0..n)
+        .into_par_iter()
+        .map(|i| (i..n).map(|j| kernel.evaluate(&tensors[i].data, &tensors[j].data)).collect())
+        .collect();
+
+    let mut data = vec![0.0; n * n];
+    for (i, row) in rows.into_iter().enumerate() {
+        for (offset, value) in row.into_iter().enumerate() {
+            let j = i + offset;
+            data[i * n + j] = value;
+            data[j * n + i] = value;
+        }
+    }
+    Ok(Tensor::new(vec![n, n], data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kernel_functions() {
+        let (a, b) = ([1.0, 2.0, 0.0], [0.0, 1.0, 2.0]);
+        assert_eq!(KernelFunction::Linear.evaluate(&a, &b), 2.0);
+        assert_eq!(KernelFunction::Polynomial { c: 0.5, d: 3.0 }.evaluate(&a, &b), 2.5f64.powi(3));
+        assert!((KernelFunction::Rbf { gamma: 0.1 }.evaluate(&a, &b) - (-0.6f64).exp()).abs() < 1e-12);
+        assert!((KernelFunction::Laplacian { gamma: 0.5 }.evaluate(&a, &b) - (-2.0f64).exp()).abs() < 1e-12);
+        assert!((KernelFunction::Sigmoid { alpha: 0.5, c: -1.0 }.evaluate(&a, &b)).abs() < 1e-12);
+        assert!((KernelFunction::Cosine.evaluate(&a, &b) - 0.4).abs() < 1e-12);
+        assert_eq!(KernelFunction::Cosine.evaluate(&a, &[0.0; 3]), 0.0);
+    }
+
+    #[test]
+    fn test_registry_and_kernel_matrix() {
+        let a = Tensor::new(vec![2], vec![1.0, 0.0]);
+        let b = Tensor::new(vec![2], vec![0.0, 2.0]);
+        let registry = KernelRegistry::builtin();
+        assert_eq!(registry.names(), vec!["cosine", "laplacian", "linear", "polynomial", "rbf", "sigmoid"]);
+        assert_eq!(registry.apply("polynomial", &a, &b).unwrap(), 1.0);
+        assert!(registry.apply("unknown", &a, &b).is_err());
+        assert!(registry.apply("linear", &a, &Tensor::new(vec![1], vec![1.0])).is_err());
+
+        let mut custom = KernelRegistry::empty();
+        custom.register("wide_rbf", KernelFunction::Rbf { gamma: 0.25 });
+        assert!((custom.apply("wide_rbf", &a, &b).unwrap() - (-1.25f64).exp()).abs() < 1e-12);
+        assert!(custom.get("rbf").is_none());
+
+        let c = Tensor::new(vec![2], vec![1.0, 1.0]);
+        let gram = kernel_matrix(&KernelFunction::Linear, &[a.clone(), b.clone(), c]).unwrap();
+        assert_eq!(gram.shape, vec![3, 3]);
+        assert_eq!(gram.data, vec![1.0, 0.0, 1.0, 0.0, 4.0, 2.0, 1.0, 2.0, 2.0]);
+        assert_eq!(kernel_matrix(&KernelFunction::Cosine, &[]).unwrap().shape, vec![0, 0]);
+        assert!(kernel_matrix(&KernelFunction::Linear, &[a, Tensor::new(vec![3], vec![0.0; 3])]).is_err());
+    }
+}
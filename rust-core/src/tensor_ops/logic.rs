@@ -0,0 +1,148 @@
+//! Fuzzy Logic - Connectives and quantifiers under configurable semantics
+//!
+//! Truth values live in `[0, 1]` and negation is always `1 - x`. The semantics pick
+//! the t-norm used for conjunction, its dual t-conorm for disjunction and the
+//! residuated implication. Quantifiers fold the t-norm (`forall`) or t-conorm
+//! (`exists`) along an axis, so first-order formulas over relation tensors reduce
+//! to element-wise connectives followed by quantifier reductions.
+
+use tracing::instrument;
+
+use super::{broadcast_zip, Tensor};
+
+/// Family of fuzzy connectives
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogicSemantics {
+    /// `a * b`, `a + b - a * b`, Goguen implication; matches `tensor_and`
+    #[default]
+    Product,
+    /// `min`, `max`, Gödel implication
+    Godel,
+    /// `max(0, a + b - 1)`, `min(1, a + b)`, Łukasiewicz implication
+    Lukasiewicz,
+}
+
+impl LogicSemantics {
+    /// Conjunction (t-norm)
+    pub fn and(&self, a: f64, b: f64) -> f64 {
+        match self {
+            Self::Product => a * b,
+            Self::Godel => a.min(b),
+            Self::Lukasiewicz => (a + b - 1.0).max(0.0),
+        }
+    }
+
+    /// Disjunction (t-conorm)
+    pub fn or(&self, a: f64, b: f64) -> f64 {
+        match self {
+            Self::Product => a + b - a * b,
+            Self::Godel => a.max(b),
+            Self::Lukasiewicz => (a + b).min(1.0),
+        }
+    }
+
+    /// Negation
+    pub fn not(&self, a: f64) -> f64 {
+        1.0 - a
+    }
+
+    /// Implication (residuum of the t-norm)
+    pub fn implies(&self, a: f64, b: f64) -> f64 {
+        match self {
+            _ if a <= b => 1.0,
+            Self::Product => b / a,
+            Self::Godel => b,
+            Self::Lukasiewicz => 1.0 - a + b,
+        }
+    }
+
+    /// Exclusive or: `(a ∨ b) ∧ ¬(a ∧ b)`
+    pub fn xor(&self, a: f64, b: f64) -> f64 {
+        self.and(self.or(a, b), self.not(self.and(a, b)))
+    }
+
+    /// Biconditional: `(a → b) ∧ (b → a)`
+    pub fn iff(&self, a: f64, b: f64) -> f64 {
+        self.and(self.implies(a, b), self.implies(b, a))
+    }
+}
+
+/// Tensor XOR operation (exclusive disjunction), broadcasting mismatched shapes
+#[instrument(skip(tensor_a, tensor_b))]
+pub fn tensor_xor(tensor_a: &Tensor, tensor_b: &Tensor, semantics: LogicSemantics) -> Result<Tensor, String> {
+    broadcast_zip(tensor_a, tensor_b, |a, b| semantics.xor(a, b))
+}
+
+/// Tensor NAND operation (negated conjunction), broadcasting mismatched shapes
+#[instrument(skip(tensor_a, tensor_b))]
+pub fn tensor_nand(tensor_a: &Tensor, tensor_b: &Tensor, semantics: LogicSemantics) -> Result<Tensor, String> {
+    broadcast_zip(tensor_a, tensor_b, |a, b| semantics.not(semantics.and(a, b)))
+}
+
+/// Tensor NOR operation (negated disjunction), broadcasting mismatched shapes
+#[instrument(skip(tensor_a, tensor_b))]
+pub fn tensor_nor(tensor_a: &Tensor, tensor_b: &Tensor, semantics: LogicSemantics) -> Result<Tensor, String> {
+    broadcast_zip(tensor_a, tensor_b, |a, b| semantics.not(semantics.or(a, b)))
+}
+
+/// Tensor IFF operation (biconditional), broadcasting mismatched shapes
+#[instrument(skip(tensor_a, tensor_b))]
+pub fn tensor_iff(tensor_a: &Tensor, tensor_b: &Tensor, semantics: LogicSemantics) -> Result<Tensor, String> {
+    broadcast_zip(tensor_a, tensor_b, |a, b| semantics.iff(a, b))
+}
+
+impl Tensor {
+    /// Universal quantifier: conjunction of every element along `axis`
+    pub fn forall(&self, axis: usize, keepdim: bool, semantics: LogicSemantics) -> Result<Tensor, String> {
+        self.fold_axes(&[axis], keepdim, 1.0, |a, b| semantics.and(a, b))
+    }
+
+    /// Existential quantifier: disjunction of every element along `axis`
+    pub fn exists(&self, axis: usize, keepdim: bool, semantics: LogicSemantics) -> Result<Tensor, String> {
+        self.fold_axes(&[axis], keepdim, 0.0, |a, b| semantics.or(a, b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [LogicSemantics; 3] = [LogicSemantics::Product, LogicSemantics::Godel, LogicSemantics::Lukasiewicz];
+
+    #[test]
+    fn test_connectives_agree_on_crisp_values() {
+        let a = Tensor::new(vec![4], vec![0.0, 0.0, 1.0, 1.0]);
+        let b = Tensor::new(vec![4], vec![0.0, 1.0, 0.0, 1.0]);
+        for semantics in ALL {
+            assert_eq!(tensor_xor(&a, &b, semantics).unwrap().data, vec![0.0, 1.0, 1.0, 0.0]);
+            assert_eq!(tensor_nand(&a, &b, semantics).unwrap().data, vec![1.0, 1.0, 1.0, 0.0]);
+            assert_eq!(tensor_nor(&a, &b, semantics).unwrap().data, vec![1.0, 0.0, 0.0, 0.0]);
+            assert_eq!(tensor_iff(&a, &b, semantics).unwrap().data, vec![1.0, 0.0, 0.0, 1.0]);
+        }
+    }
+
+    #[test]
+    fn test_semantics_differ_on_fuzzy_values() {
+        let (a, b) = (0.6, 0.3);
+        assert!((LogicSemantics::Product.and(a, b) - 0.18).abs() < 1e-12);
+        assert_eq!(LogicSemantics::Godel.and(a, b), 0.3);
+        assert!((LogicSemantics::Lukasiewicz.and(a, b)).abs() < 1e-12);
+        assert!((LogicSemantics::Product.implies(a, b) - 0.5).abs() < 1e-12);
+        assert_eq!(LogicSemantics::Godel.implies(a, b), 0.3);
+        assert!((LogicSemantics::Lukasiewicz.implies(a, b) - 0.7).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_quantifiers() {
+        // likes[person, food]
+        let likes = Tensor::new(vec![2, 3], vec![1.0, 1.0, 1.0, 0.0, 1.0, 0.0]);
+        for semantics in ALL {
+            assert_eq!(likes.forall(1, false, semantics).unwrap().data, vec![1.0, 0.0]);
+            assert_eq!(likes.exists(0, false, semantics).unwrap().data, vec![1.0, 1.0, 1.0]);
+        }
+        let fuzzy = Tensor::new(vec![2], vec![0.5, 0.5]);
+        assert_eq!(fuzzy.forall(0, true, LogicSemantics::Godel).unwrap().data, vec![0.5]);
+        assert_eq!(fuzzy.exists(0, false, LogicSemantics::Product).unwrap().data, vec![0.75]);
+        assert!(fuzzy.forall(1, false, LogicSemantics::Godel).is_err());
+    }
+}
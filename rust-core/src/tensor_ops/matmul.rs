@@ -0,0 +1,120 @@
+//! Matrix Multiplication - Dense and batched matrix products
+//!
+//! Products go through ndarray's `dot`, which uses the cache-blocked kernels of
+//! `matrixmultiply` by default and a system BLAS when the crate is built with the
+//! `blas` feature (which in turn needs a `blas-src` provider linked into the final
+//! binary). Both are far faster than the generic contraction loop in
+//! `einstein_summation` for large matrices.
+
+use ndarray::ArrayView2;
+use rayon::prelude::*;
+use tracing::instrument;
+
+use super::{broadcast_shape, Tensor};
+
+/// Product of an `m × k` block of `a` and a `k × n` block of `b`, row-major
+fn block_dot(a: &[f64], b: &[f64], m: usize, k: usize, n: usize) -> Vec<f64> {
+    let a = ArrayView2::from_shape((m, k), a).expect("block matches its shape");
+    let b = ArrayView2::from_shape((k, n), b).expect("block matches its shape");
+    let product = a.dot(&b);
+    if product.is_standard_layout() {
+        product.into_raw_vec()
+    } else {
+        product.iter().copied().collect()
+    }
+}
+
+/// Matrix product of rank-2 tensors: `[m, k] × [k, n] -> [m, n]`
+#[instrument(skip(tensor_a, tensor_b))]
+pub fn matmul(tensor_a: &Tensor, tensor_b: &Tensor) -> Result<Tensor, String> {
+    let (&[m, k], &[k_b, n]) = (tensor_a.shape.as_slice(), tensor_b.shape.as_slice()) else {
+        return Err(format!("matmul needs rank-2 tensors, got {:?} and {:?}", tensor_a.shape, tensor_b.shape));
+    };
+    if k != k_b {
+        return Err(format!("Cannot multiply {:?} by {:?}", tensor_a.shape, tensor_b.shape));
+    }
+    Ok(Tensor::new(vec![m, n], block_dot(&tensor_a.data, &tensor_b.data, m, k, n)))
+}
+
+/// Matrix products over leading batch axes: `[.., m, k] × [.., k, n] -> [.., m, n]`
+///
+/// Batch axes broadcast against each other, so a rank-2 operand multiplies every
+/// matrix of the other. Batches are multiplied in parallel.
+#[instrument(skip(tensor_a, tensor_b))]
+pub fn batched_matmul(tensor_a: &Tensor, tensor_b: &Tensor) -> Result<Tensor, String> {
+    if tensor_a.rank < 2 || tensor_b.rank < 2 {
+        return Err(format!("batched_matmul needs rank ≥ 2, got {:?} and {:?}", tensor_a.shape, tensor_b.shape));
+    }
+    let (batch_a, matrix_a) = tensor_a.shape.split_at(tensor_a.rank - 2);
+    let (batch_b, matrix_b) = tensor_b.shape.split_at(tensor_b.rank - 2);
+    let (m, k, n) = (matrix_a[0], matrix_a[1], matrix_b[1]);
+    if k != matrix_b[0] {
+        return Err(format!("Cannot multiply {:?} by {:?}", tensor_a.shape, tensor_b.shape));
+    }
+
+    let batch = broadcast_shape(batch_a, batch_b)?;
+    let batches: usize = batch.iter().product();
+    // Offset of a broadcast batch index into an operand's own batches
+    let block = |shape: &[usize], mut index: usize| {
+        let padding = batch.len() - shape.len();
+        let (mut offset, mut stride) = (0, 1);
+        for axis in (0..batch.len()).rev() {
+            let coordinate = index % batch[axis];
+            index /= batch[axis];
+            if axis >= padding {
+                let dim = shape[axis - padding];
+                offset += (coordinate % dim) * stride;
+                stride *= dim;
+            }
+        }
+        offset
+    };
+
+    let data: Vec<f64> = (0..batches)
+        .into_par_iter()
+        .flat_map_iter(|index| {
+            let a = &tensor_a.data[block(batch_a, index) * m * k..][..m * k];
+            let b = &tensor_b.data[block(batch_b, index) * k * n..][..k * n];
+            block_dot(a, b, m, k, n)
+        })
+        .collect();
+
+    let mut shape = batch;
+    shape.extend([m, n]);
+    Ok(Tensor::new(shape, data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tensor_ops::einstein_summation;
+
+    #[test]
+    fn test_matmul_matches_einstein_summation() {
+        let a = Tensor::new(vec![3, 4], (0..12).map(|x| x as f64 * 0.5).collect());
+        let b = Tensor::new(vec![4, 2], (0..8).map(|x| 1.0 - x as f64).collect());
+        let expected = einstein_summation(&a, &b, &[0, 1], &[1, 2], &[0, 2]).unwrap();
+        let product = matmul(&a, &b).unwrap();
+        assert_eq!(product.shape, vec![3, 2]);
+        assert_eq!(product.data, expected.data);
+        assert!(matmul(&b, &b).is_err());
+        assert!(matmul(&a, &Tensor::new(vec![4], vec![0.0; 4])).is_err());
+    }
+
+    #[test]
+    fn test_batched_matmul_broadcasts_batches() {
+        let a = Tensor::new(vec![2, 2, 3], (0..12).map(|x| x as f64).collect());
+        let b = Tensor::new(vec![3, 2], vec![1.0, 0.0, 0.0, 1.0, 1.0, 1.0]);
+        let product = batched_matmul(&a, &b).unwrap();
+        assert_eq!(product.shape, vec![2, 2, 2]);
+        for batch in 0..2 {
+            let matrix = Tensor::new(vec![2, 3], a.data[batch * 6..][..6].to_vec());
+            assert_eq!(&product.data[batch * 4..][..4], matmul(&matrix, &b).unwrap().data.as_slice());
+        }
+
+        let pairs = batched_matmul(&Tensor::new(vec![2, 1, 1, 1], vec![2.0, 3.0]), &Tensor::new(vec![3, 1, 1], vec![1.0, 10.0, 100.0])).unwrap();
+        assert_eq!(pairs.shape, vec![2, 3, 1, 1]);
+        assert_eq!(pairs.data, vec![2.0, 20.0, 200.0, 3.0, 30.0, 300.0]);
+        assert!(batched_matmul(&a, &a).is_err());
+    }
+}
@@ -0,0 +1,170 @@
+//! Memory-Mapped Tensors - Reading large tensor files without loading them
+//!
+//! `Tensor::open_mmap` maps an `.npy` or safetensors file read-only and parses
+//! only its header. Tensors stored as aligned little-endian `f64` are exposed as
+//! `TensorView`s straight into the mapping, so the operating system pages in just
+//! the parts a query touches; Fortran-ordered arrays become column-major strided
+//! views. Tensors of other element types are decoded into owned copies on request.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::ops::Range;
+use std::path::Path;
+
+use memmap2::Mmap;
+
+use super::io::{ElementType, NpyHeader, SafetensorsHeader};
+use super::{Tensor, TensorView};
+
+/// Layout of one tensor inside a mapped file
+#[derive(Debug, Clone)]
+struct MappedEntry {
+    dtype: ElementType,
+    shape: Vec<usize>,
+    fortran_order: bool,
+    /// Byte range within the mapping
+    bytes: Range<usize>,
+}
+
+/// A read-only memory-mapped tensor file
+///
+/// An `.npy` file holds one tensor, named after the file stem; a safetensors file
+/// holds every tensor its header names.
+#[derive(Debug)]
+pub struct MappedTensors {
+    mmap: Mmap,
+    entries: BTreeMap<String, MappedEntry>,
+}
+
+impl Tensor {
+    /// Memory-map an `.npy` or safetensors file, detected from its contents
+    ///
+    /// The file must not be modified while it is mapped.
+    pub fn open_mmap<P: AsRef<Path>>(path: P) -> Result<MappedTensors, String> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        // SAFETY: the mapping is read-only, and callers are told not to modify the
+        // file while it is mapped
+        let mmap = unsafe { Mmap::map(&file) }.map_err(|e| format!("Failed to map {}: {}", path.display(), e))?;
+
+        let mut entries = BTreeMap::new();
+        if mmap.starts_with(b"\x93NUMPY") {
+            let header = NpyHeader::parse(&mmap)?;
+            let end = header.data_offset.checked_add(header.data_len()?).filter(|&end| end <= mmap.len());
+            let end = end.ok_or("Truncated npy data")?;
+            let name = path.file_stem().map_or_else(String::new, |stem| stem.to_string_lossy().into_owned());
+            entries.insert(name, MappedEntry {
+                dtype: header.dtype,
+                shape: header.shape,
+                fortran_order: header.fortran_order,
+                bytes: header.data_offset..end,
+            });
+        } else {
+            let header = SafetensorsHeader::parse(&mmap)?;
+            for (name, entry) in &header.tensors {
+                let (dtype, range) = header.locate(name)?;
+                let bytes = header.data_offset + range.start..header.data_offset + range.end;
+                if bytes.end > mmap.len() {
+                    return Err(format!("Tensor {} extends past the end of the file", name));
+                }
+                entries.insert(name.clone(), MappedEntry { dtype, shape: entry.shape.clone(), fortran_order: false, bytes });
+            }
+        }
+        Ok(MappedTensors { mmap, entries })
+    }
+}
+
+impl MappedTensors {
+    /// Names of the mapped tensors, sorted
+    pub fn names(&self) -> Vec<&str> {
+        self.entries.keys().map(String::as_str).collect()
+    }
+
+    /// Shape of the tensor `name`
+    pub fn shape(&self, name: &str) -> Option<&[usize]> {
+        self.entries.get(name).map(|entry| entry.shape.as_slice())
+    }
+
+    /// Zero-copy view of the tensor `name`
+    ///
+    /// Fails unless the tensor is stored as `f64` at an 8-byte aligned offset on a
+    /// little-endian host; use `load` for anything else.
+    pub fn view(&self, name: &str) -> Result<TensorView<'_>, String> {
+        let entry = self.entry(name)?;
+        if entry.dtype != ElementType::F64 || cfg!(target_endian = "big") {
+            return Err(format!("Tensor {} is {:?}, not little-endian f64, and cannot be viewed in place", name, entry.dtype));
+        }
+        let bytes = &self.mmap[entry.bytes.clone()];
+        if bytes.as_ptr().align_offset(std::mem::align_of::<f64>()) != 0 {
+            return Err(format!("Tensor {} is not 8-byte aligned in the file", name));
+        }
+        // SAFETY: the bytes are in bounds, aligned for f64 and little-endian like the
+        // host, every bit pattern is a valid f64, and the read-only mapping lives as
+        // long as `self`
+        let data = unsafe { std::slice::from_raw_parts(bytes.as_ptr().cast::<f64>(), bytes.len() / 8) };
+
+        if entry.fortran_order {
+            let mut strides = vec![1; entry.shape.len()];
+            for axis in 1..entry.shape.len() {
+                strides[axis] = strides[axis - 1] * entry.shape[axis - 1];
+            }
+            TensorView::with_strides(data, 0, &entry.shape, &strides)
+        } else {
+            TensorView::new(data, &entry.shape)
+        }
+    }
+
+    /// Owned copy of the tensor `name`, widened to `f64`
+    pub fn load(&self, name: &str) -> Result<Tensor, String> {
+        if let Ok(view) = self.view(name) {
+            return Ok(view.to_tensor());
+        }
+        let entry = self.entry(name)?;
+        let tensor = Tensor::new(entry.shape.clone(), entry.dtype.decode(&self.mmap[entry.bytes.clone()]));
+        if entry.fortran_order && entry.shape.len() > 1 {
+            let reversed: Vec<usize> = entry.shape.iter().rev().copied().collect();
+            let axes: Vec<usize> = (0..entry.shape.len()).rev().collect();
+            return tensor.reshape(&reversed)?.permute(&axes);
+        }
+        Ok(tensor)
+    }
+
+    fn entry(&self, name: &str) -> Result<&MappedEntry, String> {
+        self.entries.get(name).ok_or_else(|| format!("No tensor named {}", name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tensor_ops::io::save_safetensors;
+
+    #[test]
+    fn test_mapped_views_match_saved_tensors() {
+        let dir = std::env::temp_dir().join(format!("tensor_mmap_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let relation = Tensor::new(vec![2, 3], vec![0.1, 0.9, 0.4, 1.0, 0.0, 0.5]);
+        relation.save_npy(dir.join("relation.npy")).unwrap();
+        let mapped = Tensor::open_mmap(dir.join("relation.npy")).unwrap();
+        assert_eq!(mapped.names(), vec!["relation"]);
+        let view = mapped.view("relation").unwrap();
+        assert!(view.is_contiguous());
+        assert_eq!(view.get(&[1, 2]), Some(0.5));
+        assert_eq!(view.transpose(0, 1).unwrap().to_tensor().data, relation.transpose(0, 1).unwrap().data);
+
+        let tensors = BTreeMap::from([
+            ("a".to_string(), Tensor::new(vec![3], vec![1.0, 2.0, 3.0])),
+            ("b".to_string(), Tensor::new(vec![1, 2], vec![-1.0, 4.0])),
+        ]);
+        save_safetensors(dir.join("pair.safetensors"), &tensors, &BTreeMap::new()).unwrap();
+        let mapped = Tensor::open_mmap(dir.join("pair.safetensors")).unwrap();
+        assert_eq!(mapped.names(), vec!["a", "b"]);
+        assert_eq!(mapped.shape("b"), Some(&[1, 2][..]));
+        assert_eq!(mapped.view("b").unwrap().to_tensor().data, vec![-1.0, 4.0]);
+        assert_eq!(mapped.load("a").unwrap().data, vec![1.0, 2.0, 3.0]);
+        assert!(mapped.view("c").is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
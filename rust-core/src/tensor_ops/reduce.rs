@@ -0,0 +1,207 @@
+//! Reductions - Collapsing tensor axes with sum, mean, max, min, argmax and argmin
+//!
+//! Reduced axes are removed from the shape, or kept with extent one when `keepdim`
+//! is set so that the result broadcasts against the input. Work is split across
+//! output elements when there are many, and across the reduced elements otherwise.
+
+use rayon::prelude::*;
+
+use super::Tensor;
+
+/// How the elements along reduced axes are combined
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reduction {
+    Sum,
+    Mean,
+    Max,
+    Min,
+}
+
+impl Reduction {
+    fn identity(&self) -> f64 {
+        match self {
+            Self::Sum | Self::Mean => 0.0,
+            Self::Max => f64::NEG_INFINITY,
+            Self::Min => f64::INFINITY,
+        }
+    }
+
+    fn combine(&self, a: f64, b: f64) -> f64 {
+        match self {
+            Self::Sum | Self::Mean => a + b,
+            Self::Max => a.max(b),
+            Self::Min => a.min(b),
+        }
+    }
+}
+
+/// Extents and strides of the kept and reduced axes of a tensor
+struct ReductionLayout {
+    kept: Vec<(usize, usize)>,
+    reduced: Vec<(usize, usize)>,
+    shape: Vec<usize>,
+}
+
+impl ReductionLayout {
+    fn new(tensor: &Tensor, axes: &[usize], keepdim: bool) -> Result<Self, String> {
+        for (position, &axis) in axes.iter().enumerate() {
+            if axis >= tensor.rank {
+                return Err(format!("Axis {} out of range for rank {}", axis, tensor.rank));
+            }
+            if axes[..position].contains(&axis) {
+                return Err(format!("Axis {} reduced more than once", axis));
+            }
+        }
+        let reduce_all = axes.is_empty();
+
+        let strides = tensor.strides();
+
+        let (mut kept, mut reduced, mut shape) = (Vec::new(), Vec::new(), Vec::new());
+        for (axis, (&dim, &stride)) in tensor.shape.iter().zip(&strides).enumerate() {
+            if reduce_all || axes.contains(&axis) {
+                reduced.push((dim, stride));
+                if keepdim {
+                    shape.push(1);
+                }
+            } else {
+                kept.push((dim, stride));
+                shape.push(dim);
+            }
+        }
+        Ok(Self { kept, reduced, shape })
+    }
+
+    fn output_size(&self) -> usize {
+        self.kept.iter().map(|&(dim, _)| dim).product()
+    }
+
+    fn reduced_size(&self) -> usize {
+        self.reduced.iter().map(|&(dim, _)| dim).product()
+    }
+
+    /// Data offset of a row-major position over the given axes
+    fn offset(axes: &[(usize, usize)], mut index: usize) -> usize {
+        let mut offset = 0;
+        for &(dim, stride) in axes.iter().rev() {
+            offset += (index % dim) * stride;
+            index /= dim;
+        }
+        offset
+    }
+}
+
+impl Tensor {
+    /// Reduce `axes` (every axis when empty) with the given operation
+    pub fn reduce(&self, reduction: Reduction, axes: &[usize], keepdim: bool) -> Result<Tensor, String> {
+        let mut result = self.fold_axes(axes, keepdim, reduction.identity(), |a, b| reduction.combine(a, b))?;
+        if reduction == Reduction::Mean {
+            let count: usize = if axes.is_empty() { self.size() } else { axes.iter().map(|&axis| self.shape[axis]).product() };
+            result.data.par_iter_mut().for_each(|x| *x /= count as f64);
+        }
+        Ok(result)
+    }
+
+    /// Combine the elements along `axes` (every axis when empty) with an associative,
+    /// commutative `combine` starting from its `identity`
+    pub(super) fn fold_axes(
+        &self,
+        axes: &[usize],
+        keepdim: bool,
+        identity: f64,
+        combine: impl Fn(f64, f64) -> f64 + Sync,
+    ) -> Result<Tensor, String> {
+        let layout = ReductionLayout::new(self, axes, keepdim)?;
+        let (output_size, reduced_size) = (layout.output_size(), layout.reduced_size());
+
+        let reduce_one = |output: usize| {
+            let base = ReductionLayout::offset(&layout.kept, output);
+            let element = |r: usize| self.data[base + ReductionLayout::offset(&layout.reduced, r)];
+            if output_size >= reduced_size {
+                (0..reduced_size).map(element).fold(identity, &combine)
+            } else {
+                (0..reduced_size).into_par_iter().map(element).reduce(|| identity, &combine)
+            }
+        };
+        let data: Vec<f64> = if output_size >= reduced_size {
+            (0..output_size).into_par_iter().map(reduce_one).collect()
+        } else {
+            (0..output_size).map(reduce_one).collect()
+        };
+        Ok(Tensor::new(layout.shape, data))
+    }
+
+    /// Sum over `axes`
+    pub fn sum(&self, axes: &[usize], keepdim: bool) -> Result<Tensor, String> {
+        self.reduce(Reduction::Sum, axes, keepdim)
+    }
+
+    /// Mean over `axes`
+    pub fn mean(&self, axes: &[usize], keepdim: bool) -> Result<Tensor, String> {
+        self.reduce(Reduction::Mean, axes, keepdim)
+    }
+
+    /// Maximum over `axes`
+    pub fn max(&self, axes: &[usize], keepdim: bool) -> Result<Tensor, String> {
+        self.reduce(Reduction::Max, axes, keepdim)
+    }
+
+    /// Minimum over `axes`
+    pub fn min(&self, axes: &[usize], keepdim: bool) -> Result<Tensor, String> {
+        self.reduce(Reduction::Min, axes, keepdim)
+    }
+
+    /// Index of the largest element along `axis`, the first on ties
+    pub fn argmax(&self, axis: usize, keepdim: bool) -> Result<Tensor, String> {
+        self.arg_extreme(axis, keepdim, f64::NEG_INFINITY, |value, best| value > best)
+    }
+
+    /// Index of the smallest element along `axis`, the first on ties
+    pub fn argmin(&self, axis: usize, keepdim: bool) -> Result<Tensor, String> {
+        self.arg_extreme(axis, keepdim, f64::INFINITY, |value, best| value < best)
+    }
+
+    fn arg_extreme(&self, axis: usize, keepdim: bool, initial: f64, better: fn(f64, f64) -> bool) -> Result<Tensor, String> {
+        let layout = ReductionLayout::new(self, &[axis], keepdim)?;
+        let (dim, stride) = layout.reduced[0];
+        let data = (0..layout.output_size())
+            .into_par_iter()
+            .map(|output| {
+                let base = ReductionLayout::offset(&layout.kept, output);
+                (0..dim)
+                    .fold((0, initial), |best, i| {
+                        let value = self.data[base + i * stride];
+                        if better(value, best.1) { (i, value) } else { best }
+                    })
+                    .0 as f64
+            })
+            .collect();
+        Ok(Tensor::new(layout.shape, data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reductions() {
+        let tensor = Tensor::new(vec![2, 3], vec![1.0, 5.0, 3.0, 4.0, 2.0, 6.0]);
+        assert_eq!(tensor.sum(&[1], false).unwrap().data, vec![9.0, 12.0]);
+        assert_eq!(tensor.mean(&[0], false).unwrap().data, vec![2.5, 3.5, 4.5]);
+        assert_eq!(tensor.max(&[1], true).unwrap().shape, vec![2, 1]);
+        assert_eq!(tensor.min(&[], false).unwrap().data, vec![1.0]);
+        assert_eq!(tensor.sum(&[0, 1], true).unwrap().shape, vec![1, 1]);
+        assert_eq!(tensor.argmax(1, false).unwrap().data, vec![1.0, 2.0]);
+        assert_eq!(tensor.argmin(1, false).unwrap().data, vec![0.0, 1.0]);
+        assert_eq!(tensor.argmin(0, true).unwrap().shape, vec![1, 3]);
+        assert!(tensor.sum(&[2], false).is_err());
+        assert!(tensor.sum(&[1, 1], false).is_err());
+    }
+
+    #[test]
+    fn test_large_reduction_matches_sequential() {
+        let tensor = Tensor::new(vec![1, 10_000], (0..10_000).map(|x| x as f64).collect());
+        assert_eq!(tensor.sum(&[1], false).unwrap().data, vec![49_995_000.0]);
+        assert_eq!(tensor.argmax(1, false).unwrap().data, vec![9_999.0]);
+    }
+}
@@ -0,0 +1,215 @@
+//! Search - Pairwise similarity and nearest-neighbor lookup
+//!
+//! `tensor_similarity_matrix` is the cosine Gram matrix of a batch, and `knn`
+//! scores one query against a whole corpus in parallel, so a lookup over thousands
+//! of stored tensors is a single call. `LshIndex` answers the same query
+//! approximately: random-hyperplane signatures bucket the corpus, and only the
+//! tensors sharing a bucket with the query are scored exactly.
+
+use std::collections::{HashMap, HashSet};
+
+use rand::Rng;
+use rand_distr::StandardNormal;
+use rayon::prelude::*;
+use tracing::instrument;
+
+use super::{kernel_matrix, simd, Kernel, KernelFunction, Tensor};
+
+/// A corpus entry returned by a nearest-neighbor search
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Neighbor {
+    /// Position of the entry in the corpus
+    pub index: usize,
+    /// Cosine similarity to the query
+    pub similarity: f64,
+}
+
+/// Cosine similarity of every pair of a batch of equally sized tensors
+#[instrument(skip(tensors))]
+pub fn tensor_similarity_matrix(tensors: &[Tensor]) -> Result<Tensor, String> {
+    kernel_matrix(&KernelFunction::Cosine, tensors)
+}
+
+/// The `k` corpus entries most similar to `query`, most similar first
+///
+/// Ties keep corpus order. Fewer than `k` neighbors are returned when the corpus is
+/// smaller than `k`.
+#[instrument(skip(query, corpus))]
+pub fn knn(query: &Tensor, corpus: &[Tensor], k: usize) -> Result<Vec<Neighbor>, String> {
+    check_sizes(query, corpus)?;
+    let candidates: Vec<usize> = (0..corpus.len()).collect();
+    Ok(rank(query, corpus, &candidates, k))
+}
+
+/// Approximate nearest-neighbor index over cosine similarity
+///
+/// Each of the `tables` hash tables signs the tensor against `bits` random
+/// hyperplanes; more tables raise recall, more bits shrink the buckets.
+#[derive(Debug, Clone)]
+pub struct LshIndex {
+    dim: usize,
+    bits: usize,
+    /// One `bits × dim` matrix of hyperplane normals per table
+    planes: Vec<Vec<f64>>,
+    buckets: Vec<HashMap<u64, Vec<usize>>>,
+    corpus: Vec<Tensor>,
+}
+
+impl LshIndex {
+    /// Empty index for tensors of `dim` elements
+    pub fn new<R: Rng + ?Sized>(dim: usize, tables: usize, bits: usize, rng: &mut R) -> Result<Self, String> {
+        if tables == 0 || bits == 0 || bits > 64 {
+            return Err(format!("LSH needs at least one table and 1 to 64 bits, got {} tables of {} bits", tables, bits));
+        }
+        let planes = (0..tables)
+            .map(|_| (0..bits * dim).map(|_| rng.sample(StandardNormal)).collect())
+            .collect();
+        Ok(Self { dim, bits, planes, buckets: vec![HashMap::new(); tables], corpus: Vec::new() })
+    }
+
+    /// Index built over `corpus`, whose positions become the neighbor indices
+    pub fn build<R: Rng + ?Sized>(corpus: Vec<Tensor>, tables: usize, bits: usize, rng: &mut R) -> Result<Self, String> {
+        let dim = corpus.first().map_or(0, |t| t.data.len());
+        let mut index = Self::new(dim, tables, bits, rng)?;
+        for tensor in corpus {
+            index.insert(tensor)?;
+        }
+        Ok(index)
+    }
+
+    /// Add a tensor, returning its index
+    pub fn insert(&mut self, tensor: Tensor) -> Result<usize, String> {
+        if tensor.data.len() != self.dim {
+            return Err(format!("Expected a tensor of {} elements, got {}", self.dim, tensor.data.len()));
+        }
+        let index = self.corpus.len();
+        for (table, buckets) in self.buckets.iter_mut().enumerate() {
+            let signature = signature(&self.planes[table], self.bits, &tensor.data);
+            buckets.entry(signature).or_default().push(index);
+        }
+        self.corpus.push(tensor);
+        Ok(index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.corpus.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.corpus.is_empty()
+    }
+
+    /// Indexed tensor at `index`
+    pub fn get(&self, index: usize) -> Option<&Tensor> {
+        self.corpus.get(index)
+    }
+
+    /// Approximately the `k` entries most similar to `query`
+    ///
+    /// Only entries sharing a bucket with the query in some table are considered,
+    /// so fewer than `k` neighbors may be returned.
+    #[instrument(skip(self, query))]
+    pub fn search(&self, query: &Tensor, k: usize) -> Result<Vec<Neighbor>, String> {
+        if query.data.len() != self.dim {
+            return Err(format!("Expected a tensor of {} elements, got {}", self.dim, query.data.len()));
+        }
+        let mut seen = HashSet::new();
+        let mut candidates: Vec<usize> = self.buckets.iter().enumerate()
+            .filter_map(|(table, buckets)| buckets.get(&signature(&self.planes[table], self.bits, &query.data)))
+            .flatten()
+            .copied()
+            .filter(|&index| seen.insert(index))
+            .collect();
+        candidates.sort_unstable();
+        Ok(rank(query, &self.corpus, &candidates, k))
+    }
+}
+
+/// Sign bits of `data` against each hyperplane
+fn signature(planes: &[f64], bits: usize, data: &[f64]) -> u64 {
+    let dim = data.len();
+    (0..bits).fold(0, |signature, bit| {
+        let side = simd::dot(&planes[bit * dim..(bit + 1) * dim], data) >= 0.0;
+        signature | (u64::from(side) << bit)
+    })
+}
+
+/// Score `candidates` against `query` and keep the best `k`
+fn rank(query: &Tensor, corpus: &[Tensor], candidates: &[usize], k: usize) -> Vec<Neighbor> {
+    let mut neighbors: Vec<Neighbor> = candidates
+        .par_iter()
+        .map(|&index| Neighbor {
+            index,
+            similarity: KernelFunction::Cosine.evaluate(&query.data, &corpus[index].data),
+        })
+        .collect();
+    // Ties fall back to corpus order so results are deterministic
+    let compare = |a: &Neighbor, b: &Neighbor| b.similarity.total_cmp(&a.similarity).then(a.index.cmp(&b.index));
+    if k < neighbors.len() {
+        neighbors.select_nth_unstable_by(k, compare);
+        neighbors.truncate(k);
+    }
+    neighbors.sort_unstable_by(compare);
+    neighbors
+}
+
+fn check_sizes(query: &Tensor, corpus: &[Tensor]) -> Result<(), String> {
+    for (index, tensor) in corpus.iter().enumerate() {
+        if tensor.data.len() != query.data.len() {
+            return Err(format!(
+                "Corpus tensor {} has {} elements, query has {}",
+                index, tensor.data.len(), query.data.len()
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_similarity_matrix_and_knn() {
+        let corpus = vec![
+            Tensor::new(vec![2], vec![1.0, 0.0]),
+            Tensor::new(vec![2], vec![0.0, 3.0]),
+            Tensor::new(vec![2], vec![2.0, 2.0]),
+            Tensor::new(vec![2], vec![1.0, 0.0]),
+        ];
+        let matrix = tensor_similarity_matrix(&corpus[..2]).unwrap();
+        assert_eq!(matrix.data, vec![1.0, 0.0, 0.0, 1.0]);
+
+        let query = Tensor::new(vec![2], vec![1.0, 0.1]);
+        let neighbors = knn(&query, &corpus, 3).unwrap();
+        let indices: Vec<usize> = neighbors.iter().map(|n| n.index).collect();
+        // Entries 0 and 3 are equal, so the tie keeps corpus order
+        assert_eq!(indices, vec![0, 3, 2]);
+        assert!(neighbors[0].similarity > neighbors[2].similarity);
+        assert_eq!(knn(&query, &corpus, 10).unwrap().len(), 4);
+        assert!(knn(&query, &[Tensor::new(vec![3], vec![0.0; 3])], 1).is_err());
+    }
+
+    #[test]
+    fn test_lsh_index_finds_near_duplicates() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let corpus: Vec<Tensor> = (0..200)
+            .map(|_| Tensor::rand_normal(&[16], 0.0, 1.0, &mut rng).unwrap())
+            .collect();
+        let index = LshIndex::build(corpus.clone(), 8, 6, &mut rng).unwrap();
+        assert_eq!(index.len(), 200);
+
+        // A slightly perturbed copy should land in a shared bucket with its source
+        let mut query = corpus[42].clone();
+        query.data[0] += 0.01;
+        let approximate = index.search(&query, 1).unwrap();
+        assert_eq!(approximate[0].index, 42);
+        assert_eq!(approximate[0], knn(&query, &corpus, 1).unwrap()[0]);
+
+        assert!(index.search(&Tensor::zeros(&[4]), 1).is_err());
+        assert!(LshIndex::new(16, 0, 6, &mut rng).is_err());
+        assert!(LshIndex::new(16, 1, 65, &mut rng).is_err());
+    }
+}
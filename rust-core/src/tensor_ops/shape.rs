@@ -0,0 +1,104 @@
+//! Shape Manipulation - Reshape, permute, squeeze and flatten tensors
+//!
+//! Tensors are always stored contiguously in row-major order, so reshaping only
+//! replaces the shape while permuting moves the data into the new axis order.
+//! `TensorView` offers the same permutations without moving anything.
+
+use super::Tensor;
+
+impl Tensor {
+    /// Same data viewed with a new shape of equal size
+    pub fn reshape(&self, shape: &[usize]) -> Result<Tensor, String> {
+        // A shape whose size overflows can never match, so report it as a mismatch
+        let size = shape.iter().try_fold(1usize, |size, &dim| size.checked_mul(dim));
+        if size != Some(self.size()) {
+            return Err(format!(
+                "Cannot reshape {:?} ({} elements) into {:?}",
+                self.shape, self.size(), shape
+            ));
+        }
+        Ok(Tensor::new(shape.to_vec(), self.data.clone()))
+    }
+
+    /// Reorder axes so that axis `i` of the result is axis `axes[i]` of `self`
+    ///
+    /// Copies the elements; use `Tensor::view` and `TensorView::permute` to avoid that.
+    pub fn permute(&self, axes: &[usize]) -> Result<Tensor, String> {
+        Ok(self.view().permute(axes)?.to_tensor())
+    }
+
+    /// Swap two axes
+    pub fn transpose(&self, axis_a: usize, axis_b: usize) -> Result<Tensor, String> {
+        if axis_a >= self.rank || axis_b >= self.rank {
+            return Err(format!("Axes {} and {} out of range for rank {}", axis_a, axis_b, self.rank));
+        }
+        let mut axes: Vec<usize> = (0..self.rank).collect();
+        axes.swap(axis_a, axis_b);
+        self.permute(&axes)
+    }
+
+    /// Remove `axis` if it has extent one, or every such axis when `None`
+    pub fn squeeze(&self, axis: Option<usize>) -> Result<Tensor, String> {
+        let shape: Vec<usize> = match axis {
+            Some(axis) => {
+                if self.shape.get(axis) != Some(&1) {
+                    return Err(format!("Axis {} of {:?} does not have extent 1", axis, self.shape));
+                }
+                self.shape.iter().enumerate().filter(|&(i, _)| i != axis).map(|(_, &dim)| dim).collect()
+            }
+            None => self.shape.iter().copied().filter(|&dim| dim != 1).collect(),
+        };
+        self.reshape(&shape)
+    }
+
+    /// Insert an axis of extent one at position `axis`
+    pub fn unsqueeze(&self, axis: usize) -> Result<Tensor, String> {
+        if axis > self.rank {
+            return Err(format!("Cannot insert axis {} into rank {}", axis, self.rank));
+        }
+        let mut shape = self.shape.clone();
+        shape.insert(axis, 1);
+        self.reshape(&shape)
+    }
+
+    /// Rank-1 tensor of every element in row-major order
+    pub fn flatten(&self) -> Tensor {
+        Tensor::new(vec![self.size()], self.data.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reshape_and_squeeze() {
+        let tensor = Tensor::new(vec![2, 3], vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(tensor.reshape(&[3, 2]).unwrap().shape, vec![3, 2]);
+        assert!(tensor.reshape(&[4]).is_err());
+        assert!(tensor.reshape(&[usize::MAX, 2, 3]).is_err());
+
+        let expanded = tensor.unsqueeze(0).unwrap().unsqueeze(3).unwrap();
+        assert_eq!(expanded.shape, vec![1, 2, 3, 1]);
+        assert_eq!(expanded.squeeze(Some(3)).unwrap().shape, vec![1, 2, 3]);
+        assert_eq!(expanded.squeeze(None).unwrap().shape, vec![2, 3]);
+        assert!(expanded.squeeze(Some(1)).is_err());
+        assert_eq!(expanded.flatten().shape, vec![6]);
+    }
+
+    #[test]
+    fn test_permute_moves_data() {
+        let tensor = Tensor::new(vec![2, 3], vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let transposed = tensor.transpose(0, 1).unwrap();
+        assert_eq!(transposed.shape, vec![3, 2]);
+        assert_eq!(transposed.data, vec![1.0, 4.0, 2.0, 5.0, 3.0, 6.0]);
+
+        let cube = Tensor::new(vec![2, 3, 4], (0..24).map(|x| x as f64).collect());
+        let permuted = cube.permute(&[2, 0, 1]).unwrap();
+        assert_eq!(permuted.shape, vec![4, 2, 3]);
+        // permuted[k, i, j] == cube[i, j, k]
+        assert_eq!(permuted.data[(3 * 2 + 1) * 3 + 2], cube.data[(3 + 2) * 4 + 3]);
+        assert!(cube.permute(&[0, 0, 1]).is_err());
+        assert!(cube.permute(&[0, 1]).is_err());
+    }
+}
@@ -0,0 +1,207 @@
+//! SIMD Kernels - Vectorized element-wise logic, norms and dot products
+//!
+//! On x86-64 the kernels use AVX when the running CPU supports it, detected at
+//! runtime, and otherwise fall back to scalar loops unrolled four ways (which the
+//! compiler vectorizes with the baseline SSE2 or NEON instructions). The `par_*`
+//! helpers split large buffers into chunks that rayon processes on separate cores,
+//! each chunk running a single vectorized kernel.
+
+use rayon::prelude::*;
+
+/// Elements handled by one rayon task
+const CHUNK: usize = 1 << 14;
+
+/// Kernel writing `out[i] = op(a[i], b[i])`
+pub type BinaryKernel = fn(&[f64], &[f64], &mut [f64]);
+
+mod scalar {
+    pub fn mul(a: &[f64], b: &[f64], out: &mut [f64]) {
+        for ((o, &a), &b) in out.iter_mut().zip(a).zip(b) {
+            *o = a * b;
+        }
+    }
+
+    pub fn max(a: &[f64], b: &[f64], out: &mut [f64]) {
+        for ((o, &a), &b) in out.iter_mut().zip(a).zip(b) {
+            *o = a.max(b);
+        }
+    }
+
+    pub fn implies(a: &[f64], b: &[f64], out: &mut [f64]) {
+        for ((o, &a), &b) in out.iter_mut().zip(a).zip(b) {
+            *o = (1.0 - a).max(b);
+        }
+    }
+
+    pub fn not(a: &[f64], out: &mut [f64]) {
+        for (o, &a) in out.iter_mut().zip(a) {
+            *o = 1.0 - a;
+        }
+    }
+
+    pub fn dot(a: &[f64], b: &[f64]) -> f64 {
+        let mut lanes = [0.0; 4];
+        let (chunks_a, chunks_b) = (a.chunks_exact(4), b.chunks_exact(4));
+        let tail: f64 = chunks_a.remainder().iter().zip(chunks_b.remainder()).map(|(a, b)| a * b).sum();
+        for (a, b) in chunks_a.zip(chunks_b) {
+            for lane in 0..4 {
+                lanes[lane] += a[lane] * b[lane];
+            }
+        }
+        (lanes[0] + lanes[1]) + (lanes[2] + lanes[3]) + tail
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod avx {
+    use std::arch::x86_64::*;
+
+    macro_rules! binary_kernel {
+        ($name:ident, |$x:ident, $y:ident| $body:expr) => {
+            /// # Safety
+            /// The CPU must support AVX.
+            #[target_feature(enable = "avx")]
+            pub unsafe fn $name(a: &[f64], b: &[f64], out: &mut [f64]) {
+                let n = out.len().min(a.len()).min(b.len());
+                let vectorized = n - n % 4;
+                for i in (0..vectorized).step_by(4) {
+                    unsafe {
+                        let $x = _mm256_loadu_pd(a.as_ptr().add(i));
+                        let $y = _mm256_loadu_pd(b.as_ptr().add(i));
+                        _mm256_storeu_pd(out.as_mut_ptr().add(i), $body);
+                    }
+                }
+                super::scalar::$name(&a[vectorized..n], &b[vectorized..n], &mut out[vectorized..n]);
+            }
+        };
+    }
+
+    binary_kernel!(mul, |x, y| _mm256_mul_pd(x, y));
+    binary_kernel!(max, |x, y| _mm256_max_pd(x, y));
+    binary_kernel!(implies, |x, y| _mm256_max_pd(_mm256_sub_pd(_mm256_set1_pd(1.0), x), y));
+
+    /// # Safety
+    /// The CPU must support AVX.
+    #[target_feature(enable = "avx")]
+    pub unsafe fn not(a: &[f64], out: &mut [f64]) {
+        let n = out.len().min(a.len());
+        let vectorized = n - n % 4;
+        let one = _mm256_set1_pd(1.0);
+        for i in (0..vectorized).step_by(4) {
+            unsafe {
+                let x = _mm256_loadu_pd(a.as_ptr().add(i));
+                _mm256_storeu_pd(out.as_mut_ptr().add(i), _mm256_sub_pd(one, x));
+            }
+        }
+        super::scalar::not(&a[vectorized..n], &mut out[vectorized..n]);
+    }
+
+    /// # Safety
+    /// The CPU must support AVX.
+    #[target_feature(enable = "avx")]
+    pub unsafe fn dot(a: &[f64], b: &[f64]) -> f64 {
+        let n = a.len().min(b.len());
+        let vectorized = n - n % 8;
+        let (mut even, mut odd) = (_mm256_setzero_pd(), _mm256_setzero_pd());
+        for i in (0..vectorized).step_by(8) {
+            unsafe {
+                even = _mm256_add_pd(even, _mm256_mul_pd(_mm256_loadu_pd(a.as_ptr().add(i)), _mm256_loadu_pd(b.as_ptr().add(i))));
+                odd = _mm256_add_pd(odd, _mm256_mul_pd(_mm256_loadu_pd(a.as_ptr().add(i + 4)), _mm256_loadu_pd(b.as_ptr().add(i + 4))));
+            }
+        }
+        let mut lanes = [0.0; 4];
+        unsafe { _mm256_storeu_pd(lanes.as_mut_ptr(), _mm256_add_pd(even, odd)) };
+        (lanes[0] + lanes[1]) + (lanes[2] + lanes[3]) + super::scalar::dot(&a[vectorized..n], &b[vectorized..n])
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+fn has_avx() -> bool {
+    std::is_x86_feature_detected!("avx")
+}
+
+macro_rules! dispatch_binary {
+    ($name:ident, $doc:literal) => {
+        #[doc = $doc]
+        pub fn $name(a: &[f64], b: &[f64], out: &mut [f64]) {
+            #[cfg(target_arch = "x86_64")]
+            if has_avx() {
+                // SAFETY: AVX support was just detected
+                return unsafe { avx::$name(a, b, out) };
+            }
+            scalar::$name(a, b, out)
+        }
+    };
+}
+
+dispatch_binary!(mul, "`out[i] = a[i] * b[i]` (logical AND)");
+dispatch_binary!(max, "`out[i] = max(a[i], b[i])` (unnormalized logical OR)");
+dispatch_binary!(implies, "`out[i] = max(1 - a[i], b[i])` (logical IMPLIES)");
+
+/// `out[i] = 1 - a[i]` (logical NOT)
+pub fn not(a: &[f64], out: &mut [f64]) {
+    #[cfg(target_arch = "x86_64")]
+    if has_avx() {
+        // SAFETY: AVX support was just detected
+        return unsafe { avx::not(a, out) };
+    }
+    scalar::not(a, out)
+}
+
+/// Dot product over the common length of `a` and `b`
+pub fn dot(a: &[f64], b: &[f64]) -> f64 {
+    #[cfg(target_arch = "x86_64")]
+    if has_avx() {
+        // SAFETY: AVX support was just detected
+        return unsafe { avx::dot(a, b) };
+    }
+    scalar::dot(a, b)
+}
+
+/// Element-wise `kernel` over equal-length buffers, chunked across threads
+pub fn par_binary(a: &[f64], b: &[f64], kernel: BinaryKernel) -> Vec<f64> {
+    let mut out = vec![0.0; a.len().min(b.len())];
+    out.par_chunks_mut(CHUNK)
+        .zip(a.par_chunks(CHUNK).zip(b.par_chunks(CHUNK)))
+        .for_each(|(out, (a, b))| kernel(a, b, out));
+    out
+}
+
+/// `1 - a[i]` for every element, chunked across threads
+pub fn par_not(a: &[f64]) -> Vec<f64> {
+    let mut out = vec![0.0; a.len()];
+    out.par_chunks_mut(CHUNK).zip(a.par_chunks(CHUNK)).for_each(|(out, a)| not(a, out));
+    out
+}
+
+/// Dot product, chunked across threads
+pub fn par_dot(a: &[f64], b: &[f64]) -> f64 {
+    a.par_chunks(CHUNK).zip(b.par_chunks(CHUNK)).map(|(a, b)| dot(a, b)).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kernels_match_scalar() {
+        // Odd length exercises the remainder loops
+        let a: Vec<f64> = (0..1003).map(|i| (i as f64 * 0.37).sin().abs()).collect();
+        let b: Vec<f64> = (0..1003).map(|i| (i as f64 * 0.11).cos().abs()).collect();
+
+        let kernels: [(BinaryKernel, BinaryKernel); 3] = [(mul, scalar::mul), (max, scalar::max), (implies, scalar::implies)];
+        for (kernel, reference) in kernels {
+            let mut expected = vec![0.0; a.len()];
+            reference(&a, &b, &mut expected);
+            assert_eq!(par_binary(&a, &b, kernel), expected);
+        }
+
+        let mut expected = vec![0.0; a.len()];
+        scalar::not(&a, &mut expected);
+        assert_eq!(par_not(&a), expected);
+
+        let exact: f64 = a.iter().zip(&b).map(|(x, y)| x * y).sum();
+        assert!((par_dot(&a, &b) - exact).abs() < 1e-9);
+        assert!((dot(&a[..3], &b[..3]) - scalar::dot(&a[..3], &b[..3])).abs() < 1e-15);
+    }
+}
@@ -0,0 +1,116 @@
+//! Sorting - Sort, argsort and top-k along an axis
+//!
+//! Every lane along the chosen axis is sorted independently, in parallel, under
+//! `f64::total_cmp` with ties broken by position, so equal elements keep their
+//! original order and NaNs sort above every number. `top_k` selects its `k`
+//! elements before sorting only those. Indices are returned as whole numbers stored
+//! in `f64`, like those of `Tensor::argmax`, so they feed straight into `gather`.
+
+use rayon::prelude::*;
+
+use super::Tensor;
+
+impl Tensor {
+    /// The first `keep` positions of each sorted lane along `axis`, as values and
+    /// source indices
+    fn sorted_lanes(&self, axis: usize, descending: bool, keep: usize) -> Result<(Tensor, Tensor), String> {
+        if axis >= self.rank {
+            return Err(format!("Axis {} out of range for rank {}", axis, self.rank));
+        }
+        let n = self.shape[axis];
+        let outer: usize = self.shape[..axis].iter().product();
+        let inner: usize = self.shape[axis + 1..].iter().product();
+
+        let lanes: Vec<Vec<usize>> = (0..outer * inner)
+            .into_par_iter()
+            .map(|lane| {
+                let base = (lane / inner) * n * inner + lane % inner;
+                let value = |i: usize| self.data[base + i * inner];
+                // Breaking ties by index makes the order total, so the unstable
+                // selection and sort below behave like a stable sort
+                let compare = |a: &usize, b: &usize| {
+                    let ordering = value(*a).total_cmp(&value(*b));
+                    (if descending { ordering.reverse() } else { ordering }).then(a.cmp(b))
+                };
+                let mut order: Vec<usize> = (0..n).collect();
+                if keep < n {
+                    order.select_nth_unstable_by(keep, compare);
+                    order.truncate(keep);
+                }
+                order.sort_unstable_by(compare);
+                order
+            })
+            .collect();
+
+        let size = outer * keep * inner;
+        let (mut values, mut indices) = (vec![0.0; size], vec![0.0; size]);
+        for (lane, order) in lanes.into_iter().enumerate() {
+            let (o, i) = (lane / inner, lane % inner);
+            for (rank, source) in order.into_iter().enumerate() {
+                let target = (o * keep + rank) * inner + i;
+                values[target] = self.data[(o * n + source) * inner + i];
+                indices[target] = source as f64;
+            }
+        }
+
+        let mut shape = self.shape.clone();
+        shape[axis] = keep;
+        Ok((Tensor::new(shape.clone(), values), Tensor::new(shape, indices)))
+    }
+
+    /// Elements sorted along `axis`
+    pub fn sort(&self, axis: usize, descending: bool) -> Result<Tensor, String> {
+        let n = self.shape.get(axis).copied().unwrap_or(0);
+        Ok(self.sorted_lanes(axis, descending, n)?.0)
+    }
+
+    /// Indices that sort each lane along `axis`
+    pub fn argsort(&self, axis: usize, descending: bool) -> Result<Tensor, String> {
+        let n = self.shape.get(axis).copied().unwrap_or(0);
+        Ok(self.sorted_lanes(axis, descending, n)?.1)
+    }
+
+    /// The `k` largest elements along `axis` in descending order, with their indices
+    pub fn top_k(&self, k: usize, axis: usize) -> Result<(Tensor, Tensor), String> {
+        match self.shape.get(axis) {
+            Some(&n) if k > n => Err(format!("Cannot take top {} of {} elements along axis {}", k, n, axis)),
+            _ => self.sorted_lanes(axis, true, k),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_and_argsort() {
+        let tensor = Tensor::new(vec![2, 3], vec![3.0, 1.0, 2.0, 0.5, 0.5, -1.0]);
+        assert_eq!(tensor.sort(1, false).unwrap().data, vec![1.0, 2.0, 3.0, -1.0, 0.5, 0.5]);
+        assert_eq!(tensor.argsort(1, false).unwrap().data, vec![1.0, 2.0, 0.0, 2.0, 0.0, 1.0]);
+        // Ties keep their original order when descending too
+        assert_eq!(tensor.argsort(1, true).unwrap().data, vec![0.0, 2.0, 1.0, 0.0, 1.0, 2.0]);
+        assert_eq!(tensor.sort(0, false).unwrap().data, vec![0.5, 0.5, -1.0, 3.0, 1.0, 2.0]);
+
+        let order = tensor.argsort(1, false).unwrap();
+        assert_eq!(tensor.gather(1, &order).unwrap().data, tensor.sort(1, false).unwrap().data);
+        assert!(tensor.sort(2, false).is_err());
+    }
+
+    #[test]
+    fn test_top_k() {
+        let scores = Tensor::new(vec![2, 4], vec![0.1, 0.9, 0.4, 0.7, 5.0, 1.0, 3.0, 2.0]);
+        let (values, indices) = scores.top_k(2, 1).unwrap();
+        assert_eq!(values.shape, vec![2, 2]);
+        assert_eq!(values.data, vec![0.9, 0.7, 5.0, 3.0]);
+        assert_eq!(indices.data, vec![1.0, 3.0, 0.0, 2.0]);
+
+        let (columns, rows) = scores.top_k(1, 0).unwrap();
+        assert_eq!(columns.data, vec![5.0, 1.0, 3.0, 2.0]);
+        assert_eq!(rows.data, vec![1.0; 4]);
+        assert!(scores.top_k(5, 1).is_err());
+
+        let with_nan = Tensor::new(vec![3], vec![2.0, f64::NAN, -1.0]);
+        assert_eq!(with_nan.argsort(0, false).unwrap().data, vec![2.0, 0.0, 1.0]);
+    }
+}
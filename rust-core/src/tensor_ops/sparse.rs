@@ -0,0 +1,373 @@
+//! Sparse Tensors - COO and CSR storage with logic operations that skip zeros
+//!
+//! `SparseTensor` is coordinate (COO) storage of any rank, holding the row-major
+//! offsets of its non-zero entries in increasing order. `CsrMatrix` is compressed
+//! sparse row storage for rank-2 relations, where composing relations is a sparse
+//! matrix product. Operations produce the same values as their dense counterparts
+//! while only visiting stored entries.
+
+use std::collections::HashMap;
+
+use rayon::prelude::*;
+
+use super::{row_major_strides, stable, Tensor};
+
+/// Sparse tensor in coordinate format
+#[derive(Debug, Clone, PartialEq)]
+pub struct SparseTensor {
+    shape: Vec<usize>,
+    /// Row-major offsets of the stored entries, strictly increasing
+    offsets: Vec<usize>,
+    values: Vec<f64>,
+}
+
+impl SparseTensor {
+    /// Sparse tensor from `(coordinates, value)` entries; duplicates are summed and
+    /// zeros dropped
+    pub fn from_entries(shape: Vec<usize>, entries: &[(Vec<usize>, f64)]) -> Result<Self, String> {
+        let strides = row_major_strides(&shape);
+        let mut sums: HashMap<usize, f64> = HashMap::new();
+        for (coordinates, value) in entries {
+            if coordinates.len() != shape.len() || coordinates.iter().zip(&shape).any(|(&c, &dim)| c >= dim) {
+                return Err(format!("Coordinates {:?} out of bounds for shape {:?}", coordinates, shape));
+            }
+            let offset = coordinates.iter().zip(&strides).map(|(c, s)| c * s).sum();
+            *sums.entry(offset).or_insert(0.0) += value;
+        }
+        Ok(Self::from_map(shape, sums))
+    }
+
+    fn from_map(shape: Vec<usize>, entries: HashMap<usize, f64>) -> Self {
+        let mut entries: Vec<(usize, f64)> = entries.into_iter().filter(|&(_, value)| value != 0.0).collect();
+        entries.sort_unstable_by_key(|&(offset, _)| offset);
+        let (offsets, values) = entries.into_iter().unzip();
+        Self { shape, offsets, values }
+    }
+
+    /// Sparse copy of the non-zero entries of a dense tensor
+    pub fn from_dense(tensor: &Tensor) -> Self {
+        let (offsets, values) = tensor.data.iter().enumerate()
+            .filter(|&(_, &value)| value != 0.0)
+            .map(|(offset, &value)| (offset, value))
+            .unzip();
+        Self { shape: tensor.shape.clone(), offsets, values }
+    }
+
+    /// Dense tensor with zeros at every unstored position
+    pub fn to_dense(&self) -> Tensor {
+        let mut data = vec![0.0; self.shape.iter().product()];
+        for (&offset, &value) in self.offsets.iter().zip(&self.values) {
+            data[offset] = value;
+        }
+        Tensor::new(self.shape.clone(), data)
+    }
+
+    /// Shape of the tensor
+    pub fn shape(&self) -> &[usize] {
+        &self.shape
+    }
+
+    /// Number of stored non-zero entries
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Fraction of positions that are non-zero
+    pub fn density(&self) -> f64 {
+        let size: usize = self.shape.iter().product();
+        if size == 0 { 0.0 } else { self.nnz() as f64 / size as f64 }
+    }
+
+    /// Stored entries as `(coordinates, value)` in row-major order
+    pub fn entries(&self) -> impl Iterator<Item = (Vec<usize>, f64)> + '_ {
+        self.offsets.iter().zip(&self.values).map(|(&offset, &value)| (self.coordinates(offset), value))
+    }
+
+    fn coordinates(&self, mut offset: usize) -> Vec<usize> {
+        let mut coordinates = vec![0; self.shape.len()];
+        for (coordinate, &dim) in coordinates.iter_mut().zip(&self.shape).rev() {
+            *coordinate = offset % dim;
+            offset /= dim;
+        }
+        coordinates
+    }
+
+    /// L2 norm of the stored values
+    pub fn norm(&self) -> f64 {
+        stable::stable_norm(&self.values)
+    }
+
+    /// Compressed sparse row copy of a rank-2 tensor
+    pub fn to_csr(&self) -> Result<CsrMatrix, String> {
+        let &[rows, cols] = self.shape.as_slice() else {
+            return Err(format!("CSR storage needs a rank-2 tensor, got shape {:?}", self.shape));
+        };
+        let mut row_offsets = vec![0; rows + 1];
+        for &offset in &self.offsets {
+            row_offsets[offset / cols + 1] += 1;
+        }
+        for row in 0..rows {
+            row_offsets[row + 1] += row_offsets[row];
+        }
+        Ok(CsrMatrix {
+            rows,
+            cols,
+            row_offsets,
+            columns: self.offsets.iter().map(|offset| offset % cols).collect(),
+            values: self.values.clone(),
+        })
+    }
+
+    /// Merge entries of two equally shaped tensors, applying `op` where either is
+    /// stored (the other reading as zero)
+    fn merge(&self, other: &SparseTensor, op: impl Fn(f64, f64) -> f64) -> Result<SparseTensor, String> {
+        if self.shape != other.shape {
+            return Err(format!("Shape mismatch: {:?} vs {:?}", self.shape, other.shape));
+        }
+        let (mut i, mut j) = (0, 0);
+        let (mut offsets, mut values) = (Vec::new(), Vec::new());
+        while i < self.nnz() || j < other.nnz() {
+            let a_offset = self.offsets.get(i).copied().unwrap_or(usize::MAX);
+            let b_offset = other.offsets.get(j).copied().unwrap_or(usize::MAX);
+            let offset = a_offset.min(b_offset);
+            let a = if a_offset == offset { i += 1; self.values[i - 1] } else { 0.0 };
+            let b = if b_offset == offset { j += 1; other.values[j - 1] } else { 0.0 };
+            let value = op(a, b);
+            if value != 0.0 {
+                offsets.push(offset);
+                values.push(value);
+            }
+        }
+        Ok(SparseTensor { shape: self.shape.clone(), offsets, values })
+    }
+}
+
+/// Sparse logical AND: the product of entries stored in both tensors
+pub fn sparse_and(tensor_a: &SparseTensor, tensor_b: &SparseTensor) -> Result<SparseTensor, String> {
+    tensor_a.merge(tensor_b, |a, b| a * b)
+}
+
+/// Sparse logical OR: element-wise maximum normalized to unit L2 norm, like `tensor_or`
+pub fn sparse_or(tensor_a: &SparseTensor, tensor_b: &SparseTensor) -> Result<SparseTensor, String> {
+    let mut result = tensor_a.merge(tensor_b, f64::max)?;
+    let norm = result.norm();
+    if norm > stable::epsilon() {
+        result.values.iter_mut().for_each(|v| *v /= norm);
+    }
+    Ok(result)
+}
+
+/// Cosine similarity, visiting only stored entries
+pub fn sparse_similarity(tensor_a: &SparseTensor, tensor_b: &SparseTensor) -> f64 {
+    if tensor_a.shape != tensor_b.shape {
+        return 0.0;
+    }
+    let dot: f64 = tensor_a.merge(tensor_b, |a, b| a * b).map_or(0.0, |product| product.values.iter().sum());
+    let (norm_a, norm_b) = (tensor_a.norm(), tensor_b.norm());
+    if norm_a < stable::epsilon() || norm_b < stable::epsilon() {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
+}
+
+/// Sparse Einstein summation with the index conventions of `einstein_summation`
+///
+/// Pairs of stored entries are joined on their shared labels, so the cost grows
+/// with the number of matching non-zeros rather than with the dense size.
+pub fn sparse_contract(
+    tensor_a: &SparseTensor,
+    tensor_b: &SparseTensor,
+    indices_a: &[usize],
+    indices_b: &[usize],
+    output_indices: &[usize],
+) -> Result<SparseTensor, String> {
+    if indices_a.len() != tensor_a.shape.len() || indices_b.len() != tensor_b.shape.len() {
+        return Err("Index count must match tensor rank".to_string());
+    }
+    let mut dims: HashMap<usize, usize> = HashMap::new();
+    for (&label, &dim) in indices_a.iter().zip(&tensor_a.shape).chain(indices_b.iter().zip(&tensor_b.shape)) {
+        if *dims.entry(label).or_insert(dim) != dim {
+            return Err(format!("Dimension mismatch for contracted index {}: {} vs {}", label, dims[&label], dim));
+        }
+    }
+    let mut output_shape = Vec::with_capacity(output_indices.len());
+    for (position, label) in output_indices.iter().enumerate() {
+        if output_indices[..position].contains(label) {
+            return Err(format!("Output index {} appears more than once", label));
+        }
+        output_shape.push(*dims.get(label).ok_or_else(|| format!("Output index {} not found in input tensors", label))?);
+    }
+
+    let shared: Vec<usize> = unique(indices_a).into_iter().filter(|label| indices_b.contains(label)).collect();
+    let output_strides = row_major_strides(&output_shape);
+
+    // Label values of an entry, or `None` off the diagonal of a repeated label
+    let bind = |labels: &[usize], coordinates: &[usize]| -> Option<Binding> {
+        let mut bound = HashMap::new();
+        for (&label, &coordinate) in labels.iter().zip(coordinates) {
+            if *bound.entry(label).or_insert(coordinate) != coordinate {
+                return None;
+            }
+        }
+        Some(bound)
+    };
+
+    let mut by_key: HashMap<Vec<usize>, Vec<(Binding, f64)>> = HashMap::new();
+    for (coordinates, value) in tensor_b.entries() {
+        if let Some(bound) = bind(indices_b, &coordinates) {
+            let key = shared.iter().map(|label| bound[label]).collect();
+            by_key.entry(key).or_default().push((bound, value));
+        }
+    }
+
+    let a_entries: Vec<(Vec<usize>, f64)> = tensor_a.entries().collect();
+    let sums = a_entries
+        .par_iter()
+        .fold(HashMap::new, |mut sums: HashMap<usize, f64>, (coordinates, a)| {
+            let Some(bound_a) = bind(indices_a, coordinates) else { return sums };
+            let key: Vec<usize> = shared.iter().map(|label| bound_a[label]).collect();
+            for (bound_b, b) in by_key.get(&key).into_iter().flatten() {
+                let offset: usize = output_indices.iter().zip(&output_strides)
+                    .map(|(label, stride)| bound_a.get(label).or_else(|| bound_b.get(label)).expect("label bound") * stride)
+                    .sum();
+                *sums.entry(offset).or_insert(0.0) += a * b;
+            }
+            sums
+        })
+        .reduce(HashMap::new, |mut merged, sums| {
+            for (offset, value) in sums {
+                *merged.entry(offset).or_insert(0.0) += value;
+            }
+            merged
+        });
+    Ok(SparseTensor::from_map(output_shape, sums))
+}
+
+/// Coordinate bound to each label of one stored entry
+type Binding = HashMap<usize, usize>;
+
+fn unique(labels: &[usize]) -> Vec<usize> {
+    let mut unique = Vec::new();
+    for &label in labels {
+        if !unique.contains(&label) {
+            unique.push(label);
+        }
+    }
+    unique
+}
+
+/// Compressed sparse row matrix
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsrMatrix {
+    rows: usize,
+    cols: usize,
+    /// Start of each row in `columns` and `values`, plus the total count
+    row_offsets: Vec<usize>,
+    columns: Vec<usize>,
+    values: Vec<f64>,
+}
+
+impl CsrMatrix {
+    /// `(rows, cols)`
+    pub fn dim(&self) -> (usize, usize) {
+        (self.rows, self.cols)
+    }
+
+    /// Number of stored non-zero entries
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Column indices and values stored in `row`
+    pub fn row(&self, row: usize) -> (&[usize], &[f64]) {
+        let range = self.row_offsets[row]..self.row_offsets[row + 1];
+        (&self.columns[range.clone()], &self.values[range])
+    }
+
+    /// Coordinate-format copy
+    pub fn to_sparse(&self) -> SparseTensor {
+        let offsets = (0..self.rows)
+            .flat_map(|row| self.row(row).0.iter().map(move |&col| row * self.cols + col))
+            .collect();
+        SparseTensor { shape: vec![self.rows, self.cols], offsets, values: self.values.clone() }
+    }
+
+    /// Sparse matrix product, e.g. the composition of two relations
+    pub fn matmul(&self, other: &CsrMatrix) -> Result<CsrMatrix, String> {
+        if self.cols != other.rows {
+            return Err(format!("Cannot multiply {:?} by {:?}", self.dim(), other.dim()));
+        }
+        let rows: Vec<Vec<(usize, f64)>> = (0..self.rows)
+            .into_par_iter()
+            .map(|row| {
+                let mut accumulator: HashMap<usize, f64> = HashMap::new();
+                let (columns, values) = self.row(row);
+                for (&k, &a) in columns.iter().zip(values) {
+                    let (other_columns, other_values) = other.row(k);
+                    for (&col, &b) in other_columns.iter().zip(other_values) {
+                        *accumulator.entry(col).or_insert(0.0) += a * b;
+                    }
+                }
+                let mut entries: Vec<(usize, f64)> = accumulator.into_iter().filter(|&(_, v)| v != 0.0).collect();
+                entries.sort_unstable_by_key(|&(col, _)| col);
+                entries
+            })
+            .collect();
+
+        let mut row_offsets = Vec::with_capacity(self.rows + 1);
+        row_offsets.push(0);
+        let (mut columns, mut values) = (Vec::new(), Vec::new());
+        for entries in rows {
+            for (col, value) in entries {
+                columns.push(col);
+                values.push(value);
+            }
+            row_offsets.push(columns.len());
+        }
+        Ok(CsrMatrix { rows: self.rows, cols: other.cols, row_offsets, columns, values })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tensor_ops::{einstein_summation, tensor_and, tensor_or, tensor_similarity};
+
+    fn relation() -> Tensor {
+        Tensor::new(vec![3, 3], vec![0.0, 1.0, 0.0, 0.0, 0.0, 0.5, 0.0, 0.0, 0.0])
+    }
+
+    #[test]
+    fn test_dense_roundtrip_and_csr() {
+        let dense = relation();
+        let sparse = SparseTensor::from_dense(&dense);
+        assert_eq!(sparse.nnz(), 2);
+        assert_eq!(sparse.to_dense().data, dense.data);
+
+        let csr = sparse.to_csr().unwrap();
+        assert_eq!(csr.row(1), (&[2usize][..], &[0.5][..]));
+        assert_eq!(csr.to_sparse(), sparse);
+
+        // Composition: 0 -> 1 -> 2
+        let composed = csr.matmul(&csr).unwrap().to_sparse();
+        assert_eq!(composed.entries().collect::<Vec<_>>(), vec![(vec![0, 2], 0.5)]);
+    }
+
+    #[test]
+    fn test_sparse_ops_match_dense() {
+        let a = relation();
+        let b = Tensor::new(vec![3, 3], vec![0.0, 2.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, -1.0]);
+        let (sa, sb) = (SparseTensor::from_dense(&a), SparseTensor::from_dense(&b));
+
+        assert_eq!(sparse_and(&sa, &sb).unwrap().to_dense().data, tensor_and(&a, &b).unwrap().data);
+        assert_eq!(sparse_or(&sa, &sb).unwrap().to_dense().data, tensor_or(&a, &b).unwrap().data);
+        assert!((sparse_similarity(&sa, &sb) - tensor_similarity(&a, &b)).abs() < 1e-12);
+
+        let dense = einstein_summation(&a, &b, &[0, 1], &[1, 2], &[2, 0]).unwrap();
+        let sparse = sparse_contract(&sa, &sb, &[0, 1], &[1, 2], &[2, 0]).unwrap();
+        assert_eq!(sparse.to_dense().data, dense.data);
+
+        let trace = sparse_contract(&sb, &SparseTensor::from_dense(&Tensor::new(vec![], vec![1.0])), &[0, 0], &[], &[]);
+        assert_eq!(trace.unwrap().to_dense().data, vec![-1.0]);
+    }
+}
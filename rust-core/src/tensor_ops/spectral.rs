@@ -0,0 +1,175 @@
+//! Spectral Operations - Fourier transforms along a tensor axis
+//!
+//! Transforms run independently over every lane of the chosen axis, in parallel,
+//! using `rustfft` plans that work for any length. Spectra are `ComplexTensor`s;
+//! `ifft` divides by the length, so `ifft(fft(x))` reproduces `x`. `rfft` keeps only
+//! the `n / 2 + 1` non-redundant frequencies of a real input.
+
+use num_complex::Complex64;
+use rayon::prelude::*;
+use rustfft::FftPlanner;
+use tracing::instrument;
+
+use super::Tensor;
+
+/// Tensor of complex numbers, stored row-major
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComplexTensor {
+    pub shape: Vec<usize>,
+    pub data: Vec<Complex64>,
+}
+
+impl ComplexTensor {
+    pub fn new(shape: Vec<usize>, data: Vec<Complex64>) -> Self {
+        assert_eq!(shape.iter().product::<usize>(), data.len(), "Data size doesn't match shape");
+        Self { shape, data }
+    }
+
+    /// Complex tensor with the values of `tensor` as real parts
+    pub fn from_real(tensor: &Tensor) -> Self {
+        Self::new(tensor.shape.clone(), tensor.data.iter().map(|&x| Complex64::new(x, 0.0)).collect())
+    }
+
+    pub fn real(&self) -> Tensor {
+        self.map(|z| z.re)
+    }
+
+    pub fn imag(&self) -> Tensor {
+        self.map(|z| z.im)
+    }
+
+    /// Element-wise modulus `|z|`
+    pub fn abs(&self) -> Tensor {
+        self.map(|z| z.norm())
+    }
+
+    fn map(&self, f: impl Fn(&Complex64) -> f64 + Sync) -> Tensor {
+        Tensor::new(self.shape.clone(), self.data.par_iter().map(&f).collect())
+    }
+}
+
+/// Transform every lane along `axis`, producing `output_len` values per lane
+fn transform_lanes(
+    shape: &[usize],
+    axis: usize,
+    output_len: usize,
+    lane: impl Fn(usize, usize) -> Vec<Complex64> + Sync,
+) -> Result<ComplexTensor, String> {
+    if axis >= shape.len() {
+        return Err(format!("Axis {} out of range for rank {}", axis, shape.len()));
+    }
+    let outer: usize = shape[..axis].iter().product();
+    let inner: usize = shape[axis + 1..].iter().product();
+
+    // Empty lanes have nothing to transform
+    let lane_count = if output_len == 0 { 0 } else { outer * inner };
+    let lanes: Vec<Vec<Complex64>> = (0..lane_count).into_par_iter().map(|l| lane(l / inner, l % inner)).collect();
+    let mut data = vec![Complex64::default(); outer * output_len * inner];
+    for (l, values) in lanes.into_iter().enumerate() {
+        let (o, i) = (l / inner, l % inner);
+        for (k, value) in values.into_iter().enumerate() {
+            data[(o * output_len + k) * inner + i] = value;
+        }
+    }
+
+    let mut output_shape = shape.to_vec();
+    output_shape[axis] = output_len;
+    Ok(ComplexTensor::new(output_shape, data))
+}
+
+/// Complex transform along `axis`, forward or unnormalized inverse
+fn complex_fft(input: &ComplexTensor, axis: usize, inverse: bool) -> Result<ComplexTensor, String> {
+    let n = *input.shape.get(axis).ok_or_else(|| format!("Axis {} out of range for rank {}", axis, input.shape.len()))?;
+    let inner: usize = input.shape[axis + 1..].iter().product();
+    let mut planner = FftPlanner::new();
+    let plan = if inverse { planner.plan_fft_inverse(n) } else { planner.plan_fft_forward(n) };
+    let scale = if inverse && n > 0 { 1.0 / n as f64 } else { 1.0 };
+
+    transform_lanes(&input.shape, axis, n, |o, i| {
+        let mut buffer: Vec<Complex64> = (0..n).map(|k| input.data[(o * n + k) * inner + i]).collect();
+        plan.process(&mut buffer);
+        if scale != 1.0 {
+            buffer.iter_mut().for_each(|z| *z *= scale);
+        }
+        buffer
+    })
+}
+
+/// Discrete Fourier transform along `axis`
+#[instrument(skip(input))]
+pub fn fft(input: &ComplexTensor, axis: usize) -> Result<ComplexTensor, String> {
+    complex_fft(input, axis, false)
+}
+
+/// Inverse discrete Fourier transform along `axis`, scaled by `1 / n`
+#[instrument(skip(input))]
+pub fn ifft(input: &ComplexTensor, axis: usize) -> Result<ComplexTensor, String> {
+    complex_fft(input, axis, true)
+}
+
+/// Fourier transform of a real tensor along `axis`, keeping frequencies `0..=n / 2`
+#[instrument(skip(input))]
+pub fn rfft(input: &Tensor, axis: usize) -> Result<ComplexTensor, String> {
+    let n = *input.shape.get(axis).ok_or_else(|| format!("Axis {} out of range for rank {}", axis, input.rank))?;
+    let inner: usize = input.shape[axis + 1..].iter().product();
+    let plan = FftPlanner::new().plan_fft_forward(n);
+    let kept = if n == 0 { 0 } else { n / 2 + 1 };
+
+    transform_lanes(&input.shape, axis, kept, |o, i| {
+        let mut buffer: Vec<Complex64> = (0..n).map(|k| Complex64::new(input.data[(o * n + k) * inner + i], 0.0)).collect();
+        plan.process(&mut buffer);
+        buffer.truncate(kept);
+        buffer
+    })
+}
+
+/// Power spectrum `|X_k|² / n` of a real tensor along `axis`, for frequencies
+/// `0..=n / 2`
+#[instrument(skip(input))]
+pub fn power_spectrum(input: &Tensor, axis: usize) -> Result<Tensor, String> {
+    let n = input.shape.get(axis).copied().unwrap_or(0).max(1) as f64;
+    let spectrum = rfft(input, axis)?;
+    Ok(spectrum.map(|z| z.norm_sqr() / n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_fft_round_trip_along_axis() {
+        let tensor = Tensor::new(vec![3, 4, 2], (0..24).map(|x| (x as f64 * 0.7).sin()).collect());
+        for axis in 0..3 {
+            let spectrum = fft(&ComplexTensor::from_real(&tensor), axis).unwrap();
+            assert_eq!(spectrum.shape, tensor.shape);
+            let back = ifft(&spectrum, axis).unwrap();
+            assert!(back.real().data.iter().zip(&tensor.data).all(|(x, y)| (x - y).abs() < 1e-12));
+            assert!(back.imag().data.iter().all(|x| x.abs() < 1e-12));
+        }
+        assert!(fft(&ComplexTensor::from_real(&tensor), 3).is_err());
+    }
+
+    #[test]
+    fn test_rfft_and_power_spectrum() {
+        // Two periods of a cosine over 8 samples along axis 1, for each of 2 rows
+        let n = 8;
+        let data: Vec<f64> = (0..2 * n).map(|i| (2.0 * PI * 2.0 * (i % n) as f64 / n as f64).cos()).collect();
+        let signal = Tensor::new(vec![2, n], data);
+
+        let spectrum = rfft(&signal, 1).unwrap();
+        assert_eq!(spectrum.shape, vec![2, 5]);
+        let magnitude = spectrum.abs();
+        for (k, &m) in magnitude.data[..5].iter().enumerate() {
+            let expected = if k == 2 { 4.0 } else { 0.0 };
+            assert!((m - expected).abs() < 1e-9, "bin {}: {}", k, m);
+        }
+
+        let full = fft(&ComplexTensor::from_real(&signal), 1).unwrap();
+        assert!(full.data[..5].iter().zip(&spectrum.data[..5]).all(|(a, b)| (a - b).norm() < 1e-12));
+
+        let power = power_spectrum(&signal, 1).unwrap();
+        assert!((power.data[2] - 2.0).abs() < 1e-9);
+        assert!((power.data[7] - 2.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,244 @@
+//! Numerical Stability - Compensated sums, log-sum-exp and non-finite checks
+//!
+//! Plain accumulation loses low-order bits once a running sum dwarfs its terms,
+//! which shows up as drift in the similarity of long tensors. The helpers here
+//! carry a Neumaier compensation term per rayon chunk and merge the chunk results
+//! the same way, so the error stays near one rounding regardless of length, and
+//! scale norms by the largest magnitude so squaring cannot overflow.
+//!
+//! The epsilon below which a norm counts as zero is process-wide and defaults to
+//! `DEFAULT_EPSILON`. In checked mode the core logic ops reject results holding
+//! NaN or infinity, reporting where they occurred, instead of passing them on.
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use rayon::prelude::*;
+
+use super::{broadcast_zip, Tensor};
+
+/// Norms at or below this are treated as zero unless `set_epsilon` changes it
+pub const DEFAULT_EPSILON: f64 = 1e-10;
+
+/// Elements summed sequentially by one rayon task
+const CHUNK: usize = 1 << 14;
+
+static EPSILON_BITS: AtomicU64 = AtomicU64::new(DEFAULT_EPSILON.to_bits());
+static CHECKED: AtomicBool = AtomicBool::new(false);
+
+/// Current zero-norm threshold
+pub fn epsilon() -> f64 {
+    f64::from_bits(EPSILON_BITS.load(Ordering::Relaxed))
+}
+
+/// Set the zero-norm threshold used by normalization and similarity
+pub fn set_epsilon(epsilon: f64) -> Result<(), String> {
+    if !epsilon.is_finite() || epsilon < 0.0 {
+        return Err(format!("Epsilon {} must be finite and non-negative", epsilon));
+    }
+    EPSILON_BITS.store(epsilon.to_bits(), Ordering::Relaxed);
+    Ok(())
+}
+
+/// Whether checked mode is on
+pub fn is_checked() -> bool {
+    CHECKED.load(Ordering::Relaxed)
+}
+
+/// Turn checked mode on or off for every thread
+pub fn set_checked(checked: bool) {
+    CHECKED.store(checked, Ordering::Relaxed);
+}
+
+/// Running sum with a Neumaier compensation term
+#[derive(Debug, Clone, Copy, Default)]
+struct Compensated {
+    sum: f64,
+    compensation: f64,
+}
+
+impl Compensated {
+    fn add(mut self, value: f64) -> Self {
+        let total = self.sum + value;
+        self.compensation += if self.sum.abs() >= value.abs() {
+            (self.sum - total) + value
+        } else {
+            (value - total) + self.sum
+        };
+        self.sum = total;
+        self
+    }
+
+    fn merge(self, other: Self) -> Self {
+        let merged = self.add(other.sum);
+        Self { compensation: merged.compensation + other.compensation, ..merged }
+    }
+
+    fn value(self) -> f64 {
+        self.sum + self.compensation
+    }
+}
+
+/// Compensated sum of a sequence
+pub fn kahan_sum(values: impl IntoIterator<Item = f64>) -> f64 {
+    values.into_iter().fold(Compensated::default(), Compensated::add).value()
+}
+
+/// Pairwise (cascade) sum of a slice, splitting the halves across threads
+pub fn pairwise_sum(values: &[f64]) -> f64 {
+    if values.len() <= 64 {
+        return values.iter().sum();
+    }
+    let (left, right) = values.split_at(values.len() / 2);
+    let (left, right) = rayon::join(|| pairwise_sum(left), || pairwise_sum(right));
+    left + right
+}
+
+/// Compensated dot product over the common length of `a` and `b`
+pub fn stable_dot(a: &[f64], b: &[f64]) -> f64 {
+    a.par_chunks(CHUNK)
+        .zip(b.par_chunks(CHUNK))
+        .map(|(a, b)| a.iter().zip(b).fold(Compensated::default(), |sum, (x, y)| sum.add(x * y)))
+        .reduce(Compensated::default, Compensated::merge)
+        .value()
+}
+
+/// L2 norm without overflow or underflow in the squares
+pub fn stable_norm(data: &[f64]) -> f64 {
+    let scale = data.par_iter().map(|x| x.abs()).reduce(|| 0.0, f64::max);
+    if scale == 0.0 || !scale.is_finite() {
+        return scale;
+    }
+    let squares = data
+        .par_chunks(CHUNK)
+        .map(|chunk| chunk.iter().fold(Compensated::default(), |sum, x| sum.add((x / scale) * (x / scale))))
+        .reduce(Compensated::default, Compensated::merge)
+        .value();
+    scale * squares.sqrt()
+}
+
+/// `ln(Σ exp(x))` without overflow; `-inf` for an empty slice
+pub fn logsumexp(values: &[f64]) -> f64 {
+    let peak = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    if peak.is_infinite() {
+        return peak;
+    }
+    peak + kahan_sum(values.iter().map(|x| (x - peak).exp())).ln()
+}
+
+/// Location and value of each NaN or infinity in a tensor
+#[derive(Debug, Clone, PartialEq)]
+pub struct NonFiniteReport {
+    /// Multi-index and value of each offending element, in row-major order
+    pub locations: Vec<(Vec<usize>, f64)>,
+}
+
+impl fmt::Display for NonFiniteReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} non-finite element(s)", self.locations.len())?;
+        for (index, value) in self.locations.iter().take(8) {
+            write!(f, ", {} at {:?}", value, index)?;
+        }
+        if self.locations.len() > 8 {
+            write!(f, ", ...")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for NonFiniteReport {}
+
+impl Tensor {
+    /// Report every NaN and infinity, or `Ok` when all elements are finite
+    pub fn check_finite(&self) -> Result<(), NonFiniteReport> {
+        let strides = self.strides();
+        let locations: Vec<(Vec<usize>, f64)> = self.data
+            .par_iter()
+            .enumerate()
+            .filter(|(_, value)| !value.is_finite())
+            .map(|(position, &value)| {
+                let index = strides.iter().zip(&self.shape).map(|(&stride, &dim)| position / stride % dim).collect();
+                (index, value)
+            })
+            .collect();
+        if locations.is_empty() {
+            Ok(())
+        } else {
+            Err(NonFiniteReport { locations })
+        }
+    }
+
+    /// `ln(Σ exp(x))` over `axes` (every axis when empty), shifted by the maximum
+    pub fn logsumexp(&self, axes: &[usize], keepdim: bool) -> Result<Tensor, String> {
+        let peak = self.max(axes, true)?;
+        // An infinite peak would make `x - peak` NaN; it dominates the result anyway
+        let shifted = broadcast_zip(self, &peak, |x, m| {
+            if m.is_infinite() {
+                if x == m { 1.0 } else { 0.0 }
+            } else {
+                (x - m).exp()
+            }
+        })?;
+        let mut result = shifted.sum(axes, keepdim)?;
+        result.data.par_iter_mut().zip(&peak.data).for_each(|(s, &m)| *s = s.ln() + m);
+        Ok(result)
+    }
+}
+
+/// Pass `result` through, or reject it with its non-finite locations in checked mode
+pub(super) fn checked(operation: &str, result: Result<Tensor, String>) -> Result<Tensor, String> {
+    let tensor = result?;
+    if is_checked() {
+        if let Err(report) = tensor.check_finite() {
+            return Err(format!("{} produced {}", operation, report));
+        }
+    }
+    Ok(tensor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compensated_sums() {
+        // 1 followed by many terms too small to register in a plain running sum
+        let mut values = vec![1.0];
+        values.extend(vec![1e-16; 100_000]);
+        let exact = 1.0 + 1e-11;
+        assert!((kahan_sum(values.iter().copied()) - exact).abs() < 1e-15);
+        assert!((values.iter().sum::<f64>() - exact).abs() > 1e-12);
+        assert!((pairwise_sum(&values) - exact).abs() < 1e-14);
+
+        let ones = vec![1.0; values.len()];
+        assert!((stable_dot(&values, &ones) - exact).abs() < 1e-15);
+        assert!((stable_norm(&[3e200, 4e200]) / 5e200 - 1.0).abs() < 1e-15);
+        assert_eq!(stable_norm(&[0.0; 4]), 0.0);
+    }
+
+    #[test]
+    fn test_logsumexp() {
+        assert!((logsumexp(&[1000.0, 1000.0]) - (1000.0 + 2f64.ln())).abs() < 1e-12);
+        assert_eq!(logsumexp(&[]), f64::NEG_INFINITY);
+        assert_eq!(logsumexp(&[f64::NEG_INFINITY, 0.0]), 0.0);
+
+        let tensor = Tensor::new(vec![2, 2], vec![0.0, 0.0, 800.0, f64::NEG_INFINITY]);
+        let rows = tensor.logsumexp(&[1], false).unwrap();
+        assert_eq!(rows.shape, vec![2]);
+        assert!((rows.data[0] - 2f64.ln()).abs() < 1e-12);
+        assert_eq!(rows.data[1], 800.0);
+    }
+
+    #[test]
+    fn test_check_finite_reports_locations() {
+        let tensor = Tensor::new(vec![2, 3], vec![0.0, f64::NAN, 1.0, 2.0, 3.0, f64::INFINITY]);
+        let report = tensor.check_finite().unwrap_err();
+        assert_eq!(report.locations.len(), 2);
+        assert_eq!(report.locations[0].0, vec![0, 1]);
+        assert_eq!(report.locations[1], (vec![1, 2], f64::INFINITY));
+        assert!(report.to_string().starts_with("2 non-finite element(s)"));
+        assert!(Tensor::ones(&[3]).check_finite().is_ok());
+        assert_eq!(DEFAULT_EPSILON, epsilon());
+        assert!(set_epsilon(-1.0).is_err());
+    }
+}
@@ -0,0 +1,148 @@
+//! Unification - Combining several tensors into one representation
+//!
+//! `unify_tensors` averages, which lets a single outlier drag the result. The
+//! other `UnifyStrategy` variants either weight the inputs explicitly, weight them
+//! by attention to a query, or use a robust per-element statistic (median,
+//! trimmed mean, maximum) that an outlier cannot dominate.
+
+use rayon::prelude::*;
+use tracing::instrument;
+
+use super::{stable, tensor_similarity, Tensor};
+
+/// How `unify_tensors_with` combines its inputs element by element
+#[derive(Debug, Clone)]
+pub enum UnifyStrategy {
+    /// Arithmetic mean
+    Mean,
+    /// Mean weighted by one non-negative weight per tensor
+    Weighted(Vec<f64>),
+    /// Median, averaging the middle pair for an even count
+    Median,
+    /// Mean after dropping `fraction` of the values from each end
+    TrimmedMean { fraction: f64 },
+    /// Maximum
+    MaxPool,
+    /// Mean weighted by the softmax of each tensor's cosine similarity to `query`
+    Attention { query: Tensor, temperature: f64 },
+}
+
+/// Unify equally shaped tensors under `strategy`
+#[instrument(skip(tensors, strategy))]
+pub fn unify_tensors_with(tensors: &[Tensor], strategy: &UnifyStrategy) -> Result<Tensor, String> {
+    let Some(first) = tensors.first() else {
+        return Err("Cannot unify empty tensor list".to_string());
+    };
+    if tensors.iter().skip(1).any(|tensor| tensor.shape != first.shape) {
+        return Err("All tensors must have the same shape for unification".to_string());
+    }
+
+    let data = match strategy {
+        UnifyStrategy::Mean => weighted(tensors, &vec![1.0; tensors.len()]),
+        UnifyStrategy::Weighted(weights) => {
+            if weights.len() != tensors.len() {
+                return Err(format!("Expected {} weights, got {}", tensors.len(), weights.len()));
+            }
+            if weights.iter().any(|w| !w.is_finite() || *w < 0.0) {
+                return Err("Unification weights must be finite and non-negative".to_string());
+            }
+            if weights.iter().sum::<f64>() <= 0.0 {
+                return Err("Unification weights must not all be zero".to_string());
+            }
+            weighted(tensors, weights)
+        }
+        UnifyStrategy::Attention { query, temperature } => {
+            if query.data.len() != first.data.len() {
+                return Err(format!("Query has {} elements, tensors have {}", query.data.len(), first.data.len()));
+            }
+            if temperature.is_nan() || *temperature <= 0.0 {
+                return Err(format!("Attention temperature {} must be positive", temperature));
+            }
+            let scores: Vec<f64> = tensors.iter().map(|t| tensor_similarity(query, t) / temperature).collect();
+            let peak = scores.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            let weights: Vec<f64> = scores.iter().map(|s| (s - peak).exp()).collect();
+            weighted(tensors, &weights)
+        }
+        UnifyStrategy::Median => per_element(tensors, |values| {
+            let middle = values.len() / 2;
+            if values.len() % 2 == 1 {
+                values[middle]
+            } else {
+                (values[middle - 1] + values[middle]) / 2.0
+            }
+        }),
+        UnifyStrategy::TrimmedMean { fraction } => {
+            if !(0.0..0.5).contains(fraction) {
+                return Err(format!("Trim fraction {} must be in [0, 0.5)", fraction));
+            }
+            let trim = (tensors.len() as f64 * fraction).floor() as usize;
+            per_element(tensors, |values| {
+                let kept = &values[trim..values.len() - trim];
+                kept.iter().sum::<f64>() / kept.len() as f64
+            })
+        }
+        UnifyStrategy::MaxPool => per_element(tensors, |values| values[values.len() - 1]),
+    };
+
+    stable::checked("Unification", Ok(Tensor::new(first.shape.clone(), data)))
+}
+
+/// Normalized weighted sum of the tensors
+fn weighted(tensors: &[Tensor], weights: &[f64]) -> Vec<f64> {
+    let total: f64 = weights.iter().sum();
+    (0..tensors[0].data.len())
+        .into_par_iter()
+        .map(|i| tensors.iter().zip(weights).map(|(t, w)| t.data[i] * w).sum::<f64>() / total)
+        .collect()
+}
+
+/// Apply `statistic` to the sorted values at each position
+fn per_element(tensors: &[Tensor], statistic: impl Fn(&[f64]) -> f64 + Sync) -> Vec<f64> {
+    (0..tensors[0].data.len())
+        .into_par_iter()
+        .map_init(Vec::new, |values, i| {
+            values.clear();
+            values.extend(tensors.iter().map(|t| t.data[i]));
+            values.sort_unstable_by(f64::total_cmp);
+            statistic(values)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(values: &[f64]) -> Vec<Tensor> {
+        values.iter().map(|&v| Tensor::new(vec![2], vec![v, -v])).collect()
+    }
+
+    #[test]
+    fn test_robust_strategies_ignore_outlier() {
+        let tensors = column(&[1.0, 2.0, 3.0, 4.0, 100.0]);
+        let unify = |strategy| unify_tensors_with(&tensors, &strategy).unwrap().data;
+        assert_eq!(unify(UnifyStrategy::Mean), vec![22.0, -22.0]);
+        assert_eq!(unify(UnifyStrategy::Median), vec![3.0, -3.0]);
+        assert_eq!(unify(UnifyStrategy::TrimmedMean { fraction: 0.2 }), vec![3.0, -3.0]);
+        assert_eq!(unify(UnifyStrategy::MaxPool), vec![100.0, -1.0]);
+        assert_eq!(unify_tensors_with(&column(&[1.0, 4.0]), &UnifyStrategy::Median).unwrap().data[0], 2.5);
+        assert!(unify_tensors_with(&tensors, &UnifyStrategy::TrimmedMean { fraction: 0.5 }).is_err());
+    }
+
+    #[test]
+    fn test_weighted_and_attention() {
+        let tensors = vec![Tensor::new(vec![2], vec![1.0, 0.0]), Tensor::new(vec![2], vec![0.0, 1.0])];
+        let weighted = unify_tensors_with(&tensors, &UnifyStrategy::Weighted(vec![3.0, 1.0])).unwrap();
+        assert_eq!(weighted.data, vec![0.75, 0.25]);
+        assert!(unify_tensors_with(&tensors, &UnifyStrategy::Weighted(vec![1.0])).is_err());
+        assert!(unify_tensors_with(&tensors, &UnifyStrategy::Weighted(vec![0.0, 0.0])).is_err());
+        assert!(unify_tensors_with(&tensors, &UnifyStrategy::Weighted(vec![-1.0, 2.0])).is_err());
+
+        // A sharp query attends almost entirely to the matching tensor
+        let query = Tensor::new(vec![2], vec![1.0, 0.0]);
+        let sharp = UnifyStrategy::Attention { query: query.clone(), temperature: 0.01 };
+        assert!(unify_tensors_with(&tensors, &sharp).unwrap().data[0] > 0.999);
+        let flat = UnifyStrategy::Attention { query, temperature: 1e6 };
+        assert!((unify_tensors_with(&tensors, &flat).unwrap().data[0] - 0.5).abs() < 1e-6);
+    }
+}
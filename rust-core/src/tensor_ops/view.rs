@@ -0,0 +1,317 @@
+//! Tensor Views - Borrowed, strided windows onto tensor data
+//!
+//! A `TensorView` borrows a buffer and reads it through a starting offset and
+//! per-axis strides, so slicing, permuting, transposing and broadcasting only
+//! compute new strides rather than copying data. Views can also wrap foreign
+//! memory, which lets FFI callers run logic ops on their buffers in place.
+
+use std::ops::Range;
+
+use rayon::prelude::*;
+use tracing::instrument;
+
+use super::{broadcast_shape, row_major_strides, simd, Tensor};
+
+/// A borrowed, possibly non-contiguous tensor
+#[derive(Debug, Clone)]
+pub struct TensorView<'a> {
+    data: &'a [f64],
+    offset: usize,
+    shape: Vec<usize>,
+    strides: Vec<usize>,
+}
+
+impl<'a> TensorView<'a> {
+    /// View a row-major buffer with the given shape
+    pub fn new(data: &'a [f64], shape: &[usize]) -> Result<Self, String> {
+        let size: usize = shape.iter().product();
+        if size != data.len() {
+            return Err(format!("Shape {:?} needs {} elements, got {}", shape, size, data.len()));
+        }
+        Ok(Self { data, offset: 0, shape: shape.to_vec(), strides: row_major_strides(shape) })
+    }
+
+    /// View `data` starting at `offset` with arbitrary strides, checking that every
+    /// element is in bounds
+    pub fn with_strides(data: &'a [f64], offset: usize, shape: &[usize], strides: &[usize]) -> Result<Self, String> {
+        if shape.len() != strides.len() {
+            return Err(format!("{} strides given for rank {}", strides.len(), shape.len()));
+        }
+        if let Some(extent) = extent(shape, strides) {
+            if offset + extent > data.len() {
+                return Err(format!(
+                    "View of shape {:?} with strides {:?} at offset {} exceeds buffer of {} elements",
+                    shape, strides, offset, data.len()
+                ));
+            }
+        }
+        Ok(Self { data, offset, shape: shape.to_vec(), strides: strides.to_vec() })
+    }
+
+    /// View foreign memory without copying it
+    ///
+    /// # Safety
+    /// `ptr` must be valid for reads of every element the shape and strides reach,
+    /// and the memory must not be written for the lifetime `'a`.
+    pub unsafe fn from_raw_parts(ptr: *const f64, shape: &[usize], strides: &[usize]) -> Self {
+        let len = extent(shape, strides).unwrap_or(0);
+        let data = if len == 0 { &[] } else { std::slice::from_raw_parts(ptr, len) };
+        Self { data, offset: 0, shape: shape.to_vec(), strides: strides.to_vec() }
+    }
+
+    pub fn shape(&self) -> &[usize] {
+        &self.shape
+    }
+
+    pub fn strides(&self) -> &[usize] {
+        &self.strides
+    }
+
+    pub fn rank(&self) -> usize {
+        self.shape.len()
+    }
+
+    pub fn size(&self) -> usize {
+        self.shape.iter().product()
+    }
+
+    /// Whether the elements are laid out row-major without gaps
+    pub fn is_contiguous(&self) -> bool {
+        let mut expected = 1;
+        for (&dim, &stride) in self.shape.iter().zip(&self.strides).rev() {
+            if dim != 1 && stride != expected {
+                return false;
+            }
+            expected *= dim;
+        }
+        true
+    }
+
+    /// The elements as one slice, when contiguous
+    pub fn as_slice(&self) -> Option<&'a [f64]> {
+        self.is_contiguous().then(|| &self.data[self.offset..self.offset + self.size()])
+    }
+
+    /// The element at `coordinates`, or `None` when out of bounds
+    pub fn get(&self, coordinates: &[usize]) -> Option<f64> {
+        if coordinates.len() != self.rank() || coordinates.iter().zip(&self.shape).any(|(&c, &dim)| c >= dim) {
+            return None;
+        }
+        let offset: usize = coordinates.iter().zip(&self.strides).map(|(c, stride)| c * stride).sum();
+        Some(self.data[self.offset + offset])
+    }
+
+    /// Buffer offset of the element at a row-major position
+    fn offset_of(&self, mut index: usize) -> usize {
+        let mut offset = self.offset;
+        for (&dim, &stride) in self.shape.iter().zip(&self.strides).rev() {
+            offset += (index % dim) * stride;
+            index /= dim;
+        }
+        offset
+    }
+
+    /// The element at a row-major position
+    pub(super) fn at(&self, index: usize) -> f64 {
+        self.data[self.offset_of(index)]
+    }
+
+    /// Sub-view covering `ranges` of the leading axes; later axes are kept whole
+    pub fn slice(&self, ranges: &[Range<usize>]) -> Result<Self, String> {
+        if ranges.len() > self.rank() {
+            return Err(format!("{} ranges given for rank {}", ranges.len(), self.rank()));
+        }
+        let mut view = self.clone();
+        for (axis, range) in ranges.iter().enumerate() {
+            if range.start > range.end || range.end > self.shape[axis] {
+                return Err(format!("Range {:?} out of bounds for axis {} of extent {}", range, axis, self.shape[axis]));
+            }
+            view.shape[axis] = range.end - range.start;
+            if view.shape[axis] > 0 {
+                view.offset += range.start * self.strides[axis];
+            }
+        }
+        Ok(view)
+    }
+
+    /// Reorder axes so that axis `i` of the result is axis `axes[i]` of `self`
+    pub fn permute(&self, axes: &[usize]) -> Result<Self, String> {
+        let rank = self.rank();
+        let mut seen = vec![false; rank];
+        if axes.len() != rank || !axes.iter().all(|&axis| axis < rank && !std::mem::replace(&mut seen[axis], true)) {
+            return Err(format!("{:?} is not a permutation of {} axes", axes, rank));
+        }
+        Ok(Self {
+            data: self.data,
+            offset: self.offset,
+            shape: axes.iter().map(|&axis| self.shape[axis]).collect(),
+            strides: axes.iter().map(|&axis| self.strides[axis]).collect(),
+        })
+    }
+
+    /// Swap two axes
+    pub fn transpose(&self, axis_a: usize, axis_b: usize) -> Result<Self, String> {
+        if axis_a >= self.rank() || axis_b >= self.rank() {
+            return Err(format!("Axes {} and {} out of range for rank {}", axis_a, axis_b, self.rank()));
+        }
+        let mut axes: Vec<usize> = (0..self.rank()).collect();
+        axes.swap(axis_a, axis_b);
+        self.permute(&axes)
+    }
+
+    /// Repeat size-one and missing leading axes to match `shape`, with zero strides
+    pub fn broadcast_to(&self, shape: &[usize]) -> Result<Self, String> {
+        if shape.len() < self.rank() || broadcast_shape(&self.shape, shape)? != shape {
+            return Err(format!("Cannot broadcast {:?} to {:?}", self.shape, shape));
+        }
+        let padding = shape.len() - self.rank();
+        let strides = (0..shape.len())
+            .map(|axis| match axis.checked_sub(padding) {
+                Some(own) if self.shape[own] == shape[axis] => self.strides[own],
+                _ => 0,
+            })
+            .collect();
+        Ok(Self { data: self.data, offset: self.offset, shape: shape.to_vec(), strides })
+    }
+
+    /// Copy the viewed elements into an owned, contiguous tensor
+    pub fn to_tensor(&self) -> Tensor {
+        let data = match self.as_slice() {
+            Some(slice) => slice.to_vec(),
+            None => (0..self.size()).into_par_iter().map(|index| self.at(index)).collect(),
+        };
+        Tensor::new(self.shape.clone(), data)
+    }
+}
+
+/// One past the largest offset reached by `shape` and `strides`, or `None` when
+/// the view is empty
+fn extent(shape: &[usize], strides: &[usize]) -> Option<usize> {
+    if shape.contains(&0) {
+        return None;
+    }
+    Some(1 + shape.iter().zip(strides).map(|(&dim, &stride)| (dim - 1) * stride).sum::<usize>())
+}
+
+impl Tensor {
+    /// Borrow the tensor as a contiguous view
+    pub fn view(&self) -> TensorView<'_> {
+        TensorView { data: &self.data, offset: 0, shape: self.shape.clone(), strides: self.strides() }
+    }
+}
+
+impl<'a> From<&'a Tensor> for TensorView<'a> {
+    fn from(tensor: &'a Tensor) -> Self {
+        tensor.view()
+    }
+}
+
+/// Combine two views element-wise with broadcasting, using the SIMD `kernel` when
+/// both are contiguous and of equal shape
+fn zip_views(
+    view_a: &TensorView,
+    view_b: &TensorView,
+    kernel: simd::BinaryKernel,
+    op: fn(f64, f64) -> f64,
+) -> Result<Tensor, String> {
+    if view_a.shape == view_b.shape {
+        if let (Some(a), Some(b)) = (view_a.as_slice(), view_b.as_slice()) {
+            return Ok(Tensor::new(view_a.shape.clone(), simd::par_binary(a, b, kernel)));
+        }
+    }
+
+    let shape = broadcast_shape(&view_a.shape, &view_b.shape)?;
+    let (a, b) = (view_a.broadcast_to(&shape)?, view_b.broadcast_to(&shape)?);
+    let size: usize = shape.iter().product();
+    let data = (0..size)
+        .into_par_iter()
+        .map(|index| op(a.at(index), b.at(index)))
+        .collect();
+    Ok(Tensor::new(shape, data))
+}
+
+/// Logical AND (element-wise product) of two views
+#[instrument(skip(view_a, view_b))]
+pub fn view_and(view_a: &TensorView, view_b: &TensorView) -> Result<Tensor, String> {
+    zip_views(view_a, view_b, simd::mul, |a, b| a * b)
+}
+
+/// Logical OR (normalized element-wise max) of two views
+#[instrument(skip(view_a, view_b))]
+pub fn view_or(view_a: &TensorView, view_b: &TensorView) -> Result<Tensor, String> {
+    let mut result = zip_views(view_a, view_b, simd::max, f64::max)?;
+    result.normalize();
+    Ok(result)
+}
+
+/// Logical IMPLIES, `max(1 - a, b)`, of two views
+#[instrument(skip(view_a, view_b))]
+pub fn view_implies(view_a: &TensorView, view_b: &TensorView) -> Result<Tensor, String> {
+    zip_views(view_a, view_b, simd::implies, |a, b| (1.0 - a).max(b))
+}
+
+/// Logical NOT, `1 - a`, of a view
+#[instrument(skip(view))]
+pub fn view_not(view: &TensorView) -> Tensor {
+    match view.as_slice() {
+        Some(data) => Tensor::new(view.shape.clone(), simd::par_not(data)),
+        None => {
+            let data = (0..view.size()).into_par_iter().map(|index| 1.0 - view.at(index)).collect();
+            Tensor::new(view.shape.clone(), data)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tensor_ops::{tensor_and, tensor_implies, tensor_or};
+
+    #[test]
+    fn test_views_share_data() {
+        let tensor = Tensor::new(vec![2, 3], vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let view = tensor.view();
+        assert!(view.is_contiguous());
+        assert_eq!(view.as_slice().unwrap().as_ptr(), tensor.data.as_ptr());
+
+        let transposed = view.transpose(0, 1).unwrap();
+        assert_eq!(transposed.shape(), &[3, 2]);
+        assert_eq!(transposed.strides(), &[1, 3]);
+        assert!(!transposed.is_contiguous());
+        assert_eq!(transposed.get(&[2, 1]), Some(6.0));
+        assert_eq!(transposed.to_tensor().data, tensor.transpose(0, 1).unwrap().data);
+
+        let column = view.slice(&[0..2, 1..2]).unwrap();
+        assert_eq!(column.to_tensor().data, vec![2.0, 5.0]);
+        assert_eq!(column.to_tensor().data, tensor.slice(&[0..2, 1..2]).unwrap().data);
+        let rows = view.slice(&[1..2, 0..3]).unwrap();
+        assert_eq!(rows.as_slice(), Some(&tensor.data[3..]));
+
+        let repeated = column.broadcast_to(&[2, 2, 4]).unwrap();
+        assert_eq!(repeated.strides(), &[0, 3, 0]);
+        assert_eq!(repeated.get(&[1, 1, 3]), Some(5.0));
+        assert!(view.broadcast_to(&[3, 3]).is_err());
+        assert!(view.slice(&[0..3, 0..3]).is_err());
+        assert!(TensorView::with_strides(&tensor.data, 1, &[2, 3], &[3, 1]).is_err());
+    }
+
+    #[test]
+    fn test_logic_ops_on_strided_views() {
+        let a = Tensor::new(vec![2, 2], vec![0.1, 0.2, 0.3, 0.4]);
+        let b = Tensor::new(vec![2, 2], vec![0.9, 0.8, 0.7, 0.6]);
+        let a_t = a.transpose(0, 1).unwrap();
+        let a_view = a.view().transpose(0, 1).unwrap();
+
+        assert_eq!(view_and(&a_view, &b.view()).unwrap().data, tensor_and(&a_t, &b).unwrap().data);
+        assert_eq!(view_or(&a_view, &b.view()).unwrap().data, tensor_or(&a_t, &b).unwrap().data);
+        assert_eq!(view_implies(&a_view, &b.view()).unwrap().data, tensor_implies(&a_t, &b).unwrap().data);
+        assert_eq!(view_not(&a_view).data, a_t.data.iter().map(|x| 1.0 - x).collect::<Vec<_>>());
+
+        let row = b.view().slice(&[1..2, 0..2]).unwrap();
+        assert_eq!(view_and(&a.view(), &row).unwrap().data, tensor_and(&a, &Tensor::new(vec![1, 2], vec![0.7, 0.6])).unwrap().data);
+
+        // Foreign memory is read in place
+        let raw = unsafe { TensorView::from_raw_parts(b.data.as_ptr(), &[2], &[2]) };
+        assert_eq!(raw.to_tensor().data, vec![0.9, 0.7]);
+    }
+}
@@ -0,0 +1,328 @@
+//! Tokenizer Module - Text to token id conversion
+//!
+//! Two tokenizers are provided: a word-level whitespace tokenizer with a trainable
+//! vocabulary, and a byte-level BPE tokenizer whose merges are learned from a corpus.
+//! The byte-level tokenizer accepts any input (unknown text falls back to raw bytes),
+//! which makes it the default for the neural engine.
+
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+
+/// Conversion between text and token ids
+pub trait Tokenizer: Send + Sync {
+    /// Split text into token ids
+    fn encode(&self, text: &str) -> Vec<u32>;
+
+    /// Reassemble text from token ids
+    fn decode(&self, ids: &[u32]) -> String;
+
+    /// Number of distinct token ids
+    fn vocab_size(&self) -> usize;
+}
+
+/// Word-level tokenizer over whitespace-separated, lowercased words
+///
+/// Id 0 is reserved for words outside the vocabulary.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhitespaceTokenizer {
+    words: Vec<String>,
+    #[serde(skip)]
+    index: HashMap<String, u32>,
+}
+
+impl WhitespaceTokenizer {
+    /// Token used for out-of-vocabulary words
+    pub const UNKNOWN: &'static str = "<unk>";
+
+    /// Learn a vocabulary of at most `max_vocab` entries (including `<unk>`) from a corpus
+    pub fn train(corpus: &[&str], max_vocab: usize) -> Self {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for text in corpus {
+            for word in text.split_whitespace() {
+                *counts.entry(word.to_lowercase()).or_insert(0) += 1;
+            }
+        }
+
+        // Most frequent first; ties broken alphabetically for reproducibility
+        let mut ranked: Vec<(String, usize)> = counts.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        let mut words = vec![Self::UNKNOWN.to_string()];
+        words.extend(ranked.into_iter().take(max_vocab.saturating_sub(1)).map(|(word, _)| word));
+        Self::from_words(words)
+    }
+
+    fn from_words(words: Vec<String>) -> Self {
+        let index = words.iter().enumerate().map(|(i, word)| (word.clone(), i as u32)).collect();
+        Self { words, index }
+    }
+
+    fn rebuild_index(&mut self) {
+        if self.index.len() != self.words.len() {
+            *self = Self::from_words(std::mem::take(&mut self.words));
+        }
+    }
+}
+
+impl Tokenizer for WhitespaceTokenizer {
+    fn encode(&self, text: &str) -> Vec<u32> {
+        text.split_whitespace()
+            .map(|word| self.index.get(&word.to_lowercase()).copied().unwrap_or(0))
+            .collect()
+    }
+
+    fn decode(&self, ids: &[u32]) -> String {
+        ids.iter()
+            .map(|&id| self.words.get(id as usize).map_or(Self::UNKNOWN, String::as_str))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn vocab_size(&self) -> usize {
+        self.words.len()
+    }
+}
+
+/// Byte-level byte-pair-encoding tokenizer
+///
+/// Ids 0..256 are raw bytes; each learned merge adds one id. Text is pre-split into
+/// words (a word keeps its leading whitespace) and merges never cross word boundaries.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BpeTokenizer {
+    merges: Vec<(u32, u32)>,
+    #[serde(skip)]
+    ranks: HashMap<(u32, u32), u32>,
+}
+
+impl BpeTokenizer {
+    /// Number of ids reserved for raw bytes
+    pub const BYTE_TOKENS: usize = 256;
+
+    /// Tokenizer with no merges, i.e. plain byte encoding
+    pub fn byte_level() -> Self {
+        Self::default()
+    }
+
+    /// Learn merges from a corpus until the vocabulary reaches `vocab_size`
+    ///
+    /// Training stops early once no pair occurs at least twice.
+    pub fn train(corpus: &[&str], vocab_size: usize) -> Self {
+        let mut word_counts: HashMap<&str, usize> = HashMap::new();
+        for text in corpus {
+            for word in pre_tokenize(text) {
+                *word_counts.entry(word).or_insert(0) += 1;
+            }
+        }
+        let mut words: Vec<(Vec<u32>, usize)> = word_counts.into_iter()
+            .map(|(word, count)| (word.bytes().map(u32::from).collect(), count))
+            .collect();
+        words.sort();
+
+        let mut merges = Vec::new();
+        while Self::BYTE_TOKENS + merges.len() < vocab_size {
+            let mut pair_counts: HashMap<(u32, u32), usize> = HashMap::new();
+            for (ids, count) in &words {
+                for pair in ids.windows(2) {
+                    *pair_counts.entry((pair[0], pair[1])).or_insert(0) += count;
+                }
+            }
+
+            // Most frequent pair; ties broken by the smaller pair for reproducibility
+            let best = pair_counts.into_iter()
+                .filter(|&(_, count)| count >= 2)
+                .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)));
+            let Some((pair, _)) = best else { break };
+
+            let merged = (Self::BYTE_TOKENS + merges.len()) as u32;
+            for (ids, _) in &mut words {
+                merge_pair(ids, pair, merged);
+            }
+            merges.push(pair);
+        }
+
+        Self::from_merges(merges)
+    }
+
+    fn from_merges(merges: Vec<(u32, u32)>) -> Self {
+        let ranks = merges.iter().enumerate().map(|(rank, &pair)| (pair, rank as u32)).collect();
+        Self { merges, ranks }
+    }
+
+    fn rebuild_ranks(&mut self) {
+        if self.ranks.len() != self.merges.len() {
+            *self = Self::from_merges(std::mem::take(&mut self.merges));
+        }
+    }
+
+    /// Bytes represented by a token id
+    fn token_bytes(&self, id: u32, out: &mut Vec<u8>) {
+        match id.checked_sub(Self::BYTE_TOKENS as u32) {
+            None => out.push(id as u8),
+            Some(merge) => match self.merges.get(merge as usize) {
+                Some(&(left, right)) => {
+                    self.token_bytes(left, out);
+                    self.token_bytes(right, out);
+                }
+                None => out.extend_from_slice("\u{FFFD}".as_bytes()),
+            },
+        }
+    }
+}
+
+impl Tokenizer for BpeTokenizer {
+    fn encode(&self, text: &str) -> Vec<u32> {
+        let mut tokens = Vec::with_capacity(text.len());
+        for word in pre_tokenize(text) {
+            let mut ids: Vec<u32> = word.bytes().map(u32::from).collect();
+            loop {
+                // Apply the earliest-learned merge present in the word
+                let best = ids.windows(2)
+                    .filter_map(|pair| self.ranks.get(&(pair[0], pair[1])).map(|&rank| (rank, (pair[0], pair[1]))))
+                    .min();
+                let Some((rank, pair)) = best else { break };
+                merge_pair(&mut ids, pair, Self::BYTE_TOKENS as u32 + rank);
+            }
+            tokens.extend(ids);
+        }
+        tokens
+    }
+
+    fn decode(&self, ids: &[u32]) -> String {
+        let mut bytes = Vec::new();
+        for &id in ids {
+            self.token_bytes(id, &mut bytes);
+        }
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+
+    fn vocab_size(&self) -> usize {
+        Self::BYTE_TOKENS + self.merges.len()
+    }
+}
+
+/// Replace every non-overlapping occurrence of `pair` with `merged`
+fn merge_pair(ids: &mut Vec<u32>, pair: (u32, u32), merged: u32) {
+    let mut write = 0;
+    let mut read = 0;
+    while read < ids.len() {
+        if read + 1 < ids.len() && (ids[read], ids[read + 1]) == pair {
+            ids[write] = merged;
+            read += 2;
+        } else {
+            ids[write] = ids[read];
+            read += 1;
+        }
+        write += 1;
+    }
+    ids.truncate(write);
+}
+
+/// Split text into words, each keeping its leading whitespace
+fn pre_tokenize(text: &str) -> Vec<&str> {
+    let mut start = 0;
+    let mut previous_whitespace = true;
+    let mut words = Vec::new();
+    for (i, c) in text.char_indices() {
+        let whitespace = c.is_whitespace();
+        if whitespace && !previous_whitespace {
+            words.push(&text[start..i]);
+            start = i;
+        }
+        previous_whitespace = whitespace;
+    }
+    if start < text.len() {
+        words.push(&text[start..]);
+    }
+    words
+}
+
+/// Tokenizer selection used by the neural engine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum TextTokenizer {
+    Whitespace(WhitespaceTokenizer),
+    Bpe(BpeTokenizer),
+}
+
+impl Default for TextTokenizer {
+    fn default() -> Self {
+        Self::Bpe(BpeTokenizer::byte_level())
+    }
+}
+
+impl TextTokenizer {
+    /// Restore lookup tables that are not persisted
+    pub fn rebuild(&mut self) {
+        match self {
+            Self::Whitespace(tokenizer) => tokenizer.rebuild_index(),
+            Self::Bpe(tokenizer) => tokenizer.rebuild_ranks(),
+        }
+    }
+}
+
+impl Tokenizer for TextTokenizer {
+    fn encode(&self, text: &str) -> Vec<u32> {
+        match self {
+            Self::Whitespace(tokenizer) => tokenizer.encode(text),
+            Self::Bpe(tokenizer) => tokenizer.encode(text),
+        }
+    }
+
+    fn decode(&self, ids: &[u32]) -> String {
+        match self {
+            Self::Whitespace(tokenizer) => tokenizer.decode(ids),
+            Self::Bpe(tokenizer) => tokenizer.decode(ids),
+        }
+    }
+
+    fn vocab_size(&self) -> usize {
+        match self {
+            Self::Whitespace(tokenizer) => tokenizer.vocab_size(),
+            Self::Bpe(tokenizer) => tokenizer.vocab_size(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CORPUS: &[&str] = &[
+        "the neural network learns the pattern",
+        "the quantum network entangles the qubits",
+        "a network of networks",
+    ];
+
+    #[test]
+    fn test_whitespace_vocabulary() {
+        let tokenizer = WhitespaceTokenizer::train(CORPUS, 4);
+        assert_eq!(tokenizer.vocab_size(), 4);
+
+        let ids = tokenizer.encode("The network UNSEEN");
+        assert_eq!(ids.len(), 3);
+        assert_ne!(ids[0], 0);
+        assert_eq!(ids[2], 0);
+        assert_eq!(tokenizer.decode(&ids), "the network <unk>");
+    }
+
+    #[test]
+    fn test_bpe_roundtrip_and_compression() {
+        let tokenizer = BpeTokenizer::train(CORPUS, 300);
+        assert!(tokenizer.vocab_size() > BpeTokenizer::BYTE_TOKENS);
+
+        let text = "the network learns  über qubits";
+        let ids = tokenizer.encode(text);
+        assert_eq!(tokenizer.decode(&ids), text);
+        assert!(ids.len() < text.len());
+
+        let bytes = BpeTokenizer::byte_level();
+        assert_eq!(bytes.encode("abc"), vec![97, 98, 99]);
+    }
+
+    #[test]
+    fn test_bpe_serde_restores_ranks() {
+        let tokenizer = TextTokenizer::Bpe(BpeTokenizer::train(CORPUS, 300));
+        let mut restored: TextTokenizer = bincode::deserialize(&bincode::serialize(&tokenizer).unwrap()).unwrap();
+        restored.rebuild();
+        assert_eq!(restored.encode("the network"), tokenizer.encode("the network"));
+    }
+}
@@ -12,6 +12,12 @@ pub struct AGIWasm {
     is_initialized: bool,
 }
 
+impl Default for AGIWasm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[wasm_bindgen]
 impl AGIWasm {
     /// Create a new WebAssembly AGI instance
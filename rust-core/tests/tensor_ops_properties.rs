@@ -0,0 +1,141 @@
+//! Property-based invariants for tensor_ops
+//!
+//! Each property is checked against randomly generated shapes and values, so the
+//! contraction, broadcasting and logic paths are exercised well beyond the fixed
+//! examples in the unit tests.
+
+use agi_rust_core::tensor_ops::{
+    broadcast_shape, broadcast_zip, einsum, einstein_summation, tensor_add, tensor_nand, tensor_nor,
+    tensor_not, tensor_sub, LogicSemantics, Tensor,
+};
+use proptest::prelude::*;
+
+const SEMANTICS: [LogicSemantics; 3] = [LogicSemantics::Product, LogicSemantics::Godel, LogicSemantics::Lukasiewicz];
+
+/// Tensor of `shape` with elements drawn from `values`
+fn tensor_of(shape: Vec<usize>, values: impl Strategy<Value = f64>) -> impl Strategy<Value = Tensor> {
+    let size = shape.iter().product::<usize>();
+    prop::collection::vec(values, size).prop_map(move |data| Tensor::new(shape.clone(), data))
+}
+
+/// Truth-valued tensor of `shape`
+fn truth(shape: Vec<usize>) -> impl Strategy<Value = Tensor> {
+    tensor_of(shape, 0.0..=1.0)
+}
+
+fn shape(max_rank: usize) -> impl Strategy<Value = Vec<usize>> {
+    prop::collection::vec(1usize..4, 0..=max_rank)
+}
+
+/// Two shapes that broadcast: the second drops leading axes and sets some to one
+fn broadcastable_shapes() -> impl Strategy<Value = (Vec<usize>, Vec<usize>)> {
+    shape(4).prop_flat_map(|full| {
+        let rank = full.len();
+        (Just(full), 0..=rank, prop::collection::vec(any::<bool>(), rank)).prop_map(|(full, dropped, ones)| {
+            let partial = full[dropped..].iter().zip(&ones[dropped..]).map(|(&d, &one)| if one { 1 } else { d }).collect();
+            (full, partial)
+        })
+    })
+}
+
+fn assert_close(actual: &[f64], expected: &[f64]) -> Result<(), TestCaseError> {
+    prop_assert_eq!(actual.len(), expected.len());
+    for (a, e) in actual.iter().zip(expected) {
+        prop_assert!((a - e).abs() <= 1e-9 * (1.0 + e.abs()), "{} != {}", a, e);
+    }
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn de_morgan_holds_under_every_semantics(a in 0.0..=1.0f64, b in 0.0..=1.0f64) {
+        for semantics in SEMANTICS {
+            let not_and = semantics.not(semantics.and(a, b));
+            let or_not = semantics.or(semantics.not(a), semantics.not(b));
+            prop_assert!((not_and - or_not).abs() < 1e-12, "{:?}: {} vs {}", semantics, not_and, or_not);
+
+            let not_or = semantics.not(semantics.or(a, b));
+            let and_not = semantics.and(semantics.not(a), semantics.not(b));
+            prop_assert!((not_or - and_not).abs() < 1e-12, "{:?}: {} vs {}", semantics, not_or, and_not);
+        }
+    }
+
+    #[test]
+    fn tensor_de_morgan_matches_elementwise(
+        (a, b) in shape(3).prop_flat_map(|s| (truth(s.clone()), truth(s))),
+    ) {
+        let (not_a, not_b) = (tensor_not(&a), tensor_not(&b));
+        for semantics in SEMANTICS {
+            let nand = tensor_nand(&a, &b, semantics).unwrap();
+            let or_of_nots = broadcast_zip(&not_a, &not_b, |x, y| semantics.or(x, y)).unwrap();
+            assert_close(&nand.data, &or_of_nots.data)?;
+
+            let nor = tensor_nor(&a, &b, semantics).unwrap();
+            let and_of_nots = broadcast_zip(&not_a, &not_b, |x, y| semantics.and(x, y)).unwrap();
+            assert_close(&nor.data, &and_of_nots.data)?;
+        }
+    }
+
+    #[test]
+    fn einsum_matmul_matches_naive(
+        (a, b) in (1usize..6, 1usize..6, 1usize..6).prop_flat_map(|(i, j, k)| {
+            (tensor_of(vec![i, j], -10.0..10.0), tensor_of(vec![j, k], -10.0..10.0))
+        }),
+    ) {
+        let (i, j, k) = (a.shape[0], a.shape[1], b.shape[1]);
+        let mut expected = vec![0.0; i * k];
+        for row in 0..i {
+            for col in 0..k {
+                expected[row * k + col] = (0..j).map(|m| a.data[row * j + m] * b.data[m * k + col]).sum();
+            }
+        }
+        let product = einsum("ij,jk->ik", &[&a, &b]).unwrap();
+        prop_assert_eq!(&product.shape, &vec![i, k]);
+        assert_close(&product.data, &expected)?;
+
+        // Implicit output and integer labels describe the same contraction
+        assert_close(&einsum("ij,jk", &[&a, &b]).unwrap().data, &expected)?;
+        assert_close(&einstein_summation(&a, &b, &[0, 1], &[1, 2], &[0, 2]).unwrap().data, &expected)?;
+    }
+
+    #[test]
+    fn einsum_trace_and_transpose_match_naive(
+        (square, n) in (1usize..6).prop_flat_map(|n| (tensor_of(vec![n, n], -10.0..10.0), Just(n))),
+    ) {
+        let trace: f64 = (0..n).map(|i| square.data[i * n + i]).sum();
+        assert_close(&einsum("ii", &[&square]).unwrap().data, &[trace])?;
+
+        let transposed: Vec<f64> = (0..n * n).map(|p| square.data[(p % n) * n + p / n]).collect();
+        assert_close(&einsum("ij->ji", &[&square]).unwrap().data, &transposed)?;
+        assert_close(&square.transpose(0, 1).unwrap().data, &transposed)?;
+    }
+
+    #[test]
+    fn broadcasting_round_trips(
+        (a, b) in broadcastable_shapes().prop_flat_map(|(full, partial)| {
+            (tensor_of(full, -100.0..100.0), tensor_of(partial, -100.0..100.0))
+        }),
+    ) {
+        prop_assert_eq!(broadcast_shape(&a.shape, &b.shape).unwrap(), a.shape.clone());
+        prop_assert_eq!(broadcast_shape(&b.shape, &a.shape).unwrap(), a.shape.clone());
+
+        // Adding then subtracting the broadcast operand recovers the original
+        let restored = tensor_sub(&tensor_add(&a, &b).unwrap(), &b).unwrap();
+        assert_close(&restored.data, &a.data)?;
+
+        // Broadcasting `b` against zeros materializes it; doing so twice changes nothing
+        let zeros = Tensor::zeros(&a.shape);
+        let expanded = tensor_add(&zeros, &b).unwrap();
+        prop_assert_eq!(&tensor_add(&zeros, &expanded).unwrap().data, &expanded.data);
+    }
+
+    #[test]
+    fn reshape_and_permute_round_trip(tensor in shape(4).prop_flat_map(|s| tensor_of(s, -1.0..1.0))) {
+        let flat = tensor.flatten();
+        prop_assert_eq!(&flat.reshape(&tensor.shape).unwrap().data, &tensor.data);
+
+        let axes: Vec<usize> = (0..tensor.rank).rev().collect();
+        let reversed = tensor.permute(&axes).unwrap();
+        prop_assert_eq!(&reversed.permute(&axes).unwrap().data, &tensor.data);
+    }
+}